@@ -0,0 +1,71 @@
+// Parser microbenchmarks
+// Run with `cargo bench --bench parser`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::json;
+use tiktrend_finder_lib::{NumberLocale, TikTokParser};
+
+fn bench_price_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_price_text");
+    for (name, text, locale) in [
+        ("br", "R$ 1.234,56", NumberLocale::Br),
+        ("us", "$1,234.56", NumberLocale::Us),
+        ("eu", "1 234,56 €", NumberLocale::Eu),
+        (
+            "noisy",
+            "De R$ 12.345,67 por apenas R$ 9.999,00 à vista",
+            NumberLocale::Br,
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| TikTokParser::parse_price_text(black_box(text), locale))
+        });
+    }
+    group.finish();
+}
+
+fn bench_sales_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_sales_text");
+    for (name, text) in [
+        ("plain", "1.234 vendidos"),
+        ("suffix", "2,3 mil vendidos"),
+        ("range", "1-5 mil"),
+        ("english", "10k+ sold"),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| TikTokParser::parse_sales_text(black_box(text)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_product_json(c: &mut Criterion) {
+    let parser = TikTokParser::new(None);
+    let product = json!({
+        "id": "1729384756",
+        "title": "Fone de Ouvido Bluetooth TWS com Cancelamento de Ruído",
+        "price": { "value": 89.9, "currency": "BRL" },
+        "originalPrice": "R$ 159,90",
+        "sold_count": "2,3 mil vendidos",
+        "rating": 4.8,
+        "reviews_count": 1532,
+        "seller": { "name": "Loja Oficial", "rating": 4.9 },
+        "images": ["https://example.com/1.jpg", "https://example.com/2.jpg"],
+        "skus": [
+            { "sku_id": "1", "name": "Preto", "price": "89,90", "stock": 120 },
+            { "sku_id": "2", "name": "Branco", "price": "94,90", "stock": 0 }
+        ]
+    });
+
+    c.bench_function("parse_product_json", |b| {
+        b.iter(|| parser.parse_product_json(black_box(&product)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_price_text,
+    bench_sales_text,
+    bench_product_json
+);
+criterion_main!(benches);
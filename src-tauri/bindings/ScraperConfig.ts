@@ -1,3 +0,0 @@
-// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
-
-export type ScraperConfig = { headless: boolean, max_concurrent_browsers: number, request_timeout_ms: bigint, page_load_timeout_ms: bigint, min_delay_ms: bigint, max_delay_ms: bigint, max_retries: number, use_proxy: boolean, proxies: Array<string>, categories: Array<string>, max_products: number, user_data_path: string | null, db_path: string | null, selectors: Array<string> | null, safety_switch_enabled: boolean, max_detection_rate: number, safety_cooldown_seconds: bigint, consecutive_failures_threshold: number, api_key: string | null, api_secret: string | null, };
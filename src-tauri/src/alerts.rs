@@ -0,0 +1,155 @@
+// Alert Engine
+// Compares a product before and after a refresh and raises alerts for the
+// changes worth a notification: price moves, stock changes and sales spikes
+
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+use crate::database;
+use crate::format::NumberFormat;
+use crate::models::{AlertKind, Product, ProductAlert};
+use crate::scraper::Language;
+use crate::whatsapp;
+
+/// Price moves smaller than this (in percent) are not reported
+pub const DEFAULT_PRICE_CHANGE_PERCENT: f64 = 5.0;
+
+/// Sales have to grow this much (percent, and at least SALES_SPIKE_MIN units)
+/// between two checks to count as a spike
+const SALES_SPIKE_PERCENT: f64 = 50.0;
+const SALES_SPIKE_MIN: i32 = 20;
+
+fn alert(
+    product: &Product,
+    kind: AlertKind,
+    message: String,
+    old: Option<f64>,
+    new: Option<f64>,
+) -> ProductAlert {
+    ProductAlert {
+        id: Uuid::new_v4().to_string(),
+        product_id: product.id.clone(),
+        kind,
+        message,
+        old_value: old,
+        new_value: new,
+        is_read: false,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+fn short_title(product: &Product) -> String {
+    product.title.chars().take(40).collect()
+}
+
+/// The product's price as the (Portuguese) alert texts show it
+fn price(product: &Product) -> String {
+    NumberFormat::new(Language::PtBr, &product.currency).price(product.price)
+}
+
+/// Alerts for the differences between two snapshots of the same product
+pub fn detect_changes(
+    old: &Product,
+    new: &Product,
+    price_change_percent: f64,
+) -> Vec<ProductAlert> {
+    let mut alerts = Vec::new();
+
+    // A missing price (0) is a failed read, not a change
+    if old.price > 0.0 && new.price > 0.0 {
+        let change = (new.price - old.price) / old.price * 100.0;
+        if change.abs() >= price_change_percent {
+            let (kind, verb) = if change < 0.0 {
+                (AlertKind::PriceDrop, "caiu")
+            } else {
+                (AlertKind::PriceRise, "subiu")
+            };
+            alerts.push(alert(
+                new,
+                kind,
+                format!(
+                    "Preço de {} {} {:.0}%: {} → {}",
+                    short_title(new),
+                    verb,
+                    change.abs(),
+                    price(old),
+                    price(new)
+                ),
+                Some(old.price),
+                Some(new.price),
+            ));
+        }
+    }
+
+    if old.in_stock && !new.in_stock {
+        alerts.push(alert(
+            new,
+            AlertKind::OutOfStock,
+            format!("{} esgotou", short_title(new)),
+            None,
+            None,
+        ));
+    } else if !old.in_stock && new.in_stock {
+        alerts.push(alert(
+            new,
+            AlertKind::BackInStock,
+            format!("{} voltou ao estoque", short_title(new)),
+            None,
+            None,
+        ));
+    }
+
+    let sold = new.sales_count - old.sales_count;
+    if old.sales_count > 0
+        && sold >= SALES_SPIKE_MIN
+        && sold as f64 / old.sales_count as f64 * 100.0 >= SALES_SPIKE_PERCENT
+    {
+        alerts.push(alert(
+            new,
+            AlertKind::SalesSpike,
+            format!(
+                "{} vendeu +{} desde a última verificação",
+                short_title(new),
+                sold
+            ),
+            Some(old.sales_count as f64),
+            Some(new.sales_count as f64),
+        ));
+    }
+
+    alerts
+}
+
+/// A competitor store listing a product for the first time
+pub fn new_competitor_product(product: &Product, competitor_name: &str) -> ProductAlert {
+    alert(
+        product,
+        AlertKind::NewProduct,
+        format!(
+            "{} lançou um novo produto: {} ({})",
+            competitor_name,
+            short_title(product),
+            price(product)
+        ),
+        None,
+        Some(product.price),
+    )
+}
+
+/// Store alerts and notify the frontend with a `product-alert` event each,
+/// and WhatsApp with one message for all of them when it is set up
+pub fn raise(app: Option<&AppHandle>, db_path: &Path, alerts: &[ProductAlert]) {
+    for alert in alerts {
+        log::info!("Alert: {}", alert.message);
+        if let Err(e) = database::add_product_alert(db_path, alert) {
+            log::warn!("Failed to save alert: {}", e);
+        }
+        if let Some(app) = app {
+            let _ = app.emit("product-alert", alert);
+        }
+    }
+    if let Some(app) = app {
+        whatsapp::notify_alerts(app, alerts);
+    }
+}
@@ -0,0 +1,210 @@
+// Backend Client
+// Every call to the backend goes through one pooled HTTP client with the same
+// timeouts, and through a circuit breaker: after a few failures in a row the
+// backend is taken as down and calls fail at once for a while, instead of each
+// one waiting out its own timeout. The health probe closes it again as soon as
+// the backend answers. Transient failures are retried a few times first,
+// with jittered exponential backoff.
+
+use rand::Rng;
+use reqwest::header::HeaderValue;
+use reqwest::{Client, Method, Request, RequestBuilder, Response, StatusCode};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::commands::API_URL;
+
+/// Time allowed to open a connection to the backend
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time allowed to a whole request, unless the call sets its own
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Failures in a row that open the breaker
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// How long calls are short-circuited once the breaker opens
+const OPEN_DURATION: Duration = Duration::from_secs(30);
+
+/// Retries of a call that failed on a transient error
+const MAX_RETRIES: u32 = 2;
+
+/// Delay before the first retry; doubles with each one
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Header the backend uses to recognise a repeated POST
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .pool_idle_timeout(Duration::from_secs(90))
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+static BREAKER: Mutex<Breaker> = Mutex::new(Breaker {
+    failures: 0,
+    open_until: None,
+});
+
+fn breaker() -> std::sync::MutexGuard<'static, Breaker> {
+    BREAKER.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Time left before calls go through again, while the breaker is open
+pub fn open_for() -> Option<Duration> {
+    breaker()
+        .open_until
+        .and_then(|until| until.checked_duration_since(Instant::now()))
+        .filter(|left| !left.is_zero())
+}
+
+fn record(ok: bool) {
+    let mut breaker = breaker();
+    if ok {
+        if breaker.open_until.is_some() {
+            log::info!("Backend reachable again");
+        }
+        *breaker = Breaker::default();
+        return;
+    }
+
+    breaker.failures += 1;
+    // Once past the threshold, each failed attempt after the pause reopens it
+    if breaker.failures >= FAILURE_THRESHOLD {
+        if breaker.open_until.is_none() {
+            log::warn!(
+                "Backend failed {} times in a row, pausing calls for {}s",
+                breaker.failures,
+                OPEN_DURATION.as_secs()
+            );
+        }
+        breaker.open_until = Some(Instant::now() + OPEN_DURATION);
+    }
+}
+
+pub fn get(path: &str) -> RequestBuilder {
+    CLIENT.get(format!("{}{}", API_URL, path))
+}
+
+pub fn post(path: &str) -> RequestBuilder {
+    CLIENT.post(format!("{}{}", API_URL, path))
+}
+
+/// Send a request, recording the outcome in the breaker
+async fn execute(client: &Client, request: Request) -> Result<Response, reqwest::Error> {
+    match client.execute(request).await {
+        Ok(response) => {
+            // A gateway error or a crash means the backend is down all the same
+            record(!response.status().is_server_error());
+            Ok(response)
+        }
+        Err(e) => {
+            record(false);
+            Err(e)
+        }
+    }
+}
+
+/// Whether trying again may get another answer: the connection failed or
+/// timed out, or a proxy in front of the backend couldn't reach it
+fn transient(result: &Result<Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+        ),
+        Err(e) => e.is_connect() || e.is_timeout(),
+    }
+}
+
+/// Delay before retry number `retry` (from 1): doubles each time, with up to
+/// half of it again added at random so clients don't retry in step
+fn backoff(retry: u32) -> Duration {
+    let base = RETRY_BASE_DELAY * 2u32.pow(retry.saturating_sub(1).min(6));
+    let jitter = rand::thread_rng().gen_range(0..=base.as_millis() as u64 / 2);
+    base + Duration::from_millis(jitter)
+}
+
+fn unavailable(left: Duration) -> String {
+    format!(
+        "Backend unavailable, retrying in {}s",
+        left.as_secs().max(1)
+    )
+}
+
+/// Send a request built with `get` or `post`, retrying transient failures
+/// `MAX_RETRIES` times
+pub async fn send(request: RequestBuilder) -> Result<Response, String> {
+    send_retrying(request, MAX_RETRIES).await
+}
+
+/// Send a request, retrying transient failures up to `max_retries` times with
+/// jittered exponential backoff. Fails without calling the backend while the
+/// breaker is open. POSTs carry an idempotency key, the same on every
+/// attempt, so the backend can drop the repeat of a request that went through.
+pub async fn send_retrying(request: RequestBuilder, max_retries: u32) -> Result<Response, String> {
+    let (client, request) = request.build_split();
+    let mut request = request.map_err(|e| e.to_string())?;
+    if request.method() == Method::POST {
+        if let Ok(key) = HeaderValue::from_str(&Uuid::new_v4().to_string()) {
+            request.headers_mut().insert(IDEMPOTENCY_KEY, key);
+        }
+    }
+    let target = format!("{} {}", request.method(), request.url().path());
+
+    let mut retry = 0;
+    loop {
+        if let Some(left) = open_for() {
+            return Err(unavailable(left));
+        }
+        // Streamed bodies can't be sent twice
+        let Some(attempt) = request.try_clone() else {
+            return execute(&client, request).await.map_err(|e| e.to_string());
+        };
+
+        let result = execute(&client, attempt).await;
+        let retryable = transient(&result);
+        if !retryable || retry >= max_retries {
+            if retry > 0 && !retryable {
+                log::info!("{} went through after {} retries", target, retry);
+            } else if retry > 0 {
+                log::warn!("{} still failing after {} retries", target, retry);
+            }
+            return result.map_err(|e| e.to_string());
+        }
+
+        retry += 1;
+        let delay = backoff(retry);
+        log::warn!(
+            "{} failed ({}), retry {}/{} in {}ms",
+            target,
+            match &result {
+                Ok(response) => response.status().to_string(),
+                Err(e) => e.to_string(),
+            },
+            retry,
+            max_retries,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Send a request once, even while the breaker is open, so a probe can find
+/// out the backend is back
+pub async fn probe(request: RequestBuilder) -> Result<Response, String> {
+    let (client, request) = request.build_split();
+    execute(&client, request.map_err(|e| e.to_string())?)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,134 @@
+// Changelog
+// Release notes of the updater channel, fetched from the backend and cached
+// per channel so the "what's new" screen works offline. After an update the
+// UI lists the releases since the version the user last saw.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::Path;
+
+use crate::backend;
+use crate::database;
+use crate::models::{Changelog, ReleaseNote};
+
+/// Settings key of the last version whose notes were shown
+const SEEN_VERSION_KEY: &str = "changelog_seen_version";
+
+/// Cached notes of a channel
+#[derive(Serialize, Deserialize)]
+struct CachedNotes {
+    fetched_at: String,
+    releases: Vec<ReleaseNote>,
+}
+
+fn cache_key(channel: &str) -> String {
+    format!("changelog_{}", channel)
+}
+
+/// Compare dotted versions numerically, ignoring a leading "v" and any
+/// pre-release suffix
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let x = a.get(i).copied().unwrap_or(0);
+            let y = b.get(i).copied().unwrap_or(0);
+            x.cmp(&y)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+async fn fetch(channel: &str) -> Result<Vec<ReleaseNote>, String> {
+    let response = backend::send(
+        backend::get("/releases").query(&[("channel", channel), ("os", std::env::consts::OS)]),
+    )
+    .await
+    .map_err(|e| format!("Failed to reach the server: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Release notes request failed: {}",
+            response.status()
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse API response: {}", e))
+}
+
+/// Notes of the channel: fetched and cached when the backend answers, the
+/// cached ones otherwise
+async fn notes(db_path: &Path, channel: &str) -> Result<Option<CachedNotes>, String> {
+    match fetch(channel).await {
+        Ok(releases) => {
+            let cached = CachedNotes {
+                fetched_at: Utc::now().to_rfc3339(),
+                releases,
+            };
+            let json = serde_json::to_string(&cached).map_err(|e| e.to_string())?;
+            database::set_setting(db_path, &cache_key(channel), &json)
+                .map_err(|e| format!("Database error: {}", e))?;
+            Ok(Some(cached))
+        }
+        Err(e) => {
+            log::warn!("Using cached release notes: {}", e);
+            Ok(database::get_setting(db_path, &cache_key(channel))
+                .map_err(|e| format!("Database error: {}", e))?
+                .and_then(|json| serde_json::from_str(&json).ok()))
+        }
+    }
+}
+
+/// Releases after `since_version` up to the running one, newest first.
+/// Without a version, the last one marked seen is used.
+pub async fn get(
+    db_path: &Path,
+    channel: &str,
+    since_version: Option<String>,
+) -> Result<Changelog, String> {
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+    let since_version = match since_version {
+        Some(version) => Some(version),
+        None => database::get_setting(db_path, SEEN_VERSION_KEY)
+            .map_err(|e| format!("Database error: {}", e))?,
+    };
+
+    let (fetched_at, mut releases) = match notes(db_path, channel).await? {
+        Some(cached) => (Some(cached.fetched_at), cached.releases),
+        None => (None, Vec::new()),
+    };
+    releases.retain(|release| {
+        let newer = since_version.as_deref().map_or(
+            compare_versions(&release.version, &current_version).is_eq(),
+            |since| compare_versions(&release.version, since).is_gt(),
+        );
+        newer && compare_versions(&release.version, &current_version).is_le()
+    });
+    releases.sort_by(|a, b| compare_versions(&b.version, &a.version));
+
+    Ok(Changelog {
+        channel: channel.to_string(),
+        current_version,
+        since_version,
+        releases,
+        fetched_at,
+    })
+}
+
+/// Remember that the notes of the running version were shown
+pub fn mark_seen(db_path: &Path) -> Result<(), String> {
+    database::set_setting(db_path, SEEN_VERSION_KEY, env!("CARGO_PKG_VERSION"))
+        .map_err(|e| format!("Database error: {}", e))
+}
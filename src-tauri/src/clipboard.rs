@@ -0,0 +1,129 @@
+// Clipboard Watcher
+// When enabled in the settings, polls the system clipboard for copied TikTok
+// Shop product links and emits each new one as `clipboard://product-link`,
+// so the app can offer to import it with `import_clipboard_product`. There is
+// no clipboard API in the backend: the text is read with the platform's
+// command line tool (pbpaste, PowerShell, wl-paste or xclip/xsel).
+
+use regex::Regex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::process::Command;
+
+use crate::commands;
+use crate::database;
+use crate::models::ClipboardProductLink;
+use crate::scraper::TikTokParser;
+
+/// Tag given to products imported from the clipboard
+pub const CLIPBOARD_TAG: &str = "clipboard import";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Programs that print the clipboard text, tried in order
+#[cfg(target_os = "macos")]
+const READERS: &[(&str, &[&str])] = &[("pbpaste", &[])];
+
+#[cfg(target_os = "windows")]
+const READERS: &[(&str, &[&str])] = &[(
+    "powershell",
+    &[
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        "Get-Clipboard -Raw",
+    ],
+)];
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const READERS: &[(&str, &[&str])] = &[
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+];
+
+async fn read_clipboard() -> Option<String> {
+    for (program, args) in READERS {
+        let mut command = Command::new(program);
+        command.args(*args).kill_on_drop(true);
+        // No console window flashing up on every poll
+        #[cfg(target_os = "windows")]
+        command.creation_flags(0x0800_0000);
+
+        match tokio::time::timeout(POLL_INTERVAL, command.output()).await {
+            Ok(Ok(output)) if output.status.success() => {
+                return Some(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            _ => continue,
+        }
+    }
+    None
+}
+
+/// TikTok Shop product links in `text` as (url, TikTok id), once per product
+fn product_links(text: &str) -> Vec<(String, String)> {
+    let Ok(url_re) = Regex::new(r#"https?://[^\s"'<>]+"#) else {
+        return Vec::new();
+    };
+
+    let mut links: Vec<(String, String)> = Vec::new();
+    for found in url_re.find_iter(text) {
+        let url = found.as_str().trim_end_matches(['.', ',', ';', ')']);
+        let host = url.split('/').nth(2).unwrap_or_default().to_lowercase();
+        if host != "tiktok.com" && !host.ends_with(".tiktok.com") {
+            continue;
+        }
+        let Some(tiktok_id) = TikTokParser::extract_id_from_url(url) else {
+            continue;
+        };
+        if !links.iter().any(|(_, id)| *id == tiktok_id) {
+            links.push((url.to_string(), tiktok_id));
+        }
+    }
+    links
+}
+
+/// Poll the clipboard for the lifetime of the app, while enabled in the
+/// settings. What was copied before the watcher was turned on isn't offered.
+pub fn spawn_clipboard_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        let mut last_text: Option<String> = None;
+        loop {
+            interval.tick().await;
+            let Ok(app_dir) = app.path().app_data_dir() else {
+                continue;
+            };
+            if !commands::load_settings(&app_dir).clipboard.enabled {
+                last_text = None;
+                continue;
+            }
+
+            let Some(text) = read_clipboard().await else {
+                continue;
+            };
+            if last_text.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+            let first_read = last_text.is_none();
+            last_text = Some(text.clone());
+            if first_read {
+                continue;
+            }
+
+            let db_path = app_dir.join("tiktrend.db");
+            for (url, tiktok_id) in product_links(&text) {
+                let product_id = database::get_product_by_tiktok_id(&db_path, &tiktok_id)
+                    .ok()
+                    .flatten()
+                    .map(|p| p.id);
+                let link = ClipboardProductLink {
+                    url,
+                    tiktok_id,
+                    product_id,
+                };
+                let _ = app.emit("clipboard://product-link", &link);
+            }
+        }
+    });
+}
@@ -1,21 +1,56 @@
 // Tauri commands - API for frontend
-use crate::config::{AppSettings, ScraperConfig};
+use crate::alerts;
+use crate::backend;
+use crate::changelog;
+use crate::clipboard;
+use crate::compare;
+use crate::companion;
+use crate::credits;
+use crate::crm;
+use crate::config::{AppSettings, ScraperConfig, SettingsExport, SettingsProfile};
 use crate::database;
+use crate::demo;
+use crate::digest;
+use crate::entitlements::Entitlements;
+use crate::file_import;
+use crate::format::NumberFormat;
+use crate::disk;
+use crate::health;
+use crate::jobs::{JobInfo, JobKind, JobStatus};
+use crate::license;
 use crate::models::*;
-use crate::scraper::TikTokScraper;
-use crate::ScraperState;
+use crate::scraper::affiliate::AffiliateCenter;
+use crate::related;
+use crate::remote_config;
+use crate::reports;
+use crate::saturation;
+use crate::scheduler;
+use crate::share;
+use crate::social;
+use crate::telemetry;
+use crate::trial;
+use crate::usage;
+use crate::watch;
+use crate::whatsapp;
+use crate::xlsx::{self, Cell};
+use crate::competitors;
+use crate::scraper::models::{ExtractionStrategy, SelectorManifest};
+use crate::scraper::stealth::StealthCheck;
+use crate::scraper::{self, AntiDetection, BrowserManager, NumberLocale, TikTokParser, TikTokScraper};
+use crate::{EntitlementState, InspectorState, JobState, ScraperState};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
 use std::fs;
-use sysinfo::{Disks, Networks, System};
-use tauri::{command, AppHandle, Manager, State};
+use sysinfo::{Networks, System};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tauri_plugin_shell::ShellExt;
 use ts_rs::TS;
 
-const API_URL: &str = "http://localhost:8000";
+pub(crate) const API_URL: &str = "http://localhost:8000";
 
-fn get_hardware_id() -> String {
+pub(crate) fn get_hardware_id() -> String {
     let mut sys = System::new_all();
     sys.refresh_all();
 
@@ -58,6 +93,101 @@ pub async fn search_products(
     Ok(result)
 }
 
+/// Price histogram of the products matching the filters (pagination and
+/// sorting are ignored)
+#[command]
+pub async fn get_price_distribution(
+    app: AppHandle,
+    filters: SearchFilters,
+    buckets: Option<u32>,
+) -> Result<PriceDistribution, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let buckets = buckets.unwrap_or(10).clamp(1, 100);
+    database::get_price_distribution(&db_path, &filters, buckets)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Most products the compare view lines up
+const MAX_COMPARED_PRODUCTS: usize = 6;
+
+/// Products side by side with aligned metrics and their deltas to the first
+/// one. Unknown ids are left out.
+#[command]
+pub async fn compare_products(
+    app: AppHandle,
+    ids: Vec<String>,
+) -> Result<ProductComparison, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    if ids.len() > MAX_COMPARED_PRODUCTS {
+        return Err(format!(
+            "At most {} products can be compared",
+            MAX_COMPARED_PRODUCTS
+        ));
+    }
+
+    let mut products = Vec::new();
+    for id in &ids {
+        let Some(product) =
+            database::get_product_by_id(&db_path, id).map_err(|e| format!("Database error: {}", e))?
+        else {
+            continue;
+        };
+        let history = database::get_product_history(&db_path, id)
+            .map_err(|e| format!("Database error: {}", e))?;
+        products.push((product, history));
+    }
+    if products.is_empty() {
+        return Err("No products to compare".to_string());
+    }
+
+    Ok(compare::compare(&products, Utc::now()))
+}
+
+/// Products adjacent to one being browsed: same category and shared title
+/// keywords, best match first
+#[command]
+pub async fn get_related_products(
+    app: AppHandle,
+    product_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<RelatedProduct>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let product = database::get_product_by_id(&db_path, &product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Product not found".to_string())?;
+
+    related::find(&db_path, &product, limit.unwrap_or(10).clamp(1, 50))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// A price in the app language, in `currency` or the display currency
+#[command]
+pub async fn format_price(
+    app: AppHandle,
+    value: f64,
+    currency: Option<String>,
+) -> Result<String, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let number_format = NumberFormat::from_settings(&load_settings(&app_dir));
+    Ok(match currency {
+        Some(currency) => number_format.price_in(value, &currency),
+        None => number_format.price(value),
+    })
+}
+
+/// Short form of a count in the app language, e.g. "1,2 mil"
+#[command]
+pub async fn format_compact_number(app: AppHandle, value: f64) -> Result<String, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(NumberFormat::from_settings(&load_settings(&app_dir)).compact(value))
+}
+
 /// Get paginated products
 #[command]
 pub async fn get_products(
@@ -74,24 +204,28 @@ pub async fn get_products(
     let db_path = app_dir.join("tiktrend.db");
 
     let filters = SearchFilters {
-        query: None,
-        categories: vec![],
-        price_min: None,
-        price_max: None,
-        sales_min: None,
-        rating_min: None,
-        has_free_shipping: None,
-        is_trending: None,
-        is_on_sale: None,
         sort_by: Some("collected_at".to_string()),
         sort_order: Some("DESC".to_string()),
         page: Some(page),
         page_size: Some(page_size),
+        ..SearchFilters::default()
     };
 
     database::search_products(&db_path, &filters).map_err(|e| format!("Database error: {}", e))
 }
 
+/// Get the raw JSON a product was parsed from (requires raw capture mode)
+#[command]
+pub async fn get_product_raw_payload(
+    app: AppHandle,
+    product_id: String,
+) -> Result<Option<RawPayload>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_raw_payload(&db_path, &product_id).map_err(|e| format!("Database error: {}", e))
+}
+
 /// Get single product by ID
 #[command]
 pub async fn get_product_by_id(app: AppHandle, id: String) -> Result<Option<Product>, String> {
@@ -103,415 +237,2769 @@ pub async fn get_product_by_id(app: AppHandle, id: String) -> Result<Option<Prod
     database::get_product_by_id(&db_path, &id).map_err(|e| format!("Database error: {}", e))
 }
 
-/// Add product to favorites
+/// Recategorize, tag, correct the currency of or archive many products at
+/// once; returns the number of products updated
 #[command]
-pub async fn add_favorite(
+pub async fn bulk_update_products(
     app: AppHandle,
-    product_id: String,
-    list_id: Option<String>,
-    notes: Option<String>,
-) -> Result<FavoriteItem, String> {
-    log::info!("Adding favorite: {} to list {:?}", product_id, list_id);
+    product_ids: Vec<String>,
+    patch: ProductPatch,
+) -> Result<usize, String> {
+    log::info!("Updating {} products", product_ids.len());
 
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    // Default user_id for desktop (single user)
-    let user_id = "default_user".to_string();
+    let currency = patch.currency.as_deref().map(currency_code).transpose()?;
+    let clean_tags = |tags: Vec<String>| -> Vec<String> {
+        tags.iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    };
+    let patch = ProductPatch {
+        category: patch.category.map(|c| c.trim().to_string()),
+        add_tags: clean_tags(patch.add_tags),
+        remove_tags: clean_tags(patch.remove_tags),
+        currency,
+        archived: patch.archived,
+    };
 
-    database::add_favorite(
-        &db_path,
-        &user_id,
-        &product_id,
-        list_id.as_deref(),
-        notes.as_deref(),
-    )
-    .map_err(|e| format!("Database error: {}", e))
+    database::bulk_update_products(&db_path, &product_ids, &patch)
+        .map_err(|e| format!("Database error: {}", e))
 }
 
-/// Remove product from favorites
-#[command]
-pub async fn remove_favorite(app: AppHandle, product_id: String) -> Result<bool, String> {
-    log::info!("Removing favorite: {}", product_id);
+/// ISO 4217 currency code, upper-cased
+fn currency_code(code: &str) -> Result<String, String> {
+    let code = code.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Invalid currency code: {}", code));
+    }
+    Ok(code)
+}
 
-    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let db_path = app_dir.join("tiktrend.db");
+/// Product without any values yet, for filling in from outside the scraper
+pub(crate) fn new_product(tiktok_id: String, source: ProductSource) -> Product {
+    let now = Utc::now().to_rfc3339();
+    Product {
+        id: uuid::Uuid::new_v4().to_string(),
+        tiktok_id,
+        title: String::new(),
+        description: None,
+        price: 0.0,
+        original_price: None,
+        currency: "BRL".to_string(),
+        category: None,
+        subcategory: None,
+        seller_name: None,
+        seller_rating: None,
+        product_rating: None,
+        reviews_count: 0,
+        sales_count: 0,
+        sales_7d: 0,
+        sales_30d: 0,
+        commission_rate: None,
+        open_collaboration: None,
+        image_url: None,
+        images: vec![],
+        video_url: None,
+        product_url: String::new(),
+        affiliate_url: None,
+        has_free_shipping: false,
+        is_trending: false,
+        is_on_sale: false,
+        in_stock: true,
+        stock_level: None,
+        min_price: None,
+        max_price: None,
+        variants: vec![],
+        shipping_fee: None,
+        delivery_days_min: None,
+        delivery_days_max: None,
+        demand_estimate: None,
+        collected_at: now.clone(),
+        updated_at: now,
+        raw_payload: None,
+        is_demo: false,
+        tags: vec![],
+        is_archived: false,
+        source,
+        source_file: None,
+        marketplace: MarketplaceAccess::Tiktok,
+    }
+}
 
-    let user_id = "default_user".to_string();
+/// Apply `fields` to `product` and check the result; an empty string clears
+/// an optional text
+pub(crate) fn apply_product_fields(
+    product: &mut Product,
+    fields: ProductFields,
+) -> Result<(), String> {
+    let text = |value: String| Some(value.trim().to_string()).filter(|v| !v.is_empty());
 
-    database::remove_favorite(&db_path, &user_id, &product_id)
-        .map_err(|e| format!("Database error: {}", e))
+    if let Some(title) = fields.title {
+        product.title = title.trim().to_string();
+    }
+    if let Some(description) = fields.description {
+        product.description = text(description);
+    }
+    if let Some(price) = fields.price {
+        product.price = price;
+    }
+    if let Some(original_price) = fields.original_price {
+        product.original_price = Some(original_price);
+    }
+    if let Some(currency) = fields.currency {
+        product.currency = currency_code(&currency)?;
+    }
+    if let Some(category) = fields.category {
+        product.category = text(category);
+    }
+    if let Some(seller_name) = fields.seller_name {
+        product.seller_name = text(seller_name);
+    }
+    if let Some(rating) = fields.product_rating {
+        product.product_rating = Some(rating);
+    }
+    if let Some(sales_count) = fields.sales_count {
+        product.sales_count = sales_count;
+    }
+    if let Some(shipping_fee) = fields.shipping_fee {
+        product.shipping_fee = Some(shipping_fee);
+    }
+    if let Some(image_url) = fields.image_url {
+        product.image_url = text(image_url);
+    }
+    if let Some(product_url) = fields.product_url {
+        product.product_url = product_url.trim().to_string();
+    }
+    product.is_on_sale = product.original_price.is_some_and(|op| op > product.price);
+
+    let valid_amount = |value: f64| value.is_finite() && value >= 0.0;
+    if product.title.is_empty() {
+        return Err("Product title is required".to_string());
+    }
+    if !valid_amount(product.price) {
+        return Err(format!("Invalid price: {}", product.price));
+    }
+    if let Some(original_price) = product.original_price.filter(|p| !valid_amount(*p)) {
+        return Err(format!("Invalid original price: {}", original_price));
+    }
+    if let Some(fee) = product.shipping_fee.filter(|f| !valid_amount(*f)) {
+        return Err(format!("Invalid shipping fee: {}", fee));
+    }
+    if let Some(rating) = product.product_rating.filter(|r| !(0.0..=5.0).contains(r)) {
+        return Err(format!("Rating must be between 0 and 5: {}", rating));
+    }
+    if product.sales_count < 0 {
+        return Err(format!("Invalid sales count: {}", product.sales_count));
+    }
+    if !product.product_url.starts_with("http://") && !product.product_url.starts_with("https://") {
+        return Err(format!("Invalid product URL: {}", product.product_url));
+    }
+    Ok(())
 }
 
-/// Get all favorites with product data
+/// Add a product found outside the scraper. Its TikTok id comes from the
+/// product URL when it is a shop link.
 #[command]
-pub async fn get_favorites(
+pub async fn create_product_manual(
     app: AppHandle,
-    list_id: Option<String>,
-) -> Result<Vec<FavoriteWithProduct>, String> {
-    log::info!("Getting favorites for list: {:?}", list_id);
-
+    fields: ProductFields,
+) -> Result<Product, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    let user_id = "default_user".to_string();
+    let tiktok_id = fields
+        .product_url
+        .as_deref()
+        .and_then(TikTokParser::extract_id_from_url)
+        .unwrap_or_else(|| format!("manual-{}", uuid::Uuid::new_v4()));
+    let mut product = new_product(tiktok_id, ProductSource::Manual);
+    apply_product_fields(&mut product, fields)?;
+    product.images = product.image_url.iter().cloned().collect();
+    product.has_free_shipping = product.shipping_fee == Some(0.0);
+
+    if let Some(existing) = database::get_product_by_tiktok_id(&db_path, &product.tiktok_id)
+        .map_err(|e| format!("Database error: {}", e))?
+    {
+        return Err(format!("Product already exists: {}", existing.id));
+    }
 
-    database::get_favorites(&db_path, &user_id, list_id.as_deref())
-        .map_err(|e| format!("Database error: {}", e))
+    database::save_product(&db_path, &product).map_err(|e| format!("Database error: {}", e))?;
+    log::info!("Product added by hand: {}", product.id);
+    Ok(product)
 }
 
-/// Create favorite list
+/// Change the values of a product by hand; fields left out are kept. The
+/// product is flagged as manual.
 #[command]
-pub async fn create_favorite_list(
+pub async fn update_product(
     app: AppHandle,
-    name: String,
-    description: Option<String>,
-    color: Option<String>,
-    icon: Option<String>,
-) -> Result<FavoriteList, String> {
-    log::info!("Creating favorite list: {}", name);
-
+    id: String,
+    patch: ProductFields,
+) -> Result<Product, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    let user_id = "default_user".to_string();
+    let stored = database::get_product_by_id(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", id))?;
 
-    database::create_favorite_list(
-        &db_path,
-        &user_id,
-        &name,
-        description.as_deref(),
-        color.as_deref(),
-        icon.as_deref(),
-    )
-    .map_err(|e| format!("Database error: {}", e))
+    let mut product = stored.clone();
+    apply_product_fields(&mut product, patch)?;
+    let now = Utc::now().to_rfc3339();
+    product.collected_at = now.clone();
+    product.updated_at = now;
+    product.source = ProductSource::Manual;
+
+    if !database::update_product(&db_path, &product, &stored)
+        .map_err(|e| format!("Database error: {}", e))?
+    {
+        return Err(format!("Product not found: {}", id));
+    }
+    Ok(product)
 }
 
-/// Get all favorite lists
+/// Open a product page in the system browser or, with `use_profile`, in a
+/// visible managed browser sharing the scraper's profile and fingerprint
 #[command]
-pub async fn get_favorite_lists(app: AppHandle) -> Result<Vec<FavoriteList>, String> {
-    log::info!("Getting favorite lists");
-
+pub async fn open_in_browser(
+    app: AppHandle,
+    product_id: String,
+    use_profile: bool,
+    inspector: State<'_, InspectorState>,
+) -> Result<(), String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    let user_id = "default_user".to_string();
+    let product = database::get_product_by_id(&db_path, &product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", product_id))?;
+
+    if !use_profile {
+        // The opener plugin is not a dependency yet; the shell one still opens URLs fine
+        #[allow(deprecated)]
+        return app
+            .shell()
+            .open(product.product_url, None)
+            .map_err(|e| e.to_string());
+    }
 
-    database::get_favorite_lists(&db_path, &user_id).map_err(|e| format!("Database error: {}", e))
-}
+    let config = load_settings(&app_dir).scraper;
+    let fingerprint = AntiDetection::new()
+        .profile_fingerprint(&db_path, &config.profile_id)
+        .map_err(|e| e.to_string())?;
 
-/// Delete favorite list
-#[command]
-pub async fn delete_favorite_list(app: AppHandle, list_id: String) -> Result<bool, String> {
-    log::info!("Deleting favorite list: {}", list_id);
+    let mut inspector = inspector.0.lock().await;
+    // The user may have closed the window since the last call
+    let healthy = match inspector.as_ref() {
+        Some(browser) => browser.is_healthy().await,
+        None => false,
+    };
+    if !healthy {
+        let mut browser = BrowserManager::new(false)
+            .with_profile(config.launch_profile)
+            .with_user_data(app_dir.join("browser_data"));
+        if let Some(url) = config.remote_debugging_url.filter(|url| !url.trim().is_empty()) {
+            browser = browser.with_remote_debugging(url);
+        }
+        browser.start(None).await.map_err(|e| {
+            format!(
+                "Failed to open the managed browser (is a scrape using the profile?): {}",
+                e
+            )
+        })?;
+        *inspector = Some(browser);
+    }
+    let browser = inspector.as_ref().ok_or("Managed browser not started")?;
 
-    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let db_path = app_dir.join("tiktrend.db");
+    let page = browser.new_page().await.map_err(|e| e.to_string())?;
+    AntiDetection::new()
+        .inject_stealth_scripts(&page, Some(&fingerprint))
+        .await
+        .map_err(|e| e.to_string())?;
+    page.goto(product.product_url.as_str())
+        .await
+        .map_err(|e| e.to_string())?;
 
-    database::delete_favorite_list(&db_path, &list_id).map_err(|e| format!("Database error: {}", e))
+    Ok(())
 }
 
-/// Generate AI copy for product
+/// Re-fetch one product from its page right away, outside any schedule. The
+/// check is kept in the product history and changes raise alerts, as for
+/// watched products.
 #[command]
-pub async fn generate_copy(app: AppHandle, request: CopyRequest) -> Result<CopyResponse, String> {
-    log::info!("Generating copy for product: {}", request.product_id);
-
+pub async fn refresh_product(
+    app: AppHandle,
+    jobs: State<'_, JobState>,
+    product_id: String,
+) -> Result<Product, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    // Get product data for context
-    let product = database::get_product_by_id(&db_path, &request.product_id)
+    let product = database::get_product_by_id(&db_path, &product_id)
         .map_err(|e| format!("Database error: {}", e))?
-        .ok_or("Product not found")?;
-
-    // Try to call API first
-    let client = reqwest::Client::new();
-    let api_payload = json!({
-        "product_id": product.id,
-        "product_title": product.title,
-        "product_description": product.description,
-        "product_price": product.price,
-        "copy_type": request.copy_type,
-        "tone": request.tone,
-        "platform": "instagram",
-        "language": "pt-BR"
-    });
+        .ok_or_else(|| format!("Product not found: {}", product_id))?;
+    if !scraper::can_refresh(&product.marketplace) {
+        return Err(format!(
+            "Refreshing is not supported for {} products",
+            product.marketplace.as_str()
+        ));
+    }
+    if product.product_url.is_empty() {
+        return Err("Product has no URL".to_string());
+    }
 
-    let copy_content = match client
-        .post(format!("{}/copy/generate", API_URL))
-        .json(&api_payload)
-        .send()
+    let job_id = jobs
+        .0
+        .enqueue(JobKind::Refresh, format!("Atualizar: {}", product.title))
+        .await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let config = runtime_scraper_config(&app_dir, load_settings(&app_dir).scraper);
+    let status = std::sync::Arc::new(tokio::sync::Mutex::new(ScraperStatus::default()));
+    let scraper = TikTokScraper::new(config, status, None);
+    let result = jobs
+        .0
+        .until_cancelled(&job_id, scraper.refresh_products(vec![product.clone()]))
         .await
-    {
-        Ok(response) => {
-            if response.status().is_success() {
-                let api_response: serde_json::Value = response
-                    .json()
-                    .await
-                    .map_err(|e| format!("Failed to parse API response: {}", e))?;
+        .and_then(|result| result.map_err(|e| e.to_string()))
+        .and_then(|refreshed| {
+            refreshed
+                .into_iter()
+                .next()
+                .ok_or_else(|| "No product data on the page".to_string())
+        });
 
-                api_response["copy_text"]
-                    .as_str()
-                    .unwrap_or_else(|| "Error: Empty response from AI")
-                    .to_string()
-            } else if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
-                || response.status() == reqwest::StatusCode::FORBIDDEN
-            {
-                return Err("QUOTA_EXCEEDED".to_string());
-            } else {
-                log::warn!(
-                    "API error: {}, falling back to local template",
-                    response.status()
-                );
-                generate_copy_content(&product, &request.copy_type, &request.tone)
-            }
-        }
-        Err(e) => {
-            log::warn!("API request failed: {}, falling back to local template", e);
-            generate_copy_content(&product, &request.copy_type, &request.tone)
-        }
-    };
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+    let refreshed = result?;
 
-    // Save to history
-    let user_id = "default_user".to_string();
-    database::save_copy_history(
-        &db_path,
-        &user_id,
-        Some(&request.product_id),
-        &request.copy_type,
-        &request.tone,
-        &copy_content,
-        0,
-    )
-    .ok();
+    let changes =
+        alerts::detect_changes(&product, &refreshed, alerts::DEFAULT_PRICE_CHANGE_PERCENT);
+    alerts::raise(Some(&app), &db_path, &changes);
+    database::save_product(&db_path, &refreshed).map_err(|e| format!("Database error: {}", e))?;
 
-    Ok(CopyResponse {
-        content: copy_content,
-        tokens_used: 0,
-    })
+    database::get_product_by_id(&db_path, &product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", product_id))
 }
 
-/// Get copy history
+/// Scrape a product from a link copied to the clipboard and save it tagged
+/// "clipboard import". A product already collected is updated in place.
 #[command]
-pub async fn get_copy_history(
-    app: AppHandle,
-    limit: Option<i32>,
-) -> Result<Vec<CopyHistory>, String> {
-    log::info!("Getting copy history");
+pub async fn import_clipboard_product(app: AppHandle, url: String) -> Result<Product, String> {
+    import_product_url(&app, &url, clipboard::CLIPBOARD_TAG).await
+}
 
+/// Scrape one product page by URL, as a browser job, and save the product
+/// tagged `tag`
+pub(crate) async fn import_product_url(
+    app: &AppHandle,
+    url: &str,
+    tag: &str,
+) -> Result<Product, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let db_path = app_dir.join("tiktrend.db");
 
-    let user_id = "default_user".to_string();
+    let url = url.trim().to_string();
+    let marketplace = category_marketplace(&url, &MarketplaceAccess::Tiktok);
+    if !scraper::can_refresh(&marketplace) {
+        return Err(format!(
+            "Importing is not supported for {} links",
+            marketplace.as_str()
+        ));
+    }
+    TikTokParser::extract_id_from_url(&url)
+        .ok_or_else(|| format!("Not a TikTok Shop product link: {}", url))?;
+
+    let jobs = app.state::<JobState>();
+    let job_id = jobs
+        .0
+        .enqueue(JobKind::Import, format!("Importar: {}", url))
+        .await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let config = runtime_scraper_config(&app_dir, load_settings(&app_dir).scraper);
+    let status = std::sync::Arc::new(tokio::sync::Mutex::new(ScraperStatus::default()));
+    let scraper = TikTokScraper::new(config, status, None);
+    let result = jobs
+        .0
+        .until_cancelled(&job_id, scraper.fetch_products(std::slice::from_ref(&url)))
+        .await
+        .and_then(|result| result.map_err(|e| e.to_string()))
+        .and_then(|fetched| {
+            fetched
+                .into_iter()
+                .next()
+                .ok_or_else(|| "No product data on the page".to_string())
+        });
 
-    database::get_copy_history(&db_path, &user_id, limit.unwrap_or(50))
-        .map_err(|e| format!("Database error: {}", e))
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+
+    save_imported_product(app, result?, tag)
 }
 
-/// Get dashboard statistics
-#[command]
-pub async fn get_user_stats(app: AppHandle) -> Result<DashboardStats, String> {
-    log::info!("Getting user stats");
+/// Save a product collected outside a scrape, tagged `tag`. A product already
+/// collected is updated in place, raising alerts for what changed.
+pub(crate) fn save_imported_product(
+    app: &AppHandle,
+    mut product: Product,
+    tag: &str,
+) -> Result<Product, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let previous = database::get_product_by_tiktok_id(&db_path, &product.tiktok_id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if let Some(previous) = &previous {
+        // Keep the row id so history, favorites and notes carry over
+        product.id = previous.id.clone();
+        let changes =
+            alerts::detect_changes(previous, &product, alerts::DEFAULT_PRICE_CHANGE_PERCENT);
+        alerts::raise(Some(app), &db_path, &changes);
+    }
+    database::save_product(&db_path, &product).map_err(|e| format!("Database error: {}", e))?;
+
+    let patch = ProductPatch {
+        add_tags: vec![tag.to_string()],
+        ..Default::default()
+    };
+    database::bulk_update_products(&db_path, std::slice::from_ref(&product.id), &patch)
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    database::get_product_by_id(&db_path, &product.id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", product.id))
+}
+
+/// Read a CSV or JSON export, or a page saved from TikTok Shop as HTML, and
+/// show what importing it would save
+#[command]
+pub async fn preview_import(app: AppHandle, path: String) -> Result<ImportPreview, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    file_import::preview(&db_path, std::path::Path::new(&path))
+}
+
+/// Save the products of a previewed file, tagged "file import"; with
+/// `tiktok_ids`, only those. Returns how many were saved.
+#[command]
+pub async fn confirm_import(
+    app: AppHandle,
+    path: String,
+    tiktok_ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let preview = file_import::preview(&db_path, std::path::Path::new(&path))?;
+    let mut saved = 0;
+    for product in preview.products {
+        if tiktok_ids
+            .as_ref()
+            .is_some_and(|ids| !ids.contains(&product.tiktok_id))
+        {
+            continue;
+        }
+        let tiktok_id = product.tiktok_id.clone();
+        match save_imported_product(&app, product, file_import::IMPORT_TAG) {
+            Ok(_) => saved += 1,
+            Err(e) => log::warn!("Failed to import product {}: {}", tiktok_id, e),
+        }
+    }
+
+    log::info!("Imported {} products from {}", saved, path);
+    Ok(saved)
+}
+
+/// Code for pairing the companion browser extension, valid for 10 minutes.
+/// Starts the extension endpoint if it isn't running.
+#[command]
+pub async fn start_extension_pairing(app: AppHandle) -> Result<ExtensionPairing, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let config = load_settings(&app_dir).companion;
+    if !config.enabled {
+        return Err("The browser extension endpoint is disabled in the settings".to_string());
+    }
+    companion::spawn_server(app.clone(), config.port);
+    companion::start_pairing(&db_path, config.port)
+}
+
+/// Revoke the tokens of every paired browser extension
+#[command]
+pub async fn unpair_extensions(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    companion::unpair_all(&db_path)
+}
+
+/// Re-fetch a product on its own schedule (every `interval` minutes, 60 by
+/// default) and raise alerts when its price, stock or sales change
+#[command]
+pub async fn watch_product(
+    app: AppHandle,
+    product_id: String,
+    interval: Option<u32>,
+) -> Result<WatchedProduct, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_product_by_id(&db_path, &product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", product_id))?;
+
+    let interval = interval
+        .unwrap_or(watch::DEFAULT_WATCH_INTERVAL_MINUTES)
+        .max(watch::MIN_WATCH_INTERVAL_MINUTES);
+    database::watch_product(&db_path, &product_id, interval)
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    database::get_watched_products(&db_path)
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .find(|w| w.product_id == product_id)
+        .ok_or_else(|| "Watch not saved".to_string())
+}
+
+#[command]
+pub async fn unwatch_product(app: AppHandle, product_id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::unwatch_product(&db_path, &product_id).map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn get_watched_products(app: AppHandle) -> Result<Vec<WatchedProduct>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_watched_products(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Alerts raised by watched products, newest first
+#[command]
+pub async fn get_product_alerts(
+    app: AppHandle,
+    unread_only: Option<bool>,
+    limit: Option<i32>,
+) -> Result<Vec<ProductAlert>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_product_alerts(&db_path, unread_only.unwrap_or(false), limit.unwrap_or(100))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn mark_alerts_read(app: AppHandle, ids: Vec<String>) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::mark_alerts_read(&db_path, &ids).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Register a competitor's TikTok Shop store. Its catalog is scraped every
+/// `interval_hours` (24 by default); new listings and price changes of at
+/// least `price_change_percent` raise alerts.
+#[command]
+pub async fn add_competitor(
+    app: AppHandle,
+    name: String,
+    store_url: String,
+    price_change_percent: Option<f64>,
+    interval_hours: Option<u32>,
+) -> Result<Competitor, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let store_url = store_url.trim().to_string();
+    if !store_url.starts_with("http://") && !store_url.starts_with("https://") {
+        return Err("Invalid store URL".to_string());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let competitor = Competitor {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        store_url,
+        price_change_percent: price_change_percent
+            .filter(|p| *p > 0.0)
+            .unwrap_or(crate::alerts::DEFAULT_PRICE_CHANGE_PERCENT),
+        interval_minutes: interval_hours
+            .map(|h| h * 60)
+            .unwrap_or(competitors::DEFAULT_INTERVAL_MINUTES)
+            .max(competitors::MIN_INTERVAL_MINUTES),
+        last_checked_at: None,
+        // The first scrape is due right away
+        next_check_at: now.clone(),
+        product_count: 0,
+        created_at: now,
+    };
+
+    database::add_competitor(&db_path, &competitor).map_err(|e| format!("Database error: {}", e))?;
+    Ok(competitor)
+}
+
+#[command]
+pub async fn remove_competitor(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::remove_competitor(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn get_competitors(app: AppHandle) -> Result<Vec<Competitor>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_competitors(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Products of one competitor's store, or of all competitors, newest listings first
+#[command]
+pub async fn get_competitor_products(
+    app: AppHandle,
+    competitor_id: Option<String>,
+) -> Result<Vec<CompetitorProduct>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_competitor_products(&db_path, competitor_id.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Sales growth, price movement and new listings per category over the
+/// period, the niches heating up fastest first
+#[command]
+pub async fn get_category_trends(
+    app: AppHandle,
+    period: TrendPeriod,
+) -> Result<Vec<CategoryTrend>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let since = (Utc::now() - chrono::Duration::days(period.days())).to_rfc3339();
+    database::get_category_trends(&db_path, &since).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Saturation score of every niche (category or search keyword), most crowded first
+#[command]
+pub async fn get_niche_saturation(app: AppHandle) -> Result<Vec<NicheSaturation>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    saturation::refresh(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Build the opportunity digest of the last 7 days
+#[command]
+pub async fn generate_weekly_digest(app: AppHandle) -> Result<WeeklyDigest, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    digest::generate(&db_path, Utc::now()).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Generate the weekly digest and write it to `path` as "json", "markdown"
+/// or "pdf". Returns the written path.
+#[command]
+pub async fn export_weekly_digest(
+    app: AppHandle,
+    format: String,
+    path: String,
+) -> Result<String, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let weekly =
+        digest::generate(&db_path, Utc::now()).map_err(|e| format!("Database error: {}", e))?;
+    let contents = match format.as_str() {
+        "json" => serde_json::to_vec_pretty(&weekly).map_err(|e| e.to_string())?,
+        "markdown" => digest::render_markdown(&weekly).into_bytes(),
+        "pdf" => digest::render_pdf(&weekly).await?,
+        other => return Err(format!("Unknown digest format: {}", other)),
+    };
+    fs::write(&path, contents).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
+
+/// Generate the weekly digest and send it on WhatsApp now. Returns the
+/// WhatsApp message usage of the period.
+#[command]
+pub async fn send_weekly_digest_whatsapp(app: AppHandle) -> Result<FeatureUsage, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let weekly =
+        digest::generate(&db_path, Utc::now()).map_err(|e| format!("Database error: {}", e))?;
+    whatsapp::send(&app, &digest::render_message(&weekly)).await
+}
+
+/// Send a test message with the WhatsApp settings. Returns the WhatsApp
+/// message usage of the period.
+#[command]
+pub async fn send_whatsapp_test(app: AppHandle) -> Result<FeatureUsage, String> {
+    whatsapp::send(&app, "TikTrend Finder: as notificações de WhatsApp estão funcionando.").await
+}
+
+/// Add product to favorites
+#[command]
+pub async fn add_favorite(
+    app: AppHandle,
+    product_id: String,
+    list_id: Option<String>,
+    notes: Option<String>,
+) -> Result<FavoriteItem, String> {
+    log::info!("Adding favorite: {} to list {:?}", product_id, list_id);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    // Default user_id for desktop (single user)
+    let user_id = "default_user".to_string();
+
+    database::add_favorite(
+        &db_path,
+        &user_id,
+        &product_id,
+        list_id.as_deref(),
+        notes.as_deref(),
+    )
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Remove product from favorites; the favorite goes to the trash
+#[command]
+pub async fn remove_favorite(app: AppHandle, product_id: String) -> Result<bool, String> {
+    log::info!("Removing favorite: {}", product_id);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::remove_favorite(&db_path, &user_id, &product_id)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Change a favorite's notes or move it to another list; an empty string
+/// clears the notes or takes it out of its list
+#[command]
+pub async fn update_favorite(
+    app: AppHandle,
+    id: String,
+    notes: Option<String>,
+    list_id: Option<String>,
+) -> Result<FavoriteItem, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::update_favorite(&db_path, &id, notes.as_deref(), list_id.as_deref())
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Favorite not found: {}", id))
+}
+
+/// Get all favorites with product data
+#[command]
+pub async fn get_favorites(
+    app: AppHandle,
+    list_id: Option<String>,
+) -> Result<Vec<FavoriteWithProduct>, String> {
+    log::info!("Getting favorites for list: {:?}", list_id);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::get_favorites(&db_path, &user_id, list_id.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Create favorite list
+#[command]
+pub async fn create_favorite_list(
+    app: AppHandle,
+    name: String,
+    description: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<FavoriteList, String> {
+    log::info!("Creating favorite list: {}", name);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::create_favorite_list(
+        &db_path,
+        &user_id,
+        &name,
+        description.as_deref(),
+        color.as_deref(),
+        icon.as_deref(),
+    )
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Get all favorite lists
+#[command]
+pub async fn get_favorite_lists(app: AppHandle) -> Result<Vec<FavoriteList>, String> {
+    log::info!("Getting favorite lists");
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::get_favorite_lists(&db_path, &user_id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Delete favorite list; the list and its favorites go to the trash
+#[command]
+pub async fn delete_favorite_list(app: AppHandle, list_id: String) -> Result<bool, String> {
+    log::info!("Deleting favorite list: {}", list_id);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_favorite_list(&db_path, &list_id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Re-link favorites left on stale product rows to the current row of the
+/// same TikTok product, merging duplicates, and report the orphans
+#[command]
+pub async fn repair_favorites(app: AppHandle) -> Result<FavoriteRepairReport, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    let report = database::repair_favorites(&db_path, &user_id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    log::info!(
+        "Favorites repaired: {} relinked, {} merged, {} orphans",
+        report.relinked,
+        report.merged,
+        report.orphans.len()
+    );
+    Ok(report)
+}
+
+/// Favorites, lists, notes, copies and alerts referring to a product, to
+/// show before deleting it
+#[command]
+pub async fn get_product_references(
+    app: AppHandle,
+    product_id: String,
+) -> Result<ProductReferences, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_product_references(&db_path, &product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Product not found: {}", product_id))
+}
+
+/// Delete products; they go to the trash with their variants. Products
+/// something refers to are refused and reported, unless `cascade` is set:
+/// then what refers to them goes to the trash too.
+#[command]
+pub async fn delete_products(
+    app: AppHandle,
+    product_ids: Vec<String>,
+    cascade: Option<bool>,
+) -> Result<ProductDeletion, String> {
+    log::info!("Deleting {} products", product_ids.len());
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_products(&db_path, &product_ids, cascade.unwrap_or(false))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Items in the trash, most recently deleted first. Expired items are
+/// removed first.
+#[command]
+pub async fn get_trash(app: AppHandle) -> Result<Vec<TrashItem>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::prune_trash(&db_path).map_err(|e| format!("Database error: {}", e))?;
+    database::get_trash(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Put a deleted list, favorite or product back
+#[command]
+pub async fn restore_from_trash(app: AppHandle, id: String) -> Result<TrashItem, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let item = database::restore_from_trash(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Trash item not found: {}", id))?;
+    log::info!("Restored {} from the trash: {}", item.kind.as_str(), item.item_id);
+    Ok(item)
+}
+
+/// Remove everything in the trash for good; returns the number of items
+/// removed
+#[command]
+pub async fn empty_trash(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::empty_trash(&db_path, None).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Rename a favorite list or change its description, color or icon; fields
+/// left out are kept
+#[command]
+pub async fn update_favorite_list(
+    app: AppHandle,
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+) -> Result<FavoriteList, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let name = name.map(|n| n.trim().to_string());
+    if name.as_deref() == Some("") {
+        return Err("List name can't be empty".to_string());
+    }
+
+    database::update_favorite_list(
+        &db_path,
+        &id,
+        name.as_deref(),
+        description.as_deref(),
+        color.as_deref(),
+        icon.as_deref(),
+    )
+    .map_err(|e| format!("Database error: {}", e))?
+    .ok_or_else(|| format!("Favorite list not found: {}", id))
+}
+
+/// Put the favorite lists in the given order; returns them as now ordered
+#[command]
+pub async fn reorder_favorite_lists(
+    app: AppHandle,
+    ids_in_order: Vec<String>,
+) -> Result<Vec<FavoriteList>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::reorder_favorite_lists(&db_path, &user_id, &ids_in_order)
+        .map_err(|e| format!("Database error: {}", e))?;
+    database::get_favorite_lists(&db_path, &user_id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Pin a favorite list to the top, or unpin it
+#[command]
+pub async fn set_favorite_list_pinned(
+    app: AppHandle,
+    list_id: String,
+    pinned: bool,
+) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::set_favorite_list_pinned(&db_path, &list_id, pinned)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Save a favorite list as a standalone HTML page, to share with someone
+/// who doesn't use the app
+#[command]
+pub async fn export_favorite_list_html(
+    app: AppHandle,
+    list_id: String,
+    path: String,
+) -> Result<String, String> {
+    log::info!("Exporting favorite list {} to {}", list_id, path);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let user_id = "default_user".to_string();
+
+    let list = database::get_favorite_lists(&db_path, &user_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .into_iter()
+        .find(|l| l.id == list_id)
+        .ok_or_else(|| format!("Favorite list not found: {}", list_id))?;
+    let favorites = database::get_favorites(&db_path, &user_id, Some(&list_id))
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let number_format = NumberFormat::from_settings(&load_settings(&app_dir));
+    let html = share::render_favorites_html(&list, &favorites, &number_format, Utc::now());
+    fs::write(&path, html).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
+
+/// How long a generated copy is reused for the same product, type and tone
+const COPY_CACHE_TTL_DAYS: i64 = 7;
+
+/// Cache key of a copy: hash of the product fields sent to the backend, the
+/// copy type and the tone. Editing the product gives a new key.
+fn copy_cache_key(product: &Product, request: &CopyRequest) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        product.title.as_str(),
+        product.description.as_deref().unwrap_or_default(),
+        &product.price.to_string(),
+        &request.copy_type,
+        &request.tone,
+    ] {
+        hasher.update(part.as_bytes());
+        // Separator, so ("ab", "c") and ("a", "bc") differ
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Generate AI copy for product
+///
+/// A copy the backend generated for the same product fields, type and tone
+/// in the last `COPY_CACHE_TTL_DAYS` is returned without calling it again,
+/// unless `request.force` is set.
+#[command]
+pub async fn generate_copy(app: AppHandle, request: CopyRequest) -> Result<CopyResponse, String> {
+    log::info!("Generating copy for product: {}", request.product_id);
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let number_format = NumberFormat::from_settings(&load_settings(&app_dir));
+
+    // Get product data for context
+    let product = database::get_product_by_id(&db_path, &request.product_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("Product not found")?;
+
+    let cache_key = copy_cache_key(&product, &request);
+    let now = Utc::now();
+    let expired_before = (now - chrono::Duration::days(COPY_CACHE_TTL_DAYS)).to_rfc3339();
+    if !request.force {
+        if let Some(content) = database::get_cached_copy(&db_path, &cache_key, &expired_before)
+            .map_err(|e| format!("Database error: {}", e))?
+        {
+            log::info!("Copy served from cache");
+            return Ok(CopyResponse {
+                content,
+                tokens_used: 0,
+                cached: true,
+            });
+        }
+    }
+
+    // The local balance may be stale (e.g. after a top-up), so a low one is
+    // only a warning; the backend refuses when the credits are really gone
+    let credit_check = credits::check(&db_path, credits::COPY_CREDIT_COST)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if !credit_check.sufficient {
+        log::warn!(
+            "Credits may not cover a copy: {:?} left",
+            credit_check.balance
+        );
+    }
+
+    // Try to call API first
+    let api_payload = json!({
+        "product_id": product.id,
+        "product_title": product.title,
+        "product_description": product.description,
+        "product_price": product.price,
+        "copy_type": request.copy_type,
+        "tone": request.tone,
+        "platform": "instagram",
+        "language": "pt-BR"
+    });
+
+    let mut tokens_used = 0;
+    let copy_content = match backend::send(
+        backend::post("/copy/generate")
+            .json(&api_payload)
+            // Generation takes longer than the other calls
+            .timeout(std::time::Duration::from_secs(60)),
+    )
+    .await
+    {
+        Ok(response) => {
+            if response.status().is_success() {
+                let api_response: serde_json::Value = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+                let copy_text = api_response["copy_text"]
+                    .as_str()
+                    .unwrap_or_else(|| "Error: Empty response from AI")
+                    .to_string();
+
+                tokens_used = api_response["tokens_used"].as_i64().unwrap_or(0) as i32;
+                let credits_used = api_response["credits_used"]
+                    .as_i64()
+                    .map(|c| c as i32)
+                    .unwrap_or(credits::COPY_CREDIT_COST);
+                let credits_remaining = api_response["credits_remaining"].as_i64().map(|c| c as i32);
+                if let Err(e) = credits::record_usage(
+                    &db_path,
+                    &format!("Copy: {}", product.title),
+                    credits_used,
+                    tokens_used,
+                    credits_remaining,
+                ) {
+                    log::warn!("Failed to record credit usage: {}", e);
+                }
+
+                // Only the backend's copies cost credits; local templates aren't cached
+                if api_response["copy_text"].is_string() {
+                    database::save_cached_copy(
+                        &db_path,
+                        &cache_key,
+                        &copy_text,
+                        &now.to_rfc3339(),
+                        &expired_before,
+                    )
+                    .ok();
+                }
+                copy_text
+            } else if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || response.status() == reqwest::StatusCode::FORBIDDEN
+            {
+                return Err("QUOTA_EXCEEDED".to_string());
+            } else {
+                log::warn!(
+                    "API error: {}, falling back to local template",
+                    response.status()
+                );
+                generate_copy_content(&product, &request.copy_type, &request.tone, &number_format)
+            }
+        }
+        Err(e) => {
+            log::warn!("API request failed: {}, falling back to local template", e);
+            generate_copy_content(&product, &request.copy_type, &request.tone, &number_format)
+        }
+    };
+
+    // Save to history
+    let user_id = "default_user".to_string();
+    database::save_copy_history(
+        &db_path,
+        &user_id,
+        Some(&request.product_id),
+        &request.copy_type,
+        &request.tone,
+        &copy_content,
+        tokens_used,
+    )
+    .ok();
+
+    Ok(CopyResponse {
+        content: copy_content,
+        tokens_used,
+        cached: false,
+    })
+}
+
+/// AI credits left, spent in the last 30 days and the latest ledger entries
+#[command]
+pub async fn get_credit_balance(app: AppHandle) -> Result<CreditBalance, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    credits::balance(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Whether the credits left cover an operation costing `cost`, for the app
+/// to warn before starting it
+#[command]
+pub async fn check_credits(app: AppHandle, cost: i32) -> Result<CreditCheck, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    credits::check(&db_path, cost.max(0)).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Get copy history
+#[command]
+pub async fn get_copy_history(
+    app: AppHandle,
+    limit: Option<i32>,
+) -> Result<Vec<CopyHistory>, String> {
+    log::info!("Getting copy history");
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::get_copy_history(&db_path, &user_id, limit.unwrap_or(50))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Get dashboard statistics
+#[command]
+pub async fn get_user_stats(app: AppHandle) -> Result<DashboardStats, String> {
+    log::info!("Getting user stats");
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    let mut stats = database::get_dashboard_stats(&db_path, &user_id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    stats.trial = Some(trial::status(&db_path, Utc::now()));
+    Ok(stats)
+}
+
+/// Research goals with their progress in the current week or month
+#[command]
+pub async fn get_goals(app: AppHandle) -> Result<Vec<GoalProgress>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let user_id = "default_user".to_string();
+
+    database::get_goal_progress(&db_path, &user_id).map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn add_goal(
+    app: AppHandle,
+    title: String,
+    metric: GoalMetric,
+    target: i64,
+    period: GoalPeriod,
+) -> Result<Goal, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    if target <= 0 {
+        return Err("Goal target must be positive".to_string());
+    }
+
+    let goal = Goal {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: title.trim().to_string(),
+        metric,
+        target,
+        period,
+        created_at: Utc::now().to_rfc3339(),
+    };
+
+    database::add_goal(&db_path, &goal).map_err(|e| format!("Database error: {}", e))?;
+    Ok(goal)
+}
+
+#[command]
+pub async fn delete_goal(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_goal(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Text field of a lead, trimmed; None when empty
+fn lead_text(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Apply the given fields to a lead. A product it is linked to must exist.
+fn apply_lead_fields(
+    db_path: &std::path::Path,
+    lead: &mut CrmLead,
+    fields: LeadFields,
+) -> Result<(), String> {
+    if let Some(kind) = fields.kind {
+        lead.kind = kind;
+    }
+    if let Some(name) = fields.name {
+        lead.name = lead_text(name).ok_or("Lead name is required")?;
+    }
+    if let Some(company) = fields.company {
+        lead.company = lead_text(company);
+    }
+    if let Some(email) = fields.email {
+        lead.email = lead_text(email);
+    }
+    if let Some(phone) = fields.phone {
+        lead.phone = lead_text(phone);
+    }
+    if let Some(handle) = fields.handle {
+        lead.handle = lead_text(handle);
+    }
+    if let Some(product_id) = fields.product_id {
+        lead.product_id = lead_text(product_id);
+        if let Some(product_id) = &lead.product_id {
+            database::get_product_by_id(db_path, product_id)
+                .map_err(|e| format!("Database error: {}", e))?
+                .ok_or_else(|| format!("Product not found: {}", product_id))?;
+        }
+    }
+    if let Some(status) = fields.status {
+        lead.status = status;
+    }
+    if let Some(notes) = fields.notes {
+        lead.notes = lead_text(notes);
+    }
+    Ok(())
+}
+
+/// Hybrid mode: queue a lead change for the backend CRM and upload it
+fn sync_lead(app: &AppHandle, app_dir: &std::path::Path, lead_id: &str, operation: &str) {
+    let settings = load_settings(app_dir);
+    if !hybrid_upload_enabled(&entitlements(app, app_dir), &settings) {
+        return;
+    }
+
+    let db_path = app_dir.join("tiktrend.db");
+    crm::queue(&db_path, lead_id, operation);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crm::upload_pending(&db_path, &settings).await {
+            log::warn!("CRM sync postponed: {}", e);
+        }
+    });
+}
+
+/// Add a supplier or creator contact to the CRM. Counts against the plan's
+/// crm_leads limit.
+#[command]
+pub async fn create_crm_lead(app: AppHandle, fields: LeadFields) -> Result<CrmLead, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let subscription = entitlements(&app, &app_dir).subscription;
+    usage::ensure_available(&db_path, &subscription, "crm_leads", Utc::now())?;
+
+    let now = Utc::now().to_rfc3339();
+    let mut lead = CrmLead {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: LeadKind::Supplier,
+        name: String::new(),
+        company: None,
+        email: None,
+        phone: None,
+        handle: None,
+        product_id: None,
+        status: LeadStatus::New,
+        notes: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    apply_lead_fields(&db_path, &mut lead, fields)?;
+    if lead.name.is_empty() {
+        return Err("Lead name is required".to_string());
+    }
+
+    database::save_crm_lead(&db_path, &lead).map_err(|e| format!("Database error: {}", e))?;
+    usage::record_and_warn(&app, &db_path, &subscription, "crm_leads", 1)
+        .map_err(|e| format!("Database error: {}", e))?;
+    sync_lead(&app, &app_dir, &lead.id, "upsert");
+    Ok(lead)
+}
+
+/// Change a lead, e.g. move it along the pipeline; fields left out are kept
+#[command]
+pub async fn update_crm_lead(
+    app: AppHandle,
+    id: String,
+    fields: LeadFields,
+) -> Result<CrmLead, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let mut lead = database::get_crm_lead(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Lead not found: {}", id))?;
+    apply_lead_fields(&db_path, &mut lead, fields)?;
+    lead.updated_at = Utc::now().to_rfc3339();
+
+    database::save_crm_lead(&db_path, &lead).map_err(|e| format!("Database error: {}", e))?;
+    sync_lead(&app, &app_dir, &lead.id, "upsert");
+    Ok(lead)
+}
+
+/// Leads, most recently updated first, optionally only those at `status` or
+/// found through `product_id`
+#[command]
+pub async fn get_crm_leads(
+    app: AppHandle,
+    status: Option<LeadStatus>,
+    product_id: Option<String>,
+) -> Result<Vec<CrmLead>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_crm_leads(&db_path, status, product_id.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn delete_crm_lead(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let deleted =
+        database::delete_crm_lead(&db_path, &id).map_err(|e| format!("Database error: {}", e))?;
+    if deleted {
+        sync_lead(&app, &app_dir, &id, "delete");
+    }
+    Ok(deleted)
+}
+
+/// Send lead changes still waiting for the backend CRM now; returns how many
+/// were sent
+#[command]
+pub async fn sync_crm_leads(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    crm::upload_pending(&db_path, &load_settings(&app_dir)).await
+}
+
+/// Apply the given fields to a post. A copy or product it is linked to must
+/// exist; a copy fills in the caption and product unless they are given too,
+/// and the product fills in a missing image.
+fn apply_post_fields(
+    db_path: &std::path::Path,
+    post: &mut SocialPost,
+    mut fields: SocialPostFields,
+) -> Result<(), String> {
+    if let Some(copy_id) = fields.copy_id {
+        post.copy_id = lead_text(copy_id);
+        if let Some(copy_id) = &post.copy_id {
+            let copy = database::get_copy(db_path, copy_id)
+                .map_err(|e| format!("Database error: {}", e))?
+                .ok_or_else(|| format!("Copy not found: {}", copy_id))?;
+            fields.caption.get_or_insert(copy.content);
+            if let Some(product_id) = copy.product_id {
+                fields.product_id.get_or_insert(product_id);
+            }
+        }
+    }
+    if let Some(product_id) = fields.product_id {
+        post.product_id = lead_text(product_id);
+    }
+    if let Some(platform) = fields.platform {
+        let platform = platform.trim().to_lowercase();
+        if !social::PLATFORMS.contains(&platform.as_str()) {
+            return Err(format!("Unknown social platform: {}", platform));
+        }
+        post.platform = platform;
+    }
+    if let Some(caption) = fields.caption {
+        post.caption = lead_text(caption).ok_or("Post caption is required")?;
+    }
+    if let Some(image_url) = fields.image_url {
+        post.image_url = lead_text(image_url);
+    }
+    if let Some(scheduled_at) = fields.scheduled_at {
+        post.scheduled_at = chrono::DateTime::parse_from_rfc3339(scheduled_at.trim())
+            .map_err(|e| format!("Invalid schedule time {}: {}", scheduled_at, e))?
+            .with_timezone(&Utc)
+            .to_rfc3339();
+    }
+
+    if let Some(product_id) = &post.product_id {
+        let product = database::get_product_by_id(db_path, product_id)
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| format!("Product not found: {}", product_id))?;
+        if post.image_url.is_none() {
+            post.image_url = product.image_url;
+        }
+    }
+    Ok(())
+}
+
+/// Queue a post built from a generated copy and the product image, going out
+/// at `scheduledAt` (now when left out). Counts against the plan's
+/// social_posts limit.
+#[command]
+pub async fn create_social_post(
+    app: AppHandle,
+    fields: SocialPostFields,
+) -> Result<SocialPost, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let subscription = entitlements(&app, &app_dir).subscription;
+    usage::ensure_available(&db_path, &subscription, social::FEATURE, Utc::now())?;
+
+    let now = Utc::now().to_rfc3339();
+    let mut post = SocialPost {
+        id: uuid::Uuid::new_v4().to_string(),
+        product_id: None,
+        copy_id: None,
+        platform: social::PLATFORMS[0].to_string(),
+        caption: String::new(),
+        image_url: None,
+        scheduled_at: now.clone(),
+        status: SocialPostStatus::Scheduled,
+        external_id: None,
+        error: None,
+        published_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    apply_post_fields(&db_path, &mut post, fields)?;
+    if post.caption.is_empty() {
+        return Err("Post caption is required".to_string());
+    }
+
+    database::save_social_post(&db_path, &post).map_err(|e| format!("Database error: {}", e))?;
+    usage::record_and_warn(&app, &db_path, &subscription, social::FEATURE, 1)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(post)
+}
+
+/// Change a post that hasn't gone out yet; fields left out are kept. A failed
+/// or canceled post is scheduled again.
+#[command]
+pub async fn update_social_post(
+    app: AppHandle,
+    id: String,
+    fields: SocialPostFields,
+) -> Result<SocialPost, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let mut post = database::get_social_post(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Social post not found: {}", id))?;
+    if post.status == SocialPostStatus::Published {
+        return Err(format!("Social post already published: {}", id));
+    }
+    apply_post_fields(&db_path, &mut post, fields)?;
+    post.status = SocialPostStatus::Scheduled;
+    post.error = None;
+    post.updated_at = Utc::now().to_rfc3339();
+
+    database::save_social_post(&db_path, &post).map_err(|e| format!("Database error: {}", e))?;
+    Ok(post)
+}
+
+/// Posts in the order they go out, optionally only those at `status` or
+/// scheduled between `from` and `to` (RFC 3339)
+#[command]
+pub async fn get_social_posts(
+    app: AppHandle,
+    status: Option<SocialPostStatus>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<SocialPost>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_social_posts(&db_path, status, from.as_deref(), to.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Keep a scheduled post from going out; it stays in the queue
+#[command]
+pub async fn cancel_social_post(app: AppHandle, id: String) -> Result<SocialPost, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let mut post = database::get_social_post(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Social post not found: {}", id))?;
+    if post.status == SocialPostStatus::Published {
+        return Err(format!("Social post already published: {}", id));
+    }
+    post.status = SocialPostStatus::Canceled;
+    post.updated_at = Utc::now().to_rfc3339();
+
+    database::save_social_post(&db_path, &post).map_err(|e| format!("Database error: {}", e))?;
+    Ok(post)
+}
+
+#[command]
+pub async fn delete_social_post(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_social_post(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Publish a post now instead of at its scheduled time
+#[command]
+pub async fn publish_social_post(app: AppHandle, id: String) -> Result<SocialPost, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let post = database::get_social_post(&db_path, &id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Social post not found: {}", id))?;
+    if post.status == SocialPostStatus::Published {
+        return Err(format!("Social post already published: {}", id));
+    }
+
+    social::publish_and_save(&db_path, &load_settings(&app_dir), post).await
+}
+
+/// Write the posts scheduled between `from` and `to` (RFC 3339), canceled
+/// ones left out, to `path` as a content calendar CSV. Returns the written
+/// path.
+#[command]
+pub async fn export_content_calendar(
+    app: AppHandle,
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<String, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let posts: Vec<SocialPost> =
+        database::get_social_posts(&db_path, None, from.as_deref(), to.as_deref())
+            .map_err(|e| format!("Database error: {}", e))?
+            .into_iter()
+            .filter(|post| post.status != SocialPostStatus::Canceled)
+            .collect();
+    fs::write(&path, social::render_calendar_csv(&db_path, &posts)).map_err(|e| e.to_string())?;
+
+    Ok(path)
+}
+
+/// Validate license. A license confirmed by the backend is cached, and the
+/// cache is used when the backend can't be reached.
+#[command]
+pub async fn validate_license(app: AppHandle, license_key: String) -> Result<License, String> {
+    log::info!("Validating license: {}", license_key);
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+
+    let hwid = get_hardware_id();
+
+    let api_payload = json!({
+        "email": license_key,
+        "hwid": hwid,
+        "app_version": "1.0.0"
+    });
+
+    match backend::send(backend::post("/license/validate").json(&api_payload)).await {
+        Ok(response) => {
+            if response.status().is_success() {
+                let api_response: serde_json::Value = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+                let features = api_response["features"]
+                    .as_object()
+                    .ok_or("Invalid features format")?;
+
+                let license = License {
+                    is_valid: api_response["valid"].as_bool().unwrap_or(false),
+                    plan: api_response["plan"].as_str().unwrap_or("lifetime").to_string(),
+                    features: PlanFeatures {
+                        searches_unlimited: features
+                            .get("searches_unlimited")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),  // Lifetime = unlimited
+                        favorites_unlimited: features
+                            .get("favorites_unlimited")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),  // Lifetime = unlimited
+                        export_enabled: features
+                            .get("export_enabled")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),
+                        scheduler_enabled: features
+                            .get("scheduler_enabled")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),
+                    },
+                    expires_at: api_response["expires_at"]
+                        .as_str()
+                        .unwrap_or("")
+                        .to_string(),
+                    credits: api_response["credits"]
+                        .as_i64()
+                        .unwrap_or(0) as i32,
+                };
+
+                if license.is_valid {
+                    license::store(&app_dir, &license_key, &license);
+                    if let Err(e) =
+                        credits::sync(&app_dir.join("tiktrend.db"), license.credits, "License")
+                    {
+                        log::warn!("Failed to record credit balance: {}", e);
+                    }
+                } else {
+                    license::clear(&app_dir);
+                }
+                invalidate_entitlements(&app);
+                Ok(license)
+            } else if response.status().is_server_error() {
+                // The backend is down: same as being offline
+                log::warn!("License API error: {}", response.status());
+                cached_license(&app_dir, &license_key)
+            } else {
+                // 401/403/404: the backend rejected the license
+                log::warn!("License API error: {}", response.status());
+                license::clear(&app_dir);
+                invalidate_entitlements(&app);
+                Err(format!("License validation failed: {}", response.status()))
+            }
+        }
+        Err(e) => {
+            log::warn!("License API connection failed: {}", e);
+            cached_license(&app_dir, &license_key)
+        }
+    }
+}
+
+/// The cached license, for when the backend can't be reached
+fn cached_license(app_dir: &std::path::Path, license_key: &str) -> Result<License, String> {
+    license::load(app_dir, license_key, Utc::now())
+        .map(|cached| {
+            log::info!("Using cached license (offline mode)");
+            cached.license
+        })
+        .ok_or_else(|| {
+            "License could not be validated offline: no valid cached license".to_string()
+        })
+}
+
+/// Free space on the app's disk against the configured minimum
+#[command]
+pub async fn get_disk_status(app: AppHandle) -> Result<DiskStatus, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(disk::status(&app_dir, &load_settings(&app_dir)))
+}
+
+/// Clear the browser caches and old error pages, run logs and log files
+#[command]
+pub async fn cleanup_disk_space(app: AppHandle) -> Result<DiskCleanupReport, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let log_dir = app.path().app_log_dir().ok();
+    Ok(disk::cleanup(&app_dir, log_dir.as_deref()))
+}
+
+/// Saved scrape presets, by name
+#[command]
+pub async fn get_scrape_presets(app: AppHandle) -> Result<Vec<ScrapePreset>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_scrape_presets(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Save a scraper config under a name; saving an existing name replaces its
+/// config
+#[command]
+pub async fn save_scrape_preset(
+    app: AppHandle,
+    name: String,
+    config: ScraperConfig,
+) -> Result<ScrapePreset, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name is required".to_string());
+    }
+
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let now = Utc::now().to_rfc3339();
+    let preset = ScrapePreset {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        config,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    database::save_scrape_preset(&db_path, &preset).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Make a preset (by id or name) the scraper config in the settings and
+/// return it for the scraper form
+#[command]
+pub async fn apply_scrape_preset(app: AppHandle, preset: String) -> Result<ScraperConfig, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let preset = database::get_scrape_preset(&db_path, &preset)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Scrape preset not found: {}", preset))?;
+
+    let mut settings = load_settings(&app_dir);
+    settings.scraper = preset.config.clone();
+    write_settings(&app, &app_dir, &settings)?;
+    log::info!("Applied scrape preset {}", preset.name);
+    Ok(preset.config)
+}
+
+#[command]
+pub async fn delete_scrape_preset(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_scrape_preset(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Scrapes repeated on an interval
+#[command]
+pub async fn get_scrape_schedules(app: AppHandle) -> Result<Vec<ScrapeSchedule>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_scrape_schedules(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Scrape `categories` (the trending page when empty) every
+/// `interval_minutes`, the scraper settings' interval by default. The first
+/// run is due right away.
+#[command]
+pub async fn create_scrape_schedule(
+    app: AppHandle,
+    name: String,
+    categories: Vec<String>,
+    interval_minutes: Option<u32>,
+) -> Result<ScrapeSchedule, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Schedule name is required".to_string());
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let schedule = ScrapeSchedule {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        categories: categories
+            .iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect(),
+        interval_minutes: interval_minutes
+            .unwrap_or_else(|| load_settings(&app_dir).scraper.interval_minutes)
+            .max(scheduler::MIN_INTERVAL_MINUTES),
+        paused: false,
+        last_run_at: None,
+        next_run_at: now.clone(),
+        last_error: None,
+        created_at: now,
+    };
+
+    database::add_scrape_schedule(&db_path, &schedule)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(schedule)
+}
+
+/// Resume a paused schedule; a run that came due while paused starts at once
+#[command]
+pub async fn start_scrape_schedule(app: AppHandle, id: String) -> Result<ScrapeSchedule, String> {
+    set_scrape_schedule_paused(&app, &id, false)
+}
+
+#[command]
+pub async fn pause_scrape_schedule(app: AppHandle, id: String) -> Result<ScrapeSchedule, String> {
+    set_scrape_schedule_paused(&app, &id, true)
+}
+
+fn set_scrape_schedule_paused(
+    app: &AppHandle,
+    id: &str,
+    paused: bool,
+) -> Result<ScrapeSchedule, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::set_scrape_schedule_paused(&db_path, id, paused)
+        .map_err(|e| format!("Database error: {}", e))?;
+    database::get_scrape_schedule(&db_path, id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Scrape schedule not found: {}", id))
+}
+
+#[command]
+pub async fn delete_scrape_schedule(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_scrape_schedule(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Reports exported after each scraper run
+#[command]
+pub async fn get_report_schedules(app: AppHandle) -> Result<Vec<ReportSchedule>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_report_schedules(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Create a schedule (empty id) or update one
+#[command]
+pub async fn save_report_schedule(
+    app: AppHandle,
+    mut schedule: ReportSchedule,
+) -> Result<ReportSchedule, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let has = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+    if !has(&schedule.folder) && !has(&schedule.webhook_url) {
+        return Err("A report needs a folder or a webhook".to_string());
+    }
+    if let Some(url) = schedule.webhook_url.as_deref().filter(|u| !u.trim().is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Invalid webhook URL".to_string());
+        }
+    }
+
+    schedule.name = schedule.name.trim().to_string();
+    if schedule.id.is_empty() {
+        schedule.id = uuid::Uuid::new_v4().to_string();
+        schedule.created_at = Utc::now().to_rfc3339();
+    }
+
+    database::save_report_schedule(&db_path, &schedule)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(schedule)
+}
+
+#[command]
+pub async fn delete_report_schedule(app: AppHandle, id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_report_schedule(&db_path, &id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Generated report files and failures, newest first
+#[command]
+pub async fn get_report_history(
+    app: AppHandle,
+    limit: Option<i64>,
+) -> Result<Vec<ReportRecord>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_report_history(&db_path, limit.unwrap_or(50))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Start TikTok Shop scraper
+///
+/// The run is queued behind any other browser job and starts once the
+/// browser profile is free. The subscription's plan decides whether it may
+/// run at all, which categories it covers and how many products it collects.
+#[command]
+pub async fn scrape_tiktok_shop(
+    app: AppHandle,
+    config: ScraperConfig,
+    state: State<'_, ScraperState>,
+    jobs: State<'_, JobState>,
+) -> Result<Vec<Product>, ScrapeError> {
+    let label = if config.categories.is_empty() {
+        "trending".to_string()
+    } else {
+        config.categories.join(", ")
+    };
+    queue_scrape(app, config, label, &state, &jobs).await
+}
+
+/// Continue an interrupted scrape session with the settings it was started
+/// with: finished categories are skipped, the others resume where they were
+/// and products already collected are kept
+#[command]
+pub async fn resume_scrape(
+    app: AppHandle,
+    session_id: String,
+    state: State<'_, ScraperState>,
+    jobs: State<'_, JobState>,
+) -> Result<Vec<Product>, ScrapeError> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let session = database::get_scrape_session(&db_path, &session_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| format!("Scrape session not found: {}", session_id))?;
+    if session.status != "interrupted" {
+        return Err(format!("Scrape session is {}: {}", session.status, session_id).into());
+    }
+    let mut config: ScraperConfig = database::get_scrape_session_config(&db_path, &session_id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or_else(|| format!("Scrape session has no settings: {}", session_id))?;
+    config.session_id = Some(session_id);
+
+    let label = if session.categories.is_empty() {
+        "trending".to_string()
+    } else {
+        session.categories.join(", ")
+    };
+    queue_scrape(app, config, format!("Retomada: {}", label), &state, &jobs).await
+}
+
+/// Scrape sessions that didn't complete, to resume or discard
+#[command]
+pub async fn get_scrape_sessions(app: AppHandle) -> Result<Vec<ScrapeSession>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_scrape_sessions(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Discard a scrape session and the products it had collected
+#[command]
+pub async fn delete_scrape_session(app: AppHandle, session_id: String) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_scrape_session(&db_path, &session_id)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Run a scrape within the plan's limits once the browser profile is free,
+/// as the job `label`
+pub(crate) async fn queue_scrape(
+    app: AppHandle,
+    config: ScraperConfig,
+    label: String,
+    state: &State<'_, ScraperState>,
+    jobs: &State<'_, JobState>,
+) -> Result<Vec<Product>, ScrapeError> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let config = gate_scrape(&entitlements(&app, &app_dir).subscription, config)?;
+
+    let job_id = jobs.0.enqueue(JobKind::Scrape, label).await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let result = run_scrape(app, config, state).await;
+
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+
+    Ok(result?)
+}
+
+/// Products a single run may collect on each plan
+fn max_products_per_run(plan: &PlanTier) -> u32 {
+    match plan {
+        PlanTier::Free => 50,
+        PlanTier::Starter => 200,
+        PlanTier::Business => 1000,
+        PlanTier::Enterprise => 5000,
+    }
+}
+
+/// Marketplace a category belongs to: category URLs by their host, plain
+/// category names are the run's `marketplace`
+fn category_marketplace(category: &str, marketplace: &MarketplaceAccess) -> MarketplaceAccess {
+    let Some(host) = reqwest::Url::parse(category)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase))
+    else {
+        return marketplace.clone();
+    };
+
+    if host.contains("aliexpress") {
+        MarketplaceAccess::Aliexpress
+    } else if host.contains("shopee") {
+        MarketplaceAccess::Shopee
+    } else if host.contains("amazon") {
+        MarketplaceAccess::Amazon
+    } else if host.contains("mercadolivre") || host.contains("mercadolibre") {
+        MarketplaceAccess::Mercadolivre
+    } else {
+        MarketplaceAccess::Tiktok
+    }
+}
+
+/// Apply the plan to a scrape: web-only plans can't run the local scraper,
+/// categories must belong to the plan's marketplaces and max_products is
+/// clamped to the plan's per-run limit
+fn gate_scrape(
+    subscription: &Subscription,
+    mut config: ScraperConfig,
+) -> Result<ScraperConfig, ScrapeError> {
+    if subscription.execution_mode == ExecutionMode::WebOnly {
+        return Err(ScrapeError::WebOnlyPlan);
+    }
+
+    if !scraper::has_scraper(&config.marketplace) {
+        return Err(ScrapeError::MarketplaceNotSupported {
+            marketplace: config.marketplace,
+        });
+    }
+
+    // No categories means the trending page of the selected marketplace
+    let categories: Vec<&str> = if config.categories.is_empty() {
+        vec!["trending"]
+    } else {
+        config.categories.iter().map(String::as_str).collect()
+    };
+    let excluded: Vec<String> = categories
+        .into_iter()
+        .filter(|c| {
+            !subscription
+                .marketplaces
+                .contains(&category_marketplace(c, &config.marketplace))
+        })
+        .map(str::to_string)
+        .collect();
+    if !excluded.is_empty() {
+        return Err(ScrapeError::MarketplaceNotIncluded {
+            categories: excluded,
+        });
+    }
+
+    let limit = max_products_per_run(&subscription.plan_tier);
+    if config.max_products > limit {
+        log::info!(
+            "max_products {} clamped to the plan limit of {}",
+            config.max_products,
+            limit
+        );
+        config.max_products = limit;
+    }
+    Ok(config)
+}
+
+/// Settings config filled in for a run: the app's browser profile, database
+/// and selector manifest
+pub(crate) fn runtime_scraper_config(
+    app_dir: &std::path::Path,
+    mut scraper_config: ScraperConfig,
+) -> ScraperConfig {
+    scraper_config.remote_debugging_url = scraper_config
+        .remote_debugging_url
+        .filter(|url| !url.trim().is_empty());
+
+    // Set user data path for session persistence
+    let user_data = app_dir.join("browser_data");
+    scraper_config.user_data_path = Some(user_data.to_string_lossy().to_string());
+    scraper_config.db_path = Some(app_dir.join("tiktrend.db").to_string_lossy().to_string());
+
+    // Messages in the app language; log entries kept per run when logging is on
+    let settings = load_settings(app_dir);
+    scraper_config.language = settings.language;
+    scraper_config.persist_logs = settings.system.logs_enabled;
+
+    // Load selectors and extraction strategies from the manifest
+    if let Some(manifest) = load_selector_manifest(app_dir) {
+        if !manifest.product_selectors.is_empty() {
+            scraper_config.selectors = Some(manifest.product_selectors);
+        }
+        if !manifest.extraction_strategies.is_empty() {
+            scraper_config.extraction_strategies = Some(manifest.extraction_strategies);
+        }
+        scraper_config.pagination = manifest.pagination;
+        scraper_config.pagination_rules = manifest.pagination_rules;
+    }
+
+    // Backend tunables and kill switches
+    remote_config::apply(
+        &remote_config::effective(&app_dir.join("tiktrend.db")),
+        &mut scraper_config,
+    );
 
+    scraper_config
+}
+
+/// Feature flags and tunables in effect: the backend's, cached for offline
+/// use, with the local overrides
+#[command]
+pub async fn get_remote_config(app: AppHandle) -> Result<RemoteConfig, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let db_path = app_dir.join("tiktrend.db");
+    Ok(remote_config::effective(&app_dir.join("tiktrend.db")))
+}
 
-    let user_id = "default_user".to_string();
+/// Fetch the remote config from the backend now
+#[command]
+pub async fn refresh_remote_config(app: AppHandle) -> Result<RemoteConfig, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    remote_config::refresh(&app_dir.join("tiktrend.db")).await
+}
 
-    database::get_dashboard_stats(&db_path, &user_id).map_err(|e| format!("Database error: {}", e))
+#[command]
+pub async fn get_remote_config_overrides(app: AppHandle) -> Result<RemoteConfig, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(remote_config::overrides(&app_dir.join("tiktrend.db")))
 }
 
-/// Validate license
+/// Replace the local overrides; unset values fall back to the backend's
 #[command]
-pub async fn validate_license(license_key: String) -> Result<License, String> {
-    log::info!("Validating license: {}", license_key);
+pub async fn set_remote_config_overrides(
+    app: AppHandle,
+    overrides: RemoteConfig,
+) -> Result<RemoteConfig, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
 
-    let hwid = get_hardware_id();
-    let client = reqwest::Client::new();
+    remote_config::set_overrides(&db_path, &overrides)?;
+    Ok(remote_config::effective(&db_path))
+}
 
-    let api_payload = json!({
-        "email": license_key,
-        "hwid": hwid,
-        "app_version": "1.0.0"
-    });
+/// Release notes of the updater channel since `since_version`, or since the
+/// version last marked seen, for the "what's new" screen after an update
+#[command]
+pub async fn get_changelog(
+    app: AppHandle,
+    since_version: Option<String>,
+) -> Result<Changelog, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let channel = load_settings(&app_dir).system.update_channel;
+    changelog::get(&app_dir.join("tiktrend.db"), &channel, since_version).await
+}
 
-    match client
-        .post(format!("{}/license/validate", API_URL))
-        .json(&api_payload)
-        .send()
-        .await
+#[command]
+pub async fn mark_changelog_seen(app: AppHandle) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    changelog::mark_seen(&app_dir.join("tiktrend.db"))
+}
+
+async fn run_scrape(
+    app: AppHandle,
+    config: ScraperConfig,
+    state: &State<'_, ScraperState>,
+) -> Result<Vec<Product>, String> {
+    log::info!("Starting {:?} scraper with config: {:?}", config.marketplace, config);
+    let started = std::time::Instant::now();
+    let started_at = Utc::now().to_rfc3339();
+    let category_count = config.categories.len();
+
+    // Update state to running
     {
-        Ok(response) => {
-            if response.status().is_success() {
-                let api_response: serde_json::Value = response
-                    .json()
-                    .await
-                    .map_err(|e| format!("Failed to parse API response: {}", e))?;
+        let mut status = state.0.lock().await;
+        status.is_running = true;
+        status.progress = 0.0;
+        status.products_found = 0;
+        status.errors.clear();
+        status.metrics = ScrapeMetrics::default();
+        status.persisted = PersistSummary::default();
+        status.started_at = Some(Utc::now().to_rfc3339());
+        status.run_id = Some(uuid::Uuid::new_v4().to_string());
+    }
 
-                let features = api_response["features"]
-                    .as_object()
-                    .ok_or("Invalid features format")?;
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
-                Ok(License {
-                    is_valid: api_response["valid"].as_bool().unwrap_or(false),
-                    plan: api_response["plan"].as_str().unwrap_or("lifetime").to_string(),
-                    features: PlanFeatures {
-                        searches_unlimited: features
-                            .get("searches_unlimited")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(true),  // Lifetime = unlimited
-                        favorites_unlimited: features
-                            .get("favorites_unlimited")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(true),  // Lifetime = unlimited
-                        export_enabled: features
-                            .get("export_enabled")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(true),
-                        scheduler_enabled: features
-                            .get("scheduler_enabled")
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(true),
-                    },
-                    expires_at: api_response["expires_at"]
-                        .as_str()
-                        .unwrap_or("")
-                        .to_string(),
-                    credits: api_response["credits"]
-                        .as_i64()
-                        .unwrap_or(0) as i32,
-                })
-            } else {
-                // Fallback to trial if API fails (e.g. 404, 500)
-                // But if 403/401 it means invalid.
-                // For development, let's fallback to trial if connection fails
-                log::warn!("License API error: {}", response.status());
-                Err(format!("License validation failed: {}", response.status()))
+    let log_dir = app.path().app_log_dir().ok();
+    if let Err(e) = disk::check(&app_dir, log_dir.as_deref()) {
+        let mut status = state.0.lock().await;
+        status.is_running = false;
+        status.errors.push(e.clone());
+        return Err(e);
+    }
+    disk::spawn_monitor(app.clone(), state.0.clone());
+
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = load_settings(&app_dir);
+
+    let mut scraper_config = runtime_scraper_config(&app_dir, config);
+    let run_id = state.0.lock().await.run_id.clone().unwrap_or_default();
+    scraper_config.run_id = Some(run_id.clone());
+
+    // Category runs checkpoint a session, so they can be resumed if cut short
+    let offline = scraper_config
+        .offline_folder
+        .as_deref()
+        .is_some_and(|folder| !folder.trim().is_empty());
+    match &scraper_config.session_id {
+        Some(session_id) => {
+            database::set_scrape_session_status(&db_path, session_id, "running", None, &started_at)
+                .ok();
+        }
+        None if !offline => {
+            let session_config = serde_json::to_string(&scraper_config).unwrap_or_default();
+            match database::start_scrape_session(
+                &db_path,
+                &run_id,
+                &session_config,
+                &scraper_config.categories,
+                &started_at,
+            ) {
+                Ok(()) => scraper_config.session_id = Some(run_id.clone()),
+                Err(e) => log::warn!("Failed to start scrape session: {}", e),
             }
         }
+        None => {}
+    }
+
+    // Every run gets a collection log; its log entries are kept under the same id
+    database::start_collection_log(&db_path, &run_id, &started_at).ok();
+    let retention_cutoff =
+        Utc::now() - chrono::Duration::days(settings.system.log_retention_days as i64);
+    if let Err(e) = database::prune_run_logs(&db_path, &retention_cutoff.to_rfc3339()) {
+        log::warn!("Failed to prune run logs: {}", e);
+    }
+    let error_page_days = settings.system.error_page_retention_days as i64;
+    if let Err(e) = database::prune_error_pages(&db_path, error_page_days) {
+        log::warn!("Failed to prune error pages: {}", e);
+    }
+    match database::compact_product_history(&db_path) {
+        Ok(0) => {}
+        Ok(removed) => log::info!("Compacted {} unchanged history snapshots", removed),
+        Err(e) => log::warn!("Failed to compact product history: {}", e),
+    }
+
+    let scraper = scraper::for_marketplace(scraper_config, state.0.clone(), Some(app.clone()))
+        .map_err(|e| e.to_string())?;
+    let mut products = match scraper.start().await {
+        Ok(products) => products,
         Err(e) => {
-            log::warn!("License API connection failed: {}", e);
-            // Fallback to trial for offline dev
-            Ok(License {
-                is_valid: true,
-                plan: String::from("trial (offline)"),
-                features: PlanFeatures {
-                    searches_unlimited: true,
-                    favorites_unlimited: true,
-                    export_enabled: true,
-                    scheduler_enabled: false,
-                },
-                expires_at: chrono::Utc::now()
-                    .checked_add_signed(chrono::Duration::days(7))
-                    .unwrap()
-                    .to_rfc3339(),
-                credits: 10,  // Trial credits
-            })
+            database::finish_collection_log(
+                &db_path,
+                &run_id,
+                "failed",
+                0,
+                0,
+                1,
+                &Utc::now().to_rfc3339(),
+            )
+            .ok();
+            telemetry::track(
+                &app_dir,
+                "scrape_failed",
+                &[
+                    ("error_class", telemetry::error_class(&e.to_string()).to_string()),
+                    ("duration_secs", started.elapsed().as_secs().to_string()),
+                ],
+            );
+            return Err(e.to_string());
+        }
+    };
+
+    // Save products to database, comparing them with what is already stored
+    let mut persisted = PersistSummary::default();
+    let mut changed_ids = Vec::new();
+    for product in &mut products {
+        if let Ok(Some(stored)) = database::get_product_by_tiktok_id(&db_path, &product.tiktok_id)
+        {
+            // Keep the row id so history and links carry over
+            product.id = stored.id;
+        }
+        match database::save_product(&db_path, product) {
+            Ok(SaveOutcome::New) => {
+                persisted.new += 1;
+                changed_ids.push(product.id.clone());
+            }
+            Ok(SaveOutcome::Updated) => {
+                persisted.updated += 1;
+                changed_ids.push(product.id.clone());
+            }
+            Ok(SaveOutcome::Unchanged) => persisted.unchanged += 1,
+            Err(e) => {
+                log::warn!("Failed to save product {}: {}", product.tiktok_id, e);
+                persisted.failed += 1;
+            }
         }
     }
+    let saved = (persisted.new + persisted.updated + persisted.unchanged) as i64;
+    log::info!(
+        "Saved {} new, {} updated and {} unchanged products ({} failed)",
+        persisted.new,
+        persisted.updated,
+        persisted.unchanged,
+        persisted.failed
+    );
+
+    // New listings change how crowded their niches are
+    if let Err(e) = saturation::refresh(&db_path) {
+        log::warn!("Failed to update niche saturation: {}", e);
+    }
+
+    // Hybrid mode: queue the new and updated products for backend upload
+    let entitlements = entitlements(&app, &app_dir);
+    if hybrid_upload_enabled(&entitlements, &settings) {
+        for product_id in &changed_ids {
+            if let Err(e) =
+                database::add_pending_sync(&db_path, "product", product_id, "upsert", None)
+            {
+                log::warn!("Failed to queue product {} for upload: {}", product_id, e);
+            }
+        }
+
+        let upload_app = app.clone();
+        let db_path = db_path.clone();
+        let subscription = entitlements.subscription;
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                upload_pending_products(&upload_app, &db_path, &settings, &subscription).await
+            {
+                log::warn!("Hybrid upload postponed: {}", e);
+            }
+        });
+    }
+
+    // Update status to completed
+    scraper.finish_progress().await;
+    let errors_count = {
+        let mut status = state.0.lock().await;
+        status.is_running = false;
+        status.products_found = products.len() as i32;
+        status.persisted = persisted;
+        status.errors.len() as i64
+    };
+    database::finish_collection_log(
+        &db_path,
+        &run_id,
+        "completed",
+        products.len() as i64,
+        saved,
+        errors_count,
+        &Utc::now().to_rfc3339(),
+    )
+    .ok();
+
+    log::info!("Scraper completed. Found {} products", products.len());
+
+    telemetry::track(
+        &app_dir,
+        "scrape_completed",
+        &[
+            ("duration_secs", started.elapsed().as_secs().to_string()),
+            ("products", products.len().to_string()),
+            ("categories", category_count.to_string()),
+            ("errors", errors_count.to_string()),
+        ],
+    );
+
+    // Scheduled reports of the run's products
+    let report_app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reports::run_after_scrape(&report_app, &run_id, &started_at).await {
+            log::warn!("Scheduled reports failed: {}", e);
+        }
+    });
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = telemetry::flush(&app_dir).await {
+            log::debug!("Telemetry upload postponed: {}", e);
+        }
+    });
+
+    Ok(products)
 }
 
-fn check_disk_space(path: &std::path::Path) -> Result<(), String> {
-    let disks = Disks::new_with_refreshed_list();
-    for disk in &disks {
-        if path.starts_with(disk.mount_point()) {
-            if disk.available_space() < 1_000_000_000 {
-                return Err(
-                    "Espaço em disco insuficiente (< 1GB). Libere espaço para continuar."
-                        .to_string(),
-                );
+/// Get scraper status
+#[command]
+pub async fn get_scraper_status(state: State<'_, ScraperState>) -> Result<ScraperStatus, String> {
+    let status = state.0.lock().await;
+    Ok(status.clone())
+}
+
+/// Pages the scraper was blocked on, newest first, without their HTML
+#[command]
+pub async fn list_error_pages(
+    app: AppHandle,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> Result<Vec<ErrorPageSummary>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_error_pages(&db_path, limit.unwrap_or(50), offset.unwrap_or(0))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[command]
+pub async fn get_error_page(app: AppHandle, id: i64) -> Result<Option<ErrorPage>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_error_page(&db_path, id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Delete error pages stored before `before` (RFC 3339), or all of them;
+/// returns how many were deleted
+#[command]
+pub async fn delete_error_pages(app: AppHandle, before: Option<String>) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::delete_error_pages(&db_path, before.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Persisted log entries of a run at `level` or above
+#[command]
+pub async fn get_run_logs(
+    app: AppHandle,
+    run_id: String,
+    level: Option<LogLevel>,
+) -> Result<Vec<LogEntry>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_run_logs(&db_path, &run_id, level).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Log entries of the current or last run at `min_level` or above, optionally
+/// only those of one category
+#[command]
+pub async fn get_scraper_logs(
+    state: State<'_, ScraperState>,
+    min_level: Option<LogLevel>,
+    category: Option<String>,
+) -> Result<Vec<LogEntry>, String> {
+    let status = state.0.lock().await;
+    Ok(status
+        .logs
+        .iter()
+        .filter(|entry| min_level.is_none_or(|level| entry.level >= level))
+        .filter(|entry| category.is_none() || entry.category == category)
+        .cloned()
+        .collect())
+}
+
+/// Stop running scraper
+#[command]
+pub async fn stop_scraper(state: State<'_, ScraperState>) -> Result<bool, String> {
+    let mut status = state.0.lock().await;
+    if status.is_running {
+        status.is_running = false;
+        log::info!("Scraper stopped by user");
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Navigations, scrolls, clicks and waits recorded during a run, in order
+#[command]
+pub async fn get_run_actions(app: AppHandle, run_id: String) -> Result<Vec<RunAction>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_run_actions(&db_path, &run_id).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Run the parse pipeline over recorded fixtures (.html pages, .json API
+/// responses) and report products/sec, per-stage timings and memory
+#[command]
+pub async fn benchmark_scraper(
+    app: AppHandle,
+    fixture_dir: String,
+    iterations: Option<u32>,
+) -> Result<BenchmarkReport, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let config = load_settings(&app_dir).scraper;
+
+    // Same selectors and strategies as a real scrape
+    let manifest = load_selector_manifest(&app_dir);
+    let selectors = manifest
+        .as_ref()
+        .map(|m| m.product_selectors.clone())
+        .filter(|s| !s.is_empty());
+    let strategies = manifest
+        .map(|m| m.extraction_strategies)
+        .unwrap_or_default();
+    let parser = TikTokParser::new(selectors)
+        .with_locale(NumberLocale::from_region(&config.region))
+        .with_strategies(strategies);
+
+    let iterations = iterations.unwrap_or(10);
+    tokio::task::spawn_blocking(move || {
+        crate::scraper::benchmark::run_benchmark(
+            &parser,
+            std::path::Path::new(&fixture_dir),
+            iterations,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Give a session profile a brand new fingerprint for its next scrape
+#[command]
+pub async fn rotate_fingerprint(app: AppHandle, profile_id: String) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    AntiDetection::new()
+        .rotate_fingerprint(&db_path, &profile_id)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Load bot-detection test pages in the scraper browser and report leaking signals.
+///
+/// Uses the same profile, launch flags and fingerprint as scrapes, and runs
+/// through the job queue since it needs the browser profile.
+#[command]
+pub async fn run_stealth_check(
+    app: AppHandle,
+    jobs: State<'_, JobState>,
+) -> Result<StealthReport, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let config = load_settings(&app_dir).scraper;
+
+    let job_id = jobs
+        .0
+        .enqueue(JobKind::StealthCheck, "Teste de detecção".to_string())
+        .await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let fingerprint = AntiDetection::new()
+        .profile_fingerprint(&db_path, &config.profile_id)
+        .map_err(|e| e.to_string());
+
+    let result = match fingerprint {
+        Ok(fingerprint) => {
+            let mut browser = BrowserManager::new(config.headless)
+                .with_profile(config.launch_profile)
+                .with_user_data(app_dir.join("browser_data"));
+            if let Some(url) = config.remote_debugging_url.filter(|url| !url.trim().is_empty()) {
+                browser = browser.with_remote_debugging(url);
             }
+            let check = StealthCheck::new(browser, fingerprint);
+            jobs.0
+                .until_cancelled(&job_id, check.run())
+                .await
+                .and_then(|report| report.map_err(|e| e.to_string()))
+        }
+        Err(e) => Err(e),
+    };
+
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+
+    result
+}
+
+/// List queued, running and recently finished browser jobs
+#[command]
+pub async fn get_job_queue(jobs: State<'_, JobState>) -> Result<Vec<JobInfo>, String> {
+    Ok(jobs.0.list().await)
+}
+
+/// Cancel a job. Queued jobs are dropped; a running scrape is asked to stop
+/// and other running jobs drop their work (see `JobQueue::until_cancelled`).
+#[command]
+pub async fn cancel_job(
+    id: String,
+    jobs: State<'_, JobState>,
+    state: State<'_, ScraperState>,
+) -> Result<bool, String> {
+    match jobs.0.cancel(&id).await {
+        Some(JobStatus::Queued) => Ok(true),
+        Some(JobStatus::Running) => {
+            state.0.lock().await.is_running = false;
+            log::info!("Running job {} cancelled by user", id);
+            Ok(true)
         }
+        _ => Ok(false),
     }
-    Ok(())
 }
 
-/// Start TikTok Shop scraper
+/// Refresh commission rates and open-collaboration status from the affiliate center.
+///
+/// Uses the scraper's browser profile, so the user must be logged into the
+/// affiliate center there. Runs through the job queue like scrapes do.
+/// Products of other marketplaces are left out.
 #[command]
-pub async fn scrape_tiktok_shop(
+pub async fn refresh_commissions(
     app: AppHandle,
-    config: ScraperConfig,
-    state: State<'_, ScraperState>,
+    product_ids: Vec<String>,
+    jobs: State<'_, JobState>,
 ) -> Result<Vec<Product>, String> {
-    log::info!("Starting TikTok Shop scraper with config: {:?}", config);
-
-    // Update state to running
-    {
-        let mut status = state.0.lock().await;
-        status.is_running = true;
-        status.progress = 0.0;
-        status.products_found = 0;
-        status.errors.clear();
-        status.started_at = Some(Utc::now().to_rfc3339());
-    }
-
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
 
-    if let Err(e) = check_disk_space(&app_dir) {
-        let mut status = state.0.lock().await;
-        status.is_running = false;
-        status.errors.push(e.clone());
-        return Err(e);
+    // Commissions come from the TikTok Shop affiliate center
+    let products: Vec<Product> = product_ids
+        .iter()
+        .filter_map(|id| database::get_product_by_id(&db_path, id).ok().flatten())
+        .filter(|product| product.marketplace == MarketplaceAccess::Tiktok)
+        .collect();
+
+    if products.is_empty() {
+        return Ok(vec![]);
     }
 
-    let db_path = app_dir.join("tiktrend.db");
+    let label = format!("{} produtos", products.len());
+    let job_id = jobs.0.enqueue(JobKind::Commissions, label).await;
+    jobs.0.wait_for_turn(&job_id).await?;
 
-    // Convert config to scraper config
-    let mut scraper_config = crate::scraper::models::ScraperConfig::from(config);
+    let headless = load_settings(&app_dir).scraper.headless;
+    let result = fetch_commissions(&app_dir, &db_path, &products, headless, &jobs, &job_id).await;
 
-    // Set user data path for session persistence
-    let user_data = app_dir.join("browser_data");
-    scraper_config.user_data_path = Some(user_data.to_string_lossy().to_string());
-    scraper_config.db_path = Some(db_path.to_string_lossy().to_string());
-
-    // Load selectors from file
-    let selectors_path = app_dir.join("selectors.json");
-    if selectors_path.exists() {
-        if let Ok(content) = fs::read_to_string(selectors_path) {
-            if let Ok(selectors) = serde_json::from_str::<Vec<String>>(&content) {
-                scraper_config.selectors = Some(selectors);
-            }
-        }
-    }
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
 
-    let scraper = TikTokScraper::new(scraper_config, state.0.clone(), Some(app.clone()));
-    let products = scraper.start().await.map_err(|e| e.to_string())?;
+    result
+}
 
-    // Save products to database
-    for product in &products {
-        database::save_product(&db_path, product).ok();
-    }
+async fn fetch_commissions(
+    app_dir: &std::path::Path,
+    db_path: &std::path::Path,
+    products: &[Product],
+    headless: bool,
+    jobs: &State<'_, JobState>,
+    job_id: &str,
+) -> Result<Vec<Product>, String> {
+    let center = AffiliateCenter::new(app_dir.join("browser_data"), headless);
+    let page = center.open().await.map_err(|e| e.to_string())?;
 
-    // Update status to completed
-    {
-        let mut status = state.0.lock().await;
-        status.is_running = false;
-        status.progress = 100.0;
-        status.products_found = products.len() as i32;
-    }
+    let mut updated = Vec::new();
+    let mut failure = None;
 
-    log::info!("Scraper completed. Found {} products", products.len());
+    for product in products {
+        if jobs.0.is_cancelled(job_id).await {
+            break;
+        }
 
-    Ok(products)
-}
+        match center.fetch_commission(&page, &product.tiktok_id).await {
+            Ok(info) => {
+                database::update_product_commission(
+                    db_path,
+                    &product.id,
+                    info.commission_rate,
+                    info.open_collaboration,
+                )
+                .map_err(|e| format!("Database error: {}", e))?;
+
+                if let Ok(Some(p)) = database::get_product_by_id(db_path, &product.id) {
+                    updated.push(p);
+                }
+            }
+            Err(e) => {
+                log::warn!("Commission refresh failed for {}: {}", product.tiktok_id, e);
+                // An expired session fails every product the same way
+                let session_expired = e.to_string().contains("session expired");
+                failure = Some(e.to_string());
+                if session_expired {
+                    break;
+                }
+            }
+        }
+    }
 
-/// Get scraper status
-#[command]
-pub async fn get_scraper_status(state: State<'_, ScraperState>) -> Result<ScraperStatus, String> {
-    let status = state.0.lock().await;
-    Ok(status.clone())
-}
+    center.close().await.ok();
 
-/// Stop running scraper
-#[command]
-pub async fn stop_scraper(state: State<'_, ScraperState>) -> Result<bool, String> {
-    let mut status = state.0.lock().await;
-    if status.is_running {
-        status.is_running = false;
-        log::info!("Scraper stopped by user");
-        Ok(true)
-    } else {
-        Ok(false)
+    match failure {
+        Some(e) if updated.is_empty() => Err(e),
+        _ => Ok(updated),
     }
 }
 
@@ -551,10 +3039,32 @@ pub async fn get_search_history(
 #[command]
 pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let config_path = app_dir.join("settings.json");
+    write_settings(&app, &app_dir, &settings)
+}
+
+/// Write settings.json and apply what takes effect right away (shortcuts,
+/// the extension endpoint)
+fn write_settings(
+    app: &AppHandle,
+    app_dir: &std::path::Path,
+    settings: &AppSettings,
+) -> Result<(), String> {
+    // Refuse invalid shortcuts before they are saved and fail on every launch
+    crate::shortcuts::validate(&settings.shortcuts)?;
+
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(app_dir.join("settings.json"), content).map_err(|e| e.to_string())?;
+    invalidate_entitlements(app);
+
+    // Opting out of analytics drops what was not sent yet
+    if !settings.system.analytics_enabled {
+        let _ = database::clear_telemetry_events(&app_dir.join("tiktrend.db"), true);
+    }
 
-    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(config_path, content).map_err(|e| e.to_string())?;
+    crate::shortcuts::register(app, &settings.shortcuts)?;
+    if settings.companion.enabled {
+        companion::spawn_server(app.clone(), settings.companion.port);
+    }
 
     Ok(())
 }
@@ -563,25 +3073,160 @@ pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(),
 #[command]
 pub async fn get_settings(app: AppHandle) -> Result<AppSettings, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let config_path = app_dir.join("settings.json");
+    Ok(load_settings(&app_dir))
+}
+
+/// Read settings.json, falling back to defaults when missing or invalid
+pub(crate) fn load_settings(app_dir: &std::path::Path) -> AppSettings {
+    fs::read_to_string(app_dir.join("settings.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Version of the settings export format
+const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// Export settings to a file, without API keys, the license key or proxy
+/// passwords
+#[command]
+pub async fn export_settings(app: AppHandle, path: String) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let export = SettingsExport {
+        version: SETTINGS_EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        settings: load_settings(&app_dir).without_secrets(),
+    };
+
+    let content = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    log::info!("Settings exported to {}", path);
+    Ok(())
+}
+
+/// Import settings exported by `export_settings`. Credentials, the license
+/// and setup state are kept from this install.
+#[command]
+pub async fn import_settings(app: AppHandle, path: String) -> Result<AppSettings, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    // Accept a bare settings.json as well as an export file
+    let imported = serde_json::from_str::<SettingsExport>(&content)
+        .map(|export| export.settings)
+        .or_else(|_| serde_json::from_str::<AppSettings>(&content))
+        .map_err(|e| format!("Invalid settings file: {}", e))?;
+
+    let mut settings = imported.with_account_of(&load_settings(&app_dir));
+    settings.active_profile = None;
+    write_settings(&app, &app_dir, &settings)?;
+    log::info!("Settings imported from {}", path);
+    Ok(settings)
+}
+
+fn load_settings_profiles(app_dir: &std::path::Path) -> Vec<SettingsProfile> {
+    fs::read_to_string(app_dir.join("settings_profiles.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn store_settings_profiles(
+    app_dir: &std::path::Path,
+    profiles: &[SettingsProfile],
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    fs::write(app_dir.join("settings_profiles.json"), content).map_err(|e| e.to_string())
+}
+
+/// List the named settings profiles
+#[command]
+pub async fn list_settings_profiles(app: AppHandle) -> Result<Vec<SettingsProfile>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(load_settings_profiles(&app_dir))
+}
 
-    if !config_path.exists() {
-        return Ok(AppSettings::default());
+/// Save the current settings as a named profile, replacing one with the same
+/// name
+#[command]
+pub async fn save_settings_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<SettingsProfile, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
     }
 
-    let content = fs::read_to_string(config_path).map_err(|e| e.to_string())?;
-    let settings: AppSettings = serde_json::from_str(&content).unwrap_or_default();
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut settings = load_settings(&app_dir);
+    settings.active_profile = Some(name.clone());
+    let profile = SettingsProfile {
+        name: name.clone(),
+        saved_at: Utc::now().to_rfc3339(),
+        settings: settings.clone(),
+    };
+
+    let mut profiles = load_settings_profiles(&app_dir);
+    profiles.retain(|p| p.name != name);
+    profiles.push(profile.clone());
+    store_settings_profiles(&app_dir, &profiles)?;
+    write_settings(&app, &app_dir, &settings)?;
+
+    Ok(profile)
+}
 
+/// Switch to a named profile. Credentials, the license and setup state stay
+/// as they are.
+#[command]
+pub async fn switch_settings_profile(
+    app: AppHandle,
+    name: String,
+) -> Result<AppSettings, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let profile = load_settings_profiles(&app_dir)
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Settings profile not found: {}", name))?;
+
+    let mut settings = profile.settings.with_account_of(&load_settings(&app_dir));
+    settings.active_profile = Some(profile.name);
+    write_settings(&app, &app_dir, &settings)?;
+    log::info!("Switched to settings profile {}", name);
     Ok(settings)
 }
 
-/// Export products to file
+/// Delete a named settings profile
+#[command]
+pub async fn delete_settings_profile(app: AppHandle, name: String) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut profiles = load_settings_profiles(&app_dir);
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Err(format!("Settings profile not found: {}", name));
+    }
+    store_settings_profiles(&app_dir, &profiles)?;
+
+    let mut settings = load_settings(&app_dir);
+    if settings.active_profile.as_deref() == Some(name.as_str()) {
+        settings.active_profile = None;
+        write_settings(&app, &app_dir, &settings)?;
+    }
+    Ok(())
+}
+
+/// Export products to file. With `include_history`, the price and sales
+/// history of the products goes to a second sheet for "xlsx", and to a
+/// second file next to `path` ending in "_history" otherwise.
 #[command]
 pub async fn export_products(
     app: AppHandle,
     product_ids: Vec<String>,
     format: String,
     path: String,
+    include_history: Option<bool>,
 ) -> Result<String, String> {
     log::info!(
         "Exporting {} products to {} as {}",
@@ -593,93 +3238,479 @@ pub async fn export_products(
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_dir.join("tiktrend.db");
 
-    // Get products
-    let mut products = Vec::new();
-    for id in product_ids {
-        if let Ok(Some(product)) = database::get_product_by_id(&db_path, &id) {
-            products.push(product);
+    // Get products
+    let mut products = Vec::new();
+    for id in product_ids {
+        if let Ok(Some(product)) = database::get_product_by_id(&db_path, &id) {
+            products.push(product);
+        }
+    }
+
+    if !include_history.unwrap_or(false) {
+        let output = render_products(&products, &format)?;
+        std::fs::write(&path, &output).map_err(|e| e.to_string())?;
+        return Ok(path);
+    }
+
+    let mut history = Vec::new();
+    for product in &products {
+        history.extend(
+            database::get_product_history(&db_path, &product.id)
+                .map_err(|e| format!("Database error: {}", e))?,
+        );
+    }
+
+    if format == "xlsx" {
+        let output = export_to_xlsx(&products, Some(&history))?;
+        std::fs::write(&path, &output).map_err(|e| e.to_string())?;
+    } else {
+        let output = render_products(&products, &format)?;
+        let history_output = render_history(&products, &history, &format)?;
+        std::fs::write(&path, &output).map_err(|e| e.to_string())?;
+        std::fs::write(history_path(&path), &history_output).map_err(|e| e.to_string())?;
+    }
+
+    Ok(path)
+}
+
+/// `path` with "_history" added to the file name: "out.csv" -> "out_history.csv"
+fn history_path(path: &str) -> std::path::PathBuf {
+    let path = std::path::Path::new(path);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}_history.{}", stem, ext.to_string_lossy()),
+        None => format!("{}_history", stem),
+    };
+    path.with_file_name(name)
+}
+
+/// Find rows referring to deleted rows and, unless `dry_run`, remove them or
+/// clear the reference
+#[command]
+pub async fn cleanup_orphans(
+    app: AppHandle,
+    dry_run: Option<bool>,
+) -> Result<Vec<OrphanRows>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let orphans = database::cleanup_orphans(&db_path, dry_run.unwrap_or(false))
+        .map_err(|e| format!("Database error: {}", e))?;
+    for o in &orphans {
+        log::info!(
+            "Orphans in {}.{} (-> {}): {} rows",
+            o.table,
+            o.column,
+            o.parent,
+            o.rows
+        );
+    }
+    Ok(orphans)
+}
+
+/// Status of every subsystem the app depends on, for the health banner
+#[command]
+pub async fn get_app_health(app: AppHandle) -> Result<AppHealth, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(health::check_all(&app_dir).await)
+}
+
+/// Number of demo products the onboarding seeds
+const ONBOARDING_DEMO_PRODUCTS: usize = 100;
+
+/// Settings key holding when the first-run setup last completed
+const ONBOARDING_KEY: &str = "onboarding_completed_at";
+
+/// Guided first-run setup for the onboarding wizard: creates the database,
+/// looks for a browser, runs the stealth self-test and, when asked, seeds
+/// demo products into an empty database. Each step is emitted on
+/// `onboarding://step` as it finishes; a failed step doesn't stop the rest.
+#[command]
+pub async fn initialize_app(
+    app: AppHandle,
+    jobs: State<'_, JobState>,
+    seed_demo: Option<bool>,
+) -> Result<OnboardingReport, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = load_settings(&app_dir);
+
+    let mut steps = Vec::new();
+    let mut report = |name: &str, status: StepStatus, message: String| {
+        let step = OnboardingStep {
+            name: name.to_string(),
+            status,
+            message,
+        };
+        let _ = app.emit("onboarding://step", &step);
+        steps.push(step);
+    };
+
+    let database = fs::create_dir_all(&app_dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| database::init_database(&db_path).map_err(|e| e.to_string()));
+    let database_ok = database.is_ok();
+    match database {
+        Ok(()) => report("database", StepStatus::Ok, "Banco de dados pronto".to_string()),
+        Err(e) => report("database", StepStatus::Failed, e),
+    }
+
+    // Chromium is not bundled; the scraper uses the installed Chrome/Chromium
+    let browser = health::find_browser(&settings);
+    let browser_ok = browser.is_ok();
+    match browser {
+        Ok(message) => report("browser", StepStatus::Ok, message),
+        Err(message) => report("browser", StepStatus::Failed, message),
+    }
+
+    if browser_ok && database_ok {
+        match run_stealth_check(app.clone(), jobs).await {
+            Ok(stealth) if stealth.leaks == 0 => report(
+                "stealth_check",
+                StepStatus::Ok,
+                "Nenhum sinal de automação detectado".to_string(),
+            ),
+            Ok(stealth) => report(
+                "stealth_check",
+                StepStatus::Warning,
+                format!("{} sinais de automação detectados", stealth.leaks),
+            ),
+            Err(e) => report("stealth_check", StepStatus::Failed, e),
+        }
+    } else {
+        report(
+            "stealth_check",
+            StepStatus::Skipped,
+            "Requer o banco de dados e um navegador".to_string(),
+        );
+    }
+
+    let existing = database::count_products_updated_since(&db_path, None).unwrap_or(0);
+    if !seed_demo.unwrap_or(false) || !database_ok {
+        report("demo_data", StepStatus::Skipped, "Não solicitado".to_string());
+    } else if existing > 0 {
+        report(
+            "demo_data",
+            StepStatus::Skipped,
+            format!("O banco já tem {} produtos", existing),
+        );
+    } else {
+        let saved = demo::seed(&db_path, ONBOARDING_DEMO_PRODUCTS);
+        let status = if saved == ONBOARDING_DEMO_PRODUCTS {
+            StepStatus::Ok
+        } else {
+            StepStatus::Warning
+        };
+        report(
+            "demo_data",
+            status,
+            format!("{} produtos de demonstração adicionados", saved),
+        );
+    }
+
+    let completed = steps.iter().all(|step| step.status != StepStatus::Failed);
+    if completed {
+        database::set_setting(&db_path, ONBOARDING_KEY, &Utc::now().to_rfc3339()).ok();
+    }
+
+    Ok(OnboardingReport { steps, completed })
+}
+
+/// Most demo products a single seed may add
+const MAX_DEMO_PRODUCTS: u32 = 1000;
+
+/// Fill the database with flagged demo products to explore the UI before
+/// the first scrape; returns how many were added
+#[command]
+pub async fn seed_demo_data(app: AppHandle, count: Option<u32>) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let count = count.unwrap_or(ONBOARDING_DEMO_PRODUCTS as u32).min(MAX_DEMO_PRODUCTS);
+    Ok(demo::seed(&db_path, count as usize))
+}
+
+/// Remove the demo products; returns how many were removed
+#[command]
+pub async fn clear_demo_data(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::clear_demo_products(&db_path).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Test proxy connection
+#[command]
+pub async fn test_proxy(proxy: String) -> Result<bool, String> {
+    log::info!("Testing proxy: {}", proxy);
+
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::all(&proxy).map_err(|e| e.to_string())?)
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let res = client
+        .get("https://api.ipify.org?format=json")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(res.status().is_success())
+}
+
+/// Sync products with backend
+///
+/// Uploads products in chunks of `settings.sync.batch_size`. Unless `full` is
+/// set, only products updated since the last successful sync are sent. Each
+/// chunk is retried with exponential backoff and progress is emitted on
+/// `sync://progress`.
+#[command]
+pub async fn sync_products(app: AppHandle, full: Option<bool>) -> Result<i32, String> {
+    log::info!("Syncing products with backend...");
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = load_settings(&app_dir);
+
+    let batch_size = settings.sync.batch_size.max(1) as i64;
+    let since = if full.unwrap_or(false) {
+        None
+    } else {
+        database::get_setting(&db_path, LAST_SYNC_KEY).map_err(|e| e.to_string())?
+    };
+
+    // Taken before reading so rows updated during the upload go in the next delta
+    let sync_started_at = Utc::now().to_rfc3339();
+
+    let total = database::count_products_updated_since(&db_path, since.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    if total == 0 {
+        database::set_setting(&db_path, LAST_SYNC_KEY, &sync_started_at)
+            .map_err(|e| e.to_string())?;
+        return Ok(0);
+    }
+
+    let total_chunks = (total + batch_size - 1) / batch_size;
+    let mut synced = 0;
+
+    for chunk_index in 0..total_chunks {
+        let chunk = database::get_products_updated_since(
+            &db_path,
+            since.as_deref(),
+            batch_size,
+            chunk_index * batch_size,
+        )
+        .map_err(|e| e.to_string())?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        upload_chunk(&chunk, settings.sync.max_retries).await?;
+        synced += chunk.len() as i32;
+
+        let _ = app.emit(
+            "sync://progress",
+            SyncProgress {
+                chunk: chunk_index as i32 + 1,
+                total_chunks: total_chunks as i32,
+                synced,
+                total: total as i32,
+                is_delta: since.is_some(),
+            },
+        );
+    }
+
+    database::set_setting(&db_path, LAST_SYNC_KEY, &sync_started_at)
+        .map_err(|e| e.to_string())?;
+
+    log::info!("Synced {} products in {} chunks", synced, total_chunks);
+    Ok(synced)
+}
+
+const LAST_SYNC_KEY: &str = "last_product_sync_at";
+
+/// Get backend sync state (last sync time and queued uploads)
+#[command]
+pub async fn get_sync_state(app: AppHandle) -> Result<SyncState, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = load_settings(&app_dir);
+
+    let entitlements = entitlements(&app, &app_dir);
+
+    Ok(SyncState {
+        last_sync_at: database::get_setting(&db_path, LAST_SYNC_KEY)
+            .map_err(|e| format!("Database error: {}", e))?,
+        pending_count: database::count_pending_sync(&db_path, Some("product"))
+            .map_err(|e| format!("Database error: {}", e))?,
+        auto_upload_enabled: hybrid_upload_enabled(&entitlements, &settings),
+        execution_mode: entitlements.execution_mode().clone(),
+    })
+}
+
+/// Whether scraped products should be queued for upload automatically
+fn hybrid_upload_enabled(entitlements: &Entitlements, settings: &AppSettings) -> bool {
+    settings.sync.auto_upload
+        && *entitlements.execution_mode() == ExecutionMode::Hybrid
+        && entitlements.has("hybrid_sync")
+}
+
+/// Upload products waiting in pending_sync, removing them once accepted.
+/// Every product sent counts against the plan's api_calls limit, so batches
+/// are cut to what is left of it.
+async fn upload_pending_products(
+    app: &AppHandle,
+    db_path: &std::path::Path,
+    settings: &AppSettings,
+    subscription: &Subscription,
+) -> Result<i32, String> {
+    let pending: Vec<_> = database::get_pending_sync(db_path)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(_, entity_type, _, _, _)| entity_type == "product")
+        .collect();
+
+    let mut uploaded = 0;
+    let mut remaining = pending.as_slice();
+
+    while !remaining.is_empty() {
+        let quota = usage::ensure_available(db_path, subscription, "api_calls", Utc::now())?;
+        let mut batch_size = settings.sync.batch_size.max(1) as usize;
+        if quota.limit > 0 {
+            batch_size = batch_size.min((quota.limit - quota.used) as usize);
+        }
+        let (chunk, rest) = remaining.split_at(batch_size.min(remaining.len()));
+        remaining = rest;
+
+        let mut products = Vec::new();
+        for (id, _, product_id, _, _) in chunk {
+            match database::get_product_by_id(db_path, product_id) {
+                Ok(Some(product)) => products.push(product),
+                // Product was deleted meanwhile - nothing left to upload
+                _ => database::remove_pending_sync(db_path, id).map_err(|e| e.to_string())?,
+            }
+        }
+
+        if products.is_empty() {
+            continue;
+        }
+
+        if let Err(e) = upload_chunk(&products, settings.sync.max_retries).await {
+            for (id, _, _, _, _) in chunk {
+                database::mark_pending_sync_failed(db_path, id, &e).ok();
+            }
+            return Err(e);
+        }
+
+        usage::record_and_warn(
+            app,
+            db_path,
+            subscription,
+            "api_calls",
+            products.len() as i32,
+        )
+        .map_err(|e| format!("Database error: {}", e))?;
+
+        for (id, _, _, _, _) in chunk {
+            database::remove_pending_sync(db_path, id).map_err(|e| e.to_string())?;
         }
+        uploaded += products.len() as i32;
     }
 
-    // Export based on format
-    let output = match format.as_str() {
-        "csv" => export_to_csv(&products)?,
-        "json" => serde_json::to_string_pretty(&products).map_err(|e| e.to_string())?,
-        _ => return Err("Unsupported format".to_string()),
-    };
-
-    // Write to file
-    std::fs::write(&path, &output).map_err(|e| e.to_string())?;
+    if uploaded > 0 {
+        log::info!("Hybrid upload: {} products sent to backend", uploaded);
+    }
+    Ok(uploaded)
+}
 
-    Ok(path)
+async fn upload_chunk(products: &[Product], max_retries: u32) -> Result<(), String> {
+    let res = backend::send_retrying(
+        backend::post("/api/products/batch").json(products),
+        max_retries,
+    )
+    .await?;
+    if !res.status().is_success() {
+        return Err(format!("Sync failed: {}", res.status()));
+    }
+    Ok(())
 }
 
-/// Test proxy connection
+/// Update scraper selectors, keeping the manifest's extraction strategies
 #[command]
-pub async fn test_proxy(proxy: String) -> Result<bool, String> {
-    log::info!("Testing proxy: {}", proxy);
-
-    let client = reqwest::Client::builder()
-        .proxy(reqwest::Proxy::all(&proxy).map_err(|e| e.to_string())?)
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let res = client
-        .get("https://api.ipify.org?format=json")
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn update_selectors(app: AppHandle, selectors: Vec<String>) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut manifest = load_selector_manifest(&app_dir).unwrap_or_default();
+    manifest.product_selectors = selectors;
+    save_selector_manifest(&app_dir, &manifest)
+}
 
-    Ok(res.status().is_success())
+/// Get the selector manifest, with built-in strategies filled in when unset
+#[command]
+pub async fn get_selector_manifest(app: AppHandle) -> Result<SelectorManifest, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut manifest = load_selector_manifest(&app_dir).unwrap_or_default();
+    if manifest.extraction_strategies.is_empty() {
+        manifest.extraction_strategies = ExtractionStrategy::defaults();
+    }
+    Ok(manifest)
 }
 
-/// Sync products with backend
+/// Replace the selector manifest
 #[command]
-pub async fn sync_products(app: AppHandle) -> Result<i32, String> {
-    log::info!("Syncing products with backend...");
+pub async fn update_selector_manifest(
+    app: AppHandle,
+    manifest: SelectorManifest,
+) -> Result<(), String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let db_path = app_dir.join("tiktrend.db");
+    save_selector_manifest(&app_dir, &manifest)
+}
 
-    // Get all products
-    let filters = SearchFilters {
-        page_size: Some(1000), // Batch size
-        ..Default::default()
-    };
+/// Download the latest selector manifest from the backend
+#[command]
+pub async fn sync_selector_manifest(app: AppHandle) -> Result<SelectorManifest, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
 
-    let result = database::search_products(&db_path, &filters).map_err(|e| e.to_string())?;
+    let res = backend::send(backend::get("/api/scraper/manifest")).await?;
 
-    if result.data.is_empty() {
-        return Ok(0);
+    if !res.status().is_success() {
+        return Err(format!("Manifest request failed: {}", res.status()));
     }
 
-    let client = reqwest::Client::new();
-    let res = client
-        .post(format!("{}/api/products/batch", API_URL))
-        .json(&result.data)
-        .send()
+    let manifest = res
+        .json::<SelectorManifest>()
         .await
         .map_err(|e| e.to_string())?;
+    save_selector_manifest(&app_dir, &manifest)?;
 
-    if res.status().is_success() {
-        log::info!("Synced {} products", result.data.len());
-        Ok(result.data.len() as i32)
-    } else {
-        Err(format!("Sync failed: {}", res.status()))
-    }
+    log::info!(
+        "Selector manifest {} synced ({} strategies)",
+        manifest.version.as_deref().unwrap_or("?"),
+        manifest.extraction_strategies.len()
+    );
+    Ok(manifest)
 }
 
-/// Update scraper selectors
-#[command]
-pub async fn update_selectors(app: AppHandle, selectors: Vec<String>) -> Result<(), String> {
-    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let selectors_path = app_dir.join("selectors.json");
-    let content = serde_json::to_string(&selectors).map_err(|e| e.to_string())?;
-    fs::write(selectors_path, content).map_err(|e| e.to_string())?;
-    Ok(())
+fn load_selector_manifest(app_dir: &std::path::Path) -> Option<SelectorManifest> {
+    let content = fs::read_to_string(app_dir.join("selectors.json")).ok()?;
+    SelectorManifest::from_json(&content)
+}
+
+fn save_selector_manifest(
+    app_dir: &std::path::Path,
+    manifest: &SelectorManifest,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(app_dir.join("selectors.json"), content).map_err(|e| e.to_string())
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+/// Scrape job handed out by the backend
+#[derive(serde::Serialize, serde::Deserialize, TS)]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct Job {
     pub id: String,
     pub config: ScraperConfig,
@@ -688,12 +3719,7 @@ pub struct Job {
 /// Fetch pending job from backend
 #[command]
 pub async fn fetch_job() -> Result<Option<Job>, String> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get(format!("{}/api/jobs/pending", API_URL))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+    let res = backend::send(backend::get("/api/jobs/pending")).await?;
 
     if res.status().is_success() {
         let job = res.json::<Job>().await.map_err(|e| e.to_string())?;
@@ -718,51 +3744,56 @@ pub async fn get_product_history(
 }
 
 // Helper function to generate copy content
-fn generate_copy_content(product: &Product, copy_type: &str, tone: &str) -> String {
+fn generate_copy_content(
+    product: &Product,
+    copy_type: &str,
+    tone: &str,
+    number_format: &NumberFormat,
+) -> String {
     let emoji_fire = if tone == "urgent" { "🔥" } else { "" };
     let emoji_star = "⭐";
     let emoji_cart = "🛒";
 
     match copy_type {
         "tiktok_hook" => format!(
-            "{} VOCÊ PRECISA VER ISSO!\n\n{} está BOMBANDO no TikTok!\n\n✅ {} vendidos\n✅ Avaliação {:.1}/5 {}\n✅ {}\n\nPor apenas R${:.2} 😱\n\n👇 Link na bio\n#tiktokmademebuyit #achados #compras",
+            "{} VOCÊ PRECISA VER ISSO!\n\n{} está BOMBANDO no TikTok!\n\n✅ {} vendidos\n✅ Avaliação {:.1}/5 {}\n✅ {}\n\nPor apenas {} 😱\n\n👇 Link na bio\n#tiktokmademebuyit #achados #compras",
             emoji_fire,
             product.title,
             product.sales_count,
             product.product_rating.unwrap_or(4.5),
             emoji_star,
             if product.has_free_shipping { "FRETE GRÁTIS!" } else { "Entrega rápida" },
-            product.price
+            number_format.price(product.price)
         ),
         "facebook_ad" => format!(
-            "🎯 {} {}\n\n{}\n\n✨ Benefícios:\n• Alta qualidade garantida\n• {} avaliações positivas\n• {} vendidos e contando!\n\n💰 De R${:.2} por apenas R${:.2}\n{}\n\n🔗 Clique em \"Saiba Mais\" e aproveite!\n\n#dropshipping #ofertas #promocao",
+            "🎯 {} {}\n\n{}\n\n✨ Benefícios:\n• Alta qualidade garantida\n• {} avaliações positivas\n• {} vendidos e contando!\n\n💰 De {} por apenas {}\n{}\n\n🔗 Clique em \"Saiba Mais\" e aproveite!\n\n#dropshipping #ofertas #promocao",
             emoji_fire,
             product.title,
             product.description.as_deref().unwrap_or("O produto que você estava procurando!"),
             product.reviews_count,
             product.sales_count,
-            product.original_price.unwrap_or(product.price * 1.5),
-            product.price,
+            number_format.price(product.original_price.unwrap_or(product.price * 1.5)),
+            number_format.price(product.price),
             if product.has_free_shipping { "🚚 FRETE GRÁTIS!" } else { "" }
         ),
         "product_description" => format!(
-            "{}\n\n📦 Descrição do Produto\n\n{}\n\n⭐ Avaliação: {:.1}/5 ({} avaliações)\n{} {} vendas\n\n💲 Preço: R${:.2}\n{}\n\n🏪 Vendedor: {} (Nota: {:.1})\n\n✅ {} em estoque",
+            "{}\n\n📦 Descrição do Produto\n\n{}\n\n⭐ Avaliação: {:.1}/5 ({} avaliações)\n{} {} vendas\n\n💲 Preço: {}\n{}\n\n🏪 Vendedor: {} (Nota: {:.1})\n\n✅ {} em estoque",
             product.title,
             product.description.as_deref().unwrap_or("Produto de alta qualidade importado."),
             product.product_rating.unwrap_or(4.5),
             product.reviews_count,
             emoji_cart,
             product.sales_count,
-            product.price,
-            if product.is_on_sale { format!("🏷️ PROMOÇÃO! De R${:.2}", product.original_price.unwrap_or(product.price * 1.5)) } else { String::new() },
+            number_format.price(product.price),
+            if product.is_on_sale { format!("🏷️ PROMOÇÃO! De {}", number_format.price(product.original_price.unwrap_or(product.price * 1.5))) } else { String::new() },
             product.seller_name.as_deref().unwrap_or("Loja Oficial"),
             product.seller_rating.unwrap_or(4.5),
             product.price
         ),
         _ => format!(
-            "{}\n\nPreço: R${:.2}\nAvaliação: {:.1}/5\nVendas: {}\n\n{}",
+            "{}\n\nPreço: {}\nAvaliação: {:.1}/5\nVendas: {}\n\n{}",
             product.title,
-            product.price,
+            number_format.price(product.price),
             product.product_rating.unwrap_or(4.5),
             product.sales_count,
             product.product_url
@@ -770,14 +3801,127 @@ fn generate_copy_content(product: &Product, copy_type: &str, tone: &str) -> Stri
     }
 }
 
+/// Products as a "csv", "json" or "xlsx" file
+pub(crate) fn render_products(products: &[Product], format: &str) -> Result<Vec<u8>, String> {
+    match format {
+        "csv" => Ok(export_to_csv(products)?.into_bytes()),
+        "json" => serde_json::to_vec_pretty(products).map_err(|e| e.to_string()),
+        "xlsx" => export_to_xlsx(products, None),
+        _ => Err("Unsupported format".to_string()),
+    }
+}
+
+/// History snapshots of exported products as a "csv" or "json" file
+fn render_history(
+    products: &[Product],
+    history: &[ProductHistory],
+    format: &str,
+) -> Result<Vec<u8>, String> {
+    match format {
+        "csv" => {
+            let mut csv =
+                String::from("product_id,title,collected_at,price,sales_count,stock_level\n");
+            for h in history {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    h.product_id,
+                    history_title(products, h).replace(',', ";"),
+                    h.collected_at,
+                    h.price,
+                    h.sales_count,
+                    h.stock_level.map(|s| s.to_string()).unwrap_or_default()
+                ));
+            }
+            Ok(csv.into_bytes())
+        }
+        "json" => serde_json::to_vec_pretty(history).map_err(|e| e.to_string()),
+        _ => Err("Unsupported format".to_string()),
+    }
+}
+
+fn history_title<'a>(products: &'a [Product], history: &ProductHistory) -> &'a str {
+    products
+        .iter()
+        .find(|p| p.id == history.product_id)
+        .map(|p| p.title.as_str())
+        .unwrap_or_default()
+}
+
+// Same columns as the CSV, with numbers kept as numbers; the history, if
+// given, goes to a second sheet
+fn export_to_xlsx(
+    products: &[Product],
+    history: Option<&[ProductHistory]>,
+) -> Result<Vec<u8>, String> {
+    let header = [
+        "id",
+        "title",
+        "price",
+        "original_price",
+        "category",
+        "sales_count",
+        "rating",
+        "shipping_fee",
+        "delivery_days_min",
+        "delivery_days_max",
+        "product_url",
+    ];
+    let number = |value: Option<f64>| value.map(Cell::Number).unwrap_or(Cell::Empty);
+
+    let mut rows = vec![header.iter().map(|h| Cell::Text(h.to_string())).collect()];
+    for p in products {
+        rows.push(vec![
+            Cell::Text(p.id.clone()),
+            Cell::Text(p.title.clone()),
+            Cell::Number(p.price),
+            number(p.original_price),
+            Cell::Text(p.category.clone().unwrap_or_default()),
+            Cell::Number(p.sales_count as f64),
+            number(p.product_rating),
+            number(p.shipping_fee),
+            number(p.delivery_days_min.map(f64::from)),
+            number(p.delivery_days_max.map(f64::from)),
+            Cell::Text(p.product_url.clone()),
+        ]);
+    }
+
+    let Some(history) = history else {
+        return xlsx::write("Produtos", &rows).map_err(|e| e.to_string());
+    };
+
+    let header = [
+        "product_id",
+        "title",
+        "collected_at",
+        "price",
+        "sales_count",
+        "stock_level",
+    ];
+    let mut history_rows = vec![header.iter().map(|h| Cell::Text(h.to_string())).collect()];
+    for h in history {
+        history_rows.push(vec![
+            Cell::Text(h.product_id.clone()),
+            Cell::Text(history_title(products, h).to_string()),
+            Cell::Text(h.collected_at.clone()),
+            Cell::Number(h.price),
+            Cell::Number(h.sales_count as f64),
+            number(h.stock_level.map(f64::from)),
+        ]);
+    }
+
+    xlsx::write_sheets(&[("Produtos", &rows), ("Histórico", &history_rows)])
+        .map_err(|e| e.to_string())
+}
+
 // Helper function to export to CSV
 fn export_to_csv(products: &[Product]) -> Result<String, String> {
-    let mut csv =
-        String::from("id,title,price,original_price,category,sales_count,rating,product_url\n");
+    let mut csv = String::from(
+        "id,title,price,original_price,category,sales_count,rating,shipping_fee,delivery_days_min,delivery_days_max,product_url\n",
+    );
 
     for p in products {
         csv.push_str(&format!(
-            "{},{},{},{},{},{},{},{}\n",
+            "{},{},{},{},{},{},{},{},{},{},{}\n",
             p.id,
             p.title.replace(',', ";"),
             p.price,
@@ -785,6 +3929,9 @@ fn export_to_csv(products: &[Product]) -> Result<String, String> {
             p.category.as_deref().unwrap_or(""),
             p.sales_count,
             p.product_rating.unwrap_or(0.0),
+            p.shipping_fee.map(|f| f.to_string()).unwrap_or_default(),
+            p.delivery_days_min.map(|d| d.to_string()).unwrap_or_default(),
+            p.delivery_days_max.map(|d| d.to_string()).unwrap_or_default(),
             p.product_url
         ));
     }
@@ -809,12 +3956,10 @@ pub async fn validate_subscription(
     let db_path = app_dir.join("tiktrend.db");
     let cache_path = app_dir.join("subscription_cache.json");
 
-    let client = reqwest::Client::new();
-    
     // Build request with auth token if available
-    let mut request = client.post(format!("{}/subscription/validate", API_URL));
+    let mut request = backend::post("/subscription/validate");
     
-    if let Some(token) = auth_token {
+    if let Some(token) = &auth_token {
         request = request.header("Authorization", format!("Bearer {}", token));
     }
 
@@ -823,7 +3968,7 @@ pub async fn validate_subscription(
         "app_version": env!("CARGO_PKG_VERSION"),
     });
 
-    match request.json(&api_payload).send().await {
+    match backend::send(request.json(&api_payload)).await {
         Ok(response) => {
             if response.status().is_success() {
                 let api_response: serde_json::Value = response
@@ -835,30 +3980,30 @@ pub async fn validate_subscription(
                 let subscription = parse_subscription_from_api(&api_response)?;
                 
                 // Cache subscription for offline use
-                let cached = CachedSubscription {
-                    subscription: subscription.clone(),
-                    cached_at: Utc::now().to_rfc3339(),
-                    valid_until: calculate_cache_validity(&subscription),
-                    last_sync: Utc::now().to_rfc3339(),
-                };
-                
-                // Save to file
-                if let Ok(json) = serde_json::to_string_pretty(&cached) {
-                    let _ = fs::write(&cache_path, json);
-                }
-                
-                // Also update database
-                let _ = database::save_subscription_cache(&db_path, &cached);
+                cache_subscription(&app_dir, &subscription);
+                invalidate_entitlements(&app);
 
-                Ok(SubscriptionValidation {
-                    is_valid: true,
-                    subscription: Some(subscription),
-                    reason: None,
-                    message: Some("Subscription validated successfully".to_string()),
-                })
+                // Open the current billing period and send usage recorded offline
+                if let Err(e) = usage::rollover(&db_path, &subscription, Utc::now()) {
+                    log::warn!("Failed to roll over usage period: {}", e);
+                }
+                let sync_db_path = db_path.clone();
+                let sync_token = auth_token.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = usage::sync(&sync_db_path, sync_token.as_deref()).await {
+                        log::warn!("Usage sync postponed: {}", e);
+                    }
+                });
+
+                Ok(effective_validation(
+                    subscription,
+                    None,
+                    "Subscription validated successfully",
+                ))
             } else if response.status() == reqwest::StatusCode::UNAUTHORIZED {
                 // Invalid token - clear cache and return invalid
                 let _ = fs::remove_file(&cache_path);
+                invalidate_entitlements(&app);
                 
                 Ok(SubscriptionValidation {
                     is_valid: false,
@@ -867,7 +4012,18 @@ pub async fn validate_subscription(
                     message: Some("Authentication required".to_string()),
                 })
             } else if response.status() == reqwest::StatusCode::PAYMENT_REQUIRED {
-                // Subscription expired or payment issue
+                // Subscription expired or payment issue. A plan that was
+                // paid keeps its features through the grace period.
+                if let Some(cached) = load_cached_subscription(&app_dir) {
+                    let mut subscription = cached.subscription;
+                    if lapse_date(&subscription).is_none() {
+                        subscription.status = SubscriptionStatus::PastDue;
+                    }
+                    if access_state(&subscription, Utc::now()).0 == AccessState::Grace {
+                        return Ok(effective_validation(subscription, None, ""));
+                    }
+                }
+
                 Ok(SubscriptionValidation {
                     is_valid: false,
                     subscription: None,
@@ -916,66 +4072,55 @@ pub async fn check_feature_access(
     feature: String,
 ) -> Result<FeatureAccessResult, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let cache_path = app_dir.join("subscription_cache.json");
-    
-    // Load cached subscription
-    let cached = if cache_path.exists() {
-        let content = fs::read_to_string(&cache_path)
-            .map_err(|e| format!("Failed to read cache: {}", e))?;
-        serde_json::from_str::<CachedSubscription>(&content).ok()
-    } else {
+    let db_path = app_dir.join("tiktrend.db");
+    let entitlements = entitlements(&app, &app_dir);
+
+    let has_access = entitlements.has(&feature);
+    let plan_required = if has_access {
         None
+    } else {
+        Some(get_required_plan_for_feature(&feature).unwrap_or_else(|| "starter".to_string()))
     };
-    
-    match cached {
-        Some(c) if is_cache_valid(&c) => {
-            let has_access = check_subscription_feature(&c.subscription, &feature);
-            let limit = get_feature_limit(&c.subscription, &feature);
-            let plan_required = get_required_plan_for_feature(&feature);
-            
-            Ok(FeatureAccessResult {
-                feature,
-                has_access,
-                limit,
-                current_usage: 0, // Would need to track locally
-                plan_required,
-            })
-        }
-        _ => {
-            // No valid subscription - FREE plan features only
-            let has_access = is_free_feature(&feature);
-            let limit = get_free_limit(&feature);
-            let plan_required = if has_access { None } else { Some("starter".to_string()) };
-            
-            Ok(FeatureAccessResult {
-                feature,
-                has_access,
-                limit,
-                current_usage: 0,
-                plan_required,
-            })
-        }
-    }
+    let current_usage = usage::usage(&db_path, &entitlements.subscription, &feature, Utc::now())
+        .map(|u| u.used)
+        .unwrap_or(0);
+
+    Ok(FeatureAccessResult {
+        limit: entitlements.limit(&feature),
+        feature,
+        has_access,
+        current_usage,
+        plan_required,
+        access_state: entitlements.access_state,
+    })
+}
+
+/// Everything this install may use: the subscription, trial and license
+/// merged into one set
+#[command]
+pub async fn get_entitlements(
+    app: AppHandle,
+    state: State<'_, EntitlementState>,
+) -> Result<Entitlements, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(state.0.get(&app_dir))
+}
+
+/// Entitlements from the shared service
+pub(crate) fn entitlements(app: &AppHandle, app_dir: &std::path::Path) -> Entitlements {
+    app.state::<EntitlementState>().0.get(app_dir)
+}
+
+/// Recompute the entitlements on next use, after what they derive from changed
+fn invalidate_entitlements(app: &AppHandle) {
+    app.state::<EntitlementState>().0.invalidate();
 }
 
 /// Get current execution mode
 #[command]
 pub async fn get_execution_mode(app: AppHandle) -> Result<ExecutionMode, String> {
     let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let cache_path = app_dir.join("subscription_cache.json");
-    
-    if cache_path.exists() {
-        let content = fs::read_to_string(&cache_path)
-            .map_err(|e| format!("Failed to read cache: {}", e))?;
-        if let Ok(cached) = serde_json::from_str::<CachedSubscription>(&content) {
-            if is_cache_valid(&cached) {
-                return Ok(cached.subscription.execution_mode);
-            }
-        }
-    }
-    
-    // Default to web_only for free/unknown
-    Ok(ExecutionMode::WebOnly)
+    Ok(entitlements(&app, &app_dir).execution_mode().clone())
 }
 
 /// Check if offline mode is allowed
@@ -1027,6 +4172,148 @@ pub async fn can_work_offline(app: AppHandle) -> Result<OfflineStatus, String> {
     })
 }
 
+/// Count a use of a metered feature in the current billing period. Crossing
+/// 80% or 95% of the limit emits `quota://warning`.
+#[command]
+pub async fn record_feature_usage(
+    app: AppHandle,
+    feature: String,
+    amount: Option<i32>,
+) -> Result<FeatureUsage, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let subscription = entitlements(&app, &app_dir).subscription;
+
+    telemetry::track(&app_dir, "feature_used", &[("feature", feature.clone())]);
+    usage::record_and_warn(&app, &db_path, &subscription, &feature, amount.unwrap_or(1))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Telemetry events recorded on this machine, newest first, exactly as they
+/// are (or were) uploaded
+#[command]
+pub async fn get_telemetry_events(
+    app: AppHandle,
+    limit: Option<i32>,
+) -> Result<Vec<TelemetryEvent>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::get_telemetry_events(&db_path, false, limit.unwrap_or(200))
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Upload pending telemetry events now
+#[command]
+pub async fn flush_telemetry(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    telemetry::flush(&app_dir).await
+}
+
+/// Delete the telemetry events recorded on this machine
+#[command]
+pub async fn clear_telemetry_events(app: AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    database::clear_telemetry_events(&db_path, false).map_err(|e| format!("Database error: {}", e))
+}
+
+/// Usage of every metered feature in the current billing period
+#[command]
+pub async fn get_usage_summary(app: AppHandle) -> Result<Vec<FeatureUsage>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let subscription = entitlements(&app, &app_dir).subscription;
+
+    usage::METERED_FEATURES
+        .iter()
+        .map(|feature| usage::usage(&db_path, &subscription, feature, Utc::now()))
+        .collect::<rusqlite::Result<_>>()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Send usage recorded since the last sync to the backend
+#[command]
+pub async fn sync_usage(app: AppHandle, auth_token: Option<String>) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    usage::sync(&db_path, auth_token.as_deref()).await
+}
+
+/// Start the trial of this device with the backend and cache its subscription
+#[command]
+pub async fn start_trial(
+    app: AppHandle,
+    auth_token: Option<String>,
+) -> Result<SubscriptionValidation, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let subscription = trial::start(&db_path, auth_token.as_deref()).await?;
+    cache_subscription(&app_dir, &subscription);
+    invalidate_entitlements(&app);
+    if let Err(e) = usage::rollover(&db_path, &subscription, Utc::now()) {
+        log::warn!("Failed to roll over usage period: {}", e);
+    }
+
+    Ok(SubscriptionValidation {
+        is_valid: true,
+        subscription: Some(subscription),
+        reason: Some("trial_started".to_string()),
+        message: Some("Trial started".to_string()),
+    })
+}
+
+/// Trial of this device and the days it has left
+#[command]
+pub async fn get_trial_status(app: AppHandle) -> Result<TrialStatus, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    Ok(trial::status(&db_path, Utc::now()))
+}
+
+/// Whether the cached subscription is active, in its grace period or has
+/// fallen back to Free
+#[command]
+pub async fn get_subscription_access(app: AppHandle) -> Result<SubscriptionAccess, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(subscription_access(&app_dir))
+}
+
+pub(crate) fn subscription_access(app_dir: &std::path::Path) -> SubscriptionAccess {
+    let cached = fs::read_to_string(app_dir.join("subscription_cache.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<CachedSubscription>(&content).ok())
+        .filter(is_cache_valid);
+    let Some(cached) = cached else {
+        return SubscriptionAccess {
+            state: AccessState::Free,
+            plan_tier: PlanTier::Free,
+            grace_ends_at: None,
+            grace_days_remaining: 0,
+        };
+    };
+
+    let now = Utc::now();
+    let (state, grace_ends_at) = access_state(&cached.subscription, now);
+    SubscriptionAccess {
+        state,
+        plan_tier: if state == AccessState::Free {
+            PlanTier::Free
+        } else {
+            cached.subscription.plan_tier
+        },
+        grace_ends_at: grace_ends_at.map(|t| t.to_rfc3339()),
+        grace_days_remaining: grace_ends_at
+            .filter(|_| state == AccessState::Grace)
+            .map(|t| (t - now).num_days() as i32)
+            .unwrap_or(0),
+    }
+}
+
 // ==================================================
 // SUBSCRIPTION HELPER TYPES
 // ==================================================
@@ -1040,6 +4327,30 @@ pub struct FeatureAccessResult {
     pub limit: Option<i32>,
     pub current_usage: i32,
     pub plan_required: Option<String>,
+    pub access_state: AccessState,
+}
+
+/// Access granted by the cached subscription
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum AccessState {
+    Active,
+    /// Lapsed, but the plan's features stay on until the grace period ends
+    Grace,
+    /// No subscription, or lapsed past the grace period
+    Free,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SubscriptionAccess {
+    pub state: AccessState,
+    /// Plan whose features are available
+    pub plan_tier: PlanTier,
+    pub grace_ends_at: Option<String>,
+    pub grace_days_remaining: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -1055,7 +4366,7 @@ pub struct OfflineStatus {
 // SUBSCRIPTION HELPER FUNCTIONS
 // ==================================================
 
-fn parse_subscription_from_api(response: &serde_json::Value) -> Result<Subscription, String> {
+pub(crate) fn parse_subscription_from_api(response: &serde_json::Value) -> Result<Subscription, String> {
     let plan_tier = match response["planTier"].as_str().unwrap_or("free") {
         "starter" => PlanTier::Starter,
         "business" => PlanTier::Business,
@@ -1137,6 +4448,21 @@ fn parse_subscription_from_api(response: &serde_json::Value) -> Result<Subscript
     })
 }
 
+/// Cache a validated subscription in the file and the database for offline use
+fn cache_subscription(app_dir: &std::path::Path, subscription: &Subscription) {
+    let cached = CachedSubscription {
+        subscription: subscription.clone(),
+        cached_at: Utc::now().to_rfc3339(),
+        valid_until: calculate_cache_validity(subscription),
+        last_sync: Utc::now().to_rfc3339(),
+    };
+
+    if let Ok(json) = serde_json::to_string_pretty(&cached) {
+        let _ = fs::write(app_dir.join("subscription_cache.json"), json);
+    }
+    let _ = database::save_subscription_cache(&app_dir.join("tiktrend.db"), &cached);
+}
+
 fn calculate_cache_validity(subscription: &Subscription) -> String {
     let days = match subscription.plan_tier {
         PlanTier::Enterprise => 30,
@@ -1158,6 +4484,100 @@ fn is_cache_valid(cached: &CachedSubscription) -> bool {
     false
 }
 
+/// When a lapsed subscription stopped being paid for: the end of its current
+/// period. None while the subscription is in good standing.
+fn lapse_date(subscription: &Subscription) -> Option<chrono::DateTime<Utc>> {
+    if !matches!(
+        subscription.status,
+        SubscriptionStatus::PastDue | SubscriptionStatus::Canceled | SubscriptionStatus::Expired
+    ) {
+        return None;
+    }
+    Some(
+        chrono::DateTime::parse_from_rfc3339(&subscription.current_period_end)
+            .or_else(|_| chrono::DateTime::parse_from_rfc3339(&subscription.cached_at))
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    )
+}
+
+/// Access state of a subscription at `now`, with the end of its grace period
+/// once it has lapsed
+fn access_state(
+    subscription: &Subscription,
+    now: chrono::DateTime<Utc>,
+) -> (AccessState, Option<chrono::DateTime<Utc>>) {
+    // Trials end with their period and go straight to Free
+    if subscription.status == SubscriptionStatus::Trialing {
+        let ended = chrono::DateTime::parse_from_rfc3339(&subscription.current_period_end)
+            .is_ok_and(|end| now >= end.with_timezone(&Utc));
+        let state = if ended { AccessState::Free } else { AccessState::Active };
+        return (state, None);
+    }
+
+    let Some(lapsed_at) = lapse_date(subscription) else {
+        return (AccessState::Active, None);
+    };
+    // Canceled plans stay paid until the end of the period
+    if now < lapsed_at {
+        return (AccessState::Active, None);
+    }
+
+    let grace_ends_at =
+        lapsed_at + chrono::Duration::days(subscription.grace_period_days.max(0) as i64);
+    if now < grace_ends_at {
+        (AccessState::Grace, Some(grace_ends_at))
+    } else {
+        (AccessState::Free, Some(grace_ends_at))
+    }
+}
+
+/// Validation result for a subscription as of now: a lapsed plan keeps its
+/// features with a warning during the grace period and degrades to Free after it
+fn effective_validation(
+    subscription: Subscription,
+    reason: Option<String>,
+    message: &str,
+) -> SubscriptionValidation {
+    match access_state(&subscription, Utc::now()) {
+        (AccessState::Active, _) => SubscriptionValidation {
+            is_valid: true,
+            subscription: Some(subscription),
+            reason,
+            message: Some(message.to_string()),
+        },
+        (AccessState::Grace, grace_ends_at) => SubscriptionValidation {
+            is_valid: true,
+            subscription: Some(subscription),
+            reason: Some("grace_period".to_string()),
+            message: Some(format!(
+                "Subscription payment pending. Features stay available until {}",
+                grace_ends_at.map(|t| t.to_rfc3339()).unwrap_or_default()
+            )),
+        },
+        (AccessState::Free, _) => SubscriptionValidation {
+            is_valid: true,
+            subscription: Some(create_free_subscription()),
+            reason: Some("expired".to_string()),
+            message: Some("Subscription expired - using free tier.".to_string()),
+        },
+    }
+}
+
+/// Load the file-cached subscription if it is still valid and has not lapsed
+/// past its grace period
+pub(crate) fn load_cached_subscription(app_dir: &std::path::Path) -> Option<CachedSubscription> {
+    let content = fs::read_to_string(app_dir.join("subscription_cache.json")).ok()?;
+    let cached: CachedSubscription = serde_json::from_str(&content).ok()?;
+    if is_cache_valid(&cached)
+        && access_state(&cached.subscription, Utc::now()).0 != AccessState::Free
+    {
+        Some(cached)
+    } else {
+        None
+    }
+}
+
 fn try_cached_subscription(
     cache_path: &std::path::Path,
     db_path: &std::path::Path,
@@ -1167,12 +4587,11 @@ fn try_cached_subscription(
         if let Ok(content) = fs::read_to_string(cache_path) {
             if let Ok(cached) = serde_json::from_str::<CachedSubscription>(&content) {
                 if is_cache_valid(&cached) {
-                    return Ok(SubscriptionValidation {
-                        is_valid: true,
-                        subscription: Some(cached.subscription),
-                        reason: Some("offline_cached".to_string()),
-                        message: Some("Using cached subscription (offline mode)".to_string()),
-                    });
+                    return Ok(effective_validation(
+                        cached.subscription,
+                        Some("offline_cached".to_string()),
+                        "Using cached subscription (offline mode)",
+                    ));
                 }
             }
         }
@@ -1181,15 +4600,24 @@ fn try_cached_subscription(
     // Try database cache
     if let Ok(Some(cached)) = database::get_subscription_cache(db_path) {
         if is_cache_valid(&cached) {
-            return Ok(SubscriptionValidation {
-                is_valid: true,
-                subscription: Some(cached.subscription),
-                reason: Some("offline_db_cached".to_string()),
-                message: Some("Using database cached subscription".to_string()),
-            });
+            return Ok(effective_validation(
+                cached.subscription,
+                Some("offline_db_cached".to_string()),
+                "Using database cached subscription",
+            ));
         }
     }
     
+    // A trial started on this device keeps running offline
+    if let Some(subscription) = trial::active_subscription(db_path, Utc::now()) {
+        return Ok(SubscriptionValidation {
+            is_valid: true,
+            subscription: Some(subscription),
+            reason: Some("offline_trial".to_string()),
+            message: Some("Offline - using the trial started on this device".to_string()),
+        });
+    }
+
     // No valid cache - return free tier fallback
     Ok(SubscriptionValidation {
         is_valid: true,
@@ -1199,7 +4627,7 @@ fn try_cached_subscription(
     })
 }
 
-fn create_free_subscription() -> Subscription {
+pub(crate) fn create_free_subscription() -> Subscription {
     Subscription {
         id: "free".to_string(),
         user_id: "offline".to_string(),
@@ -1229,26 +4657,7 @@ fn create_free_subscription() -> Subscription {
     }
 }
 
-fn check_subscription_feature(subscription: &Subscription, feature: &str) -> bool {
-    match feature {
-        "chatbot_ai" => subscription.features.chatbot_ai,
-        "analytics_advanced" => subscription.features.analytics_advanced,
-        "analytics_export" => subscription.features.analytics_export,
-        "crm_automation" => subscription.features.crm_automation,
-        "api_access" => subscription.features.api_access,
-        "offline_mode" => subscription.features.offline_mode,
-        "hybrid_sync" => subscription.features.hybrid_sync,
-        "priority_support" => subscription.features.priority_support,
-        // Metered features - check limits
-        "price_searches" => subscription.limits.price_searches > 0,
-        "favorites" => subscription.limits.favorites > 0,
-        "whatsapp_messages" => subscription.limits.whatsapp_messages > 0,
-        "api_calls" => subscription.limits.api_calls > 0,
-        _ => false,
-    }
-}
-
-fn get_feature_limit(subscription: &Subscription, feature: &str) -> Option<i32> {
+pub(crate) fn get_feature_limit(subscription: &Subscription, feature: &str) -> Option<i32> {
     match feature {
         "price_searches" => Some(subscription.limits.price_searches),
         "favorites" => Some(subscription.limits.favorites),
@@ -1272,14 +4681,151 @@ fn get_required_plan_for_feature(feature: &str) -> Option<String> {
     }
 }
 
-fn is_free_feature(feature: &str) -> bool {
-    matches!(feature, "price_searches" | "favorites" | "analytics_basic")
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn paid_subscription(status: SubscriptionStatus, period_end: String) -> Subscription {
+        let mut subscription = create_free_subscription();
+        subscription.id = "sub_1".to_string();
+        subscription.plan_tier = PlanTier::Business;
+        subscription.status = status;
+        subscription.billing_cycle = "monthly".to_string();
+        subscription.current_period_end = period_end;
+        subscription.grace_period_days = 3;
+        subscription
+    }
 
-fn get_free_limit(feature: &str) -> Option<i32> {
-    match feature {
-        "price_searches" => Some(50),
-        "favorites" => Some(20),
-        _ => Some(0),
+    #[test]
+    fn test_access_state() {
+        let now = Utc::now();
+        let ago = |days| (now - Duration::days(days)).to_rfc3339();
+        let ahead = |days| (now + Duration::days(days)).to_rfc3339();
+        let cases = [
+            (
+                SubscriptionStatus::Active,
+                ago(10),
+                AccessState::Active,
+                None,
+            ),
+            (
+                SubscriptionStatus::PastDue,
+                ago(1),
+                AccessState::Grace,
+                Some(2),
+            ),
+            (
+                SubscriptionStatus::PastDue,
+                ago(5),
+                AccessState::Free,
+                Some(-2),
+            ),
+            (
+                SubscriptionStatus::Canceled,
+                ahead(10),
+                AccessState::Active,
+                None,
+            ),
+            (
+                SubscriptionStatus::Canceled,
+                ago(4),
+                AccessState::Free,
+                Some(-1),
+            ),
+            (
+                SubscriptionStatus::Trialing,
+                ahead(2),
+                AccessState::Active,
+                None,
+            ),
+            (
+                SubscriptionStatus::Trialing,
+                ago(1),
+                AccessState::Free,
+                None,
+            ),
+        ];
+        for (status, period_end, expected, grace_days_left) in cases {
+            let subscription = paid_subscription(status.clone(), period_end.clone());
+            let (state, grace_ends_at) = access_state(&subscription, now);
+            assert_eq!(state, expected, "{:?} ending {}", status, period_end);
+            assert_eq!(
+                grace_ends_at.map(|t| (t - now).num_days()),
+                grace_days_left,
+                "grace end of {:?} ending {}",
+                status,
+                period_end
+            );
+        }
+    }
+
+    #[test]
+    fn test_access_state_without_a_period_end() {
+        let now = Utc::now();
+
+        // A trial without a readable end has not ended
+        let trial = paid_subscription(SubscriptionStatus::Trialing, String::new());
+        assert_eq!(access_state(&trial, now), (AccessState::Active, None));
+
+        // A lapsed plan falls back to when it was cached
+        let mut lapsed = paid_subscription(SubscriptionStatus::PastDue, "not a date".to_string());
+        lapsed.cached_at = (now - Duration::days(5)).to_rfc3339();
+        assert_eq!(access_state(&lapsed, now).0, AccessState::Free);
+        lapsed.cached_at = (now - Duration::days(1)).to_rfc3339();
+        assert_eq!(access_state(&lapsed, now).0, AccessState::Grace);
+
+        // and to the time of the check when neither can be read
+        lapsed.cached_at = String::new();
+        let (state, grace_ends_at) = access_state(&lapsed, now + Duration::days(1));
+        assert_eq!(state, AccessState::Grace);
+        assert!(grace_ends_at.is_some_and(|t| t > now + Duration::days(2)));
+        assert_eq!(
+            access_state(&lapsed, now + Duration::days(4)).0,
+            AccessState::Free
+        );
+    }
+
+    #[test]
+    fn test_effective_validation_active() {
+        let period_end = (Utc::now() + Duration::days(20)).to_rfc3339();
+        let subscription = paid_subscription(SubscriptionStatus::Active, period_end);
+        let validation =
+            effective_validation(subscription, Some("offline_cached".to_string()), "Cached");
+        assert!(validation.is_valid);
+        assert_eq!(validation.reason.as_deref(), Some("offline_cached"));
+        assert_eq!(validation.message.as_deref(), Some("Cached"));
+        assert_eq!(
+            validation.subscription.map(|s| s.plan_tier),
+            Some(PlanTier::Business)
+        );
+    }
+
+    #[test]
+    fn test_effective_validation_grace_period() {
+        let period_end = (Utc::now() - Duration::days(1)).to_rfc3339();
+        let subscription = paid_subscription(SubscriptionStatus::PastDue, period_end);
+        let validation = effective_validation(subscription, None, "Cached");
+        assert!(validation.is_valid);
+        assert_eq!(validation.reason.as_deref(), Some("grace_period"));
+        assert!(validation
+            .message
+            .is_some_and(|m| m.contains("payment pending")));
+        assert_eq!(
+            validation.subscription.map(|s| s.plan_tier),
+            Some(PlanTier::Business)
+        );
+    }
+
+    #[test]
+    fn test_effective_validation_falls_back_to_free_after_grace() {
+        let period_end = (Utc::now() - Duration::days(5)).to_rfc3339();
+        let subscription = paid_subscription(SubscriptionStatus::PastDue, period_end);
+        let validation = effective_validation(subscription, None, "Cached");
+        assert!(validation.is_valid);
+        assert_eq!(validation.reason.as_deref(), Some("expired"));
+        let subscription = validation.subscription.unwrap();
+        assert_eq!(subscription.plan_tier, PlanTier::Free);
+        assert_eq!(subscription.id, "free");
     }
 }
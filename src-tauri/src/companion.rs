@@ -0,0 +1,443 @@
+// Browser Extension Companion
+// A localhost HTTP endpoint the companion browser extension pushes products
+// to while the user browses TikTok Shop in their own browser. The extension
+// pairs once by exchanging a short-lived code shown in the app for a token,
+// then sends either the product page's JSON, saved right away, or just its
+// URL, scraped as an import job. Products are tagged "extension import".
+// Only extension origins get CORS access, and a pairing code is thrown away
+// after a few wrong tries.
+//
+//   GET  /status    app name and version, to find the app
+//   POST /pair      {"code": "..."} -> {"token": "..."}
+//   POST /products  {"url": "...", "data": {...}?} with "Authorization: Bearer <token>"
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use uuid::Uuid;
+
+use crate::commands;
+use crate::database;
+use crate::models::{ExtensionPairing, Product};
+use crate::scraper::{NumberLocale, TikTokParser};
+
+/// Tag given to products pushed by the extension
+pub const EXTENSION_TAG: &str = "extension import";
+
+/// Settings key of the pending pairing code
+const PAIRING_KEY: &str = "companion_pairing";
+
+/// Settings key of the SHA-256 hashes of the tokens handed out
+const TOKENS_KEY: &str = "companion_tokens";
+
+const PAIRING_MINUTES: i64 = 10;
+
+/// Wrong codes accepted before the pending code is thrown away, so it can't
+/// be guessed while it's valid
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+
+/// Time a client gets to send its whole request
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Origin schemes of browser extensions; web pages get no CORS access
+const EXTENSION_SCHEMES: &[&str] = &[
+    "chrome-extension://",
+    "moz-extension://",
+    "safari-web-extension://",
+];
+
+/// Request line and headers
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+
+/// Product page JSON can be large, but not this large
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// The endpoint is started once per app run
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize)]
+struct PendingPairing {
+    code: String,
+    expires_at: String,
+    /// Wrong codes tried so far
+    #[serde(default)]
+    failed_attempts: u32,
+}
+
+#[derive(Deserialize)]
+struct PairRequest {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct ProductPush {
+    url: Option<String>,
+    /// Product detail state of the page, as the scraper reads it
+    data: Option<Value>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    origin: Option<String>,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+fn hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn token_hashes(db_path: &Path) -> Vec<String> {
+    database::get_setting(db_path, TOKENS_KEY)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Create the pairing code the extension exchanges for its token, replacing
+/// any earlier one
+pub fn start_pairing(db_path: &Path, port: u16) -> Result<ExtensionPairing, String> {
+    let digits = Uuid::new_v4().simple().to_string().to_uppercase();
+    let code = format!("{}-{}", &digits[..4], &digits[4..8]);
+    let expires_at = (Utc::now() + Duration::minutes(PAIRING_MINUTES)).to_rfc3339();
+
+    let pending = PendingPairing {
+        code: code.clone(),
+        expires_at: expires_at.clone(),
+        failed_attempts: 0,
+    };
+    let json = serde_json::to_string(&pending).map_err(|e| e.to_string())?;
+    database::set_setting(db_path, PAIRING_KEY, &json)
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(ExtensionPairing {
+        code,
+        port,
+        expires_at,
+    })
+}
+
+/// Forget the tokens of every paired extension; returns how many there were
+pub fn unpair_all(db_path: &Path) -> Result<usize, String> {
+    let count = token_hashes(db_path).len();
+    database::set_setting(db_path, TOKENS_KEY, "[]")
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(count)
+}
+
+/// Exchange a pairing code for a token. The code works once, and is thrown
+/// away after `MAX_PAIRING_ATTEMPTS` wrong ones.
+fn pair(db_path: &Path, code: &str) -> Result<String, String> {
+    let pending: Option<PendingPairing> = database::get_setting(db_path, PAIRING_KEY)
+        .map_err(|e| format!("Database error: {}", e))?
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let Some(mut pending) = pending else {
+        return Err("No pairing in progress".to_string());
+    };
+
+    let normalize = |code: &str| code.trim().replace('-', "").to_uppercase();
+    let expired = DateTime::parse_from_rfc3339(&pending.expires_at)
+        .map(|expires_at| expires_at < Utc::now())
+        .unwrap_or(true);
+    if expired || normalize(code) != normalize(&pending.code) {
+        pending.failed_attempts += 1;
+        let json = if expired || pending.failed_attempts >= MAX_PAIRING_ATTEMPTS {
+            "null".to_string()
+        } else {
+            serde_json::to_string(&pending).map_err(|e| e.to_string())?
+        };
+        database::set_setting(db_path, PAIRING_KEY, &json)
+            .map_err(|e| format!("Database error: {}", e))?;
+        return Err("Invalid or expired pairing code".to_string());
+    }
+
+    let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    let mut hashes = token_hashes(db_path);
+    hashes.push(hash(&token));
+    let json = serde_json::to_string(&hashes).map_err(|e| e.to_string())?;
+    database::set_setting(db_path, TOKENS_KEY, &json)
+        .map_err(|e| format!("Database error: {}", e))?;
+    database::set_setting(db_path, PAIRING_KEY, "null")
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(token)
+}
+
+fn authorized(db_path: &Path, authorization: Option<&str>) -> bool {
+    let Some(token) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    token_hashes(db_path).contains(&hash(token.trim()))
+}
+
+/// Start the endpoint on 127.0.0.1:`port` unless it's running already
+pub fn spawn_server(app: AppHandle, port: u16) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("Extension endpoint not started on port {}: {}", port, e);
+                STARTED.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+        log::info!("Extension endpoint listening on 127.0.0.1:{}", port);
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    log::warn!("Extension endpoint accept failed: {}", e);
+                    continue;
+                }
+            };
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = handle(&app, stream).await {
+                    log::debug!("Extension request failed: {}", e);
+                }
+            });
+        }
+    });
+}
+
+async fn handle(app: &AppHandle, mut stream: TcpStream) -> std::io::Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request timed out"))??;
+    let origin = request
+        .as_ref()
+        .and_then(|request| request.origin.clone())
+        .filter(|origin| {
+            EXTENSION_SCHEMES
+                .iter()
+                .any(|scheme| origin.starts_with(scheme))
+        });
+    let (status, body) = match request {
+        Some(request) => route(app, request).await,
+        None => (400, json!({ "error": "Malformed request" })),
+    };
+    write_response(&mut stream, status, &body, origin.as_deref()).await
+}
+
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let header_end = loop {
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return Ok(None);
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Ok(None);
+    };
+
+    let mut content_length = 0;
+    let mut origin = None;
+    let mut authorization = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().unwrap_or(0),
+            "origin" => origin = Some(value.trim().to_string()),
+            "authorization" => authorization = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Ok(None);
+    }
+
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(Some(Request {
+        method: method.to_string(),
+        // Query strings aren't used
+        path: path.split('?').next().unwrap_or_default().to_string(),
+        origin,
+        authorization,
+        body,
+    }))
+}
+
+async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    body: &Value,
+    origin: Option<&str>,
+) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = if status == 204 {
+        String::new()
+    } else {
+        body.to_string()
+    };
+    // Only the extension's own origin may read the responses, so a web page
+    // open in the browser can't pair or push products
+    let cors = origin
+        .map(|origin| {
+            format!(
+                "Access-Control-Allow-Origin: {}\r\n\
+                 Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+                 Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+                 Vary: Origin\r\n",
+                origin
+            )
+        })
+        .unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         {}\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        cors,
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn route(app: &AppHandle, request: Request) -> (u16, Value) {
+    let Ok(app_dir) = app.path().app_data_dir() else {
+        return (500, json!({ "error": "App data folder unavailable" }));
+    };
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = commands::load_settings(&app_dir);
+    if !settings.companion.enabled {
+        return (503, json!({ "error": "Extension endpoint disabled" }));
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("OPTIONS", _) => (204, Value::Null),
+        ("GET", "/status") => (
+            200,
+            json!({ "app": "tiktrend", "version": env!("CARGO_PKG_VERSION") }),
+        ),
+        ("POST", "/pair") => {
+            let Ok(body) = serde_json::from_slice::<PairRequest>(&request.body) else {
+                return (400, json!({ "error": "Expected {\"code\": ...}" }));
+            };
+            match pair(&db_path, &body.code) {
+                Ok(token) => {
+                    let _ = app.emit("companion://paired", ());
+                    (200, json!({ "token": token }))
+                }
+                Err(e) => (403, json!({ "error": e })),
+            }
+        }
+        ("POST", "/products") => {
+            if !authorized(&db_path, request.authorization.as_deref()) {
+                return (401, json!({ "error": "Not paired" }));
+            }
+            let Ok(push) = serde_json::from_slice::<ProductPush>(&request.body) else {
+                return (
+                    400,
+                    json!({ "error": "Expected {\"url\": ..., \"data\": ...}" }),
+                );
+            };
+            push_product(app, &settings.scraper.region, push).await
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Product of the page state pushed by the extension
+fn parse_pushed(data: &Value, url: Option<&str>, region: &str) -> Result<Product, String> {
+    let parser = TikTokParser::default().with_locale(NumberLocale::from_region(region));
+    let mut product = parser
+        .parse_product_json(data)
+        .map_err(|e| format!("Unreadable product data: {}", e))?;
+    let locale = NumberLocale::from_currency(&product.currency)
+        .unwrap_or_else(|| NumberLocale::from_region(region));
+    TikTokParser::apply_variants(&mut product, parser.parse_variants(data, locale));
+    parser.apply_shipping(&mut product, data, locale);
+    if product.product_url.is_empty() {
+        product.product_url = url.unwrap_or_default().to_string();
+    }
+    Ok(product)
+}
+
+async fn push_product(app: &AppHandle, region: &str, push: ProductPush) -> (u16, Value) {
+    let url = push.url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+
+    if let Some(data) = &push.data {
+        let saved = parse_pushed(data, url, region)
+            .and_then(|product| commands::save_imported_product(app, product, EXTENSION_TAG));
+        return match saved {
+            Ok(product) => {
+                let _ = app.emit("companion://imported", &product);
+                (200, json!({ "id": product.id, "title": product.title }))
+            }
+            Err(e) => (400, json!({ "error": e })),
+        };
+    }
+
+    let Some(url) = url else {
+        return (400, json!({ "error": "Send the page data or its URL" }));
+    };
+    if TikTokParser::extract_id_from_url(url).is_none() {
+        return (400, json!({ "error": "Not a TikTok Shop product link" }));
+    }
+
+    // Scraping waits for its turn in the job queue; the extension isn't kept
+    // waiting for it
+    let app = app.clone();
+    let url = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        match commands::import_product_url(&app, &url, EXTENSION_TAG).await {
+            Ok(product) => {
+                let _ = app.emit("companion://imported", &product);
+            }
+            Err(e) => log::warn!("Extension import of {} failed: {}", url, e),
+        }
+    });
+    (202, json!({ "queued": true }))
+}
@@ -0,0 +1,168 @@
+// Product Comparison
+// Lines products up metric by metric for the compare view: current figures,
+// their movement over the last week from the history, and a trend score.
+// Every product carries the same metrics in the same order, each with its
+// delta to the first product and whether it is the best of the set.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{ComparedMetric, ComparedProduct, Product, ProductComparison, ProductHistory};
+
+/// Window the price and sales movement are measured over
+const MOVEMENT_DAYS: i64 = 7;
+
+const MOMENTUM_WEIGHT: f64 = 0.4;
+const DEMAND_WEIGHT: f64 = 0.4;
+const TRENDING_WEIGHT: f64 = 0.2;
+
+/// Units per day at which the demand signal reaches ~63%
+const DEMAND_SCALE: f64 = 20.0;
+
+/// Signal used when it can't be measured yet
+const UNKNOWN_SIGNAL: f64 = 0.5;
+
+/// Whether a higher value of a metric is better
+#[derive(Clone, Copy, PartialEq)]
+enum Better {
+    Higher,
+    Lower,
+}
+
+/// 0 to 100: last week's sales against the monthly pace, the estimated
+/// demand and the shop's trending flag
+pub fn trend_score(product: &Product) -> f64 {
+    let weekly_pace = product.sales_30d as f64 * 7.0 / 30.0;
+    let momentum = if weekly_pace > 0.0 {
+        // Selling at half the monthly pace scores 0, at twice the pace 1
+        ((product.sales_7d as f64 / weekly_pace - 0.5) / 1.5).clamp(0.0, 1.0)
+    } else {
+        UNKNOWN_SIGNAL
+    };
+    let demand = product
+        .demand_estimate
+        .as_ref()
+        .map(|d| 1.0 - (-d.units_per_day / DEMAND_SCALE).exp())
+        .unwrap_or(UNKNOWN_SIGNAL);
+    let trending = if product.is_trending { 1.0 } else { 0.0 };
+
+    100.0 * (MOMENTUM_WEIGHT * momentum + DEMAND_WEIGHT * demand + TRENDING_WEIGHT * trending)
+}
+
+/// Latest snapshot from before `since`, or the first one when the history
+/// is younger than that
+fn baseline(history: &[ProductHistory], since: DateTime<Utc>) -> Option<&ProductHistory> {
+    let at = |h: &ProductHistory| {
+        DateTime::parse_from_rfc3339(&h.collected_at)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    history
+        .iter()
+        .rfind(|h| at(h).is_some_and(|t| t <= since))
+        .or_else(|| history.first())
+}
+
+fn metrics(
+    product: &Product,
+    history: &[ProductHistory],
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, Better, Option<f64>)> {
+    let base = baseline(history, now - Duration::days(MOVEMENT_DAYS));
+    let price_change = base
+        .filter(|b| b.price > 0.0)
+        .map(|b| 100.0 * (product.price - b.price) / b.price);
+    let sales_gained = base.map(|b| (product.sales_count - b.sales_count) as f64);
+    let shipping_fee = if product.has_free_shipping {
+        Some(0.0)
+    } else {
+        product.shipping_fee
+    };
+
+    vec![
+        ("price", Better::Lower, Some(product.price)),
+        ("price_change_7d", Better::Lower, price_change),
+        (
+            "sales_count",
+            Better::Higher,
+            Some(product.sales_count as f64),
+        ),
+        ("sales_7d", Better::Higher, Some(product.sales_7d as f64)),
+        ("sales_30d", Better::Higher, Some(product.sales_30d as f64)),
+        ("sales_gained_7d", Better::Higher, sales_gained),
+        (
+            "units_per_day",
+            Better::Higher,
+            product.demand_estimate.as_ref().map(|d| d.units_per_day),
+        ),
+        ("rating", Better::Higher, product.product_rating),
+        (
+            "reviews_count",
+            Better::Higher,
+            Some(product.reviews_count as f64),
+        ),
+        ("shipping_fee", Better::Lower, shipping_fee),
+        (
+            "delivery_days_max",
+            Better::Lower,
+            product.delivery_days_max.map(f64::from),
+        ),
+        ("seller_rating", Better::Higher, product.seller_rating),
+        ("commission_rate", Better::Higher, product.commission_rate),
+        ("trend_score", Better::Higher, Some(trend_score(product))),
+    ]
+}
+
+/// Compare products, each with its history (oldest snapshot first). Deltas
+/// are relative to the first product.
+pub fn compare(
+    products: &[(Product, Vec<ProductHistory>)],
+    now: DateTime<Utc>,
+) -> ProductComparison {
+    let rows: Vec<_> = products
+        .iter()
+        .map(|(product, history)| metrics(product, history, now))
+        .collect();
+
+    let compared = products
+        .iter()
+        .zip(&rows)
+        .map(|((product, _), row)| {
+            let metrics =
+                row.iter()
+                    .enumerate()
+                    .map(|(i, (key, better, value))| {
+                        let values = rows.iter().filter_map(|r| r[i].2);
+                        let best = match better {
+                            Better::Higher => values
+                                .fold(None, |m: Option<f64>, v| Some(m.map_or(v, |m| m.max(v)))),
+                            Better::Lower => values
+                                .fold(None, |m: Option<f64>, v| Some(m.map_or(v, |m| m.min(v)))),
+                        };
+                        let reference = rows[0][i].2;
+                        ComparedMetric {
+                            key: key.to_string(),
+                            value: *value,
+                            delta: value.zip(reference).map(|(v, r)| v - r),
+                            delta_percent: value
+                                .zip(reference)
+                                .filter(|(_, r)| *r != 0.0)
+                                .map(|(v, r)| 100.0 * (v - r) / r.abs()),
+                            best: products.len() > 1 && value.is_some() && *value == best,
+                        }
+                    })
+                    .collect();
+
+            ComparedProduct {
+                product_id: product.id.clone(),
+                title: product.title.clone(),
+                image_url: product.image_url.clone(),
+                metrics,
+            }
+        })
+        .collect();
+
+    ProductComparison {
+        products: compared,
+        compared_at: now.to_rfc3339(),
+    }
+}
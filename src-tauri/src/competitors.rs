@@ -0,0 +1,133 @@
+// Competitor Tracking
+// Scrapes the catalog of registered competitor stores on a schedule and
+// raises alerts for new listings and price changes
+
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::alerts;
+use crate::commands;
+use crate::database;
+use crate::jobs::JobKind;
+use crate::models::{Competitor, ScraperStatus};
+use crate::scraper::TikTokScraper;
+use crate::JobState;
+
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 24 * 60;
+
+/// A store catalog is a full listing scrape; don't run it more than hourly
+pub const MIN_INTERVAL_MINUTES: u32 = 60;
+
+/// Products read per store visit
+const STORE_MAX_PRODUCTS: u32 = 200;
+
+/// Scrape the catalogs of the competitors that are due, one browser job each.
+/// Returns how many stores were scraped.
+pub async fn refresh_due(app: &AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let due = database::get_due_competitors(&db_path, &chrono::Utc::now().to_rfc3339())
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut scraped = 0;
+    for competitor in &due {
+        match scrape_store(app, &app_dir, &db_path, competitor).await {
+            Ok(count) => {
+                log::info!("Competitor {}: {} products", competitor.name, count);
+                scraped += 1;
+            }
+            Err(e) => log::warn!("Competitor {} scrape failed: {}", competitor.name, e),
+        }
+
+        // Failed stores wait for the next interval too
+        let checked_at = chrono::Utc::now();
+        let next = checked_at + chrono::Duration::minutes(competitor.interval_minutes as i64);
+        database::mark_competitor_checked(
+            &db_path,
+            &competitor.id,
+            &checked_at.to_rfc3339(),
+            &next.to_rfc3339(),
+        )
+        .ok();
+    }
+
+    Ok(scraped)
+}
+
+async fn scrape_store(
+    app: &AppHandle,
+    app_dir: &Path,
+    db_path: &Path,
+    competitor: &Competitor,
+) -> Result<usize, String> {
+    let jobs = app.state::<JobState>();
+    let job_id = jobs
+        .0
+        .enqueue(
+            JobKind::Competitor,
+            format!("Concorrente: {}", competitor.name),
+        )
+        .await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let mut config =
+        commands::runtime_scraper_config(app_dir, commands::load_settings(app_dir).scraper);
+    config.categories = vec![competitor.store_url.clone()];
+    config.max_products = STORE_MAX_PRODUCTS;
+    config.enrich_details = false;
+
+    let scraper = TikTokScraper::new(config, Arc::new(Mutex::new(ScraperStatus::default())), None);
+    let result = jobs
+        .0
+        .until_cancelled(&job_id, scraper.start())
+        .await
+        .and_then(|products| products.map_err(|e| e.to_string()));
+
+    if let Ok(products) = &result {
+        // The first visit only builds the catalog; every product would be "new"
+        let first_visit = competitor.last_checked_at.is_none();
+        let seen_at = chrono::Utc::now().to_rfc3339();
+
+        for product in products {
+            let mut product = product.clone();
+            let previous = database::get_product_by_tiktok_id(db_path, &product.tiktok_id)
+                .ok()
+                .flatten();
+            if let Some(previous) = &previous {
+                // Keep the row id so history and catalog links carry over
+                product.id = previous.id.clone();
+                let changes =
+                    alerts::detect_changes(previous, &product, competitor.price_change_percent);
+                alerts::raise(Some(app), db_path, &changes);
+            }
+
+            if let Err(e) = database::save_product(db_path, &product) {
+                log::warn!(
+                    "Failed to save competitor product {}: {}",
+                    product.tiktok_id,
+                    e
+                );
+                continue;
+            }
+
+            let listed =
+                database::add_competitor_product(db_path, &competitor.id, &product.id, &seen_at);
+            if matches!(listed, Ok(true)) && !first_visit {
+                alerts::raise(
+                    Some(app),
+                    db_path,
+                    &[alerts::new_competitor_product(&product, &competitor.name)],
+                );
+            }
+        }
+    }
+
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+
+    result.map(|products| products.len())
+}
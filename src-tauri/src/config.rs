@@ -1,26 +1,254 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use ts_rs::TS;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::models::MarketplaceAccess;
+use crate::scraper::browser::LaunchProfile;
+use crate::scraper::driver::BrowserBackend;
+use crate::scraper::models::ExtractionStrategy;
+use crate::scraper::pagination::{PaginationRule, PaginationStrategy};
+use crate::scraper::wait::WaitCondition;
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct CredentialsConfig {
     pub openai_key: String,
     pub proxies: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Scraper settings, as saved in settings.json and edited in the app, plus
+/// the run-time fields the app fills in before a run (browser profile,
+/// database, selector manifest, ...), which are never serialized
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct ScraperConfig {
     pub max_products: u32,
     pub interval_minutes: u32,
     pub categories: Vec<String>,
     pub use_proxy: bool,
-    pub proxies: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "null_as_empty")]
+    pub proxies: Vec<String>,
     pub headless: bool,
-    pub timeout: u32,
+    pub timeout: u32, // Page load timeout in milliseconds
+    #[serde(default = "default_min_delay_ms")]
+    pub min_delay_ms: u32, // Random pause between pages, lower bound
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u32, // Random pause between pages, upper bound
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32, // Retries of a failed page, with exponential backoff
+    #[serde(default = "default_max_concurrent_browsers")]
+    pub max_concurrent_browsers: u32,
+    #[serde(default = "default_true")]
+    pub safety_switch_enabled: bool, // Stop the run when bot detection is hit
+    #[serde(default = "default_max_detection_rate")]
+    pub max_detection_rate: f32, // Share of detected pages that trips the safety switch
+    #[serde(default = "default_safety_cooldown_seconds")]
+    pub safety_cooldown_seconds: u32, // Pause after the safety switch trips
+    #[serde(default = "default_consecutive_failures_threshold")]
+    pub consecutive_failures_threshold: u32,
+    #[serde(default = "default_region")]
+    pub region: String, // TikTok Shop region code, drives price/number parsing
+    #[serde(default)]
+    pub enrich_details: bool, // Deep mode: open each product page for description, variants, shipping and seller
+    #[serde(default)]
+    pub raw_capture: bool, // Keep each product's source JSON for debugging
+    #[serde(default = "default_true")]
+    pub intercept_api: bool, // Read products from the shop's API responses before the DOM
+    #[serde(default = "default_true")]
+    pub block_resources: bool, // Block fonts, analytics and ads while scraping
+    #[serde(default)]
+    pub block_images: bool, // Also block images (the live viewer shows no pictures)
+    #[serde(default)]
+    pub wait_conditions: Vec<WaitCondition>, // Page readiness checks, empty = built-in defaults
+    #[serde(default = "default_page_watchdog_secs")]
+    pub page_watchdog_secs: u32, // A page taking longer is abandoned and its tab recycled
+    #[serde(default = "default_tabs_per_browser")]
+    pub tabs_per_browser: u32, // Pages scraped side by side in one browser
+    #[serde(default)]
+    pub backend: BrowserBackend, // chromium, firefox or auto (Firefox when Chromium fails)
+    #[serde(default = "default_webdriver_url")]
+    pub webdriver_url: String, // geckodriver address used by the Firefox backend
+    #[serde(default)]
+    pub launch_profile: LaunchProfile, // standard, low_memory, stealth_max or debug
+    #[serde(default)]
+    pub remote_debugging_url: Option<String>, // e.g. http://127.0.0.1:9222 of a Chrome started with --remote-debugging-port
+    #[serde(default = "default_profile_id")]
+    pub profile_id: String, // Session profile; its fingerprint is kept between runs
+    #[serde(default)]
+    pub human_typing: bool, // Higher stealth: type search keywords with human-like delays and typos
+    #[serde(default)]
+    pub ocr_prices: bool, // Read prices rendered as images from the card screenshot (needs the `ocr` build)
+    #[serde(default)]
+    pub offline_folder: Option<String>, // Parse the .html/.mhtml pages saved here instead of browsing
+    #[serde(default)]
+    pub marketplace: MarketplaceAccess, // Storefront searched for plain categories: tiktok, shopee, aliexpress or mercadolivre
+    #[serde(default)]
+    pub mercadolivre_api: bool, // Search Mercado Livre through its REST API instead of the site
+
+    // Filled in by `runtime_scraper_config`
+    #[serde(skip)]
+    pub user_data_path: Option<String>,
+    #[serde(skip)]
+    pub db_path: Option<String>,
+    #[serde(skip)]
+    pub selectors: Option<Vec<String>>,
+    #[serde(skip)]
+    pub extraction_strategies: Option<Vec<ExtractionStrategy>>, // From the selector manifest
+    #[serde(skip)]
+    pub pagination: PaginationStrategy, // Default way to load more results
+    #[serde(skip)]
+    pub pagination_rules: Vec<PaginationRule>, // Per-URL overrides
+    #[serde(skip)]
+    pub run_id: Option<String>, // Id the run's actions are logged under
+    #[serde(skip)]
+    pub session_id: Option<String>, // Scrape session checkpointed to, resumed if it has progress
+    #[serde(skip)]
+    pub language: String, // Language of log and status messages (AppSettings.language)
+    #[serde(skip)]
+    pub persist_logs: bool, // Keep the log entries of the run in run_logs
+    #[serde(skip)]
+    pub api_key: Option<String>, // Research API
+    #[serde(skip)]
+    pub api_secret: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            max_products: 50,
+            interval_minutes: 60,
+            categories: Vec::new(),
+            use_proxy: false,
+            proxies: Vec::new(),
+            headless: true,
+            timeout: 30000,
+            min_delay_ms: default_min_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            max_retries: default_max_retries(),
+            max_concurrent_browsers: default_max_concurrent_browsers(),
+            safety_switch_enabled: true,
+            max_detection_rate: default_max_detection_rate(),
+            safety_cooldown_seconds: default_safety_cooldown_seconds(),
+            consecutive_failures_threshold: default_consecutive_failures_threshold(),
+            region: default_region(),
+            enrich_details: false,
+            raw_capture: false,
+            intercept_api: true,
+            block_resources: true,
+            block_images: false,
+            wait_conditions: Vec::new(),
+            page_watchdog_secs: default_page_watchdog_secs(),
+            tabs_per_browser: default_tabs_per_browser(),
+            backend: BrowserBackend::default(),
+            webdriver_url: default_webdriver_url(),
+            launch_profile: LaunchProfile::default(),
+            remote_debugging_url: None,
+            profile_id: default_profile_id(),
+            human_typing: false,
+            ocr_prices: false,
+            offline_folder: None,
+            marketplace: MarketplaceAccess::default(),
+            mercadolivre_api: false,
+            user_data_path: None,
+            db_path: None,
+            selectors: None,
+            extraction_strategies: None,
+            pagination: PaginationStrategy::default(),
+            pagination_rules: Vec::new(),
+            run_id: None,
+            session_id: None,
+            language: "pt-BR".to_string(),
+            persist_logs: false,
+            api_key: None,
+            api_secret: None,
+        }
+    }
+}
+
+/// Older settings files store `"proxies": null`
+fn null_as_empty<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<Vec<String>>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_min_delay_ms() -> u32 {
+    2000
+}
+
+fn default_max_delay_ms() -> u32 {
+    5000
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_max_concurrent_browsers() -> u32 {
+    1
+}
+
+fn default_max_detection_rate() -> f32 {
+    0.2
+}
+
+fn default_safety_cooldown_seconds() -> u32 {
+    3600
+}
+
+fn default_consecutive_failures_threshold() -> u32 {
+    5
+}
+
+fn default_region() -> String {
+    "BR".to_string()
+}
+
+fn default_page_watchdog_secs() -> u32 {
+    300
+}
+
+fn default_tabs_per_browser() -> u32 {
+    2
+}
+
+fn default_profile_id() -> String {
+    "default".to_string()
+}
+
+fn default_log_retention_days() -> u32 {
+    30
+}
+
+fn default_error_page_retention_days() -> u32 {
+    14
+}
+
+fn default_min_free_space_mb() -> u64 {
+    1024
+}
+
+fn default_display_currency() -> String {
+    "BRL".to_string()
+}
+
+fn default_update_channel() -> String {
+    "stable".to_string()
+}
+
+fn default_webdriver_url() -> String {
+    crate::scraper::webdriver::DEFAULT_WEBDRIVER_URL.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct LicenseConfig {
     pub key: Option<String>,
     pub plan: String,  // "lifetime" or "trial"
@@ -30,21 +258,144 @@ pub struct LicenseConfig {
     pub credits: i32,  // Créditos IA disponíveis
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct SystemConfig {
     pub auto_update: bool,
     pub check_interval: u32,
     pub logs_enabled: bool,
     pub max_log_size: u32,
     pub analytics_enabled: bool,
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32, // Persisted run logs older than this are pruned
+    #[serde(default = "default_error_page_retention_days")]
+    pub error_page_retention_days: u32, // Stored error pages older than this are pruned
+    #[serde(default = "default_min_free_space_mb")]
+    pub min_free_space_mb: u64, // Scrapes don't start, and running ones stop, below this
+    #[serde(default = "default_true")]
+    pub auto_cleanup: bool, // Clear caches and old logs when free space runs low
+    #[serde(default = "default_update_channel")]
+    pub update_channel: String, // Updater channel: "stable" or "beta"
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Fields missing from older settings files take their defaults
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(default, rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SyncConfig {
+    pub batch_size: u32,   // Products per upload request
+    pub max_retries: u32,  // Retries per chunk before the sync is aborted
+    pub auto_upload: bool, // Hybrid mode: queue freshly scraped products for upload
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 200,
+            max_retries: 3,
+            auto_upload: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ShortcutConfig {
+    pub enabled: bool,
+    pub toggle_scrape: String, // Start/stop scraping, e.g. "CmdOrControl+Shift+S"
+    pub quick_search: String,  // Focus the window and open the search palette
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            toggle_scrape: "CmdOrControl+Shift+S".to_string(),
+            quick_search: "CmdOrControl+Shift+K".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DigestConfig {
+    pub enabled: bool,              // Generate the weekly digest automatically
+    pub weekday: u32,               // 0 = Monday ... 6 = Sunday, local time
+    pub hour: u32,                  // Local hour from which the digest is due
+    pub export_dir: Option<String>, // Defaults to <app data>/digests
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            weekday: 0,
+            hour: 8,
+            export_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct WhatsappConfig {
+    pub enabled: bool,
+    pub access_token: String,    // WhatsApp Cloud API token of the Business account
+    pub phone_number_id: String, // Business number messages are sent from
+    pub recipient: String,       // Number messages go to, with country code
+    pub send_alerts: bool,       // Product alerts, as they are raised
+    pub send_digest: bool,       // The weekly digest, once generated
+}
+
+impl Default for WhatsappConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            access_token: String::new(),
+            phone_number_id: String::new(),
+            recipient: String::new(),
+            send_alerts: true,
+            send_digest: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ClipboardConfig {
+    pub enabled: bool, // Offer to import TikTok Shop product links when copied
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CompanionConfig {
+    pub enabled: bool, // Accept products pushed by the browser extension
+    pub port: u16,     // Localhost port of the endpoint; changes apply on restart
+}
+
+impl Default for CompanionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47821,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct AppSettings {
     pub theme: String,
     pub language: String,
+    #[serde(default = "default_display_currency")]
+    pub display_currency: String, // ISO 4217 code prices are shown in
     pub notifications_enabled: bool,
     pub auto_update: bool,
     pub max_products_per_search: u32,
@@ -64,6 +415,20 @@ pub struct AppSettings {
     pub scraper: ScraperConfig,
     pub license: LicenseConfig,
     pub system: SystemConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub shortcuts: ShortcutConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub clipboard: ClipboardConfig,
+    #[serde(default)]
+    pub companion: CompanionConfig,
+    #[serde(default)]
+    pub whatsapp: WhatsappConfig,
+    #[serde(default)]
+    pub active_profile: Option<String>, // Named settings profile last switched to
 }
 
 impl Default for AppSettings {
@@ -71,6 +436,7 @@ impl Default for AppSettings {
         Self {
             theme: "system".to_string(),
             language: "pt-BR".to_string(),
+            display_currency: default_display_currency(),
             notifications_enabled: true,
             auto_update: true,
             max_products_per_search: 50,
@@ -90,15 +456,7 @@ impl Default for AppSettings {
                 openai_key: "".to_string(),
                 proxies: Vec::new(),
             },
-            scraper: ScraperConfig {
-                max_products: 50,
-                interval_minutes: 60,
-                categories: Vec::new(),
-                use_proxy: false,
-                proxies: None,
-                headless: true,
-                timeout: 30000,
-            },
+            scraper: ScraperConfig::default(),
             license: LicenseConfig {
                 key: None,
                 plan: "lifetime".to_string(),
@@ -113,7 +471,94 @@ impl Default for AppSettings {
                 logs_enabled: true,
                 max_log_size: 10,
                 analytics_enabled: false,
+                log_retention_days: default_log_retention_days(),
+                error_page_retention_days: default_error_page_retention_days(),
+                min_free_space_mb: default_min_free_space_mb(),
+                auto_cleanup: true,
+                update_channel: default_update_channel(),
             },
+            sync: SyncConfig::default(),
+            shortcuts: ShortcutConfig::default(),
+            digest: DigestConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            companion: CompanionConfig::default(),
+            whatsapp: WhatsappConfig::default(),
+            active_profile: None,
         }
     }
 }
+
+/// A proxy URL carrying a username or password
+fn has_credentials(proxy: &str) -> bool {
+    proxy
+        .split_once("://")
+        .map_or(proxy, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .is_some_and(|authority| authority.contains('@'))
+}
+
+impl AppSettings {
+    /// Copy safe to write outside the app: the OpenAI, WhatsApp and license
+    /// keys are blanked and proxies with credentials in their URL are dropped
+    pub fn without_secrets(&self) -> Self {
+        let mut settings = self.clone();
+        settings.credentials.openai_key = String::new();
+        settings.whatsapp.access_token = String::new();
+        settings.credentials.proxies.retain(|p| !has_credentials(p));
+        settings.proxy_list.retain(|p| !has_credentials(p));
+        settings.scraper.proxies.retain(|p| !has_credentials(p));
+        settings.license.key = None;
+        settings
+    }
+
+    /// These settings with the parts tied to this install taken from
+    /// `current`: credentials (the WhatsApp token too), license and setup state
+    pub fn with_account_of(mut self, current: &AppSettings) -> Self {
+        self.credentials = current.credentials.clone();
+        self.whatsapp.access_token = current.whatsapp.access_token.clone();
+        self.license = current.license.clone();
+        self.setup_complete = current.setup_complete;
+        self.terms_accepted = current.terms_accepted;
+        self.terms_accepted_at = current.terms_accepted_at.clone();
+        self
+    }
+}
+
+/// File written by `export_settings`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub settings: AppSettings,
+}
+
+/// Named snapshot of the settings, e.g. "home connection" or "proxy farm"
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SettingsProfile {
+    pub name: String,
+    pub saved_at: String,
+    pub settings: AppSettings,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_without_newer_sync_fields() {
+        // settings.json as saved before auto_upload existed
+        let mut json = serde_json::to_value(AppSettings::default()).unwrap();
+        json["sync"] = serde_json::json!({ "batchSize": 50, "maxRetries": 5 });
+        json["license"]["key"] = serde_json::json!("LICENSE-KEY");
+
+        let settings: AppSettings = serde_json::from_value(json).unwrap();
+        assert_eq!(settings.sync.batch_size, 50);
+        assert_eq!(settings.sync.max_retries, 5);
+        assert!(settings.sync.auto_upload);
+        assert_eq!(settings.license.key.as_deref(), Some("LICENSE-KEY"));
+    }
+}
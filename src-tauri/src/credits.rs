@@ -0,0 +1,187 @@
+// Credit Ledger
+// AI credits spent and the balances reported by the backend. Every copy the
+// backend generates is recorded with its tokens and takes its credits off
+// the running balance; a balance the backend reports, with a copy or a
+// license check, replaces it. The UI checks operations that cost credits
+// against the balance to warn first; the backend has the final say, as the
+// local balance lags behind top-ups.
+
+use chrono::Utc;
+use std::path::Path;
+
+use crate::database;
+use crate::models::{CreditBalance, CreditCheck, CreditLedgerEntry};
+
+/// Credits a generated copy costs when the backend doesn't say
+pub const COPY_CREDIT_COST: i32 = 1;
+
+/// Ledger entries returned with the balance
+const RECENT_ENTRIES: i64 = 20;
+
+/// Latest known balance and when it was last updated
+fn current(db_path: &Path) -> rusqlite::Result<(Option<i32>, Option<String>)> {
+    Ok(database::get_credit_entries(db_path, 1)?
+        .into_iter()
+        .next()
+        .map(|entry| (entry.balance, Some(entry.created_at)))
+        .unwrap_or_default())
+}
+
+pub fn balance(db_path: &Path) -> rusqlite::Result<CreditBalance> {
+    let (balance, updated_at) = current(db_path)?;
+    let since = (Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+
+    Ok(CreditBalance {
+        balance,
+        used_last_30_days: database::get_credits_used_since(db_path, &since)?,
+        updated_at,
+        recent: database::get_credit_entries(db_path, RECENT_ENTRIES)?,
+    })
+}
+
+/// Record `credits` spent on `description`. The balance becomes
+/// `reported_balance` when the backend sent one, the previous one minus
+/// `credits` otherwise.
+pub fn record_usage(
+    db_path: &Path,
+    description: &str,
+    credits: i32,
+    tokens_used: i32,
+    reported_balance: Option<i32>,
+) -> rusqlite::Result<()> {
+    let (previous, _) = current(db_path)?;
+    let balance = reported_balance.or_else(|| previous.map(|b| (b - credits).max(0)));
+
+    database::add_credit_entry(
+        db_path,
+        &CreditLedgerEntry {
+            id: 0,
+            kind: "usage".to_string(),
+            amount: -credits,
+            balance,
+            tokens_used,
+            description: description.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+        },
+    )?;
+    Ok(())
+}
+
+/// Take the balance the backend reported; nothing is recorded when it
+/// matches the running one
+pub fn sync(db_path: &Path, reported: i32, description: &str) -> rusqlite::Result<()> {
+    let (previous, _) = current(db_path)?;
+    if previous == Some(reported) {
+        return Ok(());
+    }
+
+    database::add_credit_entry(
+        db_path,
+        &CreditLedgerEntry {
+            id: 0,
+            kind: "sync".to_string(),
+            amount: reported - previous.unwrap_or(0),
+            balance: Some(reported),
+            tokens_used: 0,
+            description: description.to_string(),
+            created_at: Utc::now().to_rfc3339(),
+        },
+    )?;
+    Ok(())
+}
+
+/// Whether the balance covers `cost`. An unknown balance isn't held against
+/// the operation; the backend has the last word.
+pub fn check(db_path: &Path, cost: i32) -> rusqlite::Result<CreditCheck> {
+    let (balance, _) = current(db_path)?;
+    let sufficient = balance.is_none_or(|b| b >= cost);
+
+    Ok(CreditCheck {
+        cost,
+        balance,
+        sufficient,
+        message: (!sufficient).then(|| {
+            format!(
+                "Esta operação usa {} créditos e restam {}.",
+                cost,
+                balance.unwrap_or(0)
+            )
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::TempDb;
+
+    fn latest(db_path: &Path) -> CreditLedgerEntry {
+        database::get_credit_entries(db_path, 1).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_unknown_balance() {
+        let db = TempDb::new();
+        assert_eq!(balance(db.path()).unwrap().balance, None);
+
+        // Nothing to hold against the operation
+        let check = check(db.path(), COPY_CREDIT_COST).unwrap();
+        assert!(check.sufficient);
+        assert_eq!(check.balance, None);
+        assert!(check.message.is_none());
+
+        // Usage without a known balance leaves it unknown
+        record_usage(db.path(), "copy", 1, 120, None).unwrap();
+        let entry = latest(db.path());
+        assert_eq!((entry.amount, entry.balance), (-1, None));
+        assert_eq!(balance(db.path()).unwrap().used_last_30_days, 1);
+    }
+
+    #[test]
+    fn test_record_usage() {
+        let db = TempDb::new();
+        sync(db.path(), 10, "license").unwrap();
+
+        // Taken off the running balance
+        record_usage(db.path(), "copy", 3, 200, None).unwrap();
+        assert_eq!(latest(db.path()).balance, Some(7));
+
+        // The balance the backend reports wins
+        record_usage(db.path(), "copy", 1, 80, Some(2)).unwrap();
+        assert_eq!(latest(db.path()).balance, Some(2));
+
+        // Never below zero
+        record_usage(db.path(), "copy", 5, 300, None).unwrap();
+        assert_eq!(latest(db.path()).balance, Some(0));
+        assert_eq!(balance(db.path()).unwrap().used_last_30_days, 9);
+    }
+
+    #[test]
+    fn test_sync() {
+        let db = TempDb::new();
+        sync(db.path(), 10, "license").unwrap();
+        let first = latest(db.path());
+        assert_eq!((first.kind.as_str(), first.amount), ("sync", 10));
+
+        // An unchanged balance adds no entry
+        sync(db.path(), 10, "license").unwrap();
+        assert_eq!(latest(db.path()).id, first.id);
+
+        // A top-up is recorded as the difference
+        sync(db.path(), 50, "license").unwrap();
+        let topped_up = latest(db.path());
+        assert_eq!((topped_up.amount, topped_up.balance), (40, Some(50)));
+    }
+
+    #[test]
+    fn test_check() {
+        let db = TempDb::new();
+        sync(db.path(), 1, "license").unwrap();
+        assert!(check(db.path(), 1).unwrap().sufficient);
+
+        let short = check(db.path(), 2).unwrap();
+        assert!(!short.sufficient);
+        assert_eq!(short.balance, Some(1));
+        assert!(short.message.unwrap().contains("restam 1"));
+    }
+}
@@ -0,0 +1,80 @@
+// CRM Leads
+// Supplier and creator contacts captured while researching products, moved
+// through a pipeline: new, contacted, negotiating, then won or lost. Leads are
+// kept locally and count against the plan's crm_leads limit when created. In
+// hybrid mode every change is queued in pending_sync and sent to the backend
+// CRM.
+
+use std::path::Path;
+
+use crate::backend;
+use crate::config::AppSettings;
+use crate::database;
+use crate::models::CrmLead;
+
+/// pending_sync entity type of leads
+pub const ENTITY_TYPE: &str = "crm_lead";
+
+/// Queue a change to a lead ("upsert" or "delete") for the backend CRM
+pub fn queue(db_path: &Path, lead_id: &str, operation: &str) {
+    if let Err(e) = database::add_pending_sync(db_path, ENTITY_TYPE, lead_id, operation, None) {
+        log::warn!("Failed to queue lead {} for sync: {}", lead_id, e);
+    }
+}
+
+/// Send the queued lead changes to the backend CRM in one request, removing
+/// them once accepted; returns how many were sent
+pub async fn upload_pending(db_path: &Path, settings: &AppSettings) -> Result<usize, String> {
+    let pending: Vec<_> = database::get_pending_sync(db_path)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(_, entity_type, _, _, _)| entity_type == ENTITY_TYPE)
+        .collect();
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let mut leads: Vec<CrmLead> = Vec::new();
+    let mut deleted: Vec<&str> = Vec::new();
+    let mut sent = Vec::new();
+    for (id, _, lead_id, operation, _) in &pending {
+        if operation == "delete" {
+            deleted.push(lead_id);
+            sent.push(id);
+            continue;
+        }
+        match database::get_crm_lead(db_path, lead_id) {
+            Ok(Some(lead)) => {
+                leads.push(lead);
+                sent.push(id);
+            }
+            // Lead was deleted meanwhile; its delete is queued too
+            _ => database::remove_pending_sync(db_path, id).map_err(|e| e.to_string())?,
+        }
+    }
+    if sent.is_empty() {
+        return Ok(0);
+    }
+
+    let request = backend::post("/api/crm/leads/sync").json(&serde_json::json!({
+        "leads": leads,
+        "deleted": deleted,
+    }));
+    let result = match backend::send_retrying(request, settings.sync.max_retries).await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("CRM sync failed: {}", response.status())),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        for id in &sent {
+            database::mark_pending_sync_failed(db_path, id, &e).ok();
+        }
+        return Err(e);
+    }
+
+    for id in &sent {
+        database::remove_pending_sync(db_path, id).map_err(|e| e.to_string())?;
+    }
+    log::info!("CRM sync: {} leads sent to backend", sent.len());
+    Ok(sent.len())
+}
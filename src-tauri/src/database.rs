@@ -1,12 +1,192 @@
 // Database module for SQLite operations
 use crate::models::*;
-use rusqlite::{params, Connection, OptionalExtension, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Result, ToSql};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::path::Path;
 use uuid::Uuid;
 
+/// Error pages kept at most; older ones are deleted as new ones come in
+const MAX_ERROR_PAGES: i64 = 200;
+
+/// Days deleted items stay in the trash
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Tables referring to products, lists and competitors, with what happens to
+/// their rows when the row referred to is deleted or saved under a new id.
+/// Databases created before these were declared are rebuilt.
+const FOREIGN_KEY_TABLES: [(&str, &str); 7] = [
+    (
+        "product_history",
+        "CREATE TABLE IF NOT EXISTS product_history (
+            id TEXT PRIMARY KEY,
+            product_id TEXT NOT NULL,
+            price REAL NOT NULL,
+            sales_count INTEGER DEFAULT 0,
+            stock_level INTEGER,
+            collected_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE
+        )",
+    ),
+    (
+        "product_variants",
+        "CREATE TABLE IF NOT EXISTS product_variants (
+            id TEXT PRIMARY KEY,
+            product_id TEXT NOT NULL,
+            sku_id TEXT,
+            name TEXT NOT NULL,
+            price REAL NOT NULL DEFAULT 0,
+            stock_level INTEGER,
+            image_url TEXT,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE
+        )",
+    ),
+    (
+        "favorites",
+        "CREATE TABLE IF NOT EXISTS favorites (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            product_id TEXT NOT NULL,
+            tiktok_id TEXT,
+            list_id TEXT,
+            notes TEXT,
+            added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (user_id) REFERENCES users(id),
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE,
+            FOREIGN KEY (list_id) REFERENCES favorite_lists(id) ON DELETE SET NULL,
+            UNIQUE(user_id, product_id)
+        )",
+    ),
+    (
+        // Copies outlive the product they were written for
+        "copy_history",
+        "CREATE TABLE IF NOT EXISTS copy_history (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            product_id TEXT,
+            copy_type TEXT NOT NULL,
+            tone TEXT NOT NULL,
+            content TEXT NOT NULL,
+            tokens_used INTEGER DEFAULT 0,
+            is_favorite INTEGER DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (user_id) REFERENCES users(id),
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE SET NULL
+        )",
+    ),
+    (
+        // Products re-fetched on their own schedule
+        "watched_products",
+        "CREATE TABLE IF NOT EXISTS watched_products (
+            product_id TEXT PRIMARY KEY,
+            interval_minutes INTEGER NOT NULL,
+            last_checked_at TEXT,
+            next_check_at TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE
+        )",
+    ),
+    (
+        // Alerts raised when a tracked product changes
+        "product_alerts",
+        "CREATE TABLE IF NOT EXISTS product_alerts (
+            id TEXT PRIMARY KEY,
+            product_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            message TEXT NOT NULL,
+            old_value REAL,
+            new_value REAL,
+            is_read INTEGER DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE
+        )",
+    ),
+    (
+        // Products seen in each competitor's store
+        "competitor_catalog",
+        "CREATE TABLE IF NOT EXISTS competitor_catalog (
+            competitor_id TEXT NOT NULL,
+            product_id TEXT NOT NULL,
+            first_seen_at TEXT NOT NULL,
+            last_seen_at TEXT NOT NULL,
+            PRIMARY KEY (competitor_id, product_id),
+            FOREIGN KEY (competitor_id) REFERENCES competitors(id) ON DELETE CASCADE,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE CASCADE
+        )",
+    ),
+];
+
+/// Create the tables of `FOREIGN_KEY_TABLES`, rebuilding those created
+/// without the ON DELETE actions. Rows are copied as they are: dangling ones
+/// are left to `cleanup_orphans`.
+fn create_foreign_key_tables(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    for (table, create) in FOREIGN_KEY_TABLES {
+        let existing: Option<String> = tx
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?",
+                params![table],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match existing {
+            None => {
+                tx.execute(create, [])?;
+            }
+            Some(sql) if !sql.contains("ON DELETE") => {
+                let columns = |table: &str| -> Result<Vec<String>> {
+                    let mut stmt = tx.prepare(&format!("PRAGMA table_info({})", table))?;
+                    let columns = stmt
+                        .query_map([], |row| row.get(1))?
+                        .collect::<Result<Vec<String>>>()?;
+                    Ok(columns)
+                };
+
+                let rebuilt = format!("{}_rebuilt", table);
+                tx.execute(
+                    &create.replacen(&format!("{} (", table), &format!("{} (", rebuilt), 1),
+                    [],
+                )?;
+                let old_columns = columns(table)?;
+                let copied: Vec<String> = columns(&rebuilt)?
+                    .into_iter()
+                    .filter(|c| old_columns.contains(c))
+                    .collect();
+
+                // Renaming checks the views, which must not refer to the
+                // dropped table; the batch below creates it again
+                tx.execute("DROP VIEW IF EXISTS competitor_products", [])?;
+                tx.execute(
+                    &format!(
+                        "INSERT INTO {} ({2}) SELECT {2} FROM {}",
+                        rebuilt,
+                        table,
+                        copied.join(", ")
+                    ),
+                    [],
+                )?;
+                tx.execute(&format!("DROP TABLE {}", table), [])?;
+                tx.execute(&format!("ALTER TABLE {} RENAME TO {}", rebuilt, table), [])?;
+                log::info!("Rebuilt table {} with its foreign key actions", table);
+            }
+            Some(_) => {}
+        }
+    }
+
+    tx.commit()
+}
+
 pub fn init_database(db_path: &Path) -> Result<()> {
     let conn = Connection::open(db_path)?;
 
+    create_foreign_key_tables(&conn)?;
+
     conn.execute_batch(
         "
         -- Users table
@@ -53,15 +233,12 @@ pub fn init_database(db_path: &Path) -> Result<()> {
             updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
         );
 
-        -- Product history table
-        CREATE TABLE IF NOT EXISTS product_history (
-            id TEXT PRIMARY KEY,
-            product_id TEXT NOT NULL,
-            price REAL NOT NULL,
-            sales_count INTEGER DEFAULT 0,
-            stock_level INTEGER,
-            collected_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (product_id) REFERENCES products(id)
+        -- Raw JSON payloads (debug capture), gzip-compressed, latest per product
+        CREATE TABLE IF NOT EXISTS product_raw_payloads (
+            tiktok_id TEXT PRIMARY KEY,
+            source TEXT NOT NULL,
+            payload BLOB NOT NULL,
+            captured_at TEXT NOT NULL
         );
 
         -- Indexes for products
@@ -71,19 +248,6 @@ pub fn init_database(db_path: &Path) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_products_rating ON products(product_rating);
         CREATE INDEX IF NOT EXISTS idx_products_category ON products(category);
 
-        -- Favorites table
-        CREATE TABLE IF NOT EXISTS favorites (
-            id TEXT PRIMARY KEY,
-            user_id TEXT NOT NULL,
-            product_id TEXT NOT NULL,
-            list_id TEXT,
-            notes TEXT,
-            added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (user_id) REFERENCES users(id),
-            FOREIGN KEY (product_id) REFERENCES products(id),
-            UNIQUE(user_id, product_id)
-        );
-
         -- Favorite lists table
         CREATE TABLE IF NOT EXISTS favorite_lists (
             id TEXT PRIMARY KEY,
@@ -97,21 +261,6 @@ pub fn init_database(db_path: &Path) -> Result<()> {
             FOREIGN KEY (user_id) REFERENCES users(id)
         );
 
-        -- Copy history table
-        CREATE TABLE IF NOT EXISTS copy_history (
-            id TEXT PRIMARY KEY,
-            user_id TEXT NOT NULL,
-            product_id TEXT,
-            copy_type TEXT NOT NULL,
-            tone TEXT NOT NULL,
-            content TEXT NOT NULL,
-            tokens_used INTEGER DEFAULT 0,
-            is_favorite INTEGER DEFAULT 0,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (user_id) REFERENCES users(id),
-            FOREIGN KEY (product_id) REFERENCES products(id)
-        );
-
         -- Search history table
         CREATE TABLE IF NOT EXISTS search_history (
             id TEXT PRIMARY KEY,
@@ -170,6 +319,209 @@ pub fn init_database(db_path: &Path) -> Result<()> {
             completed_at TEXT
         );
 
+        -- Scraper action log per run (navigations, scrolls, clicks, waits)
+        CREATE TABLE IF NOT EXISTS run_actions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            url TEXT NOT NULL,
+            detail TEXT NOT NULL DEFAULT '',
+            elapsed_ms INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        -- Scraper log entries per run, keyed by collection_logs.id
+        CREATE TABLE IF NOT EXISTS run_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_id TEXT NOT NULL,
+            level TEXT NOT NULL,
+            code TEXT NOT NULL,
+            params TEXT NOT NULL DEFAULT '{}',
+            message TEXT NOT NULL,
+            category TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        -- Competitor stores tracked on a schedule
+        CREATE TABLE IF NOT EXISTS competitors (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            store_url TEXT NOT NULL UNIQUE,
+            price_change_percent REAL NOT NULL,
+            interval_minutes INTEGER NOT NULL,
+            last_checked_at TEXT,
+            next_check_at TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE VIEW IF NOT EXISTS competitor_products AS
+            SELECT c.id AS competitor_id, c.name AS competitor_name,
+                   cc.first_seen_at, cc.last_seen_at, p.*
+            FROM competitor_catalog cc
+            JOIN competitors c ON c.id = cc.competitor_id
+            JOIN products p ON p.id = cc.product_id;
+
+        -- Named scraper configs for recurring workflows
+        CREATE TABLE IF NOT EXISTS scrape_presets (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            config TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        -- Anonymous usage events, kept until sent (only with analytics on)
+        CREATE TABLE IF NOT EXISTS telemetry_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            properties TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            sent_at TEXT
+        );
+
+        -- Reports exported after each scraper run
+        CREATE TABLE IF NOT EXISTS report_schedules (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            preset_id TEXT,
+            format TEXT NOT NULL,
+            folder TEXT,
+            webhook_url TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL
+        );
+
+        -- Reports generated by the schedules, and failures
+        CREATE TABLE IF NOT EXISTS report_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            schedule_id TEXT NOT NULL,
+            schedule_name TEXT NOT NULL,
+            run_id TEXT NOT NULL,
+            success INTEGER NOT NULL,
+            path TEXT,
+            product_count INTEGER NOT NULL,
+            error TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        -- Copies generated by the backend, by hash of what they were generated from
+        CREATE TABLE IF NOT EXISTS copy_cache (
+            key TEXT PRIMARY KEY,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        -- AI credits spent and balances reported by the backend
+        CREATE TABLE IF NOT EXISTS credit_ledger (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            amount INTEGER NOT NULL,
+            balance INTEGER,
+            tokens_used INTEGER NOT NULL DEFAULT 0,
+            description TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        -- Category scrapes repeated on an interval
+        CREATE TABLE IF NOT EXISTS scrape_schedules (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            categories TEXT NOT NULL,
+            interval_minutes INTEGER NOT NULL,
+            paused INTEGER NOT NULL DEFAULT 0,
+            last_run_at TEXT,
+            next_run_at TEXT NOT NULL,
+            last_error TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        -- Supplier and creator contacts, moved through a status pipeline
+        CREATE TABLE IF NOT EXISTS crm_leads (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            name TEXT NOT NULL,
+            company TEXT,
+            email TEXT,
+            phone TEXT,
+            handle TEXT,
+            product_id TEXT,
+            status TEXT NOT NULL DEFAULT 'new',
+            notes TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE SET NULL
+        );
+
+        -- Posts queued for social networks, published by the backend when due
+        CREATE TABLE IF NOT EXISTS social_posts (
+            id TEXT PRIMARY KEY,
+            product_id TEXT,
+            copy_id TEXT,
+            platform TEXT NOT NULL,
+            caption TEXT NOT NULL,
+            image_url TEXT,
+            scheduled_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'scheduled',
+            external_id TEXT,
+            error TEXT,
+            published_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (product_id) REFERENCES products(id) ON UPDATE CASCADE ON DELETE SET NULL
+        );
+
+        -- Progress of category scrapes, deleted once they complete
+        CREATE TABLE IF NOT EXISTS scrape_sessions (
+            id TEXT PRIMARY KEY,
+            config TEXT NOT NULL,
+            categories TEXT NOT NULL,
+            checkpoints TEXT NOT NULL DEFAULT '[]',
+            status TEXT NOT NULL DEFAULT 'running',
+            reason TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+
+        -- Products collected so far by a scrape session
+        CREATE TABLE IF NOT EXISTS scrape_session_products (
+            session_id TEXT NOT NULL,
+            tiktok_id TEXT NOT NULL,
+            product TEXT NOT NULL,
+            PRIMARY KEY (session_id, tiktok_id)
+        );
+
+        -- Deleted lists, favorites and products, restorable until they expire
+        CREATE TABLE IF NOT EXISTS trash (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            item_id TEXT NOT NULL,
+            label TEXT NOT NULL,
+            rows TEXT NOT NULL,
+            deleted_at TEXT NOT NULL
+        );
+
+        -- Research goals shown on the dashboard
+        CREATE TABLE IF NOT EXISTS goals (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            target INTEGER NOT NULL,
+            period TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        -- Saturation score per niche, recomputed after each scrape
+        CREATE TABLE IF NOT EXISTS niche_saturation (
+            niche TEXT PRIMARY KEY,
+            products INTEGER NOT NULL,
+            sellers INTEGER NOT NULL,
+            avg_price REAL NOT NULL,
+            price_dispersion REAL NOT NULL,
+            new_listing_rate REAL,
+            score REAL NOT NULL,
+            computed_at TEXT NOT NULL
+        );
+
         -- Create indexes for better performance
         CREATE INDEX IF NOT EXISTS idx_products_category ON products(category);
         CREATE INDEX IF NOT EXISTS idx_products_trending ON products(is_trending);
@@ -180,7 +532,19 @@ pub fn init_database(db_path: &Path) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_favorites_product ON favorites(product_id);
         CREATE INDEX IF NOT EXISTS idx_search_history_user ON search_history(user_id);
         CREATE INDEX IF NOT EXISTS idx_copy_history_user ON copy_history(user_id);
+        CREATE INDEX IF NOT EXISTS idx_product_variants_product ON product_variants(product_id);
+        CREATE INDEX IF NOT EXISTS idx_product_history_product ON product_history(product_id, collected_at);
+        CREATE INDEX IF NOT EXISTS idx_run_actions_run ON run_actions(run_id);
+        CREATE INDEX IF NOT EXISTS idx_run_logs_run ON run_logs(run_id);
+        CREATE INDEX IF NOT EXISTS idx_run_logs_created ON run_logs(created_at);
+        CREATE INDEX IF NOT EXISTS idx_watched_next_check ON watched_products(next_check_at);
+        CREATE INDEX IF NOT EXISTS idx_product_alerts_created ON product_alerts(created_at);
+        CREATE INDEX IF NOT EXISTS idx_social_posts_scheduled ON social_posts(status, scheduled_at);
         
+        -- The single local user favorites, lists and copies belong to
+        INSERT OR IGNORE INTO users (id, email, plan_expires_at)
+            VALUES ('default_user', 'default_user@localhost', '');
+
         -- Insert default settings
         INSERT OR IGNORE INTO settings (key, value) VALUES ('theme', 'dark');
         INSERT OR IGNORE INTO settings (key, value) VALUES ('language', 'pt-BR');
@@ -192,33 +556,92 @@ pub fn init_database(db_path: &Path) -> Result<()> {
     // Migration: Add stock_level column if it doesn't exist
     let _ = conn.execute("ALTER TABLE products ADD COLUMN stock_level INTEGER", []);
 
+    // Migration: Variant price range
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN min_price REAL", []);
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN max_price REAL", []);
+
+    // Migration: Shipping fee and delivery estimate
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN shipping_fee REAL", []);
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN delivery_days_min INTEGER", []);
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN delivery_days_max INTEGER", []);
+
+    // Migration: Affiliate open-collaboration flag
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN open_collaboration INTEGER", []);
+
+    // Migration: Persisted browser fingerprint per session profile
+    let _ = conn.execute("ALTER TABLE profiles ADD COLUMN fingerprint TEXT", []);
+
+    // Migration: Demand estimate derived from the product history (JSON)
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN demand_estimate TEXT", []);
+
+    // Migration: Seeded demo product flag
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN is_demo INTEGER DEFAULT 0", []);
+
+    // Migration: Gzipped error page HTML (`html` stays empty for new rows)
+    let _ = conn.execute("ALTER TABLE error_pages ADD COLUMN html_gz BLOB", []);
+    let _ = conn.execute("ALTER TABLE error_pages ADD COLUMN html_size INTEGER", []);
+
+    // Migration: User-defined order and pinning of favorite lists
+    let _ = conn.execute("ALTER TABLE favorite_lists ADD COLUMN sort_order INTEGER", []);
+    let _ = conn.execute("ALTER TABLE favorite_lists ADD COLUMN pinned INTEGER DEFAULT 0", []);
+
+    // Migration: TikTok id of favorited products, to follow rows saved under new ids
+    let _ = conn.execute("ALTER TABLE favorites ADD COLUMN tiktok_id TEXT", []);
+
+    // Migration: User tags and archive flag, and fields edited by hand (JSON
+    // array of column names) that scrapes leave alone
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN tags TEXT", []);
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN is_archived INTEGER DEFAULT 0", []);
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN edited_fields TEXT", []);
+
+    // Migration: Where product values come from (`ProductSource`)
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN source TEXT DEFAULT 'scraper'", []);
+
+    // Migration: Saved page of products scraped in offline mode
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN source_file TEXT", []);
+
+    // Migration: Marketplace of each product; earlier rows are all TikTok Shop's
+    let _ = conn.execute("ALTER TABLE products ADD COLUMN marketplace TEXT DEFAULT 'tiktok'", []);
+    let _ = conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_products_marketplace ON products(marketplace)",
+        [],
+    );
+
+    // Hybrid mode queues uploads in pending_sync as soon as products are scraped
+    init_subscription_tables(db_path)?;
+
     log::info!("Database initialized successfully at {:?}", db_path);
     Ok(())
 }
 
 pub fn get_connection(db_path: &Path) -> Result<Connection> {
-    Connection::open(db_path)
+    let conn = Connection::open(db_path)?;
+    // SQLite only enforces the declared foreign keys when asked to, per
+    // connection
+    conn.pragma_update(None, "foreign_keys", true)?;
+    Ok(conn)
+}
+
+/// Open the database and run a trivial query, to check it is usable
+pub fn ping(db_path: &Path) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+    Ok(())
 }
 
 // ==========================================
 // PRODUCT QUERIES
 // ==========================================
 
-pub fn search_products(
-    db_path: &Path,
-    filters: &SearchFilters,
-) -> Result<PaginatedResponse<Product>> {
-    let conn = get_connection(db_path)?;
-
-    let mut query = String::from("SELECT * FROM products WHERE 1=1");
-    let mut count_query = String::from("SELECT COUNT(*) FROM products WHERE 1=1");
+/// WHERE conditions of the filter set, each starting with " AND ", and
+/// their parameters
+fn filter_clause(filters: &SearchFilters) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+    let mut clause = String::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-    // Build WHERE clauses
     if let Some(ref q) = filters.query {
         let search_clause = " AND (title LIKE ? OR description LIKE ? OR category LIKE ?)";
-        query.push_str(search_clause);
-        count_query.push_str(search_clause);
+        clause.push_str(search_clause);
         let search_term = format!("%{}%", q);
         params_vec.push(Box::new(search_term.clone()));
         params_vec.push(Box::new(search_term.clone()));
@@ -227,55 +650,99 @@ pub fn search_products(
 
     if !filters.categories.is_empty() {
         let placeholders: Vec<&str> = filters.categories.iter().map(|_| "?").collect();
-        let clause = format!(" AND category IN ({})", placeholders.join(","));
-        query.push_str(&clause);
-        count_query.push_str(&clause);
+        clause.push_str(&format!(" AND category IN ({})", placeholders.join(",")));
         for cat in &filters.categories {
             params_vec.push(Box::new(cat.clone()));
         }
     }
 
+    if !filters.marketplaces.is_empty() {
+        let placeholders: Vec<&str> = filters.marketplaces.iter().map(|_| "?").collect();
+        clause.push_str(&format!(" AND marketplace IN ({})", placeholders.join(",")));
+        for marketplace in &filters.marketplaces {
+            params_vec.push(Box::new(marketplace.as_str()));
+        }
+    }
+
     if let Some(min) = filters.price_min {
-        query.push_str(" AND price >= ?");
-        count_query.push_str(" AND price >= ?");
+        clause.push_str(" AND price >= ?");
         params_vec.push(Box::new(min));
     }
 
     if let Some(max) = filters.price_max {
-        query.push_str(" AND price <= ?");
-        count_query.push_str(" AND price <= ?");
+        clause.push_str(" AND price <= ?");
         params_vec.push(Box::new(max));
     }
 
     if let Some(min) = filters.sales_min {
-        query.push_str(" AND sales_count >= ?");
-        count_query.push_str(" AND sales_count >= ?");
+        clause.push_str(" AND sales_count >= ?");
         params_vec.push(Box::new(min));
     }
 
     if let Some(min) = filters.rating_min {
-        query.push_str(" AND product_rating >= ?");
-        count_query.push_str(" AND product_rating >= ?");
+        clause.push_str(" AND product_rating >= ?");
         params_vec.push(Box::new(min));
     }
 
     if let Some(true) = filters.has_free_shipping {
-        query.push_str(" AND has_free_shipping = 1");
-        count_query.push_str(" AND has_free_shipping = 1");
+        clause.push_str(" AND has_free_shipping = 1");
+    }
+
+    if let Some(max) = filters.shipping_max {
+        clause.push_str(" AND (has_free_shipping = 1 OR shipping_fee <= ?)");
+        params_vec.push(Box::new(max));
+    }
+
+    if let Some(max) = filters.delivery_days_max {
+        clause.push_str(" AND delivery_days_max <= ?");
+        params_vec.push(Box::new(max));
     }
 
     if let Some(true) = filters.is_trending {
-        query.push_str(" AND is_trending = 1");
-        count_query.push_str(" AND is_trending = 1");
+        clause.push_str(" AND is_trending = 1");
     }
 
     if let Some(true) = filters.is_on_sale {
-        query.push_str(" AND is_on_sale = 1");
-        count_query.push_str(" AND is_on_sale = 1");
+        clause.push_str(" AND is_on_sale = 1");
     }
 
+    if let Some(since) = &filters.updated_since {
+        clause.push_str(" AND datetime(updated_at) >= datetime(?)");
+        params_vec.push(Box::new(since.clone()));
+    }
+
+    if !filters.include_archived.unwrap_or(false) {
+        clause.push_str(" AND is_archived = 0");
+    }
+
+    if let Some(max) = filters.saturation_max {
+        clause.push_str(
+            " AND (category IS NULL OR category NOT IN
+            (SELECT niche FROM niche_saturation WHERE score > ?))",
+        );
+        params_vec.push(Box::new(max));
+    }
+
+    (clause, params_vec)
+}
+
+pub fn search_products(
+    db_path: &Path,
+    filters: &SearchFilters,
+) -> Result<PaginatedResponse<Product>> {
+    let conn = get_connection(db_path)?;
+
+    let (clause, params_vec) = filter_clause(filters);
+    let mut query = format!("SELECT * FROM products WHERE 1=1{}", clause);
+    let count_query = format!("SELECT COUNT(*) FROM products WHERE 1=1{}", clause);
+
     // ORDER BY
-    let sort_by = filters.sort_by.as_deref().unwrap_or("collected_at");
+    let sort_by = match filters.sort_by.as_deref().unwrap_or("collected_at") {
+        // Estimated units per day, stored inside the demand estimate JSON
+        "demand" => "json_extract(demand_estimate, '$.unitsPerDay')",
+        "saturation" => "(SELECT score FROM niche_saturation WHERE niche = products.category)",
+        column => column,
+    };
     let sort_order = filters.sort_order.as_deref().unwrap_or("DESC");
     query.push_str(&format!(" ORDER BY {} {}", sort_by, sort_order));
 
@@ -296,45 +763,7 @@ pub fn search_products(
     // Execute main query
     let mut stmt = conn.prepare(&query)?;
     let products = stmt
-        .query_map(params_refs.as_slice(), |row| {
-            Ok(Product {
-                id: row.get(0)?,
-                tiktok_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                price: row.get(4)?,
-                original_price: row.get(5)?,
-                currency: row
-                    .get::<_, Option<String>>(6)?
-                    .unwrap_or_else(|| "BRL".to_string()),
-                category: row.get(7)?,
-                subcategory: row.get(8)?,
-                seller_name: row.get(9)?,
-                seller_rating: row.get(10)?,
-                product_rating: row.get(11)?,
-                reviews_count: row.get(12)?,
-                sales_count: row.get(13)?,
-                sales_7d: row.get(14)?,
-                sales_30d: row.get(15)?,
-                commission_rate: row.get(16)?,
-                image_url: row.get(17)?,
-                images: serde_json::from_str(
-                    &row.get::<_, Option<String>>(18)?
-                        .unwrap_or_else(|| "[]".to_string()),
-                )
-                .unwrap_or_default(),
-                video_url: row.get(19)?,
-                product_url: row.get(20)?,
-                affiliate_url: row.get(21)?,
-                has_free_shipping: row.get::<_, i32>(22)? == 1,
-                is_trending: row.get::<_, i32>(23)? == 1,
-                is_on_sale: row.get::<_, i32>(24)? == 1,
-                in_stock: row.get::<_, i32>(25)? == 1,
-                stock_level: row.get(28).ok(), // Try to get stock_level, default to None if column missing or null
-                collected_at: row.get(26)?,
-                updated_at: row.get(27)?,
-            })
-        })?
+        .query_map(params_refs.as_slice(), map_product_row)?
         .filter_map(|r| r.ok())
         .collect::<Vec<_>>();
 
@@ -349,89 +778,609 @@ pub fn search_products(
     })
 }
 
+/// Histogram of the prices of the products matching the filters, in
+/// `buckets` equal bands between the lowest and highest price
+pub fn get_price_distribution(
+    db_path: &Path,
+    filters: &SearchFilters,
+    buckets: u32,
+) -> Result<PriceDistribution> {
+    let conn = get_connection(db_path)?;
+
+    let (clause, mut params_vec) = filter_clause(filters);
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let (total, min_price, max_price): (i64, Option<f64>, Option<f64>) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*), MIN(price), MAX(price) FROM products WHERE 1=1{}",
+            clause
+        ),
+        params_refs.as_slice(),
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )?;
+
+    let (Some(min_price), Some(max_price)) = (min_price, max_price) else {
+        return Ok(PriceDistribution {
+            total,
+            min_price: 0.0,
+            max_price: 0.0,
+            buckets: Vec::new(),
+        });
+    };
+
+    // A single price fits in one band
+    let buckets = if max_price > min_price { buckets.max(1) } else { 1 };
+    let width = (max_price - min_price) / buckets as f64;
+    let mut counts = vec![0i64; buckets as usize];
+
+    params_vec.insert(0, Box::new(buckets as i64 - 1));
+    params_vec.insert(0, Box::new(width));
+    params_vec.insert(0, Box::new(min_price));
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CASE WHEN ?2 > 0 THEN MIN(CAST((price - ?1) / ?2 AS INTEGER), ?3) ELSE 0 END AS bucket,
+                COUNT(*)
+         FROM products WHERE 1=1{}
+         GROUP BY bucket",
+        clause
+    ))?;
+    let rows = stmt.query_map(params_refs.as_slice(), |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+    })?;
+    for row in rows {
+        let (bucket, count) = row?;
+        if let Some(slot) = counts.get_mut(bucket as usize) {
+            *slot += count;
+        }
+    }
+
+    Ok(PriceDistribution {
+        total,
+        min_price,
+        max_price,
+        buckets: counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| PriceBucket {
+                from: min_price + width * i as f64,
+                to: if i as u32 + 1 == buckets {
+                    max_price
+                } else {
+                    min_price + width * (i + 1) as f64
+                },
+                count,
+            })
+            .collect(),
+    })
+}
+
 pub fn get_product_by_id(db_path: &Path, id: &str) -> Result<Option<Product>> {
     let conn = get_connection(db_path)?;
 
     let mut stmt = conn.prepare("SELECT * FROM products WHERE id = ?")?;
-    let product = stmt
-        .query_row(params![id], |row| {
-            Ok(Product {
-                id: row.get(0)?,
-                tiktok_id: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                price: row.get(4)?,
-                original_price: row.get(5)?,
-                currency: row
-                    .get::<_, Option<String>>(6)?
-                    .unwrap_or_else(|| "BRL".to_string()),
-                category: row.get(7)?,
-                subcategory: row.get(8)?,
-                seller_name: row.get(9)?,
-                seller_rating: row.get(10)?,
-                product_rating: row.get(11)?,
-                reviews_count: row.get(12)?,
-                sales_count: row.get(13)?,
-                sales_7d: row.get(14)?,
-                sales_30d: row.get(15)?,
-                commission_rate: row.get(16)?,
-                image_url: row.get(17)?,
-                images: serde_json::from_str(
-                    &row.get::<_, Option<String>>(18)?
-                        .unwrap_or_else(|| "[]".to_string()),
-                )
-                .unwrap_or_default(),
-                video_url: row.get(19)?,
-                product_url: row.get(20)?,
-                affiliate_url: row.get(21)?,
-                has_free_shipping: row.get::<_, i32>(22)? == 1,
-                is_trending: row.get::<_, i32>(23)? == 1,
-                is_on_sale: row.get::<_, i32>(24)? == 1,
-                in_stock: row.get::<_, i32>(25)? == 1,
-                stock_level: row.get(28).ok(),
-                collected_at: row.get(26)?,
-                updated_at: row.get(27)?,
-            })
-        })
+    let mut product = stmt
+        .query_row(params![id], map_product_row)
         .optional()?;
 
+    if let Some(product) = product.as_mut() {
+        product.variants = get_product_variants(db_path, &product.id)?;
+    }
+
     Ok(product)
 }
 
-pub fn save_product_history(db_path: &Path, product: &Product) -> Result<()> {
+/// Other products in the category or with any of the keywords in their
+/// title, best sellers first
+pub fn get_related_candidates(
+    db_path: &Path,
+    product_id: &str,
+    category: Option<&str>,
+    keywords: &[String],
+    limit: i64,
+) -> Result<Vec<Product>> {
     let conn = get_connection(db_path)?;
-    let id = Uuid::new_v4().to_string();
 
-    conn.execute(
-        "INSERT INTO product_history (id, product_id, price, sales_count, stock_level, collected_at)
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![
-            id,
-            product.id,
-            product.price,
-            product.sales_count,
-            product.stock_level,
-            product.collected_at
-        ],
-    )?;
-    Ok(())
+    let mut conditions = vec!["category = ?".to_string()];
+    let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = vec![
+        Box::new(product_id.to_string()),
+        // NULL matches nothing, so only keywords find candidates
+        Box::new(category.filter(|c| !c.is_empty()).map(str::to_string)),
+    ];
+    for keyword in keywords {
+        conditions.push("title LIKE ?".to_string());
+        params_vec.push(Box::new(format!("%{}%", keyword)));
+    }
+    params_vec.push(Box::new(limit));
+    let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT * FROM products WHERE id != ? AND ({}) ORDER BY sales_count DESC LIMIT ?",
+        conditions.join(" OR ")
+    ))?;
+    let products = stmt
+        .query_map(params_refs.as_slice(), map_product_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(products)
 }
 
-pub fn save_product(db_path: &Path, product: &Product) -> Result<()> {
+pub fn get_product_by_tiktok_id(db_path: &Path, tiktok_id: &str) -> Result<Option<Product>> {
     let conn = get_connection(db_path)?;
 
-    conn.execute(
-        "INSERT OR REPLACE INTO products (
-            id, tiktok_id, title, description, price, original_price, currency,
-            category, subcategory, seller_name, seller_rating, product_rating,
-            reviews_count, sales_count, sales_7d, sales_30d, commission_rate,
-            image_url, images, video_url, product_url, affiliate_url,
-            has_free_shipping, is_trending, is_on_sale, in_stock, stock_level,
-            collected_at, updated_at
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        params![
-            product.id,
-            product.tiktok_id,
+    let mut stmt =
+        conn.prepare("SELECT * FROM products WHERE tiktok_id = ? ORDER BY updated_at DESC")?;
+    stmt.query_row(params![tiktok_id], map_product_row).optional()
+}
+
+pub fn get_product_variants(db_path: &Path, product_id: &str) -> Result<Vec<ProductVariant>> {
+    let conn = get_connection(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, product_id, sku_id, name, price, stock_level, image_url
+         FROM product_variants WHERE product_id = ? ORDER BY price ASC, name ASC",
+    )?;
+
+    let variants = stmt.query_map(params![product_id], |row| {
+        Ok(ProductVariant {
+            id: row.get(0)?,
+            product_id: row.get(1)?,
+            sku_id: row.get(2)?,
+            name: row.get(3)?,
+            price: row.get(4)?,
+            stock_level: row.get(5)?,
+            image_url: row.get(6)?,
+        })
+    })?;
+
+    Ok(variants.filter_map(|r| r.ok()).collect())
+}
+
+/// Insert the variants of a product whose previous variants were already cleared
+fn insert_product_variants(
+    conn: &Connection,
+    product_id: &str,
+    variants: &[ProductVariant],
+) -> Result<()> {
+    for variant in variants {
+        conn.execute(
+            "INSERT INTO product_variants (id, product_id, sku_id, name, price, stock_level, image_url)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                variant.id,
+                product_id,
+                variant.sku_id,
+                variant.name,
+                variant.price,
+                variant.stock_level,
+                variant.image_url
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Map a `products` row by column name.
+///
+/// Columns added through migrations (e.g. `stock_level`) end up at the end of
+/// the table on upgraded databases but in declaration order on fresh ones, so
+/// positional access is not reliable here.
+fn map_product_row(row: &rusqlite::Row) -> rusqlite::Result<Product> {
+    Ok(Product {
+        id: row.get("id")?,
+        tiktok_id: row.get("tiktok_id")?,
+        title: row.get("title")?,
+        description: row.get("description")?,
+        price: row.get("price")?,
+        original_price: row.get("original_price")?,
+        currency: row
+            .get::<_, Option<String>>("currency")?
+            .unwrap_or_else(|| "BRL".to_string()),
+        category: row.get("category")?,
+        subcategory: row.get("subcategory")?,
+        seller_name: row.get("seller_name")?,
+        seller_rating: row.get("seller_rating")?,
+        product_rating: row.get("product_rating")?,
+        reviews_count: row.get("reviews_count")?,
+        sales_count: row.get("sales_count")?,
+        sales_7d: row.get("sales_7d")?,
+        sales_30d: row.get("sales_30d")?,
+        commission_rate: row.get("commission_rate")?,
+        open_collaboration: row
+            .get::<_, Option<i32>>("open_collaboration")
+            .ok()
+            .flatten()
+            .map(|v| v == 1),
+        image_url: row.get("image_url")?,
+        images: serde_json::from_str(
+            &row.get::<_, Option<String>>("images")?
+                .unwrap_or_else(|| "[]".to_string()),
+        )
+        .unwrap_or_default(),
+        video_url: row.get("video_url")?,
+        product_url: row.get("product_url")?,
+        affiliate_url: row.get("affiliate_url")?,
+        has_free_shipping: row.get::<_, i32>("has_free_shipping")? == 1,
+        is_trending: row.get::<_, i32>("is_trending")? == 1,
+        is_on_sale: row.get::<_, i32>("is_on_sale")? == 1,
+        in_stock: row.get::<_, i32>("in_stock")? == 1,
+        stock_level: row.get::<_, Option<i32>>("stock_level").ok().flatten(),
+        min_price: row.get::<_, Option<f64>>("min_price").ok().flatten(),
+        max_price: row.get::<_, Option<f64>>("max_price").ok().flatten(),
+        variants: vec![],
+        shipping_fee: row.get::<_, Option<f64>>("shipping_fee").ok().flatten(),
+        delivery_days_min: row.get::<_, Option<i32>>("delivery_days_min").ok().flatten(),
+        delivery_days_max: row.get::<_, Option<i32>>("delivery_days_max").ok().flatten(),
+        demand_estimate: row
+            .get::<_, Option<String>>("demand_estimate")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok()),
+        collected_at: row.get("collected_at")?,
+        updated_at: row.get("updated_at")?,
+        raw_payload: None,
+        is_demo: row.get::<_, Option<i32>>("is_demo").ok().flatten().unwrap_or(0) != 0,
+        tags: row
+            .get::<_, Option<String>>("tags")
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        is_archived: row.get::<_, Option<i32>>("is_archived").ok().flatten().unwrap_or(0) != 0,
+        source: row
+            .get::<_, Option<String>>("source")
+            .ok()
+            .flatten()
+            .and_then(|source| ProductSource::parse(&source))
+            .unwrap_or_default(),
+        source_file: row.get("source_file").ok().flatten(),
+        marketplace: row
+            .get::<_, Option<String>>("marketplace")
+            .ok()
+            .flatten()
+            .and_then(|marketplace| MarketplaceAccess::parse(&marketplace))
+            .unwrap_or_default(),
+    })
+}
+
+/// Count products updated after `since` (all products when `None`). Demo
+/// products are left out here and in `get_products_updated_since`, so they are
+/// never uploaded or reported.
+pub fn count_products_updated_since(db_path: &Path, since: Option<&str>) -> Result<i64> {
+    let conn = get_connection(db_path)?;
+
+    match since {
+        Some(since) => conn.query_row(
+            "SELECT COUNT(*) FROM products WHERE updated_at > ? AND is_demo = 0",
+            params![since],
+            |row| row.get(0),
+        ),
+        None => conn.query_row(
+            "SELECT COUNT(*) FROM products WHERE is_demo = 0",
+            [],
+            |row| row.get(0),
+        ),
+    }
+}
+
+/// Get a page of products updated after `since`, oldest change first
+pub fn get_products_updated_since(
+    db_path: &Path,
+    since: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Product>> {
+    let conn = get_connection(db_path)?;
+
+    let products = match since {
+        Some(since) => {
+            let mut stmt = conn.prepare(
+                "SELECT * FROM products WHERE updated_at > ? AND is_demo = 0
+                 ORDER BY updated_at ASC, id ASC LIMIT ? OFFSET ?",
+            )?;
+            let rows = stmt.query_map(params![since, limit, offset], map_product_row)?;
+            rows.filter_map(|r| r.ok()).collect()
+        }
+        None => {
+            let mut stmt = conn.prepare(
+                "SELECT * FROM products WHERE is_demo = 0
+                 ORDER BY updated_at ASC, id ASC LIMIT ? OFFSET ?",
+            )?;
+            let rows = stmt.query_map(params![limit, offset], map_product_row)?;
+            rows.filter_map(|r| r.ok()).collect()
+        }
+    };
+
+    Ok(products)
+}
+
+fn gzip(text: &str) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(text.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Decompress the blob of result column `column`
+fn gunzip(bytes: &[u8], column: usize) -> Result<String> {
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Blob, Box::new(e))
+    })?;
+    Ok(text)
+}
+
+fn save_raw_payload(conn: &Connection, tiktok_id: &str, raw: &RawPayload) -> Result<()> {
+    let compressed = gzip(&raw.json)?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO product_raw_payloads (tiktok_id, source, payload, captured_at)
+         VALUES (?, ?, ?, ?)",
+        params![tiktok_id, raw.source, compressed, raw.captured_at],
+    )?;
+    Ok(())
+}
+
+/// Latest raw JSON captured for a product, decompressed
+pub fn get_raw_payload(db_path: &Path, product_id: &str) -> Result<Option<RawPayload>> {
+    let conn = get_connection(db_path)?;
+
+    let row = conn
+        .query_row(
+            "SELECT r.source, r.payload, r.captured_at FROM product_raw_payloads r
+             JOIN products p ON p.tiktok_id = r.tiktok_id
+             WHERE p.id = ?",
+            params![product_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((source, payload, captured_at)) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(RawPayload {
+        source,
+        json: gunzip(&payload, 1)?,
+        captured_at,
+    }))
+}
+
+/// Store commission terms fetched from the affiliate center
+pub fn update_product_commission(
+    db_path: &Path,
+    product_id: &str,
+    commission_rate: Option<f64>,
+    open_collaboration: Option<bool>,
+) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+
+    let rows = conn.execute(
+        "UPDATE products SET
+            commission_rate = COALESCE(?, commission_rate),
+            open_collaboration = COALESCE(?, open_collaboration),
+            updated_at = ?
+         WHERE id = ?",
+        params![
+            commission_rate,
+            open_collaboration.map(|v| v as i32),
+            chrono::Utc::now().to_rfc3339(),
+            product_id
+        ],
+    )?;
+
+    Ok(rows > 0)
+}
+
+/// Append a snapshot of the product to its history, unless it is identical to
+/// the latest one. Returns whether a row was written.
+pub fn save_product_history(db_path: &Path, product: &Product) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+
+    let latest: Option<(f64, i32, Option<i32>)> = conn
+        .query_row(
+            "SELECT price, sales_count, stock_level FROM product_history
+             WHERE product_id = ? ORDER BY collected_at DESC LIMIT 1",
+            params![product.id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    if latest == Some((product.price, product.sales_count, product.stock_level)) {
+        return Ok(false);
+    }
+
+    let id = Uuid::new_v4().to_string();
+
+    conn.execute(
+        "INSERT INTO product_history (id, product_id, price, sales_count, stock_level, collected_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            product.id,
+            product.price,
+            product.sales_count,
+            product.stock_level,
+            product.collected_at
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Collapse runs of identical consecutive snapshots into their first and last
+/// rows, which mark when the values started and were last seen. Returns the
+/// number of rows removed.
+pub fn compact_product_history(db_path: &Path) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+
+    conn.execute(
+        "DELETE FROM product_history WHERE id IN (
+             SELECT id FROM (
+                 SELECT id, price, sales_count, stock_level,
+                        LAG(id) OVER w AS prev_id,
+                        LAG(price) OVER w AS prev_price,
+                        LAG(sales_count) OVER w AS prev_sales,
+                        LAG(stock_level) OVER w AS prev_stock,
+                        LEAD(id) OVER w AS next_id,
+                        LEAD(price) OVER w AS next_price,
+                        LEAD(sales_count) OVER w AS next_sales,
+                        LEAD(stock_level) OVER w AS next_stock
+                 FROM product_history
+                 WINDOW w AS (PARTITION BY product_id ORDER BY collected_at)
+             )
+             WHERE prev_id IS NOT NULL AND next_id IS NOT NULL
+               AND price = prev_price AND price = next_price
+               AND sales_count IS prev_sales AND sales_count IS next_sales
+               AND stock_level IS prev_stock AND stock_level IS next_stock
+         )",
+        [],
+    )
+}
+
+/// Remove the seeded demo products and everything attached to them; returns
+/// the number of products removed
+pub fn clear_demo_products(db_path: &Path) -> Result<usize> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    for table in [
+        "product_history",
+        "product_variants",
+        "favorites",
+        "copy_history",
+        "watched_products",
+        "product_alerts",
+        "competitor_catalog",
+    ] {
+        tx.execute(
+            &format!(
+                "DELETE FROM {} WHERE product_id IN (SELECT id FROM products WHERE is_demo = 1)",
+                table
+            ),
+            [],
+        )?;
+    }
+    tx.execute(
+        "DELETE FROM product_raw_payloads
+         WHERE tiktok_id IN (SELECT tiktok_id FROM products WHERE is_demo = 1)",
+        [],
+    )?;
+    let removed = tx.execute("DELETE FROM products WHERE is_demo = 1", [])?;
+
+    tx.commit()?;
+    Ok(removed)
+}
+
+/// Whether any value tracked over time differs between the stored row and a
+/// new scrape of the same product
+fn product_changed(stored: &Product, product: &Product) -> bool {
+    stored.title != product.title
+        || stored.price != product.price
+        || stored.original_price != product.original_price
+        || stored.min_price != product.min_price
+        || stored.max_price != product.max_price
+        || stored.sales_count != product.sales_count
+        || stored.sales_7d != product.sales_7d
+        || stored.sales_30d != product.sales_30d
+        || stored.stock_level != product.stock_level
+        || stored.in_stock != product.in_stock
+        || stored.is_on_sale != product.is_on_sale
+        || stored.has_free_shipping != product.has_free_shipping
+        || stored.product_rating != product.product_rating
+        || stored.reviews_count != product.reviews_count
+        || stored.commission_rate != product.commission_rate
+        || stored.seller_name != product.seller_name
+}
+
+/// Save a product, comparing it with the row stored under its TikTok id.
+/// The row, its history and the demand estimate are only written when
+/// something changed.
+pub fn save_product(db_path: &Path, product: &Product) -> Result<SaveOutcome> {
+    let conn = get_connection(db_path)?;
+
+    let stored = conn
+        .query_row(
+            "SELECT * FROM products WHERE tiktok_id = ?",
+            params![product.tiktok_id],
+            map_product_row,
+        )
+        .optional()?;
+    let outcome = match &stored {
+        None => SaveOutcome::New,
+        Some(stored) if product_changed(stored, product) => SaveOutcome::Updated,
+        Some(_) => SaveOutcome::Unchanged,
+    };
+
+    // Nothing tracked changed: leave the row, its id and updated_at alone so
+    // it stays out of the delta sync. Only the variants and payload follow
+    // the scrape.
+    if let (SaveOutcome::Unchanged, Some(stored)) = (outcome, &stored) {
+        conn.execute(
+            "DELETE FROM product_variants WHERE product_id = ?",
+            params![stored.id],
+        )?;
+        insert_product_variants(&conn, &stored.id, &product.variants)?;
+        if let Some(raw) = &product.raw_payload {
+            save_raw_payload(&conn, &product.tiktok_id, raw)?;
+        }
+        return Ok(outcome);
+    }
+
+    // Variants mirror the latest scrape. The row may be saved under a new id,
+    // so clear the old ones by TikTok id before saving. Rows referring to the
+    // product follow the new id through their foreign keys.
+    conn.execute(
+        "DELETE FROM product_variants
+         WHERE product_id = ? OR product_id IN (SELECT id FROM products WHERE tiktok_id = ?)",
+        params![product.id, product.tiktok_id],
+    )?;
+
+    conn.execute(
+        "INSERT INTO products (
+            id, tiktok_id, title, description, price, original_price, currency,
+            category, subcategory, seller_name, seller_rating, product_rating,
+            reviews_count, sales_count, sales_7d, sales_30d, commission_rate, open_collaboration,
+            image_url, images, video_url, product_url, affiliate_url,
+            has_free_shipping, is_trending, is_on_sale, in_stock, stock_level,
+            min_price, max_price, shipping_fee, delivery_days_min, delivery_days_max,
+            collected_at, updated_at, is_demo, source, source_file, marketplace
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(tiktok_id) DO UPDATE SET
+            id = excluded.id, title = excluded.title, description = excluded.description,
+            price = excluded.price, original_price = excluded.original_price,
+            currency = CASE WHEN products.edited_fields LIKE '%\"currency\"%'
+                THEN products.currency ELSE excluded.currency END,
+            category = CASE WHEN products.edited_fields LIKE '%\"category\"%'
+                THEN products.category ELSE excluded.category END,
+            subcategory = excluded.subcategory, seller_name = excluded.seller_name,
+            seller_rating = excluded.seller_rating,
+            product_rating = excluded.product_rating,
+            reviews_count = excluded.reviews_count, sales_count = excluded.sales_count,
+            sales_7d = excluded.sales_7d, sales_30d = excluded.sales_30d,
+            commission_rate = excluded.commission_rate,
+            open_collaboration = excluded.open_collaboration,
+            image_url = excluded.image_url, images = excluded.images,
+            video_url = excluded.video_url, product_url = excluded.product_url,
+            affiliate_url = excluded.affiliate_url,
+            has_free_shipping = excluded.has_free_shipping,
+            is_trending = excluded.is_trending, is_on_sale = excluded.is_on_sale,
+            in_stock = excluded.in_stock, stock_level = excluded.stock_level,
+            min_price = excluded.min_price, max_price = excluded.max_price,
+            shipping_fee = excluded.shipping_fee,
+            delivery_days_min = excluded.delivery_days_min,
+            delivery_days_max = excluded.delivery_days_max,
+            collected_at = excluded.collected_at, updated_at = excluded.updated_at,
+            is_demo = excluded.is_demo, source = excluded.source,
+            source_file = excluded.source_file, marketplace = excluded.marketplace",
+        params![
+            product.id,
+            product.tiktok_id,
             product.title,
             product.description,
             product.price,
@@ -447,6 +1396,7 @@ pub fn save_product(db_path: &Path, product: &Product) -> Result<()> {
             product.sales_7d,
             product.sales_30d,
             product.commission_rate,
+            product.open_collaboration.map(|v| v as i32),
             product.image_url,
             serde_json::to_string(&product.images).unwrap_or_else(|_| "[]".to_string()),
             product.video_url,
@@ -457,17 +1407,194 @@ pub fn save_product(db_path: &Path, product: &Product) -> Result<()> {
             product.is_on_sale as i32,
             product.in_stock as i32,
             product.stock_level,
+            product.min_price,
+            product.max_price,
+            product.shipping_fee,
+            product.delivery_days_min,
+            product.delivery_days_max,
             product.collected_at,
-            product.updated_at
+            product.updated_at,
+            product.is_demo as i32,
+            product.source.as_str(),
+            product.source_file,
+            product.marketplace.as_str()
         ],
     )?;
 
-    // Save history
+    insert_product_variants(&conn, &product.id, &product.variants)?;
+
+    if let Some(raw) = &product.raw_payload {
+        save_raw_payload(&conn, &product.tiktok_id, raw)?;
+    }
+
+    // Favorites left on an older row of the product follow it
+    conn.execute(
+        "UPDATE OR IGNORE favorites SET product_id = ?1 WHERE tiktok_id = ?2 AND product_id != ?1",
+        params![product.id, product.tiktok_id],
+    )?;
+
     let _ = save_product_history(db_path, product);
 
+    // The new snapshot may change the demand estimate
+    let _ = update_demand_estimate(db_path, &product.id);
+
+    Ok(outcome)
+}
+
+/// Recompute a product's demand estimate from its history
+pub fn update_demand_estimate(db_path: &Path, product_id: &str) -> Result<()> {
+    let history = get_product_history(db_path, product_id)?;
+    let estimate = crate::demand::estimate_demand(&history)
+        .and_then(|estimate| serde_json::to_string(&estimate).ok());
+
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE products SET demand_estimate = ? WHERE id = ?",
+        params![estimate, product_id],
+    )?;
     Ok(())
 }
 
+/// `edited_fields` JSON with the fields of `fields` flagged as set added
+fn with_edited_fields(edited_fields: Option<String>, fields: &[(&str, bool)]) -> String {
+    let mut edited: Vec<String> = edited_fields
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    for (field, set) in fields {
+        if *set && !edited.iter().any(|f| f == field) {
+            edited.push(field.to_string());
+        }
+    }
+    serde_json::to_string(&edited).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Write the values a user can edit by hand (see `ProductFields`) and flag
+/// the product as manual. A category or currency changed here survives later
+/// scrapes; the other values are replaced when the product is scraped again.
+/// Returns false if the product is unknown.
+pub fn update_product(db_path: &Path, product: &Product, stored: &Product) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+
+    let edited_fields: Option<String> = conn
+        .query_row(
+            "SELECT edited_fields FROM products WHERE id = ?",
+            params![product.id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    let edited_fields = with_edited_fields(
+        edited_fields,
+        &[
+            ("category", product.category != stored.category),
+            ("currency", product.currency != stored.currency),
+        ],
+    );
+
+    let rows = conn.execute(
+        "UPDATE products SET
+            title = ?, description = ?, price = ?, original_price = ?, currency = ?,
+            category = ?, seller_name = ?, product_rating = ?, sales_count = ?,
+            shipping_fee = ?, image_url = ?, product_url = ?, is_on_sale = ?,
+            collected_at = ?, updated_at = ?, source = ?, edited_fields = ?
+         WHERE id = ?",
+        params![
+            product.title,
+            product.description,
+            product.price,
+            product.original_price,
+            product.currency,
+            product.category,
+            product.seller_name,
+            product.product_rating,
+            product.sales_count,
+            product.shipping_fee,
+            product.image_url,
+            product.product_url,
+            product.is_on_sale as i32,
+            product.collected_at,
+            product.updated_at,
+            product.source.as_str(),
+            edited_fields,
+            product.id
+        ],
+    )?;
+    if rows == 0 {
+        return Ok(false);
+    }
+
+    if save_product_history(db_path, product)? {
+        update_demand_estimate(db_path, &product.id)?;
+    }
+    Ok(true)
+}
+
+/// Apply `patch` to every product of `product_ids` in one transaction;
+/// returns the number of products updated. The category and currency set
+/// are marked as edited, so scrapes keep them.
+pub fn bulk_update_products(
+    db_path: &Path,
+    product_ids: &[String],
+    patch: &ProductPatch,
+) -> Result<usize> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let mut updated = 0;
+    for product_id in product_ids {
+        let row: Option<(Option<String>, Option<String>)> = tx
+            .query_row(
+                "SELECT tags, edited_fields FROM products WHERE id = ?",
+                params![product_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((tags, edited_fields)) = row else {
+            continue;
+        };
+        let mut tags: Vec<String> = tags
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        tags.retain(|tag| !patch.remove_tags.contains(tag));
+        for tag in &patch.add_tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        let edited_fields = with_edited_fields(
+            edited_fields,
+            &[
+                ("category", patch.category.is_some()),
+                ("currency", patch.currency.is_some()),
+            ],
+        );
+
+        updated += tx.execute(
+            "UPDATE products SET
+                category = CASE WHEN ?1 IS NULL THEN category ELSE NULLIF(?1, '') END,
+                currency = COALESCE(?2, currency),
+                is_archived = COALESCE(?3, is_archived),
+                tags = ?4,
+                edited_fields = ?5,
+                updated_at = ?6
+             WHERE id = ?7",
+            params![
+                patch.category,
+                patch.currency,
+                patch.archived.map(|a| a as i32),
+                serde_json::to_string(&tags).unwrap_or_else(|_| "[]".to_string()),
+                edited_fields,
+                chrono::Utc::now().to_rfc3339(),
+                product_id
+            ],
+        )?;
+    }
+
+    tx.commit()?;
+    Ok(updated)
+}
+
 // ==========================================
 // FAVORITES QUERIES
 // ==========================================
@@ -485,9 +1612,9 @@ pub fn add_favorite(
     let now = chrono::Utc::now().to_rfc3339();
 
     conn.execute(
-        "INSERT INTO favorites (id, user_id, product_id, list_id, notes, added_at)
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![id, user_id, product_id, list_id, notes, now],
+        "INSERT INTO favorites (id, user_id, product_id, tiktok_id, list_id, notes, added_at)
+         VALUES (?, ?, ?, (SELECT tiktok_id FROM products WHERE id = ?), ?, ?, ?)",
+        params![id, user_id, product_id, product_id, list_id, notes, now],
     )?;
 
     Ok(FavoriteItem {
@@ -500,15 +1627,71 @@ pub fn add_favorite(
     })
 }
 
-pub fn remove_favorite(db_path: &Path, user_id: &str, product_id: &str) -> Result<bool> {
+/// Change the notes and list of a favorite; None keeps a value, an empty
+/// string clears it. Returns the favorite updated, None if unknown.
+pub fn update_favorite(
+    db_path: &Path,
+    favorite_id: &str,
+    notes: Option<&str>,
+    list_id: Option<&str>,
+) -> Result<Option<FavoriteItem>> {
     let conn = get_connection(db_path)?;
 
-    let rows = conn.execute(
-        "DELETE FROM favorites WHERE user_id = ? AND product_id = ?",
-        params![user_id, product_id],
+    conn.execute(
+        "UPDATE favorites SET
+            notes = CASE WHEN ?1 IS NULL THEN notes ELSE NULLIF(?1, '') END,
+            list_id = CASE WHEN ?2 IS NULL THEN list_id ELSE NULLIF(?2, '') END
+         WHERE id = ?3",
+        params![notes, list_id, favorite_id],
     )?;
 
-    Ok(rows > 0)
+    conn.query_row(
+        "SELECT id, user_id, product_id, list_id, notes, added_at FROM favorites WHERE id = ?",
+        params![favorite_id],
+        |row| {
+            Ok(FavoriteItem {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                product_id: row.get(2)?,
+                list_id: row.get(3)?,
+                notes: row.get(4)?,
+                added_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Move the user's favorite of a product to the trash
+pub fn remove_favorite(db_path: &Path, user_id: &str, product_id: &str) -> Result<bool> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let favorite = tx
+        .query_row(
+            "SELECT f.id, p.title FROM favorites f
+             LEFT JOIN products p ON p.id = f.product_id
+             WHERE f.user_id = ? AND f.product_id = ?",
+            params![user_id, product_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?;
+    let Some((favorite_id, title)) = favorite else {
+        return Ok(false);
+    };
+
+    let mut rows = TrashRows::new();
+    take_rows(&tx, &mut rows, "favorites", "id = ?", &[&favorite_id])?;
+    add_to_trash(
+        &tx,
+        TrashKind::Favorite,
+        &favorite_id,
+        &title.unwrap_or_default(),
+        &rows,
+    )?;
+
+    tx.commit()?;
+    Ok(true)
 }
 
 pub fn get_favorites(
@@ -519,7 +1702,8 @@ pub fn get_favorites(
     let conn = get_connection(db_path)?;
 
     let mut query = String::from(
-        "SELECT f.*, p.* FROM favorites f
+        "SELECT f.id AS favorite_id, f.user_id, f.product_id, f.list_id, f.notes, f.added_at, p.*
+         FROM favorites f
          JOIN products p ON f.product_id = p.id
          WHERE f.user_id = ?",
     );
@@ -544,46 +1728,14 @@ pub fn get_favorites(
 fn map_favorite_with_product(row: &rusqlite::Row) -> rusqlite::Result<FavoriteWithProduct> {
     Ok(FavoriteWithProduct {
         favorite: FavoriteItem {
-            id: row.get(0)?,
-            user_id: row.get(1)?,
-            product_id: row.get(2)?,
-            list_id: row.get(3)?,
-            notes: row.get(4)?,
-            added_at: row.get(5)?,
-        },
-        product: Product {
-            id: row.get(6)?,
-            tiktok_id: row.get(7)?,
-            title: row.get(8)?,
-            description: row.get(9)?,
-            price: row.get(10)?,
-            original_price: row.get(11)?,
-            currency: row
-                .get::<_, Option<String>>(12)?
-                .unwrap_or_else(|| "BRL".to_string()),
-            category: row.get(13)?,
-            subcategory: row.get(14)?,
-            seller_name: row.get(15)?,
-            seller_rating: row.get(16)?,
-            product_rating: row.get(17)?,
-            reviews_count: row.get(18)?,
-            sales_count: row.get(19)?,
-            sales_7d: row.get(20)?,
-            sales_30d: row.get(21)?,
-            commission_rate: row.get(22)?,
-            image_url: row.get(23)?,
-            images: vec![],
-            video_url: row.get(25)?,
-            product_url: row.get(26)?,
-            affiliate_url: row.get(27)?,
-            has_free_shipping: row.get::<_, i32>(28)? == 1,
-            is_trending: row.get::<_, i32>(29)? == 1,
-            is_on_sale: row.get::<_, i32>(30)? == 1,
-            in_stock: row.get::<_, i32>(31)? == 1,
-            stock_level: row.get(34).ok(),
-            collected_at: row.get(32)?,
-            updated_at: row.get(33)?,
+            id: row.get("favorite_id")?,
+            user_id: row.get("user_id")?,
+            product_id: row.get("product_id")?,
+            list_id: row.get("list_id")?,
+            notes: row.get("notes")?,
+            added_at: row.get("added_at")?,
         },
+        product: map_product_row(row)?,
     })
 }
 
@@ -602,10 +1754,17 @@ pub fn create_favorite_list(
     let color = color.unwrap_or("#FF0050");
     let icon = icon.unwrap_or("heart");
 
+    // New lists go on top of the unpinned ones
+    let sort_order: i64 = conn.query_row(
+        "SELECT COALESCE(MIN(sort_order), 0) - 1 FROM favorite_lists WHERE user_id = ?",
+        params![user_id],
+        |row| row.get(0),
+    )?;
+
     conn.execute(
-        "INSERT INTO favorite_lists (id, user_id, name, description, color, icon, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-        params![id, user_id, name, description, color, icon, now, now],
+        "INSERT INTO favorite_lists (id, user_id, name, description, color, icon, sort_order, pinned, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, 0, ?, ?)",
+        params![id, user_id, name, description, color, icon, sort_order, now, now],
     )?;
 
     Ok(FavoriteList {
@@ -616,25 +1775,113 @@ pub fn create_favorite_list(
         color: color.to_string(),
         icon: icon.to_string(),
         product_count: 0,
+        sort_order,
+        pinned: false,
         created_at: now.clone(),
         updated_at: now,
     })
 }
 
-pub fn get_favorite_lists(db_path: &Path, user_id: &str) -> Result<Vec<FavoriteList>> {
-    let conn = get_connection(db_path)?;
+/// Re-link the user's favorites to the current row of their product. A
+/// favorite whose product is already favorited under the current row is
+/// merged into that one; favorites whose product is gone are reported.
+pub fn repair_favorites(db_path: &Path, user_id: &str) -> Result<FavoriteRepairReport> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+    let mut report = FavoriteRepairReport::default();
 
-    let mut stmt = conn.prepare(
-        "SELECT fl.*, COUNT(f.id) as product_count
+    // Favorites saved before TikTok ids were kept
+    tx.execute(
+        "UPDATE favorites SET tiktok_id = (SELECT tiktok_id FROM products WHERE products.id = favorites.product_id)
+         WHERE tiktok_id IS NULL AND user_id = ?",
+        params![user_id],
+    )?;
+
+    // (favorite id, product id, tiktok id, current row id, list id, notes)
+    type Stale = (String, String, Option<String>, Option<String>, Option<String>, Option<String>);
+    let stale: Vec<Stale> = {
+        let mut stmt = tx.prepare(
+            "SELECT f.id, f.product_id, f.tiktok_id,
+                    (SELECT p.id FROM products p WHERE p.tiktok_id = f.tiktok_id),
+                    f.list_id, f.notes
+             FROM favorites f
+             WHERE f.user_id = ? AND NOT EXISTS (SELECT 1 FROM products p WHERE p.id = f.product_id)",
+        )?;
+        let rows = stmt
+            .query_map(params![user_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        rows
+    };
+
+    for (favorite_id, product_id, tiktok_id, current_id, list_id, notes) in stale {
+        let Some(current_id) = current_id else {
+            report.orphans.push(FavoriteOrphan {
+                favorite_id,
+                product_id,
+                tiktok_id,
+            });
+            continue;
+        };
+
+        let existing: Option<(String, Option<String>, Option<String>)> = tx
+            .query_row(
+                "SELECT id, list_id, notes FROM favorites WHERE user_id = ? AND product_id = ?",
+                params![user_id, current_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        match existing {
+            Some((existing_id, existing_list, existing_notes)) => {
+                // Keep the existing favorite, filling in what only the stale one had
+                let notes = match (existing_notes, notes) {
+                    (Some(a), Some(b)) if !a.is_empty() && !b.is_empty() && a != b => {
+                        Some(format!("{}\n{}", a, b))
+                    }
+                    (Some(a), _) if !a.is_empty() => Some(a),
+                    (_, b) => b,
+                };
+                tx.execute(
+                    "UPDATE favorites SET list_id = ?, notes = ? WHERE id = ?",
+                    params![existing_list.or(list_id), notes, existing_id],
+                )?;
+                tx.execute("DELETE FROM favorites WHERE id = ?", params![favorite_id])?;
+                report.merged += 1;
+            }
+            None => {
+                tx.execute(
+                    "UPDATE favorites SET product_id = ? WHERE id = ?",
+                    params![current_id, favorite_id],
+                )?;
+                report.relinked += 1;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(report)
+}
+
+fn query_favorite_lists(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<FavoriteList>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT fl.id, fl.user_id, fl.name, fl.description, fl.color, fl.icon,
+                fl.created_at, fl.updated_at, COUNT(f.id) as product_count,
+                fl.sort_order, fl.pinned
          FROM favorite_lists fl
          LEFT JOIN favorites f ON f.list_id = fl.id
-         WHERE fl.user_id = ?
+         WHERE {}
          GROUP BY fl.id
-         ORDER BY fl.created_at DESC",
-    )?;
+         ORDER BY fl.pinned DESC, fl.sort_order IS NULL, fl.sort_order, fl.created_at DESC",
+        filter
+    ))?;
 
     let lists = stmt
-        .query_map(params![user_id], |row| {
+        .query_map(args, |row| {
             Ok(FavoriteList {
                 id: row.get(0)?,
                 user_id: row.get(1)?,
@@ -645,6 +1892,8 @@ pub fn get_favorite_lists(db_path: &Path, user_id: &str) -> Result<Vec<FavoriteL
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
                 product_count: row.get(8)?,
+                sort_order: row.get::<_, Option<i64>>(9)?.unwrap_or_default(),
+                pinned: row.get::<_, Option<i32>>(10)?.unwrap_or(0) != 0,
             })
         })?
         .filter_map(|r| r.ok())
@@ -653,27 +1902,578 @@ pub fn get_favorite_lists(db_path: &Path, user_id: &str) -> Result<Vec<FavoriteL
     Ok(lists)
 }
 
-pub fn delete_favorite_list(db_path: &Path, list_id: &str) -> Result<bool> {
+/// Lists of the user: pinned ones first, then in the user's order; lists
+/// never reordered come last, newest first
+pub fn get_favorite_lists(db_path: &Path, user_id: &str) -> Result<Vec<FavoriteList>> {
     let conn = get_connection(db_path)?;
+    query_favorite_lists(&conn, "fl.user_id = ?", &[&user_id])
+}
 
-    // First, remove all items from the list
-    conn.execute("DELETE FROM favorites WHERE list_id = ?", params![list_id])?;
+/// Change the given fields of a list; returns it updated, None if unknown.
+/// An empty description clears it.
+pub fn update_favorite_list(
+    db_path: &Path,
+    list_id: &str,
+    name: Option<&str>,
+    description: Option<&str>,
+    color: Option<&str>,
+    icon: Option<&str>,
+) -> Result<Option<FavoriteList>> {
+    let conn = get_connection(db_path)?;
 
-    // Then delete the list
-    let rows = conn.execute("DELETE FROM favorite_lists WHERE id = ?", params![list_id])?;
+    let rows = conn.execute(
+        "UPDATE favorite_lists SET
+            name = COALESCE(?, name),
+            description = CASE WHEN ?2 IS NULL THEN description ELSE NULLIF(?2, '') END,
+            color = COALESCE(?, color),
+            icon = COALESCE(?, icon),
+            updated_at = ?
+         WHERE id = ?",
+        params![
+            name,
+            description,
+            color,
+            icon,
+            chrono::Utc::now().to_rfc3339(),
+            list_id
+        ],
+    )?;
+    if rows == 0 {
+        return Ok(None);
+    }
 
-    Ok(rows > 0)
+    Ok(query_favorite_lists(&conn, "fl.id = ?", &[&list_id])?.pop())
 }
 
-// ==========================================
-// COPY HISTORY QUERIES
-// ==========================================
+/// Give the lists the order of `ids_in_order`; lists left out keep their
+/// relative order after them
+pub fn reorder_favorite_lists(db_path: &Path, user_id: &str, ids_in_order: &[String]) -> Result<()> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
 
-pub fn save_copy_history(
-    db_path: &Path,
-    user_id: &str,
-    product_id: Option<&str>,
-    copy_type: &str,
+    let mut rest: Vec<String> = {
+        let mut stmt = tx.prepare(
+            "SELECT id FROM favorite_lists WHERE user_id = ?
+             ORDER BY sort_order IS NULL, sort_order, created_at DESC",
+        )?;
+        let ids = stmt
+            .query_map(params![user_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        ids
+    };
+    rest.retain(|id| !ids_in_order.contains(id));
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for (position, id) in ids_in_order.iter().chain(rest.iter()).enumerate() {
+        tx.execute(
+            "UPDATE favorite_lists SET sort_order = ?, updated_at = ? WHERE id = ? AND user_id = ?",
+            params![position as i64, now, id, user_id],
+        )?;
+    }
+    tx.commit()
+}
+
+pub fn set_favorite_list_pinned(db_path: &Path, list_id: &str, pinned: bool) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute(
+        "UPDATE favorite_lists SET pinned = ?, updated_at = ? WHERE id = ?",
+        params![pinned as i32, chrono::Utc::now().to_rfc3339(), list_id],
+    )?;
+    Ok(rows > 0)
+}
+
+/// Move a list and its favorites to the trash
+pub fn delete_favorite_list(db_path: &Path, list_id: &str) -> Result<bool> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let name: Option<String> = tx
+        .query_row(
+            "SELECT name FROM favorite_lists WHERE id = ?",
+            params![list_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(name) = name else {
+        return Ok(false);
+    };
+
+    let mut rows = TrashRows::new();
+    take_rows(&tx, &mut rows, "favorites", "list_id = ?", &[&list_id])?;
+    take_rows(&tx, &mut rows, "favorite_lists", "id = ?", &[&list_id])?;
+    add_to_trash(&tx, TrashKind::FavoriteList, list_id, &name, &rows)?;
+
+    tx.commit()?;
+    Ok(true)
+}
+
+// ==========================================
+// TRASH
+// ==========================================
+
+/// Tables rows are moved to the trash from, in the order they are restored
+const TRASHED_TABLES: [&str; 9] = [
+    "favorite_lists",
+    "products",
+    "product_variants",
+    "product_history",
+    "competitor_catalog",
+    "watched_products",
+    "favorites",
+    "copy_history",
+    "product_alerts",
+];
+
+/// Rows moved to the trash, by table, as column name to value
+type TrashRows = BTreeMap<String, Vec<serde_json::Map<String, serde_json::Value>>>;
+
+/// Delete the rows of `table` matching `filter`, keeping them in `rows`
+fn take_rows(
+    conn: &Connection,
+    rows: &mut TrashRows,
+    table: &str,
+    filter: &str,
+    args: &[&dyn ToSql],
+) -> Result<usize> {
+    let taken = {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {} WHERE {}", table, filter))?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let taken = stmt
+            .query_map(args, |row| {
+                let mut values = serde_json::Map::new();
+                for (i, column) in columns.iter().enumerate() {
+                    let value = match row.get_ref(i)? {
+                        ValueRef::Integer(n) => n.into(),
+                        ValueRef::Real(n) => n.into(),
+                        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+                        // None of the trashed tables has blob columns
+                        ValueRef::Null | ValueRef::Blob(_) => serde_json::Value::Null,
+                    };
+                    values.insert(column.clone(), value);
+                }
+                Ok(values)
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        taken
+    };
+
+    conn.execute(&format!("DELETE FROM {} WHERE {}", table, filter), args)?;
+    let count = taken.len();
+    rows.entry(table.to_string()).or_default().extend(taken);
+    Ok(count)
+}
+
+/// Insert rows taken by `take_rows` back; rows whose key was taken since, or
+/// referring to something gone, are skipped
+fn put_rows(
+    conn: &Connection,
+    table: &str,
+    rows: &[serde_json::Map<String, serde_json::Value>],
+) -> Result<usize> {
+    let mut restored = 0;
+    for row in rows {
+        let columns: Vec<String> = row.keys().map(|c| format!("\"{}\"", c)).collect();
+        let values = row.values().map(|value| match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(Value::Integer)
+                .unwrap_or_else(|| Value::Real(n.as_f64().unwrap_or_default())),
+            serde_json::Value::String(text) => Value::Text(text.clone()),
+            other => Value::Text(other.to_string()),
+        });
+        let inserted = conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} ({}) VALUES ({})",
+                table,
+                columns.join(", "),
+                vec!["?"; columns.len()].join(", ")
+            ),
+            rusqlite::params_from_iter(values),
+        );
+        match inserted {
+            Ok(n) => restored += n,
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if e.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(restored)
+}
+
+/// Point trashed rows of `table` at what they referred to as it is now:
+/// favorites follow their product to its current row and leave a list that
+/// is gone, copies of a product that is gone keep no product
+fn relink_rows(
+    conn: &Connection,
+    table: &str,
+    rows: &mut [serde_json::Map<String, serde_json::Value>],
+) -> Result<()> {
+    // Whether the row `id` refers to in `table` is gone; false without an id
+    let gone = |table: &str, id: Option<&serde_json::Value>| -> Result<bool> {
+        match id.and_then(|id| id.as_str()) {
+            Some(id) => conn.query_row(
+                &format!("SELECT NOT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table),
+                params![id],
+                |row| row.get(0),
+            ),
+            None => Ok(false),
+        }
+    };
+
+    for row in rows {
+        match table {
+            "favorites" => {
+                if gone("products", row.get("product_id"))? {
+                    let current: Option<String> = conn
+                        .query_row(
+                            "SELECT id FROM products WHERE tiktok_id = ?",
+                            params![row.get("tiktok_id").and_then(|id| id.as_str())],
+                            |row| row.get(0),
+                        )
+                        .optional()?;
+                    if let Some(current) = current {
+                        row.insert("product_id".to_string(), current.into());
+                    }
+                }
+                if gone("favorite_lists", row.get("list_id"))? {
+                    row.insert("list_id".to_string(), serde_json::Value::Null);
+                }
+            }
+            "copy_history" if gone("products", row.get("product_id"))? => {
+                row.insert("product_id".to_string(), serde_json::Value::Null);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn add_to_trash(
+    conn: &Connection,
+    kind: TrashKind,
+    item_id: &str,
+    label: &str,
+    rows: &TrashRows,
+) -> Result<()> {
+    let rows = serde_json::to_string(rows)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO trash (id, kind, item_id, label, rows, deleted_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            Uuid::new_v4().to_string(),
+            kind.as_str(),
+            item_id,
+            label,
+            rows,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(())
+}
+
+fn query_trash(conn: &Connection, filter: &str, args: &[&dyn ToSql]) -> Result<Vec<TrashItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, kind, item_id, label, deleted_at FROM trash {} ORDER BY deleted_at DESC",
+        filter
+    ))?;
+
+    let items = stmt
+        .query_map(args, |row| {
+            let kind: String = row.get(1)?;
+            let Some(kind) = TrashKind::parse(&kind) else {
+                return Ok(None);
+            };
+            let deleted_at: String = row.get(4)?;
+            let expires_at = chrono::DateTime::parse_from_rfc3339(&deleted_at)
+                .map(|d| (d + chrono::Duration::days(TRASH_RETENTION_DAYS)).to_rfc3339())
+                .unwrap_or_default();
+            Ok(Some(TrashItem {
+                id: row.get(0)?,
+                kind,
+                item_id: row.get(2)?,
+                label: row.get(3)?,
+                deleted_at,
+                expires_at,
+            }))
+        })?
+        .filter_map(|r| r.ok().flatten())
+        .collect();
+
+    Ok(items)
+}
+
+/// Items in the trash, most recently deleted first
+pub fn get_trash(db_path: &Path) -> Result<Vec<TrashItem>> {
+    let conn = get_connection(db_path)?;
+    query_trash(&conn, "", &[])
+}
+
+fn product_references(conn: &Connection, product_id: &str) -> Result<Option<ProductReferences>> {
+    let title: Option<String> = conn
+        .query_row(
+            "SELECT title FROM products WHERE id = ?",
+            params![product_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(title) = title else {
+        return Ok(None);
+    };
+
+    let count = |table: &str| -> Result<i64> {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE product_id = ?", table),
+            params![product_id],
+            |row| row.get(0),
+        )
+    };
+
+    let lists = {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT fl.name FROM favorites f
+             JOIN favorite_lists fl ON fl.id = f.list_id
+             WHERE f.product_id = ?
+             ORDER BY fl.name",
+        )?;
+        let lists = stmt
+            .query_map(params![product_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        lists
+    };
+    let notes = {
+        let mut stmt = conn.prepare(
+            "SELECT notes FROM favorites WHERE product_id = ? AND notes IS NOT NULL AND notes != ''",
+        )?;
+        let notes = stmt
+            .query_map(params![product_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        notes
+    };
+
+    Ok(Some(ProductReferences {
+        product_id: product_id.to_string(),
+        title,
+        favorites: count("favorites")?,
+        lists,
+        notes,
+        copies: count("copy_history")?,
+        alerts: count("product_alerts")?,
+        watched: count("watched_products")? > 0,
+    }))
+}
+
+/// Favorites, lists, notes, copies, alerts and watch entries referring to a
+/// product; None if the product is unknown
+pub fn get_product_references(
+    db_path: &Path,
+    product_id: &str,
+) -> Result<Option<ProductReferences>> {
+    let conn = get_connection(db_path)?;
+    product_references(&conn, product_id)
+}
+
+/// Move products to the trash with their variants, history and competitor
+/// catalog entries. Products something refers to are refused, unless
+/// `cascade` is set: then the favorites, copies, alerts and watch entries go
+/// to the trash with them.
+pub fn delete_products(
+    db_path: &Path,
+    product_ids: &[String],
+    cascade: bool,
+) -> Result<ProductDeletion> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let mut deletion = ProductDeletion::default();
+    for product_id in product_ids {
+        let Some(references) = product_references(&tx, product_id)? else {
+            continue;
+        };
+        if !cascade && !references.is_empty() {
+            deletion.refused.push(references);
+            continue;
+        }
+
+        let mut rows = TrashRows::new();
+        for table in [
+            "favorites",
+            "copy_history",
+            "product_alerts",
+            "product_variants",
+            "product_history",
+            "competitor_catalog",
+            "watched_products",
+        ] {
+            take_rows(&tx, &mut rows, table, "product_id = ?", &[product_id])?;
+        }
+        take_rows(&tx, &mut rows, "products", "id = ?", &[product_id])?;
+        add_to_trash(&tx, TrashKind::Product, product_id, &references.title, &rows)?;
+        deletion.deleted += 1;
+    }
+
+    tx.commit()?;
+    Ok(deletion)
+}
+
+/// Put a trashed item back; returns it, None if it is not in the trash.
+/// A product collected again since it was deleted keeps its new row, and
+/// the restored history, favorites, copies and alerts follow it. Restored
+/// favorites of a list that is gone are left without a list.
+pub fn restore_from_trash(db_path: &Path, trash_id: &str) -> Result<Option<TrashItem>> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    let Some(item) = query_trash(&tx, "WHERE id = ?", &[&trash_id])?.pop() else {
+        return Ok(None);
+    };
+    let rows: String = tx.query_row(
+        "SELECT rows FROM trash WHERE id = ?",
+        params![trash_id],
+        |row| row.get(0),
+    )?;
+    let mut rows: TrashRows = serde_json::from_str(&rows).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    // Row a deleted product was collected again under
+    let mut current_id: Option<String> = None;
+    if item.kind == TrashKind::Product {
+        let tiktok_id = rows
+            .get("products")
+            .and_then(|products| products.first())
+            .and_then(|product| product.get("tiktok_id"))
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string());
+        current_id = tx
+            .query_row(
+                "SELECT id FROM products WHERE tiktok_id = ?",
+                params![tiktok_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+    }
+    if let Some(current_id) = current_id {
+        for table in ["products", "product_variants", "watched_products"] {
+            rows.remove(table);
+        }
+        for row in rows.values_mut().flatten() {
+            if row.contains_key("product_id") {
+                row.insert("product_id".to_string(), current_id.clone().into());
+            }
+        }
+    }
+
+    for table in TRASHED_TABLES {
+        if let Some(mut table_rows) = rows.remove(table) {
+            relink_rows(&tx, table, &mut table_rows)?;
+            put_rows(&tx, table, &table_rows)?;
+        }
+    }
+
+    tx.execute("DELETE FROM trash WHERE id = ?", params![trash_id])?;
+
+    tx.commit()?;
+    Ok(Some(item))
+}
+
+/// Remove items from the trash for good: all of them, or those deleted
+/// before `deleted_before`. Returns the items removed.
+pub fn empty_trash(db_path: &Path, deleted_before: Option<&str>) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+
+    match deleted_before {
+        Some(before) => conn.execute("DELETE FROM trash WHERE deleted_at < ?", params![before]),
+        None => conn.execute("DELETE FROM trash", []),
+    }
+}
+
+/// Remove the items deleted more than `TRASH_RETENTION_DAYS` ago
+pub fn prune_trash(db_path: &Path) -> Result<usize> {
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(TRASH_RETENTION_DAYS)).to_rfc3339();
+    empty_trash(db_path, Some(&cutoff))
+}
+
+// ==========================================
+// ORPHAN CLEANUP
+// ==========================================
+
+/// Rows whose foreign keys refer to rows that are gone, by table and column.
+/// Unless `dry_run`, they are deleted, or their reference cleared where the
+/// key is ON DELETE SET NULL.
+pub fn cleanup_orphans(db_path: &Path, dry_run: bool) -> Result<Vec<OrphanRows>> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+
+    // (table, rowid, foreign key id)
+    let violations: Vec<(String, i64, i64)> = {
+        let mut stmt = tx.prepare("PRAGMA foreign_key_check")?;
+        let violations = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get::<_, Option<i64>>(1)?, row.get(3)?))
+            })?
+            .filter_map(|r| match r {
+                Ok((table, Some(rowid), key)) => Some(Ok((table, rowid, key))),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        violations
+    };
+
+    let mut report: BTreeMap<(String, i64), OrphanRows> = BTreeMap::new();
+    for (table, rowid, key) in violations {
+        let orphans = match report.entry((table.clone(), key)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let (parent, column, on_delete): (String, String, String) = tx.query_row(
+                    &format!(
+                        "SELECT \"table\", \"from\", on_delete FROM pragma_foreign_key_list('{}')
+                         WHERE id = ?",
+                        table
+                    ),
+                    params![key],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )?;
+                entry.insert(OrphanRows {
+                    table: table.clone(),
+                    column,
+                    parent,
+                    rows: 0,
+                    cleared: on_delete == "SET NULL",
+                })
+            }
+        };
+        orphans.rows += 1;
+
+        if dry_run {
+            continue;
+        }
+        if orphans.cleared {
+            tx.execute(
+                &format!("UPDATE {} SET {} = NULL WHERE rowid = ?", table, orphans.column),
+                params![rowid],
+            )?;
+        } else {
+            tx.execute(&format!("DELETE FROM {} WHERE rowid = ?", table), params![rowid])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(report.into_values().collect())
+}
+
+// ==========================================
+// COPY HISTORY QUERIES
+// ==========================================
+
+pub fn save_copy_history(
+    db_path: &Path,
+    user_id: &str,
+    product_id: Option<&str>,
+    copy_type: &str,
     tone: &str,
     content: &str,
     tokens_used: i32,
@@ -715,142 +2515,1785 @@ pub fn get_copy_history(db_path: &Path, user_id: &str, limit: i32) -> Result<Vec
         .filter_map(|r| r.ok())
         .collect();
 
-    Ok(history)
+    Ok(history)
+}
+
+pub fn get_copy(db_path: &Path, id: &str) -> Result<Option<CopyHistory>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT id, user_id, product_id, copy_type, tone, content, tokens_used, is_favorite,
+                created_at
+         FROM copy_history WHERE id = ?",
+        params![id],
+        |row| {
+            Ok(CopyHistory {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                product_id: row.get(2)?,
+                copy_type: row.get(3)?,
+                tone: row.get(4)?,
+                content: row.get(5)?,
+                tokens_used: row.get(6)?,
+                is_favorite: row.get::<_, i32>(7)? == 1,
+                created_at: row.get(8)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Cached copy of `key` generated at or after `since` (RFC 3339)
+pub fn get_cached_copy(db_path: &Path, key: &str, since: &str) -> Result<Option<String>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT content FROM copy_cache WHERE key = ? AND created_at >= ?",
+        params![key, since],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Cache a generated copy, dropping entries created before `expired_before`
+pub fn save_cached_copy(
+    db_path: &Path,
+    key: &str,
+    content: &str,
+    created_at: &str,
+    expired_before: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "DELETE FROM copy_cache WHERE created_at < ?",
+        params![expired_before],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO copy_cache (key, content, created_at) VALUES (?, ?, ?)",
+        params![key, content, created_at],
+    )?;
+    Ok(())
+}
+
+// ==========================================
+// CREDIT LEDGER
+// ==========================================
+
+pub fn add_credit_entry(db_path: &Path, entry: &CreditLedgerEntry) -> Result<i64> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO credit_ledger (kind, amount, balance, tokens_used, description, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            entry.kind,
+            entry.amount,
+            entry.balance,
+            entry.tokens_used,
+            entry.description,
+            entry.created_at
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Latest ledger entries, newest first
+pub fn get_credit_entries(db_path: &Path, limit: i64) -> Result<Vec<CreditLedgerEntry>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, amount, balance, tokens_used, description, created_at
+         FROM credit_ledger ORDER BY id DESC LIMIT ?",
+    )?;
+
+    let entries = stmt
+        .query_map(params![limit], |row| {
+            Ok(CreditLedgerEntry {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                amount: row.get(2)?,
+                balance: row.get(3)?,
+                tokens_used: row.get(4)?,
+                description: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(entries)
+}
+
+/// Credits spent since `since` (RFC 3339)
+pub fn get_credits_used_since(db_path: &Path, since: &str) -> Result<i32> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT COALESCE(-SUM(amount), 0) FROM credit_ledger WHERE kind = 'usage' AND created_at >= ?",
+        params![since],
+        |row| row.get(0),
+    )
+}
+
+// ==========================================
+// SEARCH HISTORY QUERIES
+// ==========================================
+
+pub fn save_search_history(
+    db_path: &Path,
+    user_id: &str,
+    query: &str,
+    filters: &str,
+    results_count: i32,
+) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO search_history (id, user_id, query, filters, results_count, searched_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![id, user_id, query, filters, results_count, now],
+    )?;
+
+    Ok(true)
+}
+
+pub fn get_search_history(
+    db_path: &Path,
+    user_id: &str,
+    limit: i32,
+) -> Result<Vec<SearchHistoryItem>> {
+    let conn = get_connection(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT * FROM search_history WHERE user_id = ? ORDER BY searched_at DESC LIMIT ?",
+    )?;
+
+    let history = stmt
+        .query_map(params![user_id, limit], |row| {
+            Ok(SearchHistoryItem {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                query: row.get(2)?,
+                filters: row.get(3)?,
+                results_count: row.get(4)?,
+                searched_at: row.get(5)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(history)
+}
+
+// ==========================================
+// DASHBOARD STATS
+// ==========================================
+
+pub fn get_dashboard_stats(db_path: &Path, user_id: &str) -> Result<DashboardStats> {
+    let conn = get_connection(db_path)?;
+
+    let total_products: i64 = conn
+        .query_row("SELECT COUNT(*) FROM products", [], |row| row.get(0))
+        .unwrap_or(0);
+
+    let trending_products: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM products WHERE is_trending = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let favorite_count: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM favorites WHERE user_id = ?",
+            params![user_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let searches_today: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM search_history WHERE user_id = ? AND date(searched_at) = ?",
+            params![user_id, today],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    let copies_generated: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM copy_history WHERE user_id = ?",
+            params![user_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(0);
+
+    // Get top categories
+    let mut stmt = conn.prepare(
+        "SELECT category, COUNT(*) as count FROM products 
+         WHERE category IS NOT NULL 
+         GROUP BY category 
+         ORDER BY count DESC 
+         LIMIT 5",
+    )?;
+
+    let top_categories: Vec<CategoryCount> = stmt
+        .query_map([], |row| {
+            Ok(CategoryCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(DashboardStats {
+        total_products,
+        trending_products,
+        favorite_count,
+        searches_today,
+        copies_generated,
+        top_categories,
+        trial: None,
+        goals: goal_progress(&conn, user_id, chrono::Utc::now())?,
+    })
+}
+
+// ==========================================
+// GOALS
+// ==========================================
+
+pub fn add_goal(db_path: &Path, goal: &Goal) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO goals (id, title, metric, target, period, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+        params![
+            goal.id,
+            goal.title,
+            goal.metric.as_str(),
+            goal.target,
+            goal.period.as_str(),
+            goal.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete_goal(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM goals WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+/// Start of the period containing `now`, as an SQLite datetime
+fn period_start(period: GoalPeriod, now: chrono::DateTime<chrono::Utc>) -> String {
+    use chrono::Datelike;
+
+    let today = now.date_naive();
+    let start = match period {
+        GoalPeriod::Week => {
+            today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+        GoalPeriod::Month => today.with_day(1).unwrap_or(today),
+    };
+    format!("{} 00:00:00", start.format("%Y-%m-%d"))
+}
+
+fn goal_progress(
+    conn: &Connection,
+    user_id: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<GoalProgress>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, metric, target, period, created_at FROM goals ORDER BY created_at",
+    )?;
+    let goals: Vec<Goal> = stmt
+        .query_map([], |row| {
+            // Rows of metrics or periods this version doesn't know are skipped
+            let metric = GoalMetric::parse(&row.get::<_, String>(2)?);
+            let period = GoalPeriod::parse(&row.get::<_, String>(4)?);
+            let (Some(metric), Some(period)) = (metric, period) else {
+                return Ok(None);
+            };
+            Ok(Some(Goal {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                metric,
+                target: row.get(3)?,
+                period,
+                created_at: row.get(5)?,
+            }))
+        })?
+        .filter_map(|r| r.ok().flatten())
+        .collect();
+
+    goals
+        .into_iter()
+        .map(|goal| {
+            let condition = match goal.metric {
+                GoalMetric::ProductsEvaluated => "1 = 1",
+                GoalMetric::ProductsShortlisted => "list_id IS NOT NULL",
+                GoalMetric::NotesWritten => "notes IS NOT NULL AND notes != ''",
+            };
+            let period_start = period_start(goal.period, now);
+            let current: i64 = conn.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM favorites
+                     WHERE user_id = ? AND datetime(added_at) >= datetime(?) AND {}",
+                    condition
+                ),
+                params![user_id, period_start],
+                |row| row.get(0),
+            )?;
+            Ok(GoalProgress {
+                completed: current >= goal.target,
+                goal,
+                current,
+                period_start,
+            })
+        })
+        .collect()
+}
+
+/// Goals with their progress in the current period
+pub fn get_goal_progress(db_path: &Path, user_id: &str) -> Result<Vec<GoalProgress>> {
+    let conn = get_connection(db_path)?;
+    goal_progress(&conn, user_id, chrono::Utc::now())
+}
+
+// ==========================================
+// SETTINGS (key/value)
+// ==========================================
+
+pub fn get_setting(db_path: &Path, key: &str) -> Result<Option<String>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn set_setting(db_path: &Path, key: &str, value: &str) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = datetime('now')",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+// ==========================================
+// SCRAPE PRESETS
+// ==========================================
+
+/// Save a preset, replacing the config of a preset with the same name.
+/// Returns the stored preset.
+pub fn save_scrape_preset(db_path: &Path, preset: &ScrapePreset) -> Result<ScrapePreset> {
+    let conn = get_connection(db_path)?;
+    let config = serde_json::to_string(&preset.config)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO scrape_presets (id, name, config, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?4)
+         ON CONFLICT(name) DO UPDATE SET config = excluded.config, updated_at = excluded.updated_at",
+        params![preset.id, preset.name, config, preset.updated_at],
+    )?;
+
+    query_scrape_presets(&conn, "WHERE name = ?", &[&preset.name])?
+        .pop()
+        .ok_or(rusqlite::Error::QueryReturnedNoRows)
+}
+
+fn query_scrape_presets(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<ScrapePreset>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, name, config, created_at, updated_at FROM scrape_presets {} ORDER BY name",
+        filter
+    ))?;
+
+    let presets = stmt
+        .query_map(args, |row| {
+            let config: String = row.get(2)?;
+            Ok(ScrapePreset {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                config: serde_json::from_str(&config).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, Box::new(e))
+                })?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(presets)
+}
+
+pub fn get_scrape_presets(db_path: &Path) -> Result<Vec<ScrapePreset>> {
+    let conn = get_connection(db_path)?;
+    query_scrape_presets(&conn, "", &[])
+}
+
+/// A preset by id or name
+pub fn get_scrape_preset(db_path: &Path, id_or_name: &str) -> Result<Option<ScrapePreset>> {
+    let conn = get_connection(db_path)?;
+    Ok(query_scrape_presets(&conn, "WHERE id = ?1 OR name = ?1", &[&id_or_name])?.pop())
+}
+
+pub fn delete_scrape_preset(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM scrape_presets WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+// ==========================================
+// FILTER PRESETS
+// ==========================================
+
+pub fn get_filter_preset(db_path: &Path, id: &str) -> Result<Option<FilterPreset>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT id, user_id, name, filters, usage_count, created_at FROM filter_presets WHERE id = ?",
+        params![id],
+        |row| {
+            Ok(FilterPreset {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                name: row.get(2)?,
+                filters: row.get(3)?,
+                usage_count: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+}
+
+// ==========================================
+// REPORT SCHEDULES
+// ==========================================
+
+/// Insert or replace a schedule
+pub fn save_report_schedule(db_path: &Path, schedule: &ReportSchedule) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO report_schedules (id, name, preset_id, format, folder, webhook_url, enabled, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            schedule.id,
+            schedule.name,
+            schedule.preset_id,
+            schedule.format.as_str(),
+            schedule.folder,
+            schedule.webhook_url,
+            schedule.enabled as i32,
+            schedule.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_report_schedules(db_path: &Path) -> Result<Vec<ReportSchedule>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, preset_id, format, folder, webhook_url, enabled, created_at
+         FROM report_schedules ORDER BY created_at",
+    )?;
+
+    let schedules = stmt
+        .query_map([], |row| {
+            // Schedules of formats this version doesn't know are skipped
+            let Some(format) = ReportFormat::parse(&row.get::<_, String>(3)?) else {
+                return Ok(None);
+            };
+            Ok(Some(ReportSchedule {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                preset_id: row.get(2)?,
+                format,
+                folder: row.get(4)?,
+                webhook_url: row.get(5)?,
+                enabled: row.get::<_, i32>(6)? != 0,
+                created_at: row.get(7)?,
+            }))
+        })?
+        .filter_map(|r| r.ok().flatten())
+        .collect();
+
+    Ok(schedules)
+}
+
+pub fn delete_report_schedule(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM report_schedules WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+pub fn add_report_record(db_path: &Path, record: &ReportRecord) -> Result<i64> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO report_history (schedule_id, schedule_name, run_id, success, path, product_count, error, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            record.schedule_id,
+            record.schedule_name,
+            record.run_id,
+            record.success as i32,
+            record.path,
+            record.product_count,
+            record.error,
+            record.created_at
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Generated reports and failures, newest first
+pub fn get_report_history(db_path: &Path, limit: i64) -> Result<Vec<ReportRecord>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, schedule_id, schedule_name, run_id, success, path, product_count, error, created_at
+         FROM report_history ORDER BY id DESC LIMIT ?",
+    )?;
+
+    let records = stmt
+        .query_map(params![limit], |row| {
+            Ok(ReportRecord {
+                id: row.get(0)?,
+                schedule_id: row.get(1)?,
+                schedule_name: row.get(2)?,
+                run_id: row.get(3)?,
+                success: row.get::<_, i32>(4)? != 0,
+                path: row.get(5)?,
+                product_count: row.get(6)?,
+                error: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(records)
+}
+
+// ==========================================
+// TELEMETRY
+// ==========================================
+
+pub fn add_telemetry_event(
+    db_path: &Path,
+    name: &str,
+    properties: &std::collections::HashMap<String, String>,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    let properties = serde_json::to_string(properties)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO telemetry_events (name, properties, created_at) VALUES (?, ?, ?)",
+        params![name, properties, chrono::Utc::now().to_rfc3339()],
+    )?;
+    Ok(())
+}
+
+/// Recorded events, newest first; `unsent_only` keeps those not sent yet,
+/// oldest first, for upload
+pub fn get_telemetry_events(
+    db_path: &Path,
+    unsent_only: bool,
+    limit: i32,
+) -> Result<Vec<TelemetryEvent>> {
+    let conn = get_connection(db_path)?;
+    let query = if unsent_only {
+        "SELECT id, name, properties, created_at, sent_at FROM telemetry_events
+         WHERE sent_at IS NULL ORDER BY id LIMIT ?"
+    } else {
+        "SELECT id, name, properties, created_at, sent_at FROM telemetry_events
+         ORDER BY id DESC LIMIT ?"
+    };
+    let mut stmt = conn.prepare(query)?;
+
+    let events = stmt
+        .query_map(params![limit], |row| {
+            Ok(TelemetryEvent {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                properties: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                created_at: row.get(3)?,
+                sent_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(events)
+}
+
+pub fn mark_telemetry_sent(db_path: &Path, ids: &[i64], sent_at: &str) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    let mut updated = 0;
+    for id in ids {
+        updated += conn.execute(
+            "UPDATE telemetry_events SET sent_at = ? WHERE id = ?",
+            params![sent_at, id],
+        )?;
+    }
+    Ok(updated)
+}
+
+/// Delete recorded events: all of them, or only those not sent yet
+pub fn clear_telemetry_events(db_path: &Path, unsent_only: bool) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    if unsent_only {
+        conn.execute("DELETE FROM telemetry_events WHERE sent_at IS NULL", [])
+    } else {
+        conn.execute("DELETE FROM telemetry_events", [])
+    }
+}
+
+// ==========================================
+// SESSION PROFILES
+// ==========================================
+
+/// Fingerprint JSON stored for a session profile
+pub fn get_profile_fingerprint(db_path: &Path, profile_id: &str) -> Result<Option<String>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT fingerprint FROM profiles WHERE id = ?",
+        params![profile_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .map(|fp| fp.flatten())
+}
+
+/// Store the fingerprint of a profile, creating the profile if needed
+pub fn save_profile_fingerprint(
+    db_path: &Path,
+    profile_id: &str,
+    user_agent: &str,
+    fingerprint: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO profiles (id, name, user_agent, fingerprint) VALUES (?1, ?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET user_agent = ?2, fingerprint = ?3",
+        params![profile_id, user_agent, fingerprint],
+    )?;
+    Ok(())
+}
+
+// ==========================================
+// RUN ACTION LOG
+// ==========================================
+
+pub fn add_run_action(db_path: &Path, action: &RunAction) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO run_actions (run_id, action, url, detail, elapsed_ms, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            action.run_id,
+            action.action,
+            action.url,
+            action.detail,
+            action.elapsed_ms,
+            action.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Actions of a run in the order they happened
+pub fn get_run_actions(db_path: &Path, run_id: &str) -> Result<Vec<RunAction>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT run_id, action, url, detail, elapsed_ms, created_at
+         FROM run_actions WHERE run_id = ? ORDER BY id",
+    )?;
+
+    let actions = stmt
+        .query_map(params![run_id], |row| {
+            Ok(RunAction {
+                run_id: row.get(0)?,
+                action: row.get(1)?,
+                url: row.get(2)?,
+                detail: row.get(3)?,
+                elapsed_ms: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(actions)
+}
+
+pub fn start_collection_log(db_path: &Path, id: &str, started_at: &str) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO collection_logs (id, status, started_at) VALUES (?, 'running', ?)",
+        params![id, started_at],
+    )?;
+    Ok(())
+}
+
+pub fn finish_collection_log(
+    db_path: &Path,
+    id: &str,
+    status: &str,
+    products_found: i64,
+    products_saved: i64,
+    errors_count: i64,
+    completed_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE collection_logs
+         SET status = ?1, products_found = ?2, products_saved = ?3, errors_count = ?4,
+             duration_ms = CAST((julianday(?5) - julianday(started_at)) * 86400000 AS INTEGER),
+             completed_at = ?5
+         WHERE id = ?6",
+        params![status, products_found, products_saved, errors_count, completed_at, id],
+    )?;
+    Ok(())
+}
+
+pub fn add_run_log(db_path: &Path, run_id: &str, entry: &LogEntry) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO run_logs (run_id, level, code, params, message, category, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            run_id,
+            entry.level.as_str(),
+            entry.code,
+            serde_json::to_string(&entry.params).unwrap_or_else(|_| "{}".to_string()),
+            entry.message,
+            entry.category,
+            entry.timestamp
+        ],
+    )?;
+    Ok(())
+}
+
+/// Log entries of a run in order, at `min_level` or above
+pub fn get_run_logs(
+    db_path: &Path,
+    run_id: &str,
+    min_level: Option<LogLevel>,
+) -> Result<Vec<LogEntry>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT level, code, params, message, category, created_at
+         FROM run_logs WHERE run_id = ? ORDER BY id",
+    )?;
+
+    let entries = stmt
+        .query_map(params![run_id], |row| {
+            let Some(level) = LogLevel::parse(&row.get::<_, String>(0)?) else {
+                return Ok(None);
+            };
+            Ok(Some(LogEntry {
+                level,
+                code: row.get(1)?,
+                params: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                message: row.get(3)?,
+                category: row.get(4)?,
+                timestamp: row.get(5)?,
+            }))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(entries
+        .into_iter()
+        .flatten()
+        .filter(|entry| min_level.is_none_or(|level| entry.level >= level))
+        .collect())
+}
+
+/// Delete run log entries written before `before`; returns how many were removed
+pub fn prune_run_logs(db_path: &Path, before: &str) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    conn.execute("DELETE FROM run_logs WHERE created_at < ?", params![before])
+}
+
+/// Delete error pages stored more than `days` days ago
+pub fn prune_error_pages(db_path: &Path, days: i64) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "DELETE FROM error_pages WHERE created_at < datetime('now', ?)",
+        params![format!("-{} days", days)],
+    )
+}
+
+/// Give the space of deleted rows back to the file system
+pub fn vacuum(db_path: &Path) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute_batch("VACUUM")
+}
+
+// ==========================================
+// WATCHED PRODUCTS
+// ==========================================
+
+/// Start watching a product, or change its interval. The first check is due immediately.
+pub fn watch_product(db_path: &Path, product_id: &str, interval_minutes: u32) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO watched_products (product_id, interval_minutes, next_check_at, created_at)
+         VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(product_id) DO UPDATE SET interval_minutes = ?2, next_check_at = ?3",
+        params![product_id, interval_minutes, now],
+    )?;
+    Ok(())
+}
+
+pub fn unwatch_product(db_path: &Path, product_id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute(
+        "DELETE FROM watched_products WHERE product_id = ?",
+        params![product_id],
+    )?;
+    Ok(rows > 0)
+}
+
+fn query_watched_products(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<WatchedProduct>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT w.product_id, COALESCE(p.title, ''), w.interval_minutes, w.last_checked_at,
+                w.next_check_at, w.created_at
+         FROM watched_products w LEFT JOIN products p ON p.id = w.product_id
+         {} ORDER BY w.next_check_at",
+        filter
+    ))?;
+
+    let watched = stmt
+        .query_map(args, |row| {
+            Ok(WatchedProduct {
+                product_id: row.get(0)?,
+                title: row.get(1)?,
+                interval_minutes: row.get(2)?,
+                last_checked_at: row.get(3)?,
+                next_check_at: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(watched)
+}
+
+pub fn get_watched_products(db_path: &Path) -> Result<Vec<WatchedProduct>> {
+    let conn = get_connection(db_path)?;
+    query_watched_products(&conn, "", &[])
+}
+
+/// Watched products whose next check is at or before `now` (RFC 3339)
+pub fn get_due_watched_products(db_path: &Path, now: &str) -> Result<Vec<WatchedProduct>> {
+    let conn = get_connection(db_path)?;
+    query_watched_products(&conn, "WHERE w.next_check_at <= ?", &[&now])
+}
+
+pub fn mark_watch_checked(
+    db_path: &Path,
+    product_id: &str,
+    checked_at: &str,
+    next_check_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE watched_products SET last_checked_at = ?, next_check_at = ? WHERE product_id = ?",
+        params![checked_at, next_check_at, product_id],
+    )?;
+    Ok(())
+}
+
+// ==========================================
+// COMPETITORS
+// ==========================================
+
+pub fn add_competitor(db_path: &Path, competitor: &Competitor) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO competitors (id, name, store_url, price_change_percent, interval_minutes, next_check_at, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            competitor.id,
+            competitor.name,
+            competitor.store_url,
+            competitor.price_change_percent,
+            competitor.interval_minutes,
+            competitor.next_check_at,
+            competitor.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Remove a competitor and its catalog; the products themselves are kept
+pub fn remove_competitor(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    conn.execute("DELETE FROM competitor_catalog WHERE competitor_id = ?", params![id])?;
+    let rows = conn.execute("DELETE FROM competitors WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+fn query_competitors(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<Competitor>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, c.name, c.store_url, c.price_change_percent, c.interval_minutes,
+                c.last_checked_at, c.next_check_at, c.created_at,
+                (SELECT COUNT(*) FROM competitor_catalog cc WHERE cc.competitor_id = c.id)
+         FROM competitors c {} ORDER BY c.name",
+        filter
+    ))?;
+
+    let competitors = stmt
+        .query_map(args, |row| {
+            Ok(Competitor {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                store_url: row.get(2)?,
+                price_change_percent: row.get(3)?,
+                interval_minutes: row.get(4)?,
+                last_checked_at: row.get(5)?,
+                next_check_at: row.get(6)?,
+                created_at: row.get(7)?,
+                product_count: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(competitors)
+}
+
+pub fn get_competitors(db_path: &Path) -> Result<Vec<Competitor>> {
+    let conn = get_connection(db_path)?;
+    query_competitors(&conn, "", &[])
+}
+
+/// Competitors whose catalog scrape is due at `now` (RFC 3339)
+pub fn get_due_competitors(db_path: &Path, now: &str) -> Result<Vec<Competitor>> {
+    let conn = get_connection(db_path)?;
+    query_competitors(&conn, "WHERE c.next_check_at <= ?", &[&now])
+}
+
+pub fn mark_competitor_checked(
+    db_path: &Path,
+    id: &str,
+    checked_at: &str,
+    next_check_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE competitors SET last_checked_at = ?, next_check_at = ? WHERE id = ?",
+        params![checked_at, next_check_at, id],
+    )?;
+    Ok(())
+}
+
+/// Record that a competitor lists a product. Returns true the first time.
+pub fn add_competitor_product(
+    db_path: &Path,
+    competitor_id: &str,
+    product_id: &str,
+    seen_at: &str,
+) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO competitor_catalog (competitor_id, product_id, first_seen_at, last_seen_at)
+         VALUES (?1, ?2, ?3, ?3)",
+        params![competitor_id, product_id, seen_at],
+    )?;
+    if inserted == 0 {
+        conn.execute(
+            "UPDATE competitor_catalog SET last_seen_at = ? WHERE competitor_id = ? AND product_id = ?",
+            params![seen_at, competitor_id, product_id],
+        )?;
+    }
+    Ok(inserted > 0)
+}
+
+/// Catalog of one competitor, or of all of them, newest listings first
+pub fn get_competitor_products(
+    db_path: &Path,
+    competitor_id: Option<&str>,
+) -> Result<Vec<CompetitorProduct>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT * FROM competitor_products
+         WHERE ?1 IS NULL OR competitor_id = ?1
+         ORDER BY first_seen_at DESC",
+    )?;
+
+    let products = stmt
+        .query_map(params![competitor_id], |row| {
+            Ok(CompetitorProduct {
+                competitor_id: row.get("competitor_id")?,
+                competitor_name: row.get("competitor_name")?,
+                first_seen_at: row.get("first_seen_at")?,
+                last_seen_at: row.get("last_seen_at")?,
+                product: map_product_row(row)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(products)
+}
+
+// ==========================================
+// SCRAPE SCHEDULES
+// ==========================================
+
+pub fn add_scrape_schedule(db_path: &Path, schedule: &ScrapeSchedule) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    let categories = serde_json::to_string(&schedule.categories).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO scrape_schedules (id, name, categories, interval_minutes, paused, next_run_at, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+        params![
+            schedule.id,
+            schedule.name,
+            categories,
+            schedule.interval_minutes,
+            schedule.paused as i32,
+            schedule.next_run_at,
+            schedule.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn query_scrape_schedules(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<ScrapeSchedule>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, name, categories, interval_minutes, paused, last_run_at, next_run_at,
+                last_error, created_at
+         FROM scrape_schedules {} ORDER BY created_at",
+        filter
+    ))?;
+
+    let schedules = stmt
+        .query_map(args, |row| {
+            Ok(ScrapeSchedule {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                categories: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                interval_minutes: row.get(3)?,
+                paused: row.get::<_, i32>(4)? != 0,
+                last_run_at: row.get(5)?,
+                next_run_at: row.get(6)?,
+                last_error: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(schedules)
+}
+
+pub fn get_scrape_schedules(db_path: &Path) -> Result<Vec<ScrapeSchedule>> {
+    let conn = get_connection(db_path)?;
+    query_scrape_schedules(&conn, "", &[])
+}
+
+pub fn get_scrape_schedule(db_path: &Path, id: &str) -> Result<Option<ScrapeSchedule>> {
+    let conn = get_connection(db_path)?;
+    Ok(query_scrape_schedules(&conn, "WHERE id = ?", &[&id])?
+        .into_iter()
+        .next())
+}
+
+/// Schedules not paused whose run is due at `now` (RFC 3339)
+pub fn get_due_scrape_schedules(db_path: &Path, now: &str) -> Result<Vec<ScrapeSchedule>> {
+    let conn = get_connection(db_path)?;
+    query_scrape_schedules(&conn, "WHERE paused = 0 AND next_run_at <= ?", &[&now])
+}
+
+pub fn set_scrape_schedule_paused(db_path: &Path, id: &str, paused: bool) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute(
+        "UPDATE scrape_schedules SET paused = ? WHERE id = ?",
+        params![paused as i32, id],
+    )?;
+    Ok(rows > 0)
+}
+
+/// Record a run of the schedule; `error` is None when it succeeded
+pub fn mark_scrape_schedule_run(
+    db_path: &Path,
+    id: &str,
+    ran_at: &str,
+    next_run_at: &str,
+    error: Option<&str>,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE scrape_schedules SET last_run_at = ?, next_run_at = ?, last_error = ? WHERE id = ?",
+        params![ran_at, next_run_at, error, id],
+    )?;
+    Ok(())
+}
+
+pub fn delete_scrape_schedule(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM scrape_schedules WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+// ==========================================
+// CRM LEADS
+// ==========================================
+
+/// Insert the lead, or replace the stored one with the same id
+pub fn save_crm_lead(db_path: &Path, lead: &CrmLead) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO crm_leads (id, kind, name, company, email, phone, handle, product_id, status,
+                                notes, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            kind = excluded.kind, name = excluded.name, company = excluded.company,
+            email = excluded.email, phone = excluded.phone, handle = excluded.handle,
+            product_id = excluded.product_id, status = excluded.status, notes = excluded.notes,
+            updated_at = excluded.updated_at",
+        params![
+            lead.id,
+            lead.kind.as_str(),
+            lead.name,
+            lead.company,
+            lead.email,
+            lead.phone,
+            lead.handle,
+            lead.product_id,
+            lead.status.as_str(),
+            lead.notes,
+            lead.created_at,
+            lead.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn query_crm_leads(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<CrmLead>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, kind, name, company, email, phone, handle, product_id, status, notes,
+                created_at, updated_at
+         FROM crm_leads {} ORDER BY updated_at DESC",
+        filter
+    ))?;
+
+    let leads = stmt
+        .query_map(args, |row| {
+            Ok(CrmLead {
+                id: row.get(0)?,
+                kind: LeadKind::parse(&row.get::<_, String>(1)?).unwrap_or(LeadKind::Supplier),
+                name: row.get(2)?,
+                company: row.get(3)?,
+                email: row.get(4)?,
+                phone: row.get(5)?,
+                handle: row.get(6)?,
+                product_id: row.get(7)?,
+                status: LeadStatus::parse(&row.get::<_, String>(8)?).unwrap_or(LeadStatus::New),
+                notes: row.get(9)?,
+                created_at: row.get(10)?,
+                updated_at: row.get(11)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(leads)
+}
+
+/// Leads, most recently updated first, optionally only those at `status`
+/// or linked to `product_id`
+pub fn get_crm_leads(
+    db_path: &Path,
+    status: Option<LeadStatus>,
+    product_id: Option<&str>,
+) -> Result<Vec<CrmLead>> {
+    let conn = get_connection(db_path)?;
+    let status = status.map(|s| s.as_str());
+    query_crm_leads(
+        &conn,
+        "WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR product_id = ?2)",
+        &[&status, &product_id],
+    )
+}
+
+pub fn get_crm_lead(db_path: &Path, id: &str) -> Result<Option<CrmLead>> {
+    let conn = get_connection(db_path)?;
+    Ok(query_crm_leads(&conn, "WHERE id = ?", &[&id])?
+        .into_iter()
+        .next())
+}
+
+pub fn delete_crm_lead(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM crm_leads WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+// ==========================================
+// SOCIAL POSTS
+// ==========================================
+
+/// Insert the post, or replace the stored one with the same id
+pub fn save_social_post(db_path: &Path, post: &SocialPost) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO social_posts (id, product_id, copy_id, platform, caption, image_url,
+                                   scheduled_at, status, external_id, error, published_at,
+                                   created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            product_id = excluded.product_id, copy_id = excluded.copy_id,
+            platform = excluded.platform, caption = excluded.caption,
+            image_url = excluded.image_url, scheduled_at = excluded.scheduled_at,
+            status = excluded.status, external_id = excluded.external_id, error = excluded.error,
+            published_at = excluded.published_at, updated_at = excluded.updated_at",
+        params![
+            post.id,
+            post.product_id,
+            post.copy_id,
+            post.platform,
+            post.caption,
+            post.image_url,
+            post.scheduled_at,
+            post.status.as_str(),
+            post.external_id,
+            post.error,
+            post.published_at,
+            post.created_at,
+            post.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+fn query_social_posts(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<SocialPost>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, product_id, copy_id, platform, caption, image_url, scheduled_at, status,
+                external_id, error, published_at, created_at, updated_at
+         FROM social_posts {} ORDER BY scheduled_at",
+        filter
+    ))?;
+
+    let posts = stmt
+        .query_map(args, |row| {
+            Ok(SocialPost {
+                id: row.get(0)?,
+                product_id: row.get(1)?,
+                copy_id: row.get(2)?,
+                platform: row.get(3)?,
+                caption: row.get(4)?,
+                image_url: row.get(5)?,
+                scheduled_at: row.get(6)?,
+                status: SocialPostStatus::parse(&row.get::<_, String>(7)?)
+                    .unwrap_or(SocialPostStatus::Scheduled),
+                external_id: row.get(8)?,
+                error: row.get(9)?,
+                published_at: row.get(10)?,
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(posts)
+}
+
+/// Posts in the order they go out, optionally only those at `status` or
+/// scheduled between `from` and `to` (RFC 3339, inclusive)
+pub fn get_social_posts(
+    db_path: &Path,
+    status: Option<SocialPostStatus>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<SocialPost>> {
+    let conn = get_connection(db_path)?;
+    let status = status.map(|s| s.as_str());
+    query_social_posts(
+        &conn,
+        "WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR scheduled_at >= ?2)
+           AND (?3 IS NULL OR scheduled_at <= ?3)",
+        &[&status, &from, &to],
+    )
+}
+
+pub fn get_social_post(db_path: &Path, id: &str) -> Result<Option<SocialPost>> {
+    let conn = get_connection(db_path)?;
+    Ok(query_social_posts(&conn, "WHERE id = ?", &[&id])?
+        .into_iter()
+        .next())
+}
+
+/// Scheduled posts whose time has come by `now` (RFC 3339)
+pub fn get_due_social_posts(db_path: &Path, now: &str) -> Result<Vec<SocialPost>> {
+    let conn = get_connection(db_path)?;
+    query_social_posts(
+        &conn,
+        "WHERE status = 'scheduled' AND scheduled_at <= ?",
+        &[&now],
+    )
+}
+
+pub fn delete_social_post(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    let rows = conn.execute("DELETE FROM social_posts WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+// ==========================================
+// SCRAPE SESSIONS
+// ==========================================
+
+/// Start session `id` for a run of `config` (ScraperConfig as JSON)
+pub fn start_scrape_session(
+    db_path: &Path,
+    id: &str,
+    config: &str,
+    categories: &[String],
+    created_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    let categories = serde_json::to_string(categories).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "INSERT INTO scrape_sessions (id, config, categories, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?)",
+        params![id, config, categories, created_at, created_at],
+    )?;
+    Ok(())
+}
+
+fn query_scrape_sessions(
+    conn: &Connection,
+    filter: &str,
+    args: &[&dyn rusqlite::ToSql],
+) -> Result<Vec<ScrapeSession>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT s.id, s.categories, s.checkpoints, s.status, s.reason, s.created_at, s.updated_at,
+                (SELECT COUNT(*) FROM scrape_session_products p WHERE p.session_id = s.id)
+         FROM scrape_sessions s {} ORDER BY s.updated_at DESC",
+        filter
+    ))?;
+
+    let sessions = stmt
+        .query_map(args, |row| {
+            Ok(ScrapeSession {
+                id: row.get(0)?,
+                categories: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                checkpoints: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                status: row.get(3)?,
+                reason: row.get(4)?,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+                collected: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(sessions)
+}
+
+/// Sessions most recently updated first
+pub fn get_scrape_sessions(db_path: &Path) -> Result<Vec<ScrapeSession>> {
+    let conn = get_connection(db_path)?;
+    query_scrape_sessions(&conn, "", &[])
+}
+
+pub fn get_scrape_session(db_path: &Path, id: &str) -> Result<Option<ScrapeSession>> {
+    let conn = get_connection(db_path)?;
+    Ok(query_scrape_sessions(&conn, "WHERE s.id = ?", &[&id])?
+        .into_iter()
+        .next())
+}
+
+/// ScraperConfig (as JSON) the session was started with
+pub fn get_scrape_session_config(db_path: &Path, id: &str) -> Result<Option<String>> {
+    let conn = get_connection(db_path)?;
+    conn.query_row(
+        "SELECT config FROM scrape_sessions WHERE id = ?",
+        params![id],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Products the session collected so far
+pub fn get_scrape_session_products(db_path: &Path, id: &str) -> Result<Vec<Product>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT product FROM scrape_session_products WHERE session_id = ? ORDER BY rowid",
+    )?;
+
+    let products = stmt
+        .query_map(params![id], |row| row.get::<_, String>(0))?
+        .filter_map(|json| json.ok().and_then(|json| serde_json::from_str(&json).ok()))
+        .collect();
+
+    Ok(products)
+}
+
+/// Add products collected by the session; ones it already has are kept
+pub fn add_scrape_session_products(db_path: &Path, id: &str, products: &[Product]) -> Result<()> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+    for product in products {
+        let json = serde_json::to_string(product).unwrap_or_default();
+        tx.execute(
+            "INSERT OR IGNORE INTO scrape_session_products (session_id, tiktok_id, product)
+             VALUES (?, ?, ?)",
+            params![id, product.tiktok_id, json],
+        )?;
+    }
+    tx.commit()
+}
+
+pub fn save_scrape_session_checkpoints(
+    db_path: &Path,
+    id: &str,
+    checkpoints: &[CategoryCheckpoint],
+    updated_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    let checkpoints = serde_json::to_string(checkpoints).unwrap_or_else(|_| "[]".to_string());
+    conn.execute(
+        "UPDATE scrape_sessions SET checkpoints = ?, updated_at = ? WHERE id = ?",
+        params![checkpoints, updated_at, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_scrape_session_status(
+    db_path: &Path,
+    id: &str,
+    status: &str,
+    reason: Option<&str>,
+    updated_at: &str,
+) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE scrape_sessions SET status = ?, reason = ?, updated_at = ? WHERE id = ?",
+        params![status, reason, updated_at, id],
+    )?;
+    Ok(())
+}
+
+/// Sessions still marked running when the app starts were cut short by a
+/// crash; returns how many
+pub fn interrupt_running_scrape_sessions(db_path: &Path, updated_at: &str) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "UPDATE scrape_sessions SET status = 'interrupted', reason = 'crashed', updated_at = ?
+         WHERE status = 'running'",
+        params![updated_at],
+    )
+}
+
+pub fn delete_scrape_session(db_path: &Path, id: &str) -> Result<bool> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "DELETE FROM scrape_session_products WHERE session_id = ?",
+        params![id],
+    )?;
+    let rows = conn.execute("DELETE FROM scrape_sessions WHERE id = ?", params![id])?;
+    Ok(rows > 0)
+}
+
+// ==========================================
+// NICHE SATURATION
+// ==========================================
+
+/// One listing of a niche: (niche, seller, price, first seen)
+pub type NicheListing = (String, Option<String>, f64, String);
+
+/// Every priced product with a category, with the time it was first seen
+pub fn get_niche_listings(db_path: &Path) -> Result<Vec<NicheListing>> {
+    let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT p.category, p.seller_name, p.price,
+                COALESCE((SELECT MIN(h.collected_at) FROM product_history h WHERE h.product_id = p.id),
+                         p.collected_at)
+         FROM products p
+         WHERE p.category IS NOT NULL AND p.category != '' AND p.price > 0",
+    )?;
+
+    let listings = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(listings)
+}
+
+/// Replace the stored scores with a fresh computation
+pub fn save_niche_saturation(db_path: &Path, niches: &[NicheSaturation]) -> Result<()> {
+    let mut conn = get_connection(db_path)?;
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM niche_saturation", [])?;
+    for niche in niches {
+        tx.execute(
+            "INSERT INTO niche_saturation (niche, products, sellers, avg_price, price_dispersion, new_listing_rate, score, computed_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                niche.niche,
+                niche.products,
+                niche.sellers,
+                niche.avg_price,
+                niche.price_dispersion,
+                niche.new_listing_rate,
+                niche.score,
+                niche.computed_at
+            ],
+        )?;
+    }
+    tx.commit()
 }
 
 // ==========================================
-// SEARCH HISTORY QUERIES
+// CATEGORY TRENDS
 // ==========================================
 
-pub fn save_search_history(
-    db_path: &Path,
-    user_id: &str,
-    query: &str,
-    filters: &str,
-    results_count: i32,
-) -> Result<bool> {
+/// Per-category movement since `since`: each product is compared with its
+/// last snapshot from before then. Categories heating up fastest first.
+pub fn get_category_trends(db_path: &Path, since: &str) -> Result<Vec<CategoryTrend>> {
     let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "WITH listing AS (
+             SELECT p.category, p.price, p.sales_count,
+                    (SELECT h.price FROM product_history h
+                     WHERE h.product_id = p.id AND datetime(h.collected_at) <= datetime(?1)
+                     ORDER BY h.collected_at DESC LIMIT 1) AS base_price,
+                    (SELECT h.sales_count FROM product_history h
+                     WHERE h.product_id = p.id AND datetime(h.collected_at) <= datetime(?1)
+                     ORDER BY h.collected_at DESC LIMIT 1) AS base_sales,
+                    COALESCE((SELECT MIN(h.collected_at) FROM product_history h WHERE h.product_id = p.id),
+                             p.collected_at) AS first_seen
+             FROM products p
+             WHERE p.category IS NOT NULL AND p.category != ''
+         )
+         SELECT category,
+                COUNT(*),
+                SUM(datetime(first_seen) > datetime(?1)),
+                COUNT(base_sales),
+                SUM(sales_count),
+                100.0 * (SUM(CASE WHEN base_sales IS NOT NULL THEN sales_count END) - SUM(base_sales))
+                    / NULLIF(SUM(base_sales), 0),
+                AVG(price),
+                AVG(100.0 * (price - base_price) / NULLIF(base_price, 0))
+         FROM listing
+         GROUP BY category
+         ORDER BY 6 DESC NULLS LAST, 3 DESC",
+    )?;
 
-    let id = Uuid::new_v4().to_string();
-    let now = chrono::Utc::now().to_rfc3339();
+    let trends = stmt
+        .query_map(params![since], |row| {
+            Ok(CategoryTrend {
+                category: row.get(0)?,
+                products: row.get(1)?,
+                new_products: row.get(2)?,
+                tracked_products: row.get(3)?,
+                total_sales: row.get(4)?,
+                sales_growth: row.get(5)?,
+                avg_price: row.get(6)?,
+                avg_price_change: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(trends)
+}
+
+// ==========================================
+// PRODUCT ALERTS
+// ==========================================
 
+pub fn add_product_alert(db_path: &Path, alert: &ProductAlert) -> Result<()> {
+    let conn = get_connection(db_path)?;
     conn.execute(
-        "INSERT INTO search_history (id, user_id, query, filters, results_count, searched_at)
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![id, user_id, query, filters, results_count, now],
+        "INSERT INTO product_alerts (id, product_id, kind, message, old_value, new_value, is_read, created_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            alert.id,
+            alert.product_id,
+            alert.kind.as_str(),
+            alert.message,
+            alert.old_value,
+            alert.new_value,
+            alert.is_read as i32,
+            alert.created_at
+        ],
     )?;
-
-    Ok(true)
+    Ok(())
 }
 
-pub fn get_search_history(
+/// Most recent alerts first
+pub fn get_product_alerts(
     db_path: &Path,
-    user_id: &str,
+    unread_only: bool,
     limit: i32,
-) -> Result<Vec<SearchHistoryItem>> {
+) -> Result<Vec<ProductAlert>> {
     let conn = get_connection(db_path)?;
-
     let mut stmt = conn.prepare(
-        "SELECT * FROM search_history WHERE user_id = ? ORDER BY searched_at DESC LIMIT ?",
+        "SELECT id, product_id, kind, message, old_value, new_value, is_read, created_at
+         FROM product_alerts WHERE is_read = 0 OR ?1 = 0
+         ORDER BY created_at DESC LIMIT ?2",
     )?;
 
-    let history = stmt
-        .query_map(params![user_id, limit], |row| {
-            Ok(SearchHistoryItem {
-                id: row.get(0)?,
-                user_id: row.get(1)?,
-                query: row.get(2)?,
-                filters: row.get(3)?,
-                results_count: row.get(4)?,
-                searched_at: row.get(5)?,
-            })
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
+    let alerts = stmt
+        .query_map(params![unread_only as i32, limit], map_alert_row)?
+        .collect::<Result<Vec<_>>>()?;
 
-    Ok(history)
+    Ok(alerts.into_iter().flatten().collect())
 }
 
-// ==========================================
-// DASHBOARD STATS
-// ==========================================
-
-pub fn get_dashboard_stats(db_path: &Path, user_id: &str) -> Result<DashboardStats> {
+/// Alerts raised since a date, oldest first
+pub fn get_product_alerts_since(db_path: &Path, since: &str) -> Result<Vec<ProductAlert>> {
     let conn = get_connection(db_path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, product_id, kind, message, old_value, new_value, is_read, created_at
+         FROM product_alerts WHERE created_at >= ?
+         ORDER BY created_at ASC",
+    )?;
 
-    let total_products: i64 = conn
-        .query_row("SELECT COUNT(*) FROM products", [], |row| row.get(0))
-        .unwrap_or(0);
+    let alerts = stmt
+        .query_map(params![since], map_alert_row)?
+        .collect::<Result<Vec<_>>>()?;
 
-    let trending_products: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM products WHERE is_trending = 1",
-            [],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+    Ok(alerts.into_iter().flatten().collect())
+}
 
-    let favorite_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM favorites WHERE user_id = ?",
-            params![user_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+fn map_alert_row(row: &rusqlite::Row) -> rusqlite::Result<Option<ProductAlert>> {
+    // Rows of kinds this version doesn't know are skipped
+    let Some(kind) = AlertKind::parse(&row.get::<_, String>(2)?) else {
+        return Ok(None);
+    };
+    Ok(Some(ProductAlert {
+        id: row.get(0)?,
+        product_id: row.get(1)?,
+        kind,
+        message: row.get(3)?,
+        old_value: row.get(4)?,
+        new_value: row.get(5)?,
+        is_read: row.get::<_, i32>(6)? != 0,
+        created_at: row.get(7)?,
+    }))
+}
 
-    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-    let searches_today: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM search_history WHERE user_id = ? AND date(searched_at) = ?",
-            params![user_id, today],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+pub fn mark_alerts_read(db_path: &Path, ids: &[String]) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    let mut updated = 0;
+    for id in ids {
+        updated += conn.execute(
+            "UPDATE product_alerts SET is_read = 1 WHERE id = ?",
+            params![id],
+        )?;
+    }
+    Ok(updated)
+}
 
-    let copies_generated: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM copy_history WHERE user_id = ?",
-            params![user_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
+/// Store the HTML of a page that failed, gzipped. Only the newest
+/// `MAX_ERROR_PAGES` are kept.
+pub fn save_error_page(db_path: &Path, url: &str, html: &str) -> Result<()> {
+    let conn = get_connection(db_path)?;
+    conn.execute(
+        "INSERT INTO error_pages (url, html, html_gz, html_size) VALUES (?1, '', ?2, ?3)",
+        params![url, gzip(html)?, html.len() as i64],
+    )?;
+    conn.execute(
+        "DELETE FROM error_pages WHERE id NOT IN
+            (SELECT id FROM error_pages ORDER BY id DESC LIMIT ?)",
+        params![MAX_ERROR_PAGES],
+    )?;
+    Ok(())
+}
 
-    // Get top categories
+/// Stored error pages, newest first, without their HTML
+pub fn get_error_pages(db_path: &Path, limit: i32, offset: i32) -> Result<Vec<ErrorPageSummary>> {
+    let conn = get_connection(db_path)?;
     let mut stmt = conn.prepare(
-        "SELECT category, COUNT(*) as count FROM products 
-         WHERE category IS NOT NULL 
-         GROUP BY category 
-         ORDER BY count DESC 
-         LIMIT 5",
+        "SELECT id, url, COALESCE(html_size, LENGTH(html)), created_at
+         FROM error_pages ORDER BY id DESC LIMIT ? OFFSET ?",
     )?;
 
-    let top_categories: Vec<CategoryCount> = stmt
-        .query_map([], |row| {
-            Ok(CategoryCount {
-                name: row.get(0)?,
-                count: row.get(1)?,
+    let pages = stmt
+        .query_map(params![limit, offset], |row| {
+            Ok(ErrorPageSummary {
+                id: row.get(0)?,
+                url: row.get(1)?,
+                size: row.get(2)?,
+                created_at: row.get(3)?,
             })
         })?
-        .filter_map(|r| r.ok())
-        .collect();
+        .collect::<Result<Vec<_>>>()?;
 
-    Ok(DashboardStats {
-        total_products,
-        trending_products,
-        favorite_count,
-        searches_today,
-        copies_generated,
-        top_categories,
-    })
+    Ok(pages)
 }
 
-pub fn save_error_page(db_path: &Path, url: &str, html: &str) -> Result<()> {
-    let conn = Connection::open(db_path)?;
-    conn.execute(
-        "INSERT INTO error_pages (url, html) VALUES (?1, ?2)",
-        params![url, html],
-    )?;
-    Ok(())
+/// An error page with its HTML; rows from before compression hold it as text
+pub fn get_error_page(db_path: &Path, id: i64) -> Result<Option<ErrorPage>> {
+    let conn = get_connection(db_path)?;
+    let row = conn
+        .query_row(
+            "SELECT id, url, html, html_gz, created_at FROM error_pages WHERE id = ?",
+            params![id],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<Vec<u8>>>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((id, url, html, html_gz, created_at)) = row else {
+        return Ok(None);
+    };
+
+    Ok(Some(ErrorPage {
+        id,
+        url,
+        html: match html_gz {
+            Some(bytes) => gunzip(&bytes, 3)?,
+            None => html,
+        },
+        created_at,
+    }))
+}
+
+/// Delete error pages stored before `before` (RFC 3339), or all of them
+pub fn delete_error_pages(db_path: &Path, before: Option<&str>) -> Result<usize> {
+    let conn = get_connection(db_path)?;
+    match before {
+        Some(before) => conn.execute(
+            "DELETE FROM error_pages WHERE created_at < datetime(?)",
+            params![before],
+        ),
+        None => conn.execute("DELETE FROM error_pages", []),
+    }
 }
 
 pub fn get_product_history(db_path: &Path, product_id: &str) -> Result<Vec<ProductHistory>> {
@@ -864,22 +4307,44 @@ pub fn get_product_history(db_path: &Path, product_id: &str) -> Result<Vec<Produ
     )?;
 
     let history = stmt
-        .query_map(params![product_id], |row| {
-            Ok(ProductHistory {
-                id: row.get(0)?,
-                product_id: row.get(1)?,
-                price: row.get(2)?,
-                sales_count: row.get(3)?,
-                stock_level: row.get(4).ok(),
-                collected_at: row.get(5)?,
-            })
-        })?
+        .query_map(params![product_id], map_history_row)?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(history)
+}
+
+/// Snapshots of all products collected since a date, grouped by product and
+/// oldest first within each
+pub fn get_history_since(db_path: &Path, since: &str) -> Result<Vec<ProductHistory>> {
+    let conn = get_connection(db_path)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, product_id, price, sales_count, stock_level, collected_at
+         FROM product_history
+         WHERE collected_at >= ?
+         ORDER BY product_id, collected_at ASC",
+    )?;
+
+    let history = stmt
+        .query_map(params![since], map_history_row)?
         .filter_map(|r| r.ok())
         .collect();
 
     Ok(history)
 }
 
+fn map_history_row(row: &rusqlite::Row) -> rusqlite::Result<ProductHistory> {
+    Ok(ProductHistory {
+        id: row.get(0)?,
+        product_id: row.get(1)?,
+        price: row.get(2)?,
+        sales_count: row.get(3)?,
+        stock_level: row.get(4).ok(),
+        collected_at: row.get(5)?,
+    })
+}
+
 // ==================================================
 // SUBSCRIPTION CACHE (SaaS Híbrido)
 // ==================================================
@@ -994,7 +4459,9 @@ pub fn get_subscription_cache(db_path: &Path) -> Result<Option<CachedSubscriptio
     }
 }
 
-/// Update usage tracking for a feature
+/// Add `increment` to the usage of a feature in the period starting at
+/// `period_start`. Each period has its own row, so usage starts from zero when
+/// a new period begins.
 pub fn update_usage_tracking(
     db_path: &Path,
     feature: &str,
@@ -1008,47 +4475,38 @@ pub fn update_usage_tracking(
     // Ensure tables exist
     let _ = init_subscription_tables(db_path);
     
-    // Get current usage
-    let current: i32 = conn
-        .query_row(
-            "SELECT used FROM usage_tracking WHERE feature = ? AND period_end > datetime('now')",
-            params![feature],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-    
-    let new_usage = current + increment;
-    
-    // Insert or update
-    conn.execute(
+    conn.query_row(
         "INSERT INTO usage_tracking (id, feature, used, limit_value, period_start, period_end, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, strftime('%Y-%m-%d %H:%M:%f', 'now'))
          ON CONFLICT(id) DO UPDATE SET 
-            used = ?3,
-            updated_at = datetime('now')",
+            used = used + ?3,
+            limit_value = ?4,
+            period_end = ?6,
+            updated_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+         RETURNING used",
         params![
             format!("{}_{}", feature, period_start),
             feature,
-            new_usage,
+            increment,
             limit,
             period_start,
             period_end,
         ],
-    )?;
-    
-    Ok(new_usage)
+        |row| row.get(0),
+    )
 }
 
-/// Get usage for a feature
-pub fn get_feature_usage(db_path: &Path, feature: &str) -> Result<(i32, i32)> {
+/// Usage and limit of a feature in the period starting at `period_start`
+pub fn get_feature_usage(db_path: &Path, feature: &str, period_start: &str) -> Result<(i32, i32)> {
     let conn = Connection::open(db_path)?;
     
+    // Ensure tables exist
+    let _ = init_subscription_tables(db_path);
+
     let result: Option<(i32, i32)> = conn
         .query_row(
-            "SELECT used, limit_value FROM usage_tracking 
-             WHERE feature = ? AND period_end > datetime('now')
-             ORDER BY period_start DESC LIMIT 1",
-            params![feature],
+            "SELECT used, limit_value FROM usage_tracking WHERE id = ?",
+            params![format!("{}_{}", feature, period_start)],
             |row| Ok((row.get(0)?, row.get(1)?)),
         )
         .optional()?;
@@ -1056,7 +4514,56 @@ pub fn get_feature_usage(db_path: &Path, feature: &str) -> Result<(i32, i32)> {
     Ok(result.unwrap_or((0, 0)))
 }
 
+/// Usage rows changed since they were last sent to the backend. Both
+/// timestamps are kept to the millisecond so a change right after a sync
+/// is not missed.
+pub fn get_unsynced_usage(db_path: &Path) -> Result<Vec<FeatureUsage>> {
+    let conn = Connection::open(db_path)?;
+
+    // Ensure tables exist
+    let _ = init_subscription_tables(db_path);
+
+    let mut stmt = conn.prepare(
+        "SELECT feature, used, limit_value, period_start, period_end
+         FROM usage_tracking
+         WHERE synced_at IS NULL OR synced_at < updated_at
+         ORDER BY period_start ASC",
+    )?;
+
+    let usage = stmt
+        .query_map([], |row| {
+            Ok(FeatureUsage {
+                feature: row.get(0)?,
+                used: row.get(1)?,
+                limit: row.get(2)?,
+                period_start: row.get(3)?,
+                period_end: row.get(4)?,
+            })
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(usage)
+}
+
+/// Mark a usage row as sent, unless it changed while it was being sent
+pub fn mark_usage_synced(db_path: &Path, usage: &FeatureUsage) -> Result<()> {
+    let conn = Connection::open(db_path)?;
+    conn.execute(
+        "UPDATE usage_tracking SET synced_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+         WHERE id = ? AND used = ?",
+        params![
+            format!("{}_{}", usage.feature, usage.period_start),
+            usage.used
+        ],
+    )?;
+    Ok(())
+}
+
 /// Add pending sync item (for hybrid mode)
+///
+/// An entity already waiting with the same operation is not queued twice; the
+/// existing item id is returned instead.
 pub fn add_pending_sync(
     db_path: &Path,
     entity_type: &str,
@@ -1065,6 +4572,18 @@ pub fn add_pending_sync(
     data: Option<&str>,
 ) -> Result<String> {
     let conn = Connection::open(db_path)?;
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM pending_sync WHERE entity_type = ?1 AND entity_id = ?2 AND operation = ?3",
+            params![entity_type, entity_id, operation],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
     let id = Uuid::new_v4().to_string();
     
     conn.execute(
@@ -1076,6 +4595,30 @@ pub fn add_pending_sync(
     Ok(id)
 }
 
+/// Count pending sync items, optionally for a single entity type
+pub fn count_pending_sync(db_path: &Path, entity_type: Option<&str>) -> Result<i64> {
+    let conn = Connection::open(db_path)?;
+
+    match entity_type {
+        Some(entity_type) => conn.query_row(
+            "SELECT COUNT(*) FROM pending_sync WHERE entity_type = ?",
+            params![entity_type],
+            |row| row.get(0),
+        ),
+        None => conn.query_row("SELECT COUNT(*) FROM pending_sync", [], |row| row.get(0)),
+    }
+}
+
+/// Record a failed upload attempt for a pending sync item
+pub fn mark_pending_sync_failed(db_path: &Path, id: &str, error: &str) -> Result<()> {
+    let conn = Connection::open(db_path)?;
+    conn.execute(
+        "UPDATE pending_sync SET retry_count = retry_count + 1, last_error = ?2 WHERE id = ?1",
+        params![id, error],
+    )?;
+    Ok(())
+}
+
 /// Get all pending sync items
 pub fn get_pending_sync(db_path: &Path) -> Result<Vec<(String, String, String, String, Option<String>)>> {
     let conn = Connection::open(db_path)?;
@@ -1116,3 +4659,193 @@ pub fn clear_subscription_cache(db_path: &Path) -> Result<()> {
     conn.execute("DELETE FROM usage_tracking", [])?;
     Ok(())
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Database in the temp directory, deleted when dropped
+    pub(crate) struct TempDb(std::path::PathBuf);
+
+    impl TempDb {
+        pub(crate) fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("tiktrend-test-{}.db", Uuid::new_v4()));
+            init_database(&path).expect("database created");
+            Self(path)
+        }
+
+        pub(crate) fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn product(tiktok_id: &str, updated_at: &str) -> Product {
+        let mut product = crate::demo::generate_products(1).remove(0);
+        product.tiktok_id = tiktok_id.to_string();
+        product.is_demo = false;
+        product.collected_at = updated_at.to_string();
+        product.updated_at = updated_at.to_string();
+        product
+    }
+
+    #[test]
+    fn test_bulk_update_reaches_delta_sync() {
+        let db = TempDb::new();
+        let product = product("1001", "2026-01-01T00:00:00+00:00");
+        save_product(db.path(), &product).unwrap();
+
+        let last_sync = "2026-01-02T00:00:00+00:00";
+        assert!(
+            get_products_updated_since(db.path(), Some(last_sync), 10, 0)
+                .unwrap()
+                .is_empty()
+        );
+
+        let patch = ProductPatch {
+            category: Some("Cozinha".to_string()),
+            currency: Some("USD".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            bulk_update_products(db.path(), std::slice::from_ref(&product.id), &patch).unwrap(),
+            1
+        );
+
+        let updated = get_products_updated_since(db.path(), Some(last_sync), 10, 0).unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].category.as_deref(), Some("Cozinha"));
+        assert_eq!(updated[0].currency, "USD");
+    }
+
+    #[test]
+    fn test_unchanged_rescrape_keeps_row() {
+        let db = TempDb::new();
+        let first = product("1002", "2026-01-01T00:00:00+00:00");
+        assert_eq!(save_product(db.path(), &first).unwrap(), SaveOutcome::New);
+
+        let mut rescrape = first.clone();
+        rescrape.id = Uuid::new_v4().to_string();
+        rescrape.collected_at = "2026-01-03T00:00:00+00:00".to_string();
+        rescrape.updated_at = "2026-01-03T00:00:00+00:00".to_string();
+        assert_eq!(
+            save_product(db.path(), &rescrape).unwrap(),
+            SaveOutcome::Unchanged
+        );
+
+        let stored = get_product_by_tiktok_id(db.path(), "1002")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.id, first.id);
+        assert_eq!(stored.updated_at, first.updated_at);
+        let last_sync = "2026-01-02T00:00:00+00:00";
+        assert!(
+            get_products_updated_since(db.path(), Some(last_sync), 10, 0)
+                .unwrap()
+                .is_empty()
+        );
+
+        rescrape.price += 1.0;
+        assert_eq!(
+            save_product(db.path(), &rescrape).unwrap(),
+            SaveOutcome::Updated
+        );
+        let updated = get_products_updated_since(db.path(), Some(last_sync), 10, 0).unwrap();
+        assert_eq!(updated.len(), 1);
+    }
+
+    #[test]
+    fn test_foreign_key_rebuild_keeps_rows_and_cascades() {
+        let db = TempDb::new();
+        let product = product("1004", "2026-01-01T00:00:00+00:00");
+        save_product(db.path(), &product).unwrap();
+
+        // Tables as created before the foreign keys had ON DELETE actions
+        {
+            let conn = Connection::open(db.path()).unwrap();
+            conn.execute_batch(
+                "DROP TABLE product_history;
+                DROP TABLE favorites;
+                CREATE TABLE product_history (
+                    id TEXT PRIMARY KEY,
+                    product_id TEXT NOT NULL,
+                    price REAL NOT NULL,
+                    sales_count INTEGER DEFAULT 0,
+                    collected_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (product_id) REFERENCES products(id)
+                );
+                CREATE TABLE favorites (
+                    id TEXT PRIMARY KEY,
+                    user_id TEXT NOT NULL,
+                    product_id TEXT NOT NULL,
+                    notes TEXT,
+                    added_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (user_id) REFERENCES users(id),
+                    FOREIGN KEY (product_id) REFERENCES products(id),
+                    UNIQUE(user_id, product_id)
+                );",
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO product_history (id, product_id, price, sales_count)
+                 VALUES ('history-1', ?, 49.9, 120)",
+                params![product.id],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO favorites (id, user_id, product_id, notes)
+                 VALUES ('favorite-1', 'user-1', ?, 'kept')",
+                params![product.id],
+            )
+            .unwrap();
+        }
+
+        init_database(db.path()).unwrap();
+
+        let conn = get_connection(db.path()).unwrap();
+        for table in ["product_history", "favorites"] {
+            let sql: String = conn
+                .query_row(
+                    "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?",
+                    params![table],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(sql.contains("ON DELETE CASCADE"), "{} not rebuilt", table);
+        }
+        let (price, sales): (f64, i64) = conn
+            .query_row(
+                "SELECT price, sales_count FROM product_history WHERE id = 'history-1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((price, sales), (49.9, 120));
+        let notes: Option<String> = conn
+            .query_row(
+                "SELECT notes FROM favorites WHERE id = 'favorite-1' AND product_id = ?",
+                params![product.id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(notes.as_deref(), Some("kept"));
+
+        conn.execute("DELETE FROM products WHERE id = ?", params![product.id])
+            .unwrap();
+        let count = |table: &str| -> i64 {
+            conn.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE product_id = ?", table),
+                params![product.id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(count("product_history"), 0);
+        assert_eq!(count("favorites"), 0);
+    }
+}
@@ -0,0 +1,199 @@
+// Demand Estimation
+// Units sold per day from successive history snapshots. Stock drops between
+// two checks are actual sales, so when the stock level is known they give a
+// more reliable figure than the rounded (and sometimes inflated) sales counter.
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{DemandEstimate, ProductHistory};
+
+/// Shortest observation window that gives a usable rate
+const MIN_WINDOW_DAYS: f64 = 1.0 / 24.0;
+
+/// Intervals and days of observation at which confidence stops growing
+const FULL_CONFIDENCE_INTERVALS: f64 = 6.0;
+const FULL_CONFIDENCE_DAYS: f64 = 3.0;
+
+/// The displayed sales counter is rounded ("1,2 mil"), so it never gets
+/// more than this confidence
+const MAX_SALES_CONFIDENCE: f64 = 0.5;
+
+struct Snapshot {
+    at: DateTime<Utc>,
+    stock_level: Option<i32>,
+    sales_count: i32,
+}
+
+fn coverage(intervals: u32, days: f64) -> f64 {
+    (intervals as f64 / FULL_CONFIDENCE_INTERVALS).min(1.0) * (days / FULL_CONFIDENCE_DAYS).min(1.0)
+}
+
+fn days_between(a: &Snapshot, b: &Snapshot) -> f64 {
+    (b.at - a.at).num_seconds() as f64 / 86_400.0
+}
+
+/// Estimate from the product's history, stock-based when the stock level
+/// was tracked and sales-counter based otherwise
+pub fn estimate_demand(history: &[ProductHistory]) -> Option<DemandEstimate> {
+    let mut snapshots: Vec<Snapshot> = history
+        .iter()
+        .filter_map(|h| {
+            Some(Snapshot {
+                at: DateTime::parse_from_rfc3339(&h.collected_at)
+                    .ok()?
+                    .with_timezone(&Utc),
+                stock_level: h.stock_level,
+                sales_count: h.sales_count,
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|s| s.at);
+
+    from_stock(&snapshots).or_else(|| from_sales(&snapshots))
+}
+
+fn from_stock(snapshots: &[Snapshot]) -> Option<DemandEstimate> {
+    let mut units = 0i64;
+    let mut days = 0.0;
+    let mut intervals = 0u32;
+    let mut restocks = 0u32;
+
+    for pair in snapshots.windows(2) {
+        let (Some(before), Some(after)) = (pair[0].stock_level, pair[1].stock_level) else {
+            continue;
+        };
+        let elapsed = days_between(&pair[0], &pair[1]);
+        if elapsed <= 0.0 {
+            continue;
+        }
+
+        // A rising stock is a restock; the sales in that interval are unknown
+        if after > before {
+            restocks += 1;
+            continue;
+        }
+        units += (before - after) as i64;
+        days += elapsed;
+        intervals += 1;
+    }
+
+    if intervals == 0 || days < MIN_WINDOW_DAYS {
+        return None;
+    }
+
+    let restock_share = restocks as f64 / (intervals + restocks) as f64;
+    Some(DemandEstimate {
+        units_per_day: units as f64 / days,
+        confidence: coverage(intervals, days) * (1.0 - restock_share / 2.0),
+        method: "stock".to_string(),
+        samples: intervals + 1,
+        window_days: days,
+    })
+}
+
+fn from_sales(snapshots: &[Snapshot]) -> Option<DemandEstimate> {
+    let (first, last) = (snapshots.first()?, snapshots.last()?);
+    let days = days_between(first, last);
+    // The counter only moves in rounded steps; short windows show nothing
+    if days < 1.0 || last.sales_count < first.sales_count {
+        return None;
+    }
+
+    let intervals = snapshots.len() as u32 - 1;
+    Some(DemandEstimate {
+        units_per_day: (last.sales_count - first.sales_count) as f64 / days,
+        confidence: coverage(intervals, days) * MAX_SALES_CONFIDENCE,
+        method: "sales".to_string(),
+        samples: snapshots.len() as u32,
+        window_days: days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn point(hours: i64, stock_level: Option<i32>, sales_count: i32) -> ProductHistory {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        ProductHistory {
+            id: format!("history-{}", hours),
+            product_id: "product-1".to_string(),
+            price: 49.9,
+            sales_count,
+            stock_level,
+            collected_at: (start + Duration::hours(hours)).to_rfc3339(),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_empty_history() {
+        assert!(estimate_demand(&[]).is_none());
+    }
+
+    #[test]
+    fn test_single_snapshot() {
+        assert!(estimate_demand(&[point(0, Some(100), 1000)]).is_none());
+        assert!(estimate_demand(&[point(0, None, 1000)]).is_none());
+    }
+
+    #[test]
+    fn test_stock_drops() {
+        // Out of order on purpose: snapshots are sorted by time
+        let history = [
+            point(48, Some(50), 1000),
+            point(0, Some(100), 1000),
+            point(24, Some(80), 1000),
+        ];
+        let estimate = estimate_demand(&history).unwrap();
+
+        assert_eq!(estimate.method, "stock");
+        assert_eq!(estimate.samples, 3);
+        assert_close(estimate.window_days, 2.0);
+        assert_close(estimate.units_per_day, 25.0);
+        assert_close(estimate.confidence, (2.0 / 6.0) * (2.0 / 3.0));
+    }
+
+    #[test]
+    fn test_restock_is_skipped() {
+        let history = [
+            point(0, Some(100), 0),
+            point(24, Some(80), 0),
+            point(48, Some(200), 0),
+            point(72, Some(170), 0),
+        ];
+        let estimate = estimate_demand(&history).unwrap();
+
+        assert_eq!(estimate.method, "stock");
+        assert_close(estimate.units_per_day, 25.0);
+        // One interval in three was a restock
+        assert_close(
+            estimate.confidence,
+            (2.0 / 6.0) * (2.0 / 3.0) * (1.0 - (1.0 / 3.0) / 2.0),
+        );
+    }
+
+    #[test]
+    fn test_sales_counter_without_stock() {
+        let history = [point(0, None, 1000), point(72, None, 1300)];
+        let estimate = estimate_demand(&history).unwrap();
+
+        assert_eq!(estimate.method, "sales");
+        assert_eq!(estimate.samples, 2);
+        assert_close(estimate.units_per_day, 100.0);
+        assert_close(estimate.confidence, (1.0 / 6.0) * MAX_SALES_CONFIDENCE);
+
+        // Too short a window, or a counter going down, gives nothing
+        assert!(estimate_demand(&[point(0, None, 1000), point(12, None, 1100)]).is_none());
+        assert!(estimate_demand(&[point(0, None, 1300), point(72, None, 1000)]).is_none());
+    }
+}
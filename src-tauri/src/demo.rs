@@ -0,0 +1,220 @@
+// Demo Catalog
+// Realistic Brazilian TikTok Shop products, used to seed an empty database so
+// the UI can be explored before the first scrape
+
+use rand::Rng;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::database;
+use crate::models::{MarketplaceAccess, Product, ProductSource};
+
+/// `count` products spread evenly over the demo categories
+pub fn generate_products(count: usize) -> Vec<Product> {
+    let mut rng = rand::thread_rng();
+    let mut products = Vec::new();
+
+    let categories = [
+        ("Beleza & Skincare", get_beauty_products()),
+        ("Eletrônicos", get_electronics_products()),
+        ("Casa & Decorações", get_home_products()),
+        ("Moda & Acessórios", get_fashion_products()),
+        ("Saúde & Fitness", get_health_products()),
+    ];
+
+    let image_colors = [
+        "ff69b4", "9370db", "4169e1", "00ced1", "ff6347", "ffa500", "32cd32", "ff1493",
+    ];
+    let now = chrono::Utc::now().to_rfc3339();
+
+    for i in 0..count {
+        let (category, items) = &categories[i % categories.len()];
+        let item = items[rng.gen_range(0..items.len())];
+
+        // Prefixed so demo rows never collide with real TikTok ids
+        let tiktok_id = format!("demo-{}", 2001 + i);
+
+        let base_price: f64 = rng.gen_range(29.90..499.90);
+        let price = (base_price * 10.0_f64).round() / 10.0_f64;
+        let original_price: Option<f64> = if rng.gen_bool(0.3) {
+            Some((price * rng.gen_range(1.2_f64..1.8_f64) * 10.0_f64).round() / 10.0_f64)
+        } else {
+            None
+        };
+
+        let sales_count = rng.gen_range(50..5000);
+        let reviews = rng.gen_range(10..(sales_count / 5).max(11));
+        let rating = rng.gen_range(42..50) as f64 / 10.0;
+
+        let color = image_colors[rng.gen_range(0..image_colors.len())];
+        let image_url = format!(
+            "https://placehold.co/400x400/{}/white?text={}",
+            color,
+            item.replace(' ', "+")
+        );
+
+        products.push(Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: tiktok_id.clone(),
+            title: item.to_string(),
+            description: None,
+            price,
+            original_price,
+            currency: "BRL".to_string(),
+            category: Some(category.to_string()),
+            subcategory: None,
+            seller_name: None,
+            seller_rating: None,
+            product_rating: Some(rating),
+            reviews_count: reviews,
+            sales_count,
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: Some(image_url.clone()),
+            images: vec![image_url],
+            video_url: None,
+            product_url: format!("https://www.tiktok.com/product/{}", tiktok_id),
+            affiliate_url: None,
+            has_free_shipping: rng.gen_bool(0.4),
+            is_trending: rng.gen_bool(0.2),
+            is_on_sale: original_price.is_some(),
+            in_stock: true,
+            stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now.clone(),
+            raw_payload: None,
+            is_demo: true,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Tiktok,
+        });
+    }
+
+    products
+}
+
+/// Save `count` demo products, flagged so `database::clear_demo_products` can
+/// remove them; returns how many were saved
+pub fn seed(db_path: &Path, count: usize) -> usize {
+    generate_products(count)
+        .iter()
+        .filter(|product| match database::save_product(db_path, product) {
+            Ok(_) => true,
+            Err(e) => {
+                log::warn!("Failed to save demo product: {}", e);
+                false
+            }
+        })
+        .count()
+}
+
+fn get_beauty_products() -> Vec<&'static str> {
+    vec![
+        "Kit Maquiagem Profissional 32 Peças com Estojo",
+        "Paleta de Sombras 120 Cores Matte e Glitter",
+        "Base Líquida Alta Cobertura FPS 30",
+        "Máscara de Cílios Volume 10x à Prova D'água",
+        "Batom Líquido Matte 12h Longa Duração",
+        "Escova Alisadora Elétrica Cerâmica Profissional",
+        "Secador de Cabelo Íons Negativos 2000W",
+        "Chapinha Titanium Bivolt Profissional 450F",
+        "Creme Anti-Rugas Vitamina C + Ácido Hialurônico",
+        "Sérum Facial Clareador Manchas 30ml",
+        "Máscara Capilar Hidratação Profunda 1kg",
+        "Kit Pincéis Maquiagem Profissional 12 Peças",
+        "Delineador Líquido à Prova D'água Preto",
+        "Pó Compacto Matte Alta Fixação",
+        "Primer Facial Poros Invisíveis",
+    ]
+}
+
+fn get_electronics_products() -> Vec<&'static str> {
+    vec![
+        "Smartwatch Fitness Tracker Bluetooth 5.0",
+        "Fone Bluetooth sem Fio TWS Cancelamento Ruído",
+        "Carregador Rápido USB-C 65W 3 Portas",
+        "Power Bank 20000mAh Carregamento Rápido",
+        "Caixa de Som Bluetooth Portátil 50W",
+        "Smart TV LED 43' 4K Android Wi-Fi",
+        "Câmera de Segurança Wi-Fi 360° Visão Noturna",
+        "Lâmpada LED Inteligente RGB Alexa/Google",
+        "Tablet 10' 128GB Wi-Fi Android 12",
+        "Teclado Mecânico Gamer RGB Switch Blue",
+        "Mouse Gamer RGB 12000 DPI Programável",
+        "Webcam Full HD 1080p Microfone Embutido",
+        "Ring Light 26cm Tripé 2m Controle Bluetooth",
+        "SSD Externo 1TB USB 3.2 Portátil",
+        "Controle Joystick Sem Fio Bluetooth PC/Mobile",
+    ]
+}
+
+fn get_home_products() -> Vec<&'static str> {
+    vec![
+        "Jogo de Panelas Antiaderente 7 Peças Cerâmica",
+        "Conjunto Facas Cozinha Inox 8 Peças Afiadas",
+        "Liquidificador Turbo 1200W 12 Velocidades",
+        "Air Fryer Digital 5L 1500W Preta",
+        "Cafeteira Elétrica Programável 1.8L",
+        "Jogo de Cama Queen 4 Peças 100% Algodão",
+        "Edredom King Size Dupla Face Microfibra",
+        "Tapete Sala Grande 2x1.5m Antiderrapante",
+        "Aspirador Robô Inteligente Wi-Fi Mapeamento",
+        "Ventilador de Torre Silencioso Controle Remoto",
+        "Purificador de Ar HEPA Ionizador UV",
+        "Umidificador de Ar Ultrassônico LED 7 Cores",
+        "Organizador Multiuso 6 Gavetas Plástico",
+        "Estante Livros 5 Prateleiras MDF Branco",
+        "Quadro Decorativo Canvas 3 Peças Abstrato",
+    ]
+}
+
+fn get_fashion_products() -> Vec<&'static str> {
+    vec![
+        "Tênis Esportivo Feminino Academia Corrida",
+        "Bolsa Feminina Transversal Couro Sintético",
+        "Relógio Digital Esportivo à Prova D'água",
+        "Óculos de Sol Polarizado UV400 Unissex",
+        "Carteira Masculina Couro Legítimo RFID",
+        "Cinto Couro Masculino Fivela Automática",
+        "Mochila Notebook 15.6' Impermeável USB",
+        "Chinelo Slide Confortável Anatômico",
+        "Conjunto Moletom Feminino Inverno Peluciado",
+        "Jaqueta Corta-Vento Masculina Impermeável",
+        "Legging Fitness Cintura Alta Sem Costura",
+        "Camisa Polo Masculina Algodão Básica",
+        "Vestido Feminino Midi Manga Longa Casual",
+        "Short Jeans Feminino Cintura Alta Destroyed",
+        "Bota Coturno Feminino Plataforma Tratorada",
+    ]
+}
+
+fn get_health_products() -> Vec<&'static str> {
+    vec![
+        "Colchonete Yoga EVA 10mm Antiderrapante",
+        "Kit Halteres 2kg + 3kg + 5kg Emborrachado",
+        "Faixa Elástica Exercícios Kit 5 Resistências",
+        "Corda Pular Profissional Rolamento Ajustável",
+        "Garrafa Térmica 1L Inox Mantém 24h Gelado",
+        "Suplemento Whey Protein 900g Chocolate",
+        "Creatina Monohidratada 300g Pura Micronizada",
+        "Termogênico Cafeína 60 Cápsulas Original",
+        "Ômega 3 1000mg 120 Cápsulas Importado",
+        "Vitamina D3 2000UI 60 Cápsulas",
+        "Balança Digital Bioimpedância Bluetooth App",
+        "Massageador Pistola Muscular 6 Velocidades",
+        "Rolo Massagem Miofascial Texturizado 33cm",
+        "Esteira Ergométrica Dobrável 10km/h Display LCD",
+        "Bicicleta Ergométrica Residencial 8kg",
+    ]
+}
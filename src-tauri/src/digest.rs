@@ -0,0 +1,344 @@
+// Weekly Digest
+// Summarizes the last week of tracking into one opportunity report: the best
+// selling products, the biggest price moves, the least saturated niches and
+// the alerts raised. Generated on a schedule and exportable as JSON, markdown
+// or PDF.
+
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::alerts;
+use crate::commands;
+use crate::database;
+use crate::models::{DigestMover, DigestProduct, Product, WeeklyDigest};
+use crate::saturation;
+use crate::scraper::BrowserManager;
+use crate::whatsapp;
+
+const PERIOD_DAYS: i64 = 7;
+
+const TOP_TRENDING: usize = 10;
+const TOP_MOVERS: usize = 10;
+const TOP_NICHES: usize = 5;
+
+/// Rows per section in the chat message version
+const MESSAGE_ROWS: usize = 3;
+
+/// Niches with fewer listings can't be judged open or crowded yet
+const MIN_NICHE_PRODUCTS: i64 = 3;
+
+/// Products read per query while collecting the week's products
+const PAGE_SIZE: i64 = 500;
+
+/// Settings key holding the local date of the last scheduled digest
+const LAST_DIGEST_KEY: &str = "last_weekly_digest";
+
+/// Build the digest of the 7 days before `now`
+pub fn generate(db_path: &Path, now: DateTime<Utc>) -> rusqlite::Result<WeeklyDigest> {
+    let period_start = (now - Duration::days(PERIOD_DAYS)).to_rfc3339();
+
+    let mut products: Vec<Product> = Vec::new();
+    loop {
+        let page = database::get_products_updated_since(
+            db_path,
+            Some(&period_start),
+            PAGE_SIZE,
+            products.len() as i64,
+        )?;
+        let done = (page.len() as i64) < PAGE_SIZE;
+        products.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    let mut trending: Vec<DigestProduct> = products
+        .iter()
+        .filter_map(|p| {
+            let units_per_day = p
+                .demand_estimate
+                .as_ref()
+                .map(|d| d.units_per_day)
+                .unwrap_or(p.sales_7d as f64 / PERIOD_DAYS as f64);
+            (units_per_day > 0.0).then(|| DigestProduct {
+                product_id: p.id.clone(),
+                title: p.title.clone(),
+                price: p.price,
+                category: p.category.clone(),
+                product_url: p.product_url.clone(),
+                units_per_day,
+            })
+        })
+        .collect();
+    trending.sort_by(|a, b| b.units_per_day.total_cmp(&a.units_per_day));
+    trending.truncate(TOP_TRENDING);
+
+    let by_id: HashMap<&str, &Product> = products.iter().map(|p| (p.id.as_str(), p)).collect();
+    let history = database::get_history_since(db_path, &period_start)?;
+    let mut movers: Vec<DigestMover> = history
+        .chunk_by(|a, b| a.product_id == b.product_id)
+        .filter_map(|snapshots| {
+            let (first, last) = (snapshots.first()?, snapshots.last()?);
+            let product = by_id.get(first.product_id.as_str())?;
+            // A missing price (0) is a failed read, not a move
+            if first.price <= 0.0 || last.price <= 0.0 {
+                return None;
+            }
+            let change = (last.price - first.price) / first.price * 100.0;
+            (change.abs() >= alerts::DEFAULT_PRICE_CHANGE_PERCENT).then(|| DigestMover {
+                product_id: product.id.clone(),
+                title: product.title.clone(),
+                price_before: first.price,
+                price_after: last.price,
+                price_change_percent: change,
+                sales_gained: (last.sales_count - first.sales_count).max(0),
+            })
+        })
+        .collect();
+    movers.sort_by(|a, b| {
+        b.price_change_percent
+            .abs()
+            .total_cmp(&a.price_change_percent.abs())
+    });
+    movers.truncate(TOP_MOVERS);
+
+    // Sorted most crowded first
+    let niches = saturation::refresh(db_path)?;
+    let open_niches = niches
+        .into_iter()
+        .rev()
+        .filter(|n| n.products >= MIN_NICHE_PRODUCTS)
+        .take(TOP_NICHES)
+        .collect();
+    let alerts = database::get_product_alerts_since(db_path, &period_start)?;
+
+    Ok(WeeklyDigest {
+        period_start,
+        period_end: now.to_rfc3339(),
+        generated_at: Utc::now().to_rfc3339(),
+        products_tracked: products.len() as i64,
+        trending,
+        movers,
+        open_niches,
+        alerts,
+    })
+}
+
+fn day(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Local).format("%d/%m/%Y").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Sections of the report as (title, rows); shared by the markdown and HTML renderers
+fn sections(digest: &WeeklyDigest) -> Vec<(&'static str, Vec<String>)> {
+    let trending = digest
+        .trending
+        .iter()
+        .map(|p| {
+            format!(
+                "{} — R$ {:.2}, ~{:.1} vendas/dia{}",
+                p.title,
+                p.price,
+                p.units_per_day,
+                p.category
+                    .as_deref()
+                    .map(|c| format!(" ({})", c))
+                    .unwrap_or_default()
+            )
+        })
+        .collect();
+
+    let movers = digest
+        .movers
+        .iter()
+        .map(|m| {
+            format!(
+                "{} — R$ {:.2} → R$ {:.2} ({:+.0}%), +{} vendas",
+                m.title, m.price_before, m.price_after, m.price_change_percent, m.sales_gained
+            )
+        })
+        .collect();
+
+    let niches = digest
+        .open_niches
+        .iter()
+        .map(|n| {
+            format!(
+                "{} — saturação {:.0}/100, {} produtos, {} vendedores, preço médio R$ {:.2}",
+                n.niche, n.score, n.products, n.sellers, n.avg_price
+            )
+        })
+        .collect();
+
+    let alerts = digest
+        .alerts
+        .iter()
+        .map(|a| format!("{}: {}", day(&a.created_at), a.message))
+        .collect();
+
+    vec![
+        ("Produtos em alta", trending),
+        ("Maiores variações de preço", movers),
+        ("Nichos pouco saturados", niches),
+        ("Alertas da semana", alerts),
+    ]
+}
+
+fn period(digest: &WeeklyDigest) -> String {
+    format!(
+        "{} a {} · {} produtos acompanhados",
+        day(&digest.period_start),
+        day(&digest.period_end),
+        digest.products_tracked
+    )
+}
+
+pub fn render_markdown(digest: &WeeklyDigest) -> String {
+    let mut out = format!(
+        "# Resumo semanal de oportunidades\n\n_{}_\n",
+        period(digest)
+    );
+    for (title, rows) in sections(digest) {
+        out.push_str(&format!("\n## {}\n\n", title));
+        if rows.is_empty() {
+            out.push_str("Nada nesta semana.\n");
+        }
+        for (i, row) in rows.iter().enumerate() {
+            out.push_str(&format!("{}. {}\n", i + 1, row));
+        }
+    }
+    out
+}
+
+/// Short plain-text version for chat messages: the first rows of each
+/// section, with WhatsApp's *bold* and _italic_ markup
+pub fn render_message(digest: &WeeklyDigest) -> String {
+    let mut out = format!("*Resumo semanal de oportunidades*\n_{}_\n", period(digest));
+    for (title, rows) in sections(digest) {
+        if rows.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n*{}*\n", title));
+        for (i, row) in rows.iter().take(MESSAGE_ROWS).enumerate() {
+            out.push_str(&format!("{}. {}\n", i + 1, row));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(digest: &WeeklyDigest) -> String {
+    let mut body = format!(
+        "<h1>Resumo semanal de oportunidades</h1><p class=\"period\">{}</p>",
+        escape_html(&period(digest))
+    );
+    for (title, rows) in sections(digest) {
+        body.push_str(&format!("<h2>{}</h2>", title));
+        if rows.is_empty() {
+            body.push_str("<p>Nada nesta semana.</p>");
+            continue;
+        }
+        body.push_str("<ol>");
+        for row in rows {
+            body.push_str(&format!("<li>{}</li>", escape_html(&row)));
+        }
+        body.push_str("</ol>");
+    }
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><style>\
+         body {{ font-family: sans-serif; margin: 32px; color: #222; }}\
+         h2 {{ border-bottom: 1px solid #ddd; padding-bottom: 4px; margin-top: 28px; }}\
+         li {{ margin: 4px 0; }} .period {{ color: #666; }}\
+         </style></head><body>{}</body></html>",
+        body
+    )
+}
+
+/// Print the digest to PDF with a throwaway headless browser
+pub async fn render_pdf(digest: &WeeklyDigest) -> Result<Vec<u8>, String> {
+    let browser = BrowserManager::new(true);
+    browser.start(None).await.map_err(|e| e.to_string())?;
+
+    let result = async {
+        let page = browser.new_page().await.map_err(|e| e.to_string())?;
+        page.set_content(render_html(digest))
+            .await
+            .map_err(|e| e.to_string())?;
+        page.pdf(PrintToPdfParams {
+            print_background: Some(true),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| e.to_string())
+    }
+    .await;
+
+    browser.stop().await.ok();
+    result
+}
+
+/// Write the digest as `digest-<date>.json` and `.md` into `dir`; returns the
+/// markdown path
+pub fn save(dir: &Path, digest: &WeeklyDigest) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let stem = format!("digest-{}", Local::now().format("%Y-%m-%d"));
+
+    let json = serde_json::to_string_pretty(digest).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(format!("{}.json", stem)), json)?;
+
+    let markdown = dir.join(format!("{}.md", stem));
+    std::fs::write(&markdown, render_markdown(digest))?;
+    Ok(markdown)
+}
+
+/// Generate and save the digest when the configured weekday and hour have
+/// come and this week's digest hasn't been made yet. Emits `weekly-digest`.
+pub async fn run_if_due(app: &AppHandle) -> Result<bool, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let config = commands::load_settings(&app_dir).digest;
+
+    let now = Local::now();
+    if !config.enabled
+        || now.weekday().num_days_from_monday() != config.weekday
+        || now.hour() < config.hour
+    {
+        return Ok(false);
+    }
+
+    let today = now.format("%Y-%m-%d").to_string();
+    let last = database::get_setting(&db_path, LAST_DIGEST_KEY)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if last.as_deref() == Some(today.as_str()) {
+        return Ok(false);
+    }
+
+    let digest = generate(&db_path, Utc::now()).map_err(|e| format!("Database error: {}", e))?;
+    let dir = config
+        .export_dir
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| app_dir.join("digests"));
+    let path = save(&dir, &digest).map_err(|e| e.to_string())?;
+
+    database::set_setting(&db_path, LAST_DIGEST_KEY, &today)
+        .map_err(|e| format!("Database error: {}", e))?;
+    log::info!("Weekly digest saved to {}", path.display());
+    let _ = app.emit("weekly-digest", &digest);
+    if let Err(e) = whatsapp::notify_digest(app, &digest).await {
+        log::warn!("Weekly digest not sent on WhatsApp: {}", e);
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,205 @@
+// Disk Space
+// Free space on the disk holding the app data is checked before each scrape
+// and every minute while one runs. Below the configured minimum, a cleanup
+// removes what the app can do without: the browser's image and code caches,
+// and error pages, run logs and log files older than a week.
+
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use sysinfo::Disks;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+
+use crate::commands;
+use crate::config::AppSettings;
+use crate::database;
+use crate::models::{DiskCleanupReport, DiskStatus, ScraperStatus};
+
+/// How often a running scrape checks the free space
+pub const MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Error pages, run logs and log files younger than this survive a cleanup
+const CLEANUP_KEEP_DAYS: i64 = 7;
+
+/// Cache directories of the browser profile; cookies and sessions are kept
+const BROWSER_CACHE_DIRS: &[&str] = &[
+    "Cache",
+    "Code Cache",
+    "GPUCache",
+    "GrShaderCache",
+    "ShaderCache",
+    "cache2",
+];
+
+/// Free space on the disk `path` is on: the disk with the longest mount
+/// point containing it
+pub fn available_space(path: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+pub fn status(app_dir: &Path, settings: &AppSettings) -> DiskStatus {
+    let available_bytes = available_space(app_dir);
+    let min_free_bytes = settings.system.min_free_space_mb * 1024 * 1024;
+    DiskStatus {
+        available_bytes,
+        min_free_bytes,
+        low: available_bytes.is_some_and(|available| available < min_free_bytes),
+    }
+}
+
+pub fn low_space_message(status: &DiskStatus) -> String {
+    format!(
+        "Espaço em disco insuficiente (< {} MB). Libere espaço para continuar.",
+        status.min_free_bytes / (1024 * 1024)
+    )
+}
+
+/// Size of a directory tree in bytes
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Remove the cache directories anywhere under the browser profile; returns
+/// the bytes they held
+fn clear_browser_caches(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    let mut cleared = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let is_cache = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| BROWSER_CACHE_DIRS.contains(&name));
+        if is_cache {
+            let size = dir_size(&path);
+            if fs::remove_dir_all(&path).is_ok() {
+                cleared += size;
+            }
+        } else {
+            cleared += clear_browser_caches(&path);
+        }
+    }
+    cleared
+}
+
+/// Remove log files last written before `cutoff`
+fn clear_old_log_files(log_dir: &Path, cutoff: SystemTime) -> usize {
+    let Ok(entries) = fs::read_dir(log_dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| modified < cutoff)
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+/// Free space by removing caches and old logs
+pub fn cleanup(app_dir: &Path, log_dir: Option<&Path>) -> DiskCleanupReport {
+    let before = available_space(app_dir).unwrap_or(0);
+    let db_path = app_dir.join("tiktrend.db");
+    let cutoff = Utc::now() - chrono::Duration::days(CLEANUP_KEEP_DAYS);
+
+    let mut report = DiskCleanupReport {
+        cache_bytes: clear_browser_caches(&app_dir.join("browser_data")),
+        ..Default::default()
+    };
+    match database::prune_error_pages(&db_path, CLEANUP_KEEP_DAYS) {
+        Ok(removed) => report.error_pages = removed,
+        Err(e) => log::warn!("Failed to prune error pages: {}", e),
+    }
+    match database::prune_run_logs(&db_path, &cutoff.to_rfc3339()) {
+        Ok(removed) => report.run_logs = removed,
+        Err(e) => log::warn!("Failed to prune run logs: {}", e),
+    }
+    if report.error_pages + report.run_logs > 0 {
+        if let Err(e) = database::vacuum(&db_path) {
+            log::warn!("Failed to vacuum the database: {}", e);
+        }
+    }
+    if let Some(log_dir) = log_dir {
+        report.log_files = clear_old_log_files(log_dir, SystemTime::from(cutoff));
+    }
+
+    report.freed_bytes = available_space(app_dir).unwrap_or(0).saturating_sub(before);
+    log::info!("Disk cleanup: {:?}", report);
+    report
+}
+
+/// Fail when free space is below the minimum, after a cleanup when
+/// automatic cleanup is on
+pub fn check(app_dir: &Path, log_dir: Option<&Path>) -> Result<(), String> {
+    let settings = commands::load_settings(app_dir);
+    let mut current = status(app_dir, &settings);
+    if current.low && settings.system.auto_cleanup {
+        cleanup(app_dir, log_dir);
+        current = status(app_dir, &settings);
+    }
+
+    if current.low {
+        Err(low_space_message(&current))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check the free space every `MONITOR_INTERVAL` while the current scraper
+/// run goes on and stop it, with `disk://low`, when space runs out
+pub fn spawn_monitor(app: AppHandle, status: Arc<Mutex<ScraperStatus>>) {
+    tauri::async_runtime::spawn(async move {
+        let Ok(app_dir) = app.path().app_data_dir() else {
+            return;
+        };
+        let log_dir = app.path().app_log_dir().ok();
+        let run_id = status.lock().await.run_id.clone();
+
+        loop {
+            tokio::time::sleep(MONITOR_INTERVAL).await;
+            {
+                let status = status.lock().await;
+                if !status.is_running || status.run_id != run_id {
+                    break;
+                }
+            }
+
+            if let Err(e) = check(&app_dir, log_dir.as_deref()) {
+                log::warn!("Stopping the scraper: {}", e);
+                let disk = self::status(&app_dir, &commands::load_settings(&app_dir));
+                let _ = app.emit("disk://low", &disk);
+
+                let mut status = status.lock().await;
+                status.is_running = false;
+                status.errors.push(e);
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,194 @@
+// Entitlements
+// One capability set merged from the subscription (paid, in its grace
+// period, or a trial) and the legacy license. Gated commands ask the
+// service in Tauri state rather than reading either source, and the UI gets
+// the same set from `get_entitlements`. The set is kept for a minute and
+// recomputed sooner when the subscription, license or settings change.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+use crate::commands::{self, AccessState};
+use crate::license;
+use crate::models::{ExecutionMode, License, Subscription, SubscriptionStatus};
+use crate::trial;
+
+/// How long a computed set is reused; access states change with time
+const MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Where the plan comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum EntitlementSource {
+    Subscription,
+    Trial,
+    Free,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct Entitlements {
+    pub source: EntitlementSource,
+    /// A license confirmed by the backend adds its features to the plan
+    pub license_active: bool,
+    pub access_state: AccessState,
+    /// The plan with the license merged in: its limits (-1 = unlimited),
+    /// features, marketplaces and execution mode are what the app enforces
+    pub subscription: Subscription,
+    /// Every capability granted, including license-only ones such as
+    /// "export" and "scheduler"
+    pub features: Vec<String>,
+    pub credits: i32,
+    pub computed_at: String,
+}
+
+impl Entitlements {
+    /// Whether a capability or metered feature is available
+    pub fn has(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+            || commands::get_feature_limit(&self.subscription, feature).is_some_and(|l| l != 0)
+    }
+
+    pub fn limit(&self, feature: &str) -> Option<i32> {
+        commands::get_feature_limit(&self.subscription, feature)
+    }
+
+    pub fn execution_mode(&self) -> &ExecutionMode {
+        &self.subscription.execution_mode
+    }
+}
+
+/// Capabilities of the subscription's feature toggles
+fn subscription_features(subscription: &Subscription) -> Vec<String> {
+    let f = &subscription.features;
+    [
+        ("chatbot_ai", f.chatbot_ai),
+        ("analytics_advanced", f.analytics_advanced),
+        ("analytics_export", f.analytics_export),
+        ("crm_automation", f.crm_automation),
+        ("api_access", f.api_access),
+        ("offline_mode", f.offline_mode),
+        ("hybrid_sync", f.hybrid_sync),
+        ("priority_support", f.priority_support),
+    ]
+    .into_iter()
+    .filter(|(_, on)| *on)
+    .map(|(name, _)| name.to_string())
+    .chain(std::iter::once("analytics_basic".to_string()))
+    .collect()
+}
+
+/// Add what a license grants: unlimited searches and favorites, exports,
+/// the scheduler and local scraping
+fn merge_license(entitlements: &mut Entitlements, license: &License) {
+    let subscription = &mut entitlements.subscription;
+    if license.features.searches_unlimited {
+        subscription.limits.price_searches = -1;
+    }
+    if license.features.favorites_unlimited {
+        subscription.limits.favorites = -1;
+    }
+    if license.features.export_enabled {
+        subscription.features.analytics_export = true;
+        entitlements.features.push("export".to_string());
+    }
+    if license.features.scheduler_enabled {
+        entitlements.features.push("scheduler".to_string());
+    }
+    // Licenses predate the web app: they always cover the desktop scraper
+    if subscription.execution_mode == ExecutionMode::WebOnly {
+        subscription.execution_mode = ExecutionMode::LocalFirst;
+    }
+
+    entitlements.license_active = true;
+    entitlements.credits = entitlements.credits.max(license.credits);
+}
+
+/// Compute the entitlements from the cached subscription, the trial and the
+/// cached license
+pub fn resolve(app_dir: &Path, now: DateTime<Utc>) -> Entitlements {
+    let db_path = app_dir.join("tiktrend.db");
+    let (subscription, source) = match commands::load_cached_subscription(app_dir) {
+        Some(cached) if cached.subscription.status == SubscriptionStatus::Trialing => {
+            (cached.subscription, EntitlementSource::Trial)
+        }
+        Some(cached) => (cached.subscription, EntitlementSource::Subscription),
+        None => match trial::active_subscription(&db_path, now) {
+            Some(subscription) => (subscription, EntitlementSource::Trial),
+            None => (
+                commands::create_free_subscription(),
+                EntitlementSource::Free,
+            ),
+        },
+    };
+    let access_state = match source {
+        EntitlementSource::Free => AccessState::Free,
+        _ => commands::subscription_access(app_dir).state,
+    };
+
+    let mut entitlements = Entitlements {
+        source,
+        license_active: false,
+        access_state,
+        features: subscription_features(&subscription),
+        subscription,
+        credits: 0,
+        computed_at: now.to_rfc3339(),
+    };
+
+    let settings = commands::load_settings(app_dir);
+    if let Some(cached) = settings
+        .license
+        .key
+        .as_deref()
+        .and_then(|key| license::load(app_dir, key, now))
+    {
+        merge_license(&mut entitlements, &cached.license);
+    }
+    entitlements.features.sort();
+    entitlements.features.dedup();
+    entitlements
+}
+
+/// Entitlements shared by every command, recomputed when stale
+pub struct EntitlementService {
+    current: Mutex<Option<(Instant, Entitlements)>>,
+}
+
+impl EntitlementService {
+    pub fn new() -> Self {
+        Self {
+            current: Mutex::new(None),
+        }
+    }
+
+    pub fn get(&self, app_dir: &Path) -> Entitlements {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((at, entitlements)) = current.as_ref() {
+            if at.elapsed() < MAX_AGE {
+                return entitlements.clone();
+            }
+        }
+
+        let entitlements = resolve(app_dir, Utc::now());
+        *current = Some((Instant::now(), entitlements.clone()));
+        entitlements
+    }
+
+    /// Drop the computed set after the subscription, license or settings changed
+    pub fn invalidate(&self) {
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Default for EntitlementService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
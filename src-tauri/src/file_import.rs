@@ -0,0 +1,256 @@
+// File Import
+// Reads products from files dropped on the window or picked in the app: the
+// CSV and JSON exports, and product or listing pages saved from TikTok Shop
+// as HTML. A file is parsed into a preview first; nothing is saved until the
+// import is confirmed.
+
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands;
+use crate::database;
+use crate::models::{ImportPreview, Product, ProductFields, ProductSource};
+use crate::scraper::TikTokParser;
+
+/// Tag given to products imported from a file
+pub const IMPORT_TAG: &str = "file import";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+    Html,
+}
+
+impl ImportFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "json" => Some(ImportFormat::Json),
+            "html" | "htm" => Some(ImportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "csv",
+            ImportFormat::Json => "json",
+            ImportFormat::Html => "html",
+        }
+    }
+}
+
+/// Fields of a CSV line split on `delimiter`; quoted fields may hold the
+/// delimiter and doubled quotes
+fn csv_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// A number written with either decimal separator: "89.90" or "89,90"
+fn csv_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let normalized = if text.contains(',') && !text.contains('.') {
+        text.replace(',', ".")
+    } else {
+        text.replace(',', "")
+    };
+    normalized.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+/// Products of a CSV with a header row, such as the app's own export. Rows
+/// are matched to stored products by id or TikTok id and update them.
+fn read_csv(db_path: &Path, text: &str) -> Result<(Vec<Product>, Vec<String>), String> {
+    let mut lines = text
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    // Spreadsheets set to Portuguese save with semicolons
+    let delimiter = if header.contains(';') && !header.contains(',') {
+        ';'
+    } else {
+        ','
+    };
+    let columns: Vec<String> = csv_fields(header, delimiter)
+        .iter()
+        .map(|c| c.trim().to_lowercase())
+        .collect();
+    let column = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+
+    let id_col = column(&["id"]);
+    let tiktok_id_col = column(&["tiktok_id", "tiktokid"]);
+    let title_col = column(&["title", "name", "titulo", "título"]);
+    let description_col = column(&["description", "descricao", "descrição"]);
+    let price_col = column(&["price", "preco", "preço"]);
+    let original_price_col = column(&["original_price", "originalprice"]);
+    let currency_col = column(&["currency", "moeda"]);
+    let category_col = column(&["category", "categoria"]);
+    let seller_col = column(&["seller_name", "seller", "vendedor"]);
+    let rating_col = column(&["rating", "product_rating"]);
+    let sales_col = column(&["sales_count", "sales", "vendas"]);
+    let shipping_col = column(&["shipping_fee", "frete"]);
+    let image_col = column(&["image_url", "image", "imagem"]);
+    let url_col = column(&["product_url", "url", "link"]);
+    if title_col.is_none() || url_col.is_none() {
+        return Err("The CSV needs at least a title and a product_url column".to_string());
+    }
+
+    let mut products = Vec::new();
+    let mut skipped = Vec::new();
+    for (index, line) in lines {
+        let row = csv_fields(line, delimiter);
+        let cell = |col: Option<usize>| {
+            col.and_then(|c| row.get(c))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let number = |col: Option<usize>| cell(col).and_then(|v| csv_number(&v));
+
+        let url = cell(url_col).unwrap_or_default();
+        let stored = match cell(id_col) {
+            Some(id) => database::get_product_by_id(db_path, &id)
+                .map_err(|e| format!("Database error: {}", e))?,
+            None => None,
+        };
+        let tiktok_id = cell(tiktok_id_col).or_else(|| TikTokParser::extract_id_from_url(&url));
+        let stored = match (stored, &tiktok_id) {
+            (None, Some(tiktok_id)) => database::get_product_by_tiktok_id(db_path, tiktok_id)
+                .map_err(|e| format!("Database error: {}", e))?,
+            (stored, _) => stored,
+        };
+        let mut product = match (stored, tiktok_id) {
+            (Some(stored), _) => stored,
+            (None, Some(tiktok_id)) => commands::new_product(tiktok_id, ProductSource::Manual),
+            (None, None) => {
+                skipped.push(format!("Line {}: no TikTok product id", index + 1));
+                continue;
+            }
+        };
+
+        let fields = ProductFields {
+            title: cell(title_col),
+            description: cell(description_col),
+            price: number(price_col),
+            // Exports write 0 for "none"
+            original_price: number(original_price_col).filter(|p| *p > 0.0),
+            currency: cell(currency_col),
+            category: cell(category_col),
+            seller_name: cell(seller_col),
+            product_rating: number(rating_col).filter(|r| *r > 0.0),
+            sales_count: number(sales_col).map(|s| s as i32),
+            shipping_fee: number(shipping_col),
+            image_url: cell(image_col),
+            product_url: Some(url),
+        };
+        match commands::apply_product_fields(&mut product, fields) {
+            Ok(()) => products.push(product),
+            Err(e) => skipped.push(format!("Line {}: {}", index + 1, e)),
+        }
+    }
+    Ok((products, skipped))
+}
+
+/// Products of a JSON export, or of product data saved from the shop
+fn read_json(text: &str) -> Result<(Vec<Product>, Vec<String>), String> {
+    let value: Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let items = match value {
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+
+    let parser = TikTokParser::default();
+    let mut products = Vec::new();
+    let mut skipped = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let product = match serde_json::from_value::<Product>(item.clone()) {
+            Ok(product) => Ok(product),
+            Err(_) => parser.parse_product_json(&item).map_err(|e| e.to_string()),
+        };
+        match product {
+            Ok(product) => products.push(product),
+            Err(e) => skipped.push(format!("Item {}: {}", index + 1, e)),
+        }
+    }
+    Ok((products, skipped))
+}
+
+/// Read `path` into a preview of what importing it would save
+pub fn preview(db_path: &Path, path: &Path) -> Result<ImportPreview, String> {
+    let format = ImportFormat::from_path(path)
+        .ok_or_else(|| format!("Unsupported file: {}", path.display()))?;
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let (mut products, skipped) = match format {
+        ImportFormat::Csv => read_csv(db_path, &text)?,
+        ImportFormat::Json => read_json(&text)?,
+        ImportFormat::Html => (
+            TikTokParser::default().parse_html(&text).products,
+            Vec::new(),
+        ),
+    };
+
+    let mut new_count = 0;
+    for product in &mut products {
+        match database::get_product_by_tiktok_id(db_path, &product.tiktok_id)
+            .map_err(|e| format!("Database error: {}", e))?
+        {
+            Some(stored) => product.id = stored.id,
+            None => new_count += 1,
+        }
+    }
+
+    Ok(ImportPreview {
+        path: path.to_string_lossy().to_string(),
+        format: format.as_str().to_string(),
+        products,
+        new_count,
+        skipped,
+    })
+}
+
+/// Preview each file dropped on the window as `import://preview`, for the app
+/// to ask for confirmation; files that can't be read are reported as
+/// `import://failed`
+pub fn handle_drop(app: &AppHandle, paths: &[PathBuf]) {
+    let Ok(app_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let db_path = app_dir.join("tiktrend.db");
+
+    for path in paths {
+        match preview(&db_path, path) {
+            Ok(preview) => {
+                let _ = app.emit("import://preview", &preview);
+            }
+            Err(e) => {
+                log::warn!("Dropped file not imported: {}: {}", path.display(), e);
+                let _ = app.emit(
+                    "import://failed",
+                    json!({ "path": path.to_string_lossy(), "error": e }),
+                );
+            }
+        }
+    }
+}
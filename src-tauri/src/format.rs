@@ -0,0 +1,226 @@
+// Number Formatting
+// Prices and counts rendered the way the UI shows them, in the app language
+// and display currency, for exports, copies and notifications. The
+// separators follow the language, the symbol the currency: "R$ 1.234,56" in
+// Portuguese, "R$1,234.56" in English.
+
+use crate::config::AppSettings;
+use crate::scraper::{Language, NumberLocale};
+
+pub struct NumberFormat {
+    language: Language,
+    currency: String,
+}
+
+impl NumberFormat {
+    pub fn new(language: Language, currency: &str) -> Self {
+        Self {
+            language,
+            currency: currency.trim().to_uppercase(),
+        }
+    }
+
+    /// App language and display currency of the settings
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self::new(
+            Language::from_tag(&settings.language),
+            &settings.display_currency,
+        )
+    }
+
+    fn locale(&self) -> NumberLocale {
+        match self.language {
+            Language::PtBr => NumberLocale::Br,
+            Language::En => NumberLocale::Us,
+            Language::Es => NumberLocale::Eu,
+        }
+    }
+
+    /// `value` with `decimals` digits and the language's separators
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let (decimal_sep, thousands_sep) = self.locale().separators();
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = formatted
+            .split_once('.')
+            .unwrap_or((formatted.as_str(), ""));
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(thousands_sep);
+            }
+            grouped.push(digit);
+        }
+        if !fraction.is_empty() {
+            grouped.push(decimal_sep);
+            grouped.push_str(fraction);
+        }
+        // No "-0,00" for values that round to zero
+        if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            grouped.insert(0, '-');
+        }
+        grouped
+    }
+
+    /// A price in the display currency
+    pub fn price(&self, value: f64) -> String {
+        self.price_in(value, &self.currency)
+    }
+
+    /// A price in `currency` (ISO 4217)
+    pub fn price_in(&self, value: f64, currency: &str) -> String {
+        let currency = currency.trim().to_uppercase();
+        let symbol = match currency.as_str() {
+            "BRL" => "R$",
+            "USD" => "US$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "MXN" => "MX$",
+            _ => currency.as_str(),
+        };
+        let amount = self.number(value, 2);
+        match self.language {
+            Language::En if symbol.chars().count() <= 3 => format!("{}{}", symbol, amount),
+            Language::Es if currency == "EUR" => format!("{} {}", amount, symbol),
+            _ => format!("{} {}", symbol, amount),
+        }
+    }
+
+    /// Short form of a count: "1,2 mil", "3,4 mi" in Portuguese, "1.2K",
+    /// "3.4M" in English
+    pub fn compact(&self, value: f64) -> String {
+        let units: [&str; 3] = match self.language {
+            Language::PtBr => [" mil", " mi", " bi"],
+            Language::En => ["K", "M", "B"],
+            Language::Es => [" mil", " M", " mil M"],
+        };
+        let abs = value.abs();
+        let (scaled, unit) = if abs >= 1e9 {
+            (value / 1e9, units[2])
+        } else if abs >= 1e6 {
+            (value / 1e6, units[1])
+        } else if abs >= 1e3 {
+            (value / 1e3, units[0])
+        } else {
+            return self.number(value, 0);
+        };
+
+        // One decimal below 100 ("1,2 mil"), none above ("120 mil")
+        let decimals = if scaled.abs() < 100.0 { 1 } else { 0 };
+        let number = self.number(scaled, decimals);
+        let (decimal_sep, _) = self.locale().separators();
+        let zero_fraction = format!("{}0", decimal_sep);
+        let number = number
+            .strip_suffix(&zero_fraction)
+            .map(str::to_string)
+            .unwrap_or(number);
+        format!("{}{}", number, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let cases = [
+            (Language::PtBr, 1234567.891, 2, "1.234.567,89"),
+            (Language::PtBr, -1234.5, 1, "-1.234,5"),
+            (Language::PtBr, 1000.0, 0, "1.000"),
+            (Language::PtBr, -0.001, 2, "0,00"),
+            (Language::En, 1234567.891, 2, "1,234,567.89"),
+            (Language::En, -1234.5, 1, "-1,234.5"),
+            (Language::En, 999.0, 0, "999"),
+            (Language::En, 0.0, 2, "0.00"),
+            (Language::Es, 1234567.891, 2, "1.234.567,89"),
+            (Language::Es, -1234.5, 1, "-1.234,5"),
+            (Language::Es, 100000.0, 0, "100.000"),
+        ];
+
+        for (language, value, decimals, expected) in cases {
+            assert_eq!(
+                NumberFormat::new(language, "BRL").number(value, decimals),
+                expected,
+                "{:?} number({}, {})",
+                language,
+                value,
+                decimals
+            );
+        }
+    }
+
+    #[test]
+    fn test_price_in() {
+        let cases = [
+            // Symbol before the amount, spaced
+            (Language::PtBr, "BRL", "R$ 1.234,50"),
+            (Language::PtBr, "USD", "US$ 1.234,50"),
+            (Language::PtBr, "EUR", "€ 1.234,50"),
+            (Language::PtBr, "JPY", "JPY 1.234,50"),
+            // Short symbols stick to the amount in English
+            (Language::En, "BRL", "R$1,234.50"),
+            (Language::En, "usd", "US$1,234.50"),
+            (Language::En, "EUR", "€1,234.50"),
+            (Language::En, "MXN", "MX$1,234.50"),
+            // Euros go after the amount in Spanish
+            (Language::Es, "EUR", "1.234,50 €"),
+            (Language::Es, "BRL", "R$ 1.234,50"),
+            (Language::Es, "GBP", "£ 1.234,50"),
+        ];
+
+        for (language, currency, expected) in cases {
+            assert_eq!(
+                NumberFormat::new(language, "BRL").price_in(1234.5, currency),
+                expected,
+                "{:?} price_in({:?})",
+                language,
+                currency
+            );
+        }
+    }
+
+    #[test]
+    fn test_price_uses_display_currency() {
+        assert_eq!(
+            NumberFormat::new(Language::En, " usd ").price(5.0),
+            "US$5.00"
+        );
+        assert_eq!(
+            NumberFormat::new(Language::PtBr, "BRL").price(5.0),
+            "R$ 5,00"
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        let cases = [
+            (Language::PtBr, 999.0, "999"),
+            (Language::PtBr, 1000.0, "1 mil"),
+            (Language::PtBr, 1200.0, "1,2 mil"),
+            (Language::PtBr, -1500.0, "-1,5 mil"),
+            (Language::PtBr, 120000.0, "120 mil"),
+            (Language::PtBr, 3400000.0, "3,4 mi"),
+            (Language::PtBr, 2e9, "2 bi"),
+            (Language::En, 999.0, "999"),
+            (Language::En, 1000.0, "1K"),
+            (Language::En, 1200.0, "1.2K"),
+            (Language::En, 120000.0, "120K"),
+            (Language::En, 3400000.0, "3.4M"),
+            (Language::En, 2e9, "2B"),
+            (Language::Es, 1200.0, "1,2 mil"),
+            (Language::Es, 3400000.0, "3,4 M"),
+            (Language::Es, 2e9, "2 mil M"),
+        ];
+
+        for (language, value, expected) in cases {
+            assert_eq!(
+                NumberFormat::new(language, "BRL").compact(value),
+                expected,
+                "{:?} compact({})",
+                language,
+                value
+            );
+        }
+    }
+}
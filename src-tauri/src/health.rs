@@ -0,0 +1,202 @@
+// App Health
+// One status per subsystem the app depends on, for the health banner: the
+// database, disk space, the browser, the backend, the subscription, the
+// proxies and the sync backlog
+
+use chromiumoxide::detection::{default_executable, DetectionOptions};
+use chrono::Utc;
+use futures::future::join_all;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::backend;
+use crate::commands::{self, AccessState};
+use crate::config::AppSettings;
+use crate::database;
+use crate::disk;
+use crate::models::{AppHealth, HealthCheck, HealthLevel};
+
+/// Time allowed to each network probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Uploads waiting beyond this many are worth a warning
+const SYNC_BACKLOG_WARNING: i64 = 1000;
+
+fn check(name: &str, level: HealthLevel, message: impl Into<String>) -> HealthCheck {
+    HealthCheck {
+        name: name.to_string(),
+        level,
+        message: message.into(),
+    }
+}
+
+fn database_check(db_path: &Path) -> HealthCheck {
+    match database::ping(db_path) {
+        Ok(()) => check("database", HealthLevel::Ok, "Banco de dados acessível"),
+        Err(e) => check(
+            "database",
+            HealthLevel::Error,
+            format!("Banco de dados inacessível: {}", e),
+        ),
+    }
+}
+
+fn disk_check(app_dir: &Path, settings: &AppSettings) -> HealthCheck {
+    let status = disk::status(app_dir, settings);
+    if status.low {
+        check(
+            "disk_space",
+            HealthLevel::Error,
+            disk::low_space_message(&status),
+        )
+    } else {
+        check("disk_space", HealthLevel::Ok, "Espaço em disco suficiente")
+    }
+}
+
+/// Where the scraper's browser comes from: the attached browser or the local
+/// Chrome/Chromium install
+pub fn find_browser(settings: &AppSettings) -> Result<String, String> {
+    if let Some(url) = settings
+        .scraper
+        .remote_debugging_url
+        .as_ref()
+        .filter(|url| !url.trim().is_empty())
+    {
+        return Ok(format!("Usando o navegador conectado em {}", url));
+    }
+    default_executable(DetectionOptions::default())
+        .map(|path| format!("Navegador encontrado em {}", path.display()))
+        .map_err(|_| {
+            "Nenhum Chrome ou Chromium encontrado. Instale um para usar o scraper.".to_string()
+        })
+}
+
+fn browser_check(settings: &AppSettings) -> HealthCheck {
+    match find_browser(settings) {
+        Ok(message) => check("browser", HealthLevel::Ok, message),
+        Err(message) => check("browser", HealthLevel::Error, message),
+    }
+}
+
+async fn backend_check() -> HealthCheck {
+    // Goes through while calls are paused, so the breaker closes as soon as
+    // the backend is back. The app works offline, so an unreachable backend
+    // is only a warning.
+    match backend::probe(backend::get("/health").timeout(PROBE_TIMEOUT)).await {
+        Ok(res) if res.status().is_success() => {
+            check("backend", HealthLevel::Ok, "Servidor acessível")
+        }
+        Ok(res) => check(
+            "backend",
+            HealthLevel::Warning,
+            format!("Servidor respondeu {}", res.status()),
+        ),
+        Err(_) => check(
+            "backend",
+            HealthLevel::Warning,
+            "Servidor inacessível. Trabalhando offline.",
+        ),
+    }
+}
+
+fn subscription_check(app_dir: &Path) -> HealthCheck {
+    let access = commands::subscription_access(app_dir);
+    match access.state {
+        AccessState::Active => check(
+            "subscription",
+            HealthLevel::Ok,
+            format!("Plano {:?} ativo", access.plan_tier),
+        ),
+        AccessState::Grace => check(
+            "subscription",
+            HealthLevel::Warning,
+            format!(
+                "Pagamento pendente. Recursos disponíveis por mais {} dias.",
+                access.grace_days_remaining
+            ),
+        ),
+        AccessState::Free => check("subscription", HealthLevel::Ok, "Plano gratuito"),
+    }
+}
+
+async fn proxy_works(proxy: &str) -> bool {
+    let Ok(proxy) = reqwest::Proxy::all(proxy) else {
+        return false;
+    };
+    let Ok(client) = reqwest::Client::builder()
+        .proxy(proxy)
+        .timeout(PROBE_TIMEOUT)
+        .build()
+    else {
+        return false;
+    };
+    client
+        .get("https://api.ipify.org?format=json")
+        .send()
+        .await
+        .is_ok_and(|res| res.status().is_success())
+}
+
+async fn proxies_check(settings: &AppSettings) -> HealthCheck {
+    if !settings.proxy_enabled || settings.proxy_list.is_empty() {
+        return check("proxies", HealthLevel::Ok, "Proxies desativados");
+    }
+
+    let results = join_all(settings.proxy_list.iter().map(|p| proxy_works(p))).await;
+    let working = results.iter().filter(|ok| **ok).count();
+    let total = results.len();
+    let level = match working {
+        0 => HealthLevel::Error,
+        n if n < total => HealthLevel::Warning,
+        _ => HealthLevel::Ok,
+    };
+    check(
+        "proxies",
+        level,
+        format!("{} de {} proxies funcionando", working, total),
+    )
+}
+
+fn sync_backlog_check(db_path: &Path) -> HealthCheck {
+    match database::count_pending_sync(db_path, None) {
+        Ok(0) => check("sync_backlog", HealthLevel::Ok, "Nada aguardando envio"),
+        Ok(count) => check(
+            "sync_backlog",
+            if count > SYNC_BACKLOG_WARNING {
+                HealthLevel::Warning
+            } else {
+                HealthLevel::Ok
+            },
+            format!("{} itens aguardando envio", count),
+        ),
+        Err(e) => check("sync_backlog", HealthLevel::Error, e.to_string()),
+    }
+}
+
+/// Check every subsystem; the network probes run side by side
+pub async fn check_all(app_dir: &Path) -> AppHealth {
+    let db_path = app_dir.join("tiktrend.db");
+    let settings = commands::load_settings(app_dir);
+
+    let (backend, proxies) = futures::join!(backend_check(), proxies_check(&settings));
+    let checks = vec![
+        database_check(&db_path),
+        disk_check(app_dir, &settings),
+        browser_check(&settings),
+        backend,
+        subscription_check(app_dir),
+        proxies,
+        sync_backlog_check(&db_path),
+    ];
+
+    AppHealth {
+        level: checks
+            .iter()
+            .map(|c| c.level)
+            .max()
+            .unwrap_or(HealthLevel::Ok),
+        checks,
+        checked_at: Utc::now().to_rfc3339(),
+    }
+}
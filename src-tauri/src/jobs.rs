@@ -0,0 +1,347 @@
+// Job Queue Module
+// Serializes browser-bound jobs so only one of them drives the browser profile at a time.
+// A running job is stopped on cancel by running its work through `until_cancelled`.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use tokio::sync::{Mutex, Notify};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// Finished jobs kept around for `get_job_queue`
+const MAX_FINISHED_JOBS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum JobKind {
+    Scrape,
+    Commissions,
+    StealthCheck,
+    Watch,
+    Competitor,
+    Refresh,
+    Import,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    pub label: String,
+    pub status: JobStatus,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub error: Option<String>,
+}
+
+impl JobInfo {
+    /// Running jobs, and cancelled ones still winding down, occupy the browser
+    fn holds_slot(&self) -> bool {
+        match self.status {
+            JobStatus::Running => true,
+            JobStatus::Cancelled => self.started_at.is_some() && self.finished_at.is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// FIFO queue with a single running slot
+pub struct JobQueue {
+    jobs: Mutex<Vec<JobInfo>>,
+    notify: Notify,
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Add a job to the end of the queue and return its id
+    pub async fn enqueue(&self, kind: JobKind, label: String) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.jobs.lock().await.push(JobInfo {
+            id: id.clone(),
+            kind,
+            label,
+            status: JobStatus::Queued,
+            created_at: Utc::now().to_rfc3339(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        });
+        id
+    }
+
+    /// Wait until the job is first in line and the running slot is free.
+    /// Fails if the job gets cancelled while waiting.
+    pub async fn wait_for_turn(&self, id: &str) -> Result<(), String> {
+        loop {
+            // Register interest before checking so a finish in between is not missed
+            let notified = self.notify.notified();
+
+            {
+                let mut jobs = self.jobs.lock().await;
+                let status = jobs
+                    .iter()
+                    .find(|j| j.id == id)
+                    .map(|j| j.status)
+                    .ok_or("Job not found")?;
+
+                if status == JobStatus::Cancelled {
+                    return Err("Job cancelled".to_string());
+                }
+
+                let slot_busy = jobs.iter().any(|j| j.holds_slot());
+                let next_queued = jobs.iter().find(|j| j.status == JobStatus::Queued);
+
+                if !slot_busy && next_queued.map(|j| j.id.as_str()) == Some(id) {
+                    if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+                        job.status = JobStatus::Running;
+                        job.started_at = Some(Utc::now().to_rfc3339());
+                    }
+                    return Ok(());
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Mark a job as finished and wake up queued jobs.
+    /// A job cancelled while running keeps its cancelled status.
+    pub async fn finish(&self, id: &str, result: Result<(), String>) {
+        let mut jobs = self.jobs.lock().await;
+        if let Some(job) = jobs.iter_mut().find(|j| j.id == id) {
+            if job.status != JobStatus::Cancelled {
+                match result {
+                    Ok(()) => job.status = JobStatus::Completed,
+                    Err(e) => {
+                        job.status = JobStatus::Failed;
+                        job.error = Some(e);
+                    }
+                }
+            }
+            job.finished_at = Some(Utc::now().to_rfc3339());
+        }
+
+        Self::prune(&mut jobs);
+        drop(jobs);
+        self.notify.notify_waiters();
+    }
+
+    /// Cancel a queued or running job, returning the status it had
+    pub async fn cancel(&self, id: &str) -> Option<JobStatus> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs.iter_mut().find(|j| j.id == id)?;
+        let previous = job.status;
+
+        if previous.is_finished() {
+            return Some(previous);
+        }
+
+        job.status = JobStatus::Cancelled;
+        if previous == JobStatus::Queued {
+            job.finished_at = Some(Utc::now().to_rfc3339());
+        }
+        drop(jobs);
+
+        self.notify.notify_waiters();
+        Some(previous)
+    }
+
+    pub async fn is_cancelled(&self, id: &str) -> bool {
+        self.jobs
+            .lock()
+            .await
+            .iter()
+            .any(|j| j.id == id && j.status == JobStatus::Cancelled)
+    }
+
+    /// Resolves once the job is cancelled
+    async fn cancelled(&self, id: &str) {
+        loop {
+            // Register interest before checking so a cancel in between is not missed
+            let notified = self.notify.notified();
+            if self.is_cancelled(id).await {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Run the work of job `id`, dropping it as soon as the job is cancelled
+    pub async fn until_cancelled<T>(
+        &self,
+        id: &str,
+        work: impl Future<Output = T>,
+    ) -> Result<T, String> {
+        tokio::select! {
+            output = work => Ok(output),
+            _ = self.cancelled(id) => Err("Job cancelled".to_string()),
+        }
+    }
+
+    pub async fn list(&self) -> Vec<JobInfo> {
+        self.jobs.lock().await.clone()
+    }
+
+    fn prune(jobs: &mut Vec<JobInfo>) {
+        let finished = jobs
+            .iter()
+            .filter(|j| j.status.is_finished() && j.finished_at.is_some())
+            .count();
+        let mut to_remove = finished.saturating_sub(MAX_FINISHED_JOBS);
+        jobs.retain(|j| {
+            if to_remove > 0 && j.status.is_finished() && j.finished_at.is_some() {
+                to_remove -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_jobs_run_one_at_a_time_in_order() {
+        let queue = Arc::new(JobQueue::new());
+        let first = queue.enqueue(JobKind::Scrape, "first".to_string()).await;
+        let second = queue.enqueue(JobKind::Scrape, "second".to_string()).await;
+
+        queue.wait_for_turn(&first).await.unwrap();
+
+        let waiter = {
+            let queue = queue.clone();
+            let second = second.clone();
+            tokio::spawn(async move { queue.wait_for_turn(&second).await })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished(), "second job must wait for the first");
+
+        queue.finish(&first, Ok(())).await;
+        waiter.await.unwrap().unwrap();
+
+        let jobs = queue.list().await;
+        assert_eq!(jobs[0].status, JobStatus::Completed);
+        assert_eq!(jobs[1].status, JobStatus::Running);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_job() {
+        let queue = Arc::new(JobQueue::new());
+        let first = queue.enqueue(JobKind::Scrape, "first".to_string()).await;
+        let second = queue.enqueue(JobKind::Scrape, "second".to_string()).await;
+        queue.wait_for_turn(&first).await.unwrap();
+
+        let waiter = {
+            let queue = queue.clone();
+            let second = second.clone();
+            tokio::spawn(async move { queue.wait_for_turn(&second).await })
+        };
+
+        assert_eq!(queue.cancel(&second).await, Some(JobStatus::Queued));
+        assert!(waiter.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_running_job_holds_slot_until_finished() {
+        let queue = JobQueue::new();
+        let first = queue.enqueue(JobKind::Scrape, "first".to_string()).await;
+        let second = queue.enqueue(JobKind::Scrape, "second".to_string()).await;
+        queue.wait_for_turn(&first).await.unwrap();
+
+        assert_eq!(queue.cancel(&first).await, Some(JobStatus::Running));
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            queue.wait_for_turn(&second),
+        )
+        .await;
+        assert!(blocked.is_err(), "slot is busy until the cancelled job finishes");
+
+        queue.finish(&first, Ok(())).await;
+        queue.wait_for_turn(&second).await.unwrap();
+        assert_eq!(queue.list().await[0].status, JobStatus::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_job_stops_its_work() {
+        let queue = Arc::new(JobQueue::new());
+        let id = queue.enqueue(JobKind::Refresh, "refresh".to_string()).await;
+        queue.wait_for_turn(&id).await.unwrap();
+
+        let running = {
+            let queue = queue.clone();
+            let id = id.clone();
+            tokio::spawn(async move {
+                // Work that never ends on its own
+                let work = std::future::pending::<()>();
+                let result = queue.until_cancelled(&id, work).await;
+                queue.finish(&id, result.clone()).await;
+                result
+            })
+        };
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!running.is_finished(), "work runs until cancelled");
+
+        assert_eq!(queue.cancel(&id).await, Some(JobStatus::Running));
+        let result = tokio::time::timeout(std::time::Duration::from_secs(1), running)
+            .await
+            .expect("work stops when cancelled")
+            .unwrap();
+        assert_eq!(result, Err("Job cancelled".to_string()));
+
+        let job = queue.list().await.remove(0);
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert!(job.finished_at.is_some(), "slot is released");
+    }
+
+    #[tokio::test]
+    async fn test_work_finishing_first_is_kept() {
+        let queue = JobQueue::new();
+        let id = queue.enqueue(JobKind::Import, "import".to_string()).await;
+        queue.wait_for_turn(&id).await.unwrap();
+
+        assert_eq!(queue.until_cancelled(&id, async { 42 }).await, Ok(42));
+    }
+}
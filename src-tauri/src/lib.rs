@@ -0,0 +1,294 @@
+// TikTrend Finder - Tauri Backend
+// Rust backend for desktop application
+
+mod alerts;
+mod backend;
+mod changelog;
+mod clipboard;
+mod commands;
+mod compare;
+mod companion;
+mod competitors;
+mod config;
+mod credits;
+mod crm;
+mod database;
+mod demand;
+mod demo;
+mod digest;
+mod disk;
+mod entitlements;
+mod file_import;
+mod format;
+mod health;
+mod jobs;
+mod license;
+mod models;
+mod related;
+mod remote_config;
+mod reports;
+mod saturation;
+mod scheduler;
+mod scraper;
+mod share;
+mod shortcuts;
+mod social;
+mod telemetry;
+mod trial;
+mod usage;
+mod watch;
+mod whatsapp;
+mod xlsx;
+
+use tauri::{DragDropEvent, Manager, WindowEvent};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use models::ScraperStatus;
+
+// Global state for scraper status
+pub struct ScraperState(pub Arc<Mutex<ScraperStatus>>);
+
+// Global queue of browser-bound jobs
+pub struct JobState(pub Arc<jobs::JobQueue>);
+
+// Capabilities merged from the subscription and the license
+pub struct EntitlementState(pub entitlements::EntitlementService);
+
+// Visible browser with the scraping profile, kept open between `open_in_browser` calls
+pub struct InspectorState(pub Mutex<Option<scraper::BrowserManager>>);
+
+/// Parser entry points, public for the benchmarks in `benches/`
+pub use scraper::{NumberLocale, TikTokParser};
+
+/// Demo catalog, public for the `generate_mock_products` binary
+pub use demo::generate_products as generate_demo_products;
+
+pub fn run() {
+    dotenv::dotenv().ok();
+    
+    tauri::Builder::default()
+        .plugin(tauri_plugin_log::Builder::default().build())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(ScraperState(Arc::new(Mutex::new(ScraperStatus::default()))))
+        .manage(JobState(Arc::new(jobs::JobQueue::new())))
+        .manage(InspectorState(Mutex::new(None)))
+        .manage(EntitlementState(entitlements::EntitlementService::new()))
+        .on_window_event(|window, event| {
+            if let WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+                file_import::handle_drop(window.app_handle(), paths);
+            }
+        })
+        .setup(|app| {
+            // Initialize database
+            let app_dir = app.path().app_data_dir().expect("Failed to get app data dir");
+            std::fs::create_dir_all(&app_dir).ok();
+            
+            let db_path = app_dir.join("tiktrend.db");
+            database::init_database(&db_path).expect("Failed to initialize database");
+            if let Err(e) = database::prune_trash(&db_path) {
+                log::warn!("Failed to prune the trash: {}", e);
+            }
+            // Scrapes still running when the app went down can be resumed
+            let now = chrono::Utc::now().to_rfc3339();
+            match database::interrupt_running_scrape_sessions(&db_path, &now) {
+                Ok(0) => {}
+                Ok(count) => log::info!("{} interrupted scrape sessions can be resumed", count),
+                Err(e) => log::warn!("Failed to check scrape sessions: {}", e),
+            }
+
+            // A bad shortcut in settings must not keep the app from starting
+            let settings = commands::load_settings(&app_dir);
+            if let Err(e) = shortcuts::register(app.handle(), &settings.shortcuts) {
+                log::warn!("Failed to register global shortcuts: {}", e);
+            }
+            
+            watch::spawn_watcher(app.handle().clone());
+            scheduler::spawn_scheduler(app.handle().clone());
+            clipboard::spawn_clipboard_watcher(app.handle().clone());
+            if settings.companion.enabled {
+                companion::spawn_server(app.handle().clone(), settings.companion.port);
+            }
+
+            // Cached or default remote config stays in effect when offline
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = remote_config::refresh(&db_path).await {
+                    log::warn!("Remote config not refreshed: {}", e);
+                }
+            });
+
+            log::info!("TikTrend Finder initialized successfully!");
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            // Product commands
+            commands::search_products,
+            commands::get_price_distribution,
+            commands::compare_products,
+            commands::get_related_products,
+            commands::format_price,
+            commands::format_compact_number,
+            commands::get_products,
+            commands::get_product_by_id,
+            commands::bulk_update_products,
+            commands::create_product_manual,
+            commands::update_product,
+            commands::get_product_history,
+            commands::get_product_raw_payload,
+            commands::open_in_browser,
+            // Watch list & alerts
+            commands::refresh_product,
+            commands::import_clipboard_product,
+            commands::preview_import,
+            commands::confirm_import,
+            commands::start_extension_pairing,
+            commands::unpair_extensions,
+            commands::watch_product,
+            commands::unwatch_product,
+            commands::get_watched_products,
+            commands::get_product_alerts,
+            commands::mark_alerts_read,
+            // Competitor stores
+            commands::add_competitor,
+            commands::remove_competitor,
+            commands::get_competitors,
+            commands::get_competitor_products,
+            commands::get_category_trends,
+            commands::get_niche_saturation,
+            commands::generate_weekly_digest,
+            commands::export_weekly_digest,
+            commands::send_weekly_digest_whatsapp,
+            commands::send_whatsapp_test,
+            // Favorite commands
+            commands::add_favorite,
+            commands::remove_favorite,
+            commands::update_favorite,
+            commands::get_favorites,
+            commands::create_favorite_list,
+            commands::get_favorite_lists,
+            commands::update_favorite_list,
+            commands::delete_favorite_list,
+            commands::reorder_favorite_lists,
+            commands::set_favorite_list_pinned,
+            commands::export_favorite_list_html,
+            commands::repair_favorites,
+            // Trash
+            commands::get_product_references,
+            commands::delete_products,
+            commands::get_trash,
+            commands::restore_from_trash,
+            commands::empty_trash,
+            // Copy generation commands
+            commands::generate_copy,
+            commands::get_copy_history,
+            commands::get_credit_balance,
+            commands::check_credits,
+            // Dashboard & user commands
+            commands::get_user_stats,
+            commands::get_goals,
+            commands::add_goal,
+            commands::delete_goal,
+            commands::create_crm_lead,
+            commands::update_crm_lead,
+            commands::get_crm_leads,
+            commands::delete_crm_lead,
+            commands::sync_crm_leads,
+            commands::create_social_post,
+            commands::update_social_post,
+            commands::get_social_posts,
+            commands::cancel_social_post,
+            commands::delete_social_post,
+            commands::publish_social_post,
+            commands::export_content_calendar,
+            commands::validate_license,
+            // Subscription commands (SaaS Híbrido)
+            commands::validate_subscription,
+            commands::get_cached_subscription,
+            commands::get_entitlements,
+            commands::check_feature_access,
+            commands::get_execution_mode,
+            commands::can_work_offline,
+            commands::get_subscription_access,
+            commands::start_trial,
+            commands::get_trial_status,
+            commands::record_feature_usage,
+            commands::get_telemetry_events,
+            commands::flush_telemetry,
+            commands::clear_telemetry_events,
+            commands::get_usage_summary,
+            commands::sync_usage,
+            // Scraper commands
+            commands::get_disk_status,
+            commands::cleanup_disk_space,
+            commands::get_scrape_presets,
+            commands::save_scrape_preset,
+            commands::apply_scrape_preset,
+            commands::delete_scrape_preset,
+            commands::get_scrape_schedules,
+            commands::create_scrape_schedule,
+            commands::start_scrape_schedule,
+            commands::pause_scrape_schedule,
+            commands::delete_scrape_schedule,
+            commands::get_report_schedules,
+            commands::save_report_schedule,
+            commands::delete_report_schedule,
+            commands::get_report_history,
+            commands::scrape_tiktok_shop,
+            commands::resume_scrape,
+            commands::get_scrape_sessions,
+            commands::delete_scrape_session,
+            commands::get_scraper_status,
+            commands::get_scraper_logs,
+            commands::list_error_pages,
+            commands::get_error_page,
+            commands::delete_error_pages,
+            commands::get_run_logs,
+            commands::stop_scraper,
+            commands::rotate_fingerprint,
+            commands::benchmark_scraper,
+            commands::get_run_actions,
+            commands::run_stealth_check,
+            commands::get_job_queue,
+            commands::cancel_job,
+            commands::refresh_commissions,
+            commands::test_proxy,
+            commands::get_app_health,
+            commands::cleanup_orphans,
+            commands::initialize_app,
+            commands::seed_demo_data,
+            commands::clear_demo_data,
+            commands::sync_products,
+            commands::get_sync_state,
+            commands::update_selectors,
+            commands::get_selector_manifest,
+            commands::update_selector_manifest,
+            commands::sync_selector_manifest,
+            commands::fetch_job,
+            // Search history commands
+            commands::save_search_history,
+            commands::get_search_history,
+            // Settings commands
+            commands::save_settings,
+            commands::get_remote_config,
+            commands::refresh_remote_config,
+            commands::get_remote_config_overrides,
+            commands::set_remote_config_overrides,
+            commands::get_changelog,
+            commands::mark_changelog_seen,
+            commands::get_settings,
+            commands::export_settings,
+            commands::import_settings,
+            commands::list_settings_profiles,
+            commands::save_settings_profile,
+            commands::switch_settings_profile,
+            commands::delete_settings_profile,
+            // Export command
+            commands::export_products,
+        ])
+        .run(tauri::generate_context!())
+        .expect("Error while running TikTrend Finder");
+}
+
@@ -0,0 +1,78 @@
+// License Cache
+// The last license the backend confirmed is kept in license_cache.json with
+// a checksum over the hardware id and license key, so a valid license keeps
+// working offline until the cache expires. The checksum only catches a cache
+// copied from another device or edited by hand without recomputing it; its
+// salt ships in the binary, so it is not a signature and can be forged.
+
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use crate::commands;
+use crate::models::{CachedLicense, License};
+
+const CACHE_FILE: &str = "license_cache.json";
+
+/// Days a confirmed license is honoured without reaching the backend
+const OFFLINE_DAYS: i64 = 7;
+
+const CHECKSUM_SALT: &str = "tiktrend-license-v1";
+
+/// SHA-256 of the cached license with this device's hardware id and the key
+fn checksum(license_key: &str, license: &License, valid_until: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(CHECKSUM_SALT.as_bytes());
+    hasher.update(commands::get_hardware_id().as_bytes());
+    hasher.update(license_key.as_bytes());
+    hasher.update(
+        serde_json::to_string(license)
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    hasher.update(valid_until.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Cache a license the backend just confirmed. The cache never outlives the
+/// license itself.
+pub fn store(app_dir: &Path, license_key: &str, license: &License) {
+    let now = Utc::now();
+    let offline_limit = now + Duration::days(OFFLINE_DAYS);
+    let valid_until = parse_time(&license.expires_at)
+        .map_or(offline_limit, |expires| expires.min(offline_limit))
+        .to_rfc3339();
+
+    let cached = CachedLicense {
+        signature: checksum(license_key, license, &valid_until),
+        license: license.clone(),
+        cached_at: now.to_rfc3339(),
+        valid_until,
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&cached) {
+        let _ = fs::write(app_dir.join(CACHE_FILE), json);
+    }
+}
+
+/// The cached license for `license_key`, if its checksum holds for this
+/// device and it has not expired
+pub fn load(app_dir: &Path, license_key: &str, now: DateTime<Utc>) -> Option<CachedLicense> {
+    let content = fs::read_to_string(app_dir.join(CACHE_FILE)).ok()?;
+    let cached: CachedLicense = serde_json::from_str(&content).ok()?;
+
+    let intact = cached.signature == checksum(license_key, &cached.license, &cached.valid_until);
+    let valid = parse_time(&cached.valid_until).is_some_and(|until| now < until);
+    (intact && valid && cached.license.is_valid).then_some(cached)
+}
+
+/// Forget the cached license, e.g. after the backend rejected it
+pub fn clear(app_dir: &Path) {
+    let _ = fs::remove_file(app_dir.join(CACHE_FILE));
+}
@@ -58,10 +58,11 @@ impl Default for SubscriptionStatus {
 }
 
 /// Marketplace access enum
-#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub enum MarketplaceAccess {
+    #[default]
     Tiktok,
     Aliexpress,
     Shopee,
@@ -69,6 +70,29 @@ pub enum MarketplaceAccess {
     Mercadolivre,
 }
 
+impl MarketplaceAccess {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketplaceAccess::Tiktok => "tiktok",
+            MarketplaceAccess::Aliexpress => "aliexpress",
+            MarketplaceAccess::Shopee => "shopee",
+            MarketplaceAccess::Amazon => "amazon",
+            MarketplaceAccess::Mercadolivre => "mercadolivre",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tiktok" => Some(MarketplaceAccess::Tiktok),
+            "aliexpress" => Some(MarketplaceAccess::Aliexpress),
+            "shopee" => Some(MarketplaceAccess::Shopee),
+            "amazon" => Some(MarketplaceAccess::Amazon),
+            "mercadolivre" => Some(MarketplaceAccess::Mercadolivre),
+            _ => None,
+        }
+    }
+}
+
 /// Full subscription info returned from API validation
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
@@ -130,6 +154,130 @@ pub struct CachedSubscription {
     pub last_sync: String,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum HealthLevel {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// Status of one subsystem
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct HealthCheck {
+    /// database, disk_space, browser, backend, subscription, proxies or sync_backlog
+    pub name: String,
+    pub level: HealthLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct AppHealth {
+    /// Worst level of the checks
+    pub level: HealthLevel,
+    pub checks: Vec<HealthCheck>,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum StepStatus {
+    Ok,
+    /// Done, but with something the user should look at
+    Warning,
+    Failed,
+    Skipped,
+}
+
+/// Outcome of one first-run setup step, also emitted on `onboarding://step`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct OnboardingStep {
+    /// database, browser, stealth_check or demo_data
+    pub name: String,
+    pub status: StepStatus,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct OnboardingReport {
+    pub steps: Vec<OnboardingStep>,
+    /// No step failed
+    pub completed: bool,
+}
+
+/// Trial started on this device, kept in the settings table. The checksum in
+/// `signature` binds it to the hardware id and plan so a copied or edited
+/// record is not trusted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrialRecord {
+    pub hwid: String,
+    pub started_at: String,
+    pub ends_at: String,
+    pub subscription: Subscription,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum TrialState {
+    /// No trial was started on this device
+    Available,
+    Active,
+    /// Ended, or already used on this device
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct TrialStatus {
+    pub state: TrialState,
+    pub plan_tier: Option<PlanTier>,
+    pub started_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub days_remaining: i32,
+}
+
+/// Usage of a metered feature in one billing period
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FeatureUsage {
+    pub feature: String,
+    pub used: i32,
+    pub limit: i32,
+    pub period_start: String,
+    pub period_end: String,
+}
+
+/// Payload of `quota://warning`, sent when usage of a metered feature
+/// crosses a warning threshold of its limit
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct QuotaWarning {
+    pub feature: String,
+    pub used: i32,
+    pub limit: i32,
+    /// Threshold crossed, in percent of the limit
+    pub threshold: u32,
+    /// Text for the notification
+    pub message: String,
+    pub period_end: String,
+}
+
 /// Validation result from API
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
@@ -166,6 +314,9 @@ pub struct Product {
     pub sales_7d: i32,
     pub sales_30d: i32,
     pub commission_rate: Option<f64>,
+    /// Whether any creator can promote the product without an invite
+    #[serde(default)]
+    pub open_collaboration: Option<bool>,
     pub image_url: Option<String>,
     pub images: Vec<String>,
     pub video_url: Option<String>,
@@ -176,8 +327,169 @@ pub struct Product {
     pub is_on_sale: bool,
     pub in_stock: bool,
     pub stock_level: Option<i32>,
+    /// Cheapest and most expensive variant, when the product has variants
+    #[serde(default)]
+    pub min_price: Option<f64>,
+    #[serde(default)]
+    pub max_price: Option<f64>,
+    #[serde(default)]
+    pub variants: Vec<ProductVariant>,
+    /// Shipping fee to the default address (0 when shipping is free)
+    #[serde(default)]
+    pub shipping_fee: Option<f64>,
+    /// Estimated delivery window in days
+    #[serde(default)]
+    pub delivery_days_min: Option<i32>,
+    #[serde(default)]
+    pub delivery_days_max: Option<i32>,
+    /// Units sold per day estimated from the history (see `demand.rs`)
+    #[serde(default)]
+    pub demand_estimate: Option<DemandEstimate>,
     pub collected_at: String,
     pub updated_at: String,
+    /// Original JSON blob, kept only when raw capture is on
+    #[serde(skip)]
+    pub raw_payload: Option<RawPayload>,
+    /// Seeded demo product, removed by `clear_demo_data`
+    #[serde(default)]
+    pub is_demo: bool,
+    /// Labels set by the user
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Archived products are left out of searches unless asked for
+    #[serde(default)]
+    pub is_archived: bool,
+    #[serde(default)]
+    pub source: ProductSource,
+    /// Saved page the product was parsed from in offline mode
+    #[serde(default)]
+    pub source_file: Option<String>,
+    /// Marketplace the product was collected from
+    #[serde(default)]
+    pub marketplace: MarketplaceAccess,
+}
+
+/// Where the values of a product row come from
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum ProductSource {
+    #[default]
+    Scraper,
+    /// Entered or edited by hand; left out of parser quality metrics
+    Manual,
+}
+
+impl ProductSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductSource::Scraper => "scraper",
+            ProductSource::Manual => "manual",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "scraper" => Some(ProductSource::Scraper),
+            "manual" => Some(ProductSource::Manual),
+            _ => None,
+        }
+    }
+}
+
+/// A TikTok Shop product link found on the clipboard, emitted as
+/// `clipboard://product-link` for the app to offer importing it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ClipboardProductLink {
+    pub url: String,
+    pub tiktok_id: String,
+    /// Row of the product when it was already collected
+    pub product_id: Option<String>,
+}
+
+/// One-time code the browser extension exchanges for its token
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ExtensionPairing {
+    pub code: String,
+    /// Localhost port the extension talks to
+    pub port: u16,
+    pub expires_at: String,
+}
+
+/// Products read from a file, shown before `confirm_import` saves them
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ImportPreview {
+    pub path: String,
+    /// "csv", "json" or "html"
+    pub format: String,
+    /// Products already collected keep their row id
+    pub products: Vec<Product>,
+    /// How many of the products aren't collected yet
+    pub new_count: usize,
+    /// Rows or items that couldn't be read, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Fields of a product entered by hand with `create_product_manual`, or
+/// changed with `update_product`, where fields left out are kept
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductFields {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub original_price: Option<f64>,
+    /// ISO 4217 code, "BRL" when left out of a new product
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub seller_name: Option<String>,
+    #[serde(default)]
+    pub product_rating: Option<f64>,
+    #[serde(default)]
+    pub sales_count: Option<i32>,
+    #[serde(default)]
+    pub shipping_fee: Option<f64>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+    #[serde(default)]
+    pub product_url: Option<String>,
+}
+
+/// JSON a product was parsed from, for debugging parser issues
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct RawPayload {
+    pub source: String,
+    pub json: String,
+    pub captured_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductVariant {
+    pub id: String,
+    pub product_id: String,
+    pub sku_id: Option<String>,
+    pub name: String,
+    pub price: f64,
+    pub stock_level: Option<i32>,
+    pub image_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -192,25 +504,94 @@ pub struct ProductHistory {
     pub collected_at: String,
 }
 
+/// Real demand estimate, more reliable than the displayed sales counter
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DemandEstimate {
+    pub units_per_day: f64,
+    /// 0 to 1; grows with the number of snapshots and the observed window
+    pub confidence: f64,
+    /// "stock" (stock-level drops) or "sales" (sales counter deltas)
+    pub method: String,
+    pub samples: u32,
+    pub window_days: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct SearchFilters {
     pub query: Option<String>,
     pub categories: Vec<String>,
+    /// Only products of these marketplaces; all when empty
+    #[serde(default)]
+    pub marketplaces: Vec<MarketplaceAccess>,
     pub price_min: Option<f64>,
     pub price_max: Option<f64>,
     pub sales_min: Option<i32>,
     pub rating_min: Option<f64>,
     pub has_free_shipping: Option<bool>,
+    pub shipping_max: Option<f64>,
+    pub delivery_days_max: Option<i32>,
     pub is_trending: Option<bool>,
     pub is_on_sale: Option<bool>,
+    /// Leave out niches whose saturation score is above this (0-100)
+    #[serde(default)]
+    pub saturation_max: Option<f64>,
+    /// Only products updated at or after this time (RFC 3339)
+    #[serde(default)]
+    pub updated_since: Option<String>,
+    /// Include archived products
+    #[serde(default)]
+    pub include_archived: Option<bool>,
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
     pub page: Option<i32>,
     pub page_size: Option<i32>,
 }
 
+/// Changes `bulk_update_products` makes to every product given; fields left
+/// out are kept. A category or currency set here survives later scrapes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductPatch {
+    /// New category; an empty string clears it
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    /// ISO 4217 code, e.g. "BRL"
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub archived: Option<bool>,
+}
+
+/// Price band of a histogram; the last band includes its upper bound
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct PriceBucket {
+    pub from: f64,
+    pub to: f64,
+    pub count: i64,
+}
+
+/// Prices of the products matching a filter set
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct PriceDistribution {
+    pub total: i64,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub buckets: Vec<PriceBucket>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts", bound = "T: TS")]
@@ -246,6 +627,19 @@ pub struct License {
     pub credits: i32,  // Créditos IA disponíveis
 }
 
+/// Last license confirmed by the backend, for offline validation
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CachedLicense {
+    pub license: License,
+    pub cached_at: String,
+    pub valid_until: String,
+    /// Checksum binding the cache to this device and license key. Not a
+    /// cryptographic signature: it can be recomputed by anyone who edits the file.
+    pub signature: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
@@ -256,6 +650,48 @@ pub struct PlanFeatures {
     pub scheduler_enabled: bool,
 }
 
+/// One change of the AI credit balance
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CreditLedgerEntry {
+    pub id: i64,
+    /// "usage" for credits spent, "sync" when the backend reported the balance
+    pub kind: String,
+    /// Credits added (positive) or spent (negative)
+    pub amount: i32,
+    /// Balance after the change; None while the backend hasn't reported one
+    pub balance: Option<i32>,
+    pub tokens_used: i32,
+    pub description: String,
+    pub created_at: String,
+}
+
+/// AI credits left and recently spent
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CreditBalance {
+    /// None until the backend has reported a balance
+    pub balance: Option<i32>,
+    pub used_last_30_days: i32,
+    pub updated_at: Option<String>,
+    /// Latest ledger entries, newest first
+    pub recent: Vec<CreditLedgerEntry>,
+}
+
+/// Whether an operation fits in the credits left
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CreditCheck {
+    pub cost: i32,
+    pub balance: Option<i32>,
+    pub sufficient: bool,
+    /// Text for the warning when the credits don't cover the cost
+    pub message: Option<String>,
+}
+
 // Deprecated - keeping for backwards compatibility
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
@@ -275,6 +711,12 @@ pub struct DashboardStats {
     pub searches_today: i64,
     pub copies_generated: i64,
     pub top_categories: Vec<CategoryCount>,
+    /// Trial countdown, filled in by the command
+    #[serde(default)]
+    pub trial: Option<TrialStatus>,
+    /// Progress of the research goals in their current period
+    #[serde(default)]
+    pub goals: Vec<GoalProgress>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -285,6 +727,88 @@ pub struct CategoryCount {
     pub count: i64,
 }
 
+/// Research activity a goal counts, from the favorites
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum GoalMetric {
+    /// Products saved to the favorites
+    ProductsEvaluated,
+    /// Favorites filed in a list
+    ProductsShortlisted,
+    /// Favorites with notes
+    NotesWritten,
+}
+
+impl GoalMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalMetric::ProductsEvaluated => "products_evaluated",
+            GoalMetric::ProductsShortlisted => "products_shortlisted",
+            GoalMetric::NotesWritten => "notes_written",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "products_evaluated" => Some(GoalMetric::ProductsEvaluated),
+            "products_shortlisted" => Some(GoalMetric::ProductsShortlisted),
+            "notes_written" => Some(GoalMetric::NotesWritten),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum GoalPeriod {
+    /// Monday to Sunday
+    Week,
+    Month,
+}
+
+impl GoalPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalPeriod::Week => "week",
+            GoalPeriod::Month => "month",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(GoalPeriod::Week),
+            "month" => Some(GoalPeriod::Month),
+            _ => None,
+        }
+    }
+}
+
+/// A research goal, e.g. "evaluate 50 products a week"
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct Goal {
+    pub id: String,
+    pub title: String,
+    pub metric: GoalMetric,
+    pub target: i64,
+    pub period: GoalPeriod,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct GoalProgress {
+    pub goal: Goal,
+    /// Activity counted since `period_start`
+    pub current: i64,
+    pub completed: bool,
+    pub period_start: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
@@ -292,6 +816,9 @@ pub struct CopyRequest {
     pub product_id: String,
     pub copy_type: String,
     pub tone: String,
+    /// Generate again even when the same copy is cached
+    #[serde(default)]
+    pub force: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -300,6 +827,9 @@ pub struct CopyRequest {
 pub struct CopyResponse {
     pub content: String,
     pub tokens_used: i32,
+    /// Served from the copy cache; no credits were used
+    #[serde(default)]
+    pub cached: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -319,76 +849,506 @@ pub struct CopyHistory {
 
 // ScraperConfig removed to use crate::config::ScraperConfig
 
+/// Named scraper config ("quick check", "deep overnight", ...) applied to
+/// the scraper form instead of filling it in again
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ScrapePreset {
+    pub id: String,
+    pub name: String,
+    pub config: crate::config::ScraperConfig,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Why a scrape did not run, sent to the frontend as `{ kind, ... }`
+#[derive(Debug, Clone, Serialize, Deserialize, TS, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum ScrapeError {
+    /// The plan only runs in the web app
+    #[error("Your plan runs in web-only mode and doesn't include the local scraper")]
+    WebOnlyPlan,
+    /// Categories on marketplaces the plan doesn't include
+    #[error("Your plan doesn't include the marketplace of: {}", categories.join(", "))]
+    MarketplaceNotIncluded { categories: Vec<String> },
+    /// The marketplace selected for the run has no scraper
+    #[error("There is no scraper for {marketplace:?} yet")]
+    MarketplaceNotSupported { marketplace: MarketplaceAccess },
+    #[error("{message}")]
+    Failed { message: String },
+}
+
+impl From<String> for ScrapeError {
+    fn from(message: String) -> Self {
+        ScrapeError::Failed { message }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
 pub struct ScraperStatus {
     pub is_running: bool,
     pub progress: f32,
+    /// Title of the product being parsed or enriched
     pub current_product: Option<String>,
+    /// Page the scraper is working on and the category it belongs to
+    #[serde(default)]
+    pub current_url: Option<String>,
+    #[serde(default)]
+    pub current_category: Option<String>,
     pub products_found: i32,
     pub errors: Vec<String>,
-    pub logs: Vec<String>,
+    /// Most recent entries, oldest first
+    pub logs: Vec<LogEntry>,
     pub started_at: Option<String>,
     pub status_message: Option<String>,
+    #[serde(default)]
+    pub metrics: ScrapeMetrics,
+    /// Id of the current run, used to look up its action log
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Catalog key of `status_message`, for translation in the frontend
+    #[serde(default)]
+    pub status_key: Option<String>,
+    /// Stage breakdown and ETA behind `progress`
+    #[serde(default)]
+    pub progress_detail: ScrapeProgress,
+    /// Results per category of the current or last run
+    #[serde(default)]
+    pub categories: Vec<CategorySummary>,
+    /// How the results of the last run compared with the database
+    #[serde(default)]
+    pub persisted: PersistSummary,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+/// How a saved product compared with its stored row
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum SaveOutcome {
+    /// Not in the database before
+    New,
+    /// Stored with different values
+    Updated,
+    /// Stored with the same values; no history entry is written
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
-pub struct FavoriteList {
-    pub id: String,
-    pub user_id: String,
-    pub name: String,
-    pub description: Option<String>,
-    pub color: String,
-    pub icon: String,
-    pub product_count: i32,
-    pub created_at: String,
-    pub updated_at: String,
+pub struct PersistSummary {
+    pub new: u32,
+    pub updated: u32,
+    pub unchanged: u32,
+    pub failed: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+/// How productive a category was during a run
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
-pub struct FavoriteItem {
-    pub id: String,
-    pub user_id: String,
-    pub product_id: String,
-    pub list_id: Option<String>,
-    pub notes: Option<String>,
-    pub added_at: String,
+pub struct CategorySummary {
+    pub category: String,
+    /// Products parsed from its pages
+    pub found: u32,
+    /// Products not seen before in the run
+    pub new: u32,
+    /// Products another page or category already had
+    pub duplicates: u32,
+    /// Failed navigations, stalled pages, crashes and bot checks
+    pub errors: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum ScrapeStage {
+    #[default]
+    Startup,
+    Categories,
+    Enrichment,
+    /// Saving the results
+    Persistence,
+    Done,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
-pub struct FavoriteWithProduct {
-    pub favorite: FavoriteItem,
-    pub product: Product,
+pub struct CategoryProgress {
+    pub category: String,
+    pub products: u32,
+    /// Share of `max_products` expected from this category
+    pub target: u32,
+    pub done: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+/// Progress of the current run, also emitted as `scraper://progress`
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
-pub struct SearchHistoryItem {
-    pub id: String,
-    pub user_id: String,
-    pub query: String,
-    pub filters: String,
-    pub results_count: i32,
-    pub searched_at: String,
+pub struct ScrapeProgress {
+    pub stage: ScrapeStage,
+    /// Completion of the current stage, 0-100
+    pub stage_progress: f32,
+    pub categories: Vec<CategoryProgress>,
+    pub pages_done: u32,
+    /// Average duration of the recent pages
+    pub avg_page_ms: Option<u64>,
+    /// Estimated seconds left, from the pace of the recent pages
+    pub eta_seconds: Option<u64>,
 }
 
-#[allow(dead_code)]
+/// A backend message with its catalog key and params, so the frontend can
+/// render it in its own language
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export, export_to = "../src/types/tauri-bindings.ts")]
-pub struct CollectionLog {
-    pub id: String,
-    pub status: String,
-    pub products_found: i32,
-    pub products_saved: i32,
+pub struct LocalizedMessage {
+    pub key: String,
+    pub params: std::collections::HashMap<String, String>,
+    /// Text in the language of the app settings
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// One line of the scraper log, also emitted as a `scraper-log` event
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct LogEntry {
+    pub level: LogLevel,
+    /// Message catalog key, e.g. "scraper.navigating"
+    pub code: String,
+    pub params: std::collections::HashMap<String, String>,
+    /// Text in the language of the app settings
+    pub message: String,
+    pub timestamp: String,
+    /// Category or keyword being scraped when the entry was logged
+    pub category: Option<String>,
+}
+
+/// How products were extracted during the current run
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ScrapeMetrics {
+    pub pages: Vec<PageExtraction>,
+    /// Parse passes per source (strategy name, "dom:<selector>" or "none")
+    pub strategy_hits: std::collections::HashMap<String, u32>,
+    /// Pages abandoned by the watchdog
+    #[serde(default)]
+    pub timeouts: Vec<PageTimeout>,
+    /// Browser pool usage, when scraping through a `BrowserPool`
+    #[serde(default)]
+    pub browser_pool: Option<BrowserPoolStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct BrowserPoolStats {
+    pub max_browsers: u32,
+    /// Browsers currently launched (busy + idle)
+    pub open: u32,
+    pub busy: u32,
+    pub idle: u32,
+    /// Callers waiting for a free browser
+    pub waiting: u32,
+    pub checkouts: u32,
+    /// Browsers closed after staying unused past the idle timeout
+    pub idle_shutdowns: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct PageTimeout {
+    pub url: String,
+    /// "navigation" or "watchdog"
+    pub stage: String,
+    pub at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct PageExtraction {
+    pub url: String,
+    pub source: String,
+    pub products: u32,
+}
+
+impl ScrapeMetrics {
+    /// Record a parse pass; a page keeps the source of its latest pass
+    pub fn record(&mut self, url: &str, source: &str, products: usize) {
+        *self.strategy_hits.entry(source.to_string()).or_insert(0) += 1;
+
+        match self.pages.iter_mut().find(|p| p.url == url) {
+            Some(page) => {
+                page.source = source.to_string();
+                page.products = page.products.max(products as u32);
+            }
+            None => self.pages.push(PageExtraction {
+                url: url.to_string(),
+                source: source.to_string(),
+                products: products as u32,
+            }),
+        }
+    }
+
+    pub fn record_timeout(&mut self, url: &str, stage: &str) {
+        self.timeouts.push(PageTimeout {
+            url: url.to_string(),
+            stage: stage.to_string(),
+            at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    pub fn record_browser_pool(&mut self, stats: BrowserPoolStats) {
+        self.browser_pool = Some(stats);
+    }
+}
+
+/// Progress payload emitted on `sync://progress` while uploading products
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SyncProgress {
+    pub chunk: i32,
+    pub total_chunks: i32,
+    pub synced: i32,
+    pub total: i32,
+    pub is_delta: bool,
+}
+
+/// Backend sync overview for the hybrid mode indicator
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SyncState {
+    pub last_sync_at: Option<String>,
+    pub pending_count: i64,
+    pub auto_upload_enabled: bool,
+    pub execution_mode: ExecutionMode,
+}
+
+/// Favorite whose product no longer exists under any id
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FavoriteOrphan {
+    pub favorite_id: String,
+    pub product_id: String,
+    /// None for favorites saved before TikTok ids were kept
+    pub tiktok_id: Option<String>,
+}
+
+/// Outcome of `repair_favorites`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FavoriteRepairReport {
+    /// Favorites pointed at the current row of their product
+    pub relinked: usize,
+    /// Stale favorites folded into a favorite of the current row
+    pub merged: usize,
+    pub orphans: Vec<FavoriteOrphan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FavoriteList {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: String,
+    pub icon: String,
+    pub product_count: i32,
+    /// Position set by `reorder_favorite_lists`
+    #[serde(default)]
+    pub sort_order: i64,
+    /// Pinned lists stay on top
+    #[serde(default)]
+    pub pinned: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FavoriteItem {
+    pub id: String,
+    pub user_id: String,
+    pub product_id: String,
+    pub list_id: Option<String>,
+    pub notes: Option<String>,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct FavoriteWithProduct {
+    pub favorite: FavoriteItem,
+    pub product: Product,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum TrashKind {
+    /// A favorite list with its favorites
+    FavoriteList,
+    Favorite,
+    /// A product with its variants and what referred to it
+    Product,
+}
+
+impl TrashKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashKind::FavoriteList => "favorite_list",
+            TrashKind::Favorite => "favorite",
+            TrashKind::Product => "product",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "favorite_list" => Some(TrashKind::FavoriteList),
+            "favorite" => Some(TrashKind::Favorite),
+            "product" => Some(TrashKind::Product),
+            _ => None,
+        }
+    }
+}
+
+/// What refers to a product, reported before it is deleted
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductReferences {
+    pub product_id: String,
+    pub title: String,
+    pub favorites: i64,
+    /// Names of the favorite lists holding the product
+    pub lists: Vec<String>,
+    /// Notes written on its favorites
+    pub notes: Vec<String>,
+    /// Generated copies
+    pub copies: i64,
+    pub alerts: i64,
+    pub watched: bool,
+}
+
+impl ProductReferences {
+    pub fn is_empty(&self) -> bool {
+        self.favorites == 0 && self.copies == 0 && self.alerts == 0 && !self.watched
+    }
+}
+
+/// Result of `delete_products`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductDeletion {
+    pub deleted: usize,
+    /// Products kept because something refers to them and the deletion did
+    /// not cascade
+    pub refused: Vec<ProductReferences>,
+}
+
+/// Rows referring to rows that are gone, found by `cleanup_orphans`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct OrphanRows {
+    pub table: String,
+    /// Column holding the dangling reference
+    pub column: String,
+    /// Table referred to
+    pub parent: String,
+    pub rows: i64,
+    /// Whether the rows are deleted, or only their reference cleared, as the
+    /// foreign key's ON DELETE action says
+    pub cleared: bool,
+}
+
+/// Something deleted, kept in the trash until restored or expired
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct TrashItem {
+    pub id: String,
+    pub kind: TrashKind,
+    /// Id of the list, favorite or product deleted
+    pub item_id: String,
+    /// List name or product title, for display
+    pub label: String,
+    pub deleted_at: String,
+    /// When the item is removed for good
+    pub expires_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SearchHistoryItem {
+    pub id: String,
+    pub user_id: String,
+    pub query: String,
+    pub filters: String,
+    pub results_count: i32,
+    pub searched_at: String,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CollectionLog {
+    pub id: String,
+    pub status: String,
+    pub products_found: i32,
+    pub products_saved: i32,
     pub errors_count: i32,
     pub duration_ms: i64,
     pub started_at: String,
@@ -407,3 +1367,753 @@ pub struct FilterPreset {
     pub usage_count: i32,
     pub created_at: String,
 }
+
+/// Result of loading bot-detection test pages in the scraper browser
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct StealthReport {
+    pub checked_at: String,
+    pub pages: Vec<StealthPage>,
+    pub signals: Vec<StealthSignal>,
+    /// Number of signals that leaked
+    pub leaks: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct StealthPage {
+    pub url: String,
+    pub loaded: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct StealthSignal {
+    pub name: String,
+    pub leaked: bool,
+    pub detail: String,
+    /// "probe" for our own checks, otherwise the test page that flagged it
+    pub source: String,
+}
+
+/// One step the scraper took during a run (navigation, scroll, click, wait...)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct RunAction {
+    pub run_id: String,
+    /// "navigate", "type", "scroll", "click", "wait", "pause", "mouse" or "blocked"
+    pub action: String,
+    pub url: String,
+    pub detail: String,
+    /// Milliseconds since the run started
+    pub elapsed_ms: i64,
+    pub created_at: String,
+}
+
+/// Parse pipeline timings over recorded fixtures (see `benchmark_scraper`)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct BenchmarkReport {
+    pub fixtures: u32,
+    pub iterations: u32,
+    /// Products parsed over all iterations
+    pub products: u32,
+    pub total_ms: f64,
+    pub products_per_sec: f64,
+    pub stages: Vec<StageTiming>,
+    /// Resident memory of the app when the benchmark started and at its peak
+    pub start_memory_bytes: u64,
+    pub peak_memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct StageTiming {
+    /// "read", "html_parse", "dom_extract" or "api_parse"
+    pub stage: String,
+    pub total_ms: f64,
+    pub avg_ms: f64,
+    pub runs: u32,
+}
+
+/// A product re-fetched on its own schedule, independent of category scrapes
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct WatchedProduct {
+    pub product_id: String,
+    pub title: String,
+    pub interval_minutes: u32,
+    pub last_checked_at: Option<String>,
+    pub next_check_at: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum AlertKind {
+    PriceDrop,
+    PriceRise,
+    OutOfStock,
+    BackInStock,
+    SalesSpike,
+    /// A competitor store listed a product not seen before
+    NewProduct,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::PriceDrop => "price_drop",
+            AlertKind::PriceRise => "price_rise",
+            AlertKind::OutOfStock => "out_of_stock",
+            AlertKind::BackInStock => "back_in_stock",
+            AlertKind::SalesSpike => "sales_spike",
+            AlertKind::NewProduct => "new_product",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "price_drop" => Some(AlertKind::PriceDrop),
+            "price_rise" => Some(AlertKind::PriceRise),
+            "out_of_stock" => Some(AlertKind::OutOfStock),
+            "back_in_stock" => Some(AlertKind::BackInStock),
+            "sales_spike" => Some(AlertKind::SalesSpike),
+            "new_product" => Some(AlertKind::NewProduct),
+            _ => None,
+        }
+    }
+}
+
+/// A change worth telling the user about, raised by the alert engine
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductAlert {
+    pub id: String,
+    pub product_id: String,
+    pub kind: AlertKind,
+    pub message: String,
+    pub old_value: Option<f64>,
+    pub new_value: Option<f64>,
+    pub is_read: bool,
+    pub created_at: String,
+}
+
+/// A competitor's TikTok Shop store whose catalog is scraped on a schedule
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct Competitor {
+    pub id: String,
+    pub name: String,
+    pub store_url: String,
+    /// Price changes of at least this many percent raise an alert
+    pub price_change_percent: f64,
+    pub interval_minutes: u32,
+    pub last_checked_at: Option<String>,
+    pub next_check_at: String,
+    /// Products seen in the store so far
+    pub product_count: i64,
+    pub created_at: String,
+}
+
+/// A product of a competitor's catalog (row of the `competitor_products` view)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CompetitorProduct {
+    pub competitor_id: String,
+    pub competitor_name: String,
+    pub first_seen_at: String,
+    pub last_seen_at: String,
+    pub product: Product,
+}
+
+/// Category scrape repeated on an interval
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ScrapeSchedule {
+    pub id: String,
+    pub name: String,
+    /// Category names or URLs; empty scrapes the trending page
+    pub categories: Vec<String>,
+    pub interval_minutes: u32,
+    pub paused: bool,
+    pub last_run_at: Option<String>,
+    pub next_run_at: String,
+    /// Why the last run failed; None after a successful one
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+/// How far a category of a scrape session got
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CategoryCheckpoint {
+    pub category: String,
+    /// Next page to load, for listings paginated by URL
+    pub page_number: u32,
+    /// Page height scrolled down to, for infinite scroll listings
+    pub scroll_offset: i64,
+    /// Nothing left to collect in the category
+    pub done: bool,
+}
+
+/// Progress of a category scrape, kept so an interrupted one can be resumed
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ScrapeSession {
+    /// Id of the run that started it
+    pub id: String,
+    pub categories: Vec<String>,
+    pub checkpoints: Vec<CategoryCheckpoint>,
+    /// Products collected so far
+    pub collected: i64,
+    /// "running" or "interrupted"; completed sessions are deleted
+    pub status: String,
+    /// Why it was interrupted: "stopped", "crashed", "incomplete" (pages
+    /// that timed out) or the error that ended it
+    pub reason: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Who a CRM lead is
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum LeadKind {
+    Supplier,
+    Creator,
+}
+
+impl LeadKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeadKind::Supplier => "supplier",
+            LeadKind::Creator => "creator",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "supplier" => Some(LeadKind::Supplier),
+            "creator" => Some(LeadKind::Creator),
+            _ => None,
+        }
+    }
+}
+
+/// Stage of a CRM lead in the pipeline
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum LeadStatus {
+    New,
+    Contacted,
+    Negotiating,
+    Won,
+    Lost,
+}
+
+impl LeadStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeadStatus::New => "new",
+            LeadStatus::Contacted => "contacted",
+            LeadStatus::Negotiating => "negotiating",
+            LeadStatus::Won => "won",
+            LeadStatus::Lost => "lost",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "new" => Some(LeadStatus::New),
+            "contacted" => Some(LeadStatus::Contacted),
+            "negotiating" => Some(LeadStatus::Negotiating),
+            "won" => Some(LeadStatus::Won),
+            "lost" => Some(LeadStatus::Lost),
+            _ => None,
+        }
+    }
+}
+
+/// Supplier or creator contact captured while researching products
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CrmLead {
+    pub id: String,
+    pub kind: LeadKind,
+    pub name: String,
+    pub company: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    /// Social profile, e.g. "@loja_oficial"
+    pub handle: Option<String>,
+    /// Product the contact was found through
+    pub product_id: Option<String>,
+    pub status: LeadStatus,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a lead for `create_crm_lead`, or changed with `update_crm_lead`,
+/// where fields left out are kept and empty text clears them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct LeadFields {
+    /// Supplier when left out of a new lead
+    #[serde(default)]
+    pub kind: Option<LeadKind>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub company: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub handle: Option<String>,
+    #[serde(default)]
+    pub product_id: Option<String>,
+    /// New when left out of a new lead
+    #[serde(default)]
+    pub status: Option<LeadStatus>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+/// Stage of a queued social post
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum SocialPostStatus {
+    Scheduled,
+    Published,
+    Failed,
+    Canceled,
+}
+
+impl SocialPostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SocialPostStatus::Scheduled => "scheduled",
+            SocialPostStatus::Published => "published",
+            SocialPostStatus::Failed => "failed",
+            SocialPostStatus::Canceled => "canceled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "scheduled" => Some(SocialPostStatus::Scheduled),
+            "published" => Some(SocialPostStatus::Published),
+            "failed" => Some(SocialPostStatus::Failed),
+            "canceled" => Some(SocialPostStatus::Canceled),
+            _ => None,
+        }
+    }
+}
+
+/// Post queued for a social network, built from a generated copy and the
+/// product image
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SocialPost {
+    pub id: String,
+    pub product_id: Option<String>,
+    /// Copy from copy_history the caption was taken from
+    pub copy_id: Option<String>,
+    /// e.g. "instagram", "tiktok", "facebook"
+    pub platform: String,
+    pub caption: String,
+    pub image_url: Option<String>,
+    /// When the post goes out (RFC 3339)
+    pub scheduled_at: String,
+    pub status: SocialPostStatus,
+    /// Id of the post on the network once published
+    pub external_id: Option<String>,
+    /// Why the last publish attempt failed
+    pub error: Option<String>,
+    pub published_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a post for `create_social_post`, or changed with
+/// `update_social_post`, where fields left out are kept and empty text
+/// clears them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SocialPostFields {
+    /// Fills in the caption and product of a new post when they are left out
+    #[serde(default)]
+    pub copy_id: Option<String>,
+    #[serde(default)]
+    pub product_id: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub caption: Option<String>,
+    /// The product image when left out of a new post
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// Now when left out of a new post
+    #[serde(default)]
+    pub scheduled_at: Option<String>,
+}
+
+/// How crowded a niche (product category or search keyword) is
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct NicheSaturation {
+    pub niche: String,
+    pub products: i64,
+    pub sellers: i64,
+    pub avg_price: f64,
+    /// Coefficient of variation of the prices; low means commoditized
+    pub price_dispersion: f64,
+    /// Share of listings first seen in the last 7 days; None until the
+    /// niche has been tracked that long
+    pub new_listing_rate: Option<f64>,
+    /// 0 (open) to 100 (crowded)
+    pub score: f64,
+    pub computed_at: String,
+}
+
+/// Window a category trend is measured over
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum TrendPeriod {
+    Week,
+    Month,
+    Quarter,
+}
+
+impl TrendPeriod {
+    pub fn days(&self) -> i64 {
+        match self {
+            TrendPeriod::Week => 7,
+            TrendPeriod::Month => 30,
+            TrendPeriod::Quarter => 90,
+        }
+    }
+}
+
+/// How a category moved over a period, from the product history
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct CategoryTrend {
+    pub category: String,
+    pub products: i64,
+    /// Products first seen during the period
+    pub new_products: i64,
+    /// Products with a snapshot from before the period, which the growth
+    /// and price movement are measured on
+    pub tracked_products: i64,
+    pub total_sales: i64,
+    /// Sales growth of the tracked products in percent; None without any
+    pub sales_growth: Option<f64>,
+    pub avg_price: f64,
+    /// Average price change of the tracked products in percent
+    pub avg_price_change: Option<f64>,
+}
+
+/// A product ranked in the weekly digest
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DigestProduct {
+    pub product_id: String,
+    pub title: String,
+    pub price: f64,
+    pub category: Option<String>,
+    pub product_url: String,
+    /// Estimated units per day, or the 7-day sales average without an estimate
+    pub units_per_day: f64,
+}
+
+/// A product whose price or sales moved the most during the week
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DigestMover {
+    pub product_id: String,
+    pub title: String,
+    pub price_before: f64,
+    pub price_after: f64,
+    pub price_change_percent: f64,
+    pub sales_gained: i32,
+}
+
+/// Weekly opportunity report: what's selling, what moved, which niches are
+/// still open and what the alerts caught
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct WeeklyDigest {
+    pub period_start: String,
+    pub period_end: String,
+    pub generated_at: String,
+    /// Products updated during the period
+    pub products_tracked: i64,
+    pub trending: Vec<DigestProduct>,
+    pub movers: Vec<DigestMover>,
+    /// Least saturated niches, most open first
+    pub open_niches: Vec<NicheSaturation>,
+    pub alerts: Vec<ProductAlert>,
+}
+
+/// Free space on the disk holding the app data
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DiskStatus {
+    /// None when the disk could not be found
+    pub available_bytes: Option<u64>,
+    pub min_free_bytes: u64,
+    pub low: bool,
+}
+
+/// What a disk cleanup removed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct DiskCleanupReport {
+    /// Browser image and code caches
+    pub cache_bytes: u64,
+    pub error_pages: usize,
+    pub run_logs: usize,
+    pub log_files: usize,
+    /// Free space gained, as seen by the file system
+    pub freed_bytes: u64,
+}
+
+/// A stored error page without its HTML, for listing
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ErrorPageSummary {
+    pub id: i64,
+    pub url: String,
+    /// Size of the HTML in bytes, uncompressed
+    pub size: i64,
+    pub created_at: String,
+}
+
+/// HTML of a page the scraper was blocked on, kept for debugging
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ErrorPage {
+    pub id: i64,
+    pub url: String,
+    pub html: String,
+    pub created_at: String,
+}
+
+/// Anonymous usage event, shown as-is in the telemetry viewer
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct TelemetryEvent {
+    pub id: i64,
+    /// e.g. "feature_used", "scrape_completed", "scrape_failed"
+    pub name: String,
+    pub properties: std::collections::HashMap<String, String>,
+    pub created_at: String,
+    /// None until uploaded
+    pub sent_at: Option<String>,
+}
+
+/// Feature flags and tunables set by the backend. Unset tunables keep the
+/// app's own defaults; the same shape holds the user's local overrides.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct RemoteConfig {
+    pub flags: std::collections::HashMap<String, bool>,
+    /// Replace the default pause between pages
+    pub min_delay_ms: Option<u32>,
+    pub max_delay_ms: Option<u32>,
+    /// Extraction strategy names, tried in this order before the others
+    pub strategy_order: Vec<String>,
+    /// Replace the default safety switch thresholds
+    pub max_detection_rate: Option<f32>,
+    pub safety_cooldown_seconds: Option<u32>,
+    pub consecutive_failures_threshold: Option<u32>,
+    /// When the backend values were fetched, None for offline defaults
+    pub fetched_at: Option<String>,
+}
+
+/// Notes of one release on an updater channel
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ReleaseNote {
+    pub version: String,
+    pub published_at: String,
+    pub notes: Vec<String>,
+}
+
+/// Releases the user has not seen yet, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct Changelog {
+    pub channel: String,
+    pub current_version: String,
+    /// Releases after this version are listed; None lists only the current one
+    pub since_version: Option<String>,
+    pub releases: Vec<ReleaseNote>,
+    /// When the notes were fetched, None if never
+    pub fetched_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum ReportFormat {
+    Xlsx,
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Xlsx => "xlsx",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "xlsx" => Some(ReportFormat::Xlsx),
+            "csv" => Some(ReportFormat::Csv),
+            "json" => Some(ReportFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            ReportFormat::Csv => "text/csv",
+            ReportFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Report exported after each scraper run: the run's products matching a
+/// filter preset, written to a folder and/or posted to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ReportSchedule {
+    /// Empty when creating a schedule
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Filter preset the products must match; None exports every product
+    /// of the run
+    pub preset_id: Option<String>,
+    pub format: ReportFormat,
+    pub folder: Option<String>,
+    pub webhook_url: Option<String>,
+    pub enabled: bool,
+    #[serde(default)]
+    pub created_at: String,
+}
+
+/// One report generated by a schedule, or its failure
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ReportRecord {
+    pub id: i64,
+    pub schedule_id: String,
+    pub schedule_name: String,
+    pub run_id: String,
+    pub success: bool,
+    /// File written, when the schedule has a folder
+    pub path: Option<String>,
+    pub product_count: i64,
+    pub error: Option<String>,
+    pub created_at: String,
+}
+
+/// One metric of a compared product
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ComparedMetric {
+    /// e.g. "price", "sales_7d", "trend_score"
+    pub key: String,
+    /// None when unknown for this product
+    pub value: Option<f64>,
+    /// Difference to the first product of the comparison
+    pub delta: Option<f64>,
+    pub delta_percent: Option<f64>,
+    /// Best value of the set (lowest price, highest sales, ...)
+    pub best: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ComparedProduct {
+    pub product_id: String,
+    pub title: String,
+    pub image_url: Option<String>,
+    /// Same metrics in the same order for every product
+    pub metrics: Vec<ComparedMetric>,
+}
+
+/// Products side by side, in the order they were asked for
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ProductComparison {
+    pub products: Vec<ComparedProduct>,
+    pub compared_at: String,
+}
+
+/// A product adjacent to another one
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct RelatedProduct {
+    pub product: Product,
+    /// 0 (unrelated) to 1
+    pub score: f64,
+    pub same_category: bool,
+    /// Title keywords both products share
+    pub shared_keywords: Vec<String>,
+}
@@ -0,0 +1,109 @@
+// Related Products
+// Products adjacent to one being browsed: the same category and shared title
+// keywords. No embeddings are stored yet, so similarity is lexical; candidates
+// come from one SQL query and are ranked here.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::database;
+use crate::models::{Product, RelatedProduct};
+
+/// Keywords of the product searched for in other titles
+const MAX_KEYWORDS: usize = 6;
+
+/// Candidates ranked per request
+const MAX_CANDIDATES: i64 = 500;
+
+const CATEGORY_WEIGHT: f64 = 0.35;
+const SUBCATEGORY_WEIGHT: f64 = 0.15;
+const KEYWORD_WEIGHT: f64 = 0.5;
+
+/// Words too common in listings to relate two products
+const STOPWORDS: &[&str] = &[
+    "com", "para", "por", "dos", "das", "que", "uma", "kit", "novo", "nova", "original", "and",
+    "the", "for", "with", "new", "pcs", "unidades",
+];
+
+/// Distinct lowercase title words of 3+ letters, stopwords left out
+pub fn keywords(title: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3 && !w.chars().all(|c| c.is_ascii_digit()))
+        .filter(|w| !STOPWORDS.contains(w))
+        .filter(|w| seen.insert(w.to_string()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn same(a: &Option<String>, b: &Option<String>) -> bool {
+    a.as_deref()
+        .zip(b.as_deref())
+        .is_some_and(|(a, b)| !a.is_empty() && a.eq_ignore_ascii_case(b))
+}
+
+/// Score `candidate` against `product`: 0 (unrelated) to 1
+fn relate(product: &Product, words: &HashSet<String>, candidate: Product) -> RelatedProduct {
+    let candidate_words: HashSet<String> = keywords(&candidate.title).into_iter().collect();
+    let mut shared_keywords: Vec<String> = words.intersection(&candidate_words).cloned().collect();
+    shared_keywords.sort();
+    let union = words.union(&candidate_words).count();
+    let overlap = if union == 0 {
+        0.0
+    } else {
+        shared_keywords.len() as f64 / union as f64
+    };
+
+    let same_category = same(&product.category, &candidate.category);
+    let mut score = KEYWORD_WEIGHT * overlap;
+    if same_category {
+        score += CATEGORY_WEIGHT;
+        if same(&product.subcategory, &candidate.subcategory) {
+            score += SUBCATEGORY_WEIGHT;
+        }
+    }
+
+    RelatedProduct {
+        product: candidate,
+        score,
+        same_category,
+        shared_keywords,
+    }
+}
+
+/// Products related to `product`, best first
+pub fn find(
+    db_path: &Path,
+    product: &Product,
+    limit: usize,
+) -> rusqlite::Result<Vec<RelatedProduct>> {
+    let words = keywords(&product.title);
+    // Longer words are the more specific ones
+    let mut search = words.clone();
+    search.sort_by_key(|w| std::cmp::Reverse(w.chars().count()));
+    search.truncate(MAX_KEYWORDS);
+
+    let candidates = database::get_related_candidates(
+        db_path,
+        &product.id,
+        product.category.as_deref(),
+        &search,
+        MAX_CANDIDATES,
+    )?;
+
+    let words: HashSet<String> = words.into_iter().collect();
+    let mut related: Vec<RelatedProduct> = candidates
+        .into_iter()
+        .map(|candidate| relate(product, &words, candidate))
+        .filter(|r| r.score > 0.0)
+        .collect();
+    related.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.product.sales_count.cmp(&a.product.sales_count))
+    });
+    related.truncate(limit);
+    Ok(related)
+}
@@ -0,0 +1,150 @@
+// Remote Config
+// Feature flags and scraper tunables served by the backend, so behaviour can
+// be tuned without shipping an update. The last fetched values are cached
+// for offline use, and local overrides set from the app win over both. Remote
+// tunables only replace built-in defaults: a value the user changed in the
+// scraper settings is left alone.
+
+use chrono::Utc;
+use std::path::Path;
+
+use crate::backend;
+use crate::config::ScraperConfig;
+use crate::database;
+use crate::models::RemoteConfig;
+use crate::scraper::models::ExtractionStrategy;
+
+/// Settings key of the last config fetched from the backend
+const CACHE_KEY: &str = "remote_config";
+
+/// Settings key of the local overrides
+const OVERRIDES_KEY: &str = "remote_config_overrides";
+
+fn load(db_path: &Path, key: &str) -> RemoteConfig {
+    database::get_setting(db_path, key)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store(db_path: &Path, key: &str, config: &RemoteConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    database::set_setting(db_path, key, &json).map_err(|e| format!("Database error: {}", e))
+}
+
+/// `overrides` on top of `base`: set values and flags replace those of the base
+fn merge(mut base: RemoteConfig, overrides: RemoteConfig) -> RemoteConfig {
+    base.flags.extend(overrides.flags);
+    base.min_delay_ms = overrides.min_delay_ms.or(base.min_delay_ms);
+    base.max_delay_ms = overrides.max_delay_ms.or(base.max_delay_ms);
+    if !overrides.strategy_order.is_empty() {
+        base.strategy_order = overrides.strategy_order;
+    }
+    base.max_detection_rate = overrides.max_detection_rate.or(base.max_detection_rate);
+    base.safety_cooldown_seconds = overrides
+        .safety_cooldown_seconds
+        .or(base.safety_cooldown_seconds);
+    base.consecutive_failures_threshold = overrides
+        .consecutive_failures_threshold
+        .or(base.consecutive_failures_threshold);
+    base
+}
+
+/// The config in effect: the cached backend values with the local overrides
+pub fn effective(db_path: &Path) -> RemoteConfig {
+    merge(load(db_path, CACHE_KEY), load(db_path, OVERRIDES_KEY))
+}
+
+pub fn overrides(db_path: &Path) -> RemoteConfig {
+    load(db_path, OVERRIDES_KEY)
+}
+
+pub fn set_overrides(db_path: &Path, overrides: &RemoteConfig) -> Result<(), String> {
+    store(db_path, OVERRIDES_KEY, overrides)
+}
+
+/// Fetch the config from the backend and cache it; returns the config in
+/// effect afterwards
+pub async fn refresh(db_path: &Path) -> Result<RemoteConfig, String> {
+    let response = backend::send(backend::get("/config").query(&[
+        ("app_version", env!("CARGO_PKG_VERSION")),
+        ("os", std::env::consts::OS),
+    ]))
+    .await
+    .map_err(|e| format!("Failed to reach the server: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Remote config request failed: {}",
+            response.status()
+        ));
+    }
+
+    let mut config: RemoteConfig = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+    config.fetched_at = Some(Utc::now().to_rfc3339());
+    store(db_path, CACHE_KEY, &config)?;
+
+    Ok(effective(db_path))
+}
+
+/// Apply the tunables to a run's config where it still has the built-in
+/// defaults, and the kill switch flags
+pub fn apply(remote: &RemoteConfig, config: &mut ScraperConfig) {
+    let defaults = ScraperConfig::default();
+    let keep_default = |value: u32, default: u32, remote: Option<u32>| {
+        remote.filter(|_| value == default).unwrap_or(value)
+    };
+
+    config.min_delay_ms = keep_default(
+        config.min_delay_ms,
+        defaults.min_delay_ms,
+        remote.min_delay_ms,
+    );
+    config.max_delay_ms = keep_default(
+        config.max_delay_ms,
+        defaults.max_delay_ms,
+        remote.max_delay_ms,
+    );
+    config.safety_cooldown_seconds = keep_default(
+        config.safety_cooldown_seconds,
+        defaults.safety_cooldown_seconds,
+        remote.safety_cooldown_seconds,
+    );
+    config.consecutive_failures_threshold = keep_default(
+        config.consecutive_failures_threshold,
+        defaults.consecutive_failures_threshold,
+        remote.consecutive_failures_threshold,
+    );
+    if let Some(rate) = remote.max_detection_rate {
+        if config.max_detection_rate == defaults.max_detection_rate {
+            config.max_detection_rate = rate;
+        }
+    }
+
+    // Kill switches for features that break when the shop changes
+    if remote.flags.get("intercept_api") == Some(&false) {
+        config.intercept_api = false;
+    }
+    if remote.flags.get("human_typing") == Some(&false) {
+        config.human_typing = false;
+    }
+
+    if !remote.strategy_order.is_empty() {
+        let mut strategies = config
+            .extraction_strategies
+            .take()
+            .unwrap_or_else(ExtractionStrategy::defaults);
+        // Listed strategies first, in the given order; the rest keep theirs
+        strategies.sort_by_key(|s| {
+            remote
+                .strategy_order
+                .iter()
+                .position(|name| *name == s.name)
+                .unwrap_or(usize::MAX)
+        });
+        config.extraction_strategies = Some(strategies);
+    }
+}
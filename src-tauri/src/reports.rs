@@ -0,0 +1,158 @@
+// Scheduled Reports
+// After each scraper run, every enabled report schedule exports the products
+// the run updated that match its filter preset, as XLSX, CSV or JSON, to a
+// folder and/or a webhook. Each report, or its failure, is kept in the report
+// history; failures are also emitted as `report://failed`.
+
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands;
+use crate::database;
+use crate::models::{ReportRecord, ReportSchedule, SearchFilters};
+
+/// Products a single report holds at most
+const MAX_REPORT_PRODUCTS: i32 = 10_000;
+
+/// Filters of the schedule's preset, limited to the products updated since
+/// the run started
+fn report_filters(
+    db_path: &Path,
+    schedule: &ReportSchedule,
+    run_started_at: &str,
+) -> Result<SearchFilters, String> {
+    let mut filters = match &schedule.preset_id {
+        Some(preset_id) => {
+            let preset = database::get_filter_preset(db_path, preset_id)
+                .map_err(|e| format!("Database error: {}", e))?
+                .ok_or_else(|| format!("Filter preset not found: {}", preset_id))?;
+            serde_json::from_str(&preset.filters)
+                .map_err(|e| format!("Invalid filter preset: {}", e))?
+        }
+        None => SearchFilters::default(),
+    };
+    filters.updated_since = Some(run_started_at.to_string());
+    filters.sort_by = Some("sales_count".to_string());
+    filters.sort_order = Some("DESC".to_string());
+    filters.page = Some(1);
+    filters.page_size = Some(MAX_REPORT_PRODUCTS);
+    Ok(filters)
+}
+
+/// Export one report; returns the file written, if any, and the number of
+/// products
+async fn generate(
+    db_path: &Path,
+    schedule: &ReportSchedule,
+    run_started_at: &str,
+) -> Result<(Option<PathBuf>, i64), String> {
+    let filters = report_filters(db_path, schedule, run_started_at)?;
+    let products = database::search_products(db_path, &filters)
+        .map_err(|e| format!("Database error: {}", e))?
+        .data;
+    let output = commands::render_products(&products, schedule.format.as_str())?;
+
+    let mut path = None;
+    if let Some(folder) = schedule.folder.as_deref().filter(|f| !f.trim().is_empty()) {
+        let folder = PathBuf::from(folder);
+        std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+        let file = folder.join(format!(
+            "{}-{}.{}",
+            sanitize(&schedule.name),
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            schedule.format.as_str()
+        ));
+        std::fs::write(&file, &output).map_err(|e| e.to_string())?;
+        path = Some(file);
+    }
+
+    if let Some(url) = schedule
+        .webhook_url
+        .as_deref()
+        .filter(|u| !u.trim().is_empty())
+    {
+        let response = reqwest::Client::new()
+            .post(url)
+            .header("Content-Type", schedule.format.content_type())
+            .header("X-Report-Name", sanitize(&schedule.name))
+            .body(output)
+            .send()
+            .await
+            .map_err(|e| format!("Webhook failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Webhook failed: {}", response.status()));
+        }
+    }
+
+    Ok((path, products.len() as i64))
+}
+
+/// File name safe version of a schedule name
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "report".to_string()
+    } else {
+        name
+    }
+}
+
+/// Run every enabled schedule for the scraper run `run_id`, which started at
+/// `run_started_at`. Returns the records added to the history.
+pub async fn run_after_scrape(
+    app: &AppHandle,
+    run_id: &str,
+    run_started_at: &str,
+) -> Result<Vec<ReportRecord>, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let schedules =
+        database::get_report_schedules(&db_path).map_err(|e| format!("Database error: {}", e))?;
+
+    let mut records = Vec::new();
+    for schedule in schedules.iter().filter(|s| s.enabled) {
+        let result = generate(&db_path, schedule, run_started_at).await;
+        let mut record = ReportRecord {
+            id: 0,
+            schedule_id: schedule.id.clone(),
+            schedule_name: schedule.name.clone(),
+            run_id: run_id.to_string(),
+            success: result.is_ok(),
+            path: None,
+            product_count: 0,
+            error: None,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        match result {
+            Ok((path, count)) => {
+                record.path = path.map(|p| p.to_string_lossy().to_string());
+                record.product_count = count;
+            }
+            Err(e) => {
+                log::warn!("Report '{}' failed: {}", schedule.name, e);
+                record.error = Some(e);
+            }
+        }
+
+        match database::add_report_record(&db_path, &record) {
+            Ok(id) => record.id = id,
+            Err(e) => log::warn!("Failed to record report: {}", e),
+        }
+        if !record.success {
+            let _ = app.emit("report://failed", &record);
+        }
+        records.push(record);
+    }
+
+    Ok(records)
+}
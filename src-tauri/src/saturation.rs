@@ -0,0 +1,195 @@
+// Niche Saturation
+// Scores how crowded each niche is from the scraped listings: how many
+// sellers compete in it, how uniform their prices are and how fast new
+// listings keep appearing
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::database::{self, NicheListing};
+use crate::models::NicheSaturation;
+
+/// Listings first seen within this many days count as new
+const NEW_LISTING_DAYS: i64 = 7;
+
+/// Number of sellers at which seller pressure reaches ~63%
+const SELLER_SCALE: f64 = 20.0;
+
+const SELLER_WEIGHT: f64 = 0.45;
+const PRICE_WEIGHT: f64 = 0.25;
+const ENTRY_WEIGHT: f64 = 0.30;
+
+/// Pressure used when a signal can't be measured yet
+const UNKNOWN_PRESSURE: f64 = 0.5;
+
+/// Score every niche of the listings
+pub fn compute(listings: &[NicheListing], now: DateTime<Utc>) -> Vec<NicheSaturation> {
+    let mut niches: BTreeMap<&str, Vec<&NicheListing>> = BTreeMap::new();
+    for listing in listings {
+        niches.entry(listing.0.as_str()).or_default().push(listing);
+    }
+
+    let new_since = now - Duration::days(NEW_LISTING_DAYS);
+    niches
+        .into_iter()
+        .map(|(niche, listings)| {
+            let count = listings.len() as f64;
+            let sellers = listings
+                .iter()
+                .filter_map(|l| l.1.as_deref())
+                .collect::<HashSet<_>>()
+                .len();
+
+            let avg_price = listings.iter().map(|l| l.2).sum::<f64>() / count;
+            let variance = listings
+                .iter()
+                .map(|l| (l.2 - avg_price).powi(2))
+                .sum::<f64>()
+                / count;
+            let price_dispersion = variance.sqrt() / avg_price;
+
+            let first_seen: Vec<DateTime<Utc>> = listings
+                .iter()
+                .filter_map(|l| DateTime::parse_from_rfc3339(&l.3).ok())
+                .map(|t| t.with_timezone(&Utc))
+                .collect();
+            // A niche tracked for less than the window would count every listing as new
+            let tracked_long_enough = first_seen.iter().min().is_some_and(|t| *t < new_since);
+            let new_listing_rate = tracked_long_enough
+                .then(|| first_seen.iter().filter(|t| **t >= new_since).count() as f64 / count);
+
+            let seller_pressure = 1.0 - (-(sellers as f64) / SELLER_SCALE).exp();
+            let price_pressure = if listings.len() < 2 {
+                UNKNOWN_PRESSURE
+            } else {
+                1.0 - price_dispersion.min(1.0)
+            };
+            let entry_pressure = new_listing_rate
+                .map(|rate| (rate * 2.0).min(1.0))
+                .unwrap_or(UNKNOWN_PRESSURE);
+
+            NicheSaturation {
+                niche: niche.to_string(),
+                products: listings.len() as i64,
+                sellers: sellers as i64,
+                avg_price,
+                price_dispersion,
+                new_listing_rate,
+                score: 100.0
+                    * (SELLER_WEIGHT * seller_pressure
+                        + PRICE_WEIGHT * price_pressure
+                        + ENTRY_WEIGHT * entry_pressure),
+                computed_at: now.to_rfc3339(),
+            }
+        })
+        .collect()
+}
+
+/// Recompute and store the scores of all niches, most crowded first
+pub fn refresh(db_path: &Path) -> rusqlite::Result<Vec<NicheSaturation>> {
+    let listings = database::get_niche_listings(db_path)?;
+    let mut niches = compute(&listings, Utc::now());
+    database::save_niche_saturation(db_path, &niches)?;
+
+    niches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(niches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+    }
+
+    fn listing(niche: &str, seller: Option<&str>, price: f64, days_ago: i64) -> NicheListing {
+        (
+            niche.to_string(),
+            seller.map(str::to_string),
+            price,
+            (now() - Duration::days(days_ago)).to_rfc3339(),
+        )
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    fn seller_pressure(sellers: f64) -> f64 {
+        1.0 - (-sellers / SELLER_SCALE).exp()
+    }
+
+    #[test]
+    fn test_no_listings() {
+        assert!(compute(&[], now()).is_empty());
+    }
+
+    #[test]
+    fn test_single_listing() {
+        let niches = compute(&[listing("Casa", Some("Loja A"), 100.0, 30)], now());
+        assert_eq!(niches.len(), 1);
+        let casa = &niches[0];
+
+        assert_eq!(casa.products, 1);
+        assert_eq!(casa.sellers, 1);
+        assert_close(casa.avg_price, 100.0);
+        assert_close(casa.price_dispersion, 0.0);
+        assert_eq!(casa.new_listing_rate, Some(0.0));
+        // One price says nothing about how uniform the prices are
+        assert_close(
+            casa.score,
+            100.0 * (SELLER_WEIGHT * seller_pressure(1.0) + PRICE_WEIGHT * UNKNOWN_PRESSURE),
+        );
+
+        // A niche first seen within the window has no entry rate yet
+        let niches = compute(&[listing("Casa", Some("Loja A"), 100.0, 2)], now());
+        assert_eq!(niches[0].new_listing_rate, None);
+        assert_close(
+            niches[0].score,
+            100.0
+                * (SELLER_WEIGHT * seller_pressure(1.0)
+                    + PRICE_WEIGHT * UNKNOWN_PRESSURE
+                    + ENTRY_WEIGHT * UNKNOWN_PRESSURE),
+        );
+    }
+
+    #[test]
+    fn test_niches_scored_apart() {
+        let listings = [
+            listing("Beleza", Some("Loja A"), 10.0, 30),
+            listing("Casa", Some("Loja C"), 80.0, 30),
+            listing("Beleza", Some("Loja B"), 20.0, 20),
+            listing("Beleza", Some("Loja A"), 30.0, 2),
+            listing("Beleza", None, 40.0, 1),
+        ];
+        let niches = compute(&listings, now());
+        assert_eq!(niches.len(), 2);
+        let beleza = &niches[0];
+        assert_eq!(beleza.niche, "Beleza");
+        assert_eq!(niches[1].niche, "Casa");
+
+        assert_eq!(beleza.products, 4);
+        // Listings without a seller don't count as one
+        assert_eq!(beleza.sellers, 2);
+        assert_close(beleza.avg_price, 25.0);
+        let dispersion = 125f64.sqrt() / 25.0;
+        assert_close(beleza.price_dispersion, dispersion);
+        assert_eq!(beleza.new_listing_rate, Some(0.5));
+        assert_close(
+            beleza.score,
+            100.0
+                * (SELLER_WEIGHT * seller_pressure(2.0)
+                    + PRICE_WEIGHT * (1.0 - dispersion)
+                    + ENTRY_WEIGHT * 1.0),
+        );
+        assert_eq!(beleza.computed_at, now().to_rfc3339());
+    }
+}
@@ -0,0 +1,89 @@
+// Scheduled Scraping
+// Repeats category scrapes on an interval. Each schedule has its own
+// categories and runs as a regular scraper run, queued behind any other
+// browser job and saved, reported and synced like one. Schedules are kept in
+// the database, so they carry on after a restart.
+
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::commands;
+use crate::database;
+use crate::models::ScrapeSchedule;
+use crate::{JobState, ScraperState};
+
+/// A full category scrape more often than this gets the profile flagged
+pub const MIN_INTERVAL_MINUTES: u32 = 15;
+
+/// How often the schedules are checked for due runs
+const TICK: Duration = Duration::from_secs(60);
+
+/// Run the due schedules in the background for the lifetime of the app
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            match run_due(&app).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Scheduled scrapes run: {}", count),
+                Err(e) => log::warn!("Scheduled scrapes failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Run the schedules that are due, one after the other. Returns how many
+/// succeeded.
+pub async fn run_due(app: &AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let due = database::get_due_scrape_schedules(&db_path, &chrono::Utc::now().to_rfc3339())
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut succeeded = 0;
+    for schedule in &due {
+        let result = run(app, &app_dir, schedule).await;
+        match &result {
+            Ok(count) => {
+                log::info!("Scheduled scrape {}: {} products", schedule.name, count);
+                succeeded += 1;
+            }
+            Err(e) => log::warn!("Scheduled scrape {} failed: {}", schedule.name, e),
+        }
+
+        // Failed runs wait for the next interval too
+        let ran_at = chrono::Utc::now();
+        let next = ran_at + chrono::Duration::minutes(schedule.interval_minutes as i64);
+        database::mark_scrape_schedule_run(
+            &db_path,
+            &schedule.id,
+            &ran_at.to_rfc3339(),
+            &next.to_rfc3339(),
+            result.as_ref().err().map(String::as_str),
+        )
+        .ok();
+    }
+
+    Ok(succeeded)
+}
+
+async fn run(app: &AppHandle, app_dir: &Path, schedule: &ScrapeSchedule) -> Result<usize, String> {
+    let mut config = commands::load_settings(app_dir).scraper;
+    config.categories = schedule.categories.clone();
+
+    let state = app.state::<ScraperState>();
+    let jobs = app.state::<JobState>();
+    commands::queue_scrape(
+        app.clone(),
+        config,
+        format!("Agendamento: {}", schedule.name),
+        &state,
+        &jobs,
+    )
+    .await
+    .map(|products| products.len())
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,77 @@
+// Action Log Module
+// Records every navigation, scroll, click and wait of a run, so the
+// behaviour leading up to a block can be replayed afterwards
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::database;
+use crate::models::RunAction;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionKind {
+    Navigate,
+    Type,
+    Scroll,
+    Click,
+    Wait,
+    Pause,
+    Mouse,
+    Blocked,
+}
+
+impl ActionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Navigate => "navigate",
+            ActionKind::Type => "type",
+            ActionKind::Scroll => "scroll",
+            ActionKind::Click => "click",
+            ActionKind::Wait => "wait",
+            ActionKind::Pause => "pause",
+            ActionKind::Mouse => "mouse",
+            ActionKind::Blocked => "blocked",
+        }
+    }
+}
+
+pub struct ActionLog {
+    run_id: String,
+    /// Without a database the actions only go to the debug log
+    db_path: Option<PathBuf>,
+    started: Instant,
+}
+
+impl ActionLog {
+    pub fn new(run_id: String, db_path: Option<PathBuf>) -> Self {
+        Self {
+            run_id,
+            db_path,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn record(&self, kind: ActionKind, url: &str, detail: impl Into<String>) {
+        let action = RunAction {
+            run_id: self.run_id.clone(),
+            action: kind.as_str().to_string(),
+            url: url.to_string(),
+            detail: detail.into(),
+            elapsed_ms: self.started.elapsed().as_millis() as i64,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        log::debug!(
+            "[{}] {} {} {}",
+            action.elapsed_ms,
+            action.action,
+            action.url,
+            action.detail
+        );
+
+        if let Some(db_path) = &self.db_path {
+            if let Err(e) = database::add_run_action(db_path, &action) {
+                log::warn!("Failed to record scraper action: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// Affiliate Center Module
+// Reads commission rates from the TikTok Shop affiliate center using the
+// logged-in browser profile
+
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::Page;
+use rand::Rng;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use super::antibot::AntiDetection;
+use super::browser::BrowserManager;
+
+const AFFILIATE_PRODUCT_URL: &str = "https://affiliate.tiktok.com/product/detail?product_id=";
+
+/// Commission terms of a single product
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommissionInfo {
+    /// Commission in percent (15.0 = 15%)
+    pub commission_rate: Option<f64>,
+    pub open_collaboration: Option<bool>,
+}
+
+pub struct AffiliateCenter {
+    browser: BrowserManager,
+    antibot: AntiDetection,
+}
+
+impl AffiliateCenter {
+    /// The profile must be the one the user logged into the affiliate center with
+    pub fn new(user_data_dir: PathBuf, headless: bool) -> Self {
+        Self {
+            browser: BrowserManager::new(headless).with_user_data(user_data_dir),
+            antibot: AntiDetection::new(),
+        }
+    }
+
+    pub async fn open(&self) -> Result<Page> {
+        self.browser
+            .start(None)
+            .await
+            .context("Failed to start browser")?;
+
+        let page = self.browser.new_page().await?;
+        let fingerprint = self.antibot.generate_fingerprint();
+        self.antibot
+            .inject_stealth_scripts(&page, Some(&fingerprint))
+            .await?;
+
+        Ok(page)
+    }
+
+    pub async fn close(&self) -> Result<()> {
+        self.browser.stop().await
+    }
+
+    /// Open the affiliate page of a product and read its commission terms
+    pub async fn fetch_commission(&self, page: &Page, tiktok_id: &str) -> Result<CommissionInfo> {
+        let url = format!("{}{}", AFFILIATE_PRODUCT_URL, tiktok_id);
+        page.goto(&url)
+            .await
+            .context("Failed to open affiliate page")?;
+
+        let delay = rand::thread_rng().gen_range(2000..=4000);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+
+        let current_url = page.url().await?.unwrap_or_default();
+        if current_url.contains("login") || current_url.contains("passport") {
+            return Err(anyhow!(
+                "Affiliate center session expired. Log in again in the scraper browser."
+            ));
+        }
+
+        let script = r#"
+            (() => {
+                const state = window.__INITIAL_STATE__;
+                if (state) {
+                    const detail = state.productDetail || state.product || state.affiliateProduct;
+                    if (detail) return JSON.stringify(detail.product || detail);
+                }
+                return null;
+            })()
+        "#;
+
+        let data = page
+            .evaluate(script)
+            .await?
+            .value()
+            .and_then(|v| serde_json::from_value::<String>(v.clone()).ok())
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .context("Commission data not found on affiliate page")?;
+
+        Ok(Self::parse_commission(&data))
+    }
+
+    pub fn parse_commission(data: &Value) -> CommissionInfo {
+        let commission_rate = data
+            .get("commissionRate")
+            .or_else(|| data.get("commission_rate"))
+            .or_else(|| data.get("commission"))
+            .and_then(Self::parse_rate);
+
+        let open_collaboration = data
+            .get("openCollaboration")
+            .or_else(|| data.get("open_collaboration"))
+            .or_else(|| data.get("isOpenCollab"))
+            .and_then(|v| {
+                v.as_bool()
+                    .or_else(|| v.as_i64().map(|n| n != 0))
+                    .or_else(|| v.as_str().map(|s| s == "1" || s.eq_ignore_ascii_case("true")))
+            });
+
+        CommissionInfo {
+            commission_rate,
+            open_collaboration,
+        }
+    }
+
+    /// Normalize "15%", 0.15, 15 and 1500 (basis points) to 15.0
+    fn parse_rate(value: &Value) -> Option<f64> {
+        let rate = if let Some(num) = value.as_f64() {
+            num
+        } else if let Some(text) = value.as_str() {
+            let cleaned: String = text
+                .chars()
+                .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+                .collect();
+            let rate: f64 = cleaned.replace(',', ".").parse().ok()?;
+            if text.contains('%') {
+                return Some(rate);
+            }
+            rate
+        } else {
+            return None;
+        };
+
+        if rate < 0.0 {
+            None
+        } else if rate < 1.0 {
+            Some(rate * 100.0)
+        } else if rate > 100.0 {
+            Some(rate / 100.0)
+        } else {
+            Some(rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_commission() {
+        let cases = [
+            (json!({ "commissionRate": "15%" }), Some(15.0)),
+            (json!({ "commissionRate": "12,5%" }), Some(12.5)),
+            (json!({ "commission_rate": 0.2 }), Some(20.0)),
+            (json!({ "commission_rate": 8 }), Some(8.0)),
+            (json!({ "commission": 1500 }), Some(15.0)),
+            (json!({ "commission": "n/a" }), None),
+            (json!({}), None),
+        ];
+
+        for (data, expected) in cases {
+            assert_eq!(
+                AffiliateCenter::parse_commission(&data).commission_rate,
+                expected,
+                "{}",
+                data
+            );
+        }
+
+        let info = AffiliateCenter::parse_commission(&json!({ "openCollaboration": 1 }));
+        assert_eq!(info.open_collaboration, Some(true));
+        let info = AffiliateCenter::parse_commission(&json!({ "open_collaboration": false }));
+        assert_eq!(info.open_collaboration, Some(false));
+    }
+}
@@ -0,0 +1,731 @@
+// AliExpress Scraper
+// AliExpress storefront of the configured region (pt.aliexpress.com for
+// Brazil) for the same categories as TikTok Shop runs. Category names the
+// app uses for its niches ("beleza", "eletrônicos", ...) map to AliExpress
+// category listings, other names are keyword searches and "trending" is a
+// search sorted by orders. Products come from the captured search API, the
+// item list embedded in the page, or the rendered cards, located with the
+// selectors in selectors/aliexpress.json. Prices and sales counts arrive as
+// numbers or as formatted text ("R$ 12,34", "1.000+ vendidos") and are
+// normalized to the same values as TikTok Shop products.
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use super::intercept::CapturedResponse;
+use super::pagination;
+use super::parser::{NumberLocale, TikTokParser};
+use super::storefront::{Storefront, StorefrontScraper};
+use super::wait::WaitCondition;
+use crate::config::ScraperConfig;
+use crate::models::{MarketplaceAccess, Product, ProductSource};
+
+/// (region code, storefront host, currency)
+const REGIONS: &[(&str, &str, &str)] = &[
+    ("BR", "pt.aliexpress.com", "BRL"),
+    ("PT", "pt.aliexpress.com", "EUR"),
+    ("MX", "es.aliexpress.com", "MXN"),
+    ("ES", "es.aliexpress.com", "EUR"),
+    ("CO", "es.aliexpress.com", "COP"),
+    ("CL", "es.aliexpress.com", "CLP"),
+    ("FR", "fr.aliexpress.com", "EUR"),
+    ("DE", "de.aliexpress.com", "EUR"),
+    ("IT", "it.aliexpress.com", "EUR"),
+    ("US", "www.aliexpress.com", "USD"),
+    ("GB", "www.aliexpress.com", "GBP"),
+];
+
+const INTERNATIONAL: (&str, &str, &str) = ("", "www.aliexpress.com", "USD");
+
+/// App category names (lowercase, with and without accents) of each
+/// AliExpress category: (names, category id, URL slug)
+const CATEGORIES: &[(&[&str], u64, &str)] = &[
+    (
+        &["eletrônicos", "eletronicos", "electronics", "electrónica"],
+        44,
+        "consumer-electronics",
+    ),
+    (
+        &["celulares", "celular", "phones", "celulares e telefonia"],
+        509,
+        "cellphones-telecommunications",
+    ),
+    (
+        &["informática", "informatica", "computers", "computer"],
+        7,
+        "computer-office",
+    ),
+    (
+        &["casa", "casa e jardim", "casa e decoração", "home", "hogar"],
+        15,
+        "home-garden",
+    ),
+    (
+        &[
+            "beleza",
+            "beleza e saúde",
+            "beleza e saude",
+            "beauty",
+            "belleza",
+        ],
+        66,
+        "beauty-health",
+    ),
+    (&["brinquedos", "toys", "juguetes"], 26, "toys-hobbies"),
+    (
+        &["esportes", "esporte", "sports", "deportes"],
+        18,
+        "sports-entertainment",
+    ),
+    (&["bolsas", "malas", "bags"], 1524, "luggage-bags"),
+    (
+        &["joias", "acessórios", "acessorios", "jewelry"],
+        36,
+        "jewelry-accessories",
+    ),
+    (
+        &["moda feminina", "roupas femininas", "women"],
+        100003109,
+        "women-clothing",
+    ),
+    (
+        &["moda masculina", "roupas masculinas", "men"],
+        100003070,
+        "men-clothing",
+    ),
+    (&["calçados", "calcados", "shoes"], 322, "shoes"),
+    (
+        &["bebês", "bebes", "mãe e bebê", "baby", "kids"],
+        1501,
+        "mother-kids",
+    ),
+    (
+        &["automotivo", "carros", "automotive"],
+        34,
+        "automobiles-motorcycles",
+    ),
+];
+
+/// Item id in card links: `/item/<id>.html`
+static ITEM_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/item/(\d+)\.html").expect("valid regex"));
+
+/// CSS selectors and page markers of AliExpress listings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliexpressSelectors {
+    /// Product card links
+    pub card: String,
+    pub title: String,
+    pub price: String,
+    pub original_price: String,
+    pub sales: String,
+    pub rating: String,
+    pub image: String,
+    pub store: String,
+    /// `window` paths of the embedded item list, tried in order
+    #[serde(default)]
+    pub state_roots: Vec<String>,
+    /// Path or HTML fragments of the slider captcha and block pages
+    #[serde(default)]
+    pub block_markers: Vec<String>,
+}
+
+impl AliexpressSelectors {
+    /// Selectors shipped with the app
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("selectors/aliexpress.json"))
+            .expect("valid bundled AliExpress selectors")
+    }
+}
+
+/// Category id and URL slug of an app category name, if it maps to one
+pub fn category_id(category: &str) -> Option<(u64, &'static str)> {
+    let name = category.trim().to_lowercase();
+    CATEGORIES
+        .iter()
+        .find(|(names, _, _)| names.contains(&name.as_str()))
+        .map(|&(_, id, slug)| (id, slug))
+}
+
+/// URLs of the AliExpress storefront of a region
+#[derive(Debug, Clone)]
+pub struct AliexpressUrls {
+    host: &'static str,
+    currency: &'static str,
+}
+
+impl AliexpressUrls {
+    /// Storefront of a region code (ISO 3166, e.g. "BR"); unknown regions
+    /// use the international one, in dollars
+    pub fn for_region(region: &str) -> Self {
+        let code = region.trim().to_uppercase();
+        let &(_, host, currency) = REGIONS
+            .iter()
+            .find(|(c, _, _)| *c == code)
+            .unwrap_or(&INTERNATIONAL);
+        Self { host, currency }
+    }
+
+    pub fn base(&self) -> String {
+        format!("https://{}", self.host)
+    }
+
+    pub fn currency(&self) -> &'static str {
+        self.currency
+    }
+
+    /// Page `page` (from 0) of a category: its AliExpress category listing,
+    /// a search sorted by orders for "trending", a keyword search, or a
+    /// listing URL
+    pub fn category_page(&self, category: &str, page: u32) -> String {
+        let category = category.trim();
+        let url = if category.starts_with("http") || category.starts_with("file") {
+            category.to_string()
+        } else if category.eq_ignore_ascii_case("trending") {
+            format!(
+                "{}/w/wholesale-.html?SortType=total_tranpro_desc",
+                self.base()
+            )
+        } else if let Some((id, slug)) = category_id(category) {
+            format!("{}/category/{}/{}.html", self.base(), id, slug)
+        } else {
+            let keyword = category.split_whitespace().collect::<Vec<_>>().join("-");
+            let mut url = reqwest::Url::parse(&self.base()).expect("valid storefront URL");
+            if let Ok(mut segments) = url.path_segments_mut() {
+                segments
+                    .clear()
+                    .push("w")
+                    .push(&format!("wholesale-{}.html", keyword));
+            }
+            url.to_string()
+        };
+
+        // Result pages are numbered from 1
+        if page == 0 {
+            url
+        } else {
+            pagination::page_url(&url, "page", page + 1)
+        }
+    }
+
+    pub fn product_url(&self, item_id: &str) -> String {
+        format!("{}/item/{}.html", self.base(), item_id)
+    }
+
+    /// Absolute URL of an image, which listings give protocol-relative
+    pub fn image_url(&self, image: &str) -> String {
+        match image.strip_prefix("//") {
+            Some(rest) => format!("https://{}", rest),
+            None => image.to_string(),
+        }
+    }
+}
+
+/// Reads AliExpress products from API responses, page state and listings
+pub struct AliexpressParser {
+    urls: AliexpressUrls,
+    locale: NumberLocale,
+    selectors: AliexpressSelectors,
+}
+
+impl AliexpressParser {
+    pub fn new(urls: AliexpressUrls, locale: NumberLocale, selectors: AliexpressSelectors) -> Self {
+        Self {
+            urls,
+            locale,
+            selectors,
+        }
+    }
+
+    /// Products of the captured responses, in order; other JSON is skipped
+    pub fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        responses
+            .iter()
+            .filter_map(|response| serde_json::from_str::<Value>(&response.body).ok())
+            .flat_map(|data| self.parse_items(&data))
+            .collect()
+    }
+
+    /// Products of a search response or item list, wherever the items are
+    /// nested (`data.result.mods.itemList.content`, `itemList.content`, ...)
+    pub fn parse_items(&self, data: &Value) -> Vec<Product> {
+        let mut items = Vec::new();
+        collect_items(data, &mut items);
+
+        let mut products: Vec<Product> = Vec::new();
+        for product in items.into_iter().filter_map(|item| self.parse_item(item)) {
+            if !products.iter().any(|p| p.tiktok_id == product.tiktok_id) {
+                products.push(product);
+            }
+        }
+        products
+    }
+
+    fn parse_item(&self, item: &Value) -> Option<Product> {
+        let item_id = text_or_number(&item["productId"])?;
+        let title = item["title"]["displayTitle"]
+            .as_str()
+            .or_else(|| item["title"].as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())?;
+
+        let mut product = self.product(&item_id, title);
+        let sale = &item["prices"]["salePrice"];
+        if let Some(currency) = sale["currencyCode"].as_str() {
+            product.currency = currency.to_string();
+        }
+        let locale = NumberLocale::from_currency(&product.currency).unwrap_or(self.locale);
+
+        product.price = normalize_price(sale, locale).unwrap_or(0.0);
+        product.original_price = normalize_price(&item["prices"]["originalPrice"], locale)
+            .filter(|op| *op > product.price);
+        product.is_on_sale = product.original_price.is_some();
+        product.sales_count = normalize_sales(&item["trade"]);
+        product.product_rating = text_or_number(&item["evaluation"]["starRating"])
+            .and_then(|rating| rating.parse::<f64>().ok())
+            .filter(|rating| *rating > 0.0 && *rating <= 5.0);
+        product.seller_name = item["store"]["storeName"].as_str().map(str::to_string);
+        product.has_free_shipping = mentions_free_shipping(&item["sellingPoints"].to_string());
+
+        product.image_url = item["image"]["imgUrl"]
+            .as_str()
+            .map(|image| self.urls.image_url(image));
+        product.images = product.image_url.iter().cloned().collect();
+
+        Some(product)
+    }
+
+    /// Products of the rendered product cards
+    pub fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        let document = Html::parse_document(html);
+        let selector = |css: &str| Selector::parse(css).ok();
+        let Some(cards) = selector(&self.selectors.card) else {
+            return Vec::new();
+        };
+        let field = |card: &ElementRef, css: &str| -> Option<String> {
+            let selector = selector(css)?;
+            card.select(&selector)
+                .map(|element| element.text().collect::<Vec<_>>().join(" "))
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .find(|text| !text.is_empty())
+        };
+        let image_selector = selector(&self.selectors.image);
+
+        let mut products: Vec<Product> = Vec::new();
+        for card in document.select(&cards) {
+            let Some(item_id) = card
+                .value()
+                .attr("href")
+                .and_then(|href| ITEM_ID_RE.captures(href))
+                .map(|ids| ids[1].to_string())
+            else {
+                continue;
+            };
+            if products
+                .iter()
+                .any(|p| p.tiktok_id == format!("aliexpress-{}", item_id))
+            {
+                continue;
+            }
+
+            let image = image_selector
+                .as_ref()
+                .and_then(|images| card.select(images).next());
+            let title = field(&card, &self.selectors.title).or_else(|| {
+                image
+                    .and_then(|img| img.value().attr("alt"))
+                    .map(str::trim)
+                    .filter(|alt| !alt.is_empty())
+                    .map(str::to_string)
+            });
+            let Some(title) = title else {
+                continue;
+            };
+
+            let mut product = self.product(&item_id, &title);
+            let price = |css: &str| {
+                field(&card, css)
+                    .map(|text| TikTokParser::parse_price_text(&text, self.locale))
+                    .filter(|price| *price > 0.0)
+            };
+            product.price = price(&self.selectors.price).unwrap_or(0.0);
+            product.original_price =
+                price(&self.selectors.original_price).filter(|op| *op > product.price);
+            product.is_on_sale = product.original_price.is_some();
+            product.sales_count = field(&card, &self.selectors.sales)
+                .map(|text| TikTokParser::parse_sales_text(&text))
+                .unwrap_or(0);
+            product.product_rating = field(&card, &self.selectors.rating)
+                .and_then(|text| text.replace(',', ".").parse::<f64>().ok())
+                .filter(|rating| *rating > 0.0 && *rating <= 5.0);
+            product.seller_name = field(&card, &self.selectors.store);
+            product.has_free_shipping = mentions_free_shipping(&card.html());
+            product.image_url = image
+                .and_then(|img| img.value().attr("src"))
+                .filter(|src| !src.is_empty())
+                .map(|src| self.urls.image_url(src));
+            product.images = product.image_url.iter().cloned().collect();
+
+            products.push(product);
+        }
+        products
+    }
+
+    /// Script returning the first embedded item list found as JSON text
+    pub fn state_script(&self) -> Option<String> {
+        if self.selectors.state_roots.is_empty() {
+            return None;
+        }
+        let roots = serde_json::to_string(&self.selectors.state_roots).ok()?;
+        Some(format!(
+            r#"
+            (() => {{
+                for (const path of {}) {{
+                    try {{
+                        const node = path.split('.').reduce((o, k) => o && o[k], window);
+                        if (node) return JSON.stringify(node);
+                    }} catch (e) {{}}
+                }}
+                return null;
+            }})()
+        "#,
+            roots
+        ))
+    }
+
+    pub fn is_blocked(&self, path: &str, content: &str) -> bool {
+        self.selectors
+            .block_markers
+            .iter()
+            .any(|marker| path.contains(marker.as_str()) || content.contains(marker.as_str()))
+    }
+
+    /// Product with its id and title; everything else still to be read
+    fn product(&self, item_id: &str, title: &str) -> Product {
+        let now = chrono::Utc::now().to_rfc3339();
+        Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: format!("aliexpress-{}", item_id),
+            title: title.to_string(),
+            description: None,
+            price: 0.0,
+            original_price: None,
+            currency: self.urls.currency().to_string(),
+            category: None,
+            subcategory: None,
+            seller_name: None,
+            seller_rating: None,
+            product_rating: None,
+            reviews_count: 0,
+            sales_count: 0,
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: None,
+            images: vec![],
+            video_url: None,
+            product_url: self.urls.product_url(item_id),
+            affiliate_url: None,
+            has_free_shipping: false,
+            is_trending: false,
+            is_on_sale: false,
+            in_stock: true,
+            stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now,
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Aliexpress,
+        }
+    }
+}
+
+/// Item objects under `value`: any object with a product id and a title
+fn collect_items<'a>(value: &'a Value, items: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("productId") && map.contains_key("title") {
+                items.push(value);
+            } else {
+                map.values().for_each(|child| collect_items(child, items));
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|child| collect_items(child, items)),
+        _ => {}
+    }
+}
+
+fn text_or_number(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Price of a `{ minPrice, formattedPrice }` object: the number when there
+/// is one, the formatted text read in `locale` otherwise
+pub fn normalize_price(price: &Value, locale: NumberLocale) -> Option<f64> {
+    price["minPrice"]
+        .as_f64()
+        .or_else(|| {
+            price["formattedPrice"]
+                .as_str()
+                .map(|text| TikTokParser::parse_price_text(text, locale))
+        })
+        .filter(|price| *price > 0.0)
+}
+
+/// Sales of a `{ tradeDesc, realTradeCount }` object, e.g. "1.000+ vendidos"
+pub fn normalize_sales(trade: &Value) -> i32 {
+    trade["realTradeCount"]
+        .as_i64()
+        .map(|count| count as i32)
+        .or_else(|| {
+            trade["tradeDesc"]
+                .as_str()
+                .map(TikTokParser::parse_sales_text)
+        })
+        .unwrap_or(0)
+}
+
+fn mentions_free_shipping(text: &str) -> bool {
+    let text = text.to_lowercase();
+    [
+        "frete grátis",
+        "free shipping",
+        "envío gratis",
+        "envio gratis",
+    ]
+    .iter()
+    .any(|marker| text.contains(marker))
+}
+
+/// AliExpress storefront of the configured region
+pub struct Aliexpress {
+    urls: AliexpressUrls,
+    parser: AliexpressParser,
+}
+
+impl Aliexpress {
+    pub fn new(config: &ScraperConfig) -> Self {
+        let urls = AliexpressUrls::for_region(&config.region);
+        let locale = NumberLocale::from_currency(urls.currency())
+            .unwrap_or_else(|| NumberLocale::from_region(&config.region));
+        Self {
+            parser: AliexpressParser::new(urls.clone(), locale, AliexpressSelectors::bundled()),
+            urls,
+        }
+    }
+}
+
+impl Storefront for Aliexpress {
+    fn marketplace(&self) -> MarketplaceAccess {
+        MarketplaceAccess::Aliexpress
+    }
+
+    fn start_key(&self) -> &'static str {
+        "scraper.starting_aliexpress"
+    }
+
+    fn category_page(&self, category: &str, page: u32) -> String {
+        self.urls.category_page(category, page)
+    }
+
+    fn wait_conditions(&self) -> Vec<WaitCondition> {
+        vec![
+            WaitCondition::Selector {
+                selector: self.parser.selectors.card.clone(),
+                timeout_ms: 10000,
+            },
+            WaitCondition::NetworkIdle {
+                idle_ms: 1000,
+                timeout_ms: 10000,
+            },
+        ]
+    }
+
+    fn is_blocked(&self, path: &str, content: &str) -> bool {
+        self.parser.is_blocked(path, content)
+    }
+
+    fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        self.parser.parse_api_responses(responses)
+    }
+
+    fn state_script(&self) -> Option<String> {
+        self.parser.state_script()
+    }
+
+    fn parse_state(&self, state: &Value) -> Vec<Product> {
+        self.parser.parse_items(state)
+    }
+
+    fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        self.parser.parse_listing_html(html)
+    }
+}
+
+pub type AliexpressScraper = StorefrontScraper<Aliexpress>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> AliexpressParser {
+        AliexpressParser::new(
+            AliexpressUrls::for_region("BR"),
+            NumberLocale::Br,
+            AliexpressSelectors::bundled(),
+        )
+    }
+
+    #[test]
+    fn test_category_urls() {
+        let urls = AliexpressUrls::for_region("br");
+        assert_eq!(
+            urls.category_page("fone bluetooth", 0),
+            "https://pt.aliexpress.com/w/wholesale-fone-bluetooth.html"
+        );
+        assert_eq!(
+            urls.category_page("Beleza", 1),
+            "https://pt.aliexpress.com/category/66/beauty-health.html?page=2"
+        );
+        assert_eq!(
+            urls.category_page("trending", 0),
+            "https://pt.aliexpress.com/w/wholesale-.html?SortType=total_tranpro_desc"
+        );
+        assert_eq!(urls.currency(), "BRL");
+        assert_eq!(
+            AliexpressUrls::for_region("JP").product_url("1005"),
+            "https://www.aliexpress.com/item/1005.html"
+        );
+    }
+
+    #[test]
+    fn test_category_mapping() {
+        assert_eq!(
+            category_id("eletrônicos"),
+            Some((44, "consumer-electronics"))
+        );
+        assert_eq!(
+            category_id(" Eletronicos "),
+            Some((44, "consumer-electronics"))
+        );
+        assert_eq!(category_id("fone bluetooth"), None);
+    }
+
+    #[test]
+    fn test_parse_items() {
+        let data = serde_json::json!({
+            "data": { "result": { "mods": { "itemList": { "content": [
+                {
+                    "productId": "1005006",
+                    "title": { "displayTitle": "Fone Bluetooth TWS" },
+                    "prices": {
+                        "salePrice": { "currencyCode": "BRL", "minPrice": 49.9, "formattedPrice": "R$ 49,90" },
+                        "originalPrice": { "formattedPrice": "R$ 99,90" }
+                    },
+                    "trade": { "tradeDesc": "1.000+ vendidos" },
+                    "evaluation": { "starRating": 4.7 },
+                    "image": { "imgUrl": "//ae01.alicdn.com/kf/abc.jpg" },
+                    "store": { "storeName": "Loja Oficial" },
+                    "sellingPoints": [{ "tagContent": { "tagText": "Frete grátis" } }]
+                },
+                { "productId": 1005007, "title": { "displayTitle": "" } }
+            ]}}}}
+        });
+
+        let products = parser().parse_items(&data);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.tiktok_id, "aliexpress-1005006");
+        assert_eq!(product.marketplace, MarketplaceAccess::Aliexpress);
+        assert_eq!(product.price, 49.9);
+        assert_eq!(product.original_price, Some(99.9));
+        assert!(product.is_on_sale);
+        assert_eq!(product.sales_count, 1000);
+        assert_eq!(product.product_rating, Some(4.7));
+        assert_eq!(product.seller_name.as_deref(), Some("Loja Oficial"));
+        assert!(product.has_free_shipping);
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://ae01.alicdn.com/kf/abc.jpg")
+        );
+        assert_eq!(
+            product.product_url,
+            "https://pt.aliexpress.com/item/1005006.html"
+        );
+    }
+
+    #[test]
+    fn test_normalization() {
+        let price = serde_json::json!({ "formattedPrice": "R$ 1.234,56" });
+        assert_eq!(normalize_price(&price, NumberLocale::Br), Some(1234.56));
+        let price = serde_json::json!({ "formattedPrice": "US $12.50" });
+        assert_eq!(normalize_price(&price, NumberLocale::Us), Some(12.5));
+
+        assert_eq!(
+            normalize_sales(&serde_json::json!({ "tradeDesc": "5 mil+ vendidos" })),
+            5000
+        );
+        assert_eq!(
+            normalize_sales(&serde_json::json!({ "realTradeCount": 321 })),
+            321
+        );
+        assert_eq!(normalize_sales(&Value::Null), 0);
+    }
+
+    #[test]
+    fn test_parse_listing_html() {
+        let html = r#"<html><body>
+            <a href="//pt.aliexpress.com/item/1005008.html?algo=x">
+                <img src="//ae01.alicdn.com/kf/card.jpg" alt="Mini Projetor">
+                <h3>Mini Projetor Portátil</h3>
+                <div class="multi--price-sale">R$ 189,90</div>
+                <div class="multi--price-original">R$ 379,80</div>
+                <span class="multi--trade">500+ vendidos</span>
+                <span class="multi--evaluation">4,8</span>
+            </a>
+            <a href="//pt.aliexpress.com/item/1005008.html">Mini Projetor Portátil</a>
+        </body></html>"#;
+
+        let products = parser().parse_listing_html(html);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.title, "Mini Projetor Portátil");
+        assert_eq!(product.price, 189.9);
+        assert_eq!(product.original_price, Some(379.8));
+        assert_eq!(product.sales_count, 500);
+        assert_eq!(product.product_rating, Some(4.8));
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://ae01.alicdn.com/kf/card.jpg")
+        );
+    }
+
+    #[test]
+    fn test_blocked_pages() {
+        let parser = parser();
+        assert!(parser.is_blocked("/punish", ""));
+        assert!(parser.is_blocked("/w/wholesale-fone.html", "<div id=\"baxia-punish\">"));
+        assert!(!parser.is_blocked("/w/wholesale-fone.html", "<html>fone</html>"));
+    }
+}
@@ -1,10 +1,19 @@
 // Anti-Detection Module
 // Browser fingerprint randomization and stealth techniques
 
-use anyhow::Result;
+use crate::database;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    SetUserAgentOverrideParams, UserAgentBrandVersion, UserAgentMetadata,
+};
 use chromiumoxide::Page;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Weeks between two Chrome milestones (major versions)
+const WEEKS_PER_MILESTONE: i64 = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fingerprint {
@@ -20,6 +29,120 @@ pub struct Fingerprint {
     pub color_depth: u8,
     pub device_memory: u8,
     pub hardware_concurrency: u8,
+    /// When the browser version was last set (RFC 3339), used to age the fingerprint
+    #[serde(default)]
+    pub versioned_at: Option<String>,
+}
+
+impl Fingerprint {
+    /// Full Chrome version from the user agent, e.g. "124.0.0.0"
+    pub fn chrome_version(&self) -> Option<&str> {
+        let version = self.user_agent.split("Chrome/").nth(1)?;
+        version.split_whitespace().next()
+    }
+
+    /// Update the Chrome version the way an auto-updating browser would:
+    /// a patch release per week, a new milestone every four weeks.
+    /// Returns true when the fingerprint changed.
+    pub fn age(&mut self, now: DateTime<Utc>) -> bool {
+        let versioned_at = self
+            .versioned_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+        let Some(versioned_at) = versioned_at else {
+            self.versioned_at = Some(now.to_rfc3339());
+            return true;
+        };
+
+        let weeks = (now - versioned_at).num_weeks();
+        let Some(version) = self.chrome_version().map(str::to_string) else {
+            return false;
+        };
+        let parts: Vec<u32> = version.split('.').filter_map(|p| p.parse().ok()).collect();
+        if weeks < 1 || parts.len() != 4 {
+            return false;
+        }
+
+        let milestones = weeks / WEEKS_PER_MILESTONE;
+        let patches = weeks % WEEKS_PER_MILESTONE;
+        let (mut major, mut build, mut patch) = (parts[0], parts[2], parts[3]);
+        major += milestones as u32;
+        // Reduced user agents ("124.0.0.0") only ever expose the major version
+        if build != 0 {
+            if milestones > 0 {
+                build += 80 * milestones as u32;
+                patch = 0;
+            }
+            patch += patches as u32 * 2;
+        }
+
+        let aged = format!("{}.{}.{}.{}", major, parts[1], build, patch);
+        self.user_agent = self
+            .user_agent
+            .replace(&format!("Chrome/{}", version), &format!("Chrome/{}", aged));
+        self.versioned_at = Some((versioned_at + Duration::weeks(weeks)).to_rfc3339());
+        true
+    }
+
+    /// navigator.languages for the locale, most preferred first
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages = vec![self.locale.clone()];
+        if let Some((base, _)) = self.locale.split_once('-') {
+            languages.push(base.to_string());
+        }
+        for fallback in ["en-US", "en"] {
+            if !languages.iter().any(|l| l == fallback) {
+                languages.push(fallback.to_string());
+            }
+        }
+        languages
+    }
+
+    /// Accept-Language header matching `languages()`
+    pub fn accept_language(&self) -> String {
+        self.languages()
+            .iter()
+            .enumerate()
+            .map(|(i, lang)| match i {
+                0 => lang.clone(),
+                _ => format!("{};q={:.1}", lang, 1.0 - i as f32 * 0.1),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Client Hints (Sec-CH-UA*, navigator.userAgentData) matching the user agent and platform
+    pub fn user_agent_metadata(&self) -> UserAgentMetadata {
+        let full_version = self.chrome_version().unwrap_or("124.0.0.0").to_string();
+        let major = full_version.split('.').next().unwrap_or("124").to_string();
+
+        let brands = |version: &str| {
+            vec![
+                UserAgentBrandVersion::new("Chromium", version),
+                UserAgentBrandVersion::new("Google Chrome", version),
+                UserAgentBrandVersion::new("Not-A.Brand", "99"),
+            ]
+        };
+
+        let (platform, platform_version) = match self.platform.as_str() {
+            "Win32" => ("Windows", "10.0.0"),
+            "MacIntel" => ("macOS", "14.4.0"),
+            _ => ("Linux", "6.5.0"),
+        };
+
+        UserAgentMetadata {
+            brands: Some(brands(&major)),
+            full_version_list: Some(brands(&full_version)),
+            platform: platform.to_string(),
+            platform_version: platform_version.to_string(),
+            architecture: "x86".to_string(),
+            model: String::new(),
+            mobile: false,
+            bitness: Some("64".to_string()),
+            wow64: Some(false),
+        }
+    }
 }
 
 pub struct AntiDetection;
@@ -71,9 +194,41 @@ impl AntiDetection {
             color_depth: if rng.gen_bool(0.5) { 24 } else { 32 },
             device_memory: *vec![4, 8, 16].get(rng.gen_range(0..3)).unwrap(),
             hardware_concurrency: *vec![4, 8, 12, 16].get(rng.gen_range(0..4)).unwrap(),
+            versioned_at: Some(Utc::now().to_rfc3339()),
         }
     }
 
+    /// Fingerprint of a session profile: the stored one, aged to today, or a
+    /// new one the first time. A returning profile keeps the same identity.
+    pub fn profile_fingerprint(&self, db_path: &Path, profile_id: &str) -> Result<Fingerprint> {
+        let stored = database::get_profile_fingerprint(db_path, profile_id)?
+            .and_then(|json| serde_json::from_str::<Fingerprint>(&json).ok());
+
+        match stored {
+            Some(mut fingerprint) => {
+                if fingerprint.age(Utc::now()) {
+                    Self::save_fingerprint(db_path, profile_id, &fingerprint)?;
+                }
+                Ok(fingerprint)
+            }
+            None => self.rotate_fingerprint(db_path, profile_id),
+        }
+    }
+
+    /// Replace the fingerprint of a profile with a fresh identity
+    pub fn rotate_fingerprint(&self, db_path: &Path, profile_id: &str) -> Result<Fingerprint> {
+        let fingerprint = self.generate_fingerprint();
+        Self::save_fingerprint(db_path, profile_id, &fingerprint)?;
+        log::info!("New fingerprint for profile {}", profile_id);
+        Ok(fingerprint)
+    }
+
+    fn save_fingerprint(db_path: &Path, profile_id: &str, fingerprint: &Fingerprint) -> Result<()> {
+        let json = serde_json::to_string(fingerprint)?;
+        database::save_profile_fingerprint(db_path, profile_id, &fingerprint.user_agent, &json)?;
+        Ok(())
+    }
+
     pub async fn inject_stealth_scripts(
         &self,
         page: &Page,
@@ -83,12 +238,14 @@ impl AntiDetection {
         page.evaluate(Self::get_stealth_script()).await?;
 
         if let Some(fp) = fingerprint {
+            self.apply_header_overrides(page, fp).await?;
+
             let script = format!(
                 r#"
                 Object.defineProperty(navigator, 'userAgent', {{ get: () => '{}' }});
                 Object.defineProperty(navigator, 'platform', {{ get: () => '{}' }});
                 Object.defineProperty(navigator, 'language', {{ get: () => '{}' }});
-                Object.defineProperty(navigator, 'languages', {{ get: () => ['{}', 'en-US'] }});
+                Object.defineProperty(navigator, 'languages', {{ get: () => {} }});
                 Object.defineProperty(screen, 'width', {{ get: () => {} }});
                 Object.defineProperty(screen, 'height', {{ get: () => {} }});
                 Object.defineProperty(navigator, 'hardwareConcurrency', {{ get: () => {} }});
@@ -104,7 +261,7 @@ impl AntiDetection {
                 fp.user_agent,
                 fp.platform,
                 fp.locale,
-                fp.locale,
+                serde_json::to_string(&fp.languages())?,
                 fp.screen_width,
                 fp.screen_height,
                 fp.hardware_concurrency,
@@ -118,6 +275,22 @@ impl AntiDetection {
         Ok(())
     }
 
+    /// Make the network layer agree with the fingerprint: User-Agent,
+    /// Sec-CH-UA brands/platform and Accept-Language are set over CDP, since
+    /// overriding navigator properties alone leaves the real values in headers
+    pub async fn apply_header_overrides(&self, page: &Page, fp: &Fingerprint) -> Result<()> {
+        let params = SetUserAgentOverrideParams {
+            user_agent: fp.user_agent.clone(),
+            accept_language: Some(fp.accept_language()),
+            platform: Some(fp.platform.clone()),
+            user_agent_metadata: Some(fp.user_agent_metadata()),
+        };
+        page.execute(params)
+            .await
+            .context("Failed to override user agent headers")?;
+        Ok(())
+    }
+
     fn get_stealth_script() -> &'static str {
         r#"
         // Override webdriver flag
@@ -193,3 +366,58 @@ impl Default for AntiDetection {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_hints_match_fingerprint() {
+        let mut fp = AntiDetection::new().generate_fingerprint();
+        fp.user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.6478.127 Safari/537.36".to_string();
+        fp.platform = "Win32".to_string();
+
+        assert_eq!(fp.chrome_version(), Some("126.0.6478.127"));
+
+        let metadata = fp.user_agent_metadata();
+        assert_eq!(metadata.platform, "Windows");
+        let brands = metadata.brands.unwrap();
+        assert!(brands
+            .iter()
+            .any(|b| b.brand == "Google Chrome" && b.version == "126"));
+        assert_eq!(
+            metadata.full_version_list.unwrap()[0].version,
+            "126.0.6478.127"
+        );
+
+        assert_eq!(fp.languages(), vec!["pt-BR", "pt", "en-US", "en"]);
+        assert_eq!(fp.accept_language(), "pt-BR,pt;q=0.9,en-US;q=0.8,en;q=0.7");
+    }
+
+    #[test]
+    fn test_fingerprint_aging() {
+        let issued = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut fp = AntiDetection::new().generate_fingerprint();
+        fp.user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.6367.60 Safari/537.36".to_string();
+        fp.versioned_at = Some(issued.to_rfc3339());
+
+        // Same week: unchanged
+        assert!(!fp.age(issued + Duration::days(3)));
+        assert_eq!(fp.chrome_version(), Some("124.0.6367.60"));
+
+        // Two weeks: patch releases only
+        assert!(fp.age(issued + Duration::days(15)));
+        assert_eq!(fp.chrome_version(), Some("124.0.6367.64"));
+
+        // Five more weeks: one milestone and a patch
+        assert!(fp.age(issued + Duration::weeks(7)));
+        assert_eq!(fp.chrome_version(), Some("125.0.6447.2"));
+
+        // Reduced user agent keeps the zeros
+        fp.user_agent = fp.user_agent.replace("125.0.6447.2", "125.0.0.0");
+        assert!(fp.age(issued + Duration::weeks(11)));
+        assert_eq!(fp.chrome_version(), Some("126.0.0.0"));
+    }
+}
@@ -0,0 +1,167 @@
+// Benchmark Module
+// Runs the parse pipeline over recorded fixtures (HTML pages and captured
+// API JSON) and measures throughput, per-stage timings and memory
+
+use anyhow::{anyhow, Context, Result};
+use scraper::Html;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+use super::intercept::CapturedResponse;
+use super::parser::TikTokParser;
+use crate::models::{BenchmarkReport, StageTiming};
+
+enum Fixture {
+    Html(PathBuf),
+    Api(PathBuf),
+}
+
+/// `.html`/`.htm` pages go through the DOM path, `.json` files are treated
+/// as captured API responses
+fn find_fixtures(dir: &Path) -> Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Cannot read fixture directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("html" | "htm") => fixtures.push(Fixture::Html(path)),
+            Some("json") => fixtures.push(Fixture::Api(path)),
+            _ => {}
+        }
+    }
+
+    if fixtures.is_empty() {
+        return Err(anyhow!("No .html or .json fixtures in {}", dir.display()));
+    }
+    Ok(fixtures)
+}
+
+#[derive(Default)]
+struct Stages(BTreeMap<&'static str, (Duration, u32)>);
+
+impl Stages {
+    fn time<T>(&mut self, stage: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        let entry = self.0.entry(stage).or_default();
+        entry.0 += started.elapsed();
+        entry.1 += 1;
+        result
+    }
+
+    fn into_timings(self) -> Vec<StageTiming> {
+        self.0
+            .into_iter()
+            .map(|(stage, (total, runs))| {
+                let total_ms = total.as_secs_f64() * 1000.0;
+                StageTiming {
+                    stage: stage.to_string(),
+                    total_ms,
+                    avg_ms: total_ms / runs.max(1) as f64,
+                    runs,
+                }
+            })
+            .collect()
+    }
+}
+
+struct MemoryProbe {
+    system: System,
+    pid: Option<sysinfo::Pid>,
+}
+
+impl MemoryProbe {
+    fn new() -> Self {
+        Self {
+            system: System::new(),
+            pid: sysinfo::get_current_pid().ok(),
+        }
+    }
+
+    /// Resident memory of this process in bytes
+    fn sample(&mut self) -> u64 {
+        let Some(pid) = self.pid else {
+            return 0;
+        };
+        self.system.refresh_process(pid);
+        self.system.process(pid).map_or(0, |p| p.memory())
+    }
+}
+
+/// Parse every fixture of `dir` `iterations` times
+pub fn run_benchmark(
+    parser: &TikTokParser,
+    dir: &Path,
+    iterations: u32,
+) -> Result<BenchmarkReport> {
+    let fixtures = find_fixtures(dir)?;
+    let iterations = iterations.max(1);
+
+    let mut memory = MemoryProbe::new();
+    let start_memory_bytes = memory.sample();
+    let mut peak_memory_bytes = start_memory_bytes;
+
+    let mut stages = Stages::default();
+    let mut products = 0usize;
+    let started = Instant::now();
+
+    for _ in 0..iterations {
+        for fixture in &fixtures {
+            let parsed = match fixture {
+                Fixture::Html(path) => {
+                    let html = stages.time("read", || std::fs::read_to_string(path))?;
+                    let document = stages.time("html_parse", || Html::parse_document(&html));
+                    stages.time("dom_extract", || parser.parse_document(&document))
+                }
+                Fixture::Api(path) => {
+                    let body = stages.time("read", || std::fs::read_to_string(path))?;
+                    let response = CapturedResponse {
+                        url: path.to_string_lossy().to_string(),
+                        body,
+                    };
+                    stages.time("api_parse", || {
+                        parser.parse_api_responses(std::slice::from_ref(&response))
+                    })
+                }
+            };
+            products += parsed.products.len();
+            peak_memory_bytes = peak_memory_bytes.max(memory.sample());
+        }
+    }
+
+    let elapsed = started.elapsed();
+    Ok(BenchmarkReport {
+        fixtures: fixtures.len() as u32,
+        iterations,
+        products: products as u32,
+        total_ms: elapsed.as_secs_f64() * 1000.0,
+        products_per_sec: products as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        stages: stages.into_timings(),
+        start_memory_bytes,
+        peak_memory_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_fixtures() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+        let report = run_benchmark(&TikTokParser::new(None), &dir, 2).unwrap();
+
+        assert_eq!(report.iterations, 2);
+        assert!(report.products >= 6);
+        let stages: Vec<&str> = report.stages.iter().map(|s| s.stage.as_str()).collect();
+        assert!(stages.contains(&"html_parse"));
+        assert!(stages.contains(&"dom_extract"));
+    }
+}
@@ -0,0 +1,161 @@
+// Request Blocking Module
+// Fails requests for fonts, trackers, ads and (optionally) images over CDP so
+// pages load faster. XHR/fetch calls are never matched, keeping product JSON intact.
+
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    EnableParams, EventRequestPaused, FailRequestParams, RequestPattern,
+};
+use chromiumoxide::cdp::browser_protocol::network::{ErrorReason, ResourceType};
+use chromiumoxide::Page;
+use futures::StreamExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Analytics and ad hosts loaded by the shop pages
+const TRACKER_URL_PATTERNS: &[&str] = &[
+    "*google-analytics.com*",
+    "*googletagmanager.com*",
+    "*doubleclick.net*",
+    "*googlesyndication.com*",
+    "*facebook.net*",
+    "*connect.facebook.com*",
+    "*analytics.tiktok.com*",
+    "*mon.tiktokv.com*",
+    "*/slardar/*",
+    "*/monitor_browser/*",
+];
+
+/// Which requests to block during scraping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockingOptions {
+    pub fonts: bool,
+    pub trackers: bool,
+    pub images: bool,
+}
+
+impl BlockingOptions {
+    pub fn is_empty(&self) -> bool {
+        !self.fonts && !self.trackers && !self.images
+    }
+
+    /// Fetch interception patterns; every request they match gets failed
+    pub fn request_patterns(&self) -> Vec<RequestPattern> {
+        let mut patterns = Vec::new();
+
+        let mut by_type = |resource_type: ResourceType| {
+            patterns.push(RequestPattern {
+                url_pattern: None,
+                resource_type: Some(resource_type),
+                request_stage: None,
+            });
+        };
+        if self.fonts {
+            by_type(ResourceType::Font);
+        }
+        if self.images {
+            by_type(ResourceType::Image);
+            by_type(ResourceType::Media);
+        }
+
+        if self.trackers {
+            patterns.extend(TRACKER_URL_PATTERNS.iter().map(|url| RequestPattern {
+                url_pattern: Some(url.to_string()),
+                resource_type: None,
+                request_stage: None,
+            }));
+        }
+
+        patterns
+    }
+}
+
+/// Active blocking on a page, stopped when dropped
+pub struct RequestBlocker {
+    blocked: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl RequestBlocker {
+    pub async fn start(page: &Page, options: BlockingOptions) -> Result<Self> {
+        let mut paused = page.event_listener::<EventRequestPaused>().await?;
+
+        page.execute(EnableParams {
+            patterns: Some(options.request_patterns()),
+            handle_auth_requests: None,
+        })
+        .await
+        .context("Failed to enable request interception")?;
+
+        let blocked = Arc::new(AtomicUsize::new(0));
+        let counter = blocked.clone();
+        let page = page.clone();
+
+        let task = tokio::spawn(async move {
+            while let Some(event) = paused.next().await {
+                let fail =
+                    FailRequestParams::new(event.request_id.clone(), ErrorReason::BlockedByClient);
+                if let Err(e) = page.execute(fail).await {
+                    log::debug!("Failed to block {}: {}", event.request.url, e);
+                    continue;
+                }
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        Ok(Self { blocked, task })
+    }
+
+    /// Requests blocked so far
+    pub fn blocked_count(&self) -> usize {
+        self.blocked.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for RequestBlocker {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_patterns() {
+        let none = BlockingOptions {
+            fonts: false,
+            trackers: false,
+            images: false,
+        };
+        assert!(none.is_empty());
+        assert!(none.request_patterns().is_empty());
+
+        let options = BlockingOptions {
+            fonts: true,
+            trackers: true,
+            images: false,
+        };
+        let patterns = options.request_patterns();
+        assert_eq!(patterns.len(), 1 + TRACKER_URL_PATTERNS.len());
+        assert!(patterns
+            .iter()
+            .all(|p| p.resource_type != Some(ResourceType::Image)));
+        // Product API calls are never intercepted
+        assert!(patterns.iter().all(|p| !matches!(
+            p.resource_type,
+            Some(ResourceType::Xhr | ResourceType::Fetch | ResourceType::Document)
+        )));
+
+        let with_images = BlockingOptions {
+            images: true,
+            ..options
+        };
+        assert!(with_images
+            .request_patterns()
+            .iter()
+            .any(|p| p.resource_type == Some(ResourceType::Image)));
+    }
+}
@@ -4,23 +4,89 @@
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
 use chromiumoxide::browser::{Browser, BrowserConfig};
-use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotParams;
-use chromiumoxide::layout::Point;
+use chromiumoxide::Handler;
 use chromiumoxide::Page;
 use futures::StreamExt;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
+use ts_rs::TS;
+
+use crate::models::LocalizedMessage;
+use super::driver::PageDriver;
+use super::blocking::{BlockingOptions, RequestBlocker};
+use super::intercept::NetworkCapture;
+
+/// Named bundles of Chromium flags selectable from the scraper config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum LaunchProfile {
+    #[default]
+    Standard,
+    /// Fewer renderer processes and smaller caches, for 4GB machines
+    LowMemory,
+    /// Every flag that hides automation or leaks the real network
+    StealthMax,
+    /// Headed window with DevTools open on every tab
+    Debug,
+}
+
+impl LaunchProfile {
+    /// Flags added on top of the base launch arguments
+    pub fn args(&self) -> &'static [&'static str] {
+        match self {
+            LaunchProfile::Standard => &[],
+            LaunchProfile::LowMemory => &[
+                "--renderer-process-limit=2",
+                "--js-flags=--max-old-space-size=512",
+                "--disk-cache-size=33554432",
+                "--disable-extensions",
+                "--disable-background-networking",
+                "--disable-component-update",
+                "--disable-software-rasterizer",
+            ],
+            LaunchProfile::StealthMax => &[
+                "--disable-infobars",
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--lang=pt-BR",
+                "--force-webrtc-ip-handling-policy=disable_non_proxied_udp",
+                "--disable-component-extensions-with-background-pages",
+            ],
+            LaunchProfile::Debug => &["--auto-open-devtools-for-tabs"],
+        }
+    }
+
+    /// Whether the profile needs a visible window regardless of `headless`
+    pub fn forces_headed(&self) -> bool {
+        matches!(self, LaunchProfile::Debug)
+    }
+}
 
 pub struct BrowserManager {
     browser: Arc<Mutex<Option<Browser>>>,
     headless: bool,
+    profile: LaunchProfile,
     timeout_secs: u64,
     user_data_dir: Option<PathBuf>,
     app_handle: Option<AppHandle>,
+    /// Attach to a Chrome started with --remote-debugging-port instead of launching one
+    remote_debugging_url: Option<String>,
+    /// Tabs opened in an attached browser, closed on `stop` so the user's window stays clean
+    attached_pages: Mutex<Vec<Page>>,
+    /// Proxy of the last launch, reused by `restart`
+    proxy: Mutex<Option<String>>,
+    /// Cleared when the CDP connection of the current launch closes (e.g. Chromium crashed)
+    connected: Mutex<Arc<AtomicBool>>,
+    /// Incremented on every launch; tabs of older launches are dead
+    generation: AtomicU64,
+    restart_lock: Mutex<()>,
 }
 
 impl BrowserManager {
@@ -28,9 +94,16 @@ impl BrowserManager {
         Self {
             browser: Arc::new(Mutex::new(None)),
             headless,
+            profile: LaunchProfile::default(),
             timeout_secs: 30,
             user_data_dir: None,
             app_handle: None,
+            remote_debugging_url: None,
+            attached_pages: Mutex::new(Vec::new()),
+            proxy: Mutex::new(None),
+            connected: Mutex::new(Arc::new(AtomicBool::new(false))),
+            generation: AtomicU64::new(0),
+            restart_lock: Mutex::new(()),
         }
     }
 
@@ -39,6 +112,11 @@ impl BrowserManager {
         self
     }
 
+    pub fn with_profile(mut self, profile: LaunchProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout_secs = timeout;
         self
@@ -49,10 +127,58 @@ impl BrowserManager {
         self
     }
 
+    /// Drive an already running Chrome (e.g. `http://127.0.0.1:9222`) instead of
+    /// launching a managed instance
+    pub fn with_remote_debugging(mut self, url: String) -> Self {
+        self.remote_debugging_url = Some(url);
+        self
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.remote_debugging_url.is_some()
+    }
+
     pub async fn start(&self, proxy: Option<String>) -> Result<()> {
+        *self.proxy.lock().await = proxy.clone();
+
+        let (browser, mut handler) = match &self.remote_debugging_url {
+            Some(url) => {
+                if proxy.is_some() {
+                    log::warn!("Proxy ignored: the attached browser uses its own network settings");
+                }
+                log::info!("Attaching to running browser at {}...", url);
+                Browser::connect(url.as_str())
+                    .await
+                    .with_context(|| format!("Failed to attach to browser at {}", url))?
+            }
+            None => self.launch(proxy).await?,
+        };
+
+        // Spawn task to handle browser events
+        // A fresh flag per launch, so the handler of a replaced browser can't clear it
+        let connected = Arc::new(AtomicBool::new(true));
+        *self.connected.lock().await = connected.clone();
+        tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                log::trace!("Browser event: {:?}", event);
+            }
+            connected.store(false, Ordering::SeqCst);
+            log::debug!("Browser handler closed");
+        });
+
+        *self.browser.lock().await = Some(browser);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        log::info!("Browser started successfully");
+
+        Ok(())
+    }
+
+    async fn launch(&self, proxy: Option<String>) -> Result<(Browser, Handler)> {
+        let headless = self.headless && !self.profile.forces_headed();
         log::info!(
-            "Starting browser (headless: {}, proxy: {:?})...",
-            self.headless,
+            "Starting browser (headless: {}, profile: {:?}, proxy: {:?})...",
+            headless,
+            self.profile,
             proxy
         );
 
@@ -71,6 +197,7 @@ impl BrowserManager {
             "--disable-web-security",
             "--disable-features=IsolateOrigins,site-per-process",
         ];
+        args.extend_from_slice(self.profile.args());
 
         if let Some(proxy_url) = proxy {
             args.push(Box::leak(
@@ -80,7 +207,7 @@ impl BrowserManager {
 
         builder = builder.args(args);
 
-        if !self.headless {
+        if !headless {
             builder = builder.with_head();
         }
 
@@ -92,22 +219,9 @@ impl BrowserManager {
             .build()
             .map_err(|e| anyhow::anyhow!("Failed to build browser config: {}", e))?;
 
-        let (browser, mut handler) = Browser::launch(config)
+        Browser::launch(config)
             .await
-            .context("Failed to launch browser")?;
-
-        // Spawn task to handle browser events
-        tokio::spawn(async move {
-            while let Some(event) = handler.next().await {
-                log::trace!("Browser event: {:?}", event);
-            }
-            log::debug!("Browser handler closed");
-        });
-
-        *self.browser.lock().await = Some(browser);
-        log::info!("Browser started successfully");
-
-        Ok(())
+            .context("Failed to launch browser")
     }
 
     pub async fn new_page(&self) -> Result<Page> {
@@ -119,15 +233,39 @@ impl BrowserManager {
             .await
             .context("Failed to create new page")?;
 
+        if self.is_attached() {
+            self.attached_pages.lock().await.push(page.clone());
+        }
+
         log::debug!("Created new browser page");
         Ok(page)
     }
 
+    /// Start buffering the page's product API responses (interception mode).
+    /// Call before navigating so the first responses are not missed.
+    pub async fn capture_network(&self, page: &Page) -> Result<NetworkCapture> {
+        NetworkCapture::start(page)
+            .await
+            .context("Failed to enable network capture")
+    }
+
+    /// Fail requests for the resource kinds in `options` for the lifetime of the blocker
+    pub async fn block_requests(&self, page: &Page, options: BlockingOptions) -> Result<RequestBlocker> {
+        RequestBlocker::start(page, options).await
+    }
+
     pub async fn stop(&self) -> Result<()> {
+        // An attached browser keeps running; only our own tabs are closed
+        for page in self.attached_pages.lock().await.drain(..) {
+            if let Err(e) = page.close().await {
+                log::debug!("Failed to close attached tab: {}", e);
+            }
+        }
+
         let mut browser = self.browser.lock().await;
 
         if let Some(b) = browser.take() {
-            // Browser will cleanup on drop
+            // Browser will cleanup on drop (a connected browser is only disconnected)
             drop(b);
         }
         Ok(())
@@ -138,7 +276,44 @@ impl BrowserManager {
         self.browser.lock().await.is_some()
     }
 
-    pub async fn simulate_human_interaction(&self, page: &Page) -> Result<()> {
+    /// Whether the browser is still connected and answering CDP calls
+    pub async fn is_healthy(&self) -> bool {
+        if !self.connected.lock().await.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let browser = self.browser.lock().await;
+        let Some(browser) = browser.as_ref() else {
+            return false;
+        };
+        matches!(
+            tokio::time::timeout(tokio::time::Duration::from_secs(5), browser.version()).await,
+            Ok(Ok(_))
+        )
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Relaunch the browser with the same profile and proxy after a crash
+    pub async fn restart(&self) -> Result<()> {
+        log::warn!("Restarting browser...");
+        self.stop().await?;
+        let proxy = self.proxy.lock().await.clone();
+        self.start(proxy).await
+    }
+
+    /// Restart unless another task already relaunched the browser since `generation`
+    pub async fn restart_if_current(&self, generation: u64) -> Result<()> {
+        let _guard = self.restart_lock.lock().await;
+        if self.generation() != generation {
+            return Ok(());
+        }
+        self.restart().await
+    }
+
+    pub async fn simulate_human_interaction<P: PageDriver>(&self, page: &P) -> Result<()> {
         let width = 1920;
         let height = 1080;
 
@@ -148,7 +323,7 @@ impl BrowserManager {
                 (rng.gen_range(0..width), rng.gen_range(0..height))
             };
 
-            page.move_mouse(Point::new(x as f64, y as f64)).await?;
+            page.move_mouse(x as f64, y as f64).await?;
 
             let delay = { rand::thread_rng().gen_range(100..300) };
             tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
@@ -156,9 +331,9 @@ impl BrowserManager {
         Ok(())
     }
 
-    pub async fn capture_screenshot(&self, page: &Page) -> Result<String> {
+    pub async fn capture_screenshot<P: PageDriver>(&self, page: &P) -> Result<String> {
         let screenshot = page
-            .screenshot(CaptureScreenshotParams::default())
+            .screenshot()
             .await
             .context("Failed to capture screenshot")?;
 
@@ -170,7 +345,7 @@ impl BrowserManager {
     pub async fn send_browser_event(
         &self,
         url: String,
-        status: String,
+        status: LocalizedMessage,
         screenshot: Option<String>,
     ) {
         if let Some(handle) = &self.app_handle {
@@ -178,7 +353,8 @@ impl BrowserManager {
                 "browser-update",
                 json!({
                     "url": url,
-                    "status": status,
+                    "status": status.text,
+                    "statusKey": status.key,
                     "screenshot": screenshot,
                 }),
             );
@@ -213,4 +389,17 @@ mod tests {
         manager.stop().await.expect("Failed to stop browser");
         assert!(!manager.is_running().await);
     }
+
+    #[test]
+    fn test_launch_profiles() {
+        assert!(LaunchProfile::Standard.args().is_empty());
+        assert!(LaunchProfile::LowMemory
+            .args()
+            .contains(&"--renderer-process-limit=2"));
+        assert!(LaunchProfile::Debug.forces_headed());
+        assert!(!LaunchProfile::StealthMax.forces_headed());
+
+        let profile: LaunchProfile = serde_json::from_str("\"stealth_max\"").unwrap();
+        assert_eq!(profile, LaunchProfile::StealthMax);
+    }
 }
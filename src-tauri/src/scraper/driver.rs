@@ -0,0 +1,128 @@
+// Page Driver Module
+// The page operations the scraper needs, independent of the browser backend
+// (chromiumoxide/CDP for Chromium, WebDriver for Firefox)
+
+use anyhow::Result;
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, InsertTextParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::{CaptureScreenshotFormat, CaptureScreenshotParams};
+use chromiumoxide::keys;
+use chromiumoxide::layout::Point;
+use chromiumoxide::Page;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::future::Future;
+use ts_rs::TS;
+
+/// Browser engine used for scraping
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, TS, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum BrowserBackend {
+    /// Chromium over CDP (request blocking and API capture available)
+    #[default]
+    Chromium,
+    /// Firefox through a WebDriver server such as geckodriver
+    Firefox,
+    /// Chromium first, Firefox when Chromium fails to launch or gets blocked
+    Auto,
+}
+
+pub trait PageDriver: Send + Sync {
+    fn goto(&self, url: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Evaluate a JavaScript expression and return its JSON value (null for undefined)
+    fn evaluate(&self, expression: &str) -> impl Future<Output = Result<Value>> + Send;
+
+    /// Serialized HTML of the current document
+    fn content(&self) -> impl Future<Output = Result<String>> + Send;
+
+    /// PNG screenshot of the viewport
+    fn screenshot(&self) -> impl Future<Output = Result<Vec<u8>>> + Send;
+
+    /// PNG screenshot of the first element matching a CSS selector
+    fn screenshot_element(&self, selector: &str) -> impl Future<Output = Result<Vec<u8>>> + Send;
+
+    /// Whether the page still answers (false once the browser crashed)
+    fn is_alive(&self) -> impl Future<Output = bool> + Send;
+
+    /// Press and release a key in the focused element: a single character,
+    /// or a named key such as "Enter" or "Backspace"
+    fn press_key(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Move the pointer, for backends that can (used to look less robotic)
+    fn move_mouse(&self, _x: f64, _y: f64) -> impl Future<Output = Result<()>> + Send {
+        async { Ok(()) }
+    }
+}
+
+impl PageDriver for Page {
+    async fn goto(&self, url: &str) -> Result<()> {
+        Page::goto(self, url).await?;
+        Ok(())
+    }
+
+    async fn evaluate(&self, expression: &str) -> Result<Value> {
+        let result = Page::evaluate(self, expression).await?;
+        Ok(result.value().cloned().unwrap_or(Value::Null))
+    }
+
+    async fn content(&self) -> Result<String> {
+        Ok(Page::content(self).await?)
+    }
+
+    async fn screenshot(&self) -> Result<Vec<u8>> {
+        Ok(Page::screenshot(self, CaptureScreenshotParams::default()).await?)
+    }
+
+    async fn screenshot_element(&self, selector: &str) -> Result<Vec<u8>> {
+        let element = self.find_element(selector).await?;
+        Ok(element.screenshot(CaptureScreenshotFormat::Png).await?)
+    }
+
+    async fn is_alive(&self) -> bool {
+        let probe = Page::evaluate(self, "1");
+        matches!(
+            tokio::time::timeout(std::time::Duration::from_secs(5), probe).await,
+            Ok(Ok(_))
+        )
+    }
+
+    async fn press_key(&self, key: &str) -> Result<()> {
+        // Characters outside the US layout (ç, ã...) are inserted as text
+        let Some(definition) = keys::get_key_definition(key) else {
+            self.execute(InsertTextParams::new(key)).await?;
+            return Ok(());
+        };
+
+        let text = definition
+            .text
+            .or((definition.key.len() == 1).then_some(definition.key));
+        let mut down = DispatchKeyEventParams::builder()
+            .r#type(if text.is_some() {
+                DispatchKeyEventType::KeyDown
+            } else {
+                DispatchKeyEventType::RawKeyDown
+            })
+            .key(definition.key)
+            .code(definition.code)
+            .windows_virtual_key_code(definition.key_code)
+            .native_virtual_key_code(definition.key_code);
+        if let Some(text) = text {
+            down = down.text(text);
+        }
+        let up = down.clone().r#type(DispatchKeyEventType::KeyUp);
+
+        self.execute(down.build().map_err(anyhow::Error::msg)?)
+            .await?;
+        self.execute(up.build().map_err(anyhow::Error::msg)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn move_mouse(&self, x: f64, y: f64) -> Result<()> {
+        Page::move_mouse(self, Point::new(x, y)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+// Localization
+// Catalog of the messages the scraper shows the user. Messages are addressed
+// by key with named `{param}` placeholders; the key and params are also sent
+// to the frontend so it can render them in its own language.
+
+use std::collections::HashMap;
+
+use crate::models::LocalizedMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    PtBr,
+    En,
+    Es,
+}
+
+impl Language {
+    /// Language of a tag such as "pt-BR", "en-US" or "es"; unknown
+    /// languages fall back to Portuguese
+    pub fn from_tag(tag: &str) -> Self {
+        match tag.split(['-', '_']).next().unwrap_or("").to_lowercase().as_str() {
+            "en" => Language::En,
+            "es" => Language::Es,
+            _ => Language::PtBr,
+        }
+    }
+}
+
+/// (key, pt-BR, en, es)
+const CATALOG: &[(&str, &str, &str, &str)] = &[
+    (
+        "scraper.starting",
+        "Iniciando scraper do TikTok Shop...",
+        "Starting TikTok Shop scraper...",
+        "Iniciando scraper de TikTok Shop...",
+    ),
+    (
+        "scraper.starting_shopee",
+        "Iniciando scraper da Shopee...",
+        "Starting Shopee scraper...",
+        "Iniciando scraper de Shopee...",
+    ),
+    (
+        "scraper.starting_aliexpress",
+        "Iniciando scraper do AliExpress...",
+        "Starting AliExpress scraper...",
+        "Iniciando scraper de AliExpress...",
+    ),
+    (
+        "scraper.starting_mercadolivre",
+        "Iniciando scraper do Mercado Livre...",
+        "Starting Mercado Livre scraper...",
+        "Iniciando scraper de Mercado Libre...",
+    ),
+    (
+        "scraper.safety_switch_on",
+        "Safety Switch: ATIVADO",
+        "Safety Switch: ON",
+        "Safety Switch: ACTIVADO",
+    ),
+    (
+        "scraper.firefox_fallback",
+        "Chromium falhou ({error}). Tentando com Firefox...",
+        "Chromium failed ({error}). Trying Firefox...",
+        "Chromium falló ({error}). Probando con Firefox...",
+    ),
+    (
+        "scraper.finished",
+        "Processo finalizado.",
+        "Process finished.",
+        "Proceso finalizado.",
+    ),
+    (
+        "scraper.requests_blocked",
+        "{count} requisições bloqueadas (fontes, rastreadores, anúncios)",
+        "{count} requests blocked (fonts, trackers, ads)",
+        "{count} solicitudes bloqueadas (fuentes, rastreadores, anuncios)",
+    ),
+    (
+        "scraper.stopped_by_user",
+        "Scraper parado pelo usuário.",
+        "Scraper stopped by the user.",
+        "Scraper detenido por el usuario.",
+    ),
+    (
+        "scraper.resuming",
+        "Retomando sessão interrompida: {count} produtos já coletados",
+        "Resuming interrupted session: {count} products already collected",
+        "Reanudando sesión interrumpida: {count} productos ya recopilados",
+    ),
+    (
+        "scraper.navigating",
+        "Navegando para: {url}",
+        "Navigating to: {url}",
+        "Navegando a: {url}",
+    ),
+    (
+        "scraper.memory_full",
+        "Memória cheia! Pausando por 10s...",
+        "Memory full! Pausing for 10s...",
+        "¡Memoria llena! Pausando 10s...",
+    ),
+    (
+        "scraper.browser_crashed",
+        "Navegador caiu ({error}). Reiniciando e retomando {category}...",
+        "Browser crashed ({error}). Restarting and resuming {category}...",
+        "El navegador se cerró ({error}). Reiniciando y retomando {category}...",
+    ),
+    (
+        "scraper.page_stalled",
+        "Página travou ({stage}). Reciclando aba e seguindo...",
+        "Page stalled ({stage}). Recycling the tab and moving on...",
+        "La página se colgó ({stage}). Reciclando la pestaña y siguiendo...",
+    ),
+    (
+        "scraper.enriching",
+        "Detalhando {count} produtos...",
+        "Fetching details of {count} products...",
+        "Detallando {count} productos...",
+    ),
+    (
+        "scraper.restarting_browser",
+        "Reiniciando navegador...",
+        "Restarting browser...",
+        "Reiniciando navegador...",
+    ),
+    (
+        "scraper.search_typed",
+        "Pesquisa digitada: {keyword}",
+        "Search typed: {keyword}",
+        "Búsqueda escrita: {keyword}",
+    ),
+    (
+        "scraper.load_retry",
+        "Erro ao carregar. Tentando novamente em {seconds}s...",
+        "Failed to load. Retrying in {seconds}s...",
+        "Error al cargar. Reintentando en {seconds}s...",
+    ),
+    (
+        "scraper.waiting_page",
+        "Aguardando carregamento da página...",
+        "Waiting for the page to load...",
+        "Esperando a que cargue la página...",
+    ),
+    (
+        "scraper.page_ready",
+        "Página pronta em {seconds}s",
+        "Page ready in {seconds}s",
+        "Página lista en {seconds}s",
+    ),
+    (
+        "scraper.wait_timed_out",
+        "Tempo esgotado aguardando: {conditions}",
+        "Timed out waiting for: {conditions}",
+        "Tiempo agotado esperando: {conditions}",
+    ),
+    (
+        "scraper.bot_detected",
+        "DETECÇÃO DE BOT IDENTIFICADA! Abortando para segurança.",
+        "BOT DETECTION TRIGGERED! Aborting for safety.",
+        "¡DETECCIÓN DE BOT IDENTIFICADA! Abortando por seguridad.",
+    ),
+    (
+        "scraper.analyzing",
+        "Analisando produtos na página...",
+        "Analyzing products on the page...",
+        "Analizando productos en la página...",
+    ),
+    (
+        "scraper.no_products",
+        "Nenhum produto extraído ({source})",
+        "No products extracted ({source})",
+        "Ningún producto extraído ({source})",
+    ),
+    (
+        "scraper.product_found",
+        "Encontrado: {title} (R$ {price})",
+        "Found: {title} (R$ {price})",
+        "Encontrado: {title} (R$ {price})",
+    ),
+    (
+        "scraper.products_added",
+        "+{count} novos produtos adicionados",
+        "+{count} new products added",
+        "+{count} productos nuevos añadidos",
+    ),
+    (
+        "scraper.no_new_products",
+        "Nenhum produto novo. Fim da página.",
+        "No new products. End of the page.",
+        "Ningún producto nuevo. Fin de la página.",
+    ),
+    (
+        "scraper.end_of_page",
+        "Fim da página alcançado.",
+        "Reached the end of the page.",
+        "Fin de la página alcanzado.",
+    ),
+    (
+        "scraper.variants",
+        "{position}/{total}: {count} variantes",
+        "{position}/{total}: {count} variants",
+        "{position}/{total}: {count} variantes",
+    ),
+    (
+        "scraper.offline_pages",
+        "Modo offline: {count} páginas salvas em {folder}",
+        "Offline mode: {count} saved pages in {folder}",
+        "Modo offline: {count} páginas guardadas en {folder}",
+    ),
+    (
+        "scraper.offline_page",
+        "{count} produtos na página salva",
+        "{count} products in the saved page",
+        "{count} productos en la página guardada",
+    ),
+    (
+        "scraper.offline_page_failed",
+        "Página salva ilegível: {error}",
+        "Unreadable saved page: {error}",
+        "Página guardada ilegible: {error}",
+    ),
+    (
+        "pagination.scroll",
+        "Rolando página para carregar mais...",
+        "Scrolling to load more...",
+        "Desplazando la página para cargar más...",
+    ),
+    (
+        "pagination.click_next",
+        "Clicando em \"Ver mais\"...",
+        "Clicking \"Load more\"...",
+        "Haciendo clic en \"Ver más\"...",
+    ),
+    (
+        "pagination.next_page",
+        "Abrindo próxima página...",
+        "Opening the next page...",
+        "Abriendo la página siguiente...",
+    ),
+    (
+        "status.initializing",
+        "Inicializando...",
+        "Initializing...",
+        "Inicializando...",
+    ),
+    ("status.finished", "Finalizado", "Finished", "Finalizado"),
+    (
+        "status.starting_browser",
+        "Iniciando navegador...",
+        "Starting browser...",
+        "Iniciando navegador...",
+    ),
+    (
+        "status.browser_started",
+        "Navegador iniciado",
+        "Browser started",
+        "Navegador iniciado",
+    ),
+    (
+        "status.starting_firefox",
+        "Iniciando Firefox...",
+        "Starting Firefox...",
+        "Iniciando Firefox...",
+    ),
+    (
+        "status.loading_page",
+        "Carregando página...",
+        "Loading page...",
+        "Cargando página...",
+    ),
+    (
+        "status.analyzing_page",
+        "Analisando página...",
+        "Analyzing page...",
+        "Analizando página...",
+    ),
+    (
+        "error.scrape_failed",
+        "Falha no scraping: {error}",
+        "Scraping failed: {error}",
+        "Falló el scraping: {error}",
+    ),
+];
+
+/// Text of a message in the given language. Unknown keys are returned as is
+/// so a missing entry shows up instead of an empty line.
+pub fn translate(language: Language, key: &str, params: &[(&str, String)]) -> String {
+    let Some(entry) = CATALOG.iter().find(|entry| entry.0 == key) else {
+        return key.to_string();
+    };
+    let template = match language {
+        Language::PtBr => entry.1,
+        Language::En => entry.2,
+        Language::Es => entry.3,
+    };
+
+    params
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// A translated message along with its key and params for the frontend
+pub fn localize(language: Language, key: &str, params: &[(&str, String)]) -> LocalizedMessage {
+    LocalizedMessage {
+        key: key.to_string(),
+        params: params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<HashMap<_, _>>(),
+        text: translate(language, key, params),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(Language::from_tag("en-US"), Language::En);
+        assert_eq!(Language::from_tag("es"), Language::Es);
+        assert_eq!(Language::from_tag("fr-FR"), Language::PtBr);
+
+        let params = [("url", "https://shop.tiktok.com/".to_string())];
+        assert_eq!(
+            translate(Language::En, "scraper.navigating", &params),
+            "Navigating to: https://shop.tiktok.com/"
+        );
+        assert_eq!(
+            translate(Language::PtBr, "scraper.navigating", &params),
+            "Navegando para: https://shop.tiktok.com/"
+        );
+        assert_eq!(translate(Language::Es, "missing.key", &[]), "missing.key");
+
+        // Every entry has all its languages
+        for entry in CATALOG {
+            assert!(!entry.1.is_empty() && !entry.2.is_empty() && !entry.3.is_empty());
+        }
+    }
+}
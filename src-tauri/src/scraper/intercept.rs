@@ -0,0 +1,268 @@
+// Network Interception Module
+// Captures the shop's own JSON API responses over CDP while a page loads,
+// so products can be read without depending on the rendered DOM
+
+use anyhow::Result;
+use base64::{engine::general_purpose, Engine as _};
+use chromiumoxide::cdp::browser_protocol::network::{
+    EventLoadingFinished, EventResponseReceived, GetResponseBodyParams, RequestId,
+};
+use chromiumoxide::Page;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// URL fragments of the XHR/fetch calls that carry product lists
+const API_URL_PATTERNS: &[&str] = &["/api/", "product", "search", "recommend"];
+
+/// Body of a JSON response captured from the page
+#[derive(Debug, Clone)]
+pub struct CapturedResponse {
+    pub url: String,
+    pub body: String,
+}
+
+/// Buffers matching JSON responses of a page until they are drained
+pub struct NetworkCapture {
+    responses: Arc<Mutex<Vec<CapturedResponse>>>,
+    task: JoinHandle<()>,
+}
+
+impl NetworkCapture {
+    pub async fn start(page: &Page) -> Result<Self> {
+        let mut received = page.event_listener::<EventResponseReceived>().await?;
+        let mut finished = page.event_listener::<EventLoadingFinished>().await?;
+
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let buffer = responses.clone();
+        let page = page.clone();
+
+        let task = tokio::spawn(async move {
+            // The body is only available once loading finished
+            let mut pending: HashMap<RequestId, String> = HashMap::new();
+
+            loop {
+                tokio::select! {
+                    Some(event) = received.next() => {
+                        let response = &event.response;
+                        if is_product_api(&response.url, &response.mime_type) {
+                            pending.insert(event.request_id.clone(), response.url.clone());
+                        }
+                    }
+                    Some(event) = finished.next() => {
+                        let Some(url) = pending.remove(&event.request_id) else {
+                            continue;
+                        };
+                        match page
+                            .execute(GetResponseBodyParams::new(event.request_id.clone()))
+                            .await
+                        {
+                            Ok(response) => {
+                                let body = if response.result.base64_encoded {
+                                    general_purpose::STANDARD
+                                        .decode(&response.result.body)
+                                        .ok()
+                                        .and_then(|bytes| String::from_utf8(bytes).ok())
+                                        .unwrap_or_default()
+                                } else {
+                                    response.result.body.clone()
+                                };
+                                buffer.lock().await.push(CapturedResponse { url, body });
+                            }
+                            Err(e) => log::debug!("Failed to read response body of {}: {}", url, e),
+                        }
+                    }
+                    else => break,
+                }
+            }
+            log::debug!("Network capture closed");
+        });
+
+        Ok(Self { responses, task })
+    }
+
+    /// Take every response captured since the last call
+    pub async fn drain(&self) -> Vec<CapturedResponse> {
+        std::mem::take(&mut *self.responses.lock().await)
+    }
+}
+
+impl Drop for NetworkCapture {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn is_product_api(url: &str, mime_type: &str) -> bool {
+    mime_type.contains("json") && API_URL_PATTERNS.iter().any(|p| url.contains(p))
+}
+
+/// URL without query string, used as the extraction source name
+pub fn endpoint_name(url: &str) -> String {
+    let path = url.split('?').next().unwrap_or(url);
+    let path = path
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+        .unwrap_or(path);
+    format!("api:{}", path)
+}
+
+// ===== API RESPONSE TYPES =====
+
+/// Envelope of list endpoints: `{ data: { products: [...] } }` or a bare list
+#[derive(Debug, Deserialize)]
+pub struct ApiListResponse {
+    #[serde(default)]
+    pub data: Option<ApiListData>,
+    #[serde(default, alias = "productList", alias = "product_list", alias = "items")]
+    pub products: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiListData {
+    #[serde(
+        default,
+        alias = "productList",
+        alias = "product_list",
+        alias = "item_list",
+        alias = "items"
+    )]
+    pub products: Vec<Value>,
+}
+
+impl ApiListResponse {
+    /// Raw items of the list, each still to be read as an [`ApiProduct`]
+    pub fn into_items(self) -> Vec<Value> {
+        match (self.data, self.products) {
+            (Some(data), _) if !data.products.is_empty() => data.products,
+            (_, Some(products)) => products,
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiProduct {
+    #[serde(alias = "productId", alias = "id")]
+    pub product_id: ApiId,
+    #[serde(default, alias = "name")]
+    pub title: String,
+    #[serde(default, alias = "productPriceInfo")]
+    pub product_price_info: Option<ApiPriceInfo>,
+    #[serde(default)]
+    pub price: Option<ApiNumber>,
+    #[serde(default, alias = "soldInfo")]
+    pub sold_info: Option<ApiSoldInfo>,
+    #[serde(default, alias = "rateInfo")]
+    pub rate_info: Option<ApiRateInfo>,
+    #[serde(default)]
+    pub image: Option<ApiImage>,
+    #[serde(default, alias = "sellerInfo", alias = "seller")]
+    pub seller_info: Option<ApiSeller>,
+    #[serde(default, alias = "freeShipping")]
+    pub free_shipping: Option<bool>,
+    #[serde(default, alias = "productUrl", alias = "url")]
+    pub product_url: Option<String>,
+}
+
+/// Product ids come as strings or as (64-bit) numbers
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ApiId {
+    Text(String),
+    Number(u64),
+}
+
+impl std::fmt::Display for ApiId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiId::Text(s) => write!(f, "{}", s),
+            ApiId::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Numbers sent either as JSON numbers or as formatted text ("1,2 mil", "R$ 29,90")
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ApiNumber {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiPriceInfo {
+    #[serde(default, alias = "salePrice", alias = "sale_price_decimal")]
+    pub sale_price: Option<ApiNumber>,
+    #[serde(default, alias = "originPrice", alias = "origin_price_decimal")]
+    pub origin_price: Option<ApiNumber>,
+    #[serde(default, alias = "currencyName", alias = "currency")]
+    pub currency_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiSoldInfo {
+    #[serde(default, alias = "soldCount")]
+    pub sold_count: Option<ApiNumber>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiRateInfo {
+    #[serde(default)]
+    pub score: Option<f64>,
+    #[serde(default, alias = "reviewCount")]
+    pub review_count: Option<ApiNumber>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiImage {
+    #[serde(default, alias = "urlList")]
+    pub url_list: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiSeller {
+    #[serde(default, alias = "shopName", alias = "name")]
+    pub shop_name: Option<String>,
+    #[serde(default)]
+    pub rating: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_product_api_filter() {
+        assert!(is_product_api(
+            "https://shop.tiktok.com/api/v1/search/products?keyword=fone",
+            "application/json"
+        ));
+        assert!(!is_product_api("https://shop.tiktok.com/api/v1/track", "text/html"));
+        assert!(!is_product_api("https://cdn.tiktok.com/config.json", "application/json"));
+
+        assert_eq!(
+            endpoint_name("https://shop.tiktok.com/api/v1/search/products?keyword=fone"),
+            "api:/api/v1/search/products"
+        );
+    }
+
+    #[test]
+    fn test_api_list_envelopes() {
+        let nested: ApiListResponse =
+            serde_json::from_str(r#"{ "code": 0, "data": { "productList": [{ "id": 1 }] } }"#)
+                .unwrap();
+        assert_eq!(nested.into_items().len(), 1);
+
+        let flat: ApiListResponse =
+            serde_json::from_str(r#"{ "products": [{ "id": "1" }, { "id": "2" }] }"#).unwrap();
+        assert_eq!(flat.into_items().len(), 2);
+
+        let empty: ApiListResponse = serde_json::from_str(r#"{ "data": {} }"#).unwrap();
+        assert!(empty.into_items().is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+// JSON-LD Module
+// Reads schema.org Product data embedded as JSON-LD. Marketplace independent,
+// so any parser can use it as a last resort when its own selectors fail.
+
+use scraper::{Html, Selector};
+use serde_json::Value;
+
+/// The fields of a schema.org Product the scraper cares about
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonLdProduct {
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    /// Offer price, or the low end of an AggregateOffer
+    pub price: Option<f64>,
+    pub high_price: Option<f64>,
+    pub currency: Option<String>,
+    pub images: Vec<String>,
+    pub url: Option<String>,
+    pub brand: Option<String>,
+    pub rating: Option<f64>,
+    pub review_count: Option<i32>,
+    pub in_stock: Option<bool>,
+}
+
+/// Every Product found in the page's `application/ld+json` scripts,
+/// including products nested in `@graph` and ItemList elements
+pub fn extract_products(document: &Html) -> Vec<JsonLdProduct> {
+    let Ok(selector) = Selector::parse("script[type='application/ld+json']") else {
+        return Vec::new();
+    };
+
+    let mut products = Vec::new();
+    for script in document.select(&selector) {
+        let text = script.text().collect::<String>();
+        // Some sites leave trailing commas or HTML comments; skip what doesn't parse
+        if let Ok(value) = serde_json::from_str::<Value>(text.trim()) {
+            collect_products(&value, &mut products);
+        }
+    }
+    products
+}
+
+fn collect_products(value: &Value, products: &mut Vec<JsonLdProduct>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_products(item, products);
+            }
+        }
+        Value::Object(obj) => {
+            if has_type(value, "Product") {
+                if let Some(product) = parse_product(value) {
+                    products.push(product);
+                }
+                return;
+            }
+            if let Some(graph) = obj.get("@graph") {
+                collect_products(graph, products);
+            }
+            // ItemList > ListItem > item
+            if let Some(elements) = obj.get("itemListElement") {
+                collect_products(elements, products);
+            }
+            if let Some(item) = obj.get("item") {
+                collect_products(item, products);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `@type` may be a string or a list ("Product", ["Product", "Thing"])
+fn has_type(value: &Value, wanted: &str) -> bool {
+    match value.get("@type") {
+        Some(Value::String(t)) => t == wanted,
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some(wanted)),
+        _ => false,
+    }
+}
+
+fn parse_product(value: &Value) -> Option<JsonLdProduct> {
+    let name = text(value.get("name"))?;
+
+    // A single Offer, a list of offers, or an AggregateOffer
+    let offers: Vec<&Value> = match value.get("offers") {
+        Some(Value::Array(list)) => list.iter().collect(),
+        Some(offer) => vec![offer],
+        None => vec![],
+    };
+    let prices: Vec<f64> = offers
+        .iter()
+        .filter_map(|o| number(o.get("price")).or_else(|| number(o.get("lowPrice"))))
+        .collect();
+    let high_price = offers
+        .iter()
+        .filter_map(|o| number(o.get("highPrice")))
+        .chain(prices.iter().copied())
+        .reduce(f64::max);
+    let price = prices.iter().copied().reduce(f64::min);
+    let currency = offers.iter().find_map(|o| text(o.get("priceCurrency")));
+    let in_stock = offers
+        .iter()
+        .find_map(|o| text(o.get("availability")))
+        .map(|a| a.ends_with("InStock") || a.ends_with("LimitedAvailability"));
+
+    let rating = value.get("aggregateRating");
+
+    Some(JsonLdProduct {
+        id: text(value.get("sku"))
+            .or_else(|| text(value.get("productID")))
+            .or_else(|| text(value.get("@id"))),
+        name,
+        description: text(value.get("description")),
+        price,
+        high_price: high_price.filter(|high| price.is_some_and(|low| *high > low)),
+        currency,
+        images: images(value.get("image")),
+        url: text(value.get("url")),
+        brand: value
+            .get("brand")
+            .and_then(|b| text(b.get("name")).or_else(|| text(Some(b)))),
+        rating: rating.and_then(|r| number(r.get("ratingValue"))),
+        review_count: rating
+            .and_then(|r| number(r.get("reviewCount")).or_else(|| number(r.get("ratingCount"))))
+            .map(|n| n as i32),
+        in_stock,
+    })
+}
+
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Prices are numbers or plain decimal strings ("89.90") per schema.org
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|n: &f64| n.is_finite())
+}
+
+/// `image` may be a URL, a list of URLs or ImageObjects
+fn images(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(url)) => vec![url.clone()],
+        Some(Value::Array(list)) => list.iter().flat_map(|v| images(Some(v))).collect(),
+        Some(Value::Object(obj)) => obj
+            .get("url")
+            .or_else(|| obj.get("contentUrl"))
+            .and_then(|u| u.as_str())
+            .map(|u| vec![u.to_string()])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_json_ld_products() {
+        let html = r#"
+            <html><head>
+            <script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@graph": [
+                    { "@type": "BreadcrumbList", "itemListElement": [] },
+                    {
+                        "@type": ["Product", "Thing"],
+                        "name": "Garrafa Térmica 1L",
+                        "sku": "GT-1000",
+                        "image": [{ "@type": "ImageObject", "url": "https://img/1.jpg" }, "https://img/2.jpg"],
+                        "brand": { "@type": "Brand", "name": "Termax" },
+                        "offers": { "@type": "AggregateOffer", "lowPrice": "59.90", "highPrice": "79.90", "priceCurrency": "BRL" },
+                        "aggregateRating": { "ratingValue": 4.7, "reviewCount": "312" }
+                    }
+                ]
+            }
+            </script>
+            <script type="application/ld+json">{ not json }</script>
+            </head><body></body></html>
+        "#;
+
+        let products = extract_products(&Html::parse_document(html));
+        assert_eq!(products.len(), 1);
+
+        let product = &products[0];
+        assert_eq!(product.name, "Garrafa Térmica 1L");
+        assert_eq!(product.id.as_deref(), Some("GT-1000"));
+        assert_eq!(product.price, Some(59.9));
+        assert_eq!(product.high_price, Some(79.9));
+        assert_eq!(product.currency.as_deref(), Some("BRL"));
+        assert_eq!(
+            product.images,
+            vec!["https://img/1.jpg", "https://img/2.jpg"]
+        );
+        assert_eq!(product.brand.as_deref(), Some("Termax"));
+        assert_eq!(product.review_count, Some(312));
+    }
+}
@@ -0,0 +1,1004 @@
+// Mercado Livre Scraper
+// Mercado Livre (Mercado Libre outside Brazil) site of the configured region
+// for the same categories as TikTok Shop runs. Category names are searches
+// on the site ("lista.mercadolivre.com.br/fone-bluetooth") and "trending" is
+// the best sellers page. With `mercadolivre_api` the searches go to the
+// public REST API instead (api.mercadolibre.com/sites/MLB/search), through
+// the same browser and proxy, and app niche names map to Mercado Livre
+// categories there. Products come from the captured JSON (search API or the
+// site's own calls), the results embedded in the page, or the rendered
+// cards, located with the selectors in selectors/mercadolivre.json.
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use super::intercept::CapturedResponse;
+use super::parser::{NumberLocale, TikTokParser};
+use super::storefront::{Storefront, StorefrontScraper};
+use super::wait::WaitCondition;
+use crate::config::ScraperConfig;
+use crate::models::{MarketplaceAccess, Product, ProductSource};
+
+const API_URL: &str = "https://api.mercadolibre.com";
+
+/// Results per page of the site search
+const SITE_PAGE_SIZE: u32 = 48;
+
+/// Results per page requested from the search API
+const API_PAGE_SIZE: u32 = 50;
+
+/// Mercado Livre site of a region
+#[derive(Debug, Clone, Copy)]
+struct Site {
+    region: &'static str,
+    /// Site id, also the prefix of its item ids
+    id: &'static str,
+    domain: &'static str,
+    currency: &'static str,
+    /// Subdomain of item pages
+    item_host: &'static str,
+    best_sellers: &'static str,
+}
+
+const SITES: &[Site] = &[
+    Site {
+        region: "BR",
+        id: "MLB",
+        domain: "mercadolivre.com.br",
+        currency: "BRL",
+        item_host: "produto",
+        best_sellers: "mais-vendidos",
+    },
+    Site {
+        region: "AR",
+        id: "MLA",
+        domain: "mercadolibre.com.ar",
+        currency: "ARS",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+    Site {
+        region: "MX",
+        id: "MLM",
+        domain: "mercadolibre.com.mx",
+        currency: "MXN",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+    Site {
+        region: "CL",
+        id: "MLC",
+        domain: "mercadolibre.cl",
+        currency: "CLP",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+    Site {
+        region: "CO",
+        id: "MCO",
+        domain: "mercadolibre.com.co",
+        currency: "COP",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+    Site {
+        region: "UY",
+        id: "MLU",
+        domain: "mercadolibre.com.uy",
+        currency: "UYU",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+    Site {
+        region: "PE",
+        id: "MPE",
+        domain: "mercadolibre.com.pe",
+        currency: "PEN",
+        item_host: "articulo",
+        best_sellers: "mas-vendidos",
+    },
+];
+
+/// App category names (lowercase, with and without accents) of each
+/// category of the Brazilian site: (names, category id)
+const CATEGORIES: &[(&[&str], &str)] = &[
+    (&["eletrônicos", "eletronicos", "electronics"], "MLB1000"),
+    (&["celulares", "celular", "phones"], "MLB1051"),
+    (&["informática", "informatica", "computers"], "MLB1648"),
+    (
+        &["casa", "casa e jardim", "casa e decoração", "home"],
+        "MLB1574",
+    ),
+    (
+        &["beleza", "beleza e saúde", "beleza e saude", "beauty"],
+        "MLB1246",
+    ),
+    (&["brinquedos", "toys"], "MLB1132"),
+    (&["esportes", "esporte", "sports"], "MLB1276"),
+    (
+        &[
+            "moda",
+            "moda feminina",
+            "moda masculina",
+            "calçados",
+            "calcados",
+            "bolsas",
+        ],
+        "MLB1430",
+    ),
+    (&["bebês", "bebes", "mãe e bebê", "baby"], "MLB1384"),
+    (&["automotivo", "carros", "automotive"], "MLB1747"),
+    (&["joias", "acessórios", "acessorios", "jewelry"], "MLB3937"),
+    (&["pet", "pets", "pet shop"], "MLB1071"),
+    (&["ferramentas", "tools"], "MLB263532"),
+    (&["games", "jogos"], "MLB1144"),
+];
+
+/// Item id in links and ids: `MLB-1234567890`, `MLB1234567890`
+static ITEM_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(M[A-Z]{2})-?(\d{6,})").expect("valid regex"));
+
+/// CSS selectors and page markers of Mercado Livre listings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MercadolivreSelectors {
+    /// Product cards
+    pub card: String,
+    /// Link to the item page inside a card
+    pub link: String,
+    pub title: String,
+    pub price: String,
+    pub original_price: String,
+    /// Whole and cents part of an amount, inside a price
+    pub fraction: String,
+    pub cents: String,
+    pub rating: String,
+    pub reviews: String,
+    pub seller: String,
+    pub shipping: String,
+    pub image: String,
+    /// `window` paths of the embedded results, tried in order
+    #[serde(default)]
+    pub state_roots: Vec<String>,
+    /// Path or content fragments of the verification, login and API
+    /// refusal pages
+    #[serde(default)]
+    pub block_markers: Vec<String>,
+}
+
+impl MercadolivreSelectors {
+    /// Selectors shipped with the app
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("selectors/mercadolivre.json"))
+            .expect("valid bundled Mercado Livre selectors")
+    }
+}
+
+/// Category id of an app category name on the Brazilian site, if it maps
+/// to one
+pub fn category_id(category: &str) -> Option<&'static str> {
+    let name = category.trim().to_lowercase();
+    CATEGORIES
+        .iter()
+        .find(|(names, _)| names.contains(&name.as_str()))
+        .map(|&(_, id)| id)
+}
+
+/// Item id without the dash of page URLs, e.g. "MLB1234567890"
+pub fn item_id(text: &str) -> Option<String> {
+    ITEM_ID_RE
+        .captures(text)
+        .map(|ids| format!("{}{}", &ids[1], &ids[2]))
+}
+
+/// URLs of the Mercado Livre site of a region
+#[derive(Debug, Clone)]
+pub struct MercadolivreUrls {
+    site: Site,
+    /// Search through the REST API instead of the site
+    api: bool,
+}
+
+impl MercadolivreUrls {
+    /// Site of a region code (ISO 3166, e.g. "BR"); regions without one use
+    /// the Brazilian site
+    pub fn for_region(region: &str, api: bool) -> Self {
+        let code = region.trim().to_uppercase();
+        let site = SITES
+            .iter()
+            .find(|site| site.region == code)
+            .copied()
+            .unwrap_or(SITES[0]);
+        Self { site, api }
+    }
+
+    pub fn base(&self) -> String {
+        format!("https://www.{}", self.site.domain)
+    }
+
+    pub fn site_id(&self) -> &'static str {
+        self.site.id
+    }
+
+    pub fn currency(&self) -> &'static str {
+        self.site.currency
+    }
+
+    /// Page `page` (from 0) of a category: a site or API search, the best
+    /// sellers page for "trending", or a listing URL. Pages that aren't
+    /// paginated (best sellers, other URLs) give the same URL for every
+    /// page, whose results add nothing new and end the category.
+    pub fn category_page(&self, category: &str, page: u32) -> String {
+        let category = category.trim();
+        if category.starts_with("http") || category.starts_with("file") {
+            return if category.contains("://lista.") {
+                site_page(category, page)
+            } else {
+                category.to_string()
+            };
+        }
+        if category.eq_ignore_ascii_case("trending") {
+            return format!("{}/{}", self.base(), self.site.best_sellers);
+        }
+
+        if self.api {
+            return self.api_search(category, page);
+        }
+        let keyword = category
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        let mut url = reqwest::Url::parse(&format!("https://lista.{}", self.site.domain))
+            .expect("valid site URL");
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.clear().push(&keyword);
+        }
+        site_page(url.as_str(), page)
+    }
+
+    /// REST API search of a category: by category id when the app niche
+    /// maps to one, by keyword otherwise
+    fn api_search(&self, category: &str, page: u32) -> String {
+        let mut url = reqwest::Url::parse(&format!("{}/sites/{}/search", API_URL, self.site_id()))
+            .expect("valid API URL");
+        {
+            let mut query = url.query_pairs_mut();
+            match category_id(category).filter(|id| id.starts_with(self.site_id())) {
+                Some(id) => query.append_pair("category", id),
+                None => query.append_pair("q", category),
+            };
+            query
+                .append_pair("offset", &(page * API_PAGE_SIZE).to_string())
+                .append_pair("limit", &API_PAGE_SIZE.to_string());
+        }
+        url.to_string()
+    }
+
+    pub fn product_url(&self, item_id: &str) -> String {
+        let (prefix, number) = item_id.split_at(item_id.len().min(3));
+        format!(
+            "https://{}.{}/{}-{}",
+            self.site.item_host, self.site.domain, prefix, number
+        )
+    }
+
+    /// Absolute https URL of a link or image, which pages give without
+    /// scheme or over http
+    pub fn absolute_url(&self, url: &str) -> String {
+        if let Some(rest) = url.strip_prefix("//") {
+            format!("https://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            format!("https://{}", rest)
+        } else if url.starts_with("https://") {
+            url.to_string()
+        } else {
+            format!("https://{}", url.trim_start_matches('/'))
+        }
+    }
+}
+
+/// Page `page` (from 0) of a site listing: `_Desde_<first result>` appended
+/// to its path
+fn site_page(url: &str, page: u32) -> String {
+    if page == 0 {
+        return url.to_string();
+    }
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, format!("?{}", query)),
+        None => (url, String::new()),
+    };
+    format!(
+        "{}_Desde_{}{}",
+        path.trim_end_matches('/'),
+        page * SITE_PAGE_SIZE + 1,
+        query
+    )
+}
+
+/// Reads Mercado Livre products from API responses, page state and listings
+pub struct MercadolivreParser {
+    urls: MercadolivreUrls,
+    locale: NumberLocale,
+    selectors: MercadolivreSelectors,
+}
+
+impl MercadolivreParser {
+    pub fn new(
+        urls: MercadolivreUrls,
+        locale: NumberLocale,
+        selectors: MercadolivreSelectors,
+    ) -> Self {
+        Self {
+            urls,
+            locale,
+            selectors,
+        }
+    }
+
+    /// Products of the captured responses, in order; other JSON is skipped
+    pub fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        responses
+            .iter()
+            .filter_map(|response| serde_json::from_str::<Value>(&response.body).ok())
+            .flat_map(|data| self.parse_items(&data))
+            .collect()
+    }
+
+    /// Products of a search API response (`results`) or of the site's
+    /// result cards (`polycard`), wherever they are nested
+    pub fn parse_items(&self, data: &Value) -> Vec<Product> {
+        let mut items = Vec::new();
+        collect_items(data, &mut items);
+
+        let mut products: Vec<Product> = Vec::new();
+        for product in items.into_iter().filter_map(|item| match item {
+            Item::Api(item) => self.parse_api_item(item),
+            Item::Card(card) => self.parse_polycard(card),
+        }) {
+            if !products.iter().any(|p| p.tiktok_id == product.tiktok_id) {
+                products.push(product);
+            }
+        }
+        products
+    }
+
+    fn parse_api_item(&self, item: &Value) -> Option<Product> {
+        let item_id = item_id(item["id"].as_str()?)?;
+        let title = item["title"]
+            .as_str()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())?;
+
+        let mut product = self.product(&item_id, title);
+        if let Some(currency) = item["currency_id"].as_str() {
+            product.currency = currency.to_string();
+        }
+        product.price = item["price"].as_f64().unwrap_or(0.0);
+        product.original_price = item["original_price"]
+            .as_f64()
+            .filter(|op| *op > product.price);
+        product.is_on_sale = product.original_price.is_some();
+        product.sales_count = item["sold_quantity"].as_i64().unwrap_or(0) as i32;
+        product.stock_level = item["available_quantity"].as_i64().map(|q| q as i32);
+        product.in_stock = product.stock_level != Some(0);
+        product.has_free_shipping = item["shipping"]["free_shipping"].as_bool() == Some(true);
+        product.seller_name = item["seller"]["nickname"].as_str().map(str::to_string);
+        if let Some(permalink) = item["permalink"].as_str() {
+            product.product_url = self.urls.absolute_url(permalink);
+        }
+
+        product.image_url = item["thumbnail"]
+            .as_str()
+            .map(|image| self.urls.absolute_url(image));
+        product.images = product.image_url.iter().cloned().collect();
+
+        Some(product)
+    }
+
+    fn parse_polycard(&self, card: &Value) -> Option<Product> {
+        let metadata = &card["metadata"];
+        let item_id = item_id(metadata["id"].as_str()?)?;
+        let components = card["components"].as_array()?;
+        let component = |kind: &str| {
+            components
+                .iter()
+                .find(|c| c["type"].as_str() == Some(kind))
+                .map(|c| &c[kind])
+        };
+        let title = component("title")
+            .and_then(|title| title["text"].as_str())
+            .map(str::trim)
+            .filter(|t| !t.is_empty())?;
+
+        let mut product = self.product(&item_id, title);
+        if let Some(price) = component("price") {
+            if let Some(currency) = price["current_price"]["currency"].as_str() {
+                product.currency = currency.to_string();
+            }
+            product.price = price["current_price"]["value"].as_f64().unwrap_or(0.0);
+            product.original_price = price["previous_price"]["value"]
+                .as_f64()
+                .filter(|op| *op > product.price);
+            product.is_on_sale = product.original_price.is_some();
+        }
+        if let Some(reviews) = component("reviews") {
+            product.product_rating = reviews["rating_average"]
+                .as_f64()
+                .filter(|rating| *rating > 0.0 && *rating <= 5.0);
+            product.reviews_count = reviews["total"].as_i64().unwrap_or(0) as i32;
+        }
+        product.seller_name = component("seller")
+            .and_then(|seller| seller["text"].as_str())
+            .map(seller_name)
+            .filter(|name| !name.is_empty());
+        product.has_free_shipping = component("shipping")
+            .is_some_and(|shipping| mentions_free_shipping(&shipping.to_string()));
+        if let Some(url) = metadata["url"].as_str() {
+            product.product_url = self.urls.absolute_url(url);
+        }
+
+        product.images = card["pictures"]["pictures"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|picture| picture["id"].as_str())
+            .map(picture_url)
+            .collect();
+        product.image_url = product.images.first().cloned();
+
+        Some(product)
+    }
+
+    /// Products of the rendered result cards, or of the JSON the browser
+    /// shows when the page is an API response
+    pub fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        let document = Html::parse_document(html);
+        let selector = |css: &str| Selector::parse(css).ok();
+        let Some(cards) = selector(&self.selectors.card) else {
+            return Vec::new();
+        };
+        let field = |card: &ElementRef, css: &str| -> Option<String> {
+            let selector = selector(css)?;
+            card.select(&selector)
+                .map(|element| element.text().collect::<Vec<_>>().join(" "))
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .find(|text| !text.is_empty())
+        };
+        let amount = |card: &ElementRef, css: &str| -> Option<f64> {
+            let selector = selector(css)?;
+            let element = card.select(&selector).next()?;
+            self.amount(&element)
+        };
+        let link_selector = selector(&self.selectors.link);
+        let image_selector = selector(&self.selectors.image);
+
+        let mut products: Vec<Product> = Vec::new();
+        for card in document.select(&cards) {
+            let href = link_selector
+                .as_ref()
+                .and_then(|links| card.select(links).find_map(|a| a.value().attr("href")));
+            let Some(item_id) = href.and_then(item_id) else {
+                continue;
+            };
+            if products
+                .iter()
+                .any(|p| p.tiktok_id == format!("mercadolivre-{}", item_id))
+            {
+                continue;
+            }
+            let Some(title) = field(&card, &self.selectors.title) else {
+                continue;
+            };
+
+            let mut product = self.product(&item_id, &title);
+            if let Some(href) = href {
+                product.product_url = self.urls.absolute_url(href);
+            }
+            product.price = amount(&card, &self.selectors.price).unwrap_or(0.0);
+            product.original_price =
+                amount(&card, &self.selectors.original_price).filter(|op| *op > product.price);
+            product.is_on_sale = product.original_price.is_some();
+            product.product_rating = field(&card, &self.selectors.rating)
+                .and_then(|text| text.replace(',', ".").parse::<f64>().ok())
+                .filter(|rating| *rating > 0.0 && *rating <= 5.0);
+            product.reviews_count = field(&card, &self.selectors.reviews)
+                .map(|text| TikTokParser::parse_sales_text(&text))
+                .unwrap_or(0);
+            product.seller_name = field(&card, &self.selectors.seller)
+                .map(|text| seller_name(&text))
+                .filter(|name| !name.is_empty());
+            product.has_free_shipping = field(&card, &self.selectors.shipping)
+                .is_some_and(|text| mentions_free_shipping(&text));
+
+            // Images below the fold keep their URL in data-src until loaded
+            product.image_url = image_selector
+                .as_ref()
+                .and_then(|images| card.select(images).next())
+                .and_then(|img| {
+                    ["data-src", "src"]
+                        .iter()
+                        .filter_map(|attr| img.value().attr(attr))
+                        .find(|src| !src.is_empty() && !src.starts_with("data:"))
+                })
+                .map(|src| self.urls.absolute_url(src));
+            product.images = product.image_url.iter().cloned().collect();
+
+            products.push(product);
+        }
+
+        if products.is_empty() {
+            let text = document.root_element().text().collect::<String>();
+            if let Ok(data) = serde_json::from_str::<Value>(text.trim()) {
+                return self.parse_items(&data);
+            }
+        }
+        products
+    }
+
+    /// Amount of a price element: its whole and cents parts when it has
+    /// them, its text read in the site's locale otherwise
+    fn amount(&self, element: &ElementRef) -> Option<f64> {
+        let part = |css: &str| -> Option<String> {
+            let selector = Selector::parse(css).ok()?;
+            let digits: String = element
+                .select(&selector)
+                .next()?
+                .text()
+                .flat_map(str::chars)
+                .filter(char::is_ascii_digit)
+                .collect();
+            (!digits.is_empty()).then_some(digits)
+        };
+
+        let amount = match part(&self.selectors.fraction) {
+            Some(whole) => {
+                let cents = part(&self.selectors.cents)
+                    .and_then(|cents| cents.parse::<f64>().ok())
+                    .unwrap_or(0.0);
+                whole.parse::<f64>().ok()? + cents / 100.0
+            }
+            None => TikTokParser::parse_price_text(
+                &element.text().collect::<Vec<_>>().join(""),
+                self.locale,
+            ),
+        };
+        (amount > 0.0).then_some(amount)
+    }
+
+    /// Script returning the first embedded result list found as JSON text
+    pub fn state_script(&self) -> Option<String> {
+        if self.selectors.state_roots.is_empty() {
+            return None;
+        }
+        let roots = serde_json::to_string(&self.selectors.state_roots).ok()?;
+        Some(format!(
+            r#"
+            (() => {{
+                for (const path of {}) {{
+                    try {{
+                        const node = path.split('.').reduce((o, k) => o && o[k], window);
+                        if (node) return JSON.stringify(node);
+                    }} catch (e) {{}}
+                }}
+                return null;
+            }})()
+        "#,
+            roots
+        ))
+    }
+
+    pub fn is_blocked(&self, path: &str, content: &str) -> bool {
+        self.selectors
+            .block_markers
+            .iter()
+            .any(|marker| path.contains(marker.as_str()) || content.contains(marker.as_str()))
+    }
+
+    /// Product with its id and title; everything else still to be read
+    fn product(&self, item_id: &str, title: &str) -> Product {
+        let now = chrono::Utc::now().to_rfc3339();
+        Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: format!("mercadolivre-{}", item_id),
+            title: title.to_string(),
+            description: None,
+            price: 0.0,
+            original_price: None,
+            currency: self.urls.currency().to_string(),
+            category: None,
+            subcategory: None,
+            seller_name: None,
+            seller_rating: None,
+            product_rating: None,
+            reviews_count: 0,
+            sales_count: 0,
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: None,
+            images: vec![],
+            video_url: None,
+            product_url: self.urls.product_url(item_id),
+            affiliate_url: None,
+            has_free_shipping: false,
+            is_trending: false,
+            is_on_sale: false,
+            in_stock: true,
+            stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now,
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Mercadolivre,
+        }
+    }
+}
+
+/// A result found in JSON
+enum Item<'a> {
+    /// Search API result
+    Api(&'a Value),
+    /// Result card of the site
+    Card(&'a Value),
+}
+
+/// Results under `value`: result cards, and objects with an item id, a
+/// title and a price
+fn collect_items<'a>(value: &'a Value, items: &mut Vec<Item<'a>>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(card) = map.get("polycard") {
+                items.push(Item::Card(card));
+            } else if map
+                .get("id")
+                .and_then(Value::as_str)
+                .is_some_and(|id| item_id(id).is_some())
+                && map.contains_key("title")
+                && map.contains_key("price")
+            {
+                items.push(Item::Api(value));
+            } else {
+                map.values().for_each(|child| collect_items(child, items));
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|child| collect_items(child, items)),
+        _ => {}
+    }
+}
+
+/// Image of a picture id of the result cards
+fn picture_url(id: &str) -> String {
+    format!("https://http2.mlstatic.com/D_NQ_NP_{}-O.webp", id)
+}
+
+/// Store name of a card's seller line, e.g. "Por Loja Oficial"
+fn seller_name(text: &str) -> String {
+    let text = text.trim();
+    ["Vendido por ", "Por ", "por "]
+        .iter()
+        .find_map(|prefix| text.strip_prefix(prefix))
+        .unwrap_or(text)
+        .trim()
+        .to_string()
+}
+
+fn mentions_free_shipping(text: &str) -> bool {
+    let text = text.to_lowercase();
+    [
+        "frete grátis",
+        "frete gratis",
+        "envío gratis",
+        "envio gratis",
+    ]
+    .iter()
+    .any(|marker| text.contains(marker))
+}
+
+/// Mercado Livre site of the configured region
+pub struct Mercadolivre {
+    urls: MercadolivreUrls,
+    parser: MercadolivreParser,
+}
+
+impl Mercadolivre {
+    pub fn new(config: &ScraperConfig) -> Self {
+        let urls = MercadolivreUrls::for_region(&config.region, config.mercadolivre_api);
+        let locale = NumberLocale::from_currency(urls.currency())
+            .unwrap_or_else(|| NumberLocale::from_region(&config.region));
+        Self {
+            parser: MercadolivreParser::new(urls.clone(), locale, MercadolivreSelectors::bundled()),
+            urls,
+        }
+    }
+}
+
+impl Storefront for Mercadolivre {
+    fn marketplace(&self) -> MarketplaceAccess {
+        MarketplaceAccess::Mercadolivre
+    }
+
+    fn start_key(&self) -> &'static str {
+        "scraper.starting_mercadolivre"
+    }
+
+    fn category_page(&self, category: &str, page: u32) -> String {
+        self.urls.category_page(category, page)
+    }
+
+    fn wait_conditions(&self) -> Vec<WaitCondition> {
+        // API responses have no cards to wait for
+        if self.urls.api {
+            return vec![WaitCondition::NetworkIdle {
+                idle_ms: 500,
+                timeout_ms: 10000,
+            }];
+        }
+        vec![
+            WaitCondition::Selector {
+                selector: self.parser.selectors.card.clone(),
+                timeout_ms: 10000,
+            },
+            WaitCondition::NetworkIdle {
+                idle_ms: 1000,
+                timeout_ms: 10000,
+            },
+        ]
+    }
+
+    fn is_blocked(&self, path: &str, content: &str) -> bool {
+        self.parser.is_blocked(path, content)
+    }
+
+    fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        self.parser.parse_api_responses(responses)
+    }
+
+    fn state_script(&self) -> Option<String> {
+        self.parser.state_script()
+    }
+
+    fn parse_state(&self, state: &Value) -> Vec<Product> {
+        self.parser.parse_items(state)
+    }
+
+    fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        self.parser.parse_listing_html(html)
+    }
+}
+
+pub type MercadolivreScraper = StorefrontScraper<Mercadolivre>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> MercadolivreParser {
+        MercadolivreParser::new(
+            MercadolivreUrls::for_region("BR", false),
+            NumberLocale::Br,
+            MercadolivreSelectors::bundled(),
+        )
+    }
+
+    #[test]
+    fn test_site_urls() {
+        let urls = MercadolivreUrls::for_region("br", false);
+        assert_eq!(
+            urls.category_page("Fone Bluetooth", 0),
+            "https://lista.mercadolivre.com.br/fone-bluetooth"
+        );
+        assert_eq!(
+            urls.category_page("fone bluetooth", 2),
+            "https://lista.mercadolivre.com.br/fone-bluetooth_Desde_97"
+        );
+        assert_eq!(
+            urls.category_page("trending", 1),
+            "https://www.mercadolivre.com.br/mais-vendidos"
+        );
+        assert_eq!(
+            urls.category_page("https://lista.mercadolivre.com.br/celulares/", 1),
+            "https://lista.mercadolivre.com.br/celulares_Desde_49"
+        );
+        assert_eq!(
+            urls.product_url("MLB1234567890"),
+            "https://produto.mercadolivre.com.br/MLB-1234567890"
+        );
+
+        let urls = MercadolivreUrls::for_region("MX", false);
+        assert_eq!(urls.site_id(), "MLM");
+        assert_eq!(urls.currency(), "MXN");
+        assert_eq!(MercadolivreUrls::for_region("JP", false).site_id(), "MLB");
+    }
+
+    #[test]
+    fn test_api_urls() {
+        let urls = MercadolivreUrls::for_region("BR", true);
+        assert_eq!(
+            urls.category_page("Beleza", 0),
+            "https://api.mercadolibre.com/sites/MLB/search?category=MLB1246&offset=0&limit=50"
+        );
+        assert_eq!(
+            urls.category_page("fone bluetooth", 1),
+            "https://api.mercadolibre.com/sites/MLB/search?q=fone+bluetooth&offset=50&limit=50"
+        );
+        // Category ids are those of the Brazilian site
+        assert_eq!(
+            MercadolivreUrls::for_region("AR", true).category_page("beleza", 0),
+            "https://api.mercadolibre.com/sites/MLA/search?q=beleza&offset=0&limit=50"
+        );
+    }
+
+    #[test]
+    fn test_item_ids() {
+        assert_eq!(
+            item_id("https://produto.mercadolivre.com.br/MLB-3456789012-fone-_JM"),
+            Some("MLB3456789012".to_string())
+        );
+        assert_eq!(item_id("/p/MLB19876543"), Some("MLB19876543".to_string()));
+        assert_eq!(item_id("https://www.mercadolivre.com.br/ofertas"), None);
+    }
+
+    #[test]
+    fn test_parse_api_results() {
+        let data = serde_json::json!({
+            "site_id": "MLB",
+            "paging": { "total": 2, "offset": 0, "limit": 50 },
+            "results": [
+                {
+                    "id": "MLB3456789012",
+                    "title": "Fone De Ouvido Bluetooth",
+                    "price": 79.9,
+                    "original_price": 129.9,
+                    "currency_id": "BRL",
+                    "sold_quantity": 500,
+                    "available_quantity": 30,
+                    "thumbnail": "http://http2.mlstatic.com/D_612345-MLB123-I.jpg",
+                    "permalink": "https://produto.mercadolivre.com.br/MLB-3456789012-fone-_JM",
+                    "shipping": { "free_shipping": true },
+                    "seller": { "nickname": "LOJAOFICIAL" }
+                },
+                { "id": "MLB1", "title": "", "price": 10 }
+            ]
+        });
+
+        let products = parser().parse_items(&data);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.tiktok_id, "mercadolivre-MLB3456789012");
+        assert_eq!(product.marketplace, MarketplaceAccess::Mercadolivre);
+        assert_eq!(product.price, 79.9);
+        assert_eq!(product.original_price, Some(129.9));
+        assert!(product.is_on_sale);
+        assert_eq!(product.sales_count, 500);
+        assert_eq!(product.stock_level, Some(30));
+        assert!(product.has_free_shipping);
+        assert_eq!(product.seller_name.as_deref(), Some("LOJAOFICIAL"));
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_612345-MLB123-I.jpg")
+        );
+    }
+
+    #[test]
+    fn test_parse_polycards() {
+        let state = serde_json::json!([{
+            "polycard": {
+                "metadata": {
+                    "id": "MLB4455667788",
+                    "url": "produto.mercadolivre.com.br/MLB-4455667788-mini-projetor-_JM"
+                },
+                "pictures": { "pictures": [{ "id": "987654-MLB111_022026" }] },
+                "components": [
+                    { "type": "title", "title": { "text": "Mini Projetor Portátil" } },
+                    { "type": "price", "price": {
+                        "current_price": { "value": 189.9, "currency": "BRL" },
+                        "previous_price": { "value": 379.8 }
+                    }},
+                    { "type": "reviews", "reviews": { "rating_average": 4.6, "total": 1234 } },
+                    { "type": "seller", "seller": { "text": "Por Loja Projetores" } },
+                    { "type": "shipping", "shipping": { "text": "Frete grátis" } }
+                ]
+            }
+        }]);
+
+        let products = parser().parse_items(&state);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.title, "Mini Projetor Portátil");
+        assert_eq!(product.price, 189.9);
+        assert_eq!(product.original_price, Some(379.8));
+        assert_eq!(product.product_rating, Some(4.6));
+        assert_eq!(product.reviews_count, 1234);
+        assert_eq!(product.seller_name.as_deref(), Some("Loja Projetores"));
+        assert!(product.has_free_shipping);
+        assert_eq!(
+            product.product_url,
+            "https://produto.mercadolivre.com.br/MLB-4455667788-mini-projetor-_JM"
+        );
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_NQ_NP_987654-MLB111_022026-O.webp")
+        );
+    }
+
+    #[test]
+    fn test_parse_listing_html() {
+        let html = r#"<html><body><ol>
+            <li class="ui-search-layout__item"><div class="poly-card">
+                <img class="poly-component__picture" src="data:image/gif;base64,R0lGOD"
+                     data-src="https://http2.mlstatic.com/D_Q_NP_card-O.webp">
+                <a class="poly-component__title"
+                   href="https://produto.mercadolivre.com.br/MLB-1122334455-garrafa-_JM">Garrafa Térmica 1L</a>
+                <span class="poly-component__seller">Por Casa Store</span>
+                <span class="poly-reviews__rating">4,9</span>
+                <span class="poly-reviews__total">(2.345)</span>
+                <s class="andes-money-amount andes-money-amount--previous">
+                    <span class="andes-money-amount__currency-symbol">R$</span>
+                    <span class="andes-money-amount__fraction">1.299</span>
+                </s>
+                <div class="poly-price__current"><span class="andes-money-amount">
+                    <span class="andes-money-amount__currency-symbol">R$</span>
+                    <span class="andes-money-amount__fraction">1.049</span><span
+                          class="andes-money-amount__decimal-separator">,</span><span
+                          class="andes-money-amount__cents">90</span>
+                </span></div>
+                <div class="poly-component__shipping">Frete grátis</div>
+            </div></li>
+        </ol></body></html>"#;
+
+        let products = parser().parse_listing_html(html);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.tiktok_id, "mercadolivre-MLB1122334455");
+        assert_eq!(product.title, "Garrafa Térmica 1L");
+        assert_eq!(product.price, 1049.9);
+        assert_eq!(product.original_price, Some(1299.0));
+        assert_eq!(product.product_rating, Some(4.9));
+        assert_eq!(product.reviews_count, 2345);
+        assert_eq!(product.seller_name.as_deref(), Some("Casa Store"));
+        assert!(product.has_free_shipping);
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://http2.mlstatic.com/D_Q_NP_card-O.webp")
+        );
+    }
+
+    #[test]
+    fn test_parse_api_page() {
+        // What the browser shows when it opens a search API URL
+        let html = r#"<html><head></head><body><pre>{"results":[{"id":"MLB998877665","title":"Luminária LED","price":35.5}]}</pre></body></html>"#;
+
+        let products = parser().parse_listing_html(html);
+        assert_eq!(products.len(), 1);
+        assert_eq!(products[0].title, "Luminária LED");
+        assert_eq!(products[0].price, 35.5);
+    }
+
+    #[test]
+    fn test_blocked_pages() {
+        let parser = parser();
+        assert!(parser.is_blocked("/gz/account-verification", ""));
+        assert!(parser.is_blocked(
+            "/sites/MLB/search",
+            r#"<pre>{"message":"forbidden","error":"forbidden","status":403}</pre>"#
+        ));
+        assert!(!parser.is_blocked("/fone-bluetooth", "<html>fone</html>"));
+    }
+}
@@ -1,45 +1,193 @@
 // Scraper Module - Main Entry Point
 // Coordinates all scraping submodules
 
+pub mod actions;
+pub mod affiliate;
+pub mod aliexpress;
+pub mod benchmark;
 pub mod antibot;
+pub mod blocking;
 pub mod browser;
+pub mod driver;
+pub mod i18n;
+pub mod intercept;
+pub mod jsonld;
+pub mod mercadolivre;
 pub mod models;
+pub mod ocr;
+pub mod offline;
+pub mod pagination;
 pub mod parser;
 pub mod pool;
+pub mod progress;
 pub mod proxy;
 pub mod research_api;
-
-pub use antibot::AntiDetection;
+pub mod session;
+pub mod shopee;
+pub mod stealth;
+pub mod storefront;
+pub mod tabs;
+pub mod typing;
+pub mod wait;
+pub mod webdriver;
+
+pub use aliexpress::{Aliexpress, AliexpressScraper};
+pub use antibot::{AntiDetection, Fingerprint};
 pub use browser::BrowserManager;
-pub use parser::TikTokParser;
+pub use driver::{BrowserBackend, PageDriver};
+pub use i18n::Language;
+pub use mercadolivre::{Mercadolivre, MercadolivreScraper};
+pub use pagination::Paginator;
+pub use parser::{NumberLocale, TikTokParser};
 pub use proxy::ProxyPool;
 pub use research_api::ResearchApi;
+pub use shopee::{Shopee, ShopeeScraper};
 
-use crate::models::{Product, ScraperStatus};
+use crate::database;
+use crate::models::{
+    CategoryCheckpoint, CategorySummary, LocalizedMessage, LogEntry, LogLevel, MarketplaceAccess, Product,
+    ScrapeStage, ScraperStatus,
+};
 use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::{stream, StreamExt, TryStreamExt};
 use rand::Rng;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use sysinfo::System;
+use tauri::Emitter;
 // Ensure SystemExt is available if needed, or just System
 use tokio::sync::Mutex;
 
-use self::models::ScraperConfig;
+use self::actions::{ActionKind, ActionLog};
+use crate::config::ScraperConfig;
+use self::pagination::PaginationStrategy;
+use self::progress::ProgressTracker;
+use self::session::SessionLog;
+use self::intercept::NetworkCapture;
+use self::tabs::{ScrapeTab, TabLease, TabPool};
+use self::webdriver::WebDriverSession;
+
+const SHOP_HOME_URL: &str = "https://shop.tiktok.com/";
+const SEARCH_URL_PREFIX: &str = "https://shop.tiktok.com/search?keyword=";
+
+/// Log entries kept in the status; older ones are dropped
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// Relaunches allowed per category before a browser crash fails the run
+const MAX_BROWSER_RESTARTS: u32 = 2;
+
+/// Where to pick a page up again after the browser was relaunched
+#[derive(Default)]
+struct PageCheckpoint {
+    /// Last page number reached with URL pagination
+    page_number: u32,
+    /// Page height reached with infinite scroll
+    scroll_offset: i64,
+}
+
+/// A page operation exceeded its timeout; the tab should be recycled
+#[derive(Debug)]
+struct PageTimeout(&'static str);
+
+impl std::fmt::Display for PageTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "page timed out during {}", self.0)
+    }
+}
+
+impl std::error::Error for PageTimeout {}
+
+/// A marketplace scraper: runs the categories of its config and returns the
+/// products found, which the caller saves before calling `finish_progress`
+pub trait Scraper: Send + Sync {
+    fn start(&self) -> BoxFuture<'_, Result<Vec<Product>>>;
+
+    /// Mark the run complete once its results are saved
+    fn finish_progress(&self) -> BoxFuture<'_, ()>;
+}
+
+/// Whether `marketplace` has a scraper
+pub fn has_scraper(marketplace: &MarketplaceAccess) -> bool {
+    matches!(
+        marketplace,
+        MarketplaceAccess::Tiktok
+            | MarketplaceAccess::Shopee
+            | MarketplaceAccess::Aliexpress
+            | MarketplaceAccess::Mercadolivre
+    )
+}
+
+/// Whether products of `marketplace` can be re-fetched from their own page.
+/// Only the TikTok Shop scraper reads single product pages; the others only
+/// run listings.
+pub fn can_refresh(marketplace: &MarketplaceAccess) -> bool {
+    matches!(marketplace, MarketplaceAccess::Tiktok)
+}
+
+/// Scraper of the marketplace selected in `config.marketplace`
+pub fn for_marketplace(
+    config: ScraperConfig,
+    status: Arc<Mutex<ScraperStatus>>,
+    app_handle: Option<tauri::AppHandle>,
+) -> Result<Box<dyn Scraper>> {
+    match config.marketplace {
+        MarketplaceAccess::Tiktok => Ok(Box::new(TikTokScraper::new(config, status, app_handle))),
+        MarketplaceAccess::Shopee => Ok(Box::new(ShopeeScraper::new(
+            Shopee::new(&config),
+            config,
+            status,
+            app_handle,
+        ))),
+        MarketplaceAccess::Aliexpress => Ok(Box::new(AliexpressScraper::new(
+            Aliexpress::new(&config),
+            config,
+            status,
+            app_handle,
+        ))),
+        MarketplaceAccess::Mercadolivre => Ok(Box::new(MercadolivreScraper::new(
+            Mercadolivre::new(&config),
+            config,
+            status,
+            app_handle,
+        ))),
+        ref other => Err(anyhow::anyhow!("No scraper for marketplace {:?}", other)),
+    }
+}
 
 /// Main TikTok Scraper
 pub struct TikTokScraper {
     browser: BrowserManager,
     parser: TikTokParser,
     antibot: AntiDetection,
+    /// Identity of the session profile, shared by every tab of the run
+    fingerprint: Fingerprint,
     proxy_pool: Option<ProxyPool>,
     status: Arc<Mutex<ScraperStatus>>,
     config: ScraperConfig,
     system: Arc<Mutex<System>>,
+    actions: ActionLog,
     #[allow(dead_code)]
     research_api: ResearchApi,
+    /// Receives `scraper-log`, `scraper://progress`, `scraper://product-found`
+    /// and `scraper://error` events
+    app_handle: Option<tauri::AppHandle>,
+    language: Language,
+    progress: Mutex<ProgressTracker>,
+    /// Where the run checkpoints its progress, to be resumed if cut short
+    session: Option<SessionLog>,
+    /// Products collected before the session was interrupted
+    resumed: Vec<Product>,
 }
 
 impl TikTokScraper {
-    pub fn new(config: ScraperConfig, status: Arc<Mutex<ScraperStatus>>, app_handle: Option<tauri::AppHandle>) -> Self {
+    pub fn new(mut config: ScraperConfig, status: Arc<Mutex<ScraperStatus>>, app_handle: Option<tauri::AppHandle>) -> Self {
+        if config.wait_conditions.is_empty() {
+            config.wait_conditions = wait::WaitCondition::defaults();
+        }
+
         let proxy_pool = if config.use_proxy && !config.proxies.is_empty() {
             Some(ProxyPool::new(config.proxies.clone()))
         } else {
@@ -47,68 +195,268 @@ impl TikTokScraper {
         };
 
         let mut browser =
-            BrowserManager::new(config.headless).with_timeout(config.page_load_timeout_ms / 1000);
+            BrowserManager::new(config.headless)
+                .with_profile(config.launch_profile)
+                .with_timeout(config.timeout as u64 / 1000);
 
         if let Some(path) = &config.user_data_path {
             browser = browser.with_user_data(std::path::PathBuf::from(path));
         }
 
-        if let Some(handle) = app_handle {
-            browser = browser.with_app_handle(handle);
+        if let Some(handle) = &app_handle {
+            browser = browser.with_app_handle(handle.clone());
+        }
+
+        if let Some(url) = &config.remote_debugging_url {
+            browser = browser.with_remote_debugging(url.clone());
         }
 
         let research_api = ResearchApi::new(config.api_key.clone(), config.api_secret.clone());
 
+        let antibot = AntiDetection::new();
+        let fingerprint = match &config.db_path {
+            Some(db_path) => antibot
+                .profile_fingerprint(std::path::Path::new(db_path), &config.profile_id)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to load profile fingerprint: {}", e);
+                    antibot.generate_fingerprint()
+                }),
+            None => antibot.generate_fingerprint(),
+        };
+
+        let run_id = config
+            .run_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let actions = ActionLog::new(run_id, config.db_path.as_ref().map(std::path::PathBuf::from));
+        let language = Language::from_tag(&config.language);
+        let (session, resumed) = SessionLog::for_run(&config);
+        let progress = ProgressTracker::new(
+            &Self::run_categories(&config),
+            config.max_products,
+            config.enrich_details,
+        );
+
         Self {
             browser,
-            parser: TikTokParser::new(config.selectors.clone()),
-            antibot: AntiDetection::new(),
+            parser: TikTokParser::new(config.selectors.clone())
+                .with_locale(NumberLocale::from_region(&config.region))
+                .with_raw_capture(config.raw_capture)
+                .with_strategies(config.extraction_strategies.clone().unwrap_or_default()),
+            antibot,
+            fingerprint,
             proxy_pool,
             status,
             config,
             system: Arc::new(Mutex::new(System::new_all())),
+            actions,
             research_api,
+            app_handle,
+            language,
+            progress: Mutex::new(progress),
+            session,
+            resumed,
         }
     }
 
-    async fn add_log(&self, message: String) {
-        let mut status = self.status.lock().await;
-        let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
-        let log_entry = format!("[{}] {}", timestamp, message);
+    /// Emit an event to the app, when the scraper runs inside it
+    fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(handle) = &self.app_handle {
+            let _ = handle.emit(event, payload);
+        }
+    }
+
+    /// Emit a product added to the run's results as `scraper://product-found`,
+    /// before the run is over and the results are saved
+    fn product_found(&self, category: &str, product: &Product) {
+        self.emit(
+            "scraper://product-found",
+            serde_json::json!({ "category": category, "product": product }),
+        );
+    }
+
+    /// Log a catalog message in the configured language and emit it as
+    /// `scraper-log`; errors are emitted as `scraper://error` too
+    async fn add_log(
+        &self,
+        level: LogLevel,
+        category: Option<&str>,
+        key: &str,
+        params: &[(&str, String)],
+    ) {
+        let message = i18n::localize(self.language, key, params);
+        let entry = LogEntry {
+            level,
+            code: message.key,
+            params: message.params,
+            message: message.text,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            category: category.map(str::to_string),
+        };
+
+        {
+            let mut status = self.status.lock().await;
+            status.logs.push(entry.clone());
 
-        status.logs.push(log_entry);
+            // Keep only the most recent entries
+            if status.logs.len() > MAX_LOG_ENTRIES {
+                let excess = status.logs.len() - MAX_LOG_ENTRIES;
+                status.logs.drain(..excess);
+            }
+        }
 
-        // Keep only last 50 logs
-        if status.logs.len() > 50 {
-            status.logs.remove(0);
+        // Only collection runs have a run id to keep their logs under
+        if let (true, Some(run_id), Some(db_path)) = (
+            self.config.persist_logs,
+            &self.config.run_id,
+            &self.config.db_path,
+        ) {
+            if let Err(e) = database::add_run_log(std::path::Path::new(db_path), run_id, &entry) {
+                log::warn!("Failed to persist scraper log: {}", e);
+            }
+        }
+
+        self.emit("scraper-log", &entry);
+        if entry.level == LogLevel::Error {
+            self.emit("scraper://error", &entry);
         }
     }
 
+    /// Apply a change to the progress model, mirror it into the status and
+    /// emit it as `scraper://progress`
+    async fn update_progress(&self, update: impl FnOnce(&mut ProgressTracker)) {
+        let (overall, detail) = {
+            let mut progress = self.progress.lock().await;
+            update(&mut progress);
+            (progress.overall(), progress.snapshot())
+        };
+
+        {
+            let mut status = self.status.lock().await;
+            status.progress = overall;
+            status.progress_detail = detail.clone();
+        }
+
+        self.emit(
+            "scraper://progress",
+            serde_json::json!({ "progress": overall, "detail": detail }),
+        );
+    }
+
+    /// Mark the run complete once its results are saved
+    pub async fn finish_progress(&self) {
+        self.update_progress(|p| p.set_stage(ScrapeStage::Done)).await;
+    }
+
+    /// Show what is being processed in the status ("now processing")
+    async fn set_current(&self, category: Option<&str>, url: Option<&str>, product: Option<&str>) {
+        let mut status = self.status.lock().await;
+        status.current_category = category.map(str::to_string);
+        status.current_url = url.map(str::to_string);
+        status.current_product = product.map(str::to_string);
+    }
+
+    /// Update the result counters of a category in the status
+    async fn count_category(&self, category: &str, update: impl FnOnce(&mut CategorySummary)) {
+        let mut status = self.status.lock().await;
+        match status.categories.iter_mut().find(|c| c.category == category) {
+            Some(summary) => update(summary),
+            None => {
+                let mut summary = CategorySummary {
+                    category: category.to_string(),
+                    ..Default::default()
+                };
+                update(&mut summary);
+                status.categories.push(summary);
+            }
+        }
+    }
+
+    fn localize(&self, key: &str) -> LocalizedMessage {
+        i18n::localize(self.language, key, &[])
+    }
+
+    fn set_status_message(&self, status: &mut ScraperStatus, key: &str) {
+        status.status_message = Some(i18n::translate(self.language, key, &[]));
+        status.status_key = Some(key.to_string());
+    }
+
     pub async fn start(&self) -> Result<Vec<Product>> {
         log::info!("Iniciando scraper do TikTok Shop...");
-        self.add_log("🚀 Iniciando scraper do TikTok Shop...".to_string())
-            .await;
+        self.add_log(LogLevel::Info, None, "scraper.starting", &[]).await;
 
         // Safety Switch Check
         if self.config.safety_switch_enabled {
             // In a real implementation, we would check persistent state here.
             // For now, we just log that it's enabled.
-            self.add_log("🛡️ Safety Switch: ATIVADO".to_string()).await;
+            self.add_log(LogLevel::Info, None, "scraper.safety_switch_on", &[]).await;
+        }
+
+        if !self.resumed.is_empty() {
+            self.add_log(
+                LogLevel::Info,
+                None,
+                "scraper.resuming",
+                &[("count", self.resumed.len().to_string())],
+            )
+            .await;
         }
 
         let mut status = self.status.lock().await;
         status.is_running = true;
         status.progress = 0.0;
         status.started_at = Some(chrono::Utc::now().to_rfc3339());
-        status.status_message = Some("Inicializando...".to_string());
+        // Every category shows up, including the ones that yield nothing
+        status.categories = self
+            .categories()
+            .into_iter()
+            .map(|category| CategorySummary {
+                category,
+                ..Default::default()
+            })
+            .collect();
+        self.set_status_message(&mut status, "status.initializing");
         drop(status);
 
-        let result = self.scrape_products().await;
+        let offline_folder = self
+            .config
+            .offline_folder
+            .as_deref()
+            .map(str::trim)
+            .filter(|folder| !folder.is_empty());
+        let result = match (offline_folder, self.config.backend) {
+            (Some(folder), _) => self.scrape_offline(folder).await,
+            (None, BrowserBackend::Chromium) => self.scrape_products().await,
+            (None, BrowserBackend::Firefox) => self.scrape_products_firefox().await,
+            (None, BrowserBackend::Auto) => match self.scrape_products().await {
+                Err(e) => {
+                    self.add_log(
+                        LogLevel::Warn,
+                        None,
+                        "scraper.firefox_fallback",
+                        &[("error", e.to_string())],
+                    )
+                    .await;
+                    self.browser.stop().await.ok();
+                    self.scrape_products_firefox().await
+                }
+                ok => ok,
+            },
+        };
+        self.finish_session(&result).await;
+
+        // Successful results still have to be saved by the caller
+        let stage = if result.is_ok() {
+            ScrapeStage::Persistence
+        } else {
+            ScrapeStage::Done
+        };
+        self.update_progress(|p| p.set_stage(stage)).await;
 
+        self.set_current(None, None, None).await;
         let mut status = self.status.lock().await;
         status.is_running = false;
-        status.progress = 100.0;
-        status.status_message = Some("Finalizado".to_string());
+        self.set_status_message(&mut status, "status.finished");
 
         match &result {
             Ok(products) => {
@@ -120,19 +468,82 @@ impl TikTokScraper {
                 // Log added inside scrape_products
             }
             Err(e) => {
-                status.errors.push(format!("Falha no scraping: {}", e));
+                status.errors.push(i18n::translate(
+                    self.language,
+                    "error.scrape_failed",
+                    &[("error", e.to_string())],
+                ));
                 log::error!("Falha no scraping: {}", e);
-                // Log added inside scrape_products or here
             }
         }
+        drop(status);
+
+        if let Err(e) = &result {
+            self.add_log(
+                LogLevel::Error,
+                None,
+                "error.scrape_failed",
+                &[("error", e.to_string())],
+            )
+            .await;
+        }
 
-        self.add_log("🏁 Processo finalizado.".to_string()).await;
+        self.add_log(LogLevel::Info, None, "scraper.finished", &[]).await;
         result
     }
 
-    async fn scrape_products(&self) -> Result<Vec<Product>> {
-        // Get proxy if enabled
-        let proxy = if self.config.use_proxy {
+    /// Close the session: completed once every category is through or the
+    /// product limit was reached, interrupted otherwise
+    async fn finish_session(&self, result: &Result<Vec<Product>>) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let stopped = !self.status.lock().await.is_running;
+        match result {
+            Err(e) => session.finish(Some(&e.to_string())),
+            Ok(products)
+                if products.len() >= self.config.max_products as usize
+                    || session.all_done(&self.categories()) =>
+            {
+                session.finish(None)
+            }
+            // Categories abandoned after a page timeout are left to a resume
+            Ok(_) => session.finish(Some(if stopped { "stopped" } else { "incomplete" })),
+        }
+    }
+
+    /// Where the session left `category`; the start of it for a new one
+    fn resume_checkpoint(&self, category: &str) -> PageCheckpoint {
+        self.session
+            .as_ref()
+            .and_then(|session| session.checkpoint(category))
+            .map(|checkpoint| PageCheckpoint {
+                page_number: checkpoint.page_number,
+                scroll_offset: checkpoint.scroll_offset,
+            })
+            .unwrap_or_default()
+    }
+
+    fn save_checkpoint(&self, category: &str, checkpoint: &PageCheckpoint, done: bool) {
+        if let Some(session) = &self.session {
+            session.record_checkpoint(CategoryCheckpoint {
+                category: category.to_string(),
+                page_number: checkpoint.page_number,
+                scroll_offset: checkpoint.scroll_offset,
+                done,
+            });
+        }
+    }
+
+    fn category_finished(&self, category: &str) -> bool {
+        self.session
+            .as_ref()
+            .is_some_and(|session| session.is_done(category))
+    }
+
+    /// Next proxy of the pool, if proxies are enabled
+    async fn next_proxy(&self) -> Option<String> {
+        if self.config.use_proxy {
             if let Some(pool) = &self.proxy_pool {
                 pool.get_next().await.map(|p| p.to_url())
             } else {
@@ -140,11 +551,48 @@ impl TikTokScraper {
             }
         } else {
             None
-        };
+        }
+    }
+
+    fn categories(&self) -> Vec<String> {
+        Self::run_categories(&self.config)
+    }
+
+    fn run_categories(config: &ScraperConfig) -> Vec<String> {
+        if config.categories.is_empty() {
+            vec!["trending".to_string()]
+        } else {
+            config.categories.clone()
+        }
+    }
+
+    /// Niche a category stands for: the search keyword, nothing for the
+    /// trending page or store URLs
+    fn niche(category: &str) -> Option<String> {
+        let keyword = category.trim().to_lowercase();
+        if keyword.is_empty() || keyword == "trending" || keyword.contains("://") {
+            None
+        } else {
+            Some(keyword)
+        }
+    }
+
+    fn category_url(category: &str) -> String {
+        if category == "trending" {
+            "https://shop.tiktok.com/browse".to_string()
+        } else if category.starts_with("http") || category.starts_with("file") {
+            category.to_string()
+        } else {
+            format!("{}{}", SEARCH_URL_PREFIX, category)
+        }
+    }
+
+    async fn scrape_products(&self) -> Result<Vec<Product>> {
+        let proxy = self.next_proxy().await;
 
         {
             let mut status = self.status.lock().await;
-            status.status_message = Some("Iniciando navegador...".to_string());
+            self.set_status_message(&mut status, "status.starting_browser");
         }
 
         // Start browser
@@ -155,258 +603,1109 @@ impl TikTokScraper {
 
         {
             let mut status = self.status.lock().await;
-            status.status_message = Some("Navegador iniciado".to_string());
+            self.set_status_message(&mut status, "status.browser_started");
+        }
+        self.update_progress(|p| p.set_stage(ScrapeStage::Categories))
+            .await;
+
+        let pool = TabPool::new(self.tab_pool_size());
+        let all_products = Mutex::new(self.resumed.clone());
+
+        // Categories share the tab pool and run side by side
+        stream::iter(self.categories())
+            .map(Ok)
+            .try_for_each_concurrent(self.tab_pool_size(), |category| {
+                let all_products = &all_products;
+                let pool = &pool;
+                async move {
+                    let result = self
+                        .scrape_category(pool, category.clone(), all_products)
+                        .await;
+                    if result.is_err() {
+                        self.count_category(&category, |c| c.errors += 1).await;
+                    }
+                    self.update_progress(|p| p.category_done(&category)).await;
+                    result
+                }
+            })
+            .await?;
+
+        // Concurrent tabs may overshoot the limit slightly
+        let mut all_products = all_products.into_inner();
+        all_products.truncate(self.config.max_products as usize);
+
+        log::info!("Parsed {} products total", all_products.len());
+
+        if self.config.enrich_details {
+            self.enrich_products(&pool, &mut all_products).await;
+        }
+
+        let blocked_requests = pool.blocked_count();
+        if blocked_requests > 0 {
+            self.add_log(
+                LogLevel::Info,
+                None,
+                "scraper.requests_blocked",
+                &[("count", blocked_requests.to_string())],
+            )
+            .await;
+        }
+
+        // Cleanup
+        self.browser.stop().await?;
+
+        Ok(all_products)
+    }
+
+    /// Scrape one category on a tab from the pool
+    async fn scrape_category(
+        &self,
+        pool: &TabPool,
+        category: String,
+        all_products: &Mutex<Vec<Product>>,
+    ) -> Result<()> {
+        // Check if stopped
+        if !self.status.lock().await.is_running {
+            self.add_log(LogLevel::Info, Some(&category), "scraper.stopped_by_user", &[]).await;
+            return Ok(());
+        }
+
+        if self.category_finished(&category)
+            || all_products.lock().await.len() >= self.config.max_products as usize
+        {
+            return Ok(());
+        }
+
+        let url = Self::category_url(&category);
+
+        log::info!("Navigating to: {}", url);
+        self.add_log(
+            LogLevel::Info,
+            Some(&category),
+            "scraper.navigating",
+            &[("url", category.to_string())],
+        )
+        .await;
+
+        // Resource Check
+        {
+            let mut sys = self.system.lock().await;
+            sys.refresh_memory();
+            let used_mem = sys.used_memory();
+            let total_mem = sys.total_memory();
+            if total_mem > 0 && (used_mem as f64 / total_mem as f64) > 0.9 {
+                self.add_log(LogLevel::Warn, Some(&category), "scraper.memory_full", &[]).await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            }
+        }
+
+        let mut checkpoint = self.resume_checkpoint(&category);
+        let mut restarts = 0;
+        let mut tab = self.lease_tab(pool).await?;
+        loop {
+            // Hard watchdog: a page that hangs is abandoned and its tab recycled
+            let watchdog = tokio::time::Duration::from_secs(self.config.page_watchdog_secs as u64);
+            let stage = match tokio::time::timeout(
+                watchdog,
+                self.scrape_page(&tab.page, tab.capture.as_ref(), &category, &url, all_products, &mut checkpoint),
+            )
+            .await
+            {
+                Ok(Ok(())) => {
+                    // A stopped category is picked up again on resume
+                    if self.status.lock().await.is_running {
+                        self.save_checkpoint(&category, &checkpoint, true);
+                    }
+                    return Ok(());
+                }
+                Ok(Err(e)) => match e.downcast_ref::<PageTimeout>() {
+                    Some(timeout) => timeout.0,
+                    None => {
+                        // Chromium crashed (possibly noticed by another tab first):
+                        // relaunch and pick the category up again
+                        let crashed = tab.generation != self.browser.generation()
+                            || !self.browser.is_healthy().await;
+                        if restarts < MAX_BROWSER_RESTARTS && crashed {
+                            restarts += 1;
+                            self.count_category(&category, |c| c.errors += 1).await;
+                            self.add_log(
+                                LogLevel::Warn,
+                                Some(&category),
+                                "scraper.browser_crashed",
+                                &[("error", e.to_string()), ("category", category.to_string())],
+                            )
+                            .await;
+                            let generation = tab.generation;
+                            tab.discard();
+                            self.browser
+                                .restart_if_current(generation)
+                                .await
+                                .context("Failed to restart browser")?;
+                            tab = self.lease_tab(pool).await?;
+                            continue;
+                        }
+                        return Err(e);
+                    }
+                },
+                Err(_) => "watchdog",
+            };
+
+            self.count_category(&category, |c| c.errors += 1).await;
+            self.page_timed_out(&url, stage).await;
+            self.close_hung_tab(tab).await;
+            return Ok(());
+        }
+    }
+
+    async fn page_timed_out(&self, url: &str, stage: &str) {
+        self.status.lock().await.metrics.record_timeout(url, stage);
+        self.add_log(LogLevel::Warn, None, "scraper.page_stalled", &[("stage", stage.to_string())])
+            .await;
+    }
+
+    /// Scrape through Firefox over WebDriver. CDP-only features (request
+    /// blocking, API capture, tab pool) are not available on this backend.
+    async fn scrape_products_firefox(&self) -> Result<Vec<Product>> {
+        let proxy = self.next_proxy().await;
+
+        {
+            let mut status = self.status.lock().await;
+            self.set_status_message(&mut status, "status.starting_firefox");
+        }
+
+        let session = WebDriverSession::start(
+            &self.config.webdriver_url,
+            self.config.headless,
+            proxy.as_deref(),
+        )
+        .await
+        .context("Failed to start Firefox")?;
+        self.update_progress(|p| p.set_stage(ScrapeStage::Categories))
+            .await;
+
+        let all_products = Mutex::new(self.resumed.clone());
+        let result = self.scrape_categories_firefox(&session, &all_products).await;
+
+        let mut all_products = all_products.into_inner();
+        if result.is_ok() && self.config.enrich_details {
+            self.add_log(
+                LogLevel::Info,
+                None,
+                "scraper.enriching",
+                &[("count", all_products.len().to_string())],
+            )
+            .await;
+            let total = all_products.len() as u32;
+            for (position, product) in all_products.iter_mut().enumerate() {
+                if !self.status.lock().await.is_running {
+                    break;
+                }
+                self.set_current(
+                    product.category.as_deref(),
+                    Some(&product.product_url),
+                    Some(&product.title),
+                )
+                .await;
+                if let Err(e) = self.enrich_product(&session, product).await {
+                    log::warn!("Failed to enrich product {}: {}", product.tiktok_id, e);
+                }
+                self.update_progress(|p| p.enrichment(position as u32 + 1, total, Instant::now()))
+                    .await;
+            }
+        }
+
+        if let Err(e) = session.quit().await {
+            log::warn!("Failed to close Firefox: {}", e);
+        }
+
+        result?;
+        all_products.truncate(self.config.max_products as usize);
+        log::info!("Parsed {} products total", all_products.len());
+        Ok(all_products)
+    }
+
+    async fn scrape_categories_firefox(
+        &self,
+        session: &WebDriverSession,
+        all_products: &Mutex<Vec<Product>>,
+    ) -> Result<()> {
+        for category in self.categories() {
+            if !self.status.lock().await.is_running {
+                self.add_log(LogLevel::Info, Some(&category), "scraper.stopped_by_user", &[]).await;
+                break;
+            }
+
+            if all_products.lock().await.len() >= self.config.max_products as usize {
+                break;
+            }
+
+            if self.category_finished(&category) {
+                self.update_progress(|p| p.category_done(&category)).await;
+                continue;
+            }
+
+            let url = Self::category_url(&category);
+            self.add_log(
+                LogLevel::Info,
+                Some(&category),
+                "scraper.navigating",
+                &[("url", category.to_string())],
+            )
+            .await;
+
+            let mut checkpoint = self.resume_checkpoint(&category);
+            let watchdog = tokio::time::Duration::from_secs(self.config.page_watchdog_secs as u64);
+            match tokio::time::timeout(
+                watchdog,
+                self.scrape_page(session, None, &category, &url, all_products, &mut checkpoint),
+            )
+            .await
+            {
+                Ok(Ok(())) => {
+                    if self.status.lock().await.is_running {
+                        self.save_checkpoint(&category, &checkpoint, true);
+                    }
+                }
+                Ok(Err(e)) => {
+                    self.count_category(&category, |c| c.errors += 1).await;
+                    match e.downcast_ref::<PageTimeout>() {
+                        Some(timeout) => self.page_timed_out(&url, timeout.0).await,
+                        None => return Err(e),
+                    }
+                }
+                Err(_) => {
+                    self.count_category(&category, |c| c.errors += 1).await;
+                    self.page_timed_out(&url, "watchdog").await
+                }
+            }
+            self.update_progress(|p| p.category_done(&category)).await;
         }
 
-        // Create new page
+        Ok(())
+    }
+
+    /// Open a page with stealth scripts, request blocking and API capture set up
+    async fn open_tab(&self) -> Result<ScrapeTab> {
+        let generation = self.browser.generation();
         let page = self
             .browser
             .new_page()
             .await
             .context("Failed to create page")?;
 
-        // Generate fingerprint
-        let fingerprint = self.antibot.generate_fingerprint();
-
         // Inject anti-detection scripts
         self.antibot
-            .inject_stealth_scripts(&page, Some(&fingerprint))
+            .inject_stealth_scripts(&page, Some(&self.fingerprint))
             .await
             .context("Failed to inject stealth scripts")?;
 
-        let mut all_products = Vec::new();
-        let categories = if self.config.categories.is_empty() {
-            vec!["trending".to_string()]
+        // Skip fonts, trackers and ads (and images if configured) to speed up loads
+        let blocking = blocking::BlockingOptions {
+            fonts: self.config.block_resources,
+            trackers: self.config.block_resources,
+            images: self.config.block_images,
+        };
+        let blocker = if blocking.is_empty() {
+            None
         } else {
-            self.config.categories.clone()
+            match self.browser.block_requests(&page, blocking).await {
+                Ok(blocker) => Some(blocker),
+                Err(e) => {
+                    log::warn!("Request blocking unavailable: {}", e);
+                    None
+                }
+            }
         };
 
-        for category in categories {
+        // Interception mode: read the shop's own API responses, DOM only as fallback
+        let capture = if self.config.intercept_api {
+            match self.browser.capture_network(&page).await {
+                Ok(capture) => Some(capture),
+                Err(e) => {
+                    log::warn!("Network capture unavailable: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(ScrapeTab {
+            page,
+            capture,
+            blocker,
+            generation,
+        })
+    }
+
+    /// Check out a tab of the current browser. When no tab can be opened the
+    /// browser itself is relaunched.
+    async fn lease_tab<'p>(&self, pool: &'p TabPool) -> Result<TabLease<'p>> {
+        let generation = self.browser.generation();
+        let open = || async {
+            tokio::time::timeout(self.page_load_timeout(), self.open_tab())
+                .await
+                .map_err(|_| PageTimeout("open tab"))?
+        };
+
+        match pool.acquire(generation, open).await {
+            Ok(tab) => return Ok(tab),
+            Err(e) => log::warn!("Failed to open a new tab: {}", e),
+        }
+
+        self.add_log(LogLevel::Warn, None, "scraper.restarting_browser", &[]).await;
+        self.browser
+            .restart_if_current(generation)
+            .await
+            .context("Failed to restart browser")?;
+        pool.acquire(self.browser.generation(), || self.open_tab())
+            .await
+    }
+
+    /// Take a hung tab out of the pool and try to close it
+    async fn close_hung_tab(&self, tab: TabLease<'_>) {
+        if let Some(tab) = tab.discard() {
+            let close_timeout = tokio::time::Duration::from_secs(5);
+            if tokio::time::timeout(close_timeout, tab.page.close()).await.is_err() {
+                log::warn!("Hung tab did not close in time");
+            }
+        }
+    }
+
+    /// Tabs open at once: `max_concurrent_browsers * tabs_per_browser`
+    fn tab_pool_size(&self) -> usize {
+        (self.config.max_concurrent_browsers.max(1) * self.config.tabs_per_browser.max(1)) as usize
+    }
+
+    fn page_load_timeout(&self) -> tokio::time::Duration {
+        tokio::time::Duration::from_millis(self.config.timeout as u64)
+    }
+
+    /// Open the first page of a category. Keyword searches are typed into the
+    /// search box in human typing mode, falling back to the search URL.
+    async fn open_start_page<P: PageDriver>(
+        &self,
+        page: &P,
+        url: &str,
+        checkpoint: &PageCheckpoint,
+    ) -> Result<()> {
+        // Resumed pages carry pagination in the URL; open those directly
+        let keyword = url
+            .strip_prefix(SEARCH_URL_PREFIX)
+            .filter(|_| self.config.human_typing && checkpoint.page_number == 0);
+
+        if let Some(keyword) = keyword {
+            self.actions.record(ActionKind::Navigate, SHOP_HOME_URL, "");
+            page.goto(SHOP_HOME_URL).await?;
+            let ready = wait::WaitCondition::Selector {
+                selector: "input".to_string(),
+                timeout_ms: 10000,
+            };
+            ready.wait(page).await?;
+
+            match typing::type_search(page, keyword).await {
+                Ok(()) => {
+                    self.actions.record(ActionKind::Type, SHOP_HOME_URL, keyword);
+                    self.add_log(
+                        LogLevel::Info,
+                        Some(keyword),
+                        "scraper.search_typed",
+                        &[("keyword", keyword.to_string())],
+                    )
+                    .await;
+                    return Ok(());
+                }
+                Err(e) => log::warn!("Typed search failed, opening search URL: {}", e),
+            }
+        }
+
+        self.actions.record(ActionKind::Navigate, url, "");
+        page.goto(url).await
+    }
+
+    /// Navigate to `url` and collect its products, following pagination
+    async fn scrape_page<P: PageDriver>(
+        &self,
+        page: &P,
+        capture: Option<&NetworkCapture>,
+        category: &str,
+        url: &str,
+        all_products: &Mutex<Vec<Product>>,
+        checkpoint: &mut PageCheckpoint,
+    ) -> Result<()> {
+        // Load more results with the page's pagination strategy, resuming after a crash
+        let strategy = pagination::strategy_for(
+            url,
+            &self.config.pagination_rules,
+            &self.config.pagination,
+        );
+        let mut paginator =
+            Paginator::new(strategy.clone(), url).resume_at(checkpoint.page_number);
+        let start_url = paginator.current_url();
+        // The first page includes navigation and waiting; later ones the pagination step
+        let mut page_started = Instant::now();
+
+        self.set_current(Some(category), Some(url), None).await;
+
+        // Exponential Backoff
+        let mut retries = 0;
+        let max_retries = self.config.max_retries as usize;
+        loop {
             // Check if stopped
             if !self.status.lock().await.is_running {
-                self.add_log("🛑 Scraper parado pelo usuário.".to_string())
-                    .await;
+                self.add_log(LogLevel::Info, Some(category), "scraper.stopped_by_user", &[]).await;
                 break;
             }
 
-            if all_products.len() >= self.config.max_products as usize {
-                break;
+            // Drop responses left over from the previous page
+            if let Some(capture) = capture {
+                capture.drain().await;
             }
 
-            let url = if category == "trending" {
-                "https://shop.tiktok.com/browse".to_string()
-            } else if category.starts_with("http") || category.starts_with("file") {
-                category.clone()
-            } else {
-                format!("https://shop.tiktok.com/search?keyword={}", category)
-            };
+            // A wedged tab never answers; leave it to the caller to recycle
+            let navigation = tokio::time::timeout(
+                self.page_load_timeout(),
+                self.open_start_page(page, &start_url, checkpoint),
+            );
+            match navigation.await.map_err(|_| PageTimeout("navigation"))? {
+                Ok(_) => break,
+                Err(e) => {
+                    // No point retrying against a dead browser
+                    if !page.is_alive().await {
+                        return Err(anyhow::anyhow!("Browser disconnected: {}", e));
+                    }
 
-            log::info!("Navigating to: {}", url);
-            self.add_log(format!("🌐 Navegando para: {}", category))
-                .await;
+                    retries += 1;
+                    if retries > max_retries {
+                        return Err(anyhow::anyhow!("Failed to navigate: {}", e));
+                    }
 
-            // Resource Check
-            {
-                let mut sys = self.system.lock().await;
-                sys.refresh_memory();
-                let used_mem = sys.used_memory();
-                let total_mem = sys.total_memory();
-                if total_mem > 0 && (used_mem as f64 / total_mem as f64) > 0.9 {
-                    self.add_log("⚠️ Memória cheia! Pausando por 10s...".to_string())
+                    // Check if stopped before waiting
+                    if !self.status.lock().await.is_running {
+                        self.add_log(
+                            LogLevel::Info,
+                            Some(category),
+                            "scraper.stopped_by_user",
+                            &[],
+                        )
                         .await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+                        break;
+                    }
+
+                    let delay = 2u64.pow(retries as u32);
+                    self.add_log(
+                        LogLevel::Warn,
+                        Some(category),
+                        "scraper.load_retry",
+                        &[("seconds", delay.to_string())],
+                    )
+                    .await;
+                    tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
                 }
             }
+        }
 
-            // Exponential Backoff
-            let mut retries = 0;
-            let max_retries = self.config.max_retries;
-            loop {
-                // Check if stopped
-                if !self.status.lock().await.is_running {
-                    self.add_log("🛑 Scraper parado pelo usuário.".to_string())
-                        .await;
-                    break;
+        // Check if stopped after navigation loop
+        if !self.status.lock().await.is_running {
+            return Ok(());
+        }
+
+        // Send update event
+        self.browser
+            .send_browser_event(url.to_string(), self.localize("status.loading_page"), None)
+            .await;
+
+        // Wait for page to load
+        self.add_log(LogLevel::Debug, Some(category), "scraper.waiting_page", &[]).await;
+
+        // Check if stopped before waiting
+        if !self.status.lock().await.is_running {
+            return Ok(());
+        }
+
+        let outcome = wait::wait_for_page(page, &self.config.wait_conditions).await;
+        self.actions.record(
+            ActionKind::Wait,
+            url,
+            format!(
+                "{}ms{}",
+                outcome.elapsed.as_millis(),
+                if outcome.timed_out.is_empty() {
+                    String::new()
+                } else {
+                    format!(", timed out: {}", outcome.timed_out.join(", "))
                 }
+            ),
+        );
+        if outcome.timed_out.is_empty() {
+            self.add_log(
+                LogLevel::Debug,
+                Some(category),
+                "scraper.page_ready",
+                &[("seconds", format!("{:.1}", outcome.elapsed.as_secs_f32()))],
+            )
+            .await;
+        } else {
+            self.add_log(
+                LogLevel::Warn,
+                Some(category),
+                "scraper.wait_timed_out",
+                &[("conditions", outcome.timed_out.join(", "))],
+            )
+            .await;
+        }
 
-                match page.goto(&url).await {
-                    Ok(_) => break,
-                    Err(e) => {
-                        retries += 1;
-                        if retries > max_retries {
-                            return Err(anyhow::anyhow!("Failed to navigate: {}", e));
-                        }
+        // Rate Limiting: keep a random gap between pages, minus the time spent waiting
+        let delay = {
+            let max = self.config.max_delay_ms as u64;
+            rand::thread_rng().gen_range((self.config.min_delay_ms as u64).min(max)..=max)
+        };
+        if let Some(remaining) =
+            tokio::time::Duration::from_millis(delay).checked_sub(outcome.elapsed)
+        {
+            self.actions
+                .record(ActionKind::Pause, url, format!("{}ms", remaining.as_millis()));
+            tokio::time::sleep(remaining).await;
+        }
 
-                        // Check if stopped before waiting
-                        if !self.status.lock().await.is_running {
-                            self.add_log("🛑 Scraper parado pelo usuário.".to_string())
-                                .await;
-                            break;
-                        }
+        // Capture screenshot and update viewer
+        if let Ok(screenshot) = self.browser.capture_screenshot(page).await {
+            self.browser
+                .send_browser_event(
+                    url.to_string(),
+                    self.localize("status.analyzing_page"),
+                    Some(screenshot),
+                )
+                .await;
+        }
 
-                        let delay = 2u64.pow(retries as u32);
-                        self.add_log(format!(
-                            "⚠️ Erro ao carregar. Tentando novamente em {}s...",
-                            delay
-                        ))
-                        .await;
-                        tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
-                    }
+        // Check if stopped after waiting
+        if !self.status.lock().await.is_running {
+            return Ok(());
+        }
+
+        // Safety Switch: Check for immediate blocks/captchas
+        let content = page.content().await.unwrap_or_default();
+        if content.contains("captcha")
+            || content.contains("verify")
+            || content.contains("Access Denied")
+        {
+            self.actions.record(ActionKind::Blocked, url, "captcha or access denied page");
+            self.add_log(LogLevel::Error, Some(category), "scraper.bot_detected", &[]).await;
+            self.count_category(category, |c| c.errors += 1).await;
+
+            if let Some(db_path) = &self.config.db_path {
+                let _ = crate::database::save_error_page(
+                    std::path::Path::new(db_path),
+                    url,
+                    &content,
+                );
+            }
+
+            if self.config.safety_switch_enabled {
+                return Err(anyhow::anyhow!("Safety Switch triggered: Bot detection"));
+            }
+        }
+
+        // Simulate human interaction
+        if self.status.lock().await.is_running {
+            self.actions.record(ActionKind::Mouse, url, "random pointer moves");
+            self.browser.simulate_human_interaction(page).await.ok();
+        }
+
+        // Scroll back down to where the page was left before a crash or an interruption
+        if checkpoint.scroll_offset > 0
+            && matches!(paginator.strategy(), PaginationStrategy::InfiniteScroll)
+        {
+            self.actions.record(ActionKind::Scroll, url, "back to checkpoint");
+            while paginator.scroll_height() < checkpoint.scroll_offset {
+                if !self.status.lock().await.is_running || !paginator.advance(page).await? {
+                    break;
                 }
             }
+        }
 
-            // Check if stopped after navigation loop
+        while all_products.lock().await.len() < self.config.max_products as usize {
+            // Check if stopped
             if !self.status.lock().await.is_running {
                 break;
             }
 
-            // Send update event
-            self.browser.send_browser_event(url.clone(), "Carregando página...".to_string(), None).await;
+            // Parse current products
+            self.add_log(LogLevel::Debug, Some(category), "scraper.analyzing", &[]).await;
+            let intercepted = match capture {
+                Some(capture) => Some(self.parser.parse_api_responses(&capture.drain().await)),
+                None => None,
+            };
+            let parsed = match intercepted {
+                Some(parsed) if !parsed.products.is_empty() => parsed,
+                _ => self.parser.parse_product_list(page).await?,
+            };
+            self.status
+                .lock()
+                .await
+                .metrics
+                .record(url, &parsed.source, parsed.products.len());
+            if parsed.products.is_empty() {
+                self.add_log(
+                    LogLevel::Warn,
+                    Some(category),
+                    "scraper.no_products",
+                    &[("source", parsed.source.clone())],
+                )
+                .await;
+            }
+            let mut products = parsed.products;
+            if self.config.ocr_prices {
+                self.ocr_missing_prices(page, &mut products).await;
+            }
+
+            // Add new products (deduplicate by ID)
+            let found = products.len() as u32;
+            let mut last_title = None;
+            let (added, total) = {
+                let mut all_products = all_products.lock().await;
+                let mut added = Vec::new();
+                for mut p in products {
+                    last_title = Some(p.title.clone());
+                    // Products found by a keyword search belong to that niche
+                    if p.category.is_none() {
+                        p.category = Self::niche(category);
+                    }
+                    if !all_products
+                        .iter()
+                        .any(|existing: &Product| existing.tiktok_id == p.tiktok_id)
+                    {
+                        added.push(p.clone());
+                        all_products.push(p);
+                    }
+                }
+                (added, all_products.len())
+            };
+            if let Some(session) = &self.session {
+                session.record_products(&added);
+            }
+            let new_count = added.len();
+            self.set_current(Some(category), Some(url), last_title.as_deref()).await;
+            self.count_category(category, |c| {
+                c.found += found;
+                c.new += new_count as u32;
+                c.duplicates += found - new_count as u32;
+            })
+            .await;
 
-            // Wait for page to load
-            self.add_log("⏳ Aguardando carregamento da página...".to_string())
+            for product in &added {
+                self.product_found(category, product);
+                self.add_log(
+                    LogLevel::Debug,
+                    Some(category),
+                    "scraper.product_found",
+                    &[
+                        ("title", product.title.chars().take(30).collect()),
+                        ("price", format!("{:.2}", product.price)),
+                    ],
+                )
                 .await;
+            }
 
-            // Rate Limiting: 5-10 seconds (Aggressive mitigation)
-            let delay = rand::thread_rng().gen_range(5000..=10000);
+            if new_count > 0 {
+                self.add_log(
+                    LogLevel::Info,
+                    Some(category),
+                    "scraper.products_added",
+                    &[("count", new_count.to_string())],
+                )
+                .await;
+            }
 
-            // Check if stopped before waiting
-            if !self.status.lock().await.is_running {
+            // Update progress
+            self.status.lock().await.products_found = total as i32;
+            let elapsed = page_started.elapsed();
+            self.update_progress(|p| {
+                p.page_done(category, new_count as u32, elapsed, Instant::now())
+            })
+            .await;
+            page_started = Instant::now();
+
+            if total >= self.config.max_products as usize {
                 break;
             }
 
-            tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
-
-            // Capture screenshot and update viewer
-            if let Ok(screenshot) = self.browser.capture_screenshot(&page).await {
-                 self.browser.send_browser_event(url.clone(), "Analisando página...".to_string(), Some(screenshot)).await;
+            if !paginator.record_pass(new_count) {
+                self.add_log(LogLevel::Info, Some(category), "scraper.no_new_products", &[]).await;
+                break;
             }
 
-            // Check if stopped after waiting
+            self.add_log(
+                LogLevel::Debug,
+                Some(category),
+                paginator.strategy().progress_key(),
+                &[],
+            )
+            .await;
+
+            // Check if stopped
             if !self.status.lock().await.is_running {
                 break;
             }
 
-            // Safety Switch: Check for immediate blocks/captchas
-            let content = page.content().await.unwrap_or_default();
-            if content.contains("captcha")
-                || content.contains("verify")
-                || content.contains("Access Denied")
-            {
-                self.add_log(
-                    "⚠️ DETECÇÃO DE BOT IDENTIFICADA! Abortando para segurança.".to_string(),
-                )
-                .await;
+            let (kind, detail) = match paginator.strategy() {
+                PaginationStrategy::InfiniteScroll => (ActionKind::Scroll, "infinite scroll"),
+                PaginationStrategy::ClickNext { .. } => (ActionKind::Click, "load more"),
+                PaginationStrategy::UrlParam { .. } => (ActionKind::Navigate, "next page"),
+            };
+            self.actions.record(kind, url, detail);
+            if !paginator.advance(page).await? {
+                self.add_log(LogLevel::Info, Some(category), "scraper.end_of_page", &[]).await;
+                break;
+            }
+            checkpoint.page_number = paginator.page_number();
+            checkpoint.scroll_offset = paginator.scroll_height();
+            self.save_checkpoint(category, checkpoint, false);
+        }
 
-                if let Some(db_path) = &self.config.db_path {
-                    let _ = crate::database::save_error_page(
-                        std::path::Path::new(db_path),
-                        &url,
-                        &content,
-                    );
-                }
+        Ok(())
+    }
 
-                if self.config.safety_switch_enabled {
-                    return Err(anyhow::anyhow!("Safety Switch triggered: Bot detection"));
+    /// Read the price from the card screenshot of products that came
+    /// without one (listings that render prices as images)
+    async fn ocr_missing_prices<P: PageDriver>(&self, page: &P, products: &mut [Product]) {
+        let locale = NumberLocale::from_region(&self.config.region);
+        for product in products.iter_mut().filter(|p| p.price <= 0.0) {
+            match ocr::card_price(page, &product.tiktok_id, locale).await {
+                Ok(Some(price)) => {
+                    log::debug!("OCR price for {}: {}", product.tiktok_id, price);
+                    product.price = price;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    // Without the `ocr` build every attempt fails the same way
+                    log::warn!("OCR price extraction failed: {}", e);
+                    break;
                 }
             }
+        }
+    }
 
-            // Simulate human interaction
-            if self.status.lock().await.is_running {
-                self.browser.simulate_human_interaction(&page).await.ok();
-            }
+    /// Parse the pages saved in `folder` instead of browsing (offline mode).
+    /// Each file counts as a category in the status and progress.
+    async fn scrape_offline(&self, folder: &str) -> Result<Vec<Product>> {
+        let pages = offline::saved_pages(Path::new(folder))
+            .with_context(|| format!("Cannot read folder {}", folder))?;
+        if pages.is_empty() {
+            return Err(anyhow::anyhow!("No .html or .mhtml pages in {}", folder));
+        }
+        let names: Vec<String> = pages
+            .iter()
+            .map(|page| {
+                page.strip_prefix(folder)
+                    .unwrap_or(page)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+
+        self.status.lock().await.categories.clear();
+        *self.progress.lock().await =
+            ProgressTracker::new(&names, self.config.max_products, false);
+        self.update_progress(|p| p.set_stage(ScrapeStage::Categories))
+            .await;
+        self.add_log(
+            LogLevel::Info,
+            None,
+            "scraper.offline_pages",
+            &[("count", pages.len().to_string()), ("folder", folder.to_string())],
+        )
+        .await;
 
-            // Scroll and load more
-            let mut previous_height = 0;
-            let mut no_change_count = 0;
+        let max_products = self.config.max_products as usize;
+        let mut products: Vec<Product> = Vec::new();
+        for (page, name) in pages.iter().zip(&names) {
+            if !self.status.lock().await.is_running {
+                self.add_log(LogLevel::Info, None, "scraper.stopped_by_user", &[]).await;
+                break;
+            }
+            if products.len() >= max_products {
+                break;
+            }
 
-            while all_products.len() < self.config.max_products as usize {
-                // Check if stopped
-                if !self.status.lock().await.is_running {
-                    break;
+            let path = page.to_string_lossy().to_string();
+            self.set_current(Some(name), Some(&path), None).await;
+            match offline::read_page(page) {
+                Ok(html) => {
+                    let parsed = self.parser.parse_html(&html).products;
+                    let found = parsed.len() as u32;
+                    let mut new = 0;
+                    for mut product in parsed {
+                        if products.iter().any(|p| p.tiktok_id == product.tiktok_id) {
+                            continue;
+                        }
+                        product.source_file = Some(path.clone());
+                        self.product_found(name, &product);
+                        products.push(product);
+                        new += 1;
+                    }
+                    self.count_category(name, |c| {
+                        c.found += found;
+                        c.new += new;
+                        c.duplicates += found - new;
+                    })
+                    .await;
+                    self.add_log(
+                        LogLevel::Debug,
+                        Some(name),
+                        "scraper.offline_page",
+                        &[("count", found.to_string())],
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    self.count_category(name, |c| c.errors += 1).await;
+                    self.add_log(
+                        LogLevel::Warn,
+                        Some(name),
+                        "scraper.offline_page_failed",
+                        &[("error", e.to_string())],
+                    )
+                    .await;
                 }
+            }
+            self.update_progress(|p| p.category_done(name)).await;
+        }
+
+        products.truncate(max_products);
+        Ok(products)
+    }
+
+    /// Visit each product page and fill in data only the detail page has.
+    /// Pages are fetched concurrently on tabs from the pool.
+    async fn enrich_products(&self, pool: &TabPool, products: &mut [Product]) {
+        let total = products.len();
+        self.add_log(LogLevel::Info, None, "scraper.enriching", &[("count", total.to_string())])
+            .await;
 
-                // Parse current products
-                self.add_log("🔍 Analisando produtos na página...".to_string())
+        let done = AtomicUsize::new(0);
+        stream::iter(products.iter_mut())
+            .for_each_concurrent(self.tab_pool_size(), |product| {
+                let done = &done;
+                async move {
+                    if !self.status.lock().await.is_running {
+                        return;
+                    }
+
+                    let tab = match self.lease_tab(pool).await {
+                        Ok(tab) => tab,
+                        Err(e) => {
+                            log::warn!("No tab available for {}: {}", product.tiktok_id, e);
+                            return;
+                        }
+                    };
+                    self.set_current(
+                        product.category.as_deref(),
+                        Some(&product.product_url),
+                        Some(&product.title),
+                    )
                     .await;
-                let products = self.parser.parse_product_list(&page).await?;
+                    let result = self.enrich_product(&tab.page, product).await;
+                    drop(tab);
 
-                // Add new products (deduplicate by ID)
-                let mut new_count = 0;
-                for p in products {
-                    if !all_products
-                        .iter()
-                        .any(|existing: &Product| existing.tiktok_id == p.tiktok_id)
-                    {
-                        self.add_log(format!(
-                            "✨ Encontrado: {} (R$ {:.2})",
-                            p.title.chars().take(30).collect::<String>(),
-                            p.price
-                        ))
-                        .await;
-                        all_products.push(p);
-                        new_count += 1;
+                    let position = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    self.update_progress(|p| {
+                        p.enrichment(position as u32, total as u32, Instant::now())
+                    })
+                    .await;
+                    if let Err(e) = result {
+                        log::warn!("Failed to enrich product {}: {}", product.tiktok_id, e);
+                        return;
                     }
-                }
 
-                if new_count > 0 {
-                    self.add_log(format!("📦 +{} novos produtos adicionados", new_count))
+                    if !product.variants.is_empty() {
+                        self.add_log(
+                            LogLevel::Debug,
+                            None,
+                            "scraper.variants",
+                            &[
+                                ("position", position.to_string()),
+                                ("total", total.to_string()),
+                                ("count", product.variants.len().to_string()),
+                            ],
+                        )
                         .await;
+                    }
                 }
+            })
+            .await;
 
-                // Update progress
-                let mut status = self.status.lock().await;
-                status.products_found = all_products.len() as i32;
-                status.progress =
-                    (all_products.len() as f32 / self.config.max_products as f32 * 100.0).min(99.0);
-                drop(status);
+        if !self.status.lock().await.is_running {
+            self.add_log(LogLevel::Info, None, "scraper.stopped_by_user", &[]).await;
+        }
+    }
 
-                if all_products.len() >= self.config.max_products as usize {
-                    break;
-                }
+    /// Deep mode: open a product page and merge its full description,
+    /// variants, shipping options and seller into `product`
+    pub async fn enrich_product<P: PageDriver>(&self, page: &P, product: &mut Product) -> Result<()> {
+        if product.product_url.is_empty() {
+            return Ok(());
+        }
 
-                // Scroll down
-                self.add_log("⬇️ Rolando página para carregar mais...".to_string())
-                    .await;
-                page.evaluate("window.scrollTo(0, document.body.scrollHeight)")
-                    .await?;
+        self.actions
+            .record(ActionKind::Navigate, &product.product_url, "product detail");
+        page.goto(&product.product_url)
+            .await
+            .context("Failed to open product page")?;
 
-                // Check if stopped
-                if !self.status.lock().await.is_running {
-                    break;
+        let delay = rand::thread_rng().gen_range(2000..=4000);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+
+        if let Some(detail) = self.parser.extract_detail_json(page).await? {
+            let locale = NumberLocale::from_currency(&product.currency)
+                .unwrap_or_else(|| NumberLocale::from_region(&self.config.region));
+            let detail = self.parser.parse_product_detail(&detail, locale);
+            TikTokParser::apply_detail(product, detail);
+        }
+
+        Ok(())
+    }
+
+    /// Re-fetch products individually from their detail pages (watch list).
+    /// Products that fail keep their previous data and are left out of the result.
+    pub async fn refresh_products(&self, products: Vec<Product>) -> Result<Vec<Product>> {
+        let proxy = self.next_proxy().await;
+        self.browser
+            .start(proxy)
+            .await
+            .context("Failed to start browser")?;
+
+        let result = async {
+            let tab = self.open_tab().await?;
+            let mut refreshed = Vec::new();
+            for mut product in products {
+                match self.refresh_product(&tab.page, &mut product).await {
+                    Ok(()) => refreshed.push(product),
+                    Err(e) => log::warn!("Failed to refresh product {}: {}", product.tiktok_id, e),
                 }
+            }
+            Ok(refreshed)
+        }
+        .await;
 
-                tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+        self.browser.stop().await.ok();
+        result
+    }
 
-                // Check if we reached bottom
-                let height_val = page
-                    .evaluate("document.body.scrollHeight")
-                    .await?
-                    .into_value::<i64>();
-                let current_height = match height_val {
-                    Ok(h) => h,
-                    Err(_) => previous_height, // Keep same if failed to parse
-                };
+    /// Scrape products from their detail pages by URL, e.g. links copied to
+    /// the clipboard. Pages that fail are left out of the result.
+    pub async fn fetch_products(&self, urls: &[String]) -> Result<Vec<Product>> {
+        let proxy = self.next_proxy().await;
+        self.browser
+            .start(proxy)
+            .await
+            .context("Failed to start browser")?;
 
-                if current_height == previous_height {
-                    no_change_count += 1;
-                    if no_change_count >= 3 {
-                        self.add_log("⚠️ Fim da página alcançado.".to_string()).await;
-                        break; // Stop if no new content after 3 scrolls
-                    }
-                } else {
-                    no_change_count = 0;
+        let result = async {
+            let tab = self.open_tab().await?;
+            let mut fetched = Vec::new();
+            for url in urls {
+                match self.fetch_product(&tab.page, url).await {
+                    Ok(product) => fetched.push(product),
+                    Err(e) => log::warn!("Failed to fetch product {}: {}", url, e),
                 }
-                previous_height = current_height;
             }
+            Ok(fetched)
         }
+        .await;
 
-        log::info!("Parsed {} products total", all_products.len());
+        self.browser.stop().await.ok();
+        result
+    }
 
-        // Cleanup
-        self.browser.stop().await?;
+    /// Read a product, variants and shipping included, from its detail page
+    async fn fetch_product<P: PageDriver>(&self, page: &P, url: &str) -> Result<Product> {
+        self.actions
+            .record(ActionKind::Navigate, url, "product import");
+        page.goto(url).await.context("Failed to open product page")?;
+
+        let delay = rand::thread_rng().gen_range(2000..=4000);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+
+        let mut product = match self.parser.extract_detail_json(page).await? {
+            Some(detail) => {
+                let mut product = self.parser.parse_product_json(&detail)?;
+                let locale = NumberLocale::from_currency(&product.currency)
+                    .unwrap_or_else(|| NumberLocale::from_region(&self.config.region));
+                let variants = self.parser.parse_variants(&detail, locale);
+                TikTokParser::apply_variants(&mut product, variants);
+                self.parser.apply_shipping(&mut product, &detail, locale);
+                product
+            }
+            // No embedded state: the page's DOM or JSON-LD
+            None => self
+                .parser
+                .parse_html(&page.content().await?)
+                .products
+                .into_iter()
+                .next()
+                .context("No product data on the page")?,
+        };
+        if product.product_url.is_empty() {
+            product.product_url = url.to_string();
+        }
+        Ok(product)
+    }
 
-        Ok(all_products)
+    /// Update price, sales and stock of one product from its detail page
+    async fn refresh_product<P: PageDriver>(&self, page: &P, product: &mut Product) -> Result<()> {
+        if product.product_url.is_empty() {
+            return Err(anyhow::anyhow!("Product has no URL"));
+        }
+
+        self.actions
+            .record(ActionKind::Navigate, &product.product_url, "watched product");
+        page.goto(&product.product_url)
+            .await
+            .context("Failed to open product page")?;
+
+        let delay = rand::thread_rng().gen_range(2000..=4000);
+        tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
+
+        let locale = NumberLocale::from_currency(&product.currency)
+            .unwrap_or_else(|| NumberLocale::from_region(&self.config.region));
+        let fresh = match self.parser.extract_detail_json(page).await? {
+            Some(detail) => {
+                let fresh = self.parser.parse_product_json(&detail).ok();
+                TikTokParser::apply_variants(product, self.parser.parse_variants(&detail, locale));
+                self.parser.apply_shipping(product, &detail, locale);
+                fresh
+            }
+            // No embedded state: the page's DOM or JSON-LD
+            None => self
+                .parser
+                .parse_html(&page.content().await?)
+                .products
+                .into_iter()
+                .next(),
+        };
+        let fresh = fresh.context("No product data on the page")?;
+
+        if fresh.price > 0.0 {
+            product.price = fresh.price;
+            product.original_price = fresh.original_price;
+            product.is_on_sale = fresh.original_price.is_some_and(|op| op > fresh.price);
+        }
+        if fresh.sales_count > 0 {
+            product.sales_count = fresh.sales_count;
+        }
+        if fresh.reviews_count > 0 {
+            product.reviews_count = fresh.reviews_count;
+        }
+        product.product_rating = fresh.product_rating.or(product.product_rating);
+        product.stock_level = fresh.stock_level.or(product.stock_level);
+        product.in_stock = fresh.in_stock;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        product.collected_at = now.clone();
+        product.updated_at = now;
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -426,20 +1725,21 @@ impl TikTokScraper {
     }
 }
 
+impl Scraper for TikTokScraper {
+    fn start(&self) -> BoxFuture<'_, Result<Vec<Product>>> {
+        Box::pin(TikTokScraper::start(self))
+    }
+
+    fn finish_progress(&self) -> BoxFuture<'_, ()> {
+        Box::pin(TikTokScraper::finish_progress(self))
+    }
+}
+
 impl Default for TikTokScraper {
     fn default() -> Self {
         Self::new(
             ScraperConfig::default(),
-            Arc::new(Mutex::new(ScraperStatus {
-                is_running: false,
-                progress: 0.0,
-                current_product: None,
-                products_found: 0,
-                errors: vec![],
-                logs: vec![],
-                started_at: None,
-                status_message: None,
-            })),
+            Arc::new(Mutex::new(ScraperStatus::default())),
             None,
         )
     }
@@ -448,7 +1748,7 @@ impl Default for TikTokScraper {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::scraper::models::ScraperConfig;
+    use crate::config::ScraperConfig;
 
     #[tokio::test]
     async fn test_e2e_scraping() {
@@ -475,16 +1775,7 @@ mod tests {
         // Initialize scraper
         let scraper = TikTokScraper::new(
             config,
-            Arc::new(Mutex::new(ScraperStatus {
-                is_running: false,
-                progress: 0.0,
-                current_product: None,
-                products_found: 0,
-                errors: vec![],
-                logs: vec![],
-                started_at: None,
-                status_message: None,
-            })),
+            Arc::new(Mutex::new(ScraperStatus::default())),
             None,
         );
 
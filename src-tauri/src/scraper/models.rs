@@ -1,91 +1,8 @@
 // Scraper Data Models
+use serde::{Deserialize, Serialize};
 use ts_rs::TS;
-#[derive(Debug, Clone, TS)]
-#[ts(export)]
-#[allow(dead_code)]
-pub struct ScraperConfig {
-    pub headless: bool,
-    pub max_concurrent_browsers: usize,
-    pub request_timeout_ms: u64,
-    pub page_load_timeout_ms: u64,
-    pub min_delay_ms: u64,
-    pub max_delay_ms: u64,
-    pub max_retries: usize,
-    pub use_proxy: bool,
-    pub proxies: Vec<String>,
-    pub categories: Vec<String>,
-    pub max_products: u32,
-    pub user_data_path: Option<String>,
-    pub db_path: Option<String>,
-    pub selectors: Option<Vec<String>>, // Added
-    // Safety Switch
-    pub safety_switch_enabled: bool,
-    pub max_detection_rate: f32,
-    pub safety_cooldown_seconds: u64,
-    pub consecutive_failures_threshold: u32,
-    // Research API
-    pub api_key: Option<String>,
-    pub api_secret: Option<String>,
-}
-
-impl Default for ScraperConfig {
-    fn default() -> Self {
-        Self {
-            headless: true,
-            max_concurrent_browsers: 1,
-            request_timeout_ms: 30000,
-            page_load_timeout_ms: 60000,
-            min_delay_ms: 2000,
-            max_delay_ms: 5000,
-            max_retries: 3,
-            use_proxy: false,
-            proxies: vec![],
-            categories: vec![],
-            max_products: 100,
-            user_data_path: None,
-            db_path: None,
-            selectors: None,
-            safety_switch_enabled: true,
-            max_detection_rate: 0.2,
-            safety_cooldown_seconds: 3600,
-            consecutive_failures_threshold: 5,
-            api_key: None,
-            api_secret: None,
-        }
-    }
-}
 
-// Convert from crate::config::ScraperConfig to scraper::models::ScraperConfig
-impl From<crate::config::ScraperConfig> for ScraperConfig {
-    fn from(config: crate::config::ScraperConfig) -> Self {
-        Self {
-            headless: config.headless,
-            // ... other fields ...
-            // We can't easily map user_data_path from config::ScraperConfig if it doesn't exist there.
-            // But we can set it later or add it to config::ScraperConfig.
-            // For now, let's assume None and let commands.rs set it.
-            user_data_path: None,
-            db_path: None,
-            selectors: None,
-            max_concurrent_browsers: 1,
-            request_timeout_ms: config.timeout as u64 * 1000,
-            page_load_timeout_ms: 60000,
-            min_delay_ms: 2000,
-            max_delay_ms: 5000,
-            max_retries: 3,
-            use_proxy: config.use_proxy,
-            proxies: config.proxies.unwrap_or_default(),
-            categories: config.categories,
-            max_products: config.max_products as u32,
-            safety_switch_enabled: true,
-            max_detection_rate: 0.2,
-            safety_cooldown_seconds: 3600,
-            consecutive_failures_threshold: 5,
-            api_key: None,
-            api_secret: None,
-        }
-    }
-}
+use super::pagination::{PaginationRule, PaginationStrategy};
 
 #[derive(Debug, Clone)]
 pub struct ProxyConfig {
@@ -103,3 +20,108 @@ impl ProxyConfig {
         }
     }
 }
+
+/// One way of locating the embedded product list JSON on a page
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct ExtractionStrategy {
+    /// Reported in scrape metrics when this strategy finds products
+    pub name: String,
+    /// `window` global (e.g. "__INITIAL_STATE__") or `#id` of a JSON <script> tag
+    pub root: String,
+    /// Dot-separated path inside the root, empty for the root itself
+    #[serde(default)]
+    pub path: String,
+    /// Use `Object.values()` of the result (for maps keyed by product id)
+    #[serde(default)]
+    pub values: bool,
+}
+
+impl ExtractionStrategy {
+    fn new(name: &str, root: &str, path: &str, values: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            root: root.to_string(),
+            path: path.to_string(),
+            values,
+        }
+    }
+
+    /// Built-in probes, tried in order
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("initial_state.products", "__INITIAL_STATE__", "products", false),
+            Self::new(
+                "initial_state.product_list",
+                "__INITIAL_STATE__",
+                "productList.products",
+                false,
+            ),
+            Self::new("initial_state.shop", "__INITIAL_STATE__", "shop.products", false),
+            Self::new("initial_state.search", "__INITIAL_STATE__", "search.item_list", false),
+            Self::new("sigi_state.item_module", "#SIGI_STATE", "ItemModule", true),
+        ]
+    }
+}
+
+/// Contents of selectors.json, also served remotely by the backend
+#[derive(Debug, Clone, Serialize, Deserialize, TS, Default)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct SelectorManifest {
+    #[serde(default)]
+    pub version: Option<String>,
+    /// CSS selectors for product cards (DOM fallback)
+    #[serde(default)]
+    pub product_selectors: Vec<String>,
+    /// JSON probes, tried before the DOM fallback
+    #[serde(default)]
+    pub extraction_strategies: Vec<ExtractionStrategy>,
+    /// How to load more results when no rule matches the page URL
+    #[serde(default)]
+    pub pagination: PaginationStrategy,
+    #[serde(default)]
+    pub pagination_rules: Vec<PaginationRule>,
+}
+
+impl SelectorManifest {
+    /// Parse selectors.json. Older files hold a plain list of CSS selectors.
+    pub fn from_json(content: &str) -> Option<Self> {
+        if let Ok(selectors) = serde_json::from_str::<Vec<String>>(content) {
+            return Some(Self {
+                product_selectors: selectors,
+                ..Self::default()
+            });
+        }
+        serde_json::from_str(content).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_manifest_formats() {
+        let legacy = SelectorManifest::from_json(r#"[".card", ".item"]"#).unwrap();
+        assert_eq!(legacy.product_selectors, vec![".card", ".item"]);
+        assert!(legacy.extraction_strategies.is_empty());
+
+        let manifest = SelectorManifest::from_json(
+            r##"{
+                "version": "2024.06",
+                "productSelectors": [".card"],
+                "extractionStrategies": [
+                    { "name": "next_data", "root": "#__NEXT_DATA__", "path": "props.pageProps.products" }
+                ]
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(manifest.version.as_deref(), Some("2024.06"));
+        assert_eq!(manifest.extraction_strategies[0].root, "#__NEXT_DATA__");
+        assert!(!manifest.extraction_strategies[0].values);
+
+        assert!(SelectorManifest::from_json("not json").is_none());
+    }
+}
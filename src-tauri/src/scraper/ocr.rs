@@ -0,0 +1,122 @@
+// OCR Module
+// Reads prices that listings render as images to defeat scraping: the product
+// card is screenshotted and passed through tesseract. The recognition step is
+// only built with the `ocr` feature and needs the tesseract binary on PATH.
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use super::driver::PageDriver;
+use super::parser::{NumberLocale, TikTokParser};
+
+/// Marks the card of a product so it can be screenshotted by selector
+const MARK_ATTRIBUTE: &str = "data-tiktrend-ocr";
+
+/// Price in OCR output: the amount after a currency symbol, or else the
+/// first number with two decimals
+pub fn price_from_text(text: &str, locale: NumberLocale) -> Option<f64> {
+    // Thousands groups have three digits, so "89,90 1,2 mil" stops at 89,90
+    let amount = r"\d{1,3}(?:[., ]\d{3})+(?:[.,]\d{1,2})?|\d+(?:[.,]\d{1,2})?";
+    let with_symbol = Regex::new(&format!(r"(?:R\$|US\$|\$|€|£|Rp|RM|₱|฿)\s*({})", amount)).ok()?;
+    let bare = Regex::new(r"\d{1,3}(?:[., ]\d{3})*[.,]\d{2}\b").ok()?;
+
+    let candidate = with_symbol
+        .captures(text)
+        .and_then(|cap| cap.get(1))
+        .or_else(|| bare.find(text))?;
+
+    // Spaces between thousands groups ("1 234,56") are dropped
+    let amount: String = candidate.as_str().split_whitespace().collect();
+    Some(TikTokParser::parse_price_text(&amount, locale)).filter(|price| *price > 0.0)
+}
+
+/// Text in a PNG image, via the tesseract command line tool
+#[cfg(feature = "ocr")]
+pub async fn recognize_text(png: &[u8]) -> Result<String> {
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+    use tokio::process::Command;
+
+    let mut child = Command::new("tesseract")
+        .args(["stdin", "stdout", "--psm", "6"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Cannot run tesseract: {}", e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(png).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "tesseract failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(not(feature = "ocr"))]
+pub async fn recognize_text(_png: &[u8]) -> Result<String> {
+    Err(anyhow!(
+        "OCR support not built in (enable the `ocr` feature)"
+    ))
+}
+
+/// Screenshot the card linking to `tiktok_id` and read its price
+pub async fn card_price<P: PageDriver>(
+    page: &P,
+    tiktok_id: &str,
+    locale: NumberLocale,
+) -> Result<Option<f64>> {
+    let script = format!(
+        r#"
+        (() => {{
+            document.querySelectorAll('[{attr}]').forEach(el => el.removeAttribute('{attr}'));
+            const link = document.querySelector('a[href*="/product/' + {id} + '"]');
+            if (!link) return false;
+            const card = link.closest('[data-e2e*="product"], [class*="product-card"], [class*="ProductCard"], li') || link;
+            card.setAttribute('{attr}', '1');
+            return true;
+        }})()
+        "#,
+        attr = MARK_ATTRIBUTE,
+        id = serde_json::to_string(tiktok_id)?
+    );
+    if !page.evaluate(&script).await?.as_bool().unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let png = page
+        .screenshot_element(&format!("[{}]", MARK_ATTRIBUTE))
+        .await?;
+    let text = recognize_text(&png).await?;
+    Ok(price_from_text(&text, locale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_from_ocr_text() {
+        let br = NumberLocale::Br;
+        assert_eq!(
+            price_from_text("Fone Bluetooth\nR$ 89,90\n1,2 mil vendidos", br),
+            Some(89.9)
+        );
+        assert_eq!(
+            price_from_text("R$1 234,56", NumberLocale::Eu),
+            Some(1234.56)
+        );
+        assert_eq!(
+            price_from_text("$12.99 Free shipping", NumberLocale::Us),
+            Some(12.99)
+        );
+        assert_eq!(price_from_text("oferta 49,90 hoje", br), Some(49.9));
+        assert_eq!(price_from_text("4.8 ★ 1,2 mil vendidos", br), None);
+        assert_eq!(price_from_text("", br), None);
+    }
+}
@@ -0,0 +1,169 @@
+// Offline Mode
+// Parses pages saved from the browser, as .html files or .mhtml web
+// archives, straight from a folder: no browser is launched and nothing is
+// fetched. Products keep the path of the page they came from.
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// Saved pages in `folder` and its subfolders, sorted by path
+pub fn saved_pages(folder: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut pages = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let extension = path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if matches!(extension.as_str(), "html" | "htm" | "mhtml" | "mht") {
+                pages.push(path);
+            }
+        }
+    }
+    pages.sort();
+    Ok(pages)
+}
+
+/// HTML of a saved page; web archives are unpacked to their HTML part
+pub fn read_page(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if matches!(extension.as_str(), "mhtml" | "mht") {
+        mhtml_html(&text).ok_or_else(|| anyhow!("No HTML part in the web archive"))
+    } else {
+        Ok(text.into_owned())
+    }
+}
+
+/// The first text/html part of an MHTML archive, decoded
+pub fn mhtml_html(archive: &str) -> Option<String> {
+    let boundary_re = Regex::new(r#"(?i)boundary="?([^";\r\n]+)"?"#).ok()?;
+    let boundary = boundary_re.captures(archive)?.get(1)?.as_str().trim();
+    let delimiter = format!("--{}", boundary);
+
+    archive.split(delimiter.as_str()).skip(1).find_map(|part| {
+        let part = part.trim_start_matches(['\r', '\n']);
+        let (headers, body) = part
+            .split_once("\r\n\r\n")
+            .or_else(|| part.split_once("\n\n"))?;
+        let headers = headers.to_lowercase();
+        if !headers.contains("content-type: text/html") {
+            return None;
+        }
+
+        let decoded = if headers.contains("content-transfer-encoding: quoted-printable") {
+            decode_quoted_printable(body)
+        } else if headers.contains("content-transfer-encoding: base64") {
+            let compact: String = body.split_whitespace().collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .ok()?
+        } else {
+            body.as_bytes().to_vec()
+        };
+        Some(String::from_utf8_lossy(&decoded).into_owned())
+    })
+}
+
+/// Quoted-printable text: "=XX" escapes and "=" soft line breaks
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'=' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        // Soft line break
+        if bytes[i + 1..].starts_with(b"\r\n") {
+            i += 3;
+            continue;
+        }
+        if bytes[i + 1..].starts_with(b"\n") {
+            i += 2;
+            continue;
+        }
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(b'=');
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_quoted_printable() {
+        let decoded =
+            decode_quoted_printable("<p class=3D\"price\">R$ 89,90</p>=\r\n<p>Cal=C3=A7a</p>");
+        assert_eq!(
+            String::from_utf8(decoded).unwrap(),
+            "<p class=\"price\">R$ 89,90</p><p>Calça</p>"
+        );
+        // A stray "=" is kept
+        assert_eq!(decode_quoted_printable("a=zz"), b"a=zz");
+    }
+
+    #[test]
+    fn test_mhtml_html() {
+        let archive = "From: <Saved by Blink>\r\n\
+            Content-Type: multipart/related;\r\n\ttype=\"text/html\";\r\n\tboundary=\"----MultipartBoundary--abc\"\r\n\r\n\
+            ------MultipartBoundary--abc\r\n\
+            Content-Type: text/html\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\r\n\
+            <html><body><div class=3D\"product\">Fone</div></body></html>\r\n\
+            ------MultipartBoundary--abc\r\n\
+            Content-Type: text/css\r\n\r\n\
+            body { color: red; }\r\n\
+            ------MultipartBoundary--abc--\r\n";
+
+        let html = mhtml_html(archive).unwrap();
+        assert!(html.contains("<div class=\"product\">Fone</div>"));
+        assert!(!html.contains("color: red"));
+    }
+
+    #[test]
+    fn test_mhtml_base64_part() {
+        let body = base64::engine::general_purpose::STANDARD.encode("<html>ok</html>");
+        let archive = format!(
+            "Content-Type: multipart/related; boundary=xyz\n\n--xyz\n\
+             Content-Type: text/html; charset=utf-8\n\
+             Content-Transfer-Encoding: base64\n\n{}\n--xyz--\n",
+            body
+        );
+        assert_eq!(mhtml_html(&archive).unwrap().trim(), "<html>ok</html>");
+    }
+
+    #[test]
+    fn test_mhtml_without_html_part() {
+        assert!(mhtml_html("no archive here").is_none());
+    }
+}
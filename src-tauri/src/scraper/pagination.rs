@@ -0,0 +1,312 @@
+// Pagination Module
+// Loads further results with the strategy the page uses: infinite scroll,
+// "Ver mais" buttons or a page number in the URL
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::driver::PageDriver;
+
+/// Consecutive attempts without new content before a page counts as exhausted
+const MAX_IDLE_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum PaginationStrategy {
+    /// Scroll to the bottom and wait for more items to render
+    #[default]
+    InfiniteScroll,
+    /// Click a "load more"/"next" button, found by selector or by its text
+    ClickNext {
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default = "default_button_texts")]
+        texts: Vec<String>,
+    },
+    /// Navigate to the same URL with an incremented page query parameter
+    UrlParam {
+        param: String,
+        #[serde(default = "default_first_page")]
+        first_page: u32,
+    },
+}
+
+impl PaginationStrategy {
+    /// Catalog key of the scraper log line shown while loading more results
+    pub fn progress_key(&self) -> &'static str {
+        match self {
+            PaginationStrategy::InfiniteScroll => "pagination.scroll",
+            PaginationStrategy::ClickNext { .. } => "pagination.click_next",
+            PaginationStrategy::UrlParam { .. } => "pagination.next_page",
+        }
+    }
+}
+
+fn default_button_texts() -> Vec<String> {
+    ["ver mais", "carregar mais", "mostrar mais", "load more", "show more", "next"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_first_page() -> u32 {
+    1
+}
+
+/// Strategy for pages whose URL contains `url_contains`
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub struct PaginationRule {
+    pub url_contains: String,
+    pub strategy: PaginationStrategy,
+}
+
+/// Pick the first rule matching `url`, falling back to `default`
+pub fn strategy_for<'a>(
+    url: &str,
+    rules: &'a [PaginationRule],
+    default: &'a PaginationStrategy,
+) -> &'a PaginationStrategy {
+    rules
+        .iter()
+        .find(|rule| url.contains(&rule.url_contains))
+        .map(|rule| &rule.strategy)
+        .unwrap_or(default)
+}
+
+/// Per-page progress of a pagination strategy
+pub struct Paginator {
+    strategy: PaginationStrategy,
+    base_url: String,
+    page_number: u32,
+    previous_height: i64,
+    idle_attempts: u32,
+}
+
+impl Paginator {
+    pub fn new(strategy: PaginationStrategy, base_url: &str) -> Self {
+        let page_number = match &strategy {
+            PaginationStrategy::UrlParam { first_page, .. } => *first_page,
+            _ => 1,
+        };
+
+        Self {
+            strategy,
+            base_url: base_url.to_string(),
+            page_number,
+            previous_height: 0,
+            idle_attempts: 0,
+        }
+    }
+
+    pub fn strategy(&self) -> &PaginationStrategy {
+        &self.strategy
+    }
+
+    /// Continue a URL-paginated listing from `page_number` (checkpoint after a crash)
+    pub fn resume_at(mut self, page_number: u32) -> Self {
+        if matches!(self.strategy, PaginationStrategy::UrlParam { .. }) {
+            self.page_number = self.page_number.max(page_number);
+        }
+        self
+    }
+
+    pub fn page_number(&self) -> u32 {
+        self.page_number
+    }
+
+    /// Page height reached by infinite scroll
+    pub fn scroll_height(&self) -> i64 {
+        self.previous_height
+    }
+
+    /// URL of the page currently being paginated
+    pub fn current_url(&self) -> String {
+        match &self.strategy {
+            PaginationStrategy::UrlParam { param, first_page } if self.page_number > *first_page => {
+                page_url(&self.base_url, param, self.page_number)
+            }
+            _ => self.base_url.clone(),
+        }
+    }
+
+    /// Tell the paginator whether the last parse pass found new products.
+    /// Returns false once the page looks exhausted.
+    pub fn record_pass(&mut self, new_products: usize) -> bool {
+        // Scrolling tracks page height instead, since items may render late
+        if matches!(self.strategy, PaginationStrategy::InfiniteScroll) {
+            return true;
+        }
+
+        if new_products > 0 {
+            self.idle_attempts = 0;
+        } else {
+            self.idle_attempts += 1;
+        }
+        self.idle_attempts < MAX_IDLE_ATTEMPTS
+    }
+
+    /// Load the next batch of results. Returns false when there is nothing more to load.
+    pub async fn advance<P: PageDriver>(&mut self, page: &P) -> Result<bool> {
+        match self.strategy.clone() {
+            PaginationStrategy::InfiniteScroll => self.scroll(page).await,
+            PaginationStrategy::ClickNext { selector, texts } => {
+                Self::click_next(page, selector.as_deref(), &texts).await
+            }
+            PaginationStrategy::UrlParam { param, .. } => {
+                self.page_number += 1;
+                let url = page_url(&self.base_url, &param, self.page_number);
+                page.goto(&url).await?;
+                tokio::time::sleep(tokio::time::Duration::from_millis(3000)).await;
+                Ok(true)
+            }
+        }
+    }
+
+    async fn scroll<P: PageDriver>(&mut self, page: &P) -> Result<bool> {
+        page.evaluate("window.scrollTo(0, document.body.scrollHeight)")
+            .await?;
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(2000)).await;
+
+        // Check if we reached bottom
+        let current_height = page
+            .evaluate("document.body.scrollHeight")
+            .await?
+            .as_i64()
+            .unwrap_or(self.previous_height); // Keep same if failed to parse
+
+        if current_height == self.previous_height {
+            self.idle_attempts += 1;
+        } else {
+            self.idle_attempts = 0;
+        }
+        self.previous_height = current_height;
+
+        Ok(self.idle_attempts < MAX_IDLE_ATTEMPTS)
+    }
+
+    async fn click_next<P: PageDriver>(
+        page: &P,
+        selector: Option<&str>,
+        texts: &[String],
+    ) -> Result<bool> {
+        let script = format!(
+            r#"
+            ((selector, texts) => {{
+                let el = selector ? document.querySelector(selector) : null;
+                if (!el) {{
+                    el = Array.from(document.querySelectorAll('button, a, [role="button"]'))
+                        .find(b => texts.some(t => (b.innerText || '').trim().toLowerCase().includes(t)));
+                }}
+                if (!el || el.disabled || el.getAttribute('aria-disabled') === 'true') return false;
+                el.scrollIntoView({{ block: 'center' }});
+                el.click();
+                return true;
+            }})({}, {})
+        "#,
+            serde_json::to_string(&selector)?,
+            serde_json::to_string(texts)?
+        );
+
+        let clicked = page.evaluate(&script).await?.as_bool().unwrap_or(false);
+
+        if clicked {
+            tokio::time::sleep(tokio::time::Duration::from_millis(2500)).await;
+        }
+        Ok(clicked)
+    }
+}
+
+/// `base` with `param` set to `page_number`, replacing an existing value
+pub fn page_url(base: &str, param: &str, page_number: u32) -> String {
+    let (path, query) = base.split_once('?').unwrap_or((base, ""));
+
+    let mut pairs: Vec<String> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| pair.split('=').next() != Some(param))
+        .map(String::from)
+        .collect();
+    pairs.push(format!("{}={}", param, page_number));
+
+    format!("{}?{}", path, pairs.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_url() {
+        assert_eq!(
+            page_url("https://shop.tiktok.com/c/beleza", "page", 2),
+            "https://shop.tiktok.com/c/beleza?page=2"
+        );
+        assert_eq!(
+            page_url("https://shop.tiktok.com/search?keyword=fone&page=1", "page", 3),
+            "https://shop.tiktok.com/search?keyword=fone&page=3"
+        );
+        assert_eq!(
+            page_url("https://shop.tiktok.com/search?pageSize=20", "page", 2),
+            "https://shop.tiktok.com/search?pageSize=20&page=2"
+        );
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let strategy = PaginationStrategy::UrlParam {
+            param: "page".to_string(),
+            first_page: 1,
+        };
+        let base = "https://shop.tiktok.com/store/abc";
+
+        let fresh = Paginator::new(strategy.clone(), base);
+        assert_eq!(fresh.current_url(), base);
+
+        let resumed = Paginator::new(strategy, base).resume_at(4);
+        assert_eq!(resumed.page_number(), 4);
+        assert_eq!(resumed.current_url(), "https://shop.tiktok.com/store/abc?page=4");
+
+        // Scrolling pages always restart from the top
+        let scroll = Paginator::new(PaginationStrategy::InfiniteScroll, base).resume_at(4);
+        assert_eq!(scroll.current_url(), base);
+    }
+
+    #[test]
+    fn test_strategy_for_url() {
+        let rules = vec![PaginationRule {
+            url_contains: "/store/".to_string(),
+            strategy: PaginationStrategy::UrlParam {
+                param: "page".to_string(),
+                first_page: 1,
+            },
+        }];
+        let default = PaginationStrategy::default();
+
+        assert_eq!(
+            strategy_for("https://shop.tiktok.com/store/abc", &rules, &default),
+            &rules[0].strategy
+        );
+        assert_eq!(
+            strategy_for("https://shop.tiktok.com/browse", &rules, &default),
+            &PaginationStrategy::InfiniteScroll
+        );
+    }
+
+    #[test]
+    fn test_click_strategy_defaults() {
+        let strategy: PaginationStrategy =
+            serde_json::from_str(r#"{ "type": "click_next" }"#).unwrap();
+        match strategy {
+            PaginationStrategy::ClickNext { selector, texts } => {
+                assert!(selector.is_none());
+                assert!(texts.contains(&"ver mais".to_string()));
+            }
+            other => panic!("unexpected strategy {:?}", other),
+        }
+    }
+}
@@ -2,15 +2,108 @@
 // HTML/JSON parsing for TikTok Shop pages
 
 use anyhow::{Context, Result};
-use chromiumoxide::Page;
+use regex::Regex;
 use scraper::{Html, Selector};
 use serde_json::Value;
+use std::sync::LazyLock;
 use uuid::Uuid;
 
-use crate::models::Product;
+use super::driver::PageDriver;
+use super::intercept::{self, ApiListResponse, ApiNumber, ApiProduct, CapturedResponse};
+use super::jsonld::{self, JsonLdProduct};
+use super::models::ExtractionStrategy;
+use crate::models::{MarketplaceAccess, Product, ProductSource, ProductVariant, RawPayload};
+
+/// A sales count and its optional magnitude suffix, e.g. "2,3 mil"
+static SALES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\d+(?:[.,]\d+)*)\s*(milh(?:ões|oes|ão|ao)|millions?|mil|mi|k|m|b)?\b")
+        .expect("valid regex")
+});
+
+/// Number formatting convention of the storefront being scraped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberLocale {
+    /// 1.234,56 (Brazil)
+    #[default]
+    Br,
+    /// 1,234.56 (United States, UK, Mexico, Southeast Asia)
+    Us,
+    /// 1.234,56 or 1 234,56 (Eurozone, Indonesia)
+    Eu,
+}
+
+impl NumberLocale {
+    /// Locale for a TikTok Shop region code (ISO 3166, e.g. "BR", "US").
+    /// Unknown regions use the Brazilian format.
+    pub fn from_region(region: &str) -> Self {
+        match region.trim().to_uppercase().as_str() {
+            "US" | "GB" | "UK" | "MX" | "PH" | "MY" | "SG" | "TH" => NumberLocale::Us,
+            "DE" | "FR" | "ES" | "IT" | "PT" | "NL" | "IE" | "ID" | "VN" => NumberLocale::Eu,
+            _ => NumberLocale::Br,
+        }
+    }
+
+    /// Locale for an ISO 4217 currency code, if it implies one
+    pub fn from_currency(currency: &str) -> Option<Self> {
+        match currency.trim().to_uppercase().as_str() {
+            "BRL" => Some(NumberLocale::Br),
+            "USD" | "GBP" | "MXN" | "PHP" | "MYR" | "SGD" | "THB" => Some(NumberLocale::Us),
+            "EUR" | "IDR" | "VND" => Some(NumberLocale::Eu),
+            _ => None,
+        }
+    }
+
+    /// (decimal separator, thousands separator)
+    pub fn separators(&self) -> (char, char) {
+        match self {
+            NumberLocale::Br | NumberLocale::Eu => (',', '.'),
+            NumberLocale::Us => ('.', ','),
+        }
+    }
+
+    /// Currency assumed when the page does not state one
+    pub fn default_currency(&self) -> &'static str {
+        match self {
+            NumberLocale::Br => "BRL",
+            NumberLocale::Us => "USD",
+            NumberLocale::Eu => "EUR",
+        }
+    }
+}
+
+/// Products found on a page and which extraction source produced them
+pub struct PageParse {
+    pub products: Vec<Product>,
+    /// Strategy name, "dom:<selector>", "json-ld" or "none"
+    pub source: String,
+}
+
+/// One way of getting the product delivered, from a detail page
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShippingOption {
+    pub name: Option<String>,
+    pub fee: Option<f64>,
+    pub delivery_days_min: Option<i32>,
+    pub delivery_days_max: Option<i32>,
+}
+
+/// What a product detail page has beyond the listing card
+#[derive(Debug, Clone, Default)]
+pub struct ProductDetail {
+    /// Full description, as plain text
+    pub description: Option<String>,
+    pub variants: Vec<ProductVariant>,
+    /// Cheapest first
+    pub shipping_options: Vec<ShippingOption>,
+    pub seller_name: Option<String>,
+    pub seller_rating: Option<f64>,
+}
 
 pub struct TikTokParser {
     selectors: Vec<String>,
+    strategies: Vec<ExtractionStrategy>,
+    locale: NumberLocale,
+    raw_capture: bool,
 }
 
 impl TikTokParser {
@@ -23,66 +116,99 @@ impl TikTokParser {
                     ".product-item".to_string(),
                 ]
             }),
+            strategies: ExtractionStrategy::defaults(),
+            locale: NumberLocale::default(),
+            raw_capture: false,
+        }
+    }
+
+    /// Replace the built-in JSON extraction strategies (ignored when empty)
+    pub fn with_strategies(mut self, strategies: Vec<ExtractionStrategy>) -> Self {
+        if !strategies.is_empty() {
+            self.strategies = strategies;
         }
+        self
     }
 
-    pub async fn parse_product_list(&self, page: &Page) -> Result<Vec<Product>> {
-        // Try JavaScript first (faster and more reliable)
-        log::debug!("Attempting to parse products from __INITIAL_STATE__");
+    pub fn with_locale(mut self, locale: NumberLocale) -> Self {
+        self.locale = locale;
+        self
+    }
 
-        let script = r#"
-            (() => {
-                if (window.__INITIAL_STATE__) {
-                    // Direct product list
-                    if (window.__INITIAL_STATE__.products) {
-                        return JSON.stringify(window.__INITIAL_STATE__.products);
-                    }
-                    // Product list wrapper
-                    if (window.__INITIAL_STATE__.productList && window.__INITIAL_STATE__.productList.products) {
-                        return JSON.stringify(window.__INITIAL_STATE__.productList.products);
-                    }
-                    // Shop page structure
-                    if (window.__INITIAL_STATE__.shop && window.__INITIAL_STATE__.shop.products) {
-                        return JSON.stringify(window.__INITIAL_STATE__.shop.products);
-                    }
-                    // General search result
-                    if (window.__INITIAL_STATE__.search && window.__INITIAL_STATE__.search.item_list) {
-                        return JSON.stringify(window.__INITIAL_STATE__.search.item_list);
-                    }
-                }
-                
-                // Try to find JSON in script tags (SIGI_STATE is common in TikTok)
-                const sigiState = document.getElementById('SIGI_STATE');
-                if (sigiState) {
-                    try {
-                        const data = JSON.parse(sigiState.textContent);
-                        if (data.ItemModule) {
-                            return JSON.stringify(Object.values(data.ItemModule));
-                        }
-                    } catch (e) {}
-                }
-                
+    /// Keep the JSON each product was parsed from (see `Product::raw_payload`)
+    pub fn with_raw_capture(mut self, enabled: bool) -> Self {
+        self.raw_capture = enabled;
+        self
+    }
+
+    pub async fn parse_product_list<P: PageDriver>(&self, page: &P) -> Result<PageParse> {
+        // Try the embedded JSON first (faster and more reliable)
+        log::debug!(
+            "Attempting to parse products with {} extraction strategies",
+            self.strategies.len()
+        );
+
+        let strategies = serde_json::to_string(&self.strategies)?;
+        let script = format!(
+            r#"
+            ((strategies) => {{
+                for (const s of strategies) {{
+                    try {{
+                        let node;
+                        if (s.root.startsWith('#')) {{
+                            const el = document.getElementById(s.root.slice(1));
+                            if (!el) continue;
+                            node = JSON.parse(el.textContent);
+                        }} else {{
+                            node = window[s.root];
+                        }}
+                        for (const key of (s.path ? s.path.split('.') : [])) {{
+                            if (node == null) break;
+                            node = node[key];
+                        }}
+                        if (node == null) continue;
+                        if (s.values) node = Object.values(node);
+                        if (Array.isArray(node) && node.length > 0) {{
+                            return JSON.stringify({{ source: s.name, items: node }});
+                        }}
+                    }} catch (e) {{}}
+                }}
                 return null;
-            })()
-        "#;
+            }})({})
+        "#,
+            strategies
+        );
 
-        let result = page.evaluate(script).await?;
+        let result = page.evaluate(&script).await?;
 
-        if let Some(json_str) = result.value() {
-            if !json_str.is_null() {
-                if let Ok(json_text) = serde_json::from_value::<String>(json_str.clone()) {
-                    if let Ok(products_json) = serde_json::from_str::<Value>(&json_text) {
-                        if let Some(arr) = products_json.as_array() {
-                            let products: Vec<Product> = arr
-                                .iter()
-                                .filter_map(|item| self.parse_product_json(item).ok())
-                                .collect();
-
-                            if !products.is_empty() {
-                                log::info!("Parsed {} products from JSON", products.len());
-                                return Ok(products);
+        if let Ok(json_text) = serde_json::from_value::<String>(result) {
+            if let Ok(found) = serde_json::from_str::<Value>(&json_text) {
+                let source = found
+                    .get("source")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                if let Some(arr) = found.get("items").and_then(|v| v.as_array()) {
+                    let products: Vec<Product> = arr
+                        .iter()
+                        .filter_map(|item| {
+                            let mut product = self.parse_product_json(item).ok()?;
+                            if self.raw_capture {
+                                product.raw_payload = Some(RawPayload {
+                                    source: source.to_string(),
+                                    json: item.to_string(),
+                                    captured_at: product.collected_at.clone(),
+                                });
                             }
-                        }
+                            Some(product)
+                        })
+                        .collect();
+
+                    if !products.is_empty() {
+                        log::info!("Parsed {} products from {}", products.len(), source);
+                        return Ok(PageParse {
+                            products,
+                            source: source.to_string(),
+                        });
                     }
                 }
             }
@@ -93,10 +219,17 @@ impl TikTokParser {
         self.parse_product_list_from_dom(page).await
     }
 
-    async fn parse_product_list_from_dom(&self, page: &Page) -> Result<Vec<Product>> {
+    async fn parse_product_list_from_dom<P: PageDriver>(&self, page: &P) -> Result<PageParse> {
         let html = page.content().await?;
-        let document = Html::parse_document(&html);
+        Ok(self.parse_html(&html))
+    }
+
+    /// Products from a page's HTML, using the first selector that matches
+    pub fn parse_html(&self, html: &str) -> PageParse {
+        self.parse_document(&Html::parse_document(html))
+    }
 
+    pub fn parse_document(&self, document: &Html) -> PageParse {
         for selector_str in &self.selectors {
             if let Ok(selector) = Selector::parse(selector_str) {
                 let elements: Vec<_> = document.select(&selector).collect();
@@ -114,17 +247,224 @@ impl TikTokParser {
                         .collect();
 
                     if !products.is_empty() {
-                        return Ok(products);
+                        return PageParse {
+                            products,
+                            source: format!("dom:{}", selector_str),
+                        };
                     }
                 }
             }
         }
 
+        // Unknown layout: many pages still embed schema.org Product data
+        let products: Vec<Product> = jsonld::extract_products(document)
+            .into_iter()
+            .map(|data| self.parse_json_ld_product(data))
+            .collect();
+        if !products.is_empty() {
+            log::debug!("Found {} products in JSON-LD", products.len());
+            return PageParse {
+                products,
+                source: "json-ld".to_string(),
+            };
+        }
+
         log::warn!("No products found in DOM");
-        Ok(Vec::new())
+        PageParse {
+            products: Vec::new(),
+            source: "none".to_string(),
+        }
+    }
+
+    /// Products from JSON API responses captured while the page loaded.
+    /// The source is the endpoint of the first response that yielded products.
+    pub fn parse_api_responses(&self, responses: &[CapturedResponse]) -> PageParse {
+        let mut products = Vec::new();
+        let mut source = None;
+
+        for response in responses {
+            let Ok(list) = serde_json::from_str::<ApiListResponse>(&response.body) else {
+                continue;
+            };
+            let endpoint = intercept::endpoint_name(&response.url);
+
+            let before = products.len();
+            for item in list.into_items() {
+                let Ok(api_product) = serde_json::from_value::<ApiProduct>(item.clone()) else {
+                    continue;
+                };
+                let mut product = self.parse_api_product(api_product);
+                if self.raw_capture {
+                    product.raw_payload = Some(RawPayload {
+                        source: endpoint.clone(),
+                        json: item.to_string(),
+                        captured_at: product.collected_at.clone(),
+                    });
+                }
+                products.push(product);
+            }
+
+            if products.len() > before && source.is_none() {
+                source = Some(endpoint);
+            }
+        }
+
+        if !products.is_empty() {
+            log::info!("Parsed {} products from intercepted API responses", products.len());
+        }
+        PageParse {
+            products,
+            source: source.unwrap_or_else(|| "none".to_string()),
+        }
+    }
+
+    fn parse_json_ld_product(&self, data: JsonLdProduct) -> Product {
+        let tiktok_id = data
+            .url
+            .as_deref()
+            .and_then(Self::extract_id_from_url)
+            .or(data.id)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let currency = data
+            .currency
+            .unwrap_or_else(|| self.locale.default_currency().to_string());
+        let price = data.price.unwrap_or(0.0);
+        let now = chrono::Utc::now().to_rfc3339();
+
+        Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: tiktok_id.clone(),
+            title: data.name,
+            description: data.description,
+            price,
+            original_price: None,
+            currency,
+            category: None,
+            subcategory: None,
+            seller_name: data.brand,
+            seller_rating: None,
+            product_rating: data.rating.filter(|score| (0.0..=5.0).contains(score)),
+            reviews_count: data.review_count.unwrap_or(0),
+            sales_count: 0,
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: data.images.first().cloned(),
+            images: data.images,
+            video_url: None,
+            product_url: data
+                .url
+                .unwrap_or_else(|| format!("https://shop.tiktok.com/product/{}", &tiktok_id)),
+            affiliate_url: None,
+            has_free_shipping: false,
+            is_trending: false,
+            is_on_sale: false,
+            in_stock: data.in_stock.unwrap_or(true),
+            stock_level: None,
+            min_price: data.high_price.and(data.price),
+            max_price: data.high_price,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now,
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Tiktok,
+        }
+    }
+
+    fn parse_api_product(&self, data: ApiProduct) -> Product {
+        let tiktok_id = data.product_id.to_string();
+
+        let price_info = data.product_price_info.as_ref();
+        let currency = price_info
+            .and_then(|info| info.currency_name.clone())
+            .unwrap_or_else(|| self.locale.default_currency().to_string());
+        let locale = NumberLocale::from_currency(&currency).unwrap_or(self.locale);
+
+        let api_price = |value: Option<&ApiNumber>| match value {
+            Some(ApiNumber::Number(n)) => Some(*n),
+            Some(ApiNumber::Text(s)) => Some(Self::parse_price_text(s, locale)),
+            None => None,
+        };
+        let api_count = |value: Option<&ApiNumber>| match value {
+            Some(ApiNumber::Number(n)) => *n as i32,
+            Some(ApiNumber::Text(s)) => Self::parse_sales_text(s),
+            None => 0,
+        };
+
+        let price = api_price(price_info.and_then(|info| info.sale_price.as_ref()))
+            .or_else(|| api_price(data.price.as_ref()))
+            .unwrap_or(0.0);
+        let original_price = api_price(price_info.and_then(|info| info.origin_price.as_ref()))
+            .filter(|op| *op > 0.0);
+
+        let images = data.image.map(|image| image.url_list).unwrap_or_default();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: tiktok_id.clone(),
+            title: data.title,
+            description: None,
+            price,
+            original_price,
+            currency,
+            category: None,
+            subcategory: None,
+            seller_name: data.seller_info.as_ref().and_then(|s| s.shop_name.clone()),
+            seller_rating: data.seller_info.as_ref().and_then(|s| s.rating),
+            product_rating: data
+                .rate_info
+                .as_ref()
+                .and_then(|r| r.score)
+                .filter(|score| (0.0..=5.0).contains(score)),
+            reviews_count: api_count(data.rate_info.as_ref().and_then(|r| r.review_count.as_ref())),
+            sales_count: api_count(data.sold_info.as_ref().and_then(|s| s.sold_count.as_ref())),
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: images.first().cloned(),
+            images,
+            video_url: None,
+            product_url: data
+                .product_url
+                .unwrap_or_else(|| format!("https://shop.tiktok.com/product/{}", &tiktok_id)),
+            affiliate_url: None,
+            has_free_shipping: data.free_shipping.unwrap_or(false),
+            is_trending: false,
+            is_on_sale: original_price.is_some_and(|op| op > price),
+            in_stock: true,
+            stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now,
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Tiktok,
+        }
     }
 
-    fn parse_product_json(&self, data: &Value) -> Result<Product> {
+    pub fn parse_product_json(&self, data: &Value) -> Result<Product> {
         let tiktok_id = data
             .get("id")
             .or_else(|| data.get("productId"))
@@ -145,21 +485,25 @@ impl TikTokParser {
             .unwrap_or("")
             .to_string();
 
-        let price = self.extract_price(data.get("price"))?;
+        let currency = data
+            .get("currency")
+            .and_then(|v| v.as_str())
+            .unwrap_or(self.locale.default_currency())
+            .to_string();
+        let locale = NumberLocale::from_currency(&currency).unwrap_or(self.locale);
+
+        let price = self.extract_price(data.get("price"), locale)?;
         let original_price_val = self
             .extract_price(
                 data.get("originalPrice")
                     .or_else(|| data.get("original_price")),
+                locale,
             )
             .ok();
 
-        let currency = data
-            .get("currency")
-            .and_then(|v| v.as_str())
-            .unwrap_or("BRL")
-            .to_string();
+        let variants = self.parse_variants(data, locale);
 
-        Ok(Product {
+        let mut product = Product {
             id: Uuid::new_v4().to_string(),
             tiktok_id: tiktok_id.clone(),
             title,
@@ -196,6 +540,7 @@ impl TikTokParser {
             sales_7d: self.parse_sales_count(data.get("sales7d"))?,
             sales_30d: self.parse_sales_count(data.get("sales30d"))?,
             commission_rate: data.get("commissionRate").and_then(|v| v.as_f64()),
+            open_collaboration: data.get("openCollaboration").and_then(|v| v.as_bool()),
             image_url: data
                 .get("imageUrl")
                 .or_else(|| data.get("image"))
@@ -242,9 +587,404 @@ impl TikTokParser {
                 .or_else(|| data.get("quantity"))
                 .and_then(|v| v.as_i64())
                 .map(|v| v as i32),
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
             collected_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
-        })
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Tiktok,
+        };
+
+        Self::apply_variants(&mut product, variants);
+        self.apply_shipping(&mut product, data, locale);
+        Ok(product)
+    }
+
+    /// Fill shipping fee and delivery estimate from a product JSON blob.
+    /// Fields missing from `data` are left untouched.
+    pub fn apply_shipping(&self, product: &mut Product, data: &Value, locale: NumberLocale) {
+        let shipping = data
+            .get("shipping")
+            .or_else(|| data.get("logistics"))
+            .filter(|v| v.is_object());
+
+        let fee_value = data
+            .get("shippingFee")
+            .or_else(|| data.get("shipping_fee"))
+            .or_else(|| shipping.and_then(|s| s.get("fee").or_else(|| s.get("price"))));
+        if fee_value.is_some() {
+            product.shipping_fee = self.extract_price(fee_value, locale).ok();
+        }
+
+        let delivery = data
+            .get("deliveryDays")
+            .or_else(|| data.get("delivery_days"))
+            .or_else(|| data.get("delivery"))
+            .or_else(|| shipping.and_then(|s| s.get("deliveryDays").or_else(|| s.get("delivery"))));
+
+        if let Some(delivery) = delivery {
+            let (min, max) = Self::delivery_window(delivery);
+            if min.is_some() || max.is_some() {
+                product.delivery_days_min = min;
+                product.delivery_days_max = max;
+            }
+        }
+
+        Self::reconcile_free_shipping(product);
+    }
+
+    /// Delivery window of a days count, a text estimate or a `{min, max}` object
+    fn delivery_window(delivery: &Value) -> (Option<i32>, Option<i32>) {
+        if let Some(days) = delivery.as_i64() {
+            (Some(days as i32), Some(days as i32))
+        } else if let Some(text) = delivery.as_str() {
+            Self::parse_delivery_text(text)
+        } else {
+            let day = |key: &str| delivery.get(key).and_then(|v| v.as_i64()).map(|d| d as i32);
+            (day("min"), day("max"))
+        }
+    }
+
+    /// Parse the detail JSON of a product page: full description, variants,
+    /// shipping options and seller
+    pub fn parse_product_detail(&self, data: &Value, locale: NumberLocale) -> ProductDetail {
+        let description = ["description", "desc", "productDescription", "desc_detail"]
+            .iter()
+            .find_map(|key| data.get(*key))
+            .and_then(Self::description_text);
+
+        let seller = ["seller", "shop", "shopInfo", "shop_info"]
+            .iter()
+            .find_map(|key| data.get(*key).filter(|v| v.is_object()));
+        let seller_name = seller
+            .and_then(|s| {
+                ["name", "shopName", "shop_name", "sellerName"]
+                    .iter()
+                    .find_map(|key| s.get(*key).and_then(|v| v.as_str()))
+            })
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let seller_rating = seller.and_then(|s| {
+            ["rating", "score", "shopRating", "shop_rating"]
+                .iter()
+                .find_map(|key| s.get(*key))
+                .and_then(|v| {
+                    v.as_f64()
+                        .or_else(|| v.as_str().and_then(|t| t.replace(',', ".").parse().ok()))
+                })
+                .filter(|r| (0.0..=5.0).contains(r))
+        });
+
+        let list = ["shippingOptions", "shipping_options", "deliveryOptions", "logistics"]
+            .iter()
+            .find_map(|key| data.get(*key).and_then(|v| v.as_array()));
+        let mut shipping_options: Vec<ShippingOption> = match list {
+            Some(list) => list
+                .iter()
+                .map(|option| ShippingOption {
+                    name: ["name", "title", "serviceName", "service_name"]
+                        .iter()
+                        .find_map(|key| option.get(*key).and_then(|v| v.as_str()))
+                        .map(String::from),
+                    ..self.shipping_option(option, locale)
+                })
+                .collect(),
+            // A single option, in a shipping object or the page's own fields
+            None => match data
+                .get("shipping")
+                .or_else(|| data.get("logistics"))
+                .filter(|v| v.is_object())
+            {
+                Some(shipping) => vec![self.shipping_option(shipping, locale)],
+                None => {
+                    let fee = data
+                        .get("shippingFee")
+                        .or_else(|| data.get("shipping_fee"))
+                        .and_then(|v| self.extract_price(Some(v), locale).ok());
+                    let (min, max) = data
+                        .get("deliveryDays")
+                        .or_else(|| data.get("delivery_days"))
+                        .map(Self::delivery_window)
+                        .unwrap_or_default();
+                    vec![ShippingOption {
+                        name: None,
+                        fee,
+                        delivery_days_min: min,
+                        delivery_days_max: max,
+                    }]
+                }
+            },
+        };
+        shipping_options.retain(|o| o.fee.is_some() || o.delivery_days_max.is_some());
+        shipping_options.sort_by(|a, b| {
+            let fee = |o: &ShippingOption| o.fee.unwrap_or(f64::MAX);
+            fee(a).total_cmp(&fee(b))
+        });
+
+        ProductDetail {
+            description,
+            variants: self.parse_variants(data, locale),
+            shipping_options,
+            seller_name,
+            seller_rating,
+        }
+    }
+
+    /// Fee and delivery window of one shipping option
+    fn shipping_option(&self, option: &Value, locale: NumberLocale) -> ShippingOption {
+        let fee = ["fee", "price", "shippingFee", "shipping_fee"]
+            .iter()
+            .find_map(|key| option.get(*key))
+            .and_then(|v| self.extract_price(Some(v), locale).ok());
+        let (min, max) = ["deliveryDays", "delivery_days", "delivery", "eta"]
+            .iter()
+            .find_map(|key| option.get(*key))
+            .map(Self::delivery_window)
+            .unwrap_or_default();
+        ShippingOption {
+            name: None,
+            fee,
+            delivery_days_min: min,
+            delivery_days_max: max,
+        }
+    }
+
+    /// Plain text of a description given as text, HTML or a list of blocks
+    fn description_text(value: &Value) -> Option<String> {
+        let text = match value {
+            Value::String(text) if text.contains('<') => Html::parse_fragment(text)
+                .root_element()
+                .text()
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            Value::String(text) => text.clone(),
+            // Rich descriptions: [{ "type": "text", "text": "..." }, { "type": "image", ... }]
+            Value::Array(blocks) => blocks
+                .iter()
+                .filter_map(|b| b.as_str().or_else(|| b.get("text").and_then(|t| t.as_str())))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => return None,
+        };
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+
+    /// Merge a detail page into the product read from a listing. What the
+    /// page doesn't have is left untouched; the cheapest shipping option sets
+    /// the fee and delivery window.
+    pub fn apply_detail(product: &mut Product, detail: ProductDetail) {
+        if let Some(description) = detail.description {
+            product.description = Some(description);
+        }
+        Self::apply_variants(product, detail.variants);
+        if let Some(option) = detail.shipping_options.first() {
+            if option.fee.is_some() {
+                product.shipping_fee = option.fee;
+            }
+            if option.delivery_days_min.is_some() || option.delivery_days_max.is_some() {
+                product.delivery_days_min = option.delivery_days_min;
+                product.delivery_days_max = option.delivery_days_max;
+            }
+            Self::reconcile_free_shipping(product);
+        }
+        if detail.seller_name.is_some() {
+            product.seller_name = detail.seller_name;
+        }
+        if detail.seller_rating.is_some() {
+            product.seller_rating = detail.seller_rating;
+        }
+    }
+
+    /// Keep `has_free_shipping` and `shipping_fee` consistent with each other
+    fn reconcile_free_shipping(product: &mut Product) {
+        match product.shipping_fee {
+            Some(fee) if fee <= 0.0 => product.has_free_shipping = true,
+            Some(_) => product.has_free_shipping = false,
+            None if product.has_free_shipping => product.shipping_fee = Some(0.0),
+            None => {}
+        }
+    }
+
+    /// Parse delivery estimates such as "3-7 dias úteis", "Chega em até 5 dias",
+    /// "2 days" or "Chega amanhã" into a (min, max) window in days.
+    fn parse_delivery_text(text: &str) -> (Option<i32>, Option<i32>) {
+        let lower = text.to_lowercase();
+
+        if lower.contains("amanhã") || lower.contains("amanha") || lower.contains("tomorrow") {
+            return (Some(1), Some(1));
+        }
+        if lower.contains("hoje") || lower.contains("today") {
+            return (Some(0), Some(0));
+        }
+        if !lower.contains("dia") && !lower.contains("day") {
+            return (None, None);
+        }
+
+        let numbers: Vec<i32> = lower
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect();
+
+        match numbers.as_slice() {
+            [] => (None, None),
+            [days] if lower.contains("até") || lower.contains("up to") => (None, Some(*days)),
+            [days] => (Some(*days), Some(*days)),
+            [min, max, ..] => (Some(*min.min(max)), Some(*min.max(max))),
+        }
+    }
+
+    /// Detail JSON of the product page currently open, if the page embeds one
+    pub async fn extract_detail_json<P: PageDriver>(&self, page: &P) -> Result<Option<Value>> {
+        let script = r#"
+            (() => {
+                const state = window.__INITIAL_STATE__;
+                if (state) {
+                    const detail = state.productDetail || state.pdp || state.product;
+                    if (detail) {
+                        return JSON.stringify(detail.product || detail);
+                    }
+                }
+
+                const sigiState = document.getElementById('SIGI_STATE');
+                if (sigiState) {
+                    try {
+                        const data = JSON.parse(sigiState.textContent);
+                        if (data.ProductModule) {
+                            const first = Object.values(data.ProductModule)[0];
+                            if (first) return JSON.stringify(first);
+                        }
+                    } catch (e) {}
+                }
+
+                return null;
+            })()
+        "#;
+
+        let result = page.evaluate(script).await?;
+        let detail = serde_json::from_value::<String>(result)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok());
+
+        Ok(detail)
+    }
+
+    /// Parse the SKU list of a product JSON blob (list item or detail page)
+    pub fn parse_variants(&self, data: &Value, locale: NumberLocale) -> Vec<ProductVariant> {
+        let list = ["skus", "variants", "sku_list", "skuList"]
+            .iter()
+            .find_map(|key| data.get(*key).and_then(|v| v.as_array()));
+
+        let Some(list) = list else {
+            return Vec::new();
+        };
+
+        list.iter()
+            .filter_map(|sku| {
+                let name = sku
+                    .get("name")
+                    .or_else(|| sku.get("skuName"))
+                    .or_else(|| sku.get("sku_name"))
+                    .or_else(|| sku.get("title"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .or_else(|| Self::join_sku_properties(sku))?;
+
+                let price = self
+                    .extract_price(
+                        sku.get("price")
+                            .or_else(|| sku.get("salePrice"))
+                            .or_else(|| sku.get("sale_price")),
+                        locale,
+                    )
+                    .unwrap_or(0.0);
+
+                Some(ProductVariant {
+                    id: Uuid::new_v4().to_string(),
+                    product_id: String::new(),
+                    sku_id: sku
+                        .get("skuId")
+                        .or_else(|| sku.get("sku_id"))
+                        .or_else(|| sku.get("id"))
+                        .and_then(|v| {
+                            v.as_str()
+                                .map(String::from)
+                                .or_else(|| v.as_i64().map(|n| n.to_string()))
+                        }),
+                    name,
+                    price,
+                    stock_level: sku
+                        .get("stock")
+                        .or_else(|| sku.get("stockLevel"))
+                        .or_else(|| sku.get("quantity"))
+                        .and_then(|v| v.as_i64())
+                        .map(|v| v as i32),
+                    image_url: sku
+                        .get("image")
+                        .or_else(|| sku.get("imageUrl"))
+                        .or_else(|| sku.get("image_url"))
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                })
+            })
+            .collect()
+    }
+
+    /// "Cor: Azul / Tamanho: M" style name from a SKU's property list
+    fn join_sku_properties(sku: &Value) -> Option<String> {
+        let values: Vec<&str> = sku
+            .get("properties")
+            .or_else(|| sku.get("sale_props"))
+            .and_then(|v| v.as_array())?
+            .iter()
+            .filter_map(|prop| {
+                prop.get("value")
+                    .or_else(|| prop.get("valueName"))
+                    .or_else(|| prop.get("value_name"))
+                    .and_then(|v| v.as_str())
+            })
+            .collect();
+
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(" / "))
+        }
+    }
+
+    /// Attach variants to a product and derive its price range from them
+    pub fn apply_variants(product: &mut Product, mut variants: Vec<ProductVariant>) {
+        if variants.is_empty() {
+            return;
+        }
+
+        for variant in &mut variants {
+            variant.product_id = product.id.clone();
+        }
+
+        let prices = variants.iter().map(|v| v.price).filter(|p| *p > 0.0);
+        product.min_price = prices.clone().reduce(f64::min);
+        product.max_price = prices.reduce(f64::max);
+
+        if product.price <= 0.0 {
+            if let Some(min) = product.min_price {
+                product.price = min;
+            }
+        }
+
+        product.variants = variants;
     }
 
     fn parse_product_element(&self, element: &scraper::ElementRef) -> Result<Product> {
@@ -271,7 +1011,7 @@ impl TikTokParser {
         } else {
             "0".to_string()
         };
-        let price = Self::parse_price_text(&price_text);
+        let price = Self::parse_price_text(&price_text, self.locale);
 
         let image_selector = Selector::parse("img").ok();
         let image_url = if let Some(sel) = image_selector {
@@ -299,6 +1039,32 @@ impl TikTokParser {
         let tiktok_id =
             Self::extract_id_from_url(&product_url).unwrap_or_else(|| Uuid::new_v4().to_string());
 
+        let shipping_selector =
+            Selector::parse("[data-e2e='product-shipping'], .product-shipping, .shipping").ok();
+        let shipping_text = if let Some(sel) = shipping_selector {
+            element
+                .select(&sel)
+                .next()
+                .map(|e| e.text().collect::<String>().to_lowercase())
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let has_free_shipping = shipping_text.contains("grátis")
+            || shipping_text.contains("gratis")
+            || shipping_text.contains("free");
+        let shipping_fee = if has_free_shipping {
+            Some(0.0)
+        } else if shipping_text.chars().any(|c| c.is_ascii_digit())
+            && !shipping_text.contains("dia")
+            && !shipping_text.contains("day")
+        {
+            Some(Self::parse_price_text(&shipping_text, self.locale))
+        } else {
+            None
+        };
+        let (delivery_days_min, delivery_days_max) = Self::parse_delivery_text(&shipping_text);
+
         Ok(Product {
             id: Uuid::new_v4().to_string(),
             tiktok_id,
@@ -306,7 +1072,7 @@ impl TikTokParser {
             description: None,
             price,
             original_price: None,
-            currency: "BRL".to_string(),
+            currency: self.locale.default_currency().to_string(),
             category: None,
             subcategory: None,
             seller_name: None,
@@ -317,28 +1083,43 @@ impl TikTokParser {
             sales_7d: 0,
             sales_30d: 0,
             commission_rate: None,
+            open_collaboration: None,
             image_url,
             images: vec![],
             video_url: None,
             product_url,
             affiliate_url: None,
-            has_free_shipping: false,
+            has_free_shipping,
             is_trending: false,
             is_on_sale: false,
             in_stock: true,
             stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee,
+            delivery_days_min,
+            delivery_days_max,
+            demand_estimate: None,
             collected_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Tiktok,
         })
     }
 
-    fn extract_price(&self, value: Option<&Value>) -> Result<f64> {
+    fn extract_price(&self, value: Option<&Value>, locale: NumberLocale) -> Result<f64> {
         if let Some(v) = value {
             if let Some(num) = v.as_f64() {
                 return Ok(num);
             }
             if let Some(s) = v.as_str() {
-                return Ok(Self::parse_price_text(s));
+                return Ok(Self::parse_price_text(s, locale));
             }
             if let Some(obj) = v.as_object() {
                 if let Some(val) = obj.get("value").and_then(|v| v.as_f64()) {
@@ -349,54 +1130,45 @@ impl TikTokParser {
         Ok(0.0)
     }
 
-    fn parse_price_text(text: &str) -> f64 {
-        // Keep only digits, comma, dot
+    /// Parse a price string using the separators of the given locale.
+    /// Any currency symbol or surrounding text is ignored. A lone "." with one
+    /// or two digits after it is a decimal point in every locale, so machine
+    /// formatted prices from the APIs ("29.90") parse as they are.
+    pub fn parse_price_text(text: &str, locale: NumberLocale) -> f64 {
+        let (decimal, thousands) = locale.separators();
+
+        // Keep only digits and the locale's separators
         let cleaned: String = text
             .chars()
-            .filter(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+            .filter(|c| c.is_ascii_digit() || *c == decimal || *c == thousands)
             .collect();
 
         if cleaned.is_empty() {
             return 0.0;
         }
 
-        // Check for multiple separators to determine format
-        let last_comma = cleaned.rfind(',');
-        let last_dot = cleaned.rfind('.');
-
-        let normalized = match (last_comma, last_dot) {
-            (Some(c), Some(d)) => {
-                if c > d {
-                    // Format: 1.234,56 (BR/EU) -> Remove dots, replace comma with dot
-                    cleaned.replace('.', "").replace(',', ".")
-                } else {
-                    // Format: 1,234.56 (US) -> Remove commas
-                    cleaned.replace(',', "")
-                }
-            }
-            (Some(_), None) => {
-                // Format: 1234,56 (BR/EU) -> Replace comma with dot
-                cleaned.replace(',', ".")
-            }
-            (None, Some(d)) => {
-                // Format: 1234.56 or 1.234.567
-                let dot_count = cleaned.matches('.').count();
-                if dot_count > 1 {
-                    // Multiple dots = thousands separators (1.234.567)
-                    cleaned.replace('.', "")
-                } else if cleaned.len() - d - 1 == 3 {
-                    // One dot, 3 digits after = likely thousands (1.234)
-                    // This is a heuristic, but safe for TikTok BR context
-                    cleaned.replace('.', "")
-                } else {
-                    // One dot, not 3 digits = decimal (1.23)
-                    cleaned
-                }
-            }
-            (None, None) => cleaned,
+        let machine_format = thousands == '.'
+            && !cleaned.contains(decimal)
+            && cleaned.matches('.').count() == 1
+            && cleaned
+                .rsplit_once('.')
+                .is_some_and(|(_, fraction)| matches!(fraction.len(), 1 | 2));
+        let normalized: String = if machine_format {
+            cleaned
+        } else {
+            cleaned
+                .chars()
+                .filter(|c| *c != thousands)
+                .map(|c| if c == decimal { '.' } else { c })
+                .collect()
         };
 
-        normalized.parse().unwrap_or(0.0)
+        // Absurdly long digit runs overflow to infinity
+        normalized
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .unwrap_or(0.0)
     }
 
     fn parse_sales_count(&self, value: Option<&Value>) -> Result<i32> {
@@ -411,34 +1183,57 @@ impl TikTokParser {
         Ok(0)
     }
 
-    fn parse_sales_text(text: &str) -> i32 {
-        let text_lower = text.to_lowercase();
-
-        // Check for suffixes
-        let multiplier = if text_lower.contains('k') {
-            1000.0
-        } else if text_lower.contains('m') {
-            1000000.0
-        } else {
-            1.0
+    /// Parse sales counters such as "1.234", "1,5k", "2,3 mil vendidos",
+    /// "10k+ sold" or "1-5 mil". Ranges resolve to their lower bound.
+    pub fn parse_sales_text(text: &str) -> i32 {
+        let lower = text.to_lowercase();
+        let mut matches = SALES_RE.captures_iter(&lower);
+        let first = match matches.next() {
+            Some(cap) => cap,
+            None => return 0,
         };
 
-        if multiplier > 1.0 {
-            // Handle 1.5k or 1,5k
-            let num_part = text_lower
-                .trim_end_matches('k')
-                .trim_end_matches('m')
-                .trim()
-                .replace(',', "."); // Normalize decimal separator
+        let mut suffix = first.get(2).map(|m| m.as_str());
 
-            if let Ok(val) = num_part.parse::<f64>() {
-                return (val * multiplier) as i32;
+        // "1-5 mil": the suffix written after the upper bound applies to both ends
+        if suffix.is_none() {
+            if let Some(next) = matches.next() {
+                let end = first.get(0).map_or(0, |m| m.end());
+                let start = next.get(0).map_or(end, |m| m.start());
+                let between = lower[end..start].trim();
+                if matches!(between, "-" | "–" | "—" | "~" | "a" | "to") {
+                    suffix = next.get(2).map(|m| m.as_str());
+                }
             }
         }
 
-        // Fallback: extract all digits (handles 1.234 as 1234)
-        let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
-        digits.parse().unwrap_or(0)
+        let multiplier = match suffix {
+            Some("k" | "mil") => 1_000.0,
+            Some("b") => 1_000_000_000.0,
+            Some(_) => 1_000_000.0,
+            None => 1.0,
+        };
+
+        let number = &first[1];
+        let value = if multiplier > 1.0 {
+            // With a suffix the last separator is the decimal one: 1,5k / 1.5k
+            match number.rfind(['.', ',']) {
+                Some(pos) => {
+                    let int_part: String =
+                        number[..pos].chars().filter(|c| c.is_ascii_digit()).collect();
+                    format!("{}.{}", int_part, &number[pos + 1..])
+                }
+                None => number.to_string(),
+            }
+        } else {
+            // Plain counts are integers, so every separator is a thousands one
+            number.chars().filter(|c| c.is_ascii_digit()).collect()
+        };
+
+        value
+            .parse::<f64>()
+            .map(|v| (v * multiplier).min(i32::MAX as f64) as i32)
+            .unwrap_or(0)
     }
 
     fn extract_rating(value: &Value) -> Option<f64> {
@@ -455,8 +1250,7 @@ impl TikTokParser {
         None
     }
 
-    fn extract_id_from_url(url: &str) -> Option<String> {
-        use regex::Regex;
+    pub(crate) fn extract_id_from_url(url: &str) -> Option<String> {
         let re = Regex::new(r"/product/(\d+)").ok()?;
         re.captures(url)
             .and_then(|cap| cap.get(1))
@@ -518,4 +1312,438 @@ mod tests {
         let product = parser.parse_product_json(&data).unwrap();
         assert_eq!(product.stock_level, None);
     }
+
+    #[test]
+    fn test_parse_price_text_br() {
+        let br = NumberLocale::Br;
+        assert_eq!(TikTokParser::parse_price_text("R$ 29,90", br), 29.90);
+        assert_eq!(TikTokParser::parse_price_text("R$ 1.234,56", br), 1234.56);
+        assert_eq!(TikTokParser::parse_price_text("R$ 1.234", br), 1234.0);
+        assert_eq!(TikTokParser::parse_price_text("R$ 1.234.567,89", br), 1234567.89);
+        assert_eq!(TikTokParser::parse_price_text("R$\u{a0}12", br), 12.0);
+        assert_eq!(TikTokParser::parse_price_text("0,99", br), 0.99);
+        assert_eq!(TikTokParser::parse_price_text("1,5", br), 1.5);
+        // Machine formatted prices from the APIs and JSON-LD
+        assert_eq!(TikTokParser::parse_price_text("29.90", br), 29.90);
+        assert_eq!(TikTokParser::parse_price_text("29.9", br), 29.9);
+        assert_eq!(TikTokParser::parse_price_text("R$ 1.234.567", br), 1234567.0);
+    }
+
+    #[test]
+    fn test_parse_price_text_us() {
+        let us = NumberLocale::Us;
+        assert_eq!(TikTokParser::parse_price_text("$29.90", us), 29.90);
+        assert_eq!(TikTokParser::parse_price_text("$1,234.56", us), 1234.56);
+        assert_eq!(TikTokParser::parse_price_text("$1.234", us), 1.234);
+        assert_eq!(TikTokParser::parse_price_text("$1,234", us), 1234.0);
+        assert_eq!(TikTokParser::parse_price_text("$1,234,567.89", us), 1234567.89);
+        assert_eq!(TikTokParser::parse_price_text("USD 0.99", us), 0.99);
+    }
+
+    #[test]
+    fn test_parse_price_text_eu() {
+        let eu = NumberLocale::Eu;
+        assert_eq!(TikTokParser::parse_price_text("29,90 €", eu), 29.90);
+        assert_eq!(TikTokParser::parse_price_text("1.234,56 €", eu), 1234.56);
+        assert_eq!(TikTokParser::parse_price_text("1 234,56 €", eu), 1234.56);
+        assert_eq!(TikTokParser::parse_price_text("1\u{202f}234,56 €", eu), 1234.56);
+        assert_eq!(TikTokParser::parse_price_text("€1.234", eu), 1234.0);
+        assert_eq!(TikTokParser::parse_price_text("19.99", eu), 19.99);
+    }
+
+    #[test]
+    fn test_parse_price_text_invalid() {
+        for locale in [NumberLocale::Br, NumberLocale::Us, NumberLocale::Eu] {
+            assert_eq!(TikTokParser::parse_price_text("", locale), 0.0);
+            assert_eq!(TikTokParser::parse_price_text("Grátis", locale), 0.0);
+            assert_eq!(TikTokParser::parse_price_text("R$ --", locale), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_locale_from_region_and_currency() {
+        assert_eq!(NumberLocale::from_region("BR"), NumberLocale::Br);
+        assert_eq!(NumberLocale::from_region("us"), NumberLocale::Us);
+        assert_eq!(NumberLocale::from_region("DE"), NumberLocale::Eu);
+        assert_eq!(NumberLocale::from_region("??"), NumberLocale::Br);
+
+        assert_eq!(NumberLocale::from_currency("BRL"), Some(NumberLocale::Br));
+        assert_eq!(NumberLocale::from_currency("usd"), Some(NumberLocale::Us));
+        assert_eq!(NumberLocale::from_currency("EUR"), Some(NumberLocale::Eu));
+        assert_eq!(NumberLocale::from_currency("XYZ"), None);
+    }
+
+    #[test]
+    fn test_json_price_uses_item_currency() {
+        let parser = TikTokParser::default();
+
+        let data = json!({ "id": "1", "price": "$1,234.50", "currency": "USD" });
+        assert_eq!(parser.parse_product_json(&data).unwrap().price, 1234.50);
+
+        let data = json!({ "id": "2", "price": "R$ 1.234,50" });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.price, 1234.50);
+        assert_eq!(product.currency, "BRL");
+
+        // API strings are machine formatted even in the Brazilian locale
+        let data = json!({ "id": "4", "price": "29.90" });
+        assert_eq!(parser.parse_product_json(&data).unwrap().price, 29.90);
+
+        let parser = TikTokParser::default().with_locale(NumberLocale::Us);
+        let data = json!({ "id": "3", "price": "1.234" });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.price, 1.234);
+        assert_eq!(product.currency, "USD");
+    }
+
+    #[test]
+    fn test_parse_sales_text() {
+        let cases = [
+            // Plain numbers
+            ("0", 0),
+            ("123", 123),
+            ("1.234", 1234),
+            ("1,234", 1234),
+            ("1.234.567 vendidos", 1234567),
+            // English suffixes
+            ("1.5k", 1500),
+            ("1,5k", 1500),
+            ("10K+ sold", 10000),
+            ("2.3M", 2300000),
+            ("1 million sold", 1000000),
+            // Portuguese suffixes
+            ("2,3 mil vendidos", 2300),
+            ("10 mil+", 10000),
+            ("1 mil", 1000),
+            ("1,2 mi vendidos", 1200000),
+            ("3 milhões", 3000000),
+            ("1 milhão", 1000000),
+            // Plus signs and ranges (lower bound)
+            ("500+", 500),
+            ("+1000 vendidos", 1000),
+            ("100-500 sold", 100),
+            ("100 – 500", 100),
+            ("1-5 mil", 1000),
+            ("1 a 5 mil vendidos", 1000),
+            ("1k - 5k", 1000),
+            ("5 mil - 10 mil", 5000),
+            // Overflow clamps, garbage is zero
+            ("5b", i32::MAX),
+            ("", 0),
+            ("vendidos", 0),
+            ("2 months ago", 2),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                TikTokParser::parse_sales_text(input),
+                expected,
+                "parse_sales_text({:?})",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        let parser = TikTokParser::default();
+
+        let data = json!({
+            "id": "200",
+            "title": "Camiseta",
+            "price": 0,
+            "skus": [
+                { "skuId": 1, "name": "P", "price": "R$ 39,90", "stock": 10, "image": "p.jpg" },
+                { "skuId": 2, "properties": [{ "value": "Azul" }, { "value": "GG" }], "price": 49.9 },
+                { "skuId": 3, "name": "Esgotado", "price": 0, "stock": 0 }
+            ]
+        });
+
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.variants.len(), 3);
+        assert_eq!(product.variants[0].sku_id.as_deref(), Some("1"));
+        assert_eq!(product.variants[0].price, 39.90);
+        assert_eq!(product.variants[0].stock_level, Some(10));
+        assert_eq!(product.variants[0].image_url.as_deref(), Some("p.jpg"));
+        assert_eq!(product.variants[1].name, "Azul / GG");
+        assert!(product.variants.iter().all(|v| v.product_id == product.id));
+
+        // Zero-priced variants don't count towards the range
+        assert_eq!(product.min_price, Some(39.90));
+        assert_eq!(product.max_price, Some(49.9));
+        assert_eq!(product.price, 39.90);
+    }
+
+    #[test]
+    fn test_product_without_variants_has_no_range() {
+        let parser = TikTokParser::default();
+        let data = json!({ "id": "201", "price": 10.0 });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert!(product.variants.is_empty());
+        assert_eq!(product.min_price, None);
+        assert_eq!(product.max_price, None);
+    }
+
+    #[test]
+    fn test_parse_shipping_json() {
+        let parser = TikTokParser::default();
+
+        let data = json!({ "id": "300", "price": 50.0, "shippingFee": "R$ 12,90", "deliveryDays": "3-7 dias úteis" });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.shipping_fee, Some(12.90));
+        assert_eq!(product.delivery_days_min, Some(3));
+        assert_eq!(product.delivery_days_max, Some(7));
+        assert!(!product.has_free_shipping);
+
+        let data = json!({ "id": "301", "price": 50.0, "shipping": { "fee": 0, "deliveryDays": { "min": 2, "max": 4 } } });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.shipping_fee, Some(0.0));
+        assert!(product.has_free_shipping);
+        assert_eq!(product.delivery_days_min, Some(2));
+        assert_eq!(product.delivery_days_max, Some(4));
+
+        let data = json!({ "id": "302", "price": 50.0, "freeShipping": true });
+        let product = parser.parse_product_json(&data).unwrap();
+        assert_eq!(product.shipping_fee, Some(0.0));
+        assert_eq!(product.delivery_days_max, None);
+    }
+
+    #[test]
+    fn test_parse_product_detail() {
+        let parser = TikTokParser::default();
+        let data = json!({
+            "desc_detail": [
+                { "type": "text", "text": "Fone sem fio com cancelamento de ruído." },
+                { "type": "image", "url": "https://example.com/a.jpg" },
+                { "type": "text", "text": "Bateria de 30 horas." }
+            ],
+            "skus": [
+                { "id": "s1", "name": "Preto", "price": "R$ 89,90", "stock": 10 },
+                { "id": "s2", "name": "Branco", "price": "R$ 99,90", "stock": 0 }
+            ],
+            "shippingOptions": [
+                { "name": "Expressa", "fee": "R$ 19,90", "deliveryDays": "1-2 dias" },
+                { "name": "Padrão", "fee": 0, "deliveryDays": { "min": 5, "max": 9 } }
+            ],
+            "shop": { "shop_name": " Loja Oficial ", "rating": "4,8" }
+        });
+
+        let detail = parser.parse_product_detail(&data, NumberLocale::Br);
+        assert_eq!(
+            detail.description.as_deref(),
+            Some("Fone sem fio com cancelamento de ruído.\nBateria de 30 horas.")
+        );
+        assert_eq!(detail.variants.len(), 2);
+        assert_eq!(detail.shipping_options.len(), 2);
+        assert_eq!(detail.shipping_options[0].name.as_deref(), Some("Padrão"));
+        assert_eq!(detail.shipping_options[1].fee, Some(19.90));
+        assert_eq!(detail.seller_name.as_deref(), Some("Loja Oficial"));
+        assert_eq!(detail.seller_rating, Some(4.8));
+
+        let mut product = parser
+            .parse_product_json(&json!({ "id": "400", "price": 89.9, "shippingFee": 12.0 }))
+            .unwrap();
+        TikTokParser::apply_detail(&mut product, detail);
+        assert_eq!(product.shipping_fee, Some(0.0));
+        assert!(product.has_free_shipping);
+        assert_eq!(product.delivery_days_min, Some(5));
+        assert_eq!(product.delivery_days_max, Some(9));
+        assert_eq!(product.min_price, Some(89.90));
+        assert_eq!(product.seller_name.as_deref(), Some("Loja Oficial"));
+    }
+
+    #[test]
+    fn test_parse_product_detail_keeps_missing_fields() {
+        let parser = TikTokParser::default();
+        let detail = parser.parse_product_detail(
+            &json!({ "description": "<p>Garrafa <b>térmica</b></p>", "shippingFee": "R$ 7,50" }),
+            NumberLocale::Br,
+        );
+        assert_eq!(detail.description.as_deref(), Some("Garrafa térmica"));
+        assert_eq!(detail.shipping_options.len(), 1);
+        assert_eq!(detail.shipping_options[0].fee, Some(7.50));
+        assert!(detail.seller_name.is_none());
+
+        let mut product = parser
+            .parse_product_json(&json!({ "id": "401", "price": 30.0, "seller": { "name": "Casa" } }))
+            .unwrap();
+        TikTokParser::apply_detail(&mut product, detail);
+        assert_eq!(product.seller_name.as_deref(), Some("Casa"));
+        assert_eq!(product.shipping_fee, Some(7.50));
+        assert!(!product.has_free_shipping);
+    }
+
+    #[test]
+    fn test_parse_delivery_text() {
+        let cases = [
+            ("3-7 dias úteis", (Some(3), Some(7))),
+            ("Chega em 5 a 10 dias", (Some(5), Some(10))),
+            ("Chega em até 5 dias", (None, Some(5))),
+            ("2 days", (Some(2), Some(2))),
+            ("Delivery in 7-3 days", (Some(3), Some(7))),
+            ("Chega amanhã", (Some(1), Some(1))),
+            ("Frete grátis", (None, None)),
+            ("Receba até 12/12", (None, None)),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(TikTokParser::parse_delivery_text(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_api_responses() {
+        let parser = TikTokParser::default();
+        let responses = vec![
+            CapturedResponse {
+                url: "https://shop.tiktok.com/api/v1/track".to_string(),
+                body: "not json".to_string(),
+            },
+            CapturedResponse {
+                url: "https://shop.tiktok.com/api/v1/search/products?keyword=fone".to_string(),
+                body: json!({
+                    "code": 0,
+                    "data": {
+                        "products": [
+                            {
+                                "product_id": 1729384756102938475u64,
+                                "title": "Fone Bluetooth",
+                                "product_price_info": {
+                                    "sale_price_decimal": "89,90",
+                                    "origin_price_decimal": "129,90",
+                                    "currency_name": "BRL"
+                                },
+                                "sold_info": { "sold_count": "1,2 mil" },
+                                "rate_info": { "score": 4.7, "review_count": 310 },
+                                "image": { "url_list": ["https://img/1.jpg"] },
+                                "seller_info": { "shop_name": "Loja X" }
+                            },
+                            { "title": "sem id" }
+                        ]
+                    }
+                })
+                .to_string(),
+            },
+        ];
+
+        let parsed = parser.parse_api_responses(&responses);
+        assert_eq!(parsed.source, "api:/api/v1/search/products");
+        assert_eq!(parsed.products.len(), 1);
+
+        let product = &parsed.products[0];
+        assert_eq!(product.tiktok_id, "1729384756102938475");
+        assert_eq!(product.price, 89.90);
+        assert_eq!(product.original_price, Some(129.90));
+        assert!(product.is_on_sale);
+        assert_eq!(product.sales_count, 1200);
+        assert_eq!(product.reviews_count, 310);
+        assert_eq!(product.image_url.as_deref(), Some("https://img/1.jpg"));
+        assert_eq!(product.seller_name.as_deref(), Some("Loja X"));
+
+        assert_eq!(parser.parse_api_responses(&[]).source, "none");
+    }
+
+    #[test]
+    fn test_json_ld_fallback() {
+        let parser = TikTokParser::default();
+        let html = r#"
+            <html><head><script type="application/ld+json">
+            {
+                "@context": "https://schema.org",
+                "@type": "Product",
+                "name": "Luminária LED",
+                "url": "https://shop.tiktok.com/product/1731234567890",
+                "image": "https://img/led.jpg",
+                "offers": { "@type": "Offer", "price": 45.5, "priceCurrency": "BRL",
+                            "availability": "https://schema.org/OutOfStock" }
+            }
+            </script></head><body><div class="unknown-layout"></div></body></html>
+        "#;
+
+        let parsed = parser.parse_html(html);
+        assert_eq!(parsed.source, "json-ld");
+        assert_eq!(parsed.products.len(), 1);
+
+        let product = &parsed.products[0];
+        assert_eq!(product.tiktok_id, "1731234567890");
+        assert_eq!(product.title, "Luminária LED");
+        assert_eq!(product.price, 45.5);
+        assert_eq!(product.image_url.as_deref(), Some("https://img/led.jpg"));
+        assert!(!product.in_stock);
+
+        assert_eq!(parser.parse_html("<html><body></body></html>").source, "none");
+    }
+
+    mod fuzz {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn any_locale() -> impl Strategy<Value = NumberLocale> {
+            prop_oneof![
+                Just(NumberLocale::Br),
+                Just(NumberLocale::Us),
+                Just(NumberLocale::Eu),
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn price_text_never_panics(text in "\\PC*", locale in any_locale()) {
+                let price = TikTokParser::parse_price_text(&text, locale);
+                prop_assert!(price.is_finite() && price >= 0.0);
+            }
+
+            #[test]
+            fn price_text_survives_huge_numbers(digits in "[0-9]{300,400}", locale in any_locale()) {
+                let price = TikTokParser::parse_price_text(&digits, locale);
+                prop_assert!(price.is_finite());
+            }
+
+            #[test]
+            fn br_price_roundtrip(reais in 0u64..10_000_000, cents in 0u64..100) {
+                // Format with thousands dots, as the storefront does: R$ 1.234.567,89
+                let digits = reais.to_string();
+                let mut grouped = String::new();
+                for (i, c) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        grouped.push('.');
+                    }
+                    grouped.push(c);
+                }
+                let text = format!("R$ {},{:02}", grouped, cents);
+                let expected = reais as f64 + cents as f64 / 100.0;
+                let price = TikTokParser::parse_price_text(&text, NumberLocale::Br);
+                prop_assert!((price - expected).abs() < 0.001, "{} -> {}", text, price);
+            }
+
+            #[test]
+            fn sales_text_never_panics(text in "\\PC*") {
+                prop_assert!(TikTokParser::parse_sales_text(&text) >= 0);
+            }
+
+            #[test]
+            fn sales_text_mixed_separators(
+                number in "[0-9]{1,12}([.,][0-9]{1,6}){0,4}",
+                suffix in prop_oneof![Just(""), Just("k"), Just(" mil"), Just("m"), Just(" milhões"), Just("b")],
+            ) {
+                let text = format!("{}{} vendidos", number, suffix);
+                prop_assert!(TikTokParser::parse_sales_text(&text) >= 0);
+            }
+
+            #[test]
+            fn product_json_never_panics(
+                id in prop_oneof![any::<i64>().prop_map(|n| json!(n)), "\\PC{0,20}".prop_map(|s| json!(s))],
+                title in "\\PC{0,80}",
+                price in prop_oneof![
+                    any::<f64>().prop_filter("json numbers are finite", |v| v.is_finite()).prop_map(|v| json!(v)),
+                    "\\PC{0,30}".prop_map(|s| json!(s)),
+                ],
+                sold in "\\PC{0,30}",
+            ) {
+                let parser = TikTokParser::default();
+                let data = json!({ "id": id, "title": title, "price": price, "sold_count": sold });
+                let _ = parser.parse_product_json(&data);
+            }
+        }
+    }
 }
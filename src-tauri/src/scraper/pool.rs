@@ -1,45 +1,206 @@
+use crate::models::BrowserPoolStats;
 use crate::scraper::browser::BrowserManager;
 use anyhow::Result;
-use std::sync::Arc;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::task::JoinHandle;
+
+/// Browsers unused for this long are shut down by `shutdown_idle`
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+struct IdleBrowser {
+    browser: Arc<BrowserManager>,
+    since: Instant,
+}
 
 #[allow(dead_code)]
 pub struct BrowserPool {
-    browsers: Vec<Arc<BrowserManager>>,
+    permits: Semaphore,
+    idle: Mutex<Vec<IdleBrowser>>,
     max_browsers: usize,
     headless: bool,
+    idle_timeout: Duration,
+    busy: AtomicUsize,
+    waiting: AtomicUsize,
+    checkouts: AtomicUsize,
+    idle_shutdowns: AtomicUsize,
 }
 
 #[allow(dead_code)]
 impl BrowserPool {
     pub fn new(max_browsers: usize, headless: bool) -> Self {
+        let max_browsers = max_browsers.max(1);
         Self {
-            browsers: Vec::new(),
+            permits: Semaphore::new(max_browsers),
+            idle: Mutex::new(Vec::new()),
             max_browsers,
             headless,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            busy: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
+            checkouts: AtomicUsize::new(0),
+            idle_shutdowns: AtomicUsize::new(0),
         }
     }
 
-    pub async fn get_browser(&mut self) -> Result<Arc<BrowserManager>> {
-        // Simple round-robin or just create new if not full
-        // For now, just create a new one if we haven't reached max
-        if self.browsers.len() < self.max_browsers {
-            let manager = BrowserManager::new(self.headless);
-            manager.start(None).await?;
-            let manager = Arc::new(manager);
-            self.browsers.push(manager.clone());
-            return Ok(manager);
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Wait for a free browser: reuse an idle one, or launch a new one while
+    /// the pool is below `max_browsers`. The browser is checked back in when
+    /// the lease is dropped.
+    pub async fn checkout(&self) -> Result<BrowserLease<'_>> {
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let permit = self.permits.acquire().await;
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        let permit = permit?;
+
+        // Most recently used first, so older browsers can reach the idle timeout
+        let reused = self.idle.lock().unwrap().pop();
+        let browser = match reused {
+            Some(idle) => idle.browser,
+            None => {
+                let manager = BrowserManager::new(self.headless);
+                manager.start(None).await?;
+                Arc::new(manager)
+            }
+        };
+
+        self.busy.fetch_add(1, Ordering::Relaxed);
+        self.checkouts.fetch_add(1, Ordering::Relaxed);
+        Ok(BrowserLease {
+            pool: self,
+            browser,
+            _permit: permit,
+        })
+    }
+
+    /// Stop browsers that stayed idle longer than the idle timeout.
+    /// Returns how many were shut down.
+    pub async fn shutdown_idle(&self) -> usize {
+        let expired = self.take_expired(Instant::now());
+        let count = expired.len();
+
+        for browser in expired {
+            if let Err(e) = browser.stop().await {
+                log::warn!("Failed to stop idle browser: {}", e);
+            }
         }
 
-        // Return a random existing browser
-        // In a real pool, we would track availability
-        Ok(self.browsers[0].clone())
+        if count > 0 {
+            log::info!("Closed {} idle browser(s)", count);
+            self.idle_shutdowns.fetch_add(count, Ordering::Relaxed);
+        }
+        count
+    }
+
+    /// Run `shutdown_idle` periodically until the returned task is aborted
+    pub fn spawn_idle_reaper(self: &Arc<Self>) -> JoinHandle<()> {
+        let pool = Arc::clone(self);
+        let interval = (self.idle_timeout / 2).max(Duration::from_secs(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                pool.shutdown_idle().await;
+            }
+        })
+    }
+
+    fn take_expired(&self, now: Instant) -> Vec<Arc<BrowserManager>> {
+        let mut idle = self.idle.lock().unwrap();
+        let (expired, kept): (Vec<_>, Vec<_>) = idle
+            .drain(..)
+            .partition(|entry| now.duration_since(entry.since) >= self.idle_timeout);
+        *idle = kept;
+        expired.into_iter().map(|entry| entry.browser).collect()
+    }
+
+    pub fn stats(&self) -> BrowserPoolStats {
+        let busy = self.busy.load(Ordering::Relaxed) as u32;
+        let idle = self.idle.lock().unwrap().len() as u32;
+        BrowserPoolStats {
+            max_browsers: self.max_browsers as u32,
+            open: busy + idle,
+            busy,
+            idle,
+            waiting: self.waiting.load(Ordering::Relaxed) as u32,
+            checkouts: self.checkouts.load(Ordering::Relaxed) as u32,
+            idle_shutdowns: self.idle_shutdowns.load(Ordering::Relaxed) as u32,
+        }
     }
 
-    pub async fn shutdown(&mut self) -> Result<()> {
-        for browser in &self.browsers {
-            browser.stop().await?;
+    /// Stop every idle browser. Browsers still checked out are stopped by
+    /// their holders.
+    pub async fn shutdown(&self) -> Result<()> {
+        let idle: Vec<IdleBrowser> = self.idle.lock().unwrap().drain(..).collect();
+        for entry in idle {
+            entry.browser.stop().await?;
         }
-        self.browsers.clear();
         Ok(())
     }
+
+    fn checkin(&self, browser: Arc<BrowserManager>) {
+        self.busy.fetch_sub(1, Ordering::Relaxed);
+        self.idle.lock().unwrap().push(IdleBrowser {
+            browser,
+            since: Instant::now(),
+        });
+    }
+}
+
+/// A checked-out browser; goes back to the pool when dropped
+pub struct BrowserLease<'a> {
+    pool: &'a BrowserPool,
+    browser: Arc<BrowserManager>,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl Deref for BrowserLease<'_> {
+    type Target = BrowserManager;
+
+    fn deref(&self) -> &BrowserManager {
+        &self.browser
+    }
+}
+
+impl Drop for BrowserLease<'_> {
+    fn drop(&mut self) {
+        self.pool.checkin(Arc::clone(&self.browser));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_browsers_expire() {
+        let pool = BrowserPool::new(2, true).with_idle_timeout(Duration::from_secs(60));
+        let now = Instant::now();
+        {
+            let mut idle = pool.idle.lock().unwrap();
+            idle.push(IdleBrowser {
+                browser: Arc::new(BrowserManager::new(true)),
+                since: now - Duration::from_secs(120),
+            });
+            idle.push(IdleBrowser {
+                browser: Arc::new(BrowserManager::new(true)),
+                since: now,
+            });
+        }
+
+        assert_eq!(pool.take_expired(now).len(), 1);
+
+        let stats = pool.stats();
+        assert_eq!(stats.max_browsers, 2);
+        assert_eq!(stats.idle, 1);
+        assert_eq!(stats.open, 1);
+        assert_eq!(stats.busy, 0);
+    }
 }
@@ -0,0 +1,232 @@
+// Progress Module
+// Stage-aware progress of a run: startup, the categories (weighted by their
+// share of the product target), detail enrichment and persistence, with an
+// ETA from the pace of the most recent pages
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::models::{CategoryProgress, ScrapeProgress, ScrapeStage};
+
+/// Share of the overall progress of each stage
+const STARTUP_WEIGHT: f32 = 0.05;
+const CATEGORIES_WEIGHT: f32 = 0.75;
+const ENRICHMENT_WEIGHT: f32 = 0.15;
+const PERSISTENCE_WEIGHT: f32 = 0.05;
+
+/// Pages the ETA is averaged over
+const ROLLING_PAGES: usize = 10;
+
+pub struct ProgressTracker {
+    stage: ScrapeStage,
+    categories: Vec<CategoryProgress>,
+    enrich: bool,
+    enrich_done: u32,
+    enrich_total: u32,
+    /// Duration of the most recent pages
+    page_times: VecDeque<Duration>,
+    /// (time, overall progress) after each of the most recent pages
+    samples: VecDeque<(Instant, f32)>,
+    pages_done: u32,
+}
+
+impl ProgressTracker {
+    /// `max_products` is split evenly between the categories as their target
+    pub fn new(categories: &[String], max_products: u32, enrich: bool) -> Self {
+        let target = max_products.div_ceil(categories.len().max(1) as u32).max(1);
+        Self {
+            stage: ScrapeStage::Startup,
+            categories: categories
+                .iter()
+                .map(|category| CategoryProgress {
+                    category: category.clone(),
+                    products: 0,
+                    target,
+                    done: false,
+                })
+                .collect(),
+            enrich,
+            enrich_done: 0,
+            enrich_total: 0,
+            page_times: VecDeque::new(),
+            samples: VecDeque::new(),
+            pages_done: 0,
+        }
+    }
+
+    pub fn set_stage(&mut self, stage: ScrapeStage) {
+        self.stage = stage;
+    }
+
+    /// A page of `category` was parsed in `elapsed`, adding `new_products`
+    pub fn page_done(
+        &mut self,
+        category: &str,
+        new_products: u32,
+        elapsed: Duration,
+        now: Instant,
+    ) {
+        if let Some(progress) = self.categories.iter_mut().find(|c| c.category == category) {
+            progress.products += new_products;
+        }
+        self.pages_done += 1;
+        self.page_times.push_back(elapsed);
+        if self.page_times.len() > ROLLING_PAGES {
+            self.page_times.pop_front();
+        }
+        self.record_sample(now);
+    }
+
+    /// The category ran out of pages, hit the limit or was abandoned
+    pub fn category_done(&mut self, category: &str) {
+        if let Some(progress) = self.categories.iter_mut().find(|c| c.category == category) {
+            progress.done = true;
+        }
+    }
+
+    pub fn enrichment(&mut self, done: u32, total: u32, now: Instant) {
+        self.stage = ScrapeStage::Enrichment;
+        self.enrich_done = done;
+        self.enrich_total = total;
+        self.record_sample(now);
+    }
+
+    fn record_sample(&mut self, now: Instant) {
+        let overall = self.overall();
+        self.samples.push_back((now, overall));
+        if self.samples.len() > ROLLING_PAGES + 1 {
+            self.samples.pop_front();
+        }
+    }
+
+    fn categories_fraction(&self) -> f32 {
+        if self.categories.is_empty() {
+            return 0.0;
+        }
+        self.categories
+            .iter()
+            .map(|c| {
+                if c.done {
+                    1.0
+                } else {
+                    (c.products as f32 / c.target as f32).min(1.0)
+                }
+            })
+            .sum::<f32>()
+            / self.categories.len() as f32
+    }
+
+    /// Fraction of the current stage that is done (0-1)
+    fn stage_fraction(&self) -> f32 {
+        match self.stage {
+            ScrapeStage::Startup => 0.0,
+            ScrapeStage::Categories => self.categories_fraction(),
+            ScrapeStage::Enrichment if self.enrich_total > 0 => {
+                self.enrich_done as f32 / self.enrich_total as f32
+            }
+            ScrapeStage::Enrichment | ScrapeStage::Persistence => 0.0,
+            ScrapeStage::Done => 1.0,
+        }
+    }
+
+    /// Overall progress in percent
+    pub fn overall(&self) -> f32 {
+        // Without enrichment its share goes to the categories
+        let (categories_weight, enrichment_weight) = if self.enrich {
+            (CATEGORIES_WEIGHT, ENRICHMENT_WEIGHT)
+        } else {
+            (CATEGORIES_WEIGHT + ENRICHMENT_WEIGHT, 0.0)
+        };
+
+        let fraction = match self.stage {
+            ScrapeStage::Startup => 0.0,
+            ScrapeStage::Categories => {
+                STARTUP_WEIGHT + categories_weight * self.categories_fraction()
+            }
+            ScrapeStage::Enrichment => {
+                STARTUP_WEIGHT + categories_weight + enrichment_weight * self.stage_fraction()
+            }
+            ScrapeStage::Persistence => 1.0 - PERSISTENCE_WEIGHT,
+            ScrapeStage::Done => 1.0,
+        };
+        (fraction * 100.0).min(100.0)
+    }
+
+    /// Seconds left at the pace of the recent pages; None until there are
+    /// two samples that show progress
+    fn eta_seconds(&self) -> Option<u64> {
+        if self.stage == ScrapeStage::Done {
+            return Some(0);
+        }
+        let (first, last) = (self.samples.front()?, self.samples.back()?);
+        let elapsed = last.0.duration_since(first.0).as_secs_f32();
+        let gained = last.1 - first.1;
+        if elapsed <= 0.0 || gained <= 0.0 {
+            return None;
+        }
+        Some(((100.0 - last.1) / (gained / elapsed)).round() as u64)
+    }
+
+    pub fn snapshot(&self) -> ScrapeProgress {
+        let avg_page_ms = (!self.page_times.is_empty()).then(|| {
+            (self.page_times.iter().sum::<Duration>() / self.page_times.len() as u32).as_millis()
+                as u64
+        });
+
+        ScrapeProgress {
+            stage: self.stage,
+            stage_progress: self.stage_fraction() * 100.0,
+            categories: self.categories.clone(),
+            pages_done: self.pages_done,
+            avg_page_ms,
+            eta_seconds: self.eta_seconds(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_weights_and_eta() {
+        let categories = vec!["fone".to_string(), "relogio".to_string()];
+        let mut tracker = ProgressTracker::new(&categories, 100, false);
+        assert_eq!(tracker.overall(), 0.0);
+        assert_eq!(tracker.snapshot().categories[0].target, 50);
+
+        tracker.set_stage(ScrapeStage::Categories);
+        let start = Instant::now();
+        tracker.page_done("fone", 25, Duration::from_secs(2), start);
+        // Half of one of two categories done: 5% + 90% * 25%
+        assert!((tracker.overall() - 27.5).abs() < 0.01);
+        assert_eq!(tracker.snapshot().eta_seconds, None);
+
+        tracker.page_done(
+            "fone",
+            25,
+            Duration::from_secs(4),
+            start + Duration::from_secs(10),
+        );
+        // 22.5 points in 10s leaves 50 points for ~22s
+        let snapshot = tracker.snapshot();
+        assert_eq!(snapshot.eta_seconds, Some(22));
+        assert_eq!(snapshot.avg_page_ms, Some(3000));
+
+        tracker.category_done("relogio");
+        assert!((tracker.overall() - 95.0).abs() < 0.01);
+
+        tracker.set_stage(ScrapeStage::Done);
+        assert_eq!(tracker.overall(), 100.0);
+        assert_eq!(tracker.snapshot().eta_seconds, Some(0));
+    }
+
+    #[test]
+    fn test_enrichment_stage() {
+        let mut tracker = ProgressTracker::new(&["trending".to_string()], 10, true);
+        tracker.enrichment(5, 10, Instant::now());
+        // 5% + 75% + 15% * 50%
+        assert!((tracker.overall() - 87.5).abs() < 0.01);
+        assert_eq!(tracker.snapshot().stage_progress, 50.0);
+    }
+}
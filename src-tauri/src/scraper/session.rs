@@ -0,0 +1,124 @@
+// Resumable Sessions
+// Category scrapes checkpoint their progress in the database as they go: how
+// far each category got and the products collected so far. A run cut short by
+// a stop, a crash or the safety switch can be resumed later as a new run that
+// skips the finished categories, picks the others up where they were and
+// doesn't collect the same products again.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::ScraperConfig;
+use crate::database;
+use crate::models::{CategoryCheckpoint, Product};
+
+pub struct SessionLog {
+    db_path: PathBuf,
+    id: String,
+    checkpoints: Mutex<Vec<CategoryCheckpoint>>,
+}
+
+impl SessionLog {
+    /// Session `id` with its checkpoints, and the products it collected
+    pub fn open(db_path: &Path, id: &str) -> Result<(Self, Vec<Product>)> {
+        let checkpoints = database::get_scrape_session(db_path, id)?
+            .map(|session| session.checkpoints)
+            .unwrap_or_default();
+        let products = database::get_scrape_session_products(db_path, id)?;
+
+        let log = Self {
+            db_path: db_path.to_path_buf(),
+            id: id.to_string(),
+            checkpoints: Mutex::new(checkpoints),
+        };
+        Ok((log, products))
+    }
+
+    /// Session the run checkpoints to, if it has one, and the products
+    /// collected before
+    pub fn for_run(config: &ScraperConfig) -> (Option<Self>, Vec<Product>) {
+        let (Some(db_path), Some(id)) = (&config.db_path, &config.session_id) else {
+            return (None, Vec::new());
+        };
+        match Self::open(Path::new(db_path), id) {
+            Ok((session, products)) => (Some(session), products),
+            Err(e) => {
+                log::warn!("Failed to open scrape session {}: {}", id, e);
+                (None, Vec::new())
+            }
+        }
+    }
+
+    fn checkpoints(&self) -> std::sync::MutexGuard<'_, Vec<CategoryCheckpoint>> {
+        self.checkpoints.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Where `category` was left, if the session got to it
+    pub fn checkpoint(&self, category: &str) -> Option<CategoryCheckpoint> {
+        self.checkpoints()
+            .iter()
+            .find(|checkpoint| checkpoint.category == category)
+            .cloned()
+    }
+
+    pub fn is_done(&self, category: &str) -> bool {
+        self.checkpoint(category)
+            .is_some_and(|checkpoint| checkpoint.done)
+    }
+
+    /// Whether every one of `categories` was finished
+    pub fn all_done(&self, categories: &[String]) -> bool {
+        categories.iter().all(|category| self.is_done(category))
+    }
+
+    pub fn record_products(&self, products: &[Product]) {
+        if products.is_empty() {
+            return;
+        }
+        if let Err(e) = database::add_scrape_session_products(&self.db_path, &self.id, products) {
+            log::warn!(
+                "Failed to checkpoint products of session {}: {}",
+                self.id,
+                e
+            );
+        }
+    }
+
+    pub fn record_checkpoint(&self, checkpoint: CategoryCheckpoint) {
+        let mut checkpoints = self.checkpoints();
+        match checkpoints
+            .iter_mut()
+            .find(|existing| existing.category == checkpoint.category)
+        {
+            Some(existing) => *existing = checkpoint,
+            None => checkpoints.push(checkpoint),
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        if let Err(e) =
+            database::save_scrape_session_checkpoints(&self.db_path, &self.id, &checkpoints, &now)
+        {
+            log::warn!("Failed to checkpoint session {}: {}", self.id, e);
+        }
+    }
+
+    /// Close the session: interrupted for `reason`, or deleted once completed
+    /// since the run saves its products
+    pub fn finish(&self, interrupted: Option<&str>) {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = match interrupted {
+            Some(reason) => database::set_scrape_session_status(
+                &self.db_path,
+                &self.id,
+                "interrupted",
+                Some(reason),
+                &now,
+            ),
+            None => database::delete_scrape_session(&self.db_path, &self.id).map(|_| ()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to close session {}: {}", self.id, e);
+        }
+    }
+}
@@ -0,0 +1,547 @@
+// Shopee Scraper
+// Shopee storefront (shopee.com.br or the one of the configured region) for
+// the same categories as TikTok Shop runs: search keywords, "trending" (the
+// daily discover feed) or listing URLs, page by page. Products are read
+// from the search API responses captured while a page loads, or from the
+// product cards of the rendered listing when none were captured.
+
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+use super::intercept::CapturedResponse;
+use super::pagination;
+use super::parser::{NumberLocale, TikTokParser};
+use super::storefront::{Storefront, StorefrontScraper};
+use super::wait::WaitCondition;
+use crate::config::ScraperConfig;
+use crate::models::{MarketplaceAccess, Product, ProductSource};
+
+/// (region code, storefront host, currency)
+const REGIONS: &[(&str, &str, &str)] = &[
+    ("BR", "shopee.com.br", "BRL"),
+    ("MX", "shopee.com.mx", "MXN"),
+    ("CO", "shopee.com.co", "COP"),
+    ("CL", "shopee.cl", "CLP"),
+    ("SG", "shopee.sg", "SGD"),
+    ("MY", "shopee.com.my", "MYR"),
+    ("TH", "shopee.co.th", "THB"),
+    ("PH", "shopee.ph", "PHP"),
+    ("ID", "shopee.co.id", "IDR"),
+    ("VN", "shopee.vn", "VND"),
+    ("TW", "shopee.tw", "TWD"),
+];
+
+/// API prices are integers in 1/100000 of the currency unit
+const PRICE_SCALE: f64 = 100_000.0;
+
+/// Links of the product cards of a listing
+const CARD_SELECTOR: &str = "a[href*='-i.']";
+
+/// Product id in card links: `/<slug>-i.<shop id>.<item id>`
+static CARD_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"-i\.(\d+)\.(\d+)").expect("valid regex"));
+static CARD_PRICE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:R\$|RM|S\$|Rp|₱|฿|₫|\$)\s*(\d[\d.,]*)").expect("valid regex"));
+static CARD_SOLD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)(\d[\d.,]*\s*(?:mil|k)?)\+?\s*(?:vendidos?|sold)").expect("valid regex")
+});
+
+/// URLs of the Shopee storefront of a region
+#[derive(Debug, Clone)]
+pub struct ShopeeUrls {
+    region: String,
+    host: &'static str,
+    currency: &'static str,
+}
+
+impl ShopeeUrls {
+    /// Storefront of a region code (ISO 3166, e.g. "BR"); unknown regions
+    /// use the Brazilian one
+    pub fn for_region(region: &str) -> Self {
+        let code = region.trim().to_uppercase();
+        let &(code, host, currency) = REGIONS
+            .iter()
+            .find(|(c, _, _)| *c == code)
+            .unwrap_or(&REGIONS[0]);
+        Self {
+            region: code.to_lowercase(),
+            host,
+            currency,
+        }
+    }
+
+    pub fn base(&self) -> String {
+        format!("https://{}", self.host)
+    }
+
+    pub fn currency(&self) -> &'static str {
+        self.currency
+    }
+
+    /// Page `page` (from 0) of a category: a keyword search, the daily
+    /// discover feed for "trending", or a listing URL
+    pub fn category_page(&self, category: &str, page: u32) -> String {
+        let category = category.trim();
+        let url = if category.eq_ignore_ascii_case("trending") {
+            format!("{}/daily_discover", self.base())
+        } else if category.starts_with("http") || category.starts_with("file") {
+            category.to_string()
+        } else {
+            reqwest::Url::parse_with_params(
+                &format!("{}/search", self.base()),
+                &[("keyword", category)],
+            )
+            .map(String::from)
+            .unwrap_or_else(|_| format!("{}/search?keyword={}", self.base(), category))
+        };
+
+        if page == 0 {
+            url
+        } else {
+            pagination::page_url(&url, "page", page)
+        }
+    }
+
+    pub fn product_url(&self, shop_id: u64, item_id: u64) -> String {
+        format!("{}/product/{}/{}", self.base(), shop_id, item_id)
+    }
+
+    /// Image of a file hash on the region's CDN; full URLs are kept
+    pub fn image_url(&self, image: &str) -> String {
+        if image.starts_with("http") {
+            image.to_string()
+        } else {
+            format!(
+                "https://down-{}.img.susercontent.com/file/{}",
+                self.region, image
+            )
+        }
+    }
+}
+
+/// Reads Shopee products from API responses and listing pages
+pub struct ShopeeParser {
+    urls: ShopeeUrls,
+    locale: NumberLocale,
+}
+
+impl ShopeeParser {
+    pub fn new(urls: ShopeeUrls, locale: NumberLocale) -> Self {
+        Self { urls, locale }
+    }
+
+    /// Products of the captured responses, in order; other JSON is skipped
+    pub fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        responses
+            .iter()
+            .filter_map(|response| serde_json::from_str::<Value>(&response.body).ok())
+            .flat_map(|data| self.parse_search_items(&data))
+            .collect()
+    }
+
+    /// Products of a search or recommendation response. Items are found
+    /// wherever they are nested: `items[].item_basic` for searches, bare
+    /// item objects in the feeds.
+    pub fn parse_search_items(&self, data: &Value) -> Vec<Product> {
+        let mut items = Vec::new();
+        collect_items(data, &mut items);
+        items
+            .into_iter()
+            .filter_map(|item| self.parse_item(item))
+            .collect()
+    }
+
+    fn parse_item(&self, item: &Value) -> Option<Product> {
+        let item_id = id(&item["itemid"])?;
+        let shop_id = id(&item["shopid"])?;
+        let title = item["name"]
+            .as_str()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())?;
+
+        let mut product = self.product(shop_id, item_id, title);
+        let scaled = |key: &str| {
+            number(&item[key])
+                .map(|n| n / PRICE_SCALE)
+                .filter(|n| *n > 0.0)
+        };
+
+        product.price = scaled("price")
+            .or_else(|| scaled("price_min"))
+            .unwrap_or(0.0);
+        product.original_price = scaled("price_before_discount").filter(|op| *op > product.price);
+        product.is_on_sale = product.original_price.is_some();
+        let (min, max) = (scaled("price_min"), scaled("price_max"));
+        if min != max {
+            product.min_price = min;
+            product.max_price = max;
+        }
+        if let Some(currency) = item["currency"].as_str() {
+            product.currency = currency.to_string();
+        }
+
+        product.sales_count = number(&item["historical_sold"])
+            .or_else(|| number(&item["sold"]))
+            .unwrap_or(0.0) as i32;
+        product.product_rating = number(&item["item_rating"]["rating_star"])
+            .filter(|rating| *rating > 0.0 && *rating <= 5.0);
+        product.reviews_count = number(&item["item_rating"]["rating_count"][0])
+            .or_else(|| number(&item["cmt_count"]))
+            .unwrap_or(0.0) as i32;
+        product.stock_level = number(&item["stock"]).map(|stock| stock as i32);
+        product.in_stock = product.stock_level.is_none_or(|stock| stock > 0);
+        product.has_free_shipping = item["show_free_shipping"].as_bool().unwrap_or(false);
+        product.seller_name = item["shop_name"].as_str().map(str::to_string);
+
+        product.images = item["images"]
+            .as_array()
+            .map(|images| {
+                images
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|image| self.urls.image_url(image))
+                    .collect()
+            })
+            .unwrap_or_default();
+        product.image_url = item["image"]
+            .as_str()
+            .map(|image| self.urls.image_url(image))
+            .or_else(|| product.images.first().cloned());
+
+        Some(product)
+    }
+
+    /// Products of the cards of a rendered listing page
+    pub fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        let document = Html::parse_document(html);
+        let (Ok(cards), Ok(images)) = (Selector::parse(CARD_SELECTOR), Selector::parse("img"))
+        else {
+            return Vec::new();
+        };
+
+        let mut products: Vec<Product> = Vec::new();
+        for card in document.select(&cards) {
+            let Some(href) = card.value().attr("href") else {
+                continue;
+            };
+            let Some(ids) = CARD_ID_RE.captures(href) else {
+                continue;
+            };
+            let (Ok(shop_id), Ok(item_id)) = (ids[1].parse(), ids[2].parse()) else {
+                continue;
+            };
+
+            let text = card
+                .text()
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let image = card.select(&images).next();
+            let title = image
+                .and_then(|img| img.value().attr("alt"))
+                .map(str::trim)
+                .filter(|alt| !alt.is_empty())
+                .or_else(|| card.text().map(str::trim).find(|t| t.len() > 10));
+            let Some(title) = title else {
+                continue;
+            };
+
+            let mut product = self.product(shop_id, item_id, title);
+            let mut prices = CARD_PRICE_RE
+                .captures_iter(&text)
+                .map(|m| TikTokParser::parse_price_text(&m[1], self.locale))
+                .filter(|price| *price > 0.0);
+            product.price = prices.next().unwrap_or(0.0);
+            // A second, higher price is the one before the discount
+            product.original_price = prices.next().filter(|op| *op > product.price);
+            product.is_on_sale = product.original_price.is_some();
+            product.sales_count = CARD_SOLD_RE
+                .captures(&text)
+                .map(|m| TikTokParser::parse_sales_text(&m[1]))
+                .unwrap_or(0);
+            product.image_url = image
+                .and_then(|img| img.value().attr("src"))
+                .filter(|src| src.starts_with("http"))
+                .map(str::to_string);
+            product.images = product.image_url.iter().cloned().collect();
+
+            if !products.iter().any(|p| p.tiktok_id == product.tiktok_id) {
+                products.push(product);
+            }
+        }
+        products
+    }
+
+    /// Product with its ids and title; everything else still to be read
+    fn product(&self, shop_id: u64, item_id: u64, title: &str) -> Product {
+        let now = chrono::Utc::now().to_rfc3339();
+        Product {
+            id: Uuid::new_v4().to_string(),
+            tiktok_id: format!("shopee-{}-{}", shop_id, item_id),
+            title: title.to_string(),
+            description: None,
+            price: 0.0,
+            original_price: None,
+            currency: self.urls.currency().to_string(),
+            category: None,
+            subcategory: None,
+            seller_name: None,
+            seller_rating: None,
+            product_rating: None,
+            reviews_count: 0,
+            sales_count: 0,
+            sales_7d: 0,
+            sales_30d: 0,
+            commission_rate: None,
+            open_collaboration: None,
+            image_url: None,
+            images: vec![],
+            video_url: None,
+            product_url: self.urls.product_url(shop_id, item_id),
+            affiliate_url: None,
+            has_free_shipping: false,
+            is_trending: false,
+            is_on_sale: false,
+            in_stock: true,
+            stock_level: None,
+            min_price: None,
+            max_price: None,
+            variants: vec![],
+            shipping_fee: None,
+            delivery_days_min: None,
+            delivery_days_max: None,
+            demand_estimate: None,
+            collected_at: now.clone(),
+            updated_at: now,
+            raw_payload: None,
+            is_demo: false,
+            tags: vec![],
+            is_archived: false,
+            source: ProductSource::Scraper,
+            source_file: None,
+            marketplace: MarketplaceAccess::Shopee,
+        }
+    }
+}
+
+/// Item objects under `value`: the `item_basic` of search results, or any
+/// object with an item and a shop id
+fn collect_items<'a>(value: &'a Value, items: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(basic) = map.get("item_basic").filter(|basic| basic.is_object()) {
+                items.push(basic);
+            } else if map.contains_key("itemid") && map.contains_key("shopid") {
+                items.push(value);
+            } else {
+                map.values().for_each(|child| collect_items(child, items));
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|child| collect_items(child, items)),
+        _ => {}
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn id(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+/// Shopee sends blocked sessions to a traffic check or the login page
+fn is_blocked(path: &str, content: &str) -> bool {
+    path.starts_with("/verify") || path.starts_with("/buyer/login") || content.contains("captcha")
+}
+
+/// Shopee storefront of the configured region
+pub struct Shopee {
+    urls: ShopeeUrls,
+    parser: ShopeeParser,
+}
+
+impl Shopee {
+    pub fn new(config: &ScraperConfig) -> Self {
+        let urls = ShopeeUrls::for_region(&config.region);
+        Self {
+            parser: ShopeeParser::new(urls.clone(), NumberLocale::from_region(&config.region)),
+            urls,
+        }
+    }
+}
+
+impl Storefront for Shopee {
+    fn marketplace(&self) -> MarketplaceAccess {
+        MarketplaceAccess::Shopee
+    }
+
+    fn start_key(&self) -> &'static str {
+        "scraper.starting_shopee"
+    }
+
+    fn category_page(&self, category: &str, page: u32) -> String {
+        self.urls.category_page(category, page)
+    }
+
+    fn wait_conditions(&self) -> Vec<WaitCondition> {
+        vec![
+            WaitCondition::Selector {
+                selector: CARD_SELECTOR.to_string(),
+                timeout_ms: 10000,
+            },
+            WaitCondition::NetworkIdle {
+                idle_ms: 1000,
+                timeout_ms: 10000,
+            },
+        ]
+    }
+
+    fn is_blocked(&self, path: &str, content: &str) -> bool {
+        is_blocked(path, content)
+    }
+
+    fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product> {
+        self.parser.parse_api_responses(responses)
+    }
+
+    fn parse_listing_html(&self, html: &str) -> Vec<Product> {
+        self.parser.parse_listing_html(html)
+    }
+}
+
+pub type ShopeeScraper = StorefrontScraper<Shopee>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> ShopeeParser {
+        ShopeeParser::new(ShopeeUrls::for_region("BR"), NumberLocale::Br)
+    }
+
+    #[test]
+    fn test_category_urls() {
+        let urls = ShopeeUrls::for_region("br");
+        assert_eq!(
+            urls.category_page("fone bluetooth", 0),
+            "https://shopee.com.br/search?keyword=fone+bluetooth"
+        );
+        assert_eq!(
+            urls.category_page("fone bluetooth", 2),
+            "https://shopee.com.br/search?keyword=fone+bluetooth&page=2"
+        );
+        assert_eq!(
+            urls.category_page("trending", 1),
+            "https://shopee.com.br/daily_discover?page=1"
+        );
+        assert_eq!(
+            urls.category_page("https://shopee.com.br/Celulares-cat.11059988?page=0", 3),
+            "https://shopee.com.br/Celulares-cat.11059988?page=3"
+        );
+    }
+
+    #[test]
+    fn test_region_storefronts() {
+        let urls = ShopeeUrls::for_region("MX");
+        assert_eq!(
+            urls.product_url(12, 34),
+            "https://shopee.com.mx/product/12/34"
+        );
+        assert_eq!(urls.currency(), "MXN");
+        assert_eq!(
+            urls.image_url("br-11134207-abc"),
+            "https://down-mx.img.susercontent.com/file/br-11134207-abc"
+        );
+        // Unknown regions use the Brazilian storefront
+        assert_eq!(ShopeeUrls::for_region("US").base(), "https://shopee.com.br");
+    }
+
+    #[test]
+    fn test_parse_search_items() {
+        let data = serde_json::json!({
+            "items": [
+                {
+                    "itemid": 22,
+                    "shopid": 11,
+                    "item_basic": {
+                        "itemid": 22,
+                        "shopid": 11,
+                        "name": "Fone Bluetooth TWS",
+                        "price": 4990000,
+                        "price_min": 4990000,
+                        "price_max": 5990000,
+                        "price_before_discount": 9990000,
+                        "historical_sold": 1520,
+                        "stock": 0,
+                        "image": "hash1",
+                        "images": ["hash1", "hash2"],
+                        "show_free_shipping": true,
+                        "item_rating": { "rating_star": 4.8, "rating_count": [310, 1, 2, 3, 4, 300] }
+                    }
+                },
+                { "itemid": 23, "shopid": 11, "name": "" }
+            ]
+        });
+
+        let products = parser().parse_search_items(&data);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.tiktok_id, "shopee-11-22");
+        assert_eq!(product.price, 49.9);
+        assert_eq!(product.original_price, Some(99.9));
+        assert!(product.is_on_sale);
+        assert_eq!(product.max_price, Some(59.9));
+        assert_eq!(product.sales_count, 1520);
+        assert_eq!(product.reviews_count, 310);
+        assert_eq!(product.product_rating, Some(4.8));
+        assert!(!product.in_stock);
+        assert!(product.has_free_shipping);
+        assert_eq!(product.currency, "BRL");
+        assert_eq!(product.product_url, "https://shopee.com.br/product/11/22");
+        assert_eq!(
+            product.image_url.as_deref(),
+            Some("https://down-br.img.susercontent.com/file/hash1")
+        );
+        assert_eq!(product.images.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_listing_html() {
+        let html = r#"<html><body>
+            <a href="/Fone-Bluetooth-TWS-i.11.22?sp_atk=x">
+                <img alt="Fone Bluetooth TWS" src="https://down-br.img.susercontent.com/file/hash1">
+                <span>R$</span><span>49,90</span>
+                <span>R$ 99,90</span>
+                <div>1,2mil vendidos</div>
+            </a>
+            <a href="/Fone-Bluetooth-TWS-i.11.22">duplicate card</a>
+            <a href="/help">Ajuda</a>
+        </body></html>"#;
+
+        let products = parser().parse_listing_html(html);
+        assert_eq!(products.len(), 1);
+        let product = &products[0];
+        assert_eq!(product.tiktok_id, "shopee-11-22");
+        assert_eq!(product.title, "Fone Bluetooth TWS");
+        assert_eq!(product.price, 49.9);
+        assert_eq!(product.original_price, Some(99.9));
+        assert_eq!(product.sales_count, 1200);
+        assert_eq!(product.product_url, "https://shopee.com.br/product/11/22");
+    }
+
+    #[test]
+    fn test_blocked_pages() {
+        assert!(is_blocked("/verify/traffic", ""));
+        assert!(is_blocked("/buyer/login", ""));
+        assert!(!is_blocked("/search", "<html>fone</html>"));
+    }
+}
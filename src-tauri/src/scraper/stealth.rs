@@ -0,0 +1,215 @@
+// Stealth Check Module
+// Loads public bot-detection test pages in the scraper browser and reports
+// which automation signals leak
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::antibot::{AntiDetection, Fingerprint};
+use super::browser::BrowserManager;
+use super::driver::PageDriver;
+use crate::models::{StealthPage, StealthReport, StealthSignal};
+
+/// Bot-detection test pages, checked in order
+pub const TEST_PAGES: &[&str] = &[
+    "https://bot.sannysoft.com/",
+    "https://arh.antoinevastel.com/bots/areyouheadless",
+    "https://abrahamjuliot.github.io/creepjs/",
+];
+
+/// Time given to a test page to run its checks after loading
+const SETTLE_TIME: Duration = Duration::from_secs(6);
+
+/// Signals read directly from the page, independent of any test site
+const PROBE_SCRIPT: &str = r#"
+    (async () => {
+        const signals = [];
+        const add = (name, leaked, detail) => signals.push({ name, leaked: !!leaked, detail: String(detail ?? '') });
+
+        add('navigator.webdriver', navigator.webdriver === true, navigator.webdriver);
+        add('headless_user_agent', /HeadlessChrome/.test(navigator.userAgent), navigator.userAgent);
+
+        const brands = (navigator.userAgentData?.brands || []).map(b => `${b.brand}/${b.version}`);
+        add('headless_client_hints', brands.some(b => /Headless/i.test(b)), brands.join(', '));
+        const uaMajor = (navigator.userAgent.match(/Chrome\/(\d+)/) || [])[1];
+        const chMajor = (navigator.userAgentData?.brands || []).find(b => b.brand === 'Google Chrome')?.version;
+        add('client_hints_mismatch', uaMajor && chMajor && uaMajor !== chMajor, `${uaMajor} / ${chMajor}`);
+
+        add('plugins_empty', navigator.plugins.length === 0, navigator.plugins.length);
+        add('languages_empty', !navigator.languages?.length, (navigator.languages || []).join(','));
+        add('window_chrome_missing', !window.chrome, typeof window.chrome);
+        add('cdc_variables', Object.keys(window).some(k => k.startsWith('cdc_')), '');
+        add('zero_outer_size', window.outerWidth === 0 || window.outerHeight === 0, `${window.outerWidth}x${window.outerHeight}`);
+
+        try {
+            const status = await navigator.permissions.query({ name: 'notifications' });
+            add('permissions_inconsistent', Notification.permission === 'denied' && status.state === 'prompt',
+                `${Notification.permission} / ${status.state}`);
+        } catch (e) {}
+
+        let renderer = '';
+        try {
+            const gl = document.createElement('canvas').getContext('webgl');
+            const info = gl.getExtension('WEBGL_debug_renderer_info');
+            renderer = gl.getParameter(info.UNMASKED_RENDERER_WEBGL);
+        } catch (e) {}
+        add('software_webgl', /SwiftShader|llvmpipe/i.test(renderer), renderer);
+
+        return signals;
+    })()
+"#;
+
+/// What the test page itself flagged: failed rows of result tables and
+/// headless verdicts
+const PAGE_FLAGS_SCRIPT: &str = r#"
+    (() => {
+        const flags = [];
+        document.querySelectorAll('td.failed').forEach(cell => {
+            const row = cell.closest('tr');
+            const name = row?.cells[0]?.innerText.trim() || 'unknown';
+            flags.push({ name, leaked: true, detail: cell.innerText.trim().slice(0, 200) });
+        });
+
+        const text = document.body?.innerText || '';
+        if (/You are Chrome headless/i.test(text)) {
+            flags.push({ name: 'headless_verdict', leaked: true, detail: 'You are Chrome headless' });
+        }
+        const headless = text.match(/(\d+)% headless/i);
+        if (headless) {
+            flags.push({ name: 'headless_score', leaked: Number(headless[1]) > 0, detail: headless[0] });
+        }
+        return flags;
+    })()
+"#;
+
+#[derive(Debug, Deserialize)]
+struct RawSignal {
+    name: String,
+    leaked: bool,
+    #[serde(default)]
+    detail: String,
+}
+
+pub struct StealthCheck {
+    browser: BrowserManager,
+    antibot: AntiDetection,
+    fingerprint: Fingerprint,
+}
+
+impl StealthCheck {
+    /// `browser` should be set up like the scraper's (profile, launch flags),
+    /// so the check reflects what the scraper actually exposes
+    pub fn new(browser: BrowserManager, fingerprint: Fingerprint) -> Self {
+        Self {
+            browser,
+            antibot: AntiDetection::new(),
+            fingerprint,
+        }
+    }
+
+    pub async fn run(&self) -> Result<StealthReport> {
+        self.browser
+            .start(None)
+            .await
+            .context("Failed to start browser")?;
+
+        let result = self.check_pages().await;
+        self.browser.stop().await.ok();
+        result
+    }
+
+    async fn check_pages(&self) -> Result<StealthReport> {
+        let page = self.browser.new_page().await?;
+        self.antibot
+            .inject_stealth_scripts(&page, Some(&self.fingerprint))
+            .await?;
+
+        let mut pages = Vec::new();
+        let mut signals = Vec::new();
+
+        for url in TEST_PAGES {
+            if let Err(e) = PageDriver::goto(&page, url).await {
+                log::warn!("Stealth check could not load {}: {}", url, e);
+                pages.push(StealthPage {
+                    url: url.to_string(),
+                    loaded: false,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+            tokio::time::sleep(SETTLE_TIME).await;
+
+            // The probes only depend on the browser, one page is enough
+            if !signals.iter().any(|s: &StealthSignal| s.source == "probe") {
+                signals.extend(run_script(&page, PROBE_SCRIPT, "probe").await?);
+            }
+            signals.extend(run_script(&page, PAGE_FLAGS_SCRIPT, url).await?);
+
+            pages.push(StealthPage {
+                url: url.to_string(),
+                loaded: true,
+                error: None,
+            });
+        }
+
+        Ok(build_report(pages, signals))
+    }
+}
+
+async fn run_script<P: PageDriver>(
+    page: &P,
+    script: &str,
+    source: &str,
+) -> Result<Vec<StealthSignal>> {
+    let value = page.evaluate(script).await?;
+    let raw: Vec<RawSignal> = serde_json::from_value(value).unwrap_or_default();
+    Ok(raw
+        .into_iter()
+        .map(|s| StealthSignal {
+            name: s.name,
+            leaked: s.leaked,
+            detail: s.detail,
+            source: source.to_string(),
+        })
+        .collect())
+}
+
+fn build_report(pages: Vec<StealthPage>, mut signals: Vec<StealthSignal>) -> StealthReport {
+    // Leaks first, so the UI can show them at the top
+    signals.sort_by_key(|s| !s.leaked);
+    let leaks = signals.iter().filter(|s| s.leaked).count() as u32;
+
+    StealthReport {
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        pages,
+        signals,
+        leaks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_lists_leaks_first() {
+        let signal = |name: &str, leaked: bool| StealthSignal {
+            name: name.to_string(),
+            leaked,
+            detail: String::new(),
+            source: "probe".to_string(),
+        };
+        let report = build_report(
+            vec![],
+            vec![
+                signal("plugins_empty", false),
+                signal("navigator.webdriver", true),
+                signal("software_webgl", false),
+            ],
+        );
+
+        assert_eq!(report.leaks, 1);
+        assert_eq!(report.signals[0].name, "navigator.webdriver");
+    }
+}
@@ -0,0 +1,623 @@
+// Storefront Scraper
+// Runs the marketplaces other than TikTok Shop. A storefront only knows its
+// own URLs and how to read its products: from the API responses captured
+// while a listing loads, from the JSON state embedded in the page or from
+// the rendered product cards, in that order. The run itself (categories,
+// result pages, delays, the safety switch, resumable sessions, status and
+// events) is the same for every storefront and mirrors TikTok Shop runs.
+// Storefronts always run on Chromium; offline pages are only parsed for
+// TikTok Shop.
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use rand::Rng;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::Emitter;
+use tokio::sync::Mutex;
+
+use super::browser::BrowserManager;
+use super::driver::PageDriver;
+use super::i18n::{self, Language};
+use super::intercept::{CapturedResponse, NetworkCapture};
+use super::progress::ProgressTracker;
+use super::proxy::ProxyPool;
+use super::session::SessionLog;
+use super::wait::{self, WaitCondition};
+use super::{Scraper, TikTokScraper, MAX_LOG_ENTRIES};
+use crate::config::ScraperConfig;
+use crate::database;
+use crate::models::{
+    CategoryCheckpoint, CategorySummary, LogEntry, LogLevel, MarketplaceAccess, Product,
+    ScrapeStage, ScraperStatus,
+};
+
+/// Result pages read per category at most
+const MAX_PAGES: u32 = 10;
+
+/// URLs and parsing of one marketplace
+pub trait Storefront: Send + Sync {
+    /// Marketplace its products are tagged with
+    fn marketplace(&self) -> MarketplaceAccess;
+
+    /// Catalog key logged when a run starts
+    fn start_key(&self) -> &'static str;
+
+    /// Page `page` (from 0) of a category: a keyword search, the trending
+    /// listing or a listing URL
+    fn category_page(&self, category: &str, page: u32) -> String;
+
+    /// Readiness checks of a listing, used when the config lists none
+    fn wait_conditions(&self) -> Vec<WaitCondition>;
+
+    /// Whether the page is a captcha, traffic check or login wall;
+    /// `path` is the path the page ended up on
+    fn is_blocked(&self, path: &str, content: &str) -> bool;
+
+    /// Products of the API responses captured while the page loaded
+    fn parse_api_responses(&self, responses: &[CapturedResponse]) -> Vec<Product>;
+
+    /// Script returning the page's embedded product state as a JSON string,
+    /// if the storefront has one
+    fn state_script(&self) -> Option<String> {
+        None
+    }
+
+    /// Products of the embedded state
+    fn parse_state(&self, _state: &Value) -> Vec<Product> {
+        Vec::new()
+    }
+
+    /// Products of the rendered product cards
+    fn parse_listing_html(&self, html: &str) -> Vec<Product>;
+}
+
+/// Scraper of a [`Storefront`]
+pub struct StorefrontScraper<S> {
+    store: S,
+    browser: BrowserManager,
+    proxy_pool: Option<ProxyPool>,
+    status: Arc<Mutex<ScraperStatus>>,
+    config: ScraperConfig,
+    /// Receives the same events as TikTok Shop runs
+    app_handle: Option<tauri::AppHandle>,
+    language: Language,
+    progress: Mutex<ProgressTracker>,
+    session: Option<SessionLog>,
+    resumed: Vec<Product>,
+}
+
+impl<S: Storefront> StorefrontScraper<S> {
+    pub fn new(
+        store: S,
+        mut config: ScraperConfig,
+        status: Arc<Mutex<ScraperStatus>>,
+        app_handle: Option<tauri::AppHandle>,
+    ) -> Self {
+        if config.wait_conditions.is_empty() {
+            config.wait_conditions = store.wait_conditions();
+        }
+
+        let proxy_pool = (config.use_proxy && !config.proxies.is_empty())
+            .then(|| ProxyPool::new(config.proxies.clone()));
+
+        let mut browser = BrowserManager::new(config.headless)
+            .with_profile(config.launch_profile)
+            .with_timeout(config.timeout as u64 / 1000);
+        if let Some(path) = &config.user_data_path {
+            browser = browser.with_user_data(std::path::PathBuf::from(path));
+        }
+        if let Some(handle) = &app_handle {
+            browser = browser.with_app_handle(handle.clone());
+        }
+        if let Some(url) = &config.remote_debugging_url {
+            browser = browser.with_remote_debugging(url.clone());
+        }
+
+        let (session, resumed) = SessionLog::for_run(&config);
+        let progress = ProgressTracker::new(
+            &TikTokScraper::run_categories(&config),
+            config.max_products,
+            false,
+        );
+
+        Self {
+            store,
+            browser,
+            proxy_pool,
+            status,
+            language: Language::from_tag(&config.language),
+            config,
+            app_handle,
+            progress: Mutex::new(progress),
+            session,
+            resumed,
+        }
+    }
+
+    fn emit<T: serde::Serialize + Clone>(&self, event: &str, payload: T) {
+        if let Some(handle) = &self.app_handle {
+            let _ = handle.emit(event, payload);
+        }
+    }
+
+    /// Log a catalog message like TikTok Shop runs do
+    async fn add_log(
+        &self,
+        level: LogLevel,
+        category: Option<&str>,
+        key: &str,
+        params: &[(&str, String)],
+    ) {
+        let message = i18n::localize(self.language, key, params);
+        let entry = LogEntry {
+            level,
+            code: message.key,
+            params: message.params,
+            message: message.text,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            category: category.map(str::to_string),
+        };
+
+        {
+            let mut status = self.status.lock().await;
+            status.logs.push(entry.clone());
+            if status.logs.len() > MAX_LOG_ENTRIES {
+                let excess = status.logs.len() - MAX_LOG_ENTRIES;
+                status.logs.drain(..excess);
+            }
+        }
+
+        if let (true, Some(run_id), Some(db_path)) = (
+            self.config.persist_logs,
+            &self.config.run_id,
+            &self.config.db_path,
+        ) {
+            if let Err(e) = database::add_run_log(std::path::Path::new(db_path), run_id, &entry) {
+                log::warn!("Failed to persist scraper log: {}", e);
+            }
+        }
+
+        self.emit("scraper-log", &entry);
+        if entry.level == LogLevel::Error {
+            self.emit("scraper://error", &entry);
+        }
+    }
+
+    async fn update_progress(&self, update: impl FnOnce(&mut ProgressTracker)) {
+        let (overall, detail) = {
+            let mut progress = self.progress.lock().await;
+            update(&mut progress);
+            (progress.overall(), progress.snapshot())
+        };
+
+        {
+            let mut status = self.status.lock().await;
+            status.progress = overall;
+            status.progress_detail = detail.clone();
+        }
+
+        self.emit(
+            "scraper://progress",
+            serde_json::json!({ "progress": overall, "detail": detail }),
+        );
+    }
+
+    async fn set_status_message(&self, key: &str) {
+        let mut status = self.status.lock().await;
+        status.status_message = Some(i18n::translate(self.language, key, &[]));
+        status.status_key = Some(key.to_string());
+    }
+
+    async fn count_category(&self, category: &str, update: impl FnOnce(&mut CategorySummary)) {
+        let mut status = self.status.lock().await;
+        if let Some(summary) = status
+            .categories
+            .iter_mut()
+            .find(|c| c.category == category)
+        {
+            update(summary);
+        }
+    }
+
+    async fn is_running(&self) -> bool {
+        self.status.lock().await.is_running
+    }
+
+    pub async fn start(&self) -> Result<Vec<Product>> {
+        log::info!("Starting {:?} scraper", self.store.marketplace());
+        self.add_log(LogLevel::Info, None, self.store.start_key(), &[])
+            .await;
+        if !self.resumed.is_empty() {
+            self.add_log(
+                LogLevel::Info,
+                None,
+                "scraper.resuming",
+                &[("count", self.resumed.len().to_string())],
+            )
+            .await;
+        }
+
+        {
+            let mut status = self.status.lock().await;
+            status.is_running = true;
+            status.progress = 0.0;
+            status.started_at = Some(chrono::Utc::now().to_rfc3339());
+            status.categories = TikTokScraper::run_categories(&self.config)
+                .into_iter()
+                .map(|category| CategorySummary {
+                    category,
+                    ..Default::default()
+                })
+                .collect();
+        }
+        self.set_status_message("status.initializing").await;
+
+        let offline = self
+            .config
+            .offline_folder
+            .as_deref()
+            .is_some_and(|folder| !folder.trim().is_empty());
+        let result = if offline {
+            Err(anyhow::anyhow!(
+                "Offline pages can only be parsed for TikTok Shop"
+            ))
+        } else {
+            self.scrape_products().await
+        };
+        self.finish_session(&result).await;
+
+        let stage = if result.is_ok() {
+            ScrapeStage::Persistence
+        } else {
+            ScrapeStage::Done
+        };
+        self.update_progress(|p| p.set_stage(stage)).await;
+
+        {
+            let mut status = self.status.lock().await;
+            status.is_running = false;
+            status.current_category = None;
+            status.current_url = None;
+            status.current_product = None;
+            match &result {
+                Ok(products) => status.products_found = products.len() as i32,
+                Err(e) => status.errors.push(i18n::translate(
+                    self.language,
+                    "error.scrape_failed",
+                    &[("error", e.to_string())],
+                )),
+            }
+        }
+        self.set_status_message("status.finished").await;
+
+        if let Err(e) = &result {
+            log::error!("{:?} scrape failed: {}", self.store.marketplace(), e);
+            self.add_log(
+                LogLevel::Error,
+                None,
+                "error.scrape_failed",
+                &[("error", e.to_string())],
+            )
+            .await;
+        }
+        self.add_log(LogLevel::Info, None, "scraper.finished", &[])
+            .await;
+        result
+    }
+
+    /// Close the session: completed once every category is through or the
+    /// product limit was reached, interrupted otherwise
+    async fn finish_session(&self, result: &Result<Vec<Product>>) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let stopped = !self.is_running().await;
+        match result {
+            Err(e) => session.finish(Some(&e.to_string())),
+            Ok(products)
+                if products.len() >= self.config.max_products as usize
+                    || session.all_done(&TikTokScraper::run_categories(&self.config)) =>
+            {
+                session.finish(None)
+            }
+            Ok(_) => session.finish(Some(if stopped { "stopped" } else { "incomplete" })),
+        }
+    }
+
+    fn save_checkpoint(&self, category: &str, page_number: u32, done: bool) {
+        if let Some(session) = &self.session {
+            session.record_checkpoint(CategoryCheckpoint {
+                category: category.to_string(),
+                page_number,
+                scroll_offset: 0,
+                done,
+            });
+        }
+    }
+
+    async fn scrape_products(&self) -> Result<Vec<Product>> {
+        let proxy = match &self.proxy_pool {
+            Some(pool) => pool.get_next().await.map(|p| p.to_url()),
+            None => None,
+        };
+
+        self.set_status_message("status.starting_browser").await;
+        self.browser
+            .start(proxy)
+            .await
+            .context("Failed to start browser")?;
+        self.set_status_message("status.browser_started").await;
+        self.update_progress(|p| p.set_stage(ScrapeStage::Categories))
+            .await;
+
+        let result = self.scrape_categories().await;
+        self.browser.stop().await?;
+        result
+    }
+
+    async fn scrape_categories(&self) -> Result<Vec<Product>> {
+        let page = self.browser.new_page().await?;
+        let capture = if self.config.intercept_api {
+            match self.browser.capture_network(&page).await {
+                Ok(capture) => Some(capture),
+                Err(e) => {
+                    log::warn!("API capture unavailable, reading the page instead: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut all_products = self.resumed.clone();
+        for category in TikTokScraper::run_categories(&self.config) {
+            if !self.is_running().await {
+                self.add_log(
+                    LogLevel::Info,
+                    Some(&category),
+                    "scraper.stopped_by_user",
+                    &[],
+                )
+                .await;
+                break;
+            }
+            if all_products.len() >= self.config.max_products as usize {
+                break;
+            }
+            if self.session.as_ref().is_some_and(|s| s.is_done(&category)) {
+                continue;
+            }
+
+            let result = self
+                .scrape_category(&page, capture.as_ref(), &category, &mut all_products)
+                .await;
+            if result.is_err() {
+                self.count_category(&category, |c| c.errors += 1).await;
+            }
+            self.update_progress(|p| p.category_done(&category)).await;
+            result?;
+        }
+
+        all_products.truncate(self.config.max_products as usize);
+        log::info!(
+            "Parsed {} {:?} products total",
+            all_products.len(),
+            self.store.marketplace()
+        );
+        Ok(all_products)
+    }
+
+    /// Products of the loaded page: captured API responses first, then the
+    /// embedded state, then the product cards; with the source they came from
+    async fn parse_page<P: PageDriver>(
+        &self,
+        page: &P,
+        capture: Option<&NetworkCapture>,
+        content: &str,
+    ) -> (Vec<Product>, String) {
+        if let Some(capture) = capture {
+            let products = self.store.parse_api_responses(&capture.drain().await);
+            if !products.is_empty() {
+                return (
+                    products,
+                    format!("{}:api", self.store.marketplace().as_str()),
+                );
+            }
+        }
+
+        if let Some(script) = self.store.state_script() {
+            let state = page
+                .evaluate(&script)
+                .await
+                .ok()
+                .and_then(|result| serde_json::from_value::<String>(result).ok())
+                .and_then(|text| serde_json::from_str::<Value>(&text).ok());
+            if let Some(state) = state {
+                let products = self.store.parse_state(&state);
+                if !products.is_empty() {
+                    return (
+                        products,
+                        format!("{}:state", self.store.marketplace().as_str()),
+                    );
+                }
+            }
+        }
+
+        let source = format!("{}:dom", self.store.marketplace().as_str());
+        (self.store.parse_listing_html(content), source)
+    }
+
+    /// Read the result pages of a category until they run out, from where
+    /// the session left it
+    async fn scrape_category<P: PageDriver>(
+        &self,
+        page: &P,
+        capture: Option<&NetworkCapture>,
+        category: &str,
+        all_products: &mut Vec<Product>,
+    ) -> Result<()> {
+        let mut page_number = self
+            .session
+            .as_ref()
+            .and_then(|session| session.checkpoint(category))
+            .map_or(0, |checkpoint| checkpoint.page_number);
+        self.add_log(
+            LogLevel::Info,
+            Some(category),
+            "scraper.navigating",
+            &[("url", category.to_string())],
+        )
+        .await;
+
+        while page_number < MAX_PAGES && all_products.len() < self.config.max_products as usize {
+            if !self.is_running().await {
+                // Picked up again on resume
+                return Ok(());
+            }
+
+            let url = self.store.category_page(category, page_number);
+            let page_started = Instant::now();
+            {
+                let mut status = self.status.lock().await;
+                status.current_category = Some(category.to_string());
+                status.current_url = Some(url.clone());
+            }
+
+            // Responses of the previous page are not this page's products
+            if let Some(capture) = capture {
+                capture.drain().await;
+            }
+            page.goto(&url).await.context("Failed to open page")?;
+            let outcome = wait::wait_for_page(page, &self.config.wait_conditions).await;
+
+            let delay = {
+                let max = self.config.max_delay_ms as u64;
+                rand::thread_rng().gen_range((self.config.min_delay_ms as u64).min(max)..=max)
+            };
+            if let Some(remaining) =
+                tokio::time::Duration::from_millis(delay).checked_sub(outcome.elapsed)
+            {
+                tokio::time::sleep(remaining).await;
+            }
+
+            let content = page.content().await.unwrap_or_default();
+            let path = page
+                .evaluate("location.pathname")
+                .await
+                .ok()
+                .and_then(|path| path.as_str().map(str::to_string))
+                .unwrap_or_default();
+            if self.store.is_blocked(&path, &content) {
+                self.add_log(LogLevel::Error, Some(category), "scraper.bot_detected", &[])
+                    .await;
+                self.count_category(category, |c| c.errors += 1).await;
+                if let Some(db_path) = &self.config.db_path {
+                    let _ =
+                        database::save_error_page(std::path::Path::new(db_path), &url, &content);
+                }
+                if self.config.safety_switch_enabled {
+                    return Err(anyhow::anyhow!("Safety Switch triggered: Bot detection"));
+                }
+                return Ok(());
+            }
+
+            let (products, source) = self.parse_page(page, capture, &content).await;
+            self.status
+                .lock()
+                .await
+                .metrics
+                .record(&url, &source, products.len());
+            if products.is_empty() {
+                // Past the last page of results
+                self.add_log(
+                    LogLevel::Warn,
+                    Some(category),
+                    "scraper.no_products",
+                    &[("source", source.clone())],
+                )
+                .await;
+                break;
+            }
+
+            let found = products.len() as u32;
+            let mut added = Vec::new();
+            for mut product in products {
+                product.marketplace = self.store.marketplace();
+                if product.category.is_none() {
+                    product.category = TikTokScraper::niche(category);
+                }
+                if !all_products
+                    .iter()
+                    .any(|p| p.tiktok_id == product.tiktok_id)
+                {
+                    added.push(product.clone());
+                    all_products.push(product);
+                }
+            }
+            if let Some(session) = &self.session {
+                session.record_products(&added);
+            }
+            let new_count = added.len() as u32;
+            self.count_category(category, |c| {
+                c.found += found;
+                c.new += new_count;
+                c.duplicates += found - new_count;
+            })
+            .await;
+            for product in &added {
+                self.emit(
+                    "scraper://product-found",
+                    serde_json::json!({ "category": category, "product": product }),
+                );
+            }
+            if new_count > 0 {
+                self.add_log(
+                    LogLevel::Info,
+                    Some(category),
+                    "scraper.products_added",
+                    &[("count", new_count.to_string())],
+                )
+                .await;
+            }
+
+            {
+                let mut status = self.status.lock().await;
+                status.products_found = all_products.len() as i32;
+                status.current_product = added.last().map(|p| p.title.clone());
+            }
+            let elapsed = page_started.elapsed();
+            self.update_progress(|p| p.page_done(category, new_count, elapsed, Instant::now()))
+                .await;
+
+            page_number += 1;
+            if new_count == 0 {
+                // The same results again: a listing without further pages
+                break;
+            }
+            self.save_checkpoint(category, page_number, false);
+        }
+
+        if self.is_running().await {
+            self.save_checkpoint(category, page_number, true);
+        }
+        Ok(())
+    }
+
+    /// Mark the run complete once its results are saved
+    pub async fn finish_progress(&self) {
+        self.update_progress(|p| p.set_stage(ScrapeStage::Done))
+            .await;
+    }
+}
+
+impl<S: Storefront> Scraper for StorefrontScraper<S> {
+    fn start(&self) -> BoxFuture<'_, Result<Vec<Product>>> {
+        Box::pin(StorefrontScraper::start(self))
+    }
+
+    fn finish_progress(&self) -> BoxFuture<'_, ()> {
+        Box::pin(StorefrontScraper::finish_progress(self))
+    }
+}
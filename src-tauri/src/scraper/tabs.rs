@@ -0,0 +1,128 @@
+// Tab Pool Module
+// Shares a bounded number of browser tabs between concurrent listing and
+// detail-page work inside one browser
+
+use anyhow::Result;
+use chromiumoxide::Page;
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use super::blocking::RequestBlocker;
+use super::intercept::NetworkCapture;
+
+/// Browser tab used for scraping, with its per-page CDP listeners
+pub struct ScrapeTab {
+    pub page: Page,
+    pub capture: Option<NetworkCapture>,
+    pub blocker: Option<RequestBlocker>,
+    /// Browser launch the tab belongs to (see `BrowserManager::generation`)
+    pub generation: u64,
+}
+
+impl ScrapeTab {
+    pub fn blocked_count(&self) -> usize {
+        self.blocker.as_ref().map_or(0, |b| b.blocked_count())
+    }
+}
+
+pub struct TabPool {
+    permits: Semaphore,
+    idle: Mutex<Vec<ScrapeTab>>,
+    /// Requests blocked by tabs that were already closed
+    retired_blocked: AtomicUsize,
+}
+
+impl TabPool {
+    pub fn new(size: usize) -> Self {
+        Self {
+            permits: Semaphore::new(size.max(1)),
+            idle: Mutex::new(Vec::new()),
+            retired_blocked: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a free slot and reuse an idle tab of the current browser
+    /// `generation`, or open a new one with `open`
+    pub async fn acquire<F, Fut>(&self, generation: u64, open: F) -> Result<TabLease<'_>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ScrapeTab>>,
+    {
+        let permit = self.permits.acquire().await?;
+
+        let reused = {
+            let mut idle = self.idle.lock().unwrap();
+            // Tabs of a browser that has since been relaunched are dead
+            for stale in idle.iter().filter(|tab| tab.generation != generation) {
+                self.retire(stale);
+            }
+            idle.retain(|tab| tab.generation == generation);
+            idle.pop()
+        };
+
+        let tab = match reused {
+            Some(tab) => tab,
+            None => open().await?,
+        };
+
+        Ok(TabLease {
+            pool: self,
+            tab: Some(tab),
+            _permit: permit,
+        })
+    }
+
+    /// Requests blocked by every tab of the pool so far
+    pub fn blocked_count(&self) -> usize {
+        let idle: usize = self
+            .idle
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|tab| tab.blocked_count())
+            .sum();
+        self.retired_blocked.load(Ordering::Relaxed) + idle
+    }
+
+    fn retire(&self, tab: &ScrapeTab) {
+        self.retired_blocked
+            .fetch_add(tab.blocked_count(), Ordering::Relaxed);
+    }
+}
+
+/// A checked-out tab; goes back to the pool when dropped
+pub struct TabLease<'a> {
+    pool: &'a TabPool,
+    tab: Option<ScrapeTab>,
+    _permit: SemaphorePermit<'a>,
+}
+
+impl TabLease<'_> {
+    /// Close the tab instead of returning it (hung or crashed tabs)
+    pub fn discard(mut self) -> Option<ScrapeTab> {
+        let tab = self.tab.take();
+        if let Some(tab) = &tab {
+            self.pool.retire(tab);
+        }
+        tab
+    }
+}
+
+impl Deref for TabLease<'_> {
+    type Target = ScrapeTab;
+
+    fn deref(&self) -> &ScrapeTab {
+        self.tab.as_ref().expect("tab lease already discarded")
+    }
+}
+
+impl Drop for TabLease<'_> {
+    fn drop(&mut self) {
+        if let Some(tab) = self.tab.take() {
+            self.pool.idle.lock().unwrap().push(tab);
+        }
+    }
+}
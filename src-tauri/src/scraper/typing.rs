@@ -0,0 +1,163 @@
+// Human Typing Module
+// Types search keywords into the site's search box with human-like timing
+// and the occasional corrected typo, instead of opening the search URL
+
+use anyhow::{anyhow, Result};
+use rand::Rng;
+use std::time::Duration;
+
+use super::driver::PageDriver;
+
+/// Search boxes of the shop pages, most specific first
+const SEARCH_INPUT_SELECTORS: &[&str] = &[
+    "input[data-e2e='search-user-input']",
+    "input[type='search']",
+    "input[placeholder*='Pesquis']",
+    "input[placeholder*='Search']",
+    "input[name='q']",
+];
+
+/// Chance of hitting a neighbouring key instead of the intended one
+const TYPO_RATE: f64 = 0.04;
+
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Keystroke {
+    /// Type a character after waiting `delay`
+    Char(char, Duration),
+    Backspace(Duration),
+}
+
+/// Keystrokes to type `text`, including typos that get corrected
+pub fn plan_keystrokes<R: Rng>(text: &str, typo_rate: f64, rng: &mut R) -> Vec<Keystroke> {
+    let mut keys = Vec::new();
+
+    for c in text.chars() {
+        if let Some(wrong) = neighbour_key(c, rng).filter(|_| rng.gen_bool(typo_rate)) {
+            keys.push(Keystroke::Char(wrong, key_delay(rng)));
+            // Noticing the mistake takes a moment
+            keys.push(Keystroke::Backspace(Duration::from_millis(
+                rng.gen_range(250..=600),
+            )));
+        }
+
+        let mut delay = key_delay(rng);
+        if c == ' ' {
+            // Short pause between words
+            delay += Duration::from_millis(rng.gen_range(100..=400));
+        }
+        keys.push(Keystroke::Char(c, delay));
+    }
+
+    keys
+}
+
+fn key_delay<R: Rng>(rng: &mut R) -> Duration {
+    Duration::from_millis(rng.gen_range(70..=220))
+}
+
+/// A key next to `c` on a QWERTY keyboard
+fn neighbour_key<R: Rng>(c: char, rng: &mut R) -> Option<char> {
+    let lower = c.to_ascii_lowercase();
+    let row: Vec<char> = KEYBOARD_ROWS
+        .iter()
+        .find(|row| row.contains(lower))?
+        .chars()
+        .collect();
+    let idx = row.iter().position(|&k| k == lower)?;
+
+    let mut candidates = Vec::new();
+    if idx > 0 {
+        candidates.push(row[idx - 1]);
+    }
+    if idx + 1 < row.len() {
+        candidates.push(row[idx + 1]);
+    }
+    let wrong = candidates[rng.gen_range(0..candidates.len())];
+    Some(if c.is_uppercase() {
+        wrong.to_ascii_uppercase()
+    } else {
+        wrong
+    })
+}
+
+/// Focus the page's search box, type `keyword` and submit it
+pub async fn type_search<P: PageDriver>(page: &P, keyword: &str) -> Result<()> {
+    let script = format!(
+        r#"
+        (() => {{
+            for (const selector of {}) {{
+                const input = document.querySelector(selector);
+                if (input) {{
+                    input.scrollIntoView({{ block: 'center' }});
+                    input.focus();
+                    input.click();
+                    input.value = '';
+                    return true;
+                }}
+            }}
+            return false;
+        }})()
+        "#,
+        serde_json::to_string(SEARCH_INPUT_SELECTORS)?
+    );
+    if !page.evaluate(&script).await?.as_bool().unwrap_or(false) {
+        return Err(anyhow!("Search box not found"));
+    }
+
+    // ThreadRng is not Send; plan everything before the first await
+    let keystrokes = plan_keystrokes(keyword, TYPO_RATE, &mut rand::thread_rng());
+    for keystroke in keystrokes {
+        match keystroke {
+            Keystroke::Char(c, delay) => {
+                tokio::time::sleep(delay).await;
+                page.press_key(&c.to_string()).await?;
+            }
+            Keystroke::Backspace(delay) => {
+                tokio::time::sleep(delay).await;
+                page.press_key("Backspace").await?;
+            }
+        }
+    }
+
+    let pause = rand::thread_rng().gen_range(300..=900);
+    tokio::time::sleep(Duration::from_millis(pause)).await;
+    page.press_key("Enter").await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn typed_text(keys: &[Keystroke]) -> String {
+        let mut text = String::new();
+        for key in keys {
+            match key {
+                Keystroke::Char(c, _) => text.push(*c),
+                Keystroke::Backspace(_) => {
+                    text.pop();
+                }
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_typos_are_corrected() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let keys = plan_keystrokes("Fone Bluetooth", 1.0, &mut rng);
+
+        assert!(keys.iter().any(|k| matches!(k, Keystroke::Backspace(_))));
+        assert_eq!(typed_text(&keys), "Fone Bluetooth");
+
+        let clean = plan_keystrokes("fone", 0.0, &mut rng);
+        assert_eq!(clean.len(), 4);
+        assert!(clean.iter().all(|k| match k {
+            Keystroke::Char(_, delay) => *delay >= Duration::from_millis(70),
+            Keystroke::Backspace(_) => false,
+        }));
+    }
+}
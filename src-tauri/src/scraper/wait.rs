@@ -0,0 +1,206 @@
+// Wait Conditions Module
+// Decides when a freshly navigated page is ready to parse, instead of
+// sleeping a fixed amount of time
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+use super::driver::PageDriver;
+
+/// Interval between two checks of a condition
+const POLL_INTERVAL_MS: u64 = 250;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, export_to = "../src/types/tauri-bindings.ts")]
+pub enum WaitCondition {
+    /// No new network resources for `idle_ms`
+    NetworkIdle {
+        #[serde(default = "default_idle_ms")]
+        idle_ms: u64,
+        #[serde(default = "default_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// An element matching the CSS selector exists
+    Selector {
+        selector: String,
+        #[serde(default = "default_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// A JavaScript expression (e.g. "window.__INITIAL_STATE__") is truthy
+    JsonState {
+        expression: String,
+        #[serde(default = "default_timeout_ms")]
+        timeout_ms: u64,
+    },
+}
+
+fn default_idle_ms() -> u64 {
+    1000
+}
+
+fn default_timeout_ms() -> u64 {
+    10000
+}
+
+impl WaitCondition {
+    /// Conditions used when the config lists none
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            WaitCondition::JsonState {
+                expression: "window.__INITIAL_STATE__ || document.getElementById('SIGI_STATE')"
+                    .to_string(),
+                timeout_ms: 8000,
+            },
+            WaitCondition::NetworkIdle {
+                idle_ms: default_idle_ms(),
+                timeout_ms: default_timeout_ms(),
+            },
+        ]
+    }
+
+    pub fn timeout(&self) -> Duration {
+        let ms = match self {
+            WaitCondition::NetworkIdle { timeout_ms, .. }
+            | WaitCondition::Selector { timeout_ms, .. }
+            | WaitCondition::JsonState { timeout_ms, .. } => *timeout_ms,
+        };
+        Duration::from_millis(ms)
+    }
+
+    /// Short name for scraper logs
+    pub fn label(&self) -> String {
+        match self {
+            WaitCondition::NetworkIdle { .. } => "network_idle".to_string(),
+            WaitCondition::Selector { selector, .. } => format!("selector {}", selector),
+            WaitCondition::JsonState { expression, .. } => format!("state {}", expression),
+        }
+    }
+
+    /// Wait until the condition holds. Returns false when it timed out.
+    pub async fn wait<P: PageDriver>(&self, page: &P) -> Result<bool> {
+        let deadline = Instant::now() + self.timeout();
+
+        match self {
+            WaitCondition::NetworkIdle { idle_ms, .. } => {
+                Self::wait_network_idle(page, Duration::from_millis(*idle_ms), deadline).await
+            }
+            WaitCondition::Selector { selector, .. } => {
+                let script = format!(
+                    "document.querySelector({}) !== null",
+                    serde_json::to_string(selector)?
+                );
+                Self::poll(page, &script, deadline).await
+            }
+            WaitCondition::JsonState { expression, .. } => {
+                let script = format!(
+                    "(() => {{ try {{ return !!({}); }} catch (e) {{ return false; }} }})()",
+                    expression
+                );
+                Self::poll(page, &script, deadline).await
+            }
+        }
+    }
+
+    async fn poll<P: PageDriver>(page: &P, script: &str, deadline: Instant) -> Result<bool> {
+        loop {
+            // Evaluation fails while the document is being replaced; keep polling
+            let ready = match page.evaluate(script).await {
+                Ok(result) => result.as_bool().unwrap_or(false),
+                Err(_) => false,
+            };
+            if ready {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    async fn wait_network_idle<P: PageDriver>(
+        page: &P,
+        idle: Duration,
+        deadline: Instant,
+    ) -> Result<bool> {
+        let script = "performance.getEntriesByType('resource').length";
+        let mut last_count = -1i64;
+        let mut quiet_since = Instant::now();
+
+        loop {
+            let count = match page.evaluate(script).await {
+                Ok(result) => result.as_i64().unwrap_or(-1),
+                Err(_) => -1,
+            };
+            if count != last_count {
+                last_count = count;
+                quiet_since = Instant::now();
+            } else if quiet_since.elapsed() >= idle {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+}
+
+/// Result of waiting for a page
+#[derive(Debug, Default)]
+pub struct WaitOutcome {
+    pub elapsed: Duration,
+    /// Labels of the conditions that timed out
+    pub timed_out: Vec<String>,
+}
+
+/// Wait for each condition in turn, each bounded by its own timeout
+pub async fn wait_for_page<P: PageDriver>(page: &P, conditions: &[WaitCondition]) -> WaitOutcome {
+    let started = Instant::now();
+    let mut outcome = WaitOutcome::default();
+
+    for condition in conditions {
+        match condition.wait(page).await {
+            Ok(true) => {}
+            Ok(false) => outcome.timed_out.push(condition.label()),
+            Err(e) => {
+                log::debug!("Wait condition {} failed: {}", condition.label(), e);
+                outcome.timed_out.push(condition.label());
+            }
+        }
+    }
+
+    outcome.elapsed = started.elapsed();
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wait_condition_config() {
+        let conditions: Vec<WaitCondition> = serde_json::from_str(
+            r#"[
+                { "type": "network_idle" },
+                { "type": "selector", "selector": ".product-card", "timeout_ms": 3000 },
+                { "type": "json_state", "expression": "window.__INITIAL_STATE__" }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            conditions[0],
+            WaitCondition::NetworkIdle {
+                idle_ms: 1000,
+                timeout_ms: 10000
+            }
+        );
+        assert_eq!(conditions[1].timeout(), Duration::from_millis(3000));
+        assert_eq!(conditions[1].label(), "selector .product-card");
+        assert_eq!(conditions[2].timeout(), Duration::from_millis(10000));
+    }
+}
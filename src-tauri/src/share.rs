@@ -0,0 +1,270 @@
+// Shareable Favorites Page
+// Renders a favorites list as one standalone HTML file, styles inlined, to
+// send to someone who doesn't use the app. Each product is a card with its
+// image, prices, link to the shop and the notes kept on the favorite.
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::format::NumberFormat;
+use crate::models::{FavoriteList, FavoriteWithProduct};
+
+/// Accent of lists whose color isn't a plain hex color
+const DEFAULT_COLOR: &str = "#6366f1";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// `url` if it's a web address; anything else (javascript:, file:) is dropped
+fn web_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    (url.starts_with("https://") || url.starts_with("http://")).then_some(url)
+}
+
+/// The list color, if it's "#rgb" or "#rrggbb", so it can go in the styles as is
+fn accent(color: &str) -> &str {
+    let hex = color.trim().strip_prefix('#').unwrap_or_default();
+    if matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        color.trim()
+    } else {
+        DEFAULT_COLOR
+    }
+}
+
+fn card(item: &FavoriteWithProduct, number_format: &NumberFormat) -> String {
+    let product = &item.product;
+    let mut card = String::from("<article class=\"card\">");
+
+    match product.image_url.as_deref().and_then(web_url) {
+        Some(src) => card.push_str(&format!(
+            "<img src=\"{}\" alt=\"\" loading=\"lazy\">",
+            escape_html(src)
+        )),
+        None => card.push_str("<div class=\"no-image\"></div>"),
+    }
+
+    card.push_str("<div class=\"body\">");
+    card.push_str(&format!("<h2>{}</h2>", escape_html(&product.title)));
+
+    card.push_str("<p class=\"price\">");
+    card.push_str(&escape_html(
+        &number_format.price_in(product.price, &product.currency),
+    ));
+    if let Some(original) = product.original_price.filter(|op| *op > product.price) {
+        card.push_str(&format!(
+            " <s>{}</s>",
+            escape_html(&number_format.price_in(original, &product.currency))
+        ));
+    }
+    card.push_str("</p>");
+
+    let mut facts = Vec::new();
+    if product.sales_count > 0 {
+        facts.push(format!(
+            "{} vendidos",
+            number_format.compact(product.sales_count as f64)
+        ));
+    }
+    if let Some(rating) = product.product_rating.filter(|r| *r > 0.0) {
+        facts.push(format!("★ {}", number_format.number(rating, 1)));
+    }
+    if let Some(seller) = product.seller_name.as_deref().filter(|s| !s.is_empty()) {
+        facts.push(seller.to_string());
+    }
+    if !facts.is_empty() {
+        card.push_str(&format!(
+            "<p class=\"facts\">{}</p>",
+            escape_html(&facts.join(" · "))
+        ));
+    }
+
+    if let Some(notes) = item
+        .favorite
+        .notes
+        .as_deref()
+        .filter(|n| !n.trim().is_empty())
+    {
+        card.push_str(&format!(
+            "<p class=\"notes\">{}</p>",
+            escape_html(notes.trim()).replace('\n', "<br>")
+        ));
+    }
+
+    if let Some(href) = web_url(&product.product_url) {
+        card.push_str(&format!(
+            "<a href=\"{}\" target=\"_blank\" rel=\"noopener\">Ver produto</a>",
+            escape_html(href)
+        ));
+    }
+
+    card.push_str("</div></article>");
+    card
+}
+
+/// The page of `list` holding `favorites` in the given order, generated at `now`
+pub fn render_favorites_html(
+    list: &FavoriteList,
+    favorites: &[FavoriteWithProduct],
+    number_format: &NumberFormat,
+    now: DateTime<Utc>,
+) -> String {
+    let mut body = format!("<header><h1>{}</h1>", escape_html(&list.name));
+    if let Some(description) = list.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        body.push_str(&format!("<p>{}</p>", escape_html(description)));
+    }
+    body.push_str(&format!(
+        "<p class=\"meta\">{} produtos · {}</p></header>",
+        favorites.len(),
+        now.with_timezone(&Local).format("%d/%m/%Y")
+    ));
+
+    if favorites.is_empty() {
+        body.push_str("<p class=\"empty\">Nenhum produto nesta lista.</p>");
+    } else {
+        body.push_str("<main>");
+        for item in favorites {
+            body.push_str(&card(item, number_format));
+        }
+        body.push_str("</main>");
+    }
+
+    format!(
+        "<!DOCTYPE html><html lang=\"pt-BR\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{title}</title><style>\
+         :root {{ --accent: {accent}; }}\
+         body {{ font-family: system-ui, sans-serif; margin: 0; background: #f5f5f7; color: #222; }}\
+         header {{ padding: 32px 24px 16px; border-top: 6px solid var(--accent); background: #fff; }}\
+         h1 {{ margin: 0 0 8px; }} header p {{ margin: 4px 0; }} .meta {{ color: #666; }}\
+         main {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(240px, 1fr)); \
+         gap: 16px; padding: 24px; }}\
+         .card {{ background: #fff; border-radius: 12px; overflow: hidden; \
+         box-shadow: 0 1px 3px rgba(0, 0, 0, .12); display: flex; flex-direction: column; }}\
+         .card img, .no-image {{ width: 100%; aspect-ratio: 1; object-fit: cover; background: #e5e5ea; }}\
+         .body {{ padding: 12px 16px 16px; display: flex; flex-direction: column; gap: 6px; flex: 1; }}\
+         h2 {{ font-size: 15px; margin: 0; line-height: 1.3; }}\
+         .price {{ font-size: 18px; font-weight: 600; margin: 0; }}\
+         .price s {{ font-size: 13px; font-weight: 400; color: #888; }}\
+         .facts {{ font-size: 13px; color: #666; margin: 0; }}\
+         .notes {{ font-size: 13px; margin: 0; padding: 8px; border-left: 3px solid var(--accent); \
+         background: #fafafa; }}\
+         .card a {{ margin-top: auto; align-self: flex-start; padding: 6px 12px; border-radius: 6px; \
+         background: var(--accent); color: #fff; text-decoration: none; font-size: 13px; }}\
+         .empty {{ padding: 24px; color: #666; }}\
+         </style></head><body>{body}</body></html>",
+        title = escape_html(&list.name),
+        accent = accent(&list.color),
+        body = body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FavoriteItem;
+    use crate::scraper::Language;
+
+    fn list(color: &str) -> FavoriteList {
+        FavoriteList {
+            id: "list-1".to_string(),
+            user_id: "user-1".to_string(),
+            name: "Achados".to_string(),
+            description: None,
+            color: color.to_string(),
+            icon: "star".to_string(),
+            product_count: 1,
+            sort_order: 0,
+            pinned: false,
+            created_at: "2026-01-01T00:00:00+00:00".to_string(),
+            updated_at: "2026-01-01T00:00:00+00:00".to_string(),
+        }
+    }
+
+    fn favorite(title: &str, product_url: &str, notes: Option<&str>) -> FavoriteWithProduct {
+        let mut product = crate::demo::generate_products(1).remove(0);
+        product.title = title.to_string();
+        product.product_url = product_url.to_string();
+        product.image_url = Some("https://example.com/image.jpg".to_string());
+        FavoriteWithProduct {
+            favorite: FavoriteItem {
+                id: "favorite-1".to_string(),
+                user_id: "user-1".to_string(),
+                product_id: product.id.clone(),
+                list_id: Some("list-1".to_string()),
+                notes: notes.map(str::to_string),
+                added_at: "2026-01-01T00:00:00+00:00".to_string(),
+            },
+            product,
+        }
+    }
+
+    fn render(list: &FavoriteList, favorites: &[FavoriteWithProduct]) -> String {
+        let number_format = NumberFormat::new(Language::PtBr, "BRL");
+        render_favorites_html(list, favorites, &number_format, Utc::now())
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x" title='y'>Tom & Jerry</a>"#),
+            "&lt;a href=&quot;x&quot; title=&#39;y&#39;&gt;Tom &amp; Jerry&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_script_in_title_and_notes_is_escaped() {
+        let item = favorite(
+            "<script>alert('title')</script>",
+            "https://shop.tiktok.com/view/product/1",
+            Some("<img src=x onerror=alert(1)>"),
+        );
+        let html = render(&list("#ff0000"), &[item]);
+
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img src=x"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;title&#39;)&lt;/script&gt;"));
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    }
+
+    #[test]
+    fn test_javascript_url_is_dropped() {
+        assert_eq!(
+            web_url(" https://example.com "),
+            Some("https://example.com")
+        );
+        assert_eq!(web_url("javascript:alert(1)"), None);
+        assert_eq!(web_url("file:///etc/passwd"), None);
+
+        let mut item = favorite("Fone", "javascript:alert(document.cookie)", None);
+        item.product.image_url = Some("javascript:alert(1)".to_string());
+        let html = render(&list("#ff0000"), &[item]);
+
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("Ver produto"));
+        assert!(html.contains("no-image"));
+    }
+
+    #[test]
+    fn test_invalid_list_color_uses_default() {
+        let cases = [
+            ("#abc", "#abc"),
+            (" #A1B2C3 ", "#A1B2C3"),
+            ("red", DEFAULT_COLOR),
+            ("#12345", DEFAULT_COLOR),
+            ("#ggg", DEFAULT_COLOR),
+            ("#fff; } body { background: url(x)", DEFAULT_COLOR),
+            ("", DEFAULT_COLOR),
+        ];
+        for (color, expected) in cases {
+            assert_eq!(accent(color), expected, "accent({:?})", color);
+        }
+
+        let html = render(&list("#fff; } body { background: url(x)"), &[]);
+        assert!(html.contains(&format!("--accent: {};", DEFAULT_COLOR)));
+        assert!(!html.contains("url(x)"));
+    }
+}
@@ -0,0 +1,107 @@
+// Global Shortcuts Module
+// System-wide hotkeys that work even when the window is minimized
+
+use crate::config::ShortcutConfig;
+use crate::{JobState, ScraperState};
+use serde_json::json;
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Check that the configured shortcuts are valid accelerators, without
+/// registering them
+pub fn validate(config: &ShortcutConfig) -> Result<(), String> {
+    for accelerator in [&config.toggle_scrape, &config.quick_search] {
+        if !accelerator.is_empty() {
+            Shortcut::from_str(accelerator)
+                .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// (Re)register the configured shortcuts, replacing any previous bindings
+pub fn register(app: &AppHandle, config: &ShortcutConfig) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| e.to_string())?;
+
+    if !config.enabled {
+        return Ok(());
+    }
+
+    if !config.toggle_scrape.is_empty() {
+        shortcuts
+            .on_shortcut(config.toggle_scrape.as_str(), |app, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    toggle_scrape(app);
+                }
+            })
+            .map_err(|e| format!("Invalid shortcut '{}': {}", config.toggle_scrape, e))?;
+    }
+
+    if !config.quick_search.is_empty() {
+        shortcuts
+            .on_shortcut(config.quick_search.as_str(), |app, _, event| {
+                if event.state == ShortcutState::Pressed {
+                    open_quick_search(app);
+                }
+            })
+            .map_err(|e| format!("Invalid shortcut '{}': {}", config.quick_search, e))?;
+    }
+
+    log::info!(
+        "Global shortcuts registered (scrape: {}, search: {})",
+        config.toggle_scrape,
+        config.quick_search
+    );
+    Ok(())
+}
+
+/// Stop the running scrape, or start one with the saved scraper settings
+fn toggle_scrape(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<ScraperState>();
+
+        let was_running = {
+            let mut status = state.0.lock().await;
+            let running = status.is_running;
+            status.is_running = false;
+            running
+        };
+
+        if was_running {
+            log::info!("Scraper stopped via global shortcut");
+            let _ = app.emit("shortcut://toggle-scrape", json!({ "started": false }));
+            return;
+        }
+
+        let _ = app.emit("shortcut://toggle-scrape", json!({ "started": true }));
+
+        let app_dir = match app.path().app_data_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                log::error!("Shortcut scrape failed: {}", e);
+                return;
+            }
+        };
+        let config = crate::commands::load_settings(&app_dir).scraper;
+
+        let jobs = app.state::<JobState>();
+        if let Err(e) =
+            crate::commands::scrape_tiktok_shop(app.clone(), config, state, jobs).await
+        {
+            log::error!("Shortcut scrape failed: {}", e);
+        }
+    });
+}
+
+/// Bring the main window to front and ask the UI to open the search palette
+fn open_quick_search(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit("shortcut://quick-search", ());
+}
@@ -0,0 +1,156 @@
+// Social Posts
+// Queue of posts for the social networks, each built from a generated copy and
+// the product image and scheduled for a given time. Due posts are handed to
+// the backend, which publishes them on the connected accounts; the queue can
+// also be exported as a content calendar CSV to post by hand. Every queued
+// post counts against the plan's social_posts limit.
+
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::backend;
+use crate::commands;
+use crate::config::AppSettings;
+use crate::database;
+use crate::models::{SocialPost, SocialPostStatus};
+
+/// Metered feature the posts count against
+pub const FEATURE: &str = "social_posts";
+
+/// Networks the backend publishes to
+pub const PLATFORMS: &[&str] = &["instagram", "tiktok", "facebook"];
+
+/// Why the backend couldn't publish a post
+pub enum PublishError {
+    /// The backend couldn't be reached; the post stays scheduled
+    Unreachable(String),
+    /// The backend or the network refused the post
+    Rejected(String),
+}
+
+/// Hand `post` to the backend to publish; returns its id on the network
+pub async fn publish(post: &SocialPost, settings: &AppSettings) -> Result<String, PublishError> {
+    let request = backend::post("/api/social/posts/publish").json(post);
+    let response = backend::send_retrying(request, settings.sync.max_retries)
+        .await
+        .map_err(PublishError::Unreachable)?;
+
+    let status = response.status();
+    let data: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = data["detail"].as_str().unwrap_or("no details");
+        return Err(PublishError::Rejected(format!(
+            "Publishing failed {}: {}",
+            status, message
+        )));
+    }
+
+    Ok(data["externalId"]
+        .as_str()
+        .or_else(|| data["id"].as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Publish `post` and store the outcome. A post the backend couldn't be
+/// reached for is left as it was.
+pub async fn publish_and_save(
+    db_path: &Path,
+    settings: &AppSettings,
+    mut post: SocialPost,
+) -> Result<SocialPost, String> {
+    let now = Utc::now().to_rfc3339();
+    match publish(&post, settings).await {
+        Ok(external_id) => {
+            post.status = SocialPostStatus::Published;
+            post.external_id = Some(external_id).filter(|id| !id.is_empty());
+            post.error = None;
+            post.published_at = Some(now.clone());
+        }
+        Err(PublishError::Rejected(e)) => {
+            post.status = SocialPostStatus::Failed;
+            post.error = Some(e);
+        }
+        Err(PublishError::Unreachable(e)) => return Err(e),
+    }
+    post.updated_at = now;
+
+    database::save_social_post(db_path, &post).map_err(|e| format!("Database error: {}", e))?;
+    Ok(post)
+}
+
+/// Publish the posts whose time has come. Returns how many went out.
+pub async fn publish_due(app: &AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let due = database::get_due_social_posts(&db_path, &Utc::now().to_rfc3339())
+        .map_err(|e| format!("Database error: {}", e))?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let settings = commands::load_settings(&app_dir);
+    let mut published = 0;
+    for post in due {
+        let id = post.id.clone();
+        let post = publish_and_save(&db_path, &settings, post).await?;
+        match post.status {
+            SocialPostStatus::Published => published += 1,
+            _ => log::warn!(
+                "Social post {} failed: {}",
+                id,
+                post.error.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+    Ok(published)
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Content calendar of `posts`, one row per post with its date and time,
+/// network, product and caption
+pub fn render_calendar_csv(db_path: &Path, posts: &[SocialPost]) -> String {
+    let mut csv = String::from("date,time,platform,status,product,caption,image_url\n");
+
+    for post in posts {
+        let (date, time) = DateTime::parse_from_rfc3339(&post.scheduled_at)
+            .map(|at| {
+                (
+                    at.format("%Y-%m-%d").to_string(),
+                    at.format("%H:%M").to_string(),
+                )
+            })
+            .unwrap_or_else(|_| (post.scheduled_at.clone(), String::new()));
+        let product = post
+            .product_id
+            .as_deref()
+            .and_then(|id| database::get_product_by_id(db_path, id).ok().flatten())
+            .map(|product| product.title)
+            .unwrap_or_default();
+
+        let row = [
+            date,
+            time,
+            post.platform.clone(),
+            post.status.as_str().to_string(),
+            product,
+            post.caption.clone(),
+            post.image_url.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
@@ -0,0 +1,106 @@
+// Telemetry
+// Anonymous usage events (features used, scrape durations, error classes),
+// recorded only while SystemConfig.analytics_enabled is on. Events are kept
+// in the database, uploaded in batches under a random install id that is not
+// tied to the hardware id, and listed as-is in the telemetry viewer so users
+// can see exactly what leaves the machine. Turning analytics off drops the
+// events not sent yet.
+
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::backend;
+use crate::commands;
+use crate::database;
+
+/// Settings key of the random install id
+const INSTALL_ID_KEY: &str = "telemetry_install_id";
+
+/// Events per upload request
+const BATCH_SIZE: i32 = 100;
+
+fn enabled(app_dir: &Path) -> bool {
+    commands::load_settings(app_dir).system.analytics_enabled
+}
+
+/// Record an event when analytics are on
+pub fn track(app_dir: &Path, name: &str, properties: &[(&str, String)]) {
+    if !enabled(app_dir) {
+        return;
+    }
+
+    let properties: HashMap<String, String> = properties
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+    if let Err(e) = database::add_telemetry_event(&app_dir.join("tiktrend.db"), name, &properties) {
+        log::warn!("Failed to record telemetry event: {}", e);
+    }
+}
+
+/// Class of an error, sent instead of its message
+pub fn error_class(error: &str) -> &'static str {
+    let error = error.to_lowercase();
+    if error.contains("captcha") || error.contains("bot detection") || error.contains("blocked") {
+        "blocked"
+    } else if error.contains("timeout") || error.contains("timed out") {
+        "timeout"
+    } else if error.contains("database") || error.contains("sqlite") {
+        "database"
+    } else if error.contains("disco") || error.contains("disk") {
+        "disk_space"
+    } else if error.contains("browser") || error.contains("chrome") || error.contains("cdp") {
+        "browser"
+    } else if error.contains("connect") || error.contains("network") || error.contains("dns") {
+        "network"
+    } else if error.contains("parse") || error.contains("json") {
+        "parse"
+    } else {
+        "other"
+    }
+}
+
+fn install_id(db_path: &Path) -> Result<String, String> {
+    if let Some(id) = database::get_setting(db_path, INSTALL_ID_KEY).map_err(|e| e.to_string())? {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    database::set_setting(db_path, INSTALL_ID_KEY, &id).map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Upload the events not sent yet, in batches; returns how many were sent
+pub async fn flush(app_dir: &Path) -> Result<usize, String> {
+    if !enabled(app_dir) {
+        return Ok(0);
+    }
+
+    let db_path = app_dir.join("tiktrend.db");
+    let install_id = install_id(&db_path)?;
+    let mut sent = 0;
+
+    loop {
+        let events = database::get_telemetry_events(&db_path, true, BATCH_SIZE)
+            .map_err(|e| e.to_string())?;
+        if events.is_empty() {
+            return Ok(sent);
+        }
+
+        let response = backend::send(backend::post("/telemetry/events").json(&serde_json::json!({
+            "install_id": install_id,
+            "app_version": env!("CARGO_PKG_VERSION"),
+            "os": std::env::consts::OS,
+            "events": events,
+        })))
+        .await?;
+        if !response.status().is_success() {
+            return Err(format!("Telemetry upload failed: {}", response.status()));
+        }
+
+        let ids: Vec<i64> = events.iter().map(|e| e.id).collect();
+        database::mark_telemetry_sent(&db_path, &ids, &Utc::now().to_rfc3339())
+            .map_err(|e| e.to_string())?;
+        sent += ids.len();
+    }
+}
@@ -0,0 +1,217 @@
+// Trial Management
+// Trials are provisioned by the backend and recorded locally, bound to the
+// hardware id, so the countdown works offline and a device only gets one
+// trial even when the backend can't be reached to check. The record carries
+// a checksum over the device, the dates and the plan that catches copied or
+// hand-edited records; its salt ships in the binary, so it is not a
+// signature and can be forged.
+
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+use crate::backend;
+use crate::commands;
+use crate::database;
+use crate::models::{Subscription, TrialRecord, TrialState, TrialStatus};
+
+/// Settings key holding the trial record
+const TRIAL_KEY: &str = "trial";
+
+/// Length of a trial when the backend doesn't say
+const DEFAULT_TRIAL_DAYS: i64 = 7;
+
+const CHECKSUM_SALT: &str = "tiktrend-trial-v2";
+
+/// SHA-256 of the trial's device, dates and subscription
+fn checksum(hwid: &str, started_at: &str, ends_at: &str, subscription: &Subscription) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(CHECKSUM_SALT.as_bytes());
+    hasher.update(hwid.as_bytes());
+    hasher.update(started_at.as_bytes());
+    hasher.update(ends_at.as_bytes());
+    hasher.update(
+        serde_json::to_string(subscription)
+            .unwrap_or_default()
+            .as_bytes(),
+    );
+    format!("{:x}", hasher.finalize())
+}
+
+/// The stored trial record, if any. A record that fails its checksum or
+/// belongs to another device is returned as `Err(())`: it still counts as a
+/// used trial, but its subscription is not honoured.
+fn load(db_path: &Path, hwid: &str) -> Option<Result<TrialRecord, ()>> {
+    let json = database::get_setting(db_path, TRIAL_KEY).ok()??;
+    let Ok(record) = serde_json::from_str::<TrialRecord>(&json) else {
+        return Some(Err(()));
+    };
+
+    let intact = record.hwid == hwid
+        && record.signature
+            == checksum(
+                &record.hwid,
+                &record.started_at,
+                &record.ends_at,
+                &record.subscription,
+            );
+    Some(if intact { Ok(record) } else { Err(()) })
+}
+
+fn ends_at(record: &TrialRecord) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&record.ends_at)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+pub fn status(db_path: &Path, now: DateTime<Utc>) -> TrialStatus {
+    let hwid = commands::get_hardware_id();
+    match load(db_path, &hwid) {
+        None => TrialStatus {
+            state: TrialState::Available,
+            plan_tier: None,
+            started_at: None,
+            ends_at: None,
+            days_remaining: 0,
+        },
+        Some(Err(())) => TrialStatus {
+            state: TrialState::Expired,
+            plan_tier: None,
+            started_at: None,
+            ends_at: None,
+            days_remaining: 0,
+        },
+        Some(Ok(record)) => {
+            let remaining = ends_at(&record).map(|end| end - now).unwrap_or_default();
+            TrialStatus {
+                state: if remaining > Duration::zero() {
+                    TrialState::Active
+                } else {
+                    TrialState::Expired
+                },
+                plan_tier: Some(record.subscription.plan_tier),
+                started_at: Some(record.started_at),
+                ends_at: Some(record.ends_at),
+                // A partial day counts as a day left
+                days_remaining: (remaining.num_hours().max(0) as f64 / 24.0).ceil() as i32,
+            }
+        }
+    }
+}
+
+/// Subscription of a trial that is still running on this device
+pub fn active_subscription(db_path: &Path, now: DateTime<Utc>) -> Option<Subscription> {
+    let record = load(db_path, &commands::get_hardware_id())?.ok()?;
+    (ends_at(&record)? > now).then_some(record.subscription)
+}
+
+/// Ask the backend for a trial and record it for this device
+pub async fn start(db_path: &Path, auth_token: Option<&str>) -> Result<Subscription, String> {
+    let hwid = commands::get_hardware_id();
+    if load(db_path, &hwid).is_some() {
+        return Err("A trial was already used on this device".to_string());
+    }
+
+    let mut request = backend::post("/subscription/trial").json(&serde_json::json!({
+        "hwid": hwid,
+        "app_version": env!("CARGO_PKG_VERSION"),
+    }));
+    if let Some(token) = auth_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = backend::send(request)
+        .await
+        .map_err(|e| format!("Failed to reach the server: {}", e))?;
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        return Err("A trial was already used on this account or device".to_string());
+    }
+    if !response.status().is_success() {
+        return Err(format!("Trial request failed: {}", response.status()));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+    let subscription = commands::parse_subscription_from_api(&body)?;
+
+    let now = Utc::now();
+    let ends_at = DateTime::parse_from_rfc3339(&subscription.current_period_end)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| now + Duration::days(DEFAULT_TRIAL_DAYS));
+    let (started_at, ends_at) = (now.to_rfc3339(), ends_at.to_rfc3339());
+    let record = TrialRecord {
+        signature: checksum(&hwid, &started_at, &ends_at, &subscription),
+        hwid,
+        started_at,
+        ends_at,
+        subscription: subscription.clone(),
+    };
+
+    let json = serde_json::to_string(&record).map_err(|e| e.to_string())?;
+    database::set_setting(db_path, TRIAL_KEY, &json)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(subscription)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::tests::TempDb;
+    use crate::models::PlanTier;
+
+    const HWID: &str = "device-1";
+
+    fn store(db_path: &Path, record: &TrialRecord) {
+        let json = serde_json::to_string(record).unwrap();
+        database::set_setting(db_path, TRIAL_KEY, &json).unwrap();
+    }
+
+    fn record() -> TrialRecord {
+        let subscription = commands::create_free_subscription();
+        let (started_at, ends_at) = (
+            "2026-01-01T00:00:00+00:00".to_string(),
+            "2026-01-08T00:00:00+00:00".to_string(),
+        );
+        TrialRecord {
+            signature: checksum(HWID, &started_at, &ends_at, &subscription),
+            hwid: HWID.to_string(),
+            started_at,
+            ends_at,
+            subscription,
+        }
+    }
+
+    #[test]
+    fn test_intact_record_loads() {
+        let db = TempDb::new();
+        assert!(load(db.path(), HWID).is_none());
+
+        store(db.path(), &record());
+        assert!(matches!(load(db.path(), HWID), Some(Ok(_))));
+        // Copied to another device
+        assert!(matches!(load(db.path(), "device-2"), Some(Err(()))));
+    }
+
+    #[test]
+    fn test_tampered_subscription_is_rejected() {
+        let db = TempDb::new();
+        let mut tampered = record();
+        tampered.subscription.plan_tier = PlanTier::Enterprise;
+        tampered.subscription.limits.api_calls = -1;
+        store(db.path(), &tampered);
+
+        assert!(matches!(load(db.path(), HWID), Some(Err(()))));
+    }
+
+    #[test]
+    fn test_tampered_end_date_is_rejected() {
+        let db = TempDb::new();
+        let mut tampered = record();
+        tampered.ends_at = "2099-01-01T00:00:00+00:00".to_string();
+        store(db.path(), &tampered);
+
+        assert!(matches!(load(db.path(), HWID), Some(Err(()))));
+    }
+}
@@ -0,0 +1,336 @@
+// Usage Tracking
+// Metered feature usage per billing period. Periods follow the cached
+// subscription's current_period_* fields and roll over on their own when the
+// period ends before the next sync; usage of a new period starts from zero.
+// Usage recorded offline is sent to the backend on the next sync, and
+// crossing 80% or 95% of a limit raises a `quota://warning`.
+
+use chrono::{DateTime, Datelike, Months, TimeZone, Utc};
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+
+use crate::backend;
+use crate::commands;
+use crate::database;
+use crate::models::{FeatureUsage, QuotaWarning, Subscription};
+
+/// Features with a per-period limit
+pub const METERED_FEATURES: &[&str] = &[
+    "price_searches",
+    "favorites",
+    "whatsapp_messages",
+    "api_calls",
+    "crm_leads",
+    "chatbot_flows",
+    "social_posts",
+];
+
+/// Shares of a limit, in percent, that raise a warning when crossed; highest
+/// first so a jump over both reports the higher one
+pub const WARNING_THRESHOLDS: &[u32] = &[95, 80];
+
+fn cycle_months(subscription: &Subscription) -> u32 {
+    match subscription.billing_cycle.as_str() {
+        "yearly" | "annual" => 12,
+        "quarterly" => 3,
+        _ => 1,
+    }
+}
+
+/// First day of the calendar month of `now`
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// The billing period `now` falls in. The cached period is carried forward
+/// by whole billing cycles when it has already ended; subscriptions without
+/// a billing cycle (the free plan) use calendar months. Boundaries are counted
+/// from the cached period's start so an anchor on the 31st stays on the last
+/// day of short months instead of drifting to the 28th.
+pub fn billing_period(
+    subscription: &Subscription,
+    now: DateTime<Utc>,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let months = cycle_months(subscription);
+    let cached = DateTime::parse_from_rfc3339(&subscription.current_period_start)
+        .ok()
+        .zip(DateTime::parse_from_rfc3339(&subscription.current_period_end).ok())
+        .map(|(start, end)| (start.with_timezone(&Utc), end.with_timezone(&Utc)))
+        .filter(|(start, end)| start < end);
+
+    let (mut start, mut end) = match cached {
+        Some(period) if subscription.billing_cycle != "none" => period,
+        _ => {
+            let start = month_start(now);
+            (start, start + Months::new(1))
+        }
+    };
+
+    let anchor = start;
+    let mut cycles = 0;
+    while end <= now {
+        cycles += 1;
+        let Some(next) = anchor.checked_add_months(Months::new(months * cycles)) else {
+            break;
+        };
+        if next > end {
+            start = end;
+            end = next;
+        }
+    }
+    (start, end)
+}
+
+/// Usage of a feature in the current period
+pub fn usage(
+    db_path: &Path,
+    subscription: &Subscription,
+    feature: &str,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<FeatureUsage> {
+    let (start, end) = billing_period(subscription, now);
+    let period_start = start.to_rfc3339();
+    let (used, _) = database::get_feature_usage(db_path, feature, &period_start)?;
+
+    Ok(FeatureUsage {
+        feature: feature.to_string(),
+        used,
+        limit: commands::get_feature_limit(subscription, feature).unwrap_or(0),
+        period_start,
+        period_end: end.to_rfc3339(),
+    })
+}
+
+/// Usage of a feature in the current period, or why it can't be used
+/// again: the plan doesn't include it, or its limit is used up
+/// ("QUOTA_EXCEEDED"). A limit of -1 is unlimited.
+pub fn ensure_available(
+    db_path: &Path,
+    subscription: &Subscription,
+    feature: &str,
+    now: DateTime<Utc>,
+) -> Result<FeatureUsage, String> {
+    let current =
+        usage(db_path, subscription, feature, now).map_err(|e| format!("Database error: {}", e))?;
+    if current.limit == 0 {
+        return Err(format!("The plan doesn't include {}", feature));
+    }
+    if current.limit > 0 && current.used >= current.limit {
+        return Err("QUOTA_EXCEEDED".to_string());
+    }
+    Ok(current)
+}
+
+/// Add `amount` to the usage of a feature in the current period
+pub fn record(
+    db_path: &Path,
+    subscription: &Subscription,
+    feature: &str,
+    amount: i32,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<FeatureUsage> {
+    let (start, end) = billing_period(subscription, now);
+    let limit = commands::get_feature_limit(subscription, feature).unwrap_or(0);
+    let (period_start, period_end) = (start.to_rfc3339(), end.to_rfc3339());
+    let used = database::update_usage_tracking(
+        db_path,
+        feature,
+        amount,
+        limit,
+        &period_start,
+        &period_end,
+    )?;
+
+    Ok(FeatureUsage {
+        feature: feature.to_string(),
+        used,
+        limit,
+        period_start,
+        period_end,
+    })
+}
+
+/// The highest warning threshold crossed by the last `amount` uses, if any
+pub fn crossed_threshold(usage: &FeatureUsage, amount: i32) -> Option<QuotaWarning> {
+    if usage.limit <= 0 || amount <= 0 {
+        return None;
+    }
+    let percent = |used: i32| used as f64 * 100.0 / usage.limit as f64;
+    let (before, after) = (percent(usage.used - amount), percent(usage.used));
+
+    let threshold = WARNING_THRESHOLDS
+        .iter()
+        .copied()
+        .find(|&t| before < t as f64 && after >= t as f64)?;
+    Some(QuotaWarning {
+        feature: usage.feature.clone(),
+        used: usage.used,
+        limit: usage.limit,
+        threshold,
+        message: format!(
+            "Você já usou {}% do limite de {} ({} de {}) neste período",
+            threshold,
+            feature_label(&usage.feature),
+            usage.used,
+            usage.limit
+        ),
+        period_end: usage.period_end.clone(),
+    })
+}
+
+fn feature_label(feature: &str) -> &str {
+    match feature {
+        "price_searches" => "buscas de preço",
+        "favorites" => "favoritos",
+        "whatsapp_messages" => "mensagens de WhatsApp",
+        "api_calls" => "chamadas de API",
+        "crm_leads" => "leads do CRM",
+        "chatbot_flows" => "fluxos de chatbot",
+        "social_posts" => "posts sociais",
+        other => other,
+    }
+}
+
+/// Record usage and emit `quota://warning` when it crosses a threshold
+pub fn record_and_warn(
+    app: &AppHandle,
+    db_path: &Path,
+    subscription: &Subscription,
+    feature: &str,
+    amount: i32,
+) -> rusqlite::Result<FeatureUsage> {
+    let usage = record(db_path, subscription, feature, amount, Utc::now())?;
+    if let Some(warning) = crossed_threshold(&usage, amount) {
+        log::info!("Quota warning: {}", warning.message);
+        let _ = app.emit("quota://warning", &warning);
+    }
+    Ok(usage)
+}
+
+/// Start the current period of every metered feature at zero with the plan's
+/// limits, so a rollover shows up even before anything is used
+pub fn rollover(
+    db_path: &Path,
+    subscription: &Subscription,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<()> {
+    for feature in METERED_FEATURES {
+        record(db_path, subscription, feature, 0, now)?;
+    }
+    Ok(())
+}
+
+/// Send usage that changed since the last sync to the backend; returns the
+/// number of rows sent
+pub async fn sync(db_path: &Path, auth_token: Option<&str>) -> Result<usize, String> {
+    let pending = database::get_unsynced_usage(db_path).map_err(|e| e.to_string())?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let mut request =
+        backend::post("/subscription/usage").json(&serde_json::json!({ "usage": pending }));
+    if let Some(token) = auth_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let response = backend::send(request).await?;
+    if !response.status().is_success() {
+        return Err(format!("Usage sync failed: {}", response.status()));
+    }
+
+    for usage in &pending {
+        database::mark_usage_synced(db_path, usage).map_err(|e| e.to_string())?;
+    }
+    Ok(pending.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn subscription(billing_cycle: &str, start: &str, end: &str) -> Subscription {
+        let mut subscription = commands::create_free_subscription();
+        subscription.billing_cycle = billing_cycle.to_string();
+        subscription.current_period_start = start.to_string();
+        subscription.current_period_end = end.to_string();
+        subscription
+    }
+
+    #[test]
+    fn test_cached_period_still_current() {
+        let sub = subscription("monthly", "2026-03-10T12:00:00Z", "2026-04-10T12:00:00Z");
+        let period = billing_period(&sub, at("2026-03-25T00:00:00Z"));
+        assert_eq!(
+            period,
+            (at("2026-03-10T12:00:00Z"), at("2026-04-10T12:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_period_rolls_over_several_cycles() {
+        let sub = subscription("monthly", "2026-01-10T00:00:00Z", "2026-02-10T00:00:00Z");
+        let period = billing_period(&sub, at("2026-05-20T00:00:00Z"));
+        assert_eq!(
+            period,
+            (at("2026-05-10T00:00:00Z"), at("2026-06-10T00:00:00Z"))
+        );
+
+        let sub = subscription("quarterly", "2026-01-10T00:00:00Z", "2026-04-10T00:00:00Z");
+        let period = billing_period(&sub, at("2026-08-01T00:00:00Z"));
+        assert_eq!(
+            period,
+            (at("2026-07-10T00:00:00Z"), at("2026-10-10T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_period_anchored_on_the_31st_does_not_drift() {
+        let sub = subscription("monthly", "2026-01-31T00:00:00Z", "2026-02-28T00:00:00Z");
+        let cases = [
+            (
+                "2026-03-05T00:00:00Z",
+                "2026-02-28T00:00:00Z",
+                "2026-03-31T00:00:00Z",
+            ),
+            (
+                "2026-04-15T00:00:00Z",
+                "2026-03-31T00:00:00Z",
+                "2026-04-30T00:00:00Z",
+            ),
+            (
+                "2026-05-31T00:00:00Z",
+                "2026-05-31T00:00:00Z",
+                "2026-06-30T00:00:00Z",
+            ),
+        ];
+        for (now, start, end) in cases {
+            assert_eq!(
+                billing_period(&sub, at(now)),
+                (at(start), at(end)),
+                "billing_period at {}",
+                now
+            );
+        }
+    }
+
+    #[test]
+    fn test_free_plan_uses_calendar_months() {
+        let now = at("2026-02-14T08:30:00Z");
+        let expected = (at("2026-02-01T00:00:00Z"), at("2026-03-01T00:00:00Z"));
+
+        let free = commands::create_free_subscription();
+        assert_eq!(billing_period(&free, now), expected);
+
+        let unparseable = subscription("monthly", "soon", "later");
+        assert_eq!(billing_period(&unparseable, now), expected);
+    }
+}
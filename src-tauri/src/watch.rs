@@ -0,0 +1,159 @@
+// Product Watcher
+// Re-fetches watched products on their own schedule, independent of category
+// scrapes. Every check is kept in the product history and changes are fed to
+// the alert engine. Only TikTok Shop products can be re-fetched; watched
+// products of other marketplaces are skipped.
+
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::alerts;
+use crate::commands;
+use crate::competitors;
+use crate::database;
+use crate::digest;
+use crate::jobs::JobKind;
+use crate::models::{ScraperStatus, WatchedProduct};
+use crate::scraper::{self, TikTokScraper};
+use crate::social;
+use crate::JobState;
+
+pub const DEFAULT_WATCH_INTERVAL_MINUTES: u32 = 60;
+
+/// Shorter intervals hit the same page often enough to get the profile flagged
+pub const MIN_WATCH_INTERVAL_MINUTES: u32 = 15;
+
+/// How often the watch list is checked for due products
+const TICK: Duration = Duration::from_secs(60);
+
+/// Check the watch list, competitor stores, the weekly digest schedule and the
+/// social post queue in the background for the lifetime of the app
+pub fn spawn_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            match refresh_due(&app).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Watched products refreshed: {}", count),
+                Err(e) => log::warn!("Watched product refresh failed: {}", e),
+            }
+            if let Err(e) = competitors::refresh_due(&app).await {
+                log::warn!("Competitor refresh failed: {}", e);
+            }
+            if let Err(e) = digest::run_if_due(&app).await {
+                log::warn!("Weekly digest failed: {}", e);
+            }
+            match social::publish_due(&app).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("Scheduled social posts published: {}", count),
+                Err(e) => log::warn!("Scheduled social posts postponed: {}", e),
+            }
+        }
+    });
+}
+
+/// Push the next check of `watched` one interval past `checked_at`
+fn reschedule(db_path: &Path, watched: &WatchedProduct, checked_at: DateTime<Utc>) {
+    let next = checked_at + chrono::Duration::minutes(watched.interval_minutes as i64);
+    database::mark_watch_checked(
+        db_path,
+        &watched.product_id,
+        &checked_at.to_rfc3339(),
+        &next.to_rfc3339(),
+    )
+    .ok();
+}
+
+/// Re-fetch the watched products whose check is due, as one browser job.
+/// Returns how many were refreshed.
+pub async fn refresh_due(app: &AppHandle) -> Result<usize, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+
+    let due = database::get_due_watched_products(&db_path, &chrono::Utc::now().to_rfc3339())
+        .map_err(|e| format!("Database error: {}", e))?;
+    if due.is_empty() {
+        return Ok(0);
+    }
+
+    let mut products = Vec::new();
+    for watched in &due {
+        match database::get_product_by_id(&db_path, &watched.product_id) {
+            Ok(Some(product)) if scraper::can_refresh(&product.marketplace) => {
+                products.push(product)
+            }
+            // Only TikTok Shop pages can be re-read; other marketplaces wait
+            // for their next interval as they are
+            Ok(Some(product)) => log::debug!(
+                "Skipping watched {} product {}",
+                product.marketplace.as_str(),
+                product.id
+            ),
+            // The product was deleted; nothing left to watch
+            Ok(None) => {
+                database::unwatch_product(&db_path, &watched.product_id).ok();
+            }
+            Err(e) => log::warn!(
+                "Failed to load watched product {}: {}",
+                watched.product_id,
+                e
+            ),
+        }
+    }
+    if products.is_empty() {
+        for watched in &due {
+            reschedule(&db_path, watched, chrono::Utc::now());
+        }
+        return Ok(0);
+    }
+
+    let jobs = app.state::<JobState>();
+    let job_id = jobs
+        .0
+        .enqueue(
+            JobKind::Watch,
+            format!("Monitoramento ({} produtos)", products.len()),
+        )
+        .await;
+    jobs.0.wait_for_turn(&job_id).await?;
+
+    let config =
+        commands::runtime_scraper_config(&app_dir, commands::load_settings(&app_dir).scraper);
+    let scraper = TikTokScraper::new(config, Arc::new(Mutex::new(ScraperStatus::default())), None);
+    let result = jobs
+        .0
+        .until_cancelled(&job_id, scraper.refresh_products(products.clone()))
+        .await
+        .and_then(|refreshed| refreshed.map_err(|e| e.to_string()));
+
+    if let Ok(refreshed) = &result {
+        for product in refreshed {
+            if let Some(old) = products.iter().find(|p| p.id == product.id) {
+                let changes =
+                    alerts::detect_changes(old, product, alerts::DEFAULT_PRICE_CHANGE_PERCENT);
+                alerts::raise(Some(app), &db_path, &changes);
+            }
+            // Appends to the product history when the check found changes
+            if let Err(e) = database::save_product(&db_path, product) {
+                log::warn!("Failed to save watched product {}: {}", product.id, e);
+            }
+        }
+    }
+
+    // Failed checks wait for the next interval too, so a broken page is not hammered
+    let checked_at = chrono::Utc::now();
+    for watched in &due {
+        reschedule(&db_path, watched, checked_at);
+    }
+
+    jobs.0
+        .finish(&job_id, result.as_ref().map(|_| ()).map_err(|e| e.clone()))
+        .await;
+
+    result.map(|refreshed| refreshed.len())
+}
@@ -0,0 +1,151 @@
+// WhatsApp Notifications
+// Sends product alerts and the weekly digest to a WhatsApp number through the
+// WhatsApp Cloud API, from the Business number set up in settings. Every
+// message counts against the plan's whatsapp_messages limit for the billing
+// period, tracked in usage_tracking like the other metered features; none are
+// sent once the limit is reached.
+
+use chrono::Utc;
+use reqwest::Client;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::commands;
+use crate::config::WhatsappConfig;
+use crate::digest;
+use crate::models::{FeatureUsage, ProductAlert, WeeklyDigest};
+use crate::usage;
+
+const GRAPH_API_URL: &str = "https://graph.facebook.com/v19.0";
+
+/// Metered feature the messages count against
+const FEATURE: &str = "whatsapp_messages";
+
+/// Longest text body the Cloud API accepts
+const MAX_TEXT_LEN: usize = 4096;
+
+/// Alerts listed in one message; the rest are only counted
+const MAX_ALERT_LINES: usize = 10;
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .unwrap_or_default()
+});
+
+fn configured(config: &WhatsappConfig) -> bool {
+    config.enabled
+        && !config.access_token.trim().is_empty()
+        && !config.phone_number_id.trim().is_empty()
+        && !config.recipient.trim().is_empty()
+}
+
+/// Send `text` to the configured recipient; returns the id of the message
+pub async fn send_text(config: &WhatsappConfig, text: &str) -> Result<String, String> {
+    let body: String = text.chars().take(MAX_TEXT_LEN).collect();
+    // The API takes the number as digits only
+    let recipient: String = config
+        .recipient
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+
+    let response = CLIENT
+        .post(format!(
+            "{}/{}/messages",
+            GRAPH_API_URL,
+            config.phone_number_id.trim()
+        ))
+        .bearer_auth(config.access_token.trim())
+        .json(&serde_json::json!({
+            "messaging_product": "whatsapp",
+            "to": recipient,
+            "type": "text",
+            "text": { "preview_url": false, "body": body },
+        }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = response.status();
+    let data: serde_json::Value = response.json().await.unwrap_or_default();
+    if !status.is_success() {
+        let message = data["error"]["message"].as_str().unwrap_or("no details");
+        return Err(format!("WhatsApp API error {}: {}", status, message));
+    }
+
+    Ok(data["messages"][0]["id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Send `text` if the plan has messages left this period, and count it
+pub async fn send(app: &AppHandle, text: &str) -> Result<FeatureUsage, String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let db_path = app_dir.join("tiktrend.db");
+    let config = commands::load_settings(&app_dir).whatsapp;
+    if !configured(&config) {
+        return Err("WhatsApp is not set up".to_string());
+    }
+
+    let subscription = commands::entitlements(app, &app_dir).subscription;
+    usage::ensure_available(&db_path, &subscription, FEATURE, Utc::now())?;
+
+    let id = send_text(&config, text).await?;
+    log::info!("WhatsApp message sent: {}", id);
+
+    usage::record_and_warn(app, &db_path, &subscription, FEATURE, 1)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+fn alerts_message(alerts: &[ProductAlert]) -> String {
+    let mut out = if alerts.len() == 1 {
+        "*Alerta de produto*\n".to_string()
+    } else {
+        format!("*{} alertas de produtos*\n", alerts.len())
+    };
+    for alert in alerts.iter().take(MAX_ALERT_LINES) {
+        out.push_str(&format!("• {}\n", alert.message));
+    }
+    if alerts.len() > MAX_ALERT_LINES {
+        out.push_str(&format!("… e mais {}\n", alerts.len() - MAX_ALERT_LINES));
+    }
+    out
+}
+
+/// Send newly raised alerts as one message in the background, when alerts
+/// go to WhatsApp
+pub fn notify_alerts(app: &AppHandle, alerts: &[ProductAlert]) {
+    if alerts.is_empty() {
+        return;
+    }
+    let Ok(app_dir) = app.path().app_data_dir() else {
+        return;
+    };
+    let config = commands::load_settings(&app_dir).whatsapp;
+    if !configured(&config) || !config.send_alerts {
+        return;
+    }
+
+    let app = app.clone();
+    let text = alerts_message(alerts);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = send(&app, &text).await {
+            log::warn!("Alerts not sent on WhatsApp: {}", e);
+        }
+    });
+}
+
+/// Send the weekly digest, when the digest goes to WhatsApp
+pub async fn notify_digest(app: &AppHandle, weekly: &WeeklyDigest) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let config = commands::load_settings(&app_dir).whatsapp;
+    if !configured(&config) || !config.send_digest {
+        return Ok(());
+    }
+
+    send(app, &digest::render_message(weekly)).await.map(|_| ())
+}
@@ -78,6 +78,8 @@ export interface FavoriteList {
   color: string;
   icon: string;
   productCount: number;
+  sortOrder?: number;
+  pinned?: boolean;
   createdAt: string;
   updatedAt: string;
 }
@@ -316,7 +318,16 @@ export interface ScraperConfig {
   categories: string[];
   useProxy: boolean;
   headless: boolean;
-  timeout: number;
+  timeout: number; // Page load timeout (ms)
+  proxies?: string[];
+  minDelayMs?: number; // Random pause between pages
+  maxDelayMs?: number;
+  maxRetries?: number;
+  maxConcurrentBrowsers?: number;
+  safetySwitchEnabled?: boolean; // Stop the run on bot detection
+  maxDetectionRate?: number;
+  safetyCooldownSeconds?: number;
+  consecutiveFailuresThreshold?: number;
 }
 
 export interface LicenseConfig {